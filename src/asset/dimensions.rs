@@ -0,0 +1,43 @@
+//! Intrinsic size of image assets.
+//!
+//! The frontend reserves space for images from their `width` and `height`
+//! before they load. Both are read from the image header only: on upload
+//! from the stored bytes, and for older assets from the first
+//! [`HEADER_BYTES`] of the stored object. Other files keep null dimensions.
+
+use super::models::Asset;
+
+/// Bytes of a stored object read to find its size. Covers the headers of
+/// common formats; a JPEG with a larger EXIF block is read in full.
+pub const HEADER_BYTES: usize = 64 * 1024;
+
+/// Whether `filename` has an image type
+pub fn is_image(filename: &str) -> bool {
+    mime_guess::from_path(filename)
+        .first()
+        .is_some_and(|mime| mime.type_() == mime_guess::mime::IMAGE)
+}
+
+/// Width and height of the image `data` stored as `filename`. None when
+/// `filename` is not an image or the header cannot be read.
+pub fn image_dimensions(filename: &str, data: &[u8]) -> Option<(i32, i32)> {
+    if !is_image(filename) {
+        return None;
+    }
+    let size = imagesize::blob_size(data).ok()?;
+    Some((
+        i32::try_from(size.width).ok()?,
+        i32::try_from(size.height).ok()?,
+    ))
+}
+
+impl Asset {
+    /// Record the size of `data`, the stored file of this asset, when it is
+    /// an image
+    pub fn read_dimensions(&mut self, data: &[u8]) {
+        if let Some((width, height)) = image_dimensions(&self.filename, data) {
+            self.width = Some(width);
+            self.height = Some(height);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! `Content-Disposition` of files served from `/assets/serve`.
+//!
+//! Stored filenames carry a uuid prefix (`<uuid>_<name>_<ext>.<ext>`), so
+//! downloads are saved under the asset's display name instead. Names outside
+//! ASCII are sent RFC 5987 encoded in `filename*`, with an ASCII fallback in
+//! `filename` for older clients.
+
+use actix_web::http::header::{
+    Charset, ContentDisposition, DispositionParam, DispositionType, ExtendedValue,
+};
+use serde::Deserialize;
+use std::path::Path;
+
+use super::models::Asset;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ServeAssetQuery {
+    /// `true` saves the file, `false` shows it in the browser. Without it
+    /// images are shown and everything else is saved.
+    pub download: Option<bool>,
+    /// Name to save the file as, defaults to the asset name
+    pub filename: Option<String>,
+}
+
+impl ServeAssetQuery {
+    /// Whether `asset` is shown in the browser rather than saved
+    pub fn inline(&self, asset: &Asset) -> bool {
+        match self.download {
+            Some(download) => !download,
+            None => is_inline_by_default(&asset.filename),
+        }
+    }
+}
+
+/// Images are shown in the browser unless a download is asked for
+pub fn is_inline_by_default(filename: &str) -> bool {
+    mime_guess::from_path(filename)
+        .first()
+        .is_some_and(|mime| mime.type_() == mime_guess::mime::IMAGE)
+}
+
+/// Name a download of `asset` is saved as: `requested`, or the asset name,
+/// with the extension of the stored file added when it is missing
+pub fn download_filename(asset: &Asset, requested: Option<&str>) -> String {
+    let name = requested
+        .map(sanitize_filename::sanitize)
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| sanitize_filename::sanitize(asset.name.trim()));
+    let name = if name.is_empty() {
+        "download".to_string()
+    } else {
+        name
+    };
+
+    match Path::new(&asset.filename)
+        .extension()
+        .and_then(std::ffi::OsStr::to_str)
+    {
+        Some(ext)
+            if !name
+                .to_lowercase()
+                .ends_with(&format!(".{}", ext.to_lowercase())) =>
+        {
+            format!("{}.{}", name, ext)
+        }
+        _ => name,
+    }
+}
+
+/// `Content-Disposition` showing the file in the browser or saving it as
+/// `filename`
+pub fn content_disposition(inline: bool, filename: &str) -> ContentDisposition {
+    let fallback: String = filename
+        .chars()
+        .map(|c| {
+            if c.is_ascii() && !c.is_ascii_control() {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let mut parameters = vec![DispositionParam::Filename(fallback)];
+    if !filename.is_ascii() {
+        parameters.push(DispositionParam::FilenameExt(ExtendedValue {
+            charset: Charset::Ext("UTF-8".to_string()),
+            language_tag: None,
+            value: filename.as_bytes().to_vec(),
+        }));
+    }
+
+    ContentDisposition {
+        disposition: if inline {
+            DispositionType::Inline
+        } else {
+            DispositionType::Attachment
+        },
+        parameters,
+    }
+}
@@ -0,0 +1,66 @@
+//! Orientation fix and EXIF removal for JPEG uploads.
+//!
+//! Phones store portrait photos as landscape pixels plus an EXIF
+//! orientation, which not every browser or CDN applies, and their EXIF
+//! often carries the GPS position of the photo. JPEGs with EXIF are
+//! decoded, turned upright and re-encoded without any metadata. Other
+//! files, and JPEGs that fail to decode, are stored as uploaded.
+
+use std::io::Cursor;
+
+use exif::{In, Tag};
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, ImageFormat};
+
+/// Quality of re-encoded photos
+const JPEG_QUALITY: u8 = 90;
+
+/// Whether `data` starts with a JPEG start-of-image marker
+pub fn is_jpeg(data: &[u8]) -> bool {
+    data.starts_with(&[0xFF, 0xD8, 0xFF])
+}
+
+/// `data` turned upright and re-encoded without metadata, when it is a JPEG
+/// carrying EXIF. None leaves the file as it is.
+pub fn strip_exif(data: &[u8]) -> Option<Vec<u8>> {
+    if !is_jpeg(data) {
+        return None;
+    }
+    let exif = exif::Reader::new()
+        .read_from_container(&mut Cursor::new(data))
+        .ok()?;
+    let orientation = exif
+        .get_field(Tag::Orientation, In::PRIMARY)
+        .and_then(|field| field.value.get_uint(0))
+        .unwrap_or(1);
+
+    let image = match image::load_from_memory_with_format(data, ImageFormat::Jpeg) {
+        Ok(image) => image,
+        Err(e) => {
+            log::warn!("Keeping EXIF of a JPEG that failed to decode: {}", e);
+            return None;
+        }
+    };
+    let image = apply_orientation(image, orientation);
+
+    let mut out = Vec::new();
+    if let Err(e) = JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY).encode_image(&image) {
+        log::warn!("Keeping EXIF of a JPEG that failed to re-encode: {}", e);
+        return None;
+    }
+    Some(out)
+}
+
+/// Pixels of `image` as shown for the EXIF `orientation` (1-8)
+pub fn apply_orientation(image: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
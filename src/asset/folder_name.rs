@@ -0,0 +1,89 @@
+//! Validation of user-chosen asset folder names.
+//!
+//! Folder names end up in storage keys and in the `/assets/folders/{name}`
+//! route, so only one path segment of letters, digits and a little
+//! punctuation is accepted. Names are trimmed, NFC-normalized and
+//! lowercased, making "Berita " and "berita" the same folder.
+
+use unicode_normalization::UnicodeNormalization;
+use uuid::Uuid;
+
+/// The virtual group of assets not linked to any folder. It has no
+/// `folders` row; uploads without a folder simply stay unlinked.
+pub const OTHERS_FOLDER: &str = "others";
+
+/// Longest accepted folder name, in characters
+pub const MAX_FOLDER_NAME_CHARS: usize = 100;
+
+/// Punctuation allowed besides letters and digits
+const ALLOWED_PUNCTUATION: &[char] = &[' ', '-', '_', '.', '(', ')', '&', ','];
+
+/// Why a folder name was rejected
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FolderNameError {
+    #[error("Folder name cannot be empty")]
+    Empty,
+    #[error("Folder name cannot be longer than {0} characters")]
+    TooLong(usize),
+    #[error("Folder name cannot contain '/' or '\\'")]
+    Separator,
+    #[error("Folder name cannot contain '..' or be '.'")]
+    DotSegment,
+    #[error("Folder name cannot contain control characters")]
+    ControlCharacter,
+    #[error("Folder name cannot contain '{0}'")]
+    UnsupportedCharacter(char),
+}
+
+/// The canonical form of `name`, or the rule it breaks
+pub fn normalize_folder_name(name: &str) -> Result<String, FolderNameError> {
+    let name: String = name.trim().nfc().collect::<String>().to_lowercase();
+    if name.is_empty() {
+        return Err(FolderNameError::Empty);
+    }
+    if name.chars().count() > MAX_FOLDER_NAME_CHARS {
+        return Err(FolderNameError::TooLong(MAX_FOLDER_NAME_CHARS));
+    }
+    if name.contains(['/', '\\']) {
+        return Err(FolderNameError::Separator);
+    }
+    if name == "." || name.contains("..") {
+        return Err(FolderNameError::DotSegment);
+    }
+    if name.chars().any(char::is_control) {
+        return Err(FolderNameError::ControlCharacter);
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !c.is_alphanumeric() && !ALLOWED_PUNCTUATION.contains(c))
+    {
+        return Err(FolderNameError::UnsupportedCharacter(c));
+    }
+    Ok(name)
+}
+
+/// Prefix of the folders holding post assets
+pub const POST_FOLDER_PREFIX: &str = "posts/";
+
+/// Folder holding the assets of post `id`. The only nested folder name,
+/// built here rather than taken from users.
+pub fn post_folder_name(id: Uuid) -> String {
+    format!("{}{}", POST_FOLDER_PREFIX, id)
+}
+
+/// Prefix of the private folders holding submission attachments
+pub const SUBMISSION_FOLDER_PREFIX: &str = "submissions/";
+
+/// Folder holding the attachment of submission `id`. Like post folders it
+/// is nested, so it can never be named in `PUBLIC_FOLDERS`.
+pub fn submission_folder_name(id: Uuid) -> String {
+    format!("{}{}", SUBMISSION_FOLDER_PREFIX, id)
+}
+
+/// Folders only admins may list: the virtual `others` group and the post
+/// and submission folders, which the public reaches through their owner
+pub fn is_internal_folder(name: &str) -> bool {
+    name == OTHERS_FOLDER
+        || name.starts_with(POST_FOLDER_PREFIX)
+        || name.starts_with(SUBMISSION_FOLDER_PREFIX)
+}
@@ -0,0 +1,180 @@
+//! Rebuild post folder links from the bucket layout.
+//!
+//! Objects stored under a `posts/{id}/` prefix belong to the folder of that
+//! name. When `asset_folders` drifts from the bucket, the links of each such
+//! folder are replaced by the assets whose `filename` matches an object under
+//! its prefix. Prefixes without any known asset are left alone, so folders
+//! whose files sit at the bucket root keep their links.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use super::folder_name::POST_FOLDER_PREFIX;
+use super::models::Asset;
+use crate::storage::FOLDER_PLACEHOLDER;
+use crate::storage_usage::scan_bucket;
+use crate::{ApiError, AppState};
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct RebuildFoldersQuery {
+    /// Only report the changes
+    pub dry_run: Option<bool>,
+}
+
+/// Links of one folder that differ from the bucket
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct FolderRebuild {
+    #[schema(example = "posts/0f8c7e52-3d1a-4b7e-9a51-2f0d5c9e8b41")]
+    pub folder: String,
+    /// Assets found under the prefix but not linked to the folder
+    pub added: Vec<Uuid>,
+    /// Assets linked to the folder but not found under the prefix
+    pub removed: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RebuildFoldersResponse {
+    /// Nothing was changed
+    pub dry_run: bool,
+    /// Folders whose links were, or in a dry run would be, replaced
+    pub folders: Vec<FolderRebuild>,
+    /// Objects under `posts/` that match no asset
+    pub unmatched_objects: Vec<String>,
+}
+
+/// Folder an object path under `posts/` belongs to
+fn folder_of(path: &str) -> Option<&str> {
+    let rest = path.strip_prefix(POST_FOLDER_PREFIX)?;
+    let (id, _) = rest.split_once('/')?;
+    (!id.is_empty()).then(|| &path[..POST_FOLDER_PREFIX.len() + id.len()])
+}
+
+/// Compare the objects under `posts/` with the `current` links of each
+/// folder. Returns the folders whose links differ, and the objects matching
+/// no asset by full path or by name.
+pub fn plan_rebuild(
+    objects: &[String],
+    assets: &[Asset],
+    current: &HashMap<String, Vec<Uuid>>,
+) -> (Vec<FolderRebuild>, Vec<String>) {
+    let by_filename: HashMap<&str, Uuid> = assets
+        .iter()
+        .map(|asset| (asset.filename.as_str(), asset.id))
+        .collect();
+
+    let mut found: BTreeMap<&str, Vec<Uuid>> = BTreeMap::new();
+    let mut unmatched = Vec::new();
+    for path in objects {
+        let Some(folder) = folder_of(path) else {
+            continue;
+        };
+        let name = path.rsplit('/').next().unwrap_or(path);
+        if name == FOLDER_PLACEHOLDER {
+            continue;
+        }
+        match by_filename
+            .get(path.as_str())
+            .or_else(|| by_filename.get(name))
+        {
+            Some(id) => {
+                let ids = found.entry(folder).or_default();
+                if !ids.contains(id) {
+                    ids.push(*id);
+                }
+            }
+            None => unmatched.push(path.clone()),
+        }
+    }
+
+    let folders = found
+        .into_iter()
+        .filter_map(|(folder, ids)| {
+            let linked = current.get(folder).map(Vec::as_slice).unwrap_or_default();
+            let linked_set: HashSet<&Uuid> = linked.iter().collect();
+            let found_set: HashSet<&Uuid> = ids.iter().collect();
+            let rebuild = FolderRebuild {
+                folder: folder.to_string(),
+                added: ids
+                    .iter()
+                    .filter(|id| !linked_set.contains(id))
+                    .copied()
+                    .collect(),
+                removed: linked
+                    .iter()
+                    .filter(|id| !found_set.contains(id))
+                    .copied()
+                    .collect(),
+            };
+            (!rebuild.added.is_empty() || !rebuild.removed.is_empty()).then_some(rebuild)
+        })
+        .collect();
+    (folders, unmatched)
+}
+
+impl AppState {
+    /// Replace the links of every post folder that differs from the objects
+    /// under its prefix, all in one transaction. A dry run only reports.
+    pub async fn rebuild_post_folders(
+        &self,
+        dry_run: bool,
+    ) -> Result<RebuildFoldersResponse, ApiError> {
+        let objects: Vec<String> = scan_bucket(self.storage.as_ref(), POST_FOLDER_PREFIX)
+            .await
+            .map_err(ApiError::storage("Failed to list post folders"))?
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        let assets = self
+            .get_all_assets()
+            .await
+            .map_err(ApiError::database("Failed to retrieve assets"))?;
+
+        let mut current = HashMap::new();
+        let folders: HashSet<&str> = objects.iter().filter_map(|path| folder_of(path)).collect();
+        for folder in folders {
+            let linked = self
+                .get_folder_contents(folder)
+                .await
+                .map_err(ApiError::database("Failed to retrieve folder contents"))?
+                .unwrap_or_default();
+            current.insert(folder.to_string(), linked);
+        }
+
+        let (rebuilds, unmatched_objects) = plan_rebuild(&objects, &assets, &current);
+        if !dry_run && !rebuilds.is_empty() {
+            let links: Vec<(String, Vec<Uuid>)> = rebuilds
+                .iter()
+                .map(|rebuild| {
+                    let linked = &current[&rebuild.folder];
+                    let ids = linked
+                        .iter()
+                        .filter(|id| !rebuild.removed.contains(id))
+                        .chain(&rebuild.added)
+                        .copied()
+                        .collect();
+                    (rebuild.folder.clone(), ids)
+                })
+                .collect();
+            self.replace_folder_links(&links)
+                .await
+                .map_err(ApiError::database("Failed to rebuild folder links"))?;
+        }
+        for rebuild in &rebuilds {
+            log::info!(
+                "{}folder {}: {} assets added, {} removed",
+                if dry_run { "[dry run] " } else { "" },
+                rebuild.folder,
+                rebuild.added.len(),
+                rebuild.removed.len()
+            );
+        }
+
+        Ok(RebuildFoldersResponse {
+            dry_run,
+            folders: rebuilds,
+            unmatched_objects,
+        })
+    }
+}
@@ -0,0 +1,80 @@
+//! Read-only gallery for the public site.
+//!
+//! Only folders named in `PUBLIC_FOLDERS` are listed, so internal folders
+//! (post folders, organization files) never reach visitors. Entries must be
+//! plain folder names; `posts/*` folders cannot be made public.
+
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::folder_name::normalize_folder_name;
+use super::models::Asset;
+
+/// Assets per page when `limit` is not given
+pub const DEFAULT_PAGE_SIZE: i64 = 20;
+/// Largest accepted `limit`
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GalleryConfig {
+    /// Normalized names of the folders shown in the gallery
+    pub public_folders: Vec<String>,
+}
+
+impl GalleryConfig {
+    /// Load using a custom variable lookup
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut public_folders = Vec::new();
+        let value = lookup("PUBLIC_FOLDERS").unwrap_or_default();
+        for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let name = normalize_folder_name(entry)
+                .map_err(|e| format!("PUBLIC_FOLDERS entry '{}': {}", entry, e))?;
+            if !public_folders.contains(&name) {
+                public_folders.push(name);
+            }
+        }
+        Ok(Self { public_folders })
+    }
+
+    /// Normalized `name`, when that folder is shown in the gallery
+    pub fn public_name(&self, name: &str) -> Option<String> {
+        normalize_folder_name(name)
+            .ok()
+            .filter(|name| self.public_folders.contains(name))
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GalleryQuery {
+    /// Only list this public folder
+    pub folder: Option<String>,
+    /// Page number, starting at 1 (default: 1)
+    pub page: Option<i64>,
+    /// Assets per page, at most 100 (default: 20)
+    pub limit: Option<i64>,
+}
+
+/// An asset with the public folder it is listed under
+#[derive(Debug, Serialize, ToSchema, sqlx::FromRow)]
+pub struct GalleryAsset {
+    #[schema(example = "galeri")]
+    pub folder: String,
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub asset: Asset,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GalleryResponse {
+    /// Folders shown in the gallery
+    pub folders: Vec<String>,
+    pub page: i64,
+    pub limit: i64,
+    /// Assets across all pages
+    pub total: i64,
+    /// Newest first
+    pub assets: Vec<GalleryAsset>,
+}
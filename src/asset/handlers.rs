@@ -1,18 +1,39 @@
 use actix_multipart::Multipart;
 use actix_web::{
-    HttpResponse, Responder,
     web::{self, Json, Path},
+    HttpResponse,
 };
 use futures::StreamExt;
-use log::{debug, error, info};
-use serde::Serialize;
-use utoipa::ToSchema;
+use log::{debug, error, info, warn};
 use sanitize_filename::sanitize;
+use serde::Serialize;
 use std::path::Path as StdPath;
-use crate::ErrorResponse;
-use crate::{asset::models::Asset, db::AppState, posting::multipart_parser::{MultipartParser, MultipartParseError}};
+use std::time::Duration;
+use tempfile::TempPath;
+use tokio::io::AsyncWriteExt;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
+use crate::asset::dimensions::{image_dimensions, is_image, HEADER_BYTES};
+use crate::asset::download::{content_disposition, download_filename, ServeAssetQuery};
+use crate::asset::exif_cleanup;
+use crate::asset::folder_name::{
+    is_internal_folder, normalize_folder_name, post_folder_name, OTHERS_FOLDER, POST_FOLDER_PREFIX,
+    SUBMISSION_FOLDER_PREFIX,
+};
+use crate::asset::folder_rebuild::{RebuildFoldersQuery, RebuildFoldersResponse};
+use crate::asset::gallery::{GalleryQuery, GalleryResponse, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::asset::heic::{self, JPEG_EXTENSION};
+use crate::asset::public_url::serve_path;
+use crate::asset::upload_session::{ReceivedBytes, UploadProgress, UploadTarget, UPLOAD_ID_HEADER};
+use crate::auth::validate_request_token;
+use crate::storage::upload_with_unique_name;
+use crate::webhook::WebhookEvent;
+use crate::{asset::models::Asset, db::AppState, posting::multipart_parser::MultipartParser};
+use crate::{ApiError, ErrorResponse};
+
+/// Validity of the URL `serve_asset` redirects to for a private bucket
+const SIGNED_URL_TTL: Duration = Duration::from_secs(60 * 60);
 
 #[derive(Serialize, ToSchema)]
 pub struct FolderWithAssets {
@@ -21,154 +42,527 @@ pub struct FolderWithAssets {
 }
 
 #[derive(Serialize, ToSchema)]
+#[schema(example = json!({
+    "folders": [{
+        "name": "posts/kerja-bakti-rw-05",
+        "assets": [{
+            "id": "a1b2c3d4-e5f6-7890-1234-567890abcdef",
+            "name": "Kerja Bakti",
+            "filename": "kerja-bakti.jpg",
+            "url": "/assets/serve/kerja-bakti.jpg",
+            "public_url": "https://example.com/assets/serve/kerja-bakti.jpg",
+            "download_url": "https://example.com/assets/serve/kerja-bakti.jpg?download=true",
+            "description": "Warga membersihkan saluran air",
+            "width": 1920,
+            "height": 1080,
+            "created_at": "2025-01-15T08:30:00Z",
+            "updated_at": "2025-01-15T08:30:00Z"
+        }]
+    }]
+}))]
 pub struct AllAssetsResponse {
     pub folders: Vec<FolderWithAssets>,
 }
 
+/// A file of an upload that could not be stored
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FailedUpload {
+    pub filename: String,
+    pub error: String,
+}
 
+/// Outcome of one file of `POST /api/assets`: the created asset, or why the
+/// file was not stored
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum UploadedFile {
+    Uploaded(Asset),
+    Failed(FailedUpload),
+}
 
 #[utoipa::path(
+    operation_id = "uploadAsset",
     context_path = "/api",
     tag = "Asset Service",
     post,
     path = "/assets",
     request_body(content = inline(UploadAssetRequest), content_type = "multipart/form-data"),
     responses(
-        (status = 201, description = "Asset created successfully", body = Asset),
+        (status = 201, description = "One entry per file, in upload order: the created asset, or the filename and error of a file that failed while others succeeded", body = Vec<UploadedFile>),
         (status = 400, description = "Invalid request", body = ErrorResponse),
         (status = 404, description = "Posting not found for asset", body = ErrorResponse),
+        (status = 413, description = "File exceeds the upload size limit", body = ErrorResponse),
         (status = 500, description = "Internal Server Error", body = ErrorResponse)
     )
 )]
-pub async fn upload_asset(payload: Multipart, data: web::Data<AppState>) -> impl Responder {
+pub async fn upload_asset(
+    payload: Multipart,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
     info!("Executing upload_asset handler");
     debug!("Attempting to parse multipart payload.");
 
-    match MultipartParser::parse_asset_multipart(payload).await {
-        Ok((file_data, original_filename, asset_name, posting_id_opt, folder_names)) => {
-            // Generate a unique filename for storage
-            let ext = StdPath::new(&original_filename)
-                .extension()
-                .and_then(std::ffi::OsStr::to_str)
-                .unwrap_or("");
-
-            let unique_filename = format!("{}_{}.{}", Uuid::new_v4(), sanitize(&original_filename).replace(".", "_"), ext);
-
-            // Upload file to storage
-            debug!("Attempting to upload file to storage with unique name: {}", unique_filename);
-            if let Err(e) = data.storage.upload_file(&unique_filename, &file_data).await {
-                error!("Failed to upload file to storage: {}", e);
-                return HttpResponse::InternalServerError()
-                    .json(ErrorResponse::internal_error("Failed to upload file"));
-            }
-
-            info!("File saved successfully with filename: {}", unique_filename);
-            let name = asset_name.unwrap_or_else(|| original_filename.clone());
-            let new_asset = Asset::new(
-                name,
-                unique_filename.clone(),
-                format!("/assets/serve/{}", unique_filename),
-                None,
-            );
+    let parsed = MultipartParser::parse_asset_multipart(payload)
+        .await
+        .map_err(|e| {
+            error!("Failed during multipart parsing: {}", e);
+            ApiError::from(e)
+        })?;
+    // A custom name only makes sense for a single file
+    let single_name = match parsed.files.len() {
+        1 => parsed.name,
+        _ => None,
+    };
+
+    let mut results = Vec::with_capacity(parsed.files.len());
+    for (file_data, original_filename) in parsed.files {
+        let result = store_asset(&data, &file_data, &original_filename, single_name.clone()).await;
+        results.push((original_filename, result));
+    }
 
-            debug!("Attempting to insert new asset into 'assets' table.");
-            if let Err(e) = data.insert_asset(&new_asset).await {
-                error!("Failed to insert asset into db: {}", e);
-                return HttpResponse::InternalServerError()
-                    .json(ErrorResponse::internal_error("Failed to save asset"));
-            }
-            info!("Asset {:?} created and stored in database.", new_asset.id);
+    let new_assets: Vec<Asset> = results
+        .iter()
+        .filter_map(|(_, result)| result.as_ref().ok().cloned())
+        .collect();
+    if new_assets.is_empty() {
+        // Nothing was stored; fail the request as a single upload would
+        let (_, first_error) = results.into_iter().next().expect("parser returns files");
+        return Err(first_error.expect_err("no file succeeded"));
+    }
+    let new_asset_ids: Vec<Uuid> = new_assets.iter().map(|asset| asset.id).collect();
+    link_new_assets(&data, &new_asset_ids, parsed.folders, parsed.posting_id).await?;
+
+    let uploaded: Vec<UploadedFile> = results
+        .into_iter()
+        .map(|(filename, result)| match result {
+            Ok(asset) => UploadedFile::Uploaded(asset),
+            Err(e) => UploadedFile::Failed(FailedUpload {
+                filename,
+                error: e.message(),
+            }),
+        })
+        .collect();
+    Ok(HttpResponse::Created().json(uploaded))
+}
 
-            let mut processed_folder_names = Vec::new();
-            if folder_names.is_empty() {
-                processed_folder_names.push("others".to_string());
-            } else {
-                for folder_name in folder_names {
-                    if folder_name.is_empty() {
-                        processed_folder_names.push("others".to_string());
-                    } else {
-                        processed_folder_names.push(folder_name);
-                    }
-                }
-            }
-            let unique_folder_names: Vec<String> = processed_folder_names
-                .into_iter()
-                .collect::<std::collections::HashSet<String>>()
-                .into_iter()
-                .collect();
+/// Add newly stored assets to `folders` and to the posting `posting_id`.
+/// Only a failure to read a folder is returned; failed updates are logged.
+async fn link_new_assets(
+    data: &AppState,
+    new_asset_ids: &[Uuid],
+    folders: Vec<String>,
+    posting_id: Option<Uuid>,
+) -> Result<(), ApiError> {
+    // Assets without a folder stay unlinked and are listed under "others"
+    let unique_folder_names: Vec<String> = folders
+        .into_iter()
+        .filter(|name| !name.is_empty() && name != OTHERS_FOLDER)
+        .collect::<std::collections::HashSet<String>>()
+        .into_iter()
+        .collect();
+
+    for folder_name in unique_folder_names {
+        debug!(
+            "Associating assets {:?} with folder '{}'",
+            new_asset_ids, folder_name
+        );
+        let mut asset_ids = data
+            .get_folder_contents(&folder_name)
+            .await
+            .map_err(ApiError::database("Failed to retrieve folder contents"))?
+            .unwrap_or_default();
+        asset_ids.extend(new_asset_ids);
+        if let Err(e) = data.insert_folder_contents(&folder_name, &asset_ids).await {
+            error!("Failed to associate assets with folder: {}", e);
+        } else {
+            info!(
+                "Assets {:?} successfully associated with folder '{}'",
+                new_asset_ids, folder_name
+            );
+        }
+    }
 
-            for folder_name in unique_folder_names {
-                debug!(
-                    "Associating asset {:?} with folder '{}'",
-                    new_asset.id, folder_name
-                );
-                let folder_contents_result = data.get_folder_contents(&folder_name).await;
-                let mut asset_ids = match folder_contents_result {
-                    Ok(Some(ids)) => ids,
-                    Ok(None) => Vec::new(),
-                    Err(e) => {
-                        error!("Database error when getting folder contents: {}", e);
-                        return HttpResponse::InternalServerError()
-                            .json(ErrorResponse::internal_error("Failed to retrieve folder contents"));
-                    }
-                };
-                asset_ids.push(new_asset.id);
-                if let Err(e) = data.insert_folder_contents(&folder_name, &asset_ids).await {
-                    error!("Failed to associate asset with folder: {}", e);
+    if let Some(posting_id) = posting_id {
+        debug!(
+            "Associating assets {:?} with posting '{:?}'",
+            new_asset_ids, posting_id
+        );
+        match data.get_posting_by_id_with_assets(&posting_id).await {
+            Ok(Some(mut posting)) => {
+                posting.asset_ids.extend(new_asset_ids);
+                if let Err(e) = data.upsert_posting_with_assets(&posting).await {
+                    error!(
+                        "Failed to update posting {} with new assets {:?}: {}",
+                        posting.id, new_asset_ids, e
+                    );
                 } else {
                     info!(
-                        "Asset {:?} successfully associated with folder '{}'",
-                        new_asset.id, folder_name
+                        "Assets {:?} successfully associated with posting '{:?}'",
+                        new_asset_ids, posting_id
                     );
                 }
             }
-
-            if let Some(posting_id) = posting_id_opt {
-                debug!(
-                    "Associating asset {:?} with posting '{:?}'",
-                    new_asset.id, posting_id
+            Ok(None) => {
+                error!(
+                    "Posting not found for asset association: posting_id='{:?}'",
+                    posting_id
                 );
-                match data.get_posting_by_id_with_assets(&posting_id).await {
-                    Ok(Some(mut posting)) => {
-                        posting.asset_ids.push(new_asset.id);
-                        if let Err(e) = data.upsert_posting_with_assets(&posting).await {
-                            error!(
-                                "Failed to update posting {} with new asset {}: {}",
-                                posting.id, new_asset.id, e
-                            );
-                        } else {
-                            info!(
-                                "Asset {:?} successfully associated with posting '{:?}'",
-                                new_asset.id, posting_id
-                            );
-                        }
-                    }
-                    Ok(None) => {
-                        error!(
-                            "Posting not found for asset association: posting_id='{:?}'",
-                            posting_id
-                        );
-                    }
-                    Err(e) => {
-                        error!("Database error when fetching posting: {}", e);
-                    }
-                }
             }
+            Err(e) => {
+                error!("Database error when fetching posting: {}", e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Upload one file to storage and record it in the `assets` table
+async fn store_asset(
+    data: &AppState,
+    file_data: &[u8],
+    original_filename: &str,
+    asset_name: Option<String>,
+) -> Result<Asset, ApiError> {
+    let new_asset = save_asset(data, file_data, original_filename, asset_name).await?;
+    data.publish_change(WebhookEvent::AssetUploaded, new_asset.id, &new_asset);
+    Ok(new_asset)
+}
+
+/// [`store_asset`] without announcing the upload to webhooks and the change
+/// feed, for files that are not part of the public site
+pub(crate) async fn save_asset(
+    data: &AppState,
+    file_data: &[u8],
+    original_filename: &str,
+    asset_name: Option<String>,
+) -> Result<Asset, ApiError> {
+    data.upload
+        .check_size(original_filename, file_data.len())
+        .map_err(ApiError::PayloadTooLarge)?;
+
+    let jpeg = convert_heic(data, original_filename, file_data).await;
+
+    // Generate a unique filename for storage
+    let ext = match jpeg {
+        Some(_) => JPEG_EXTENSION,
+        None => StdPath::new(original_filename)
+            .extension()
+            .and_then(std::ffi::OsStr::to_str)
+            .unwrap_or(""),
+    };
+
+    let base_name = sanitize(original_filename).replace(".", "_");
+
+    // Upload file to storage
+    debug!(
+        "Attempting to upload file to storage for: {}",
+        original_filename
+    );
+    let stored_data = jpeg.as_deref().unwrap_or(file_data);
+    let stripped = strip_photo_metadata(data, stored_data).await;
+    let stored_data = stripped.as_deref().unwrap_or(stored_data);
+    let unique_filename = upload_with_unique_name(data.storage.as_ref(), stored_data, |_| {
+        format!("{}_{}.{}", Uuid::new_v4(), base_name, ext)
+    })
+    .await
+    .map_err(ApiError::storage("Failed to upload file"))?;
+
+    info!("File saved successfully with filename: {}", unique_filename);
+    if jpeg.is_some() {
+        keep_heic_original(data, &unique_filename, file_data).await;
+    }
+    let name = asset_name.unwrap_or_else(|| match jpeg {
+        Some(_) => heic::jpeg_filename(original_filename),
+        None => original_filename.to_string(),
+    });
+    let mut new_asset = Asset::new(
+        name,
+        unique_filename.clone(),
+        serve_path(&unique_filename),
+        None,
+    );
+    new_asset.read_dimensions(stored_data);
+
+    debug!("Attempting to insert new asset into 'assets' table.");
+    data.insert_asset(&new_asset)
+        .await
+        .map_err(ApiError::database("Failed to save asset"))?;
+    info!("Asset {:?} created and stored in database.", new_asset.id);
+    Ok(new_asset)
+}
 
-            HttpResponse::Created().json(new_asset)
+/// JPEG version of `file_data` when it is a HEIC photo and conversion is
+/// enabled. A photo that fails to convert is stored as uploaded.
+async fn convert_heic(data: &AppState, filename: &str, file_data: &[u8]) -> Option<Vec<u8>> {
+    let config = &data.upload.heic;
+    if !config.convert || !heic::is_heic(filename, file_data) {
+        return None;
+    }
+    match heic::convert_to_jpeg(config, file_data).await {
+        Ok(jpeg) => {
+            info!("Converted HEIC upload {} to JPEG", filename);
+            Some(jpeg)
         }
-        Err(MultipartParseError::FieldError(e)) | Err(MultipartParseError::MetadataError(e)) | Err(MultipartParseError::Utf8Error(e)) | Err(MultipartParseError::SerializationError(e)) => {
-            error!("Failed during multipart parsing: {}", e);
-            HttpResponse::BadRequest().json(ErrorResponse::bad_request(&e))
+        Err(e) => {
+            warn!("Storing HEIC upload {} unconverted: {}", filename, e);
+            None
         }
-        Err(MultipartParseError::IoError(e)) => {
-            error!("IO error during multipart parsing: {}", e);
-            HttpResponse::InternalServerError().json(ErrorResponse::internal_error(&e))
+    }
+}
+
+/// `file_data` turned upright and without EXIF when it is a JPEG carrying
+/// EXIF and `STRIP_EXIF` is on
+async fn strip_photo_metadata(data: &AppState, file_data: &[u8]) -> Option<Vec<u8>> {
+    if !data.upload.strip_exif || !exif_cleanup::is_jpeg(file_data) {
+        return None;
+    }
+    let file_data = file_data.to_vec();
+    match tokio::task::spawn_blocking(move || exif_cleanup::strip_exif(&file_data)).await {
+        Ok(stripped) => stripped,
+        Err(e) => {
+            warn!("EXIF removal task failed: {}", e);
+            None
         }
     }
 }
 
+/// Store the uploaded HEIC file next to its JPEG `jpeg_filename` when
+/// `HEIC_KEEP_ORIGINAL` is set
+async fn keep_heic_original(data: &AppState, jpeg_filename: &str, file_data: &[u8]) {
+    if !data.upload.heic.keep_original {
+        return;
+    }
+    if let Some(original) = heic::kept_original(jpeg_filename) {
+        if let Err(e) = data.storage.upload_file(&original, file_data).await {
+            warn!("Failed to keep original HEIC file {}: {}", original, e);
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, ToSchema)]
+pub struct StartUploadRequest {
+    /// Name of the file that will be sent
+    #[schema(example = "video-kerja-bakti.mp4")]
+    pub filename: String,
+    /// Display name of the asset, defaults to `filename`
+    pub name: Option<String>,
+    /// Folders the asset is added to once finalized
+    #[serde(default)]
+    pub folders: Vec<String>,
+    /// Posting the asset is attached to once finalized
+    pub posting_id: Option<Uuid>,
+}
+
+/// Open an upload session for a large file. Send the file with
+/// `PUT /api/assets/uploads/{id}`, poll its progress, then finalize it.
+/// Sessions expire after an hour.
+#[utoipa::path(
+    operation_id = "startAssetUpload",
+    context_path = "/api",
+    tag = "Asset Service",
+    post,
+    path = "/assets/uploads",
+    request_body = StartUploadRequest,
+    responses(
+        (status = 201, description = "Session opened", body = UploadProgress,
+            headers(("X-Upload-Id" = Uuid, description = "Id of the new session"))),
+        (status = 400, description = "Invalid request", body = ErrorResponse)
+    )
+)]
+pub async fn start_upload(
+    body: web::Json<StartUploadRequest>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let request = body.into_inner();
+    if request.filename.trim().is_empty() {
+        return Err(ApiError::BadRequest("filename is required".to_string()));
+    }
+
+    let progress = data.upload_sessions.open(UploadTarget {
+        filename: request.filename,
+        name: request.name,
+        folders: request.folders,
+        posting_id: request.posting_id,
+    });
+    info!(
+        "Opened upload session {} for '{}'",
+        progress.id, progress.filename
+    );
+    Ok(HttpResponse::Created()
+        .insert_header((UPLOAD_ID_HEADER, progress.id.to_string()))
+        .json(progress))
+}
+
+/// Send the file of an upload session as the raw request body. Progress is
+/// measured against `Content-Length`. Sending again replaces the file.
+#[utoipa::path(
+    operation_id = "sendAssetUpload",
+    context_path = "/api",
+    tag = "Asset Service",
+    put,
+    path = "/assets/uploads/{id}",
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = 200, description = "File received, the session can be finalized", body = UploadProgress),
+        (status = 400, description = "The body ended before `Content-Length` bytes", body = ErrorResponse),
+        (status = 404, description = "Session not found or expired", body = ErrorResponse),
+        (status = 409, description = "The session is already receiving a file", body = ErrorResponse),
+        (status = 413, description = "File exceeds the upload size limit", body = ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Upload session id")
+    )
+)]
+pub async fn receive_upload(
+    req: actix_web::HttpRequest,
+    id: Path<Uuid>,
+    mut payload: web::Payload,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let id = id.into_inner();
+    let filename = data.upload_sessions.progress(id)?.filename;
+    let expected = req
+        .headers()
+        .get(actix_web::http::header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if let Some(expected) = expected {
+        data.upload
+            .check_size(&filename, expected as usize)
+            .map_err(ApiError::PayloadTooLarge)?;
+    }
+
+    let received = data.upload_sessions.begin(id, expected)?;
+    let file = match receive_to_temp_file(&data, &filename, &mut payload, &received).await {
+        Ok(file) => file,
+        Err(e) => {
+            data.upload_sessions.abort(id);
+            return Err(e);
+        }
+    };
+    if let Some(expected) = expected.filter(|&expected| expected != received.get()) {
+        data.upload_sessions.abort(id);
+        return Err(ApiError::BadRequest(format!(
+            "Upload incomplete: received {} of {} bytes",
+            received.get(),
+            expected
+        )));
+    }
+
+    let progress = data.upload_sessions.complete(id, file)?;
+    info!(
+        "Upload session {} received {} bytes",
+        id, progress.received_bytes
+    );
+    Ok(HttpResponse::Ok()
+        .insert_header((UPLOAD_ID_HEADER, id.to_string()))
+        .json(progress))
+}
+
+/// Write `payload` to a new temp file, advancing `received` per chunk
+async fn receive_to_temp_file(
+    data: &AppState,
+    filename: &str,
+    payload: &mut web::Payload,
+    received: &ReceivedBytes,
+) -> Result<TempPath, ApiError> {
+    // Creating the file is a blocking call, like every std::fs one
+    let (temp, file) = tokio::task::spawn_blocking(|| {
+        let temp = tempfile::NamedTempFile::new_in(crate::maintenance::temp_dir())?;
+        let file = temp.reopen()?;
+        Ok::<_, std::io::Error>((temp, file))
+    })
+    .await
+    .map_err(|e| ApiError::Internal(format!("Temp file task failed: {}", e)))?
+    .map_err(|e| ApiError::Internal(format!("Failed to create temp file: {}", e)))?;
+    let mut file = tokio::fs::File::from_std(file);
+
+    while let Some(chunk) = payload.next().await {
+        let chunk =
+            chunk.map_err(|e| ApiError::BadRequest(format!("Upload interrupted: {}", e)))?;
+        let total = received.add(chunk.len() as u64);
+        data.upload
+            .check_size(filename, total as usize)
+            .map_err(ApiError::PayloadTooLarge)?;
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| ApiError::Internal(format!("Failed to write temp file: {}", e)))?;
+    }
+    file.flush()
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to write temp file: {}", e)))?;
+    Ok(temp.into_temp_path())
+}
+
+/// Bytes received so far by an upload session
 #[utoipa::path(
+    operation_id = "getAssetUploadProgress",
+    context_path = "/api",
+    tag = "Asset Service",
+    get,
+    path = "/assets/uploads/{id}/progress",
+    responses(
+        (status = 200, description = "Current progress", body = UploadProgress),
+        (status = 404, description = "Session not found or expired", body = ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Upload session id")
+    )
+)]
+pub async fn get_upload_progress(
+    id: Path<Uuid>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let progress = data.upload_sessions.progress(id.into_inner())?;
+    Ok(HttpResponse::Ok().json(progress))
+}
+
+/// Store the received file of an upload session as an asset, like
+/// `POST /api/assets` does, and close the session. A failed finalize also
+/// closes it.
+#[utoipa::path(
+    operation_id = "finalizeAssetUpload",
+    context_path = "/api",
+    tag = "Asset Service",
+    post,
+    path = "/assets/uploads/{id}/finalize",
+    responses(
+        (status = 201, description = "Asset created", body = Asset),
+        (status = 404, description = "Session not found or expired", body = ErrorResponse),
+        (status = 409, description = "The file has not been received yet", body = ErrorResponse),
+        (status = 413, description = "File exceeds the upload size limit", body = ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Upload session id")
+    )
+)]
+pub async fn finalize_upload(
+    id: Path<Uuid>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let (target, file) = data.upload_sessions.take(id.into_inner())?;
+    let file_data = tokio::fs::read(&file)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to read uploaded file: {}", e)))?;
+    remove_temp_file(file);
+
+    let asset = store_asset(&data, &file_data, &target.filename, target.name).await?;
+    link_new_assets(&data, &[asset.id], target.folders, target.posting_id).await?;
+    Ok(HttpResponse::Created().json(asset))
+}
+
+/// Delete `file` on the blocking pool rather than when it is dropped here
+fn remove_temp_file(file: TempPath) {
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = file.close() {
+            warn!("Failed to remove temp file: {}", e);
+        }
+    });
+}
+
+#[utoipa::path(
+    operation_id = "deleteAsset",
     context_path = "/api",
     tag = "Asset Service",
     delete,
@@ -182,12 +576,18 @@ pub async fn upload_asset(payload: Multipart, data: web::Data<AppState>) -> impl
         ("id" = Uuid, Path, description = "ID of the asset to delete")
     )
 )]
-pub async fn delete_asset(id: Path<Uuid>, data: web::Data<AppState>) -> impl Responder {
+pub async fn delete_asset(
+    id: Path<Uuid>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
     let asset_id_to_delete = id.into_inner();
     delete_asset_by_id(asset_id_to_delete, data).await
 }
 
-async fn delete_asset_by_id(asset_id_to_delete: Uuid, data: web::Data<AppState>) -> impl Responder {
+pub(crate) async fn delete_asset_by_id(
+    asset_id_to_delete: Uuid,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
     info!(
         "Executing delete_asset handler for ID: {:?}",
         asset_id_to_delete
@@ -197,133 +597,160 @@ async fn delete_asset_by_id(asset_id_to_delete: Uuid, data: web::Data<AppState>)
         "Attempting to fetch asset with ID {:?} for deletion.",
         asset_id_to_delete
     );
-    match data.get_asset_by_id(&asset_id_to_delete).await {
-        Ok(Some(asset)) => {
-            info!("Found asset {:?} to delete.", asset_id_to_delete);
-            debug!(
-                "Attempting to delete physical asset file: {}",
-                &asset.filename
-            );
-            if let Err(e) = data.storage.delete_file(&asset.filename).await {
-                error!(
-                    "Failed to delete physical asset file {}: {}.",
-                    asset.filename, e
-                );
-                return HttpResponse::InternalServerError()
-                    .json(ErrorResponse::internal_error("Failed to delete asset file"));
-            }
-            info!("Physical file {} deleted successfully.", asset.filename);
-
-            debug!(
-                "Attempting to delete asset record {:?} from 'assets' table.",
-                asset_id_to_delete
-            );
-            if let Err(e) = data.delete_asset(&asset_id_to_delete).await {
-                error!(
-                    "Failed to delete asset from db, but file was deleted: {}",
-                    e
-                );
-            }
+    let asset = data
+        .get_asset_by_id(&asset_id_to_delete)
+        .await
+        .map_err(ApiError::database("Failed to retrieve asset"))?
+        .ok_or_else(|| {
+            error!("Asset not found for deletion: {:?}", asset_id_to_delete);
+            ApiError::NotFound(format!("Asset with ID {:?} not found", asset_id_to_delete))
+        })?;
 
-            debug!(
-                "Scanning postings to disassociate asset {:?}",
-                asset_id_to_delete
-            );
-            if let Ok(postings) = data.get_all_postings_with_assets().await {
-                for mut posting in postings {
-                    if posting.asset_ids.contains(&asset_id_to_delete) {
-                        debug!(
-                            "Disassociating asset {:?} from posting {:?}",
-                            asset_id_to_delete, posting.id
-                        );
-                        posting.asset_ids.retain(|id| *id != asset_id_to_delete);
-                        if let Err(e) = data.upsert_posting_with_assets(&posting).await {
-                            error!("Failed to update posting after disassociating asset: {}", e);
-                        }
-                    }
+    info!("Found asset {:?} to delete.", asset_id_to_delete);
+    debug!(
+        "Attempting to delete physical asset file: {}",
+        &asset.filename
+    );
+    if let Err(e) = data.storage.delete_file(&asset.filename).await {
+        // A file that is already gone leaves only the row to delete
+        match data.storage.exists(&asset.filename).await {
+            Ok(false) => warn!("Asset file {} was already missing: {}", asset.filename, e),
+            _ => return Err(ApiError::storage("Failed to delete asset file")(e)),
+        }
+    } else {
+        info!("Physical file {} deleted successfully.", asset.filename);
+    }
+    if let Some(original) = heic::kept_original(&asset.filename) {
+        match data.storage.exists(&original).await {
+            Ok(true) => {
+                if let Err(e) = data.storage.delete_file(&original).await {
+                    warn!("Failed to delete original HEIC file {}: {}", original, e);
                 }
             }
+            Ok(false) => {}
+            Err(e) => warn!("Failed to look up original HEIC file {}: {}", original, e),
+        }
+    }
 
-            debug!(
-                "Scanning folders to disassociate asset {:?}",
-                asset_id_to_delete
-            );
+    // Looked up first, the folder links go with the asset row
+    let postings = data
+        .get_all_postings_with_assets(Some(asset_id_to_delete))
+        .await;
 
-            info!(
-                "Asset {:?} deleted successfully from all records.",
-                asset_id_to_delete
-            );
-            HttpResponse::NoContent().finish()
-        }
-        Ok(None) => {
-            error!("Asset not found for deletion: {:?}", asset_id_to_delete);
-            HttpResponse::NotFound().json(ErrorResponse::not_found(&format!(
-                "Asset with ID {:?} not found",
-                asset_id_to_delete
-            )))
-        }
-        Err(e) => {
-            error!("Failed to retrieve asset for deletion from database: {}", e);
-            HttpResponse::InternalServerError()
-                .json(ErrorResponse::internal_error("Failed to retrieve asset"))
+    debug!(
+        "Attempting to delete asset record {:?} from 'assets' table.",
+        asset_id_to_delete
+    );
+    if let Err(e) = data.delete_asset(&asset_id_to_delete).await {
+        error!(
+            "Failed to delete asset from db, but file was deleted: {}",
+            e
+        );
+    }
+
+    debug!(
+        "Scanning postings to disassociate asset {:?}",
+        asset_id_to_delete
+    );
+    match postings {
+        Ok(postings) => {
+            for mut posting in postings {
+                debug!(
+                    "Disassociating asset {:?} from posting {:?}",
+                    asset_id_to_delete, posting.id
+                );
+                posting.asset_ids.retain(|id| *id != asset_id_to_delete);
+                if let Err(e) = data.upsert_posting_with_assets(&posting).await {
+                    error!("Failed to update posting after disassociating asset: {}", e);
+                }
+            }
         }
+        Err(e) => error!("Failed to look up postings holding the asset: {}", e),
     }
+
+    debug!(
+        "Scanning folders to disassociate asset {:?}",
+        asset_id_to_delete
+    );
+
+    info!(
+        "Asset {:?} deleted successfully from all records.",
+        asset_id_to_delete
+    );
+    data.publish_change(
+        WebhookEvent::AssetDeleted,
+        asset_id_to_delete,
+        &serde_json::json!({ "id": asset_id_to_delete }),
+    );
+    Ok(HttpResponse::NoContent().finish())
 }
 
+/// One asset. Submission attachments need an admin token.
 #[utoipa::path(
+    operation_id = "getAsset",
     context_path = "/api",
     tag = "Asset Service",
     get,
     path = "/assets/{id}",
+    security((), ("bearer_auth" = [])),
     responses(
         (status = 200, description = "Asset found", body = Asset),
+        (status = 401, response = crate::UnauthorizedResponse),
         (status = 404, description = "Asset not found", body = ErrorResponse)
     ),
     params(
         ("id" = Uuid, Path, description = "ID of the asset to retrieve")
     )
 )]
-pub async fn get_asset_by_id(id: Path<Uuid>, data: web::Data<AppState>) -> impl Responder {
+pub async fn get_asset_by_id(
+    req: actix_web::HttpRequest,
+    id: Path<Uuid>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
     let asset_id = id.into_inner();
     info!("Executing get_asset_by_id handler for ID: {:?}", asset_id);
     debug!(
         "Received GET request to /assets/{:?} - this endpoint only supports GET and DELETE methods",
         asset_id
     );
-    match data.get_asset_by_id(&asset_id).await {
-        Ok(Some(asset)) => {
-            info!("Successfully fetched asset with ID: {:?}", asset_id);
-            HttpResponse::Ok().json(asset)
-        }
-        Ok(None) => {
+    let asset = data
+        .get_asset_by_id(&asset_id)
+        .await
+        .map_err(ApiError::database("Failed to retrieve asset"))?
+        .ok_or_else(|| {
             error!("Asset not found in database for ID: {:?}", asset_id);
-            HttpResponse::NotFound().json(ErrorResponse::not_found(&format!(
-                "Asset with ID {:?} not found",
-                asset_id
-            )))
-        }
-        Err(e) => {
-            error!(
-                "Failed to get asset by ID '{}' from database: {}",
-                asset_id, e
-            );
-            HttpResponse::InternalServerError()
-                .json(ErrorResponse::internal_error("Failed to retrieve asset"))
-        }
+            ApiError::NotFound(format!("Asset with ID {:?} not found", asset_id))
+        })?;
+    if let Some(refusal) = private_asset_refusal(&req, &data, &asset_id).await? {
+        return Ok(refusal);
     }
+
+    info!("Successfully fetched asset with ID: {:?}", asset_id);
+    Ok(HttpResponse::Ok().json(asset))
 }
 
+/// Every folder with its assets, including internal ones. The public site
+/// uses `GET /api/gallery` instead. (protected)
 #[utoipa::path(
+    operation_id = "listAssets",
     context_path = "/api",
     tag = "Asset Service",
     get,
     path = "/assets",
+    security(("bearer_auth" = [])),
     responses(
         (status = 200, description = "List of all assets, structured by folder", body = AllAssetsResponse),
+        (status = 401, response = crate::UnauthorizedResponse),
         (status = 500, description = "Internal Server Error", body = ErrorResponse)
     )
 )]
-pub async fn get_all_assets_structured(data: web::Data<AppState>) -> impl Responder {
+pub async fn get_all_assets_structured(
+    req: actix_web::HttpRequest,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    if let Err(e) = validate_request_token(&req) {
+        return Ok(e.error_response());
+    }
     info!("Executing get_all_assets_structured handler");
     debug!("Fetching all assets structured by folder using optimized SQL query.");
 
@@ -337,6 +764,8 @@ pub async fn get_all_assets_structured(data: web::Data<AppState>) -> impl Respon
                     'filename', a.filename,
                     'url', a.url,
                     'description', a.description,
+                    'width', a.width,
+                    'height', a.height,
                     'created_at', a.created_at,
                     'updated_at', a.updated_at
                 ) ORDER BY a.created_at DESC
@@ -344,6 +773,7 @@ pub async fn get_all_assets_structured(data: web::Data<AppState>) -> impl Respon
         FROM folders f
         LEFT JOIN asset_folders af ON f.id = af.folder_id
         LEFT JOIN assets a ON af.asset_id = a.id
+        WHERE f.name <> $1 AND f.name NOT LIKE $2 AND f.name NOT LIKE $3
         GROUP BY f.name
         ORDER BY f.name
     "#;
@@ -354,81 +784,143 @@ pub async fn get_all_assets_structured(data: web::Data<AppState>) -> impl Respon
         assets_json: serde_json::Value,
     }
 
-    let folder_results: Result<Vec<FolderAssetsRow>, _> = sqlx::query_as(folder_assets_query)
+    // A leftover "others" row is not listed until it is dissolved. Post and
+    // submission folders are internal and reached through their owner.
+    let folder_rows: Vec<FolderAssetsRow> = sqlx::query_as(folder_assets_query)
+        .bind(OTHERS_FOLDER)
+        .bind(format!("{}%", POST_FOLDER_PREFIX))
+        .bind(format!("{}%", SUBMISSION_FOLDER_PREFIX))
         .fetch_all(&data.pool)
-        .await;
+        .await
+        .map_err(ApiError::database("Failed to retrieve structured assets"))?;
 
-    match folder_results {
-        Ok(folder_rows) => {
-            let mut folders_with_assets: Vec<FolderWithAssets> = Vec::new();
-
-            for row in folder_rows {
-                let assets: Vec<Asset> = if row.assets_json.is_array() {
-                    match serde_json::from_value(row.assets_json.clone()) {
-                        Ok(assets) => assets,
-                        Err(e) => {
-                            error!("Failed to parse assets JSON for folder {}: {}", row.folder_name, e);
-                            Vec::new()
-                        }
-                    }
-                } else {
-                    Vec::new()
-                };
-
-                folders_with_assets.push(FolderWithAssets {
-                    name: row.folder_name,
-                    assets,
-                });
-            }
+    let mut folders_with_assets: Vec<FolderWithAssets> = Vec::new();
 
-            let unassigned_query = r#"
-                SELECT
-                    id, name, filename, url, description, created_at, updated_at
-                FROM assets
-                WHERE id NOT IN (
-                    SELECT DISTINCT asset_id
-                    FROM asset_folders
-                    WHERE asset_id IS NOT NULL
-                )
-                ORDER BY created_at DESC
-            "#;
-
-            let unassigned_assets: Result<Vec<Asset>, _> = sqlx::query_as(unassigned_query)
-                .fetch_all(&data.pool)
-                .await;
-
-            match unassigned_assets {
-                Ok(unassigned) => {
-                    if !unassigned.is_empty() {
-                        folders_with_assets.push(FolderWithAssets {
-                            name: "others".to_string(),
-                            assets: unassigned,
-                        });
-                    }
-
-                    info!("Successfully fetched structured assets: {} folders", folders_with_assets.len());
-                    let response = AllAssetsResponse {
-                        folders: folders_with_assets,
-                    };
-                    HttpResponse::Ok().json(response)
-                }
+    for row in folder_rows {
+        let assets: Vec<Asset> = if row.assets_json.is_array() {
+            match serde_json::from_value(row.assets_json.clone()) {
+                Ok(assets) => assets,
                 Err(e) => {
-                    error!("Failed to fetch unassigned assets: {}", e);
-                    HttpResponse::InternalServerError()
-                        .json(ErrorResponse::internal_error("Failed to retrieve unassigned assets"))
+                    error!(
+                        "Failed to parse assets JSON for folder {}: {}",
+                        row.folder_name, e
+                    );
+                    Vec::new()
                 }
             }
-        }
-        Err(e) => {
-            error!("Failed to get structured assets from database: {}", e);
-            HttpResponse::InternalServerError()
-                .json(ErrorResponse::internal_error("Failed to retrieve structured assets"))
-        }
+        } else {
+            Vec::new()
+        };
+
+        folders_with_assets.push(FolderWithAssets {
+            name: row.folder_name,
+            assets,
+        });
+    }
+
+    let unassigned = data
+        .get_unlinked_assets()
+        .await
+        .map_err(ApiError::database("Failed to retrieve unassigned assets"))?;
+
+    if !unassigned.is_empty() {
+        folders_with_assets.push(FolderWithAssets {
+            name: OTHERS_FOLDER.to_string(),
+            assets: unassigned,
+        });
+    }
+
+    info!(
+        "Successfully fetched structured assets: {} folders",
+        folders_with_assets.len()
+    );
+    let response = AllAssetsResponse {
+        folders: folders_with_assets,
+    };
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Assets of the folders listed in `PUBLIC_FOLDERS`, newest first. Other
+/// folders are never shown; asking for one with `folder` gives 404.
+#[utoipa::path(
+    operation_id = "listGallery",
+    context_path = "/api",
+    tag = "Asset Service",
+    get,
+    path = "/gallery",
+    params(GalleryQuery),
+    responses(
+        (status = 200, description = "One page of public assets", body = GalleryResponse),
+        (status = 400, description = "Invalid page or limit", body = ErrorResponse),
+        (status = 404, description = "Folder is not public", body = ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+pub async fn get_gallery(
+    query: web::Query<GalleryQuery>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let page = query.page.unwrap_or(1);
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if page < 1 {
+        return Err(ApiError::BadRequest("page must be at least 1".to_string()));
     }
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(ApiError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_PAGE_SIZE
+        )));
+    }
+
+    let public_folders = &data.gallery.public_folders;
+    let folders = match &query.folder {
+        Some(folder) => vec![data
+            .gallery
+            .public_name(folder)
+            .ok_or_else(|| ApiError::NotFound(format!("Folder '{}' not found", folder)))?],
+        None => public_folders.clone(),
+    };
+
+    let (assets, total) = if folders.is_empty() {
+        (Vec::new(), 0)
+    } else {
+        data.get_gallery_assets(&folders, limit, (page - 1).saturating_mul(limit))
+            .await
+            .map_err(ApiError::database("Failed to retrieve gallery"))?
+    };
+    Ok(HttpResponse::Ok().json(GalleryResponse {
+        folders: public_folders.clone(),
+        page,
+        limit,
+        total,
+        assets,
+    }))
 }
 
+/// The 401 response when `asset_id` is a submission attachment and `req`
+/// carries no admin token
+async fn private_asset_refusal(
+    req: &actix_web::HttpRequest,
+    data: &AppState,
+    asset_id: &Uuid,
+) -> Result<Option<HttpResponse>, ApiError> {
+    let private = data
+        .get_private_asset_ids(std::slice::from_ref(asset_id))
+        .await
+        .map_err(ApiError::database("Failed to retrieve asset"))?;
+    if private.is_empty() {
+        return Ok(None);
+    }
+    Ok(validate_request_token(req)
+        .err()
+        .map(|e| e.error_response()))
+}
 
-pub async fn serve_asset(req: actix_web::HttpRequest, data: web::Data<AppState>) -> impl Responder {
+pub async fn serve_asset(
+    req: actix_web::HttpRequest,
+    query: web::Query<ServeAssetQuery>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
     let filename: String = req.match_info().query("filename").into();
     info!("Executing serve_asset handler for filename: {}", &filename);
 
@@ -439,13 +931,50 @@ pub async fn serve_asset(req: actix_web::HttpRequest, data: web::Data<AppState>)
     match data.get_all_assets().await {
         Ok(assets) => {
             if let Some(asset) = assets.iter().find(|a| a.filename == filename) {
-                info!("Asset found for filename: {}. Redirecting to Supabase storage.", &filename);
-                let supabase_url = data.storage.get_asset_url(&asset.filename);
-                return HttpResponse::TemporaryRedirect()
-                    .append_header(("Location", supabase_url))
-                    .finish();
+                if let Some(refusal) = private_asset_refusal(&req, &data, &asset.id).await? {
+                    return Ok(refusal);
+                }
+                let inline = query.inline(asset);
+                let download_name = download_filename(asset, query.filename.as_deref());
+
+                if let Some(path) = data.storage.local_path(&asset.filename) {
+                    info!(
+                        "Asset found for filename: {}. Streaming from local storage.",
+                        &filename
+                    );
+                    let file = actix_files::NamedFile::open_async(&path)
+                        .await
+                        .map_err(|e| {
+                            error!("Failed to open local asset '{}': {}", path.display(), e);
+                            ApiError::NotFound(format!("Asset '{}' not found", filename))
+                        })?;
+                    return Ok(file
+                        .set_content_disposition(content_disposition(inline, &download_name))
+                        .into_response(&req));
+                }
+
+                info!(
+                    "Asset found for filename: {}. Redirecting to storage.",
+                    &filename
+                );
+                let url = if inline {
+                    data.storage
+                        .get_signed_url(&asset.filename, SIGNED_URL_TTL)
+                        .await
+                } else {
+                    data.storage
+                        .get_download_url(&asset.filename, SIGNED_URL_TTL, &download_name)
+                        .await
+                }
+                .map_err(ApiError::Storage)?;
+                return Ok(HttpResponse::TemporaryRedirect()
+                    .append_header(("Location", url))
+                    .finish());
             }
         }
+        Err(e) if e.is_unavailable() => {
+            return Err(ApiError::database("Failed to look up asset")(e));
+        }
         Err(e) => {
             error!(
                 "Database error while trying to serve asset '{}': {}",
@@ -455,13 +984,14 @@ pub async fn serve_asset(req: actix_web::HttpRequest, data: web::Data<AppState>)
     }
 
     error!("Asset not found for serving: {}", &filename);
-    HttpResponse::NotFound().json(ErrorResponse::not_found(&format!(
+    Err(ApiError::NotFound(format!(
         "Asset '{}' not found",
         filename
     )))
 }
 
 #[utoipa::path(
+    operation_id = "createFolder",
     context_path = "/api",
     tag = "Asset Service",
     post,
@@ -469,151 +999,421 @@ pub async fn serve_asset(req: actix_web::HttpRequest, data: web::Data<AppState>)
     request_body(content = inline(CreateFolderRequest), content_type = "application/json"),
     responses(
         (status = 201, description = "Folder created successfully"),
-        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 400, description = "Invalid folder name; the message names the rule broken", body = ErrorResponse),
+        (status = 409, description = "A folder with this name already exists", body = ErrorResponse),
         (status = 500, description = "Internal Server Error", body = ErrorResponse)
     )
 )]
 pub async fn create_folder_handler(
     req: Json<CreateFolderRequest>,
     data: web::Data<AppState>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     info!(
         "Executing create_folder_handler for folder: {}",
         &req.folder_name
     );
 
-    if req.folder_name.is_empty() {
-        error!("Folder name cannot be empty.");
-        return HttpResponse::BadRequest()
-            .json(ErrorResponse::bad_request("Folder name cannot be empty"));
+    let folder_name = normalize_folder_name(&req.folder_name).map_err(|e| {
+        error!("Rejected folder name '{}': {}", &req.folder_name, e);
+        ApiError::BadRequest(e.to_string())
+    })?;
+    if folder_name == OTHERS_FOLDER {
+        return Err(ApiError::Conflict(format!(
+            "Folder '{}' already exists",
+            OTHERS_FOLDER
+        )));
+    }
+    if let Some(existing) = data
+        .find_folder_name(&folder_name)
+        .await
+        .map_err(ApiError::database("Failed to check existing folders"))?
+    {
+        return Err(ApiError::Conflict(format!(
+            "Folder '{}' already exists",
+            existing
+        )));
     }
 
     debug!(
         "Attempting to create folder '{}' in Supabase storage.",
-        &req.folder_name
+        &folder_name
     );
-    match data.storage.create_folder(&req.folder_name).await {
-        Ok(_) => {
-            info!("Folder '{}' created in Supabase storage.", &req.folder_name);
-            debug!(
-                "Attempting to insert empty folder record '{}' into database.",
-                &req.folder_name
-            );
-            if let Err(e) = data.insert_folder_contents(&req.folder_name, &vec![]).await {
-                error!("Failed to create folder record in db: {}", e);
-                return HttpResponse::InternalServerError().json(ErrorResponse::internal_error(
-                    "Failed to create folder record",
-                ));
-            }
-            info!(
-                "Folder record '{}' created successfully in database.",
-                &req.folder_name
-            );
-            HttpResponse::Created().finish()
-        }
-        Err(e) => {
+    data.storage
+        .create_folder(&folder_name)
+        .await
+        .map_err(|e| {
             error!(
                 "Failed to create folder '{}' in Supabase storage: {}",
-                &req.folder_name, e
+                &folder_name, e
             );
-            HttpResponse::BadRequest().json(ErrorResponse::bad_request(&e.to_string()))
-        }
-    }
+            ApiError::BadRequest(e)
+        })?;
+
+    info!("Folder '{}' created in Supabase storage.", &folder_name);
+    debug!(
+        "Attempting to insert empty folder record '{}' into database.",
+        &folder_name
+    );
+    data.insert_folder_contents(&folder_name, &vec![])
+        .await
+        .map_err(ApiError::database("Failed to create folder record"))?;
+    info!(
+        "Folder record '{}' created successfully in database.",
+        &folder_name
+    );
+    Ok(HttpResponse::Created().finish())
+}
+
+/// What `GET /api/assets/folders/{folder_name}` returns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FolderInclude {
+    /// The full assets
+    #[default]
+    Assets,
+    /// The ids of the assets
+    Ids,
+    /// The folder name and the number of assets
+    Count,
 }
 
+#[derive(Debug, serde::Deserialize, IntoParams)]
+pub struct FolderListQuery {
+    /// `assets` (default), `ids` or `count`
+    pub include: Option<FolderInclude>,
+}
+
+/// Size of a folder, returned for `include=count`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FolderAssetCount {
+    #[schema(example = "galeri")]
+    pub name: String,
+    #[schema(example = 12)]
+    pub asset_count: i64,
+}
+
+/// Contents of a folder in the projection asked for with `include`
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum FolderListing {
+    Assets(Vec<Asset>),
+    Ids(Vec<Uuid>),
+    Count(FolderAssetCount),
+}
+
+/// Assets of one folder. Internal folders, such as those of posts and
+/// submissions, need an admin token.
 #[utoipa::path(
+    operation_id = "listFolder",
     context_path = "/api",
     tag = "Asset Service",
     get,
     path = "/assets/folders/{folder_name}",
     params(
-        ("folder_name" = String, Path, description = "Name of the folder to list asset details from")
+        ("folder_name" = String, Path, description = "Name of the folder to list asset details from"),
+        FolderListQuery
     ),
+    security((), ("bearer_auth" = [])),
     responses(
-        (status = 200, description = "A list of assets in the folder", body = Vec<Asset>),
+        (status = 200, description = "The assets in the folder, their ids with `include=ids`, or the folder name and asset count with `include=count`", body = FolderListing),
+        (status = 400, description = "Unknown `include` value", body = ErrorResponse),
+        (status = 401, response = crate::UnauthorizedResponse),
         (status = 404, description = "Folder not found", body = ErrorResponse),
         (status = 500, description = "Internal Server Error", body = ErrorResponse)
     )
 )]
 pub async fn list_folder_handler(
+    req: actix_web::HttpRequest,
     folder_name: Path<String>,
+    query: web::Query<FolderListQuery>,
     data: web::Data<AppState>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let folder_name = folder_name.into_inner();
-    info!("Executing list_folder_handler for folder: {}", &folder_name);
+    let include = query.include.unwrap_or_default();
+    info!(
+        "Executing list_folder_handler for folder: {} ({:?})",
+        &folder_name, include
+    );
 
     if folder_name.is_empty() {
         error!("Folder name cannot be empty.");
-        return HttpResponse::BadRequest()
-            .json(ErrorResponse::bad_request("Folder name cannot be empty"));
+        return Err(ApiError::BadRequest(
+            "Folder name cannot be empty".to_string(),
+        ));
     }
-
-    debug!(
-        "Attempting to get asset IDs for folder '{}' from database.",
-        &folder_name
-    );
-    match data.get_folder_contents(&folder_name).await {
-        Ok(Some(asset_ids)) => {
-            let mut assets = Vec::new();
-            for asset_id in asset_ids {
-                match data.get_asset_by_id(&asset_id).await {
-                    Ok(Some(asset)) => assets.push(asset),
-                    Ok(None) => {
-                        error!("Asset with ID {} found in folder but not in assets table.", asset_id);
-                    }
-                    Err(e) => {
-                        error!("Failed to fetch asset {}: {}", asset_id, e);
-                        return HttpResponse::InternalServerError().json(
-                            ErrorResponse::internal_error("Failed to retrieve asset details"),
-                        );
-                    }
-                }
-            }
-            info!(
-                "Successfully fetched {} assets for folder '{}'",
-                assets.len(),
-                &folder_name
-            );
-            HttpResponse::Ok().json(assets)
-        }
-        Ok(None) => {
-            error!("Folder not found in database: {}", &folder_name);
-            HttpResponse::NotFound().json(ErrorResponse::not_found(&format!(
-                "Folder '{}' not found",
-                folder_name
-            )))
-        }
-        Err(e) => {
-            error!(
-                "Failed to get folder contents for '{}': {}",
-                &folder_name, e
-            );
-            HttpResponse::InternalServerError()
-                .json(ErrorResponse::internal_error("Failed to retrieve folder contents"))
+    if is_internal_folder(&folder_name) {
+        if let Err(e) = validate_request_token(&req) {
+            return Ok(e.error_response());
         }
     }
+    let not_found = || {
+        error!("Folder not found in database: {}", &folder_name);
+        ApiError::NotFound(format!("Folder '{}' not found", folder_name))
+    };
+    let others = folder_name == OTHERS_FOLDER;
+
+    let listing = match include {
+        FolderInclude::Count => {
+            let asset_count = if others {
+                data.count_unlinked_assets()
+                    .await
+                    .map_err(ApiError::database("Failed to count unassigned assets"))?
+            } else {
+                data.count_folder_assets(&folder_name)
+                    .await
+                    .map_err(ApiError::database("Failed to count folder contents"))?
+                    .ok_or_else(not_found)?
+            };
+            FolderListing::Count(FolderAssetCount {
+                name: folder_name.clone(),
+                asset_count,
+            })
+        }
+        FolderInclude::Ids => FolderListing::Ids(if others {
+            data.get_unlinked_asset_ids()
+                .await
+                .map_err(ApiError::database("Failed to retrieve unassigned assets"))?
+        } else {
+            data.get_folder_contents(&folder_name)
+                .await
+                .map_err(ApiError::database("Failed to retrieve folder contents"))?
+                .ok_or_else(not_found)?
+        }),
+        FolderInclude::Assets => FolderListing::Assets(if others {
+            data.get_unlinked_assets()
+                .await
+                .map_err(ApiError::database("Failed to retrieve unassigned assets"))?
+        } else {
+            data.get_folder_assets(&folder_name)
+                .await
+                .map_err(ApiError::database("Failed to retrieve folder contents"))?
+                .ok_or_else(not_found)?
+        }),
+    };
+    Ok(HttpResponse::Ok().json(listing))
 }
 
-
-
 #[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
 pub struct UploadAssetRequest {
+    /// One or more files, each sent as its own `file` part. Every file
+    /// becomes a separate asset.
     #[allow(unused)]
-    pub file: Vec<u8>,
+    pub file: Vec<Vec<u8>>,
+    /// Posting every uploaded asset is attached to
     #[allow(unused)]
     pub posting_id: Option<Uuid>,
+    /// Comma-separated folders every uploaded asset is added to. Without
+    /// one the assets stay unlinked and are listed under `others`.
     #[allow(unused)]
     pub folders: Option<Vec<String>>,
+    /// Display name of the asset. Only used when a single file is sent;
+    /// otherwise each asset is named after its file.
     #[allow(unused)]
     pub name: Option<String>,
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DissolveOthersResponse {
+    /// Asset links removed along with the folder row
+    pub unlinked: u64,
+}
+
+/// Remove the `folders` row named "others" left by older versions, which
+/// put folderless uploads there. Its assets keep any other folders; the
+/// rest join the virtual "others" group. Safe to call again. (protected)
+#[utoipa::path(
+    operation_id = "dissolveOthersFolder",
+    post,
+    path = "/api/admin/folders/others/dissolve",
+    tag = "Admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The folder row is gone", body = DissolveOthersResponse),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+pub async fn dissolve_others_folder(
+    req: actix_web::HttpRequest,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    if let Err(e) = validate_request_token(&req) {
+        return Ok(e.error_response());
+    }
+
+    let unlinked = data
+        .dissolve_folder(OTHERS_FOLDER)
+        .await
+        .map_err(ApiError::database("Failed to dissolve the others folder"))?;
+    info!(
+        "Dissolved the '{}' folder row, {} asset links removed",
+        OTHERS_FOLDER, unlinked
+    );
+    Ok(HttpResponse::Ok().json(DissolveOthersResponse { unlinked }))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RelativizeUrlsResponse {
+    /// Assets whose stored URL was rewritten
+    pub updated: u64,
+}
+
+/// Rewrite asset URLs stored as absolute links by older versions to the
+/// relative `/assets/serve/...` form uploads use now. Responses already
+/// show both forms either way. Safe to call again. (protected)
+#[utoipa::path(
+    operation_id = "relativizeAssetUrls",
+    post,
+    path = "/api/admin/assets/relativize-urls",
+    tag = "Admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Stored URLs are relative", body = RelativizeUrlsResponse),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+pub async fn relativize_asset_urls(
+    req: actix_web::HttpRequest,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    if let Err(e) = validate_request_token(&req) {
+        return Ok(e.error_response());
+    }
+
+    let updated = data
+        .relativize_asset_urls()
+        .await
+        .map_err(ApiError::database("Failed to rewrite asset URLs"))?;
+    info!("Rewrote {} absolute asset URLs to relative paths", updated);
+    Ok(HttpResponse::Ok().json(RelativizeUrlsResponse { updated }))
+}
+
+/// Assets read per query by the dimension backfill
+const BACKFILL_BATCH_SIZE: i64 = 50;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BackfillDimensionsResponse {
+    /// Image assets whose width and height were recorded
+    pub updated: u64,
+    /// Image assets whose file could not be read or decoded
+    pub failed: u64,
+}
+
+/// Record width and height of image assets uploaded before sizes were
+/// stored, reading only the header of each file. Safe to call again;
+/// assets that failed are retried. (protected)
+#[utoipa::path(
+    operation_id = "backfillAssetDimensions",
+    post,
+    path = "/api/admin/assets/backfill-dimensions",
+    tag = "Admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Sizes recorded", body = BackfillDimensionsResponse),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+pub async fn backfill_asset_dimensions(
+    req: actix_web::HttpRequest,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    if let Err(e) = validate_request_token(&req) {
+        return Ok(e.error_response());
+    }
+
+    let mut response = BackfillDimensionsResponse {
+        updated: 0,
+        failed: 0,
+    };
+    let mut after = Uuid::nil();
+    loop {
+        let batch = data
+            .get_assets_without_dimensions(after, BACKFILL_BATCH_SIZE)
+            .await
+            .map_err(ApiError::database("Failed to retrieve assets"))?;
+        let Some(last) = batch.last() else {
+            break;
+        };
+        after = last.id;
+
+        for asset in batch.iter().filter(|asset| is_image(&asset.filename)) {
+            match read_stored_dimensions(&data, &asset.filename).await {
+                Ok((width, height)) => {
+                    data.set_asset_dimensions(&asset.id, width, height)
+                        .await
+                        .map_err(ApiError::database("Failed to save asset dimensions"))?;
+                    response.updated += 1;
+                }
+                Err(e) => {
+                    warn!("No dimensions for asset {}: {}", asset.id, e);
+                    response.failed += 1;
+                }
+            }
+        }
+        if (batch.len() as i64) < BACKFILL_BATCH_SIZE {
+            break;
+        }
+    }
+    info!(
+        "Recorded dimensions of {} image assets, {} failed",
+        response.updated, response.failed
+    );
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Size of the stored image `filename`, read from its header, or from the
+/// whole file when the header is longer than [`HEADER_BYTES`]
+async fn read_stored_dimensions(data: &AppState, filename: &str) -> Result<(i32, i32), String> {
+    let head = data.storage.download_head(filename, HEADER_BYTES).await?;
+    if let Some(dimensions) = image_dimensions(filename, &head) {
+        return Ok(dimensions);
+    }
+    if head.len() < HEADER_BYTES {
+        return Err("unrecognized image header".to_string());
+    }
+    let file = data.storage.download_file(filename).await?;
+    image_dimensions(filename, &file).ok_or_else(|| "unrecognized image header".to_string())
+}
+
+/// Rebuild the asset links of post folders from the objects stored under
+/// their `posts/{id}/` prefixes, matching object names against asset
+/// filenames. Only folders holding at least one known asset are rebuilt.
+/// Any admin token is accepted, as there are no roles yet. (protected)
+#[utoipa::path(
+    operation_id = "rebuildAssetFolders",
+    post,
+    path = "/api/admin/assets/rebuild-folders",
+    tag = "Admin",
+    security(("bearer_auth" = [])),
+    params(RebuildFoldersQuery),
+    responses(
+        (status = 200, description = "Folder links rebuilt, or the changes of a dry run", body = RebuildFoldersResponse),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+pub async fn rebuild_asset_folders(
+    req: actix_web::HttpRequest,
+    query: web::Query<RebuildFoldersQuery>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    if let Err(e) = validate_request_token(&req) {
+        return Ok(e.error_response());
+    }
+
+    let response = data
+        .rebuild_post_folders(query.dry_run.unwrap_or(false))
+        .await?;
+    Ok(HttpResponse::Ok().json(response))
+}
+
 #[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
 pub struct CreateFolderRequest {
     pub folder_name: String,
 }
 
-
 #[allow(dead_code)]
 #[derive(serde::Deserialize, utoipa::ToSchema)]
 pub struct CreateFolderForm {
@@ -621,21 +1421,24 @@ pub struct CreateFolderForm {
 }
 
 #[utoipa::path(
+    operation_id = "getAssetsByIds",
     context_path = "/api",
     tag = "Asset Service",
     post,
     path = "/assets/by-ids",
     request_body(content = inline(GetAssetsByIdsRequest), content_type = "application/json"),
+    security((), ("bearer_auth" = [])),
     responses(
-        (status = 200, description = "List of assets found", body = Vec<Asset>),
+        (status = 200, description = "List of assets found. Submission attachments only with an admin token", body = Vec<Asset>),
         (status = 400, description = "Invalid request", body = ErrorResponse),
         (status = 500, description = "Internal Server Error", body = ErrorResponse)
     )
 )]
 pub async fn get_assets_by_ids(
+    http_req: actix_web::HttpRequest,
     req: web::Json<GetAssetsByIdsRequest>,
     data: web::Data<AppState>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     info!("Executing get_assets_by_ids handler");
     debug!("Request received with {} IDs: {:?}", req.ids.len(), req.ids);
 
@@ -649,23 +1452,33 @@ pub async fn get_assets_by_ids(
     }
 
     debug!("Attempting to fetch assets for provided IDs from database.");
-    match data.get_assets_by_ids(&req.ids).await {
-        Ok(assets) => {
-            info!("Successfully fetched {} assets out of {} requested IDs", assets.len(), req.ids.len());
-
-            for (index, asset) in assets.iter().enumerate() {
-                debug!("Fetched asset[{}]: ID={}, filename='{}'", index, asset.id, asset.filename);
-            }
+    let mut assets = data.get_assets_by_ids(&req.ids).await.map_err(|e| {
+        error!("Error details - Requested IDs: {:?}, Error: {}", req.ids, e);
+        ApiError::database("Failed to retrieve assets")(e)
+    })?;
+    // Submission attachments are left out as if missing, unless an admin asks
+    if validate_request_token(&http_req).is_err() {
+        let found: Vec<Uuid> = assets.iter().map(|asset| asset.id).collect();
+        let private = data
+            .get_private_asset_ids(&found)
+            .await
+            .map_err(ApiError::database("Failed to retrieve assets"))?;
+        assets.retain(|asset| !private.contains(&asset.id));
+    }
 
-            HttpResponse::Ok().json(assets)
-        }
-        Err(e) => {
-            error!("Failed to fetch assets by IDs: {}", e);
-            error!("Error details - Requested IDs: {:?}, Error: {}", req.ids, e);
-            HttpResponse::InternalServerError()
-                .json(ErrorResponse::internal_error("Failed to retrieve assets"))
-        }
+    info!(
+        "Successfully fetched {} assets out of {} requested IDs",
+        assets.len(),
+        req.ids.len()
+    );
+    for (index, asset) in assets.iter().enumerate() {
+        debug!(
+            "Fetched asset[{}]: ID={}, filename='{}'",
+            index, asset.id, asset.filename
+        );
     }
+
+    Ok(HttpResponse::Ok().json(assets))
 }
 
 #[derive(serde::Deserialize, serde::Serialize, utoipa::ToSchema)]
@@ -674,6 +1487,7 @@ pub struct GetAssetsByIdsRequest {
 }
 
 #[utoipa::path(
+    operation_id = "uploadAssetToPost",
     context_path = "/api",
     tag = "Asset Service",
     post,
@@ -693,154 +1507,193 @@ pub async fn upload_asset_to_post(
     path: Path<Uuid>,
     payload: Multipart,
     data: web::Data<AppState>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let post_id = path.into_inner();
-    info!("Executing upload_asset_to_post handler for post ID: {}", post_id);
-
-    match data.get_post_by_id(&post_id).await {
-        Ok(Some(post)) => {
-            let folder_id = match &post.folder_id {
-                Some(folder_id) => folder_id.clone(),
-                None => {
-                    let new_folder_id = format!("posts/{}", post_id);
-
-                    if let Err(e) = data.storage.create_folder(&new_folder_id).await {
-                        error!("Failed to create folder for post {}: {}", post_id, e);
-                        return HttpResponse::InternalServerError()
-                            .json(ErrorResponse::internal_error("Failed to create post folder"));
-                    }
-
-                    let mut updated_post = post.clone();
-                    updated_post.folder_id = Some(new_folder_id.clone());
-                    if let Err(e) = data.update_post(&updated_post).await {
-                        error!("Failed to update post {} with folder ID: {}", post_id, e);
-                        return HttpResponse::InternalServerError()
-                            .json(ErrorResponse::internal_error("Failed to update post with folder ID"));
-                    }
-
-                    new_folder_id
-                }
-            };
+    info!(
+        "Executing upload_asset_to_post handler for post ID: {}",
+        post_id
+    );
 
-            // Process multiple file uploads
-            let mut uploaded_assets = Vec::new();
-            let mut errors = Vec::new();
-
-            let mut payload = payload;
-            while let Some(item) = payload.next().await {
-                match item {
-                    Ok(mut field) => {
-                        let content_disposition = field.content_disposition();
-                        if let Some(content_disposition) = content_disposition {
-                            let field_name = content_disposition.get_name();
-                            if let Some(field_name) = field_name {
-                                if field_name.starts_with("file") {
-                                    let file_name = content_disposition.get_filename()
-                                        .map(|s| s.to_string())
-                                        .unwrap_or_else(|| format!("unnamed_file_{}.dat", uploaded_assets.len()));
-
-                                    let ext = StdPath::new(&file_name)
-                                        .extension()
-                                        .and_then(std::ffi::OsStr::to_str)
-                                        .unwrap_or("dat");
-
-                                    let unique_filename = format!("{}_{}.{}", Uuid::new_v4(), file_name.replace(".", "_"), ext);
-
-                                    let mut file_data = Vec::new();
-                                    while let Some(chunk_result) = field.next().await {
-                                        match chunk_result {
-                                            Ok(data) => file_data.extend_from_slice(&data),
-                                            Err(e) => {
-                                                error!("Failed to read chunk: {}", e);
-                                                errors.push(format!("Failed to read chunk: {}", e));
-                                                break;
-                                            }
-                                        }
+    let post = data
+        .get_post_by_id(&post_id)
+        .await
+        .map_err(ApiError::database("Failed to retrieve post"))?
+        .ok_or_else(|| {
+            error!("Post not found for ID: {}", post_id);
+            ApiError::NotFound(format!("Post with ID {} not found", post_id))
+        })?;
+
+    let folder_id = match &post.folder_id {
+        Some(folder_id) => folder_id.clone(),
+        None => {
+            let new_folder_id = post_folder_name(post_id);
+
+            data.storage
+                .create_folder(&new_folder_id)
+                .await
+                .map_err(ApiError::storage("Failed to create post folder"))?;
+
+            let mut updated_post = post.clone();
+            updated_post.folder_id = Some(new_folder_id.clone());
+            data.update_post(&updated_post)
+                .await
+                .map_err(ApiError::database("Failed to update post with folder ID"))?;
+
+            new_folder_id
+        }
+    };
+
+    // Process multiple file uploads
+    let mut uploaded_assets = Vec::new();
+    let mut errors = Vec::new();
+
+    let mut payload = payload;
+    while let Some(item) = payload.next().await {
+        match item {
+            Ok(mut field) => {
+                let content_disposition = field.content_disposition();
+                if let Some(content_disposition) = content_disposition {
+                    let field_name = content_disposition.get_name();
+                    if let Some(field_name) = field_name {
+                        if field_name.starts_with("file") {
+                            let file_name = content_disposition
+                                .get_filename()
+                                .map(|s| s.to_string())
+                                .unwrap_or_else(|| {
+                                    format!("unnamed_file_{}.dat", uploaded_assets.len())
+                                });
+
+                            let base_name = file_name.replace(".", "_");
+
+                            let mut file_data = Vec::new();
+                            while let Some(chunk_result) = field.next().await {
+                                match chunk_result {
+                                    Ok(data) => file_data.extend_from_slice(&data),
+                                    Err(e) => {
+                                        error!("Failed to read chunk: {}", e);
+                                        errors.push(format!("Failed to read chunk: {}", e));
+                                        break;
                                     }
+                                }
+                            }
 
-                                    let upload_result = data.storage.upload_file(&unique_filename, &file_data).await;
+                            if let Err(e) = data.upload.check_size(&file_name, file_data.len()) {
+                                error!("Rejected upload: {}", e);
+                                errors.push(e);
+                                continue;
+                            }
 
-                                    if let Err(e) = upload_result {
-                                        error!("Failed to upload file to Supabase: {}", e);
-                                        errors.push(format!("Failed to upload file: {}", e));
-                                        continue;
-                                    }
+                            let jpeg = convert_heic(&data, &file_name, &file_data).await;
+                            let ext = match jpeg {
+                                Some(_) => JPEG_EXTENSION,
+                                None => StdPath::new(&file_name)
+                                    .extension()
+                                    .and_then(std::ffi::OsStr::to_str)
+                                    .unwrap_or("dat"),
+                            };
+
+                            let stored_data = jpeg.as_deref().unwrap_or(&file_data);
+                            let stripped = strip_photo_metadata(&data, stored_data).await;
+                            let stored_data = stripped.as_deref().unwrap_or(stored_data);
+                            let upload_result =
+                                upload_with_unique_name(data.storage.as_ref(), stored_data, |_| {
+                                    format!("{}_{}.{}", Uuid::new_v4(), base_name, ext)
+                                })
+                                .await;
+
+                            let unique_filename = match upload_result {
+                                Ok(name) => name,
+                                Err(e) => {
+                                    error!("Failed to upload file to Supabase: {}", e);
+                                    errors.push(format!("Failed to upload file: {}", e));
+                                    continue;
+                                }
+                            };
 
-                                    info!("File saved successfully with filename: {}", unique_filename);
+                            info!("File saved successfully with filename: {}", unique_filename);
+                            if jpeg.is_some() {
+                                keep_heic_original(&data, &unique_filename, &file_data).await;
+                            }
 
-                                    let new_asset = Asset::new(
-                                        file_name.clone(),
-                                        unique_filename.clone(),
-                                        format!("/assets/serve/{}", unique_filename),
-                                        None,
-                                    );
+                            let mut new_asset = Asset::new(
+                                match jpeg {
+                                    Some(_) => heic::jpeg_filename(&file_name),
+                                    None => file_name.clone(),
+                                },
+                                unique_filename.clone(),
+                                serve_path(&unique_filename),
+                                None,
+                            );
+                            new_asset.read_dimensions(stored_data);
 
-                                    debug!("Attempting to insert new asset into 'assets' table.");
-                                    if let Err(e) = data.insert_asset(&new_asset).await {
-                                        error!("Failed to insert asset into db: {}", e);
-                                        errors.push(format!("Failed to insert asset into db: {}", e));
-                                        continue;
-                                    }
-                                    info!("Asset {:?} created and stored in database.", new_asset.id);
-
-                                    // Associate the asset with the post folder
-                                    let folder_contents_result = data.get_folder_contents(&folder_id).await;
-                                    let mut asset_ids = match folder_contents_result {
-                                        Ok(Some(ids)) => ids,
-                                        Ok(None) => Vec::new(),
-                                        Err(e) => {
-                                            error!("Database error when getting folder contents for post: {}", e);
-                                            errors.push(format!("Failed to retrieve folder contents for post: {}", e));
-                                            continue;
-                                        }
-                                    };
-                                    asset_ids.push(new_asset.id);
-                                    if let Err(e) = data.insert_folder_contents(&folder_id, &asset_ids).await {
-                                        error!("Failed to associate asset with post folder: {}", e);
-                                        errors.push(format!("Failed to associate asset with post folder: {}", e));
-                                    } else {
-                                        info!(
-                                            "Asset {:?} successfully associated with post folder '{}'",
-                                            new_asset.id, folder_id
-                                        );
-                                    }
+                            debug!("Attempting to insert new asset into 'assets' table.");
+                            if let Err(e) = data.insert_asset(&new_asset).await {
+                                error!("Failed to insert asset into db: {}", e);
+                                errors.push(format!("Failed to insert asset into db: {}", e));
+                                continue;
+                            }
+                            info!("Asset {:?} created and stored in database.", new_asset.id);
+                            data.publish_change(
+                                WebhookEvent::AssetUploaded,
+                                new_asset.id,
+                                &new_asset,
+                            );
 
-                                    uploaded_assets.push(new_asset);
+                            // Associate the asset with the post folder
+                            let folder_contents_result = data.get_folder_contents(&folder_id).await;
+                            let mut asset_ids = match folder_contents_result {
+                                Ok(Some(ids)) => ids,
+                                Ok(None) => Vec::new(),
+                                Err(e) => {
+                                    error!(
+                                        "Database error when getting folder contents for post: {}",
+                                        e
+                                    );
+                                    errors.push(format!(
+                                        "Failed to retrieve folder contents for post: {}",
+                                        e
+                                    ));
+                                    continue;
                                 }
+                            };
+                            asset_ids.push(new_asset.id);
+                            if let Err(e) =
+                                data.insert_folder_contents(&folder_id, &asset_ids).await
+                            {
+                                error!("Failed to associate asset with post folder: {}", e);
+                                errors.push(format!(
+                                    "Failed to associate asset with post folder: {}",
+                                    e
+                                ));
+                            } else {
+                                info!(
+                                    "Asset {:?} successfully associated with post folder '{}'",
+                                    new_asset.id, folder_id
+                                );
                             }
+
+                            uploaded_assets.push(new_asset);
                         }
                     }
-                    Err(e) => {
-                        error!("Failed to process multipart field: {}", e);
-                        errors.push(format!("Failed to process multipart field: {}", e));
-                    }
                 }
             }
-
-            if !errors.is_empty() {
-                error!("Errors occurred during upload: {:?}", errors);
+            Err(e) => {
+                error!("Failed to process multipart field: {}", e);
+                errors.push(format!("Failed to process multipart field: {}", e));
             }
+        }
+    }
 
-            if uploaded_assets.is_empty() {
-                error!("No files were uploaded for post ID: {}", post_id);
-                return HttpResponse::BadRequest()
-                    .json(ErrorResponse::bad_request("No files were uploaded"));
-            }
+    if !errors.is_empty() {
+        error!("Errors occurred during upload: {:?}", errors);
+    }
 
-            HttpResponse::Created().json(uploaded_assets[0].clone())
-        }
-        Ok(None) => {
-            error!("Post not found for ID: {}", post_id);
-            HttpResponse::NotFound().json(ErrorResponse::not_found(&format!(
-                "Post with ID {} not found", post_id
-            )))
-        }
-        Err(e) => {
-            error!("Database error when fetching post {}: {}", post_id, e);
-            HttpResponse::InternalServerError()
-                .json(ErrorResponse::internal_error("Failed to retrieve post"))
-        }
+    if uploaded_assets.is_empty() {
+        error!("No files were uploaded for post ID: {}", post_id);
+        return Err(ApiError::BadRequest("No files were uploaded".to_string()));
     }
+
+    Ok(HttpResponse::Created().json(uploaded_assets[0].clone()))
 }
 
 #[cfg(test)]
@@ -854,4 +1707,4 @@ mod tests {
 
         assert_eq!(request.ids.len(), 2);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,169 @@
+//! JPEG conversion of HEIC/HEIF uploads.
+//!
+//! iPhones save photos as HEIC, which most browsers cannot display. Uploads
+//! recognized as HEIC by extension or by their `ftyp` box are converted to
+//! JPEG with an external converter (`heif-convert` from libheif by default)
+//! before they are stored. A file the converter rejects is stored as
+//! uploaded, with a warning.
+
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+
+use crate::config::parse_bool;
+
+const DEFAULT_COMMAND: &str = "heif-convert";
+
+/// Longest a single conversion may take before it is abandoned
+pub const CONVERT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Extension of converted files
+pub const JPEG_EXTENSION: &str = "jpg";
+
+/// Major brands of the HEIF image formats, see ISO/IEC 23008-12
+const HEIC_BRANDS: &[&[u8; 4]] = &[b"heic", b"heix", b"heim", b"heis", b"hevc", b"hevx"];
+/// Brands of HEIF files that may hold codecs other than HEVC, e.g. AVIF
+const GENERIC_HEIF_BRANDS: &[&[u8; 4]] = &[b"mif1", b"msf1"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeicConfig {
+    /// Convert HEIC/HEIF uploads to JPEG
+    pub convert: bool,
+    /// Store the uploaded HEIC file next to the JPEG as well
+    pub keep_original: bool,
+    /// Converter, run as `<command> <input> <output>`
+    pub command: String,
+}
+
+impl Default for HeicConfig {
+    fn default() -> Self {
+        Self {
+            convert: true,
+            keep_original: false,
+            command: DEFAULT_COMMAND.to_string(),
+        }
+    }
+}
+
+impl HeicConfig {
+    /// Load using a custom variable lookup
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let get = |key: &str| {
+            lookup(key)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let defaults = Self::default();
+
+        Ok(Self {
+            convert: match get("HEIC_CONVERT") {
+                Some(v) => parse_bool("HEIC_CONVERT", &v)?,
+                None => defaults.convert,
+            },
+            keep_original: match get("HEIC_KEEP_ORIGINAL") {
+                Some(v) => parse_bool("HEIC_KEEP_ORIGINAL", &v)?,
+                None => defaults.keep_original,
+            },
+            command: get("HEIC_CONVERT_COMMAND").unwrap_or(defaults.command),
+        })
+    }
+}
+
+/// Whether `data`, uploaded as `filename`, is a HEIC/HEIF image
+pub fn is_heic(filename: &str, data: &[u8]) -> bool {
+    let by_extension = Path::new(filename)
+        .extension()
+        .and_then(std::ffi::OsStr::to_str)
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("heic") || ext.eq_ignore_ascii_case("heif"));
+    by_extension || has_heic_brand(data)
+}
+
+/// Whether `data` starts with an ISO BMFF `ftyp` box naming a HEIC brand
+fn has_heic_brand(data: &[u8]) -> bool {
+    if data.len() < 16 || &data[4..8] != b"ftyp" {
+        return false;
+    }
+    let box_size = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as usize;
+    let major = &data[8..12];
+    if HEIC_BRANDS.iter().any(|brand| major == &brand[..]) {
+        return true;
+    }
+    if !GENERIC_HEIF_BRANDS.iter().any(|brand| major == &brand[..]) {
+        return false;
+    }
+    // Generic HEIF: look for a HEVC brand among the compatible ones, which
+    // follow the major brand and minor version
+    data[16..box_size.clamp(16, data.len())]
+        .chunks_exact(4)
+        .any(|brand| HEIC_BRANDS.iter().any(|heic| brand == &heic[..]))
+}
+
+/// Display name of the converted upload `filename`
+pub fn jpeg_filename(filename: &str) -> String {
+    let stem = Path::new(filename)
+        .file_stem()
+        .and_then(std::ffi::OsStr::to_str)
+        .unwrap_or(filename);
+    format!("{}.{}", stem, JPEG_EXTENSION)
+}
+
+/// Stored name of the original kept next to the stored file `filename`,
+/// when `filename` is a converted upload (`<uuid>_<name>_heic.jpg`)
+pub fn kept_original(filename: &str) -> Option<String> {
+    let stem = filename.strip_suffix(&format!(".{}", JPEG_EXTENSION))?;
+    let lower = stem.to_ascii_lowercase();
+    (lower.ends_with("_heic") || lower.ends_with("_heif")).then(|| format!("{}.heic", stem))
+}
+
+/// Convert the HEIC image `data` to JPEG with the configured command
+pub async fn convert_to_jpeg(config: &HeicConfig, data: &[u8]) -> Result<Vec<u8>, String> {
+    let dir = tokio::task::spawn_blocking(|| tempfile::tempdir_in(crate::maintenance::temp_dir()))
+        .await
+        .map_err(|e| format!("Temp dir task failed: {}", e))?
+        .map_err(|e| format!("Failed to create temp dir: {}", e))?;
+    let input = dir.path().join("input.heic");
+    let output = dir.path().join("output.jpg");
+    tokio::fs::write(&input, data)
+        .await
+        .map_err(|e| format!("Failed to write temp file: {}", e))?;
+
+    let run = tokio::process::Command::new(&config.command)
+        .arg(&input)
+        .arg(&output)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output();
+    let result = tokio::time::timeout(CONVERT_TIMEOUT, run)
+        .await
+        .map_err(|_| {
+            format!(
+                "'{}' did not finish within {}s",
+                config.command,
+                CONVERT_TIMEOUT.as_secs()
+            )
+        })?
+        .map_err(|e| format!("Failed to run '{}': {}", config.command, e))?;
+    if !result.status.success() {
+        return Err(format!(
+            "'{}' failed with {}: {}",
+            config.command,
+            result.status,
+            String::from_utf8_lossy(&result.stderr).trim()
+        ));
+    }
+
+    let jpeg = tokio::fs::read(&output)
+        .await
+        .map_err(|e| format!("'{}' wrote no output: {}", config.command, e))?;
+    // Removing the directory is blocking too
+    tokio::task::spawn_blocking(move || drop(dir));
+    if !jpeg.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Err(format!("'{}' did not write a JPEG", config.command));
+    }
+    Ok(jpeg)
+}
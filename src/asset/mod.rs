@@ -1,2 +1,11 @@
+pub mod dimensions;
+pub mod download;
+pub mod exif_cleanup;
+pub mod folder_name;
+pub mod folder_rebuild;
+pub mod gallery;
 pub mod handlers;
+pub mod heic;
 pub mod models;
+pub mod public_url;
+pub mod upload_session;
@@ -1,24 +1,97 @@
-use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use serde::{Deserialize, Serialize, Serializer};
+use utoipa::openapi::{schema::Schema, RefOr};
+use utoipa::{PartialSchema, ToSchema};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
-#[derive(Debug, Serialize, Deserialize, Clone, ToSchema, sqlx::FromRow)]
+/// Serialized with the relative `url` and a computed `public_url`, see
+/// `crate::asset::public_url`
+#[derive(Debug, Deserialize, Clone, sqlx::FromRow)]
 pub struct Asset {
-    #[schema(example = "a1b2c3d4-e5f6-7890-1234-567890abcdef")]
     pub id: Uuid,
-    #[schema(example = "My Cool Image")]
     pub name: String,
-    #[schema(example = "image.png")]
     pub filename: String,
-    #[schema(example = "https://example.com/assets/image.png")]
     pub url: String,
-    #[schema(example = "This is an example image asset.")]
     pub description: Option<String>,
+    /// Intrinsic size in pixels, None for files that are not images
+    pub width: Option<i32>,
+    pub height: Option<i32>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
 }
 
+/// An asset as it appears in API responses
+#[derive(Serialize, ToSchema)]
+#[schema(example = json!({
+    "id": "a1b2c3d4-e5f6-7890-1234-567890abcdef",
+    "name": "Kerja Bakti",
+    "filename": "kerja-bakti.jpg",
+    "url": "/assets/serve/kerja-bakti.jpg",
+    "public_url": "https://example.com/assets/serve/kerja-bakti.jpg",
+    "download_url": "https://example.com/assets/serve/kerja-bakti.jpg?download=true",
+    "description": "Warga membersihkan saluran air",
+    "width": 1920,
+    "height": 1080,
+    "created_at": "2025-01-15T08:30:00Z",
+    "updated_at": "2025-01-15T08:30:00Z"
+}))]
+struct AssetJson<'a> {
+    #[schema(example = "a1b2c3d4-e5f6-7890-1234-567890abcdef")]
+    id: Uuid,
+    #[schema(example = "My Cool Image")]
+    name: &'a str,
+    #[schema(example = "image.png")]
+    filename: &'a str,
+    /// Path of the file on this server
+    #[schema(example = "/assets/serve/image.png")]
+    url: String,
+    /// Absolute URL of the file. Built from `PUBLIC_BASE_URL`, or by the
+    /// storage backend; equals `url` when neither is configured.
+    #[schema(example = "https://example.com/assets/serve/image.png")]
+    public_url: String,
+    /// Link that saves the file under the asset name rather than the stored
+    /// filename
+    #[schema(example = "https://example.com/assets/serve/image.png?download=true")]
+    download_url: String,
+    #[schema(example = "This is an example image asset.")]
+    description: Option<&'a str>,
+    /// Intrinsic width in pixels, null for files that are not images
+    #[schema(example = 1920)]
+    width: Option<i32>,
+    /// Intrinsic height in pixels, null for files that are not images
+    #[schema(example = 1080)]
+    height: Option<i32>,
+    created_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+impl Serialize for Asset {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        AssetJson {
+            id: self.id,
+            name: &self.name,
+            filename: &self.filename,
+            url: self.relative_url(),
+            public_url: self.public_url(),
+            download_url: self.download_url(),
+            description: self.description.as_deref(),
+            width: self.width,
+            height: self.height,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl PartialSchema for Asset {
+    fn schema() -> RefOr<Schema> {
+        AssetJson::schema()
+    }
+}
+
+impl ToSchema for Asset {}
+
 impl Asset {
     pub fn new(name: String, filename: String, url: String, description: Option<String>) -> Self {
         Asset {
@@ -27,8 +100,39 @@ impl Asset {
             filename,
             url,
             description,
+            width: None,
+            height: None,
             created_at: Some(Utc::now()),
             updated_at: Some(Utc::now()),
         }
     }
 }
+
+/// Enough of an asset to show it on a post card
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct AssetSummary {
+    pub id: Uuid,
+    /// Absolute URL of the file
+    #[schema(example = "https://example.com/assets/serve/kerja-bakti.jpg")]
+    pub url: String,
+    /// Image to show on the card: the file itself for images, null for
+    /// other files
+    #[schema(example = "https://example.com/assets/serve/kerja-bakti.jpg")]
+    pub thumbnail_url: Option<String>,
+    #[schema(example = "image/jpeg")]
+    pub mime: String,
+}
+
+impl AssetSummary {
+    /// Summary of the asset `id` stored as `filename`
+    pub fn new(id: Uuid, filename: &str) -> Self {
+        let url = crate::asset::public_url::public_urls().resolve_filename(filename);
+        let mime = mime_guess::from_path(filename).first_or_octet_stream();
+        AssetSummary {
+            id,
+            thumbnail_url: (mime.type_() == mime_guess::mime::IMAGE).then(|| url.clone()),
+            url,
+            mime: mime.essence_str().to_string(),
+        }
+    }
+}
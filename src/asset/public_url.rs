@@ -0,0 +1,109 @@
+//! Absolute URLs for assets in API responses.
+//!
+//! `Asset::url` is stored as the path `serve_asset` answers on, but rows
+//! written by older versions hold absolute Supabase URLs. Responses carry
+//! the relative path as `url` and an absolute `public_url` built from
+//! `PUBLIC_BASE_URL`, or from the storage backend when
+//! `PUBLIC_URL_FROM_STORAGE` is set.
+
+use std::sync::{Arc, OnceLock};
+
+use super::models::Asset;
+use crate::storage::ObjectStorage;
+
+/// How `public_url` is built
+#[derive(Clone)]
+pub enum PublicUrls {
+    /// `PUBLIC_BASE_URL` followed by the relative path
+    Base(String),
+    /// The backend's URL for the stored object
+    Storage(Arc<dyn ObjectStorage + Send + Sync>),
+    /// No public origin configured; `public_url` repeats the relative path
+    Relative,
+}
+
+static PUBLIC_URLS: OnceLock<PublicUrls> = OnceLock::new();
+
+/// Set how responses build `public_url`. Only the first call has an effect.
+pub fn init_public_urls(urls: PublicUrls) {
+    let _ = PUBLIC_URLS.set(urls);
+}
+
+/// The setting from [`init_public_urls`], `Relative` before it is called
+pub fn public_urls() -> &'static PublicUrls {
+    PUBLIC_URLS.get_or_init(|| PublicUrls::Relative)
+}
+
+/// Path `serve_asset` answers on for `filename`
+pub fn serve_path(filename: &str) -> String {
+    format!("/assets/serve/{}", filename)
+}
+
+/// Whether `url` is a full URL rather than a path on this server
+pub fn is_absolute(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+impl PublicUrls {
+    pub fn from_config(
+        public_base_url: Option<&str>,
+        from_storage: bool,
+        storage: Arc<dyn ObjectStorage + Send + Sync>,
+    ) -> Self {
+        if from_storage {
+            return Self::Storage(storage);
+        }
+        match public_base_url {
+            Some(base) => Self::Base(base.trim_end_matches('/').to_string()),
+            None => Self::Relative,
+        }
+    }
+
+    /// Link to this server that saves `asset` under its name
+    pub fn download_url(&self, asset: &Asset) -> String {
+        let path = format!("{}?download=true", serve_path(&asset.filename));
+        match self {
+            Self::Base(base) => format!("{}{}", base, path),
+            Self::Storage(_) | Self::Relative => path,
+        }
+    }
+
+    /// Absolute URL of the stored file `filename`
+    pub fn resolve_filename(&self, filename: &str) -> String {
+        match self {
+            Self::Base(base) => format!("{}{}", base, serve_path(filename)),
+            Self::Storage(storage) => storage.get_asset_url(filename),
+            Self::Relative => serve_path(filename),
+        }
+    }
+
+    /// Absolute URL of `asset`
+    pub fn resolve(&self, asset: &Asset) -> String {
+        match self {
+            Self::Base(base) => format!("{}{}", base, asset.relative_url()),
+            Self::Storage(storage) => storage.get_asset_url(&asset.filename),
+            Self::Relative => asset.relative_url(),
+        }
+    }
+}
+
+impl Asset {
+    /// `url` as a path on this server, also for rows holding an absolute URL
+    pub fn relative_url(&self) -> String {
+        if is_absolute(&self.url) {
+            serve_path(&self.filename)
+        } else {
+            self.url.clone()
+        }
+    }
+
+    /// Absolute URL of the file, as configured with [`init_public_urls`]
+    pub fn public_url(&self) -> String {
+        public_urls().resolve(self)
+    }
+
+    /// Link that downloads the file under the asset name
+    pub fn download_url(&self) -> String {
+        public_urls().download_url(self)
+    }
+}
@@ -0,0 +1,237 @@
+//! Upload sessions with progress reporting.
+//!
+//! `POST /api/assets/uploads` opens a session and returns its id, also in
+//! the `X-Upload-Id` header. `PUT /api/assets/uploads/{id}` streams the raw
+//! file into a temp file, counting bytes against the request's
+//! `Content-Length`, while the admin UI polls
+//! `GET /api/assets/uploads/{id}/progress`. `POST /api/assets/uploads/{id}/finalize`
+//! then stores the file the same way a multipart upload does.
+//!
+//! Sessions expire an hour after they were opened. Expired sessions and
+//! their temp files are dropped whenever a session is opened or looked up.
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tempfile::TempPath;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Time after which an unfinished session is dropped
+pub const UPLOAD_SESSION_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Response header carrying the session id
+pub const UPLOAD_ID_HEADER: &str = "X-Upload-Id";
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum UploadSessionError {
+    #[error("Upload session '{0}' not found or expired")]
+    NotFound(Uuid),
+    #[error("Upload session '{0}' is already receiving a file")]
+    Busy(Uuid),
+    #[error("Upload session '{0}' has not received its file yet")]
+    Incomplete(Uuid),
+}
+
+/// What a finalized session stores, given when it is opened
+#[derive(Debug, Clone, Default)]
+pub struct UploadTarget {
+    pub filename: String,
+    /// Display name, defaults to `filename`
+    pub name: Option<String>,
+    pub folders: Vec<String>,
+    pub posting_id: Option<Uuid>,
+}
+
+/// State of an upload session as reported to clients
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct UploadProgress {
+    pub id: Uuid,
+    #[schema(example = "video-kerja-bakti.mp4")]
+    pub filename: String,
+    /// Bytes of the file received so far
+    pub received_bytes: u64,
+    /// `Content-Length` of the upload, unknown until it starts
+    pub expected_bytes: Option<u64>,
+    /// The file was received in full and the session can be finalized
+    pub complete: bool,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Byte counter of an upload in progress
+#[derive(Clone)]
+pub struct ReceivedBytes(Arc<AtomicU64>);
+
+impl ReceivedBytes {
+    /// Count `n` more bytes, returning the new total
+    pub fn add(&self, n: u64) -> u64 {
+        self.0.fetch_add(n, Ordering::Relaxed) + n
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+struct UploadSession {
+    target: UploadTarget,
+    received: ReceivedBytes,
+    expected: Option<u64>,
+    receiving: bool,
+    /// Set once the file was received in full; removed on drop
+    file: Option<TempPath>,
+    opened_at: Instant,
+    expires_at: DateTime<Utc>,
+}
+
+impl UploadSession {
+    fn progress(&self, id: Uuid) -> UploadProgress {
+        UploadProgress {
+            id,
+            filename: self.target.filename.clone(),
+            received_bytes: self.received.get(),
+            expected_bytes: self.expected,
+            complete: self.file.is_some(),
+            expires_at: self.expires_at,
+        }
+    }
+}
+
+/// Open upload sessions keyed by id, held by `AppState`
+pub struct UploadSessions {
+    sessions: DashMap<Uuid, UploadSession>,
+    ttl: Duration,
+}
+
+impl Default for UploadSessions {
+    fn default() -> Self {
+        Self::new(UPLOAD_SESSION_TTL)
+    }
+}
+
+impl UploadSessions {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            sessions: DashMap::new(),
+            ttl,
+        }
+    }
+
+    /// Register a new session for `target`
+    pub fn open(&self, target: UploadTarget) -> UploadProgress {
+        self.remove_expired();
+
+        let id = Uuid::new_v4();
+        let session = UploadSession {
+            target,
+            received: ReceivedBytes(Arc::default()),
+            expected: None,
+            receiving: false,
+            file: None,
+            opened_at: Instant::now(),
+            expires_at: Utc::now()
+                + chrono::Duration::from_std(self.ttl).unwrap_or(chrono::Duration::MAX),
+        };
+        let progress = session.progress(id);
+        self.sessions.insert(id, session);
+        progress
+    }
+
+    /// Start receiving the file of session `id`, replacing one received
+    /// earlier. Returns the counter to advance as bytes arrive.
+    pub fn begin(
+        &self,
+        id: Uuid,
+        expected: Option<u64>,
+    ) -> Result<ReceivedBytes, UploadSessionError> {
+        self.remove_expired();
+
+        let mut session = self
+            .sessions
+            .get_mut(&id)
+            .ok_or(UploadSessionError::NotFound(id))?;
+        if session.receiving {
+            return Err(UploadSessionError::Busy(id));
+        }
+        session.receiving = true;
+        session.expected = expected;
+        session.file = None;
+        session.received = ReceivedBytes(Arc::default());
+        Ok(session.received.clone())
+    }
+
+    /// Record the file of session `id` as received in full
+    pub fn complete(&self, id: Uuid, file: TempPath) -> Result<UploadProgress, UploadSessionError> {
+        let mut session = self
+            .sessions
+            .get_mut(&id)
+            .ok_or(UploadSessionError::NotFound(id))?;
+        session.receiving = false;
+        session.file = Some(file);
+        Ok(session.progress(id))
+    }
+
+    /// Give up on the file being received for session `id`, so it can be
+    /// sent again
+    pub fn abort(&self, id: Uuid) {
+        if let Some(mut session) = self.sessions.get_mut(&id) {
+            session.receiving = false;
+        }
+    }
+
+    pub fn progress(&self, id: Uuid) -> Result<UploadProgress, UploadSessionError> {
+        self.remove_expired();
+
+        self.sessions
+            .get(&id)
+            .map(|session| session.progress(id))
+            .ok_or(UploadSessionError::NotFound(id))
+    }
+
+    /// Remove session `id` for finalizing, once its file is complete
+    pub fn take(&self, id: Uuid) -> Result<(UploadTarget, TempPath), UploadSessionError> {
+        self.remove_expired();
+
+        let session = self
+            .sessions
+            .remove_if(&id, |_, session| session.file.is_some())
+            .map(|(_, session)| session);
+        match session {
+            Some(UploadSession {
+                target,
+                file: Some(file),
+                ..
+            }) => Ok((target, file)),
+            _ if self.sessions.contains_key(&id) => Err(UploadSessionError::Incomplete(id)),
+            _ => Err(UploadSessionError::NotFound(id)),
+        }
+    }
+
+    /// Drop sessions past their lifetime along with their temp files.
+    /// Returns the number removed.
+    pub fn remove_expired(&self) -> usize {
+        let mut removed = 0;
+        self.sessions.retain(|_, session| {
+            let keep = session.opened_at.elapsed() < self.ttl;
+            if !keep {
+                removed += 1;
+            }
+            keep
+        });
+        if removed > 0 {
+            log::info!("Removed {} expired upload sessions", removed);
+        }
+        removed
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+}
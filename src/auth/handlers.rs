@@ -1,44 +1,97 @@
 use actix_web::{web, HttpRequest, HttpResponse, Responder};
 use bcrypt::{hash, verify, DEFAULT_COST};
+use std::sync::OnceLock;
 
 use super::jwt::{
     generate_access_token, generate_refresh_token, get_access_token_expiry, validate_token,
 };
 use super::middleware::validate_request_token;
 use super::model::{
-    AdminInfo, AuthStatusResponse, CreateAdminRequest, LoginRequest, RefreshRequest, TokenResponse,
+    Admin, AdminInfo, AuthStatusResponse, Claims, CreateAdminRequest, LoginRequest, RefreshRequest,
+    SessionInfoResponse, TokenResponse, UpdateAdminRequest,
 };
 use crate::AppState;
 
 const DEFAULT_ADMIN_USERNAME: &str = "admin";
 const DEFAULT_ADMIN_PASSWORD: &str = "admin123";
 
+/// Hash verified against when the username does not exist, so unknown
+/// usernames cost the same bcrypt work as wrong passwords
+static DUMMY_PASSWORD_HASH: OnceLock<String> = OnceLock::new();
+
+const DUMMY_PASSWORD: &str = "cakung-barat-dummy-password";
+
+/// Hash the dummy password. Called at startup, so a bcrypt failure stops the
+/// server instead of leaving unknown usernames without the bcrypt delay.
+pub fn init_dummy_password_hash() -> Result<(), String> {
+    if DUMMY_PASSWORD_HASH.get().is_none() {
+        let dummy = hash(DUMMY_PASSWORD, DEFAULT_COST)
+            .map_err(|e| format!("Failed to hash dummy password: {}", e))?;
+        let _ = DUMMY_PASSWORD_HASH.set(dummy);
+    }
+    Ok(())
+}
+
+fn dummy_password_hash() -> &'static str {
+    DUMMY_PASSWORD_HASH.get_or_init(|| {
+        hash(DUMMY_PASSWORD, DEFAULT_COST).expect("bcrypt failed to hash the dummy password")
+    })
+}
+
+/// Verify a password against the stored hash, or against a dummy hash when
+/// the admin does not exist. Returns false whenever `stored_hash` is None.
+pub(crate) fn verify_credentials(password: &str, stored_hash: Option<&str>) -> bool {
+    match stored_hash {
+        Some(stored) => verify(password, stored).unwrap_or(false),
+        None => {
+            let _ = verify(password, dummy_password_hash());
+            false
+        }
+    }
+}
+
+/// Single response for every credential failure (unknown user or bad password)
+pub(crate) fn invalid_credentials_response() -> HttpResponse {
+    HttpResponse::Unauthorized().json(crate::ErrorResponse::new(
+        "Unauthorized",
+        "Invalid username or password",
+    ))
+}
+
 /// Check if setup is required (no admins exist)
 #[utoipa::path(
+    operation_id = "getAuthStatus",
     get,
     path = "/api/auth/status",
     tag = "Authentication",
     responses(
-        (status = 200, description = "Auth status", body = AuthStatusResponse)
+        (status = 200, description = "Auth status", body = AuthStatusResponse),
+        (status = 500, description = "Internal Server Error", body = crate::ErrorResponse)
     )
 )]
-pub async fn get_auth_status(state: web::Data<AppState>) -> impl Responder {
-    let count = state.get_admin_count().await.unwrap_or(0);
-    HttpResponse::Ok().json(AuthStatusResponse {
+pub async fn get_auth_status(state: web::Data<AppState>) -> Result<HttpResponse, crate::ApiError> {
+    // A failed count must not report that setup is required
+    let count = state
+        .get_admin_count()
+        .await
+        .map_err(crate::ApiError::database("Failed to check admin accounts"))?;
+    Ok(HttpResponse::Ok().json(AuthStatusResponse {
         has_admins: count > 0,
         setup_required: count == 0,
-    })
+    }))
 }
 
 /// Login endpoint
 #[utoipa::path(
+    operation_id = "login",
     post,
     path = "/api/auth/login",
     tag = "Authentication",
     request_body = LoginRequest,
     responses(
         (status = 200, description = "Login successful", body = TokenResponse),
-        (status = 401, description = "Invalid credentials")
+        (status = 401, description = "Invalid credentials", body = crate::ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = crate::ErrorResponse)
     )
 )]
 pub async fn login(state: web::Data<AppState>, body: web::Json<LoginRequest>) -> impl Responder {
@@ -86,13 +139,7 @@ pub async fn login(state: web::Data<AppState>, body: web::Json<LoginRequest>) ->
 
     // Normal login flow
     let admin = match state.get_admin_by_username(&body.username).await {
-        Ok(Some(admin)) => admin,
-        Ok(None) => {
-            return HttpResponse::Unauthorized().json(crate::ErrorResponse::new(
-                "Unauthorized",
-                "Invalid username or password",
-            ));
-        }
+        Ok(admin) => admin,
         Err(e) => {
             log::error!("Database error during login: {:?}", e);
             return HttpResponse::InternalServerError()
@@ -100,14 +147,15 @@ pub async fn login(state: web::Data<AppState>, body: web::Json<LoginRequest>) ->
         }
     };
 
-    // Verify password
-    let password_valid = verify(&body.password, &admin.password_hash).unwrap_or(false);
-    if !password_valid {
-        return HttpResponse::Unauthorized().json(crate::ErrorResponse::new(
-            "Unauthorized",
-            "Invalid username or password",
-        ));
-    }
+    // Verify password (runs bcrypt even for unknown usernames)
+    let password_valid = verify_credentials(
+        &body.password,
+        admin.as_ref().map(|a| a.password_hash.as_str()),
+    );
+    let admin = match admin {
+        Some(admin) if password_valid => admin,
+        _ => return invalid_credentials_response(),
+    };
 
     // Generate tokens
     let admin_id = admin.id.to_string();
@@ -151,13 +199,15 @@ pub async fn login(state: web::Data<AppState>, body: web::Json<LoginRequest>) ->
 
 /// Refresh access token
 #[utoipa::path(
+    operation_id = "refreshToken",
     post,
     path = "/api/auth/refresh",
     tag = "Authentication",
     request_body = RefreshRequest,
     responses(
         (status = 200, description = "Token refreshed", body = TokenResponse),
-        (status = 401, description = "Invalid refresh token")
+        (status = 401, description = "Invalid refresh token", body = crate::ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = crate::ErrorResponse)
     )
 )]
 pub async fn refresh_token(
@@ -220,8 +270,87 @@ pub async fn refresh_token(
     })
 }
 
+/// Seconds left before the token described by `claims` expires (never negative)
+pub(crate) fn remaining_lifetime(claims: &Claims, now: i64) -> i64 {
+    (claims.exp as i64 - now).max(0)
+}
+
+/// Get current session details (protected)
+#[utoipa::path(
+    operation_id = "getSession",
+    get,
+    path = "/api/auth/sessions",
+    tag = "Authentication",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Current session", body = SessionInfoResponse),
+        (status = 401, response = crate::UnauthorizedResponse)
+    )
+)]
+pub async fn get_session(req: HttpRequest, state: web::Data<AppState>) -> impl Responder {
+    // Check authorization
+    let claims = match validate_request_token(&req) {
+        Ok(c) => c,
+        Err(e) => return e.error_response(),
+    };
+
+    // Setup-mode tokens have no admin row and therefore no stored session
+    let (has_refresh_token, refresh_token_issued_at) = match uuid::Uuid::parse_str(&claims.sub) {
+        Ok(admin_id) => match state.get_admin_session(&admin_id).await {
+            Ok(Some(session)) => session,
+            Ok(None) => (false, None),
+            Err(e) => {
+                log::error!("Failed to get session: {:?}", e);
+                return HttpResponse::InternalServerError().json(
+                    crate::ErrorResponse::internal_error("Failed to get session"),
+                );
+            }
+        },
+        Err(_) => (false, None),
+    };
+
+    HttpResponse::Ok().json(SessionInfoResponse {
+        access_token_expires_in: remaining_lifetime(&claims, chrono::Utc::now().timestamp()),
+        admin_id: claims.sub,
+        username: claims.username,
+        has_refresh_token,
+        refresh_token_issued_at,
+    })
+}
+
+/// Logout: drop the stored refresh token for the current admin (protected)
+#[utoipa::path(
+    operation_id = "deleteSession",
+    delete,
+    path = "/api/auth/sessions",
+    tag = "Authentication",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Logged out"),
+        (status = 401, response = crate::UnauthorizedResponse)
+    )
+)]
+pub async fn delete_session(req: HttpRequest, state: web::Data<AppState>) -> impl Responder {
+    // Check authorization
+    let claims = match validate_request_token(&req) {
+        Ok(c) => c,
+        Err(e) => return e.error_response(),
+    };
+
+    if let Ok(admin_id) = uuid::Uuid::parse_str(&claims.sub) {
+        if let Err(e) = state.clear_admin_refresh_token(&admin_id).await {
+            log::error!("Failed to clear refresh token: {:?}", e);
+            return HttpResponse::InternalServerError()
+                .json(crate::ErrorResponse::internal_error("Logout failed"));
+        }
+    }
+
+    HttpResponse::Ok().finish()
+}
+
 /// Create new admin (protected - requires admin auth)
 #[utoipa::path(
+    operation_id = "createAdmin",
     post,
     path = "/api/auth/admins",
     tag = "Authentication",
@@ -229,7 +358,7 @@ pub async fn refresh_token(
     security(("bearer_auth" = [])),
     responses(
         (status = 201, description = "Admin created", body = AdminInfo),
-        (status = 401, description = "Unauthorized"),
+        (status = 401, response = crate::UnauthorizedResponse),
         (status = 409, description = "Username already exists")
     )
 )]
@@ -281,6 +410,13 @@ pub async fn create_admin(
         .await
     {
         Ok(admin) => admin,
+        // Another request took the username since the check above
+        Err(e) if e.is_unique_violation() => {
+            return HttpResponse::Conflict().json(crate::ErrorResponse::new(
+                "Conflict",
+                "Username already exists",
+            ));
+        }
         Err(e) => {
             log::error!("Failed to create admin: {:?}", e);
             return HttpResponse::InternalServerError().json(crate::ErrorResponse::internal_error(
@@ -294,13 +430,14 @@ pub async fn create_admin(
 
 /// List all admins (protected)
 #[utoipa::path(
+    operation_id = "listAdmins",
     get,
     path = "/api/auth/admins",
     tag = "Authentication",
     security(("bearer_auth" = [])),
     responses(
         (status = 200, description = "Admin list", body = Vec<AdminInfo>),
-        (status = 401, description = "Unauthorized")
+        (status = 401, response = crate::UnauthorizedResponse)
     )
 )]
 pub async fn list_admins(req: HttpRequest, state: web::Data<AppState>) -> impl Responder {
@@ -322,8 +459,96 @@ pub async fn list_admins(req: HttpRequest, state: web::Data<AppState>) -> impl R
     }
 }
 
+/// Returns true when `existing` (the admin currently holding a username) is
+/// someone other than the admin being renamed.
+pub(crate) fn username_taken_by_other(existing: Option<&Admin>, admin_id: &uuid::Uuid) -> bool {
+    existing.is_some_and(|admin| admin.id != *admin_id)
+}
+
+/// Update admin username and/or display name (protected)
+///
+/// Admins can always edit their own row. There are no roles yet, so any
+/// authenticated admin may also edit other admins. Tokens carry the admin id,
+/// so a rename does not invalidate existing sessions.
+#[utoipa::path(
+    operation_id = "updateAdmin",
+    put,
+    path = "/api/auth/admins/{id}",
+    tag = "Authentication",
+    params(("id" = String, Path, description = "Admin ID")),
+    request_body = UpdateAdminRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Admin updated", body = AdminInfo),
+        (status = 400, description = "Invalid input"),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 404, description = "Admin not found"),
+        (status = 409, description = "Username already exists")
+    )
+)]
+pub async fn update_admin(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+    body: web::Json<UpdateAdminRequest>,
+) -> impl Responder {
+    // Check authorization
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    let admin_id = path.into_inner();
+    let username = body.username.as_deref().map(str::trim);
+
+    if username.is_some_and(str::is_empty) {
+        return HttpResponse::BadRequest().json(crate::ErrorResponse::bad_request(
+            "Username cannot be empty",
+        ));
+    }
+
+    // Check if the new username belongs to another admin
+    if let Some(username) = username {
+        match state.get_admin_by_username(username).await {
+            Ok(existing) => {
+                if username_taken_by_other(existing.as_ref(), &admin_id) {
+                    return HttpResponse::Conflict().json(crate::ErrorResponse::new(
+                        "Conflict",
+                        "Username already exists",
+                    ));
+                }
+            }
+            Err(e) => {
+                log::error!("Failed to check username: {:?}", e);
+                return HttpResponse::InternalServerError().json(
+                    crate::ErrorResponse::internal_error("Failed to update admin"),
+                );
+            }
+        }
+    }
+
+    match state
+        .update_admin(&admin_id, username, body.display_name.as_deref())
+        .await
+    {
+        Ok(Some(admin)) => HttpResponse::Ok().json(AdminInfo::from(admin)),
+        Ok(None) => {
+            HttpResponse::NotFound().json(crate::ErrorResponse::not_found("Admin not found"))
+        }
+        Err(e) if e.is_unique_violation() => HttpResponse::Conflict().json(
+            crate::ErrorResponse::new("Conflict", "Username already exists"),
+        ),
+        Err(e) => {
+            log::error!("Failed to update admin: {:?}", e);
+            HttpResponse::InternalServerError().json(crate::ErrorResponse::internal_error(
+                "Failed to update admin",
+            ))
+        }
+    }
+}
+
 /// Delete admin (protected)
 #[utoipa::path(
+    operation_id = "deleteAdmin",
     delete,
     path = "/api/auth/admins/{id}",
     tag = "Authentication",
@@ -331,7 +556,7 @@ pub async fn list_admins(req: HttpRequest, state: web::Data<AppState>) -> impl R
     security(("bearer_auth" = [])),
     responses(
         (status = 200, description = "Admin deleted"),
-        (status = 401, description = "Unauthorized"),
+        (status = 401, response = crate::UnauthorizedResponse),
         (status = 404, description = "Admin not found")
     )
 )]
@@ -381,11 +606,23 @@ pub async fn delete_admin(
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/auth")
-            .route("/status", web::get().to(get_auth_status))
-            .route("/login", web::post().to(login))
-            .route("/refresh", web::post().to(refresh_token))
-            .route("/admins", web::get().to(list_admins))
-            .route("/admins", web::post().to(create_admin))
-            .route("/admins/{id}", web::delete().to(delete_admin)),
+            .service(web::resource("/status").route(web::get().to(get_auth_status)))
+            .service(web::resource("/login").route(web::post().to(login)))
+            .service(web::resource("/refresh").route(web::post().to(refresh_token)))
+            .service(
+                web::resource("/sessions")
+                    .route(web::get().to(get_session))
+                    .route(web::delete().to(delete_session)),
+            )
+            .service(
+                web::resource("/admins")
+                    .route(web::get().to(list_admins))
+                    .route(web::post().to(create_admin)),
+            )
+            .service(
+                web::resource("/admins/{id}")
+                    .route(web::put().to(update_admin))
+                    .route(web::delete().to(delete_admin)),
+            ),
     );
 }
@@ -1,16 +1,30 @@
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
-use std::env;
+use std::sync::OnceLock;
 
 use super::model::Claims;
+use crate::config::JwtConfig;
 
-const DEFAULT_JWT_SECRET: &str = "cakung-barat-jwt-secret-change-in-production";
 const ACCESS_TOKEN_EXPIRY_SECONDS: i64 = 15 * 60; // 15 minutes
 const REFRESH_TOKEN_EXPIRY_SECONDS: i64 = 7 * 24 * 60 * 60; // 7 days
 
-fn get_jwt_secret() -> String {
-    env::var("JWT_SECRET").unwrap_or_else(|_| {
+static JWT_SECRET: OnceLock<String> = OnceLock::new();
+
+/// Install the signing secret from the startup configuration. Only the first
+/// call has any effect.
+pub fn init_jwt_secret(config: &JwtConfig) {
+    if config.is_default() {
         log::warn!("JWT_SECRET not set, using default secret. SET THIS IN PRODUCTION!");
-        DEFAULT_JWT_SECRET.to_string()
+    }
+    let _ = JWT_SECRET.set(config.secret.clone());
+}
+
+/// Secret set by [`init_jwt_secret`], or read from the environment when running
+/// without it (tests and tools)
+fn get_jwt_secret() -> &'static str {
+    JWT_SECRET.get_or_init(|| {
+        JwtConfig::from_lookup(|key| std::env::var(key).ok())
+            .unwrap_or_default()
+            .secret
     })
 }
 
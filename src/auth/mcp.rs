@@ -0,0 +1,108 @@
+use actix_web::http::header;
+use actix_web::HttpRequest;
+use sha2::{Digest, Sha256};
+
+use super::middleware::validate_request_token;
+
+/// Scope an API key needs to call the MCP endpoint
+pub const MCP_SCOPE: &str = "mcp";
+
+/// Header carrying an API key, as an alternative to `Authorization: Bearer`
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
+/// Methods served without credentials so clients can connect and learn
+/// that the rest requires authentication
+const OPEN_METHODS: &[&str] = &["initialize", "ping"];
+
+/// Authentication for the MCP transports. Every request except
+/// [`OPEN_METHODS`] and notifications needs an admin access token or an API
+/// key with the `mcp` scope.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct McpAuth {
+    /// Keys from `MCP_API_KEYS` that carry the `mcp` scope
+    pub api_keys: Vec<String>,
+    /// Reject with HTTP 401 + `WWW-Authenticate` instead of a JSON-RPC error
+    pub http_401: bool,
+}
+
+impl McpAuth {
+    /// Read `MCP_API_KEYS` and `MCP_AUTH_HTTP_401` using a custom variable
+    /// lookup. Keys are listed as `<key>:<scope>[+<scope>...]`, separated by
+    /// commas; keys without the `mcp` scope are ignored.
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let get = |key: &str| {
+            lookup(key)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        let api_keys = match get("MCP_API_KEYS") {
+            Some(v) => parse_api_keys(&v)?,
+            None => Vec::new(),
+        };
+        let http_401 = match get("MCP_AUTH_HTTP_401") {
+            Some(v) => crate::config::parse_bool("MCP_AUTH_HTTP_401", &v)?,
+            None => false,
+        };
+
+        Ok(Self { api_keys, http_401 })
+    }
+
+    /// Whether `method` may be called without credentials
+    pub fn is_open(method: &str) -> bool {
+        OPEN_METHODS.contains(&method) || method.starts_with("notifications/")
+    }
+
+    /// Whether the request carries an admin access token or an accepted API key
+    pub fn is_authorized(&self, req: &HttpRequest) -> bool {
+        if validate_request_token(req).is_ok() {
+            return true;
+        }
+        api_key(req).is_some_and(|key| self.api_keys.iter().any(|k| k == key))
+    }
+
+    /// Stable name for the credentials on `req`, such as `admin:<id>`, or
+    /// `None` when they are not accepted. API keys appear only as a hash.
+    pub fn caller(&self, req: &HttpRequest) -> Option<String> {
+        if let Ok(claims) = validate_request_token(req) {
+            return Some(format!("admin:{}", claims.sub));
+        }
+        api_key(req)
+            .filter(|key| self.api_keys.iter().any(|k| k == key))
+            .map(|key| format!("key:{}", &hex::encode(Sha256::digest(key))[..16]))
+    }
+}
+
+fn parse_api_keys(value: &str) -> Result<Vec<String>, String> {
+    let mut keys = Vec::new();
+    for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (key, scopes) = entry
+            .rsplit_once(':')
+            .filter(|(key, scopes)| !key.is_empty() && !scopes.is_empty())
+            .ok_or_else(|| {
+                "MCP_API_KEYS entries must look like '<key>:<scope>[+<scope>...]'".to_string()
+            })?;
+        if scopes.split('+').any(|scope| scope.trim() == MCP_SCOPE) {
+            keys.push(key.to_string());
+        }
+    }
+    Ok(keys)
+}
+
+/// API key from `X-API-Key`, falling back to the bearer token
+fn api_key(req: &HttpRequest) -> Option<&str> {
+    let headers = req.headers();
+    headers
+        .get(API_KEY_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .or_else(|| {
+            headers
+                .get(header::AUTHORIZATION)
+                .and_then(|h| h.to_str().ok())
+                .and_then(|auth| auth.strip_prefix("Bearer "))
+        })
+        .map(str::trim)
+}
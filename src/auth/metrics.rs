@@ -0,0 +1,113 @@
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header;
+use actix_web::middleware::Next;
+use actix_web::{web, Error, HttpResponse};
+use base64::Engine;
+use std::env;
+use subtle::ConstantTimeEq;
+
+use super::middleware::validate_request_token;
+
+/// Path of the Prometheus scrape endpoint, see `crate::request_metrics`
+pub const METRICS_PATH: &str = "/metrics";
+
+/// Protection mode for the `/metrics` endpoint, configured via `METRICS_AUTH`:
+/// - `bearer` requires a valid admin access token
+/// - `basic:<username>:<password>` requires HTTP basic auth credentials
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetricsAuth {
+    Bearer,
+    Basic { username: String, password: String },
+}
+
+impl MetricsAuth {
+    /// Parse a `METRICS_AUTH` value. Returns None for unknown formats.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("bearer") {
+            return Some(Self::Bearer);
+        }
+
+        let credentials = value.strip_prefix("basic:")?;
+        let (username, password) = credentials.split_once(':')?;
+        if username.is_empty() || password.is_empty() {
+            return None;
+        }
+
+        Some(Self::Basic {
+            username: username.to_string(),
+            password: password.to_string(),
+        })
+    }
+
+    /// Read `METRICS_AUTH` from environment. None keeps `/metrics` open.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        Self::from_lookup(|key| env::var(key).ok())
+    }
+
+    /// Read `METRICS_AUTH` using a custom variable lookup. An unrecognized
+    /// value is an error rather than leaving `/metrics` open.
+    pub fn from_lookup<F>(lookup: F) -> Result<Option<Self>, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let Some(value) = lookup("METRICS_AUTH").filter(|v| !v.trim().is_empty()) else {
+            return Ok(None);
+        };
+
+        Self::parse(&value)
+            .map(Some)
+            .ok_or_else(|| "METRICS_AUTH must be 'bearer' or 'basic:<user>:<password>'".to_string())
+    }
+
+    fn is_authorized(&self, req: &ServiceRequest) -> bool {
+        match self {
+            Self::Bearer => validate_request_token(req.request()).is_ok(),
+            Self::Basic { username, password } => req
+                .headers()
+                .get(header::AUTHORIZATION)
+                .and_then(|h| h.to_str().ok())
+                .and_then(|auth| auth.strip_prefix("Basic "))
+                .and_then(|encoded| {
+                    base64::engine::general_purpose::STANDARD
+                        .decode(encoded.trim())
+                        .ok()
+                })
+                .and_then(|decoded| String::from_utf8(decoded).ok())
+                .is_some_and(|decoded| {
+                    // Constant time, so the response time does not reveal
+                    // how much of the credentials matched
+                    let expected = format!("{}:{}", username, password);
+                    decoded.as_bytes().ct_eq(expected.as_bytes()).into()
+                }),
+        }
+    }
+}
+
+/// Middleware guarding the metrics endpoint. Requests to other paths, or any
+/// request when no `MetricsAuth` is registered as app data, pass through.
+pub async fn metrics_auth_guard(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    if req.path() == METRICS_PATH {
+        if let Some(auth) = req.app_data::<web::Data<MetricsAuth>>() {
+            if !auth.is_authorized(&req) {
+                let mut response = HttpResponse::Unauthorized();
+                if matches!(auth.get_ref(), MetricsAuth::Basic { .. }) {
+                    response.insert_header((header::WWW_AUTHENTICATE, "Basic realm=\"metrics\""));
+                }
+                let response = response.json(crate::ErrorResponse::new(
+                    "Unauthorized",
+                    "Metrics require authentication",
+                ));
+                return Ok(req.into_response(response).map_into_right_body());
+            }
+        }
+    }
+
+    next.call(req)
+        .await
+        .map(ServiceResponse::map_into_left_body)
+}
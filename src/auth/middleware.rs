@@ -1,8 +1,18 @@
-use actix_web::error::ErrorUnauthorized;
-use actix_web::{Error, HttpMessage, HttpRequest};
+use actix_web::error::InternalError;
+use actix_web::{Error, HttpMessage, HttpRequest, HttpResponse};
 
 use super::jwt::validate_token;
 use super::model::Claims;
+use crate::{ErrorCode, ErrorResponse};
+
+/// 401 with an `ErrorResponse` body, as documented by `UnauthorizedResponse`
+fn unauthorized(message: &'static str) -> Error {
+    let response = HttpResponse::Unauthorized().json(ErrorResponse::new(
+        ErrorCode::Unauthorized.as_str(),
+        message,
+    ));
+    InternalError::from_response(message, response).into()
+}
 
 /// Extract token from Authorization header
 fn extract_token(req: &HttpRequest) -> Option<String> {
@@ -20,16 +30,15 @@ fn extract_token(req: &HttpRequest) -> Option<String> {
 
 /// Validate token from HttpRequest and return claims
 pub fn validate_request_token(req: &HttpRequest) -> Result<Claims, Error> {
-    let token =
-        extract_token(req).ok_or_else(|| ErrorUnauthorized("Missing authorization token"))?;
+    let token = extract_token(req).ok_or_else(|| unauthorized("Missing authorization token"))?;
 
     let claims = validate_token(&token).map_err(|e| {
         log::warn!("Token validation failed: {:?}", e);
-        ErrorUnauthorized("Invalid or expired token")
+        unauthorized("Invalid or expired token")
     })?;
 
     if claims.token_type != "access" {
-        return Err(ErrorUnauthorized("Invalid token type"));
+        return Err(unauthorized("Invalid token type"));
     }
 
     Ok(claims)
@@ -1,5 +1,7 @@
 pub mod handlers;
 pub mod jwt;
+pub mod mcp;
+pub mod metrics;
 pub mod middleware;
 pub mod model;
 
@@ -8,5 +10,7 @@ mod tests;
 
 pub use handlers::*;
 pub use jwt::*;
+pub use mcp::*;
+pub use metrics::*;
 pub use middleware::*;
 pub use model::*;
@@ -2,8 +2,11 @@
 
 #[cfg(test)]
 mod tests {
+    use crate::auth::handlers::{remaining_lifetime, username_taken_by_other, verify_credentials};
     use crate::auth::jwt::{generate_access_token, generate_refresh_token, validate_token};
-    use crate::auth::model::{Admin, AdminInfo, Claims, LoginRequest, TokenResponse};
+    use crate::auth::model::{
+        Admin, AdminInfo, Claims, LoginRequest, TokenResponse, UpdateAdminRequest,
+    };
     use uuid::Uuid;
 
     #[test]
@@ -141,4 +144,83 @@ mod tests {
         // Refresh token should expire later than access token
         assert!(refresh_claims.exp > access_claims.exp);
     }
+
+    fn sample_admin(username: &str) -> Admin {
+        Admin {
+            id: Uuid::new_v4(),
+            username: username.to_string(),
+            password_hash: "hash".to_string(),
+            display_name: None,
+            refresh_token: None,
+            created_at: None,
+            updated_at: None,
+            created_by: None,
+        }
+    }
+
+    #[test]
+    fn test_rename_to_username_of_other_admin_conflicts() {
+        let other = sample_admin("operator");
+        let target = Uuid::new_v4();
+
+        assert!(username_taken_by_other(Some(&other), &target));
+    }
+
+    #[test]
+    fn test_self_edit_keeping_own_username_is_not_conflict() {
+        let me = sample_admin("admin");
+
+        assert!(!username_taken_by_other(Some(&me), &me.id));
+        assert!(!username_taken_by_other(None, &me.id));
+    }
+
+    #[test]
+    fn test_update_admin_request_fields_are_optional() {
+        let request: UpdateAdminRequest = serde_json::from_str(r#"{"display_name": "Pak Lurah"}"#)
+            .expect("Failed to deserialize");
+
+        assert_eq!(request.display_name.as_deref(), Some("Pak Lurah"));
+        assert!(request.username.is_none());
+    }
+
+    #[test]
+    fn test_remaining_lifetime_from_access_token() {
+        let token = generate_access_token("test-id", "testuser").expect("Failed to generate token");
+        let claims = validate_token(&token).expect("Failed to validate token");
+
+        let remaining = remaining_lifetime(&claims, claims.iat as i64);
+        assert_eq!(remaining, (claims.exp - claims.iat) as i64);
+    }
+
+    #[test]
+    fn test_remaining_lifetime_never_negative() {
+        let claims = Claims {
+            sub: "test-id".to_string(),
+            username: "testuser".to_string(),
+            exp: 1000,
+            iat: 100,
+            token_type: "access".to_string(),
+        };
+
+        assert_eq!(remaining_lifetime(&claims, 5000), 0);
+    }
+
+    #[test]
+    fn test_unknown_username_still_runs_bcrypt() {
+        let stored = bcrypt::hash("correct-password", bcrypt::DEFAULT_COST).expect("hash");
+        // Warm up the lazily computed dummy hash so it is not part of the timing
+        verify_credentials("warmup", None);
+
+        let start = std::time::Instant::now();
+        assert!(!verify_credentials("wrong-password", Some(&stored)));
+        let wrong_password = start.elapsed();
+
+        let start = std::time::Instant::now();
+        assert!(!verify_credentials("wrong-password", None));
+        let unknown_user = start.elapsed();
+
+        // Coarse check: both paths do a full bcrypt verification
+        assert!(unknown_user * 3 > wrong_password);
+        assert!(verify_credentials("correct-password", Some(&stored)));
+    }
 }
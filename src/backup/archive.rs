@@ -0,0 +1,184 @@
+//! Writing and reading the tar.gz backup archives
+
+use actix_web::web::Bytes;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Component, Path};
+
+use super::{
+    BackupManifest, ContentSnapshot, ADMINS_FILE, ASSETS_FILE, FILES_DIR, FOLDERS_FILE,
+    MANIFEST_FILE, ORGANIZATION_FILE, POSTINGS_FILE, SCHEMA_VERSION,
+};
+
+/// Builds an archive one file at a time, handing out the compressed bytes
+/// as they are produced instead of keeping them
+pub struct ArchiveWriter {
+    builder: tar::Builder<GzEncoder<Vec<u8>>>,
+    mtime: u64,
+}
+
+impl Default for ArchiveWriter {
+    fn default() -> Self {
+        Self {
+            builder: tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default())),
+            mtime: chrono::Utc::now().timestamp().max(0) as u64,
+        }
+    }
+}
+
+impl ArchiveWriter {
+    /// Add a file and return the output produced since the last call
+    pub fn append(&mut self, path: &str, data: &[u8]) -> io::Result<Bytes> {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(self.mtime);
+        self.builder.append_data(&mut header, path, data)?;
+        Ok(Bytes::from(std::mem::take(
+            self.builder.get_mut().get_mut(),
+        )))
+    }
+
+    pub fn append_json<T: Serialize>(&mut self, path: &str, value: &T) -> io::Result<Bytes> {
+        let data = serde_json::to_vec_pretty(value)?;
+        self.append(path, &data)
+    }
+
+    /// End the archive and return the remaining output
+    pub fn finish(self) -> io::Result<Bytes> {
+        let encoder = self.builder.into_inner()?;
+        Ok(Bytes::from(encoder.finish()?))
+    }
+}
+
+/// Contents of an archive apart from the asset file data
+#[derive(Debug)]
+pub struct ArchiveContents {
+    pub manifest: BackupManifest,
+    pub snapshot: ContentSnapshot,
+    /// Names of the asset files, relative to `files/`
+    pub files: Vec<String>,
+}
+
+/// Asset filename of an archive entry under `files/`. Names that could
+/// escape the storage root are refused.
+fn asset_filename(entry_path: &str) -> Option<&str> {
+    let name = entry_path.strip_prefix(FILES_DIR)?;
+    let safe = !name.is_empty()
+        && Path::new(name)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    safe.then_some(name)
+}
+
+fn parse_json<T: DeserializeOwned>(name: &str, data: &[u8]) -> Result<T, String> {
+    serde_json::from_slice(data).map_err(|e| format!("{} is invalid: {}", name, e))
+}
+
+fn entries(file: File) -> tar::Archive<GzDecoder<File>> {
+    tar::Archive::new(GzDecoder::new(file))
+}
+
+/// Read the JSON files and the list of asset files of the archive at `path`
+pub fn read_archive(path: &Path) -> Result<ArchiveContents, String> {
+    let mut archive = entries(File::open(path).map_err(|e| e.to_string())?);
+    let mut manifest: Option<BackupManifest> = None;
+    let mut postings = None;
+    let mut assets = None;
+    let mut folders = None;
+    let mut organization = None;
+    let mut admins = None;
+    let mut files = Vec::new();
+
+    let invalid = |e: io::Error| format!("Not a valid tar.gz archive: {}", e);
+    for entry in archive.entries().map_err(invalid)? {
+        let mut entry = entry.map_err(invalid)?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry
+            .path()
+            .map_err(invalid)?
+            .to_string_lossy()
+            .into_owned();
+        if name.starts_with(FILES_DIR) {
+            match asset_filename(&name) {
+                Some(filename) => files.push(filename.to_string()),
+                None => return Err(format!("Invalid file name in archive: {}", name)),
+            }
+            continue;
+        }
+
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data).map_err(invalid)?;
+        match name.as_str() {
+            MANIFEST_FILE => manifest = Some(parse_json(&name, &data)?),
+            POSTINGS_FILE => postings = Some(parse_json(&name, &data)?),
+            ASSETS_FILE => assets = Some(parse_json(&name, &data)?),
+            FOLDERS_FILE => folders = Some(parse_json(&name, &data)?),
+            ORGANIZATION_FILE => organization = Some(parse_json(&name, &data)?),
+            ADMINS_FILE => admins = Some(parse_json(&name, &data)?),
+            _ => log::debug!("Ignoring unknown backup entry {}", name),
+        }
+    }
+
+    let manifest = manifest.ok_or_else(|| {
+        format!(
+            "{} is missing, the archive is incomplete or not a backup",
+            MANIFEST_FILE
+        )
+    })?;
+    if manifest.schema_version > SCHEMA_VERSION {
+        return Err(format!(
+            "Archive schema version {} is newer than the supported version {}",
+            manifest.schema_version, SCHEMA_VERSION
+        ));
+    }
+    let missing = |name: &str| format!("{} is missing from the archive", name);
+    Ok(ArchiveContents {
+        manifest,
+        snapshot: ContentSnapshot {
+            postings: postings.ok_or_else(|| missing(POSTINGS_FILE))?,
+            assets: assets.ok_or_else(|| missing(ASSETS_FILE))?,
+            folders: folders.ok_or_else(|| missing(FOLDERS_FILE))?,
+            organization: organization.ok_or_else(|| missing(ORGANIZATION_FILE))?,
+            admins: admins.ok_or_else(|| missing(ADMINS_FILE))?,
+        },
+        files,
+    })
+}
+
+/// Hand each asset file of the archive at `path` to `each` with its
+/// filename, one at a time. Stops early when `each` returns false.
+pub fn read_files(
+    path: &Path,
+    mut each: impl FnMut(String, Vec<u8>) -> bool,
+) -> Result<(), String> {
+    let mut archive = entries(File::open(path).map_err(|e| e.to_string())?);
+    for entry in archive.entries().map_err(|e| e.to_string())? {
+        let mut entry = entry.map_err(|e| e.to_string())?;
+        let name = entry
+            .path()
+            .map_err(|e| e.to_string())?
+            .to_string_lossy()
+            .into_owned();
+        let Some(filename) = asset_filename(&name) else {
+            continue;
+        };
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let filename = filename.to_string();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data).map_err(|e| e.to_string())?;
+        if !each(filename, data) {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,309 @@
+//! Admin endpoints producing and restoring backup archives
+
+use actix_web::http::header;
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use chrono::Utc;
+use futures::stream::{self, StreamExt};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::io;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use utoipa::IntoParams;
+
+use super::archive::{self, ArchiveWriter};
+use super::{
+    ContentSnapshot, RestoreReport, ADMINS_FILE, ASSETS_FILE, FILES_DIR, FILE_CONCURRENCY,
+    FOLDERS_FILE, MANIFEST_FILE, ORGANIZATION_FILE, POSTINGS_FILE,
+};
+use crate::asset::download::content_disposition;
+use crate::auth::validate_request_token;
+use crate::error::ApiError;
+use crate::storage::ObjectStorage;
+use crate::AppState;
+
+/// Archive chunks waiting for a slow client
+const ARCHIVE_CHANNEL_CAPACITY: usize = 4;
+
+type Storage = Arc<dyn ObjectStorage + Send + Sync>;
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct BackupQuery {
+    /// Also include the stored asset files (default: false)
+    pub include_files: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct RestoreQuery {
+    /// Only report what would change
+    pub dry_run: Option<bool>,
+}
+
+async fn send(sender: &mpsc::Sender<io::Result<web::Bytes>>, bytes: web::Bytes) -> io::Result<()> {
+    if bytes.is_empty() {
+        return Ok(());
+    }
+    sender.send(Ok(bytes)).await.map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::BrokenPipe,
+            "client stopped reading the backup",
+        )
+    })
+}
+
+async fn write_archive(
+    snapshot: ContentSnapshot,
+    storage: Option<Storage>,
+    sender: &mpsc::Sender<io::Result<web::Bytes>>,
+) -> io::Result<()> {
+    let mut archive = ArchiveWriter::default();
+    send(
+        sender,
+        archive.append_json(POSTINGS_FILE, &snapshot.postings)?,
+    )
+    .await?;
+    send(sender, archive.append_json(ASSETS_FILE, &snapshot.assets)?).await?;
+    send(
+        sender,
+        archive.append_json(FOLDERS_FILE, &snapshot.folders)?,
+    )
+    .await?;
+    send(
+        sender,
+        archive.append_json(ORGANIZATION_FILE, &snapshot.organization)?,
+    )
+    .await?;
+    send(sender, archive.append_json(ADMINS_FILE, &snapshot.admins)?).await?;
+
+    let mut files = 0;
+    if let Some(storage) = storage {
+        let filenames: BTreeSet<String> = snapshot
+            .assets
+            .iter()
+            .map(|asset| asset.filename.clone())
+            .collect();
+        let mut downloads = stream::iter(filenames)
+            .map(|filename| {
+                let storage = storage.clone();
+                async move {
+                    let data = storage.download_file(&filename).await;
+                    (filename, data)
+                }
+            })
+            .buffered(FILE_CONCURRENCY);
+        while let Some((filename, data)) = downloads.next().await {
+            match data {
+                Ok(data) => {
+                    let path = format!("{}{}", FILES_DIR, filename);
+                    send(sender, archive.append(&path, &data)?).await?;
+                    files += 1;
+                }
+                Err(e) => log::warn!("Backup leaves out asset file {}: {}", filename, e),
+            }
+        }
+    }
+
+    send(
+        sender,
+        archive.append_json(MANIFEST_FILE, &snapshot.manifest(files))?,
+    )
+    .await?;
+    send(sender, archive.finish()?).await
+}
+
+/// Download a tar.gz snapshot of all content (protected)
+///
+/// Holds `postings.json`, `assets.json`, `folders.json`,
+/// `organization.json`, `admins.json` (without password hashes) and
+/// `manifest.json` with the counts and schema version, written last. With
+/// `include_files=true` the stored asset files follow under `files/`. The
+/// archive is streamed while it is built.
+#[utoipa::path(
+    operation_id = "createBackup",
+    post,
+    path = "/api/admin/backup",
+    tag = "Admin",
+    params(BackupQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Backup archive", content_type = "application/gzip"),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 500, description = "Content could not be read", body = crate::ErrorResponse)
+    )
+)]
+pub async fn create_backup(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<BackupQuery>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    let snapshot = match state.content_snapshot().await {
+        Ok(snapshot) => snapshot,
+        Err(e) => return e.error_response(),
+    };
+    let storage = query
+        .include_files
+        .unwrap_or(false)
+        .then(|| state.storage.clone());
+    let (sender, receiver) = mpsc::channel(ARCHIVE_CHANNEL_CAPACITY);
+    actix_web::rt::spawn(async move {
+        if let Err(e) = write_archive(snapshot, storage, &sender).await {
+            log::error!("Backup archive stopped: {}", e);
+            // Ends the response early; the archive has no manifest
+            let _ = sender.send(Err(e)).await;
+        }
+    });
+
+    let filename = format!(
+        "cakung-barat-backup-{}.tar.gz",
+        Utc::now().format("%Y%m%d-%H%M%S")
+    );
+    HttpResponse::Ok()
+        .content_type("application/gzip")
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            content_disposition(false, &filename),
+        ))
+        .streaming(ReceiverStream::new(receiver))
+}
+
+/// Upload the asset files of the archive at `path`, a few at a time
+async fn restore_files(
+    path: std::path::PathBuf,
+    storage: Storage,
+    report: &mut RestoreReport,
+) -> Result<(), ApiError> {
+    let (sender, receiver) = mpsc::channel::<(String, Vec<u8>)>(FILE_CONCURRENCY);
+    let reader = tokio::task::spawn_blocking(move || {
+        archive::read_files(&path, |filename, data| {
+            sender.blocking_send((filename, data)).is_ok()
+        })
+    });
+
+    let mut uploads = ReceiverStream::new(receiver)
+        .map(|(filename, data)| {
+            let storage = storage.clone();
+            async move {
+                let result = storage.upload_file(&filename, &data).await;
+                (filename, result)
+            }
+        })
+        .buffer_unordered(FILE_CONCURRENCY);
+    while let Some((filename, result)) = uploads.next().await {
+        match result {
+            Ok(()) => report.files += 1,
+            Err(e) => {
+                log::error!("Failed to restore asset file {}: {}", filename, e);
+                report.failed_files.push(filename);
+            }
+        }
+    }
+
+    reader
+        .await
+        .map_err(|e| ApiError::Internal(format!("Archive reader failed: {}", e)))?
+        .map_err(ApiError::BadRequest)
+}
+
+async fn restore(
+    state: &web::Data<AppState>,
+    mut payload: web::Payload,
+    dry_run: bool,
+) -> Result<RestoreReport, ApiError> {
+    // Spooled to disk, as the archive is read once for the JSON files and
+    // again for the asset files
+    let spool = tempfile::NamedTempFile::new_in(crate::maintenance::temp_dir())
+        .map_err(|e| ApiError::Internal(format!("Failed to create temp file: {}", e)))?;
+    let spool_error = |e: io::Error| ApiError::Internal(format!("Failed to store upload: {}", e));
+    let mut file = tokio::fs::File::from_std(spool.reopen().map_err(spool_error)?);
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        file.write_all(&chunk).await.map_err(spool_error)?;
+    }
+    file.flush().await.map_err(spool_error)?;
+
+    let path = spool.path().to_path_buf();
+    let contents = web::block(move || archive::read_archive(&path))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .map_err(ApiError::BadRequest)?;
+
+    let mut report = state
+        .restore_snapshot(&contents.snapshot, dry_run)
+        .await
+        .map_err(ApiError::database("Failed to restore content"))?;
+    report.schema_version = contents.manifest.schema_version;
+    if dry_run {
+        report.files = contents.files.len();
+        return Ok(report);
+    }
+
+    state
+        .restore_organization_members(contents.snapshot.organization.clone())
+        .await
+        .map_err(ApiError::Internal)?;
+    if !contents.files.is_empty() {
+        restore_files(
+            spool.path().to_path_buf(),
+            state.storage.clone(),
+            &mut report,
+        )
+        .await?;
+    }
+    log::info!(
+        "Restored backup from {}: {} posts, {} assets, {} files",
+        contents.manifest.created_at,
+        contents.snapshot.postings.len(),
+        contents.snapshot.assets.len(),
+        report.files
+    );
+    Ok(report)
+}
+
+/// Restore an archive made by `POST /api/admin/backup` (protected)
+///
+/// Posts, assets and folder links in the archive are created or
+/// overwritten; anything else is kept. The organization structure is
+/// replaced, and asset files in the archive are written to storage. Admin
+/// accounts are not restored. With `dry_run=true` the changes are computed
+/// in a transaction that is rolled back.
+#[utoipa::path(
+    operation_id = "restoreBackup",
+    post,
+    path = "/api/admin/restore",
+    tag = "Admin",
+    params(RestoreQuery),
+    request_body(content = Vec<u8>, content_type = "application/gzip", description = "Backup archive"),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "What was restored, or would be", body = RestoreReport),
+        (status = 400, description = "Not a valid or complete backup archive", body = crate::ErrorResponse),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 500, description = "Internal Server Error", body = crate::ErrorResponse)
+    )
+)]
+pub async fn restore_backup(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<RestoreQuery>,
+    payload: web::Payload,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    match restore(&state, payload, query.dry_run.unwrap_or(false)).await {
+        Ok(report) => HttpResponse::Ok().json(report),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/admin/backup").route(web::post().to(create_backup)))
+        .service(web::resource("/admin/restore").route(web::post().to(restore_backup)));
+}
@@ -0,0 +1,322 @@
+//! Content snapshots for backup and restore.
+//!
+//! `POST /api/admin/backup` streams a tar.gz holding one JSON file per kind
+//! of content and, on request, the stored asset files under `files/`.
+//! `manifest.json` is written last, so an archive cut short by a dropped
+//! connection has none and is refused by `POST /api/admin/restore`.
+//!
+//! Restoring inserts or overwrites the rows found in the archive and keeps
+//! every other row. Admins are exported without password hashes and are
+//! never restored; the report lists the ones missing here.
+
+pub mod archive;
+pub mod handlers;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::asset::models::Asset;
+use crate::db::{link_posts_to_folder, DbError};
+use crate::error::ApiError;
+use crate::organization::model::OrganizationMember;
+use crate::posting::models::Post;
+use crate::AppState;
+
+/// Version of the archive layout, recorded in the manifest. Archives from a
+/// newer version are refused.
+pub const SCHEMA_VERSION: u32 = 1;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+pub const POSTINGS_FILE: &str = "postings.json";
+pub const ASSETS_FILE: &str = "assets.json";
+pub const FOLDERS_FILE: &str = "folders.json";
+pub const ORGANIZATION_FILE: &str = "organization.json";
+pub const ADMINS_FILE: &str = "admins.json";
+/// Directory of the stored asset files, each under its asset filename
+pub const FILES_DIR: &str = "files/";
+
+/// Asset files downloaded or uploaded at the same time
+pub const FILE_CONCURRENCY: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub schema_version: u32,
+    pub created_at: DateTime<Utc>,
+    pub postings: usize,
+    pub assets: usize,
+    pub folders: usize,
+    pub organization_members: usize,
+    pub admins: usize,
+    /// Asset files in `files/`. Files that could not be downloaded are left
+    /// out, so this can be lower than `assets`.
+    pub files: usize,
+}
+
+/// A folder and the assets linked to it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BackupFolder {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub asset_ids: Vec<Uuid>,
+}
+
+/// An admin account without its password hash and refresh token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BackupAdmin {
+    pub id: Uuid,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+}
+
+/// Everything a backup holds apart from the asset files
+#[derive(Debug, Clone)]
+pub struct ContentSnapshot {
+    pub postings: Vec<Post>,
+    pub assets: Vec<Asset>,
+    pub folders: Vec<BackupFolder>,
+    pub organization: Vec<OrganizationMember>,
+    pub admins: Vec<BackupAdmin>,
+}
+
+impl ContentSnapshot {
+    /// Manifest for this snapshot and `files` asset files
+    pub fn manifest(&self, files: usize) -> BackupManifest {
+        BackupManifest {
+            schema_version: SCHEMA_VERSION,
+            created_at: Utc::now(),
+            postings: self.postings.len(),
+            assets: self.assets.len(),
+            folders: self.folders.len(),
+            organization_members: self.organization.len(),
+            admins: self.admins.len(),
+            files,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct RestoreCounts {
+    pub created: u64,
+    pub updated: u64,
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct RestoreReport {
+    /// Nothing was changed
+    pub dry_run: bool,
+    /// Version of the restored archive
+    pub schema_version: u32,
+    pub postings: RestoreCounts,
+    pub assets: RestoreCounts,
+    /// Folders created. A folder whose name already exists is kept and
+    /// receives the archived links.
+    pub folders_created: u64,
+    /// Asset links added to folders
+    pub folder_links_created: u64,
+    /// Members of the restored organization structure, which replaces the
+    /// current one
+    pub organization_members: usize,
+    /// Asset files written to storage, or found in the archive on a dry run
+    pub files: usize,
+    /// Asset files that could not be written
+    pub failed_files: Vec<String>,
+    /// Archived admins with no account of that username here. They have to
+    /// be created again by hand.
+    pub missing_admins: Vec<String>,
+}
+
+impl AppState {
+    /// Current posts, assets, folders, organization structure and admins
+    pub async fn content_snapshot(&self) -> Result<ContentSnapshot, ApiError> {
+        let postings = self
+            .get_all_posts()
+            .await
+            .map_err(ApiError::database("Failed to read posts"))?;
+        let assets = self
+            .get_all_assets()
+            .await
+            .map_err(ApiError::database("Failed to read assets"))?;
+        let folders = self
+            .timed("backup_folders", async {
+                sqlx::query_as(
+                    r#"
+                    SELECT f.id, f.name, f.created_at,
+                           COALESCE(array_agg(af.asset_id ORDER BY af.asset_id)
+                                    FILTER (WHERE af.asset_id IS NOT NULL), '{}') AS asset_ids
+                    FROM folders f
+                    LEFT JOIN asset_folders af ON af.folder_id = f.id
+                    GROUP BY f.id
+                    ORDER BY f.name
+                    "#,
+                )
+                .fetch_all(&self.pool)
+                .await
+            })
+            .await
+            .map_err(ApiError::database("Failed to read folders"))?;
+        let admins = self
+            .timed("backup_admins", async {
+                sqlx::query_as(
+                    "SELECT id, username, display_name, created_at, updated_at, created_by FROM admins ORDER BY created_at",
+                )
+                .fetch_all(&self.pool)
+                .await
+            })
+            .await
+            .map_err(ApiError::database("Failed to read admins"))?;
+        let organization = self
+            .get_organization_structure()
+            .await
+            .map_err(ApiError::Internal)?;
+
+        Ok(ContentSnapshot {
+            postings,
+            assets,
+            folders,
+            organization,
+            admins,
+        })
+    }
+
+    /// Write the rows of `snapshot` in one transaction, rolled back on a dry
+    /// run. The organization structure and asset files are left to the
+    /// caller.
+    pub async fn restore_snapshot(
+        &self,
+        snapshot: &ContentSnapshot,
+        dry_run: bool,
+    ) -> Result<RestoreReport, DbError> {
+        self.timed("restore_snapshot", async {
+            let mut report = RestoreReport {
+                dry_run,
+                schema_version: SCHEMA_VERSION,
+                organization_members: snapshot.organization.len(),
+                ..Default::default()
+            };
+            let mut tx = self.pool.begin().await?;
+
+            for asset in &snapshot.assets {
+                // xmax is 0 only for freshly inserted rows
+                let created: bool = sqlx::query_scalar(
+                    r#"
+                    INSERT INTO assets (id, name, filename, url, description, width, height, created_at, updated_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, COALESCE($8, NOW()), COALESCE($9, NOW()))
+                    ON CONFLICT (id) DO UPDATE SET
+                        name = EXCLUDED.name, filename = EXCLUDED.filename, url = EXCLUDED.url,
+                        description = EXCLUDED.description, width = EXCLUDED.width,
+                        height = EXCLUDED.height, created_at = EXCLUDED.created_at
+                    RETURNING xmax = 0
+                    "#,
+                )
+                .bind(asset.id)
+                .bind(&asset.name)
+                .bind(&asset.filename)
+                .bind(&asset.url)
+                .bind(&asset.description)
+                .bind(asset.width)
+                .bind(asset.height)
+                .bind(asset.created_at)
+                .bind(asset.updated_at)
+                .fetch_one(&mut *tx)
+                .await?;
+                count(&mut report.assets, created);
+            }
+
+            for post in &snapshot.postings {
+                let created: bool = sqlx::query_scalar(
+                    r#"
+                    INSERT INTO posts (id, title, category, date, excerpt, folder_id, folder_uuid, created_at, updated_at, cover_asset_id)
+                    VALUES ($1, $2, $3, $4, $5, $6, (SELECT id FROM folders WHERE name = $6),
+                            COALESCE($7, NOW()), COALESCE($8, NOW()),
+                            (SELECT id FROM assets WHERE id = $9))
+                    ON CONFLICT (id) DO UPDATE SET
+                        title = EXCLUDED.title, category = EXCLUDED.category, date = EXCLUDED.date,
+                        excerpt = EXCLUDED.excerpt, folder_id = EXCLUDED.folder_id,
+                        folder_uuid = EXCLUDED.folder_uuid, created_at = EXCLUDED.created_at,
+                        cover_asset_id = EXCLUDED.cover_asset_id
+                    RETURNING xmax = 0
+                    "#,
+                )
+                .bind(post.id)
+                .bind(&post.title)
+                .bind(&post.category)
+                .bind(post.date)
+                .bind(&post.excerpt)
+                .bind(&post.folder_id)
+                .bind(post.created_at)
+                .bind(post.updated_at)
+                .bind(post.cover_asset_id)
+                .fetch_one(&mut *tx)
+                .await?;
+                count(&mut report.postings, created);
+            }
+
+            for folder in &snapshot.folders {
+                // Posts refer to folders by name, so an existing folder of
+                // the same name wins over the archived id
+                report.folders_created += sqlx::query(
+                    "INSERT INTO folders (id, name, created_at) VALUES ($1, $2, COALESCE($3, NOW())) ON CONFLICT DO NOTHING",
+                )
+                .bind(folder.id)
+                .bind(&folder.name)
+                .bind(folder.created_at)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+                let folder_id: Uuid = sqlx::query_scalar("SELECT id FROM folders WHERE name = $1")
+                    .bind(&folder.name)
+                    .fetch_one(&mut *tx)
+                    .await?;
+                link_posts_to_folder(&mut tx, folder_id, &folder.name).await?;
+                report.folder_links_created += sqlx::query(
+                    r#"
+                    INSERT INTO asset_folders (folder_id, asset_id)
+                    SELECT f.id, a.id FROM folders f, assets a
+                    WHERE f.name = $1 AND a.id = ANY($2)
+                    ON CONFLICT DO NOTHING
+                    "#,
+                )
+                .bind(&folder.name)
+                .bind(&folder.asset_ids)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            }
+
+            let usernames: Vec<&str> = snapshot
+                .admins
+                .iter()
+                .map(|admin| admin.username.as_str())
+                .collect();
+            report.missing_admins = sqlx::query_scalar(
+                "SELECT u FROM UNNEST($1::text[]) u WHERE NOT EXISTS (SELECT 1 FROM admins WHERE username = u) ORDER BY u",
+            )
+            .bind(&usernames)
+            .fetch_all(&mut *tx)
+            .await?;
+
+            if dry_run {
+                tx.rollback().await?;
+            } else {
+                tx.commit().await?;
+                self.clear_cache(crate::cache::CacheTarget::Posts).await;
+            }
+            Ok(report)
+        })
+        .await
+    }
+}
+
+fn count(counts: &mut RestoreCounts, created: bool) {
+    if created {
+        counts.created += 1;
+    } else {
+        counts.updated += 1;
+    }
+}
@@ -0,0 +1,141 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use super::{CacheEntryInfo, CacheStatsResponse, CacheTarget};
+use crate::auth::validate_request_token;
+use crate::error::ApiError;
+use crate::AppState;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ClearCacheQuery {
+    /// `posts`, `organization` or `all` (default)
+    #[serde(default)]
+    #[param(value_type = Option<String>)]
+    pub cache: CacheTarget,
+}
+
+/// Hit/miss counters and entry counts per cache (protected)
+#[utoipa::path(
+    operation_id = "getCacheStats",
+    get,
+    path = "/api/admin/cache/stats",
+    tag = "Cache",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Cache statistics", body = CacheStatsResponse),
+        (status = 401, response = crate::UnauthorizedResponse)
+    )
+)]
+pub async fn get_cache_stats(req: HttpRequest, state: web::Data<AppState>) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    HttpResponse::Ok().json(state.cache_stats().await)
+}
+
+/// Clear one or all caches (protected)
+#[utoipa::path(
+    operation_id = "clearCache",
+    post,
+    path = "/api/admin/cache/clear",
+    tag = "Cache",
+    params(ClearCacheQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Cache cleared, returns updated statistics", body = CacheStatsResponse),
+        (status = 400, description = "Unknown cache name"),
+        (status = 401, response = crate::UnauthorizedResponse)
+    )
+)]
+pub async fn clear_cache(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<ClearCacheQuery>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    state.clear_cache(query.cache).await;
+    log::info!("Cache cleared: {:?}", query.cache);
+
+    HttpResponse::Ok().json(state.cache_stats().await)
+}
+
+/// Keys currently cached, with their size and remaining TTL (protected)
+///
+/// Any admin token is accepted, as there are no roles yet.
+#[utoipa::path(
+    operation_id = "listCacheEntries",
+    get,
+    path = "/api/admin/cache/entries",
+    tag = "Cache",
+    params(ClearCacheQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Cached entries", body = Vec<CacheEntryInfo>),
+        (status = 400, description = "Unknown cache name"),
+        (status = 401, response = crate::UnauthorizedResponse)
+    )
+)]
+pub async fn list_cache_entries(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<ClearCacheQuery>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    HttpResponse::Ok().json(state.cache_entries(query.cache).await)
+}
+
+/// Drop a single cached key (protected)
+///
+/// Any admin token is accepted, as there are no roles yet.
+#[utoipa::path(
+    operation_id = "deleteCacheEntry",
+    delete,
+    path = "/api/admin/cache/entries/{key}",
+    tag = "Cache",
+    params(
+        ("key" = String, Path, description = "Cache key, as listed by `GET /api/admin/cache/entries`"),
+        ClearCacheQuery
+    ),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Entry removed"),
+        (status = 400, description = "Unknown cache name"),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 404, description = "No such key in the selected caches", body = crate::ErrorResponse)
+    )
+)]
+pub async fn delete_cache_entry(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    key: web::Path<String>,
+    query: web::Query<ClearCacheQuery>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    let key = key.into_inner();
+    if !state.remove_cache_entry(query.cache, &key).await {
+        return ApiError::NotFound(format!("Cache key {} not found", key)).error_response();
+    }
+    log::info!("Cache entry removed: {} ({:?})", key, query.cache);
+    HttpResponse::NoContent().finish()
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/admin/cache")
+            .service(web::resource("/stats").route(web::get().to(get_cache_stats)))
+            .service(web::resource("/clear").route(web::post().to(clear_cache)))
+            .service(web::resource("/entries").route(web::get().to(list_cache_entries)))
+            .service(web::resource("/entries/{key}").route(web::delete().to(delete_cache_entry))),
+    );
+}
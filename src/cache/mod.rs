@@ -0,0 +1,332 @@
+//! Hit/miss metrics for the in-memory post and organization caches.
+//!
+//! Counters live on `AppState` and are registered with the Prometheus
+//! registry used by the `/metrics` endpoint. Cached values are stored as
+//! [`Cached`] so the admin endpoints can show how long each has left.
+
+pub mod handlers;
+
+use moka::future::Cache;
+use prometheus::{IntCounterVec, IntGaugeVec, Opts, Registry};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+use crate::organization::model::MemberMap;
+use crate::AppState;
+
+const METRICS_NAMESPACE: &str = "cakung_barat_server";
+
+/// A cached value and when it was inserted
+#[derive(Debug, Clone)]
+pub struct Cached<T> {
+    pub value: T,
+    pub inserted_at: Instant,
+}
+
+impl<T> Cached<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            inserted_at: Instant::now(),
+        }
+    }
+
+    /// Time until the cache's `ttl` expires this entry
+    pub fn remaining_ttl(&self, ttl: Option<Duration>) -> Option<Duration> {
+        ttl.map(|ttl| ttl.saturating_sub(self.inserted_at.elapsed()))
+    }
+}
+
+/// Caches tracked by [`CacheMetrics`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheKind {
+    Posts,
+    Organization,
+}
+
+impl CacheKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Posts => "posts",
+            Self::Organization => "organization",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CacheMetrics {
+    hits: IntCounterVec,
+    misses: IntCounterVec,
+    entries: IntGaugeVec,
+    weighted_size: IntGaugeVec,
+}
+
+impl Default for CacheMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CacheMetrics {
+    pub fn new() -> Self {
+        let hits = IntCounterVec::new(
+            Opts::new("cache_hits_total", "Cache lookups served from memory")
+                .namespace(METRICS_NAMESPACE),
+            &["cache"],
+        )
+        .expect("valid cache hit metric");
+        let misses = IntCounterVec::new(
+            Opts::new(
+                "cache_misses_total",
+                "Cache lookups that went to the backend",
+            )
+            .namespace(METRICS_NAMESPACE),
+            &["cache"],
+        )
+        .expect("valid cache miss metric");
+        let entries = IntGaugeVec::new(
+            Opts::new("cache_entries", "Entries currently held in the cache")
+                .namespace(METRICS_NAMESPACE),
+            &["cache"],
+        )
+        .expect("valid cache entries metric");
+        let weighted_size = IntGaugeVec::new(
+            Opts::new(
+                "cache_weighted_size",
+                "Weighted size of the cached entries, the entry count unless a weigher is set",
+            )
+            .namespace(METRICS_NAMESPACE),
+            &["cache"],
+        )
+        .expect("valid cache weighted size metric");
+
+        Self {
+            hits,
+            misses,
+            entries,
+            weighted_size,
+        }
+    }
+
+    /// Register the metrics so they are exported on `/metrics`
+    pub fn register(&self, registry: &Registry) -> prometheus::Result<()> {
+        registry.register(Box::new(self.hits.clone()))?;
+        registry.register(Box::new(self.misses.clone()))?;
+        registry.register(Box::new(self.entries.clone()))?;
+        registry.register(Box::new(self.weighted_size.clone()))?;
+        Ok(())
+    }
+
+    pub fn record_hit(&self, cache: CacheKind) {
+        self.hits.with_label_values(&[cache.label()]).inc();
+    }
+
+    pub fn record_miss(&self, cache: CacheKind) {
+        self.misses.with_label_values(&[cache.label()]).inc();
+    }
+
+    pub fn set_entries(&self, cache: CacheKind, count: u64) {
+        self.entries
+            .with_label_values(&[cache.label()])
+            .set(count as i64);
+    }
+
+    pub fn set_weighted_size(&self, cache: CacheKind, size: u64) {
+        self.weighted_size
+            .with_label_values(&[cache.label()])
+            .set(size as i64);
+    }
+
+    pub fn hits(&self, cache: CacheKind) -> u64 {
+        self.hits.with_label_values(&[cache.label()]).get()
+    }
+
+    pub fn misses(&self, cache: CacheKind) -> u64 {
+        self.misses.with_label_values(&[cache.label()]).get()
+    }
+
+    pub fn entries(&self, cache: CacheKind) -> u64 {
+        self.entries
+            .with_label_values(&[cache.label()])
+            .get()
+            .max(0) as u64
+    }
+
+    pub fn weighted_size(&self, cache: CacheKind) -> u64 {
+        self.weighted_size
+            .with_label_values(&[cache.label()])
+            .get()
+            .max(0) as u64
+    }
+}
+
+/// Which cache to clear
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheTarget {
+    Posts,
+    Organization,
+    #[default]
+    All,
+}
+
+impl CacheTarget {
+    fn includes(&self, cache: CacheKind) -> bool {
+        match self {
+            Self::All => true,
+            Self::Posts => cache == CacheKind::Posts,
+            Self::Organization => cache == CacheKind::Organization,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CacheCounters {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: u64,
+}
+
+/// One key held by a cache
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CacheEntryInfo {
+    /// `posts` or `organization`
+    #[schema(example = "posts")]
+    pub cache: String,
+    #[schema(example = "all_posts")]
+    pub key: String,
+    /// Posts or members in the cached list
+    pub items: usize,
+    /// Size of the value serialized as JSON
+    pub size_bytes: usize,
+    pub age_secs: u64,
+    /// Seconds until the entry expires, absent when the cache has no TTL
+    pub ttl_remaining_secs: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CacheStatsResponse {
+    pub posts: CacheCounters,
+    pub organization: CacheCounters,
+}
+
+impl AppState {
+    /// Entry count and weighted size after moka has applied pending inserts
+    /// and invalidations
+    async fn cache_size(&self, cache: CacheKind) -> (u64, u64) {
+        match cache {
+            CacheKind::Posts => {
+                self.post_cache.run_pending_tasks().await;
+                (
+                    self.post_cache.entry_count(),
+                    self.post_cache.weighted_size(),
+                )
+            }
+            CacheKind::Organization => {
+                self.organization_cache.run_pending_tasks().await;
+                (
+                    self.organization_cache.entry_count(),
+                    self.organization_cache.weighted_size(),
+                )
+            }
+        }
+    }
+
+    /// Update the size gauges for a cache after an insert or invalidation.
+    /// Expiry is only noticed here, so these are also refreshed by the
+    /// runtime metrics sampler, see `crate::runtime_metrics`.
+    pub(crate) async fn refresh_cache_entries(&self, cache: CacheKind) {
+        let (count, weighted_size) = self.cache_size(cache).await;
+        self.cache_metrics.set_entries(cache, count);
+        self.cache_metrics.set_weighted_size(cache, weighted_size);
+    }
+
+    async fn cache_counters(&self, cache: CacheKind) -> CacheCounters {
+        self.refresh_cache_entries(cache).await;
+        CacheCounters {
+            hits: self.cache_metrics.hits(cache),
+            misses: self.cache_metrics.misses(cache),
+            entries: self.cache_metrics.entries(cache),
+        }
+    }
+
+    pub async fn cache_stats(&self) -> CacheStatsResponse {
+        CacheStatsResponse {
+            posts: self.cache_counters(CacheKind::Posts).await,
+            organization: self.cache_counters(CacheKind::Organization).await,
+        }
+    }
+
+    /// Drop cached entries. The organization cache is reloaded from storage
+    /// on the next read, so updates still waiting in the persistence worker's
+    /// debounce window are not visible until they are written.
+    pub async fn clear_cache(&self, target: CacheTarget) {
+        if target.includes(CacheKind::Posts) {
+            self.post_cache.invalidate_all();
+            self.refresh_cache_entries(CacheKind::Posts).await;
+        }
+        if target.includes(CacheKind::Organization) {
+            self.organization_cache.invalidate_all();
+            self.refresh_cache_entries(CacheKind::Organization).await;
+        }
+    }
+
+    /// Keys held by the selected caches, sorted by cache and key
+    pub async fn cache_entries(&self, target: CacheTarget) -> Vec<CacheEntryInfo> {
+        let mut entries = Vec::new();
+        if target.includes(CacheKind::Posts) {
+            entries.extend(list_entries(CacheKind::Posts, &self.post_cache, Vec::len).await);
+        }
+        if target.includes(CacheKind::Organization) {
+            entries.extend(
+                list_entries(
+                    CacheKind::Organization,
+                    &self.organization_cache,
+                    MemberMap::len,
+                )
+                .await,
+            );
+        }
+        entries
+    }
+
+    /// Drop `key` from the selected caches. Returns false when none held it.
+    pub async fn remove_cache_entry(&self, target: CacheTarget, key: &str) -> bool {
+        let mut removed = false;
+        if target.includes(CacheKind::Posts) && self.post_cache.remove(key).await.is_some() {
+            self.refresh_cache_entries(CacheKind::Posts).await;
+            removed = true;
+        }
+        if target.includes(CacheKind::Organization)
+            && self.organization_cache.remove(key).await.is_some()
+        {
+            self.refresh_cache_entries(CacheKind::Organization).await;
+            removed = true;
+        }
+        removed
+    }
+}
+
+/// Entries of `cache`, with `len` giving the number of items in a value
+async fn list_entries<V: Serialize + Clone + Send + Sync + 'static>(
+    kind: CacheKind,
+    cache: &Cache<String, Cached<V>>,
+    len: fn(&V) -> usize,
+) -> Vec<CacheEntryInfo> {
+    cache.run_pending_tasks().await;
+    let ttl = cache.policy().time_to_live();
+    let mut entries: Vec<CacheEntryInfo> = cache
+        .iter()
+        .map(|(key, cached)| CacheEntryInfo {
+            cache: kind.label().to_string(),
+            key: key.to_string(),
+            items: len(&cached.value),
+            size_bytes: serde_json::to_vec(&cached.value).map_or(0, |json| json.len()),
+            age_secs: cached.inserted_at.elapsed().as_secs(),
+            ttl_remaining_secs: cached.remaining_ttl(ttl).map(|left| left.as_secs()),
+        })
+        .collect();
+    entries.sort_by(|a, b| a.key.cmp(&b.key));
+    entries
+}
@@ -0,0 +1,321 @@
+//! Public comment endpoints and admin moderation
+
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use super::{
+    Comment, CommentStatus, CreateCommentRequest, PublicComment, SubmittedComment,
+    MAX_AUTHOR_NAME_LENGTH, MAX_CONTACT_LENGTH, RATE_LIMIT_KEY,
+};
+use crate::auth::validate_request_token;
+use crate::error::ApiError;
+use crate::AppState;
+
+const DEFAULT_COMMENT_LIST_LIMIT: i64 = 50;
+const MAX_COMMENT_LIST_LIMIT: i64 = 200;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct CommentListQuery {
+    /// Only comments in this status, e.g. `pending` for the moderation queue
+    pub status: Option<CommentStatus>,
+    /// Comments returned, newest first (default 50, max 200)
+    pub limit: Option<i64>,
+}
+
+/// Trimmed `request` fields, or the first one that is empty or too long
+fn validate_comment(
+    request: &CreateCommentRequest,
+    max_length: usize,
+) -> Result<(&str, Option<&str>, &str), ApiError> {
+    let author_name = request.author_name.trim();
+    if author_name.is_empty() {
+        return Err(ApiError::BadRequest("author_name must not be empty".to_string()));
+    }
+    if author_name.chars().count() > MAX_AUTHOR_NAME_LENGTH {
+        return Err(ApiError::BadRequest(format!(
+            "author_name must be at most {} characters",
+            MAX_AUTHOR_NAME_LENGTH
+        )));
+    }
+    let contact = request
+        .contact
+        .as_deref()
+        .map(str::trim)
+        .filter(|contact| !contact.is_empty());
+    if contact.is_some_and(|contact| contact.chars().count() > MAX_CONTACT_LENGTH) {
+        return Err(ApiError::BadRequest(format!(
+            "contact must be at most {} characters",
+            MAX_CONTACT_LENGTH
+        )));
+    }
+    let body = request.body.trim();
+    if body.is_empty() {
+        return Err(ApiError::BadRequest("body must not be empty".to_string()));
+    }
+    if body.chars().count() > max_length {
+        return Err(ApiError::BadRequest(format!(
+            "body must be at most {} characters",
+            max_length
+        )));
+    }
+    Ok((author_name, contact, body))
+}
+
+/// Submit a comment on a post. It is published once an admin approves it.
+#[utoipa::path(
+    operation_id = "createPostingComment",
+    context_path = "/api",
+    tag = "Posting Service",
+    post,
+    path = "/postings/{id}/comments",
+    request_body = CreateCommentRequest,
+    params(("id" = Uuid, Path, description = "ID of the post")),
+    responses(
+        (status = 202, description = "Comment received and waiting for moderation", body = SubmittedComment),
+        (status = 400, description = "Empty or too long name, contact or body", body = crate::ErrorResponse),
+        (status = 404, description = "Post not found", body = crate::ErrorResponse),
+        (status = 429, description = "Too many comments from this address, see `Retry-After`", body = crate::ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = crate::ErrorResponse)
+    )
+)]
+pub async fn create_comment(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    request: web::Json<CreateCommentRequest>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let post_id = path.into_inner();
+    let (author_name, contact, body) = validate_comment(&request, data.comments.max_length)?;
+
+    // The connecting address, not a forwarded one the client could make up
+    let client = req
+        .peer_addr()
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    data.comment_limiter
+        .check(&client, RATE_LIMIT_KEY, false)
+        .map_err(|throttled| ApiError::TooManyRequests {
+            message: format!(
+                "At most {} comments per hour, try again later",
+                throttled.limit
+            ),
+            retry_after_secs: throttled.retry_after_secs(),
+        })?;
+
+    if request.website.as_deref().is_some_and(|v| !v.trim().is_empty()) {
+        // Bots get the same answer as people, but nothing is stored
+        log::info!("Dropped comment on post {:?} with the honeypot filled in", post_id);
+        return Ok(HttpResponse::Accepted().json(SubmittedComment {
+            id: Uuid::new_v4(),
+            status: CommentStatus::Pending,
+        }));
+    }
+
+    data.get_post_by_id(&post_id)
+        .await
+        .map_err(ApiError::database("Failed to retrieve post"))?
+        .ok_or_else(|| ApiError::NotFound(format!("Post with ID {:?} not found", post_id)))?;
+
+    let comment = data
+        .insert_comment(&post_id, author_name, contact, body)
+        .await
+        .map_err(ApiError::database("Failed to store comment"))?;
+    log::info!("Comment {:?} on post {:?} waiting for moderation", comment.id, post_id);
+    Ok(HttpResponse::Accepted().json(SubmittedComment {
+        id: comment.id,
+        status: comment.status,
+    }))
+}
+
+/// Approved comments on a post, oldest first
+#[utoipa::path(
+    operation_id = "listPostingComments",
+    context_path = "/api",
+    tag = "Posting Service",
+    get,
+    path = "/postings/{id}/comments",
+    params(("id" = Uuid, Path, description = "ID of the post")),
+    responses(
+        (status = 200, description = "Approved comments", body = Vec<PublicComment>),
+        (status = 500, description = "Internal Server Error", body = crate::ErrorResponse)
+    )
+)]
+pub async fn list_post_comments(
+    path: web::Path<Uuid>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let comments = data
+        .list_approved_comments(&path)
+        .await
+        .map_err(ApiError::database("Failed to list comments"))?;
+    Ok(HttpResponse::Ok().json(comments))
+}
+
+/// Comments of all posts for moderation, with contact details (protected)
+#[utoipa::path(
+    operation_id = "listComments",
+    get,
+    path = "/api/admin/comments",
+    tag = "Admin",
+    params(CommentListQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Comments, newest first", body = Vec<Comment>),
+        (status = 400, description = "Unknown status or limit out of range"),
+        (status = 401, response = crate::UnauthorizedResponse)
+    )
+)]
+pub async fn list_comments(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<CommentListQuery>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_COMMENT_LIST_LIMIT);
+    if !(1..=MAX_COMMENT_LIST_LIMIT).contains(&limit) {
+        return ApiError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_COMMENT_LIST_LIMIT
+        ))
+        .error_response();
+    }
+
+    match state.list_comments(query.status, limit).await {
+        Ok(comments) => HttpResponse::Ok().json(comments),
+        Err(e) => ApiError::database("Failed to list comments")(e).error_response(),
+    }
+}
+
+/// Set the status of a comment, telling a missing comment apart from one
+/// that cannot move to `status`
+async fn moderate(state: &AppState, id: &Uuid, status: CommentStatus) -> Result<Comment, ApiError> {
+    if let Some(comment) = state
+        .moderate_comment(id, status)
+        .await
+        .map_err(ApiError::database("Failed to moderate comment"))?
+    {
+        log::info!("Comment {:?} {}", id, status.as_str());
+        return Ok(comment);
+    }
+
+    match state
+        .get_comment(id)
+        .await
+        .map_err(ApiError::database("Failed to moderate comment"))?
+    {
+        Some(comment) => Err(ApiError::Conflict(format!(
+            "Comment is already {}",
+            comment.status.as_str()
+        ))),
+        None => Err(ApiError::NotFound("Comment not found".to_string())),
+    }
+}
+
+/// Publish a pending or rejected comment (protected)
+#[utoipa::path(
+    operation_id = "approveComment",
+    post,
+    path = "/api/admin/comments/{id}/approve",
+    tag = "Admin",
+    params(("id" = Uuid, Path, description = "Comment ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Comment approved", body = Comment),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 404, description = "Comment not found"),
+        (status = 409, description = "Comment is already approved")
+    )
+)]
+pub async fn approve_comment(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    match moderate(&state, &path, CommentStatus::Approved).await {
+        Ok(comment) => HttpResponse::Ok().json(comment),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// Hide a pending or approved comment (protected)
+#[utoipa::path(
+    operation_id = "rejectComment",
+    post,
+    path = "/api/admin/comments/{id}/reject",
+    tag = "Admin",
+    params(("id" = Uuid, Path, description = "Comment ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Comment rejected", body = Comment),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 404, description = "Comment not found"),
+        (status = 409, description = "Comment is already rejected")
+    )
+)]
+pub async fn reject_comment(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    match moderate(&state, &path, CommentStatus::Rejected).await {
+        Ok(comment) => HttpResponse::Ok().json(comment),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// Remove a comment for good (protected)
+#[utoipa::path(
+    operation_id = "deleteComment",
+    delete,
+    path = "/api/admin/comments/{id}",
+    tag = "Admin",
+    params(("id" = Uuid, Path, description = "Comment ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Comment deleted"),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 404, description = "Comment not found")
+    )
+)]
+pub async fn delete_comment(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    match state.delete_comment(&path).await {
+        Ok(true) => HttpResponse::NoContent().finish(),
+        Ok(false) => ApiError::NotFound("Comment not found".to_string()).error_response(),
+        Err(e) => ApiError::database("Failed to delete comment")(e).error_response(),
+    }
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/postings/{id}/comments")
+            .route(web::get().to(list_post_comments))
+            .route(web::post().to(create_comment)),
+    )
+    .service(
+        web::scope("/admin/comments")
+            .service(web::resource("").route(web::get().to(list_comments)))
+            .service(web::resource("/{id}/approve").route(web::post().to(approve_comment)))
+            .service(web::resource("/{id}/reject").route(web::post().to(reject_comment)))
+            .service(web::resource("/{id}").route(web::delete().to(delete_comment))),
+    );
+}
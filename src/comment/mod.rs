@@ -0,0 +1,309 @@
+//! Resident comments under posts.
+//!
+//! Anyone can submit a comment with `POST /api/postings/{id}/comments`; it
+//! stays `pending` until an admin approves it under `/api/admin/comments`,
+//! and only approved comments are listed publicly or counted in a post's
+//! `comment_count`. Submissions are limited per client IP with the same
+//! sliding-window [`RateLimiter`] as MCP tool calls, and a hidden `website`
+//! field catches form-filling bots.
+
+pub mod handlers;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::cache::CacheKind;
+use crate::mcp::tools::rate_limit::{RateLimitConfig, RateLimiter};
+use crate::db::DbError;
+use crate::AppState;
+
+pub const DEFAULT_COMMENTS_PER_HOUR: usize = 5;
+pub const DEFAULT_MAX_COMMENT_LENGTH: usize = 2000;
+/// Longest accepted `author_name`, in characters
+pub const MAX_AUTHOR_NAME_LENGTH: usize = 100;
+/// Longest accepted `contact`, in characters
+pub const MAX_CONTACT_LENGTH: usize = 200;
+
+/// Name under which submissions are counted in the rate limiter
+const RATE_LIMIT_KEY: &str = "post_comment";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentConfig {
+    /// Comments accepted per client IP and hour
+    pub per_ip_per_hour: usize,
+    /// Longest accepted body, in characters
+    pub max_length: usize,
+}
+
+impl Default for CommentConfig {
+    fn default() -> Self {
+        Self {
+            per_ip_per_hour: DEFAULT_COMMENTS_PER_HOUR,
+            max_length: DEFAULT_MAX_COMMENT_LENGTH,
+        }
+    }
+}
+
+impl CommentConfig {
+    /// Load using a custom variable lookup
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let get = |key: &str| {
+            lookup(key)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let defaults = Self::default();
+
+        let per_ip_per_hour = get("COMMENTS_PER_HOUR")
+            .map(|v| crate::config::parse_positive("COMMENTS_PER_HOUR", &v))
+            .transpose()?
+            .unwrap_or(defaults.per_ip_per_hour);
+        let max_length = get("COMMENT_MAX_LENGTH")
+            .map(|v| crate::config::parse_positive("COMMENT_MAX_LENGTH", &v))
+            .transpose()?
+            .unwrap_or(defaults.max_length);
+
+        Ok(Self {
+            per_ip_per_hour,
+            max_length,
+        })
+    }
+
+    /// Limiter counting submissions per client IP
+    pub fn rate_limiter(&self) -> RateLimiter {
+        RateLimiter::new(RateLimitConfig {
+            tool_calls_per_hour: [(RATE_LIMIT_KEY.to_string(), self.per_ip_per_hour)].into(),
+            ..RateLimitConfig::default()
+        })
+    }
+}
+
+/// Where a comment is in moderation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum CommentStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl CommentStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+
+    /// Whether moderation may move a comment from `self` to `next`. Nothing
+    /// goes back to pending, and an approved comment can still be hidden by
+    /// rejecting it (or a rejected one approved on second thought).
+    pub fn can_become(self, next: CommentStatus) -> bool {
+        matches!(
+            (self, next),
+            (Self::Pending, Self::Approved)
+                | (Self::Pending, Self::Rejected)
+                | (Self::Approved, Self::Rejected)
+                | (Self::Rejected, Self::Approved)
+        )
+    }
+
+    /// Statuses that may move to `self`
+    fn sources(self) -> Vec<&'static str> {
+        [Self::Pending, Self::Approved, Self::Rejected]
+            .into_iter()
+            .filter(|status| status.can_become(self))
+            .map(Self::as_str)
+            .collect()
+    }
+}
+
+/// A comment as shown under a post
+#[derive(Debug, Clone, Serialize, ToSchema, sqlx::FromRow)]
+pub struct PublicComment {
+    pub id: Uuid,
+    #[schema(example = "Bu Siti")]
+    pub author_name: String,
+    #[schema(example = "Apakah kerja bakti juga dilakukan di RT 07?")]
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A comment with the fields only moderators see
+#[derive(Debug, Clone, Serialize, ToSchema, sqlx::FromRow)]
+pub struct Comment {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    #[schema(example = "Bu Siti")]
+    pub author_name: String,
+    /// Phone number or e-mail for a private reply
+    #[schema(example = "0812-3456-7890")]
+    pub contact: Option<String>,
+    #[schema(example = "Apakah kerja bakti juga dilakukan di RT 07?")]
+    pub body: String,
+    pub status: CommentStatus,
+    pub created_at: DateTime<Utc>,
+    /// When the status was last changed by an admin
+    pub moderated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateCommentRequest {
+    #[schema(example = "Bu Siti")]
+    pub author_name: String,
+    /// Not published
+    #[schema(example = "0812-3456-7890")]
+    pub contact: Option<String>,
+    #[schema(example = "Apakah kerja bakti juga dilakukan di RT 07?")]
+    pub body: String,
+    /// Leave empty. Hidden from people in the form, filled in by bots.
+    #[serde(default)]
+    pub website: Option<String>,
+}
+
+/// Response to a submission
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SubmittedComment {
+    pub id: Uuid,
+    /// Always `pending`: the comment is shown once an admin approves it
+    pub status: CommentStatus,
+}
+
+const COMMENT_COLUMNS: &str =
+    "id, post_id, author_name, contact, body, status, created_at, moderated_at";
+
+impl AppState {
+    /// Store a pending comment on `post_id`
+    pub async fn insert_comment(
+        &self,
+        post_id: &Uuid,
+        author_name: &str,
+        contact: Option<&str>,
+        body: &str,
+    ) -> Result<Comment, DbError> {
+        self.timed("insert_comment", async {
+            sqlx::query_as(&format!(
+                "INSERT INTO comments (post_id, author_name, contact, body)
+                 VALUES ($1, $2, $3, $4)
+                 RETURNING {}",
+                COMMENT_COLUMNS
+            ))
+            .bind(post_id)
+            .bind(author_name)
+            .bind(contact)
+            .bind(body)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    /// Approved comments on `post_id`, oldest first
+    pub async fn list_approved_comments(
+        &self,
+        post_id: &Uuid,
+    ) -> Result<Vec<PublicComment>, DbError> {
+        self.timed("list_approved_comments", async {
+            sqlx::query_as(
+                "SELECT id, author_name, body, created_at FROM comments
+                 WHERE post_id = $1 AND status = 'approved'
+                 ORDER BY created_at, id",
+            )
+            .bind(post_id)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    /// Comments for moderators, newest first, optionally only one status
+    pub async fn list_comments(
+        &self,
+        status: Option<CommentStatus>,
+        limit: i64,
+    ) -> Result<Vec<Comment>, DbError> {
+        self.timed("list_comments", async {
+            sqlx::query_as(&format!(
+                "SELECT {} FROM comments
+                 WHERE $1::text IS NULL OR status = $1
+                 ORDER BY created_at DESC, id
+                 LIMIT $2",
+                COMMENT_COLUMNS
+            ))
+            .bind(status.map(CommentStatus::as_str))
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    pub async fn get_comment(&self, id: &Uuid) -> Result<Option<Comment>, DbError> {
+        self.timed("get_comment", async {
+            sqlx::query_as(&format!("SELECT {} FROM comments WHERE id = $1", COMMENT_COLUMNS))
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    /// Move a comment to `status` if [`CommentStatus::can_become`] allows
+    /// it from its current status. `None` when the comment does not exist
+    /// or is in a status that cannot move to `status`.
+    pub async fn moderate_comment(
+        &self,
+        id: &Uuid,
+        status: CommentStatus,
+    ) -> Result<Option<Comment>, DbError> {
+        let comment = self
+            .timed("moderate_comment", async {
+                sqlx::query_as(&format!(
+                    "UPDATE comments SET status = $2, moderated_at = NOW()
+                     WHERE id = $1 AND status = ANY($3)
+                     RETURNING {}",
+                    COMMENT_COLUMNS
+                ))
+                .bind(id)
+                .bind(status.as_str())
+                .bind(status.sources())
+                .fetch_optional(&self.pool)
+                .await
+            })
+            .await?;
+
+        if comment.is_some() {
+            // Post lists carry the approved comment count
+            self.post_cache.invalidate_all();
+            self.refresh_cache_entries(CacheKind::Posts).await;
+        }
+        Ok(comment)
+    }
+
+    /// Returns whether the comment existed
+    pub async fn delete_comment(&self, id: &Uuid) -> Result<bool, DbError> {
+        let deleted = self
+            .timed("delete_comment", async {
+                sqlx::query("DELETE FROM comments WHERE id = $1")
+                    .bind(id)
+                    .execute(&self.pool)
+                    .await
+            })
+            .await?
+            .rows_affected()
+            > 0;
+
+        if deleted {
+            self.post_cache.invalidate_all();
+            self.refresh_cache_entries(CacheKind::Posts).await;
+        }
+        Ok(deleted)
+    }
+}
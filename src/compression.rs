@@ -0,0 +1,90 @@
+//! Response compression policy applied in front of `Compress`.
+//!
+//! `Compress` already leaves raster images and video alone. This middleware
+//! also skips other already-compressed formats, server-sent event streams
+//! and the asset upload routes by marking the response `Content-Encoding: identity`, which `Compress`
+//! respects. It must be registered before (inside) `Compress`.
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::http::Method;
+use actix_web::middleware::Next;
+use actix_web::Error;
+
+/// Content types that gain nothing from another compression pass
+const PRECOMPRESSED_TYPES: &[&str] = &[
+    "application/zip",
+    "application/gzip",
+    "application/x-gzip",
+    "application/x-bzip2",
+    "application/x-xz",
+    "application/x-7z-compressed",
+    "application/x-rar-compressed",
+    "application/vnd.rar",
+    "application/zstd",
+    "font/woff",
+    "font/woff2",
+];
+
+/// Returns true for content types that are already compressed
+pub fn is_precompressed(content_type: &str) -> bool {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    if mime == "image/svg+xml" {
+        return false;
+    }
+
+    mime.starts_with("image/")
+        || mime.starts_with("video/")
+        || mime.starts_with("audio/")
+        || PRECOMPRESSED_TYPES.contains(&mime.as_str())
+}
+
+/// Returns true for server-sent event streams. An encoder buffers its
+/// output, so events and keepalives would sit in it instead of reaching
+/// the client.
+pub fn is_event_stream(content_type: &str) -> bool {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .eq_ignore_ascii_case("text/event-stream")
+}
+
+/// Returns true for the asset upload routes, whose responses are never compressed
+pub fn is_upload_route(method: &Method, path: &str) -> bool {
+    method == Method::POST && (path == "/api/assets" || path.starts_with("/api/assets/posts/"))
+}
+
+/// Marks responses that `Compress` should pass through unencoded
+pub async fn compression_policy(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let upload = is_upload_route(req.method(), req.path());
+    let mut res = next.call(req).await?;
+
+    let headers = res.headers();
+    let skip = !headers.contains_key(header::CONTENT_ENCODING)
+        && (upload
+            || headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|ct| ct.to_str().ok())
+                .is_some_and(|ct| is_precompressed(ct) || is_event_stream(ct)));
+
+    if skip {
+        res.headers_mut().insert(
+            header::CONTENT_ENCODING,
+            HeaderValue::from_static("identity"),
+        );
+    }
+
+    Ok(res)
+}
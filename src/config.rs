@@ -0,0 +1,556 @@
+//! Application configuration loaded from environment variables
+//!
+//! `AppConfig::from_env` is the single place the server reads its
+//! environment. Each section keeps its own `from_lookup` so it can be
+//! tested in isolation.
+
+use actix_cors::Cors;
+use actix_web::http::header;
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::asset::gallery::GalleryConfig;
+use crate::asset::heic::HeicConfig;
+use crate::auth::{McpAuth, MetricsAuth};
+use crate::comment::CommentConfig;
+use crate::db::pool::DbPoolConfig;
+use crate::generated_documents::DocumentLogConfig;
+use crate::http_client::HttpClientConfig;
+use crate::maintenance::MaintenanceConfig;
+use crate::mcp::generators::GenerationConfig;
+use crate::mcp::session::SessionConfig;
+use crate::mcp::tools::rate_limit::RateLimitConfig;
+use crate::openapi_servers::OpenApiServers;
+use crate::posting::sanitize::SanitizeConfig;
+use crate::storage::StorageConfig;
+use crate::storage_usage::StorageUsageConfig;
+use crate::submission::SubmissionConfig;
+use crate::timezone::TimezoneConfig;
+
+const DEFAULT_HOST: &str = "0.0.0.0";
+const DEFAULT_PORT: u16 = 8080;
+const DEFAULT_MAX_CONNECTIONS: usize = 25000;
+const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_CORS_ORIGINS: &[&str] = &[
+    "https://cakung-barat-server-1065513777845.asia-southeast2.run.app",
+    "https://tsfarizi.github.io",
+    "http://localhost:5173",
+    "http://localhost:3000",
+    "http://localhost:8080",
+    "http://127.0.0.1:8080",
+];
+
+const DEFAULT_JWT_SECRET: &str = "cakung-barat-jwt-secret-change-in-production";
+
+/// Every configuration problem found while loading, reported together
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid configuration: {}", .0.join("; "))]
+pub struct ConfigError(pub Vec<String>);
+
+/// Complete server configuration, parsed once at startup
+#[derive(Debug, Clone)]
+pub struct AppConfig {
+    pub database: DatabaseConfig,
+    pub storage: StorageConfig,
+    pub server: ServerConfig,
+    pub jwt: JwtConfig,
+    pub upload: UploadConfig,
+    pub gallery: GalleryConfig,
+    /// Length and rate limits of resident comments
+    pub comments: CommentConfig,
+    /// Length and rate limits of the complaint form
+    pub submissions: SubmissionConfig,
+    /// Tags kept in rendered post excerpts
+    pub sanitize: SanitizeConfig,
+    pub http: HttpClientConfig,
+    pub maintenance: MaintenanceConfig,
+    pub storage_usage: StorageUsageConfig,
+    /// Concurrency cap for MCP document generation
+    pub generation: GenerationConfig,
+    /// Credentials accepted by the MCP endpoint
+    pub mcp_auth: McpAuth,
+    /// Keepalive and idle timeout of MCP sessions
+    pub mcp_sessions: SessionConfig,
+    /// Hourly quotas on MCP tool calls
+    pub mcp_rate_limits: RateLimitConfig,
+    /// None leaves `/metrics` open
+    pub metrics_auth: Option<MetricsAuth>,
+    /// Salt for NIK hashes in the generated-letter log
+    pub documents: DocumentLogConfig,
+    /// Zone of post dates and dates on generated letters
+    pub timezone: TimezoneConfig,
+}
+
+impl AppConfig {
+    /// Load from process environment
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// Load every section using a custom variable lookup. Errors from all
+    /// sections are collected instead of stopping at the first one.
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, ConfigError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut errors = Vec::new();
+
+        let database = collect(DatabaseConfig::from_lookup(&lookup), &mut errors);
+        let storage = collect(StorageConfig::from_lookup(&lookup), &mut errors);
+        let server = collect(ServerConfig::from_lookup(&lookup), &mut errors);
+        let jwt = collect(JwtConfig::from_lookup(&lookup), &mut errors);
+        let upload = collect(UploadConfig::from_lookup(&lookup), &mut errors);
+        let gallery = collect(GalleryConfig::from_lookup(&lookup), &mut errors);
+        let comments = collect(CommentConfig::from_lookup(&lookup), &mut errors);
+        let submissions = collect(SubmissionConfig::from_lookup(&lookup), &mut errors);
+        let sanitize = collect(SanitizeConfig::from_lookup(&lookup), &mut errors);
+        let http = collect(HttpClientConfig::from_lookup(&lookup), &mut errors);
+        let maintenance = collect(MaintenanceConfig::from_lookup(&lookup), &mut errors);
+        let storage_usage = collect(StorageUsageConfig::from_lookup(&lookup), &mut errors);
+        let generation = collect(GenerationConfig::from_lookup(&lookup), &mut errors);
+        let mcp_auth = collect(McpAuth::from_lookup(&lookup), &mut errors);
+        let mcp_sessions = collect(SessionConfig::from_lookup(&lookup), &mut errors);
+        let mcp_rate_limits = collect(RateLimitConfig::from_lookup(&lookup), &mut errors);
+        let metrics_auth = collect(MetricsAuth::from_lookup(&lookup), &mut errors);
+        let documents = DocumentLogConfig::from_lookup(&lookup);
+        let timezone = collect(TimezoneConfig::from_lookup(&lookup), &mut errors);
+
+        match (
+            database,
+            storage,
+            server,
+            jwt,
+            upload,
+            gallery,
+            comments,
+            submissions,
+            sanitize,
+            http,
+            maintenance,
+            storage_usage,
+            generation,
+            mcp_auth,
+            mcp_sessions,
+            mcp_rate_limits,
+            metrics_auth,
+            timezone,
+        ) {
+            (
+                Some(database),
+                Some(storage),
+                Some(server),
+                Some(jwt),
+                Some(upload),
+                Some(gallery),
+                Some(comments),
+                Some(submissions),
+                Some(sanitize),
+                Some(http),
+                Some(maintenance),
+                Some(storage_usage),
+                Some(generation),
+                Some(mcp_auth),
+                Some(mcp_sessions),
+                Some(mcp_rate_limits),
+                Some(metrics_auth),
+                Some(timezone),
+            ) => Ok(Self {
+                database,
+                storage,
+                server,
+                jwt,
+                upload,
+                gallery,
+                comments,
+                submissions,
+                sanitize,
+                http,
+                maintenance,
+                storage_usage,
+                generation,
+                mcp_auth,
+                mcp_sessions,
+                mcp_rate_limits,
+                metrics_auth,
+                documents,
+                timezone,
+            }),
+            _ => Err(ConfigError(errors)),
+        }
+    }
+}
+
+fn collect<T>(result: Result<T, String>, errors: &mut Vec<String>) -> Option<T> {
+    result.map_err(|e| errors.push(e)).ok()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub pool: DbPoolConfig,
+}
+
+impl DatabaseConfig {
+    /// Requires `SUPABASE_DATABASE_URL`; pool settings use `DbPoolConfig`
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let url = lookup("SUPABASE_DATABASE_URL")
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+        let pool = DbPoolConfig::from_lookup(&lookup);
+
+        match (url, pool) {
+            (Some(url), Ok(pool)) => Ok(Self { url, pool }),
+            (None, Ok(_)) => Err("SUPABASE_DATABASE_URL must be set".to_string()),
+            (Some(_), Err(e)) => Err(e),
+            (None, Err(e)) => Err(format!("SUPABASE_DATABASE_URL must be set; {}", e)),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct JwtConfig {
+    pub secret: String,
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        Self {
+            secret: DEFAULT_JWT_SECRET.to_string(),
+        }
+    }
+}
+
+impl fmt::Debug for JwtConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JwtConfig")
+            .field("secret", &"<redacted>")
+            .finish()
+    }
+}
+
+impl JwtConfig {
+    /// `JWT_SECRET` is optional; the built-in default is only meant for
+    /// local development.
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        Ok(lookup("JWT_SECRET")
+            .filter(|v| !v.trim().is_empty())
+            .map(|secret| Self { secret })
+            .unwrap_or_default())
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.secret == DEFAULT_JWT_SECRET
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadConfig {
+    /// Largest accepted file in an upload, in bytes. None accepts any size.
+    pub max_file_size: Option<usize>,
+    /// JPEG conversion of HEIC photos
+    pub heic: HeicConfig,
+    /// Rotate JPEG photos upright and remove their EXIF metadata
+    pub strip_exif: bool,
+}
+
+impl Default for UploadConfig {
+    fn default() -> Self {
+        Self {
+            max_file_size: None,
+            heic: HeicConfig::default(),
+            strip_exif: true,
+        }
+    }
+}
+
+impl UploadConfig {
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let max_file_size = match lookup("MAX_UPLOAD_SIZE").map(|v| v.trim().to_string()) {
+            Some(v) if !v.is_empty() => Some(parse_positive("MAX_UPLOAD_SIZE", &v)?),
+            _ => None,
+        };
+        let strip_exif = match lookup("STRIP_EXIF").map(|v| v.trim().to_string()) {
+            Some(v) if !v.is_empty() => parse_bool("STRIP_EXIF", &v)?,
+            _ => true,
+        };
+        Ok(Self {
+            max_file_size,
+            heic: HeicConfig::from_lookup(&lookup)?,
+            strip_exif,
+        })
+    }
+
+    /// Error message for a file over the size limit
+    pub fn check_size(&self, filename: &str, size: usize) -> Result<(), String> {
+        match self.max_file_size {
+            Some(limit) if size > limit => Err(format!(
+                "File '{}' is {} bytes, the limit is {} bytes",
+                filename, size, limit
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Allowed CORS origins
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CorsOrigins {
+    /// `*` - any origin is allowed
+    Any,
+    List(Vec<String>),
+}
+
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    /// Worker count, None uses actix default (number of physical CPUs)
+    pub workers: Option<usize>,
+    pub max_connections: usize,
+    pub cors_allowed_origins: CorsOrigins,
+    pub cors_allow_credentials: bool,
+    /// Seconds to let in-flight requests finish after a stop signal
+    pub shutdown_timeout_secs: u64,
+    /// Maximum JSON request body size in bytes
+    pub json_payload_limit: usize,
+    /// Start with writes disabled, see `crate::read_only`
+    pub read_only: bool,
+    /// Log failed API calls with their request body, see
+    /// `crate::http_debug_log`
+    pub debug_http_log: bool,
+    /// Refuse to start when the storage health check fails, instead of
+    /// logging a warning
+    pub storage_strict_startup: bool,
+    /// Externally reachable origin of this server, without trailing slash
+    pub public_base_url: Option<String>,
+    /// Let the storage backend build the `public_url` of assets instead of
+    /// prefixing `public_base_url`
+    pub public_url_from_storage: bool,
+    /// Directory of the built frontend served at `/`, see `crate::frontend`.
+    /// None serves the API only.
+    pub frontend_dist_path: Option<PathBuf>,
+    /// `servers` of the OpenAPI document, see `crate::openapi_servers`
+    pub openapi_servers: OpenApiServers,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: DEFAULT_HOST.to_string(),
+            port: DEFAULT_PORT,
+            workers: None,
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            cors_allowed_origins: CorsOrigins::List(
+                DEFAULT_CORS_ORIGINS.iter().map(|o| o.to_string()).collect(),
+            ),
+            cors_allow_credentials: true,
+            shutdown_timeout_secs: DEFAULT_SHUTDOWN_TIMEOUT_SECS,
+            json_payload_limit: crate::error_handlers::DEFAULT_JSON_PAYLOAD_LIMIT,
+            read_only: false,
+            debug_http_log: false,
+            storage_strict_startup: false,
+            public_base_url: None,
+            public_url_from_storage: false,
+            frontend_dist_path: None,
+            openapi_servers: OpenApiServers::fallback(None, DEFAULT_PORT),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Load from process environment
+    pub fn from_env() -> Result<Self, String> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// Load using a custom variable lookup. `CORS_ALLOWED_ORIGINS=*` is
+    /// rejected unless `CORS_ALLOW_CREDENTIALS=false`.
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let get = |key: &str| {
+            lookup(key)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let defaults = Self::default();
+
+        let host = get("HOST").unwrap_or(defaults.host);
+        let port = match get("PORT") {
+            Some(v) => v
+                .parse::<u16>()
+                .map_err(|_| format!("PORT must be a valid port number, got '{}'", v))?,
+            None => defaults.port,
+        };
+        let workers = match get("WORKERS") {
+            Some(v) => Some(parse_positive("WORKERS", &v)?),
+            None => defaults.workers,
+        };
+        let max_connections = match get("MAX_CONNECTIONS") {
+            Some(v) => parse_positive("MAX_CONNECTIONS", &v)?,
+            None => defaults.max_connections,
+        };
+        let cors_allowed_origins = match get("CORS_ALLOWED_ORIGINS") {
+            Some(v) => parse_origins(&v)?,
+            None => defaults.cors_allowed_origins,
+        };
+        let cors_allow_credentials = match get("CORS_ALLOW_CREDENTIALS") {
+            Some(v) => parse_bool("CORS_ALLOW_CREDENTIALS", &v)?,
+            None => defaults.cors_allow_credentials,
+        };
+        if cors_allowed_origins == CorsOrigins::Any && cors_allow_credentials {
+            // Browsers refuse credentials for `*`, and echoing every origin
+            // instead would let any site make authenticated requests
+            return Err("CORS_ALLOWED_ORIGINS=* requires CORS_ALLOW_CREDENTIALS=false".to_string());
+        }
+        let shutdown_timeout_secs = match get("SHUTDOWN_TIMEOUT_SECS") {
+            Some(v) => v.parse::<u64>().map_err(|_| {
+                format!(
+                    "SHUTDOWN_TIMEOUT_SECS must be a non-negative integer, got '{}'",
+                    v
+                )
+            })?,
+            None => defaults.shutdown_timeout_secs,
+        };
+        let json_payload_limit = match get("JSON_PAYLOAD_LIMIT") {
+            Some(v) => parse_positive("JSON_PAYLOAD_LIMIT", &v)?,
+            None => defaults.json_payload_limit,
+        };
+        let read_only = match get("READ_ONLY") {
+            Some(v) => parse_bool("READ_ONLY", &v)?,
+            None => defaults.read_only,
+        };
+        let debug_http_log = match get("DEBUG_HTTP_LOG") {
+            Some(v) => parse_bool("DEBUG_HTTP_LOG", &v)?,
+            None => defaults.debug_http_log,
+        };
+        let storage_strict_startup = match get("STORAGE_STRICT_STARTUP") {
+            Some(v) => parse_bool("STORAGE_STRICT_STARTUP", &v)?,
+            None => defaults.storage_strict_startup,
+        };
+        let public_base_url = match get("PUBLIC_BASE_URL") {
+            Some(v) if v.starts_with("http://") || v.starts_with("https://") => {
+                Some(v.trim_end_matches('/').to_string())
+            }
+            Some(v) => {
+                return Err(format!(
+                    "PUBLIC_BASE_URL must start with http:// or https://, got '{}'",
+                    v
+                ))
+            }
+            None => defaults.public_base_url,
+        };
+        let public_url_from_storage = match get("PUBLIC_URL_FROM_STORAGE") {
+            Some(v) => parse_bool("PUBLIC_URL_FROM_STORAGE", &v)?,
+            None => defaults.public_url_from_storage,
+        };
+        let frontend_dist_path = get("FRONTEND_DIST_PATH")
+            .map(PathBuf::from)
+            .or(defaults.frontend_dist_path);
+        let openapi_servers = match get("OPENAPI_SERVERS") {
+            Some(v) => OpenApiServers::parse(&v)?,
+            None => None,
+        }
+        .unwrap_or_else(|| OpenApiServers::fallback(public_base_url.as_deref(), port));
+
+        Ok(Self {
+            host,
+            port,
+            workers,
+            max_connections,
+            cors_allowed_origins,
+            cors_allow_credentials,
+            shutdown_timeout_secs,
+            json_payload_limit,
+            read_only,
+            debug_http_log,
+            storage_strict_startup,
+            public_base_url,
+            public_url_from_storage,
+            frontend_dist_path,
+            openapi_servers,
+        })
+    }
+
+    /// Build the CORS middleware from this configuration
+    pub fn cors(&self) -> Cors {
+        let mut cors = match &self.cors_allowed_origins {
+            CorsOrigins::Any => Cors::default().allow_any_origin(),
+            CorsOrigins::List(origins) => origins
+                .iter()
+                .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin)),
+        };
+
+        cors = cors
+            .allowed_methods(vec!["GET", "POST", "PUT", "DELETE", "OPTIONS"])
+            .allowed_headers(vec![
+                header::AUTHORIZATION,
+                header::ACCEPT,
+                header::CONTENT_TYPE,
+            ])
+            // Streamable HTTP MCP clients in the browser send and read it
+            .allowed_header(crate::mcp::streamable::MCP_SESSION_HEADER)
+            .expose_headers([crate::mcp::streamable::MCP_SESSION_HEADER])
+            .max_age(3600);
+
+        if self.cors_allow_credentials && self.cors_allowed_origins != CorsOrigins::Any {
+            cors = cors.supports_credentials();
+        }
+        cors
+    }
+}
+
+pub(crate) fn parse_positive(key: &str, value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!(
+            "{} must be a positive integer, got '{}'",
+            key, value
+        )),
+    }
+}
+
+pub(crate) fn parse_bool(key: &str, value: &str) -> Result<bool, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" => Ok(true),
+        "false" | "0" | "no" => Ok(false),
+        _ => Err(format!("{} must be true or false, got '{}'", key, value)),
+    }
+}
+
+/// Parse a comma-separated origin list. `*` anywhere in the list allows any origin.
+pub fn parse_origins(value: &str) -> Result<CorsOrigins, String> {
+    let origins: Vec<String> = value
+        .split(',')
+        .map(|o| o.trim().trim_end_matches('/').to_string())
+        .filter(|o| !o.is_empty())
+        .collect();
+
+    if origins.is_empty() {
+        return Err("CORS_ALLOWED_ORIGINS must contain at least one origin".to_string());
+    }
+    if origins.iter().any(|o| o == "*") {
+        return Ok(CorsOrigins::Any);
+    }
+    if let Some(invalid) = origins
+        .iter()
+        .find(|o| !o.starts_with("http://") && !o.starts_with("https://"))
+    {
+        return Err(format!(
+            "CORS_ALLOWED_ORIGINS entry '{}' must start with http:// or https://",
+            invalid
+        ));
+    }
+
+    Ok(CorsOrigins::List(origins))
+}
@@ -1,11 +1,12 @@
 //! Admin database operations for authentication
 
-use super::AppState;
+use super::{AppState, DbError};
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
 impl AppState {
     /// Get count of admins in database
-    pub async fn get_admin_count(&self) -> Result<i64, sqlx::Error> {
+    pub async fn get_admin_count(&self) -> Result<i64, DbError> {
         let result = sqlx::query_scalar!("SELECT COUNT(*) FROM admins")
             .fetch_one(&self.pool)
             .await?;
@@ -16,7 +17,7 @@ impl AppState {
     pub async fn get_admin_by_username(
         &self,
         username: &str,
-    ) -> Result<Option<crate::auth::model::Admin>, sqlx::Error> {
+    ) -> Result<Option<crate::auth::model::Admin>, DbError> {
         sqlx::query_as!(
             crate::auth::model::Admin,
             "SELECT id, username, password_hash, display_name, refresh_token, created_at, updated_at, created_by FROM admins WHERE username = $1",
@@ -24,13 +25,14 @@ impl AppState {
         )
         .fetch_optional(&self.pool)
         .await
+        .map_err(DbError::from)
     }
 
     /// Get admin by refresh token
     pub async fn get_admin_by_refresh_token(
         &self,
         refresh_token: &str,
-    ) -> Result<Option<crate::auth::model::Admin>, sqlx::Error> {
+    ) -> Result<Option<crate::auth::model::Admin>, DbError> {
         sqlx::query_as!(
             crate::auth::model::Admin,
             "SELECT id, username, password_hash, display_name, refresh_token, created_at, updated_at, created_by FROM admins WHERE refresh_token = $1",
@@ -38,6 +40,7 @@ impl AppState {
         )
         .fetch_optional(&self.pool)
         .await
+        .map_err(DbError::from)
     }
 
     /// Create new admin
@@ -47,7 +50,7 @@ impl AppState {
         password_hash: &str,
         display_name: Option<&str>,
         created_by: Option<Uuid>,
-    ) -> Result<crate::auth::model::Admin, sqlx::Error> {
+    ) -> Result<crate::auth::model::Admin, DbError> {
         sqlx::query_as!(
             crate::auth::model::Admin,
             r#"
@@ -62,6 +65,7 @@ impl AppState {
         )
         .fetch_one(&self.pool)
         .await
+        .map_err(DbError::from)
     }
 
     /// Update admin's refresh token (invalidates previous sessions)
@@ -69,9 +73,9 @@ impl AppState {
         &self,
         admin_id: &Uuid,
         refresh_token: &str,
-    ) -> Result<(), sqlx::Error> {
+    ) -> Result<(), DbError> {
         sqlx::query!(
-            "UPDATE admins SET refresh_token = $1, updated_at = NOW() WHERE id = $2",
+            "UPDATE admins SET refresh_token = $1, refresh_token_issued_at = NOW(), updated_at = NOW() WHERE id = $2",
             refresh_token,
             admin_id
         )
@@ -80,18 +84,71 @@ impl AppState {
         Ok(())
     }
 
+    /// Update admin username and/or display name, leaving omitted fields untouched
+    pub async fn update_admin(
+        &self,
+        admin_id: &Uuid,
+        username: Option<&str>,
+        display_name: Option<&str>,
+    ) -> Result<Option<crate::auth::model::Admin>, DbError> {
+        sqlx::query_as!(
+            crate::auth::model::Admin,
+            r#"
+            UPDATE admins
+            SET username = COALESCE($2, username),
+                display_name = COALESCE($3, display_name),
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING id, username, password_hash, display_name, refresh_token, created_at, updated_at, created_by
+            "#,
+            admin_id,
+            username,
+            display_name
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DbError::from)
+    }
+
+    /// Clear admin's refresh token (logout)
+    pub async fn clear_admin_refresh_token(&self, admin_id: &Uuid) -> Result<(), DbError> {
+        sqlx::query!(
+            "UPDATE admins SET refresh_token = NULL, refresh_token_issued_at = NULL, updated_at = NOW() WHERE id = $1",
+            admin_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Get stored session metadata for an admin: whether a refresh token is
+    /// stored and when it was issued
+    pub async fn get_admin_session(
+        &self,
+        admin_id: &Uuid,
+    ) -> Result<Option<(bool, Option<DateTime<Utc>>)>, DbError> {
+        let row = sqlx::query!(
+            r#"SELECT refresh_token IS NOT NULL AS "has_refresh_token!", refresh_token_issued_at FROM admins WHERE id = $1"#,
+            admin_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|r| (r.has_refresh_token, r.refresh_token_issued_at)))
+    }
+
     /// Get all admins
-    pub async fn get_all_admins(&self) -> Result<Vec<crate::auth::model::Admin>, sqlx::Error> {
+    pub async fn get_all_admins(&self) -> Result<Vec<crate::auth::model::Admin>, DbError> {
         sqlx::query_as!(
             crate::auth::model::Admin,
             "SELECT id, username, password_hash, display_name, refresh_token, created_at, updated_at, created_by FROM admins ORDER BY created_at"
         )
         .fetch_all(&self.pool)
         .await
+        .map_err(DbError::from)
     }
 
     /// Delete admin by id
-    pub async fn delete_admin(&self, admin_id: &Uuid) -> Result<bool, sqlx::Error> {
+    pub async fn delete_admin(&self, admin_id: &Uuid) -> Result<bool, DbError> {
         let result = sqlx::query!("DELETE FROM admins WHERE id = $1", admin_id)
             .execute(&self.pool)
             .await?;
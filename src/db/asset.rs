@@ -1,68 +1,323 @@
 //! Asset database operations
 
-use super::AppState;
+use super::{AppState, DbError};
 use uuid::Uuid;
 
 impl AppState {
     pub async fn get_asset_by_id(
         &self,
         id: &Uuid,
-    ) -> Result<Option<crate::asset::models::Asset>, sqlx::Error> {
-        sqlx::query_as!(crate::asset::models::Asset, "SELECT id, name, filename, url, description, created_at, updated_at FROM assets WHERE id = $1", id)
-            .fetch_optional(&self.pool)
+    ) -> Result<Option<crate::asset::models::Asset>, DbError> {
+        self.timed("get_asset_by_id", async {
+            sqlx::query_as!(crate::asset::models::Asset, "SELECT id, name, filename, url, description, width, height, created_at, updated_at FROM assets WHERE id = $1", id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    pub async fn get_all_assets(&self) -> Result<Vec<crate::asset::models::Asset>, DbError> {
+        self.guarded(self.timed("get_all_assets", async {
+            sqlx::query_as!(crate::asset::models::Asset, "SELECT id, name, filename, url, description, width, height, created_at, updated_at FROM assets ORDER BY created_at DESC")
+                .fetch_all(&self.pool)
+                .await
+        }))
+        .await
+    }
+
+    /// Assets not linked to any folder, newest first. Listed as the virtual
+    /// `others` folder.
+    pub async fn get_unlinked_assets(&self) -> Result<Vec<crate::asset::models::Asset>, DbError> {
+        self.timed("get_unlinked_assets", async {
+            sqlx::query_as(
+                r#"
+                SELECT id, name, filename, url, description, width, height, created_at, updated_at
+                FROM assets a
+                WHERE NOT EXISTS (SELECT 1 FROM asset_folders af WHERE af.asset_id = a.id)
+                ORDER BY created_at DESC
+                "#,
+            )
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    /// Ids of the assets listed under the virtual `others` folder
+    pub async fn get_unlinked_asset_ids(&self) -> Result<Vec<Uuid>, DbError> {
+        self.timed("get_unlinked_asset_ids", async {
+            sqlx::query_scalar(
+                r#"
+                SELECT id
+                FROM assets a
+                WHERE NOT EXISTS (SELECT 1 FROM asset_folders af WHERE af.asset_id = a.id)
+                ORDER BY created_at DESC
+                "#,
+            )
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    /// Number of assets listed under the virtual `others` folder
+    pub async fn count_unlinked_assets(&self) -> Result<i64, DbError> {
+        self.timed("count_unlinked_assets", async {
+            sqlx::query_scalar(
+                r#"
+                SELECT COUNT(*)
+                FROM assets a
+                WHERE NOT EXISTS (SELECT 1 FROM asset_folders af WHERE af.asset_id = a.id)
+                "#,
+            )
+            .fetch_one(&self.pool)
             .await
+        })
+        .await
     }
 
-    pub async fn get_all_assets(&self) -> Result<Vec<crate::asset::models::Asset>, sqlx::Error> {
-        sqlx::query_as!(crate::asset::models::Asset, "SELECT id, name, filename, url, description, created_at, updated_at FROM assets ORDER BY created_at DESC")
+    /// Assets linked to the folder `folder_name`, newest first. `None` when
+    /// there is no such folder.
+    pub async fn get_folder_assets(
+        &self,
+        folder_name: &str,
+    ) -> Result<Option<Vec<crate::asset::models::Asset>>, DbError> {
+        self.timed("get_folder_assets", async {
+            let Some(folder_id) =
+                sqlx::query_scalar::<_, Uuid>("SELECT id FROM folders WHERE name = $1")
+                    .bind(folder_name)
+                    .fetch_optional(&self.pool)
+                    .await?
+            else {
+                return Ok(None);
+            };
+            let assets = sqlx::query_as(
+                r#"
+                SELECT a.id, a.name, a.filename, a.url, a.description, a.width, a.height,
+                       a.created_at, a.updated_at
+                FROM assets a
+                JOIN asset_folders af ON af.asset_id = a.id
+                WHERE af.folder_id = $1
+                ORDER BY a.created_at DESC
+                "#,
+            )
+            .bind(folder_id)
             .fetch_all(&self.pool)
+            .await?;
+            Ok(Some(assets))
+        })
+        .await
+    }
+
+    /// Number of assets linked to the folder `folder_name`. `None` when
+    /// there is no such folder.
+    pub async fn count_folder_assets(&self, folder_name: &str) -> Result<Option<i64>, DbError> {
+        self.timed("count_folder_assets", async {
+            sqlx::query_scalar(
+                r#"
+                SELECT COUNT(af.asset_id)
+                FROM folders f
+                LEFT JOIN asset_folders af ON af.folder_id = f.id
+                WHERE f.name = $1
+                GROUP BY f.id
+                "#,
+            )
+            .bind(folder_name)
+            .fetch_optional(&self.pool)
             .await
+        })
+        .await
     }
 
     #[allow(dead_code)]
     pub async fn get_assets_by_ids(
         &self,
         ids: &Vec<Uuid>,
-    ) -> Result<Vec<crate::asset::models::Asset>, sqlx::Error> {
+    ) -> Result<Vec<crate::asset::models::Asset>, DbError> {
         if ids.is_empty() {
             return Ok(Vec::new());
         }
 
-        sqlx::query_as!(crate::asset::models::Asset, "SELECT id, name, filename, url, description, created_at, updated_at FROM assets WHERE id = ANY($1)", ids)
+        self.timed("get_assets_by_ids", async {
+            sqlx::query_as!(crate::asset::models::Asset, "SELECT id, name, filename, url, description, width, height, created_at, updated_at FROM assets WHERE id = ANY($1)", ids)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    /// Those of `ids` that are submission attachments, readable by admins
+    /// only. Checked through both the submission and its folder, so
+    /// relinking the asset elsewhere does not make it public.
+    pub async fn get_private_asset_ids(&self, ids: &[Uuid]) -> Result<Vec<Uuid>, DbError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.timed("get_private_asset_ids", async {
+            sqlx::query_scalar(
+                r#"
+                SELECT af.asset_id
+                FROM asset_folders af
+                JOIN folders f ON f.id = af.folder_id
+                WHERE af.asset_id = ANY($1) AND f.name LIKE $2
+                UNION
+                SELECT attachment_asset_id
+                FROM submissions
+                WHERE attachment_asset_id = ANY($1)
+                "#,
+            )
+            .bind(ids)
+            .bind(format!(
+                "{}%",
+                crate::asset::folder_name::SUBMISSION_FOLDER_PREFIX
+            ))
             .fetch_all(&self.pool)
             .await
+        })
+        .await
     }
 
-    pub async fn insert_asset(
+    /// One page of the assets linked to `folders`, newest first, and the
+    /// number of assets across all pages. An asset in two of the folders is
+    /// listed under each.
+    pub async fn get_gallery_assets(
         &self,
-        asset: &crate::asset::models::Asset,
-    ) -> Result<(), sqlx::Error> {
-        sqlx::query!(
-            r#"
-            INSERT INTO assets (id, name, filename, url, description, created_at, updated_at)
-             VALUES ($1, $2, $3, $4, $5, $6, $7)
+        folders: &[String],
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<crate::asset::gallery::GalleryAsset>, i64), DbError> {
+        self.timed("get_gallery_assets", async {
+            let assets = sqlx::query_as(
+                r#"
+                SELECT f.name AS folder, a.id, a.name, a.filename, a.url, a.description,
+                       a.width, a.height, a.created_at, a.updated_at
+                FROM assets a
+                JOIN asset_folders af ON af.asset_id = a.id
+                JOIN folders f ON f.id = af.folder_id
+                WHERE lower(f.name) = ANY($1)
+                ORDER BY a.created_at DESC NULLS LAST, a.id, f.name
+                LIMIT $2 OFFSET $3
+                "#,
+            )
+            .bind(folders)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+            let total = sqlx::query_scalar(
+                r#"
+                SELECT COUNT(*)
+                FROM asset_folders af
+                JOIN folders f ON f.id = af.folder_id
+                WHERE lower(f.name) = ANY($1)
+                "#,
+            )
+            .bind(folders)
+            .fetch_one(&self.pool)
+            .await?;
+            Ok((assets, total))
+        })
+        .await
+    }
+
+    pub async fn insert_asset(&self, asset: &crate::asset::models::Asset) -> Result<(), DbError> {
+        self.timed("insert_asset", async {
+            sqlx::query!(
+                r#"
+            INSERT INTO assets (id, name, filename, url, description, width, height, created_at, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
              ON CONFLICT (id) DO UPDATE
-             SET name = $2, filename = $3, url = $4, description = $5, updated_at = $7
+             SET name = $2, filename = $3, url = $4, description = $5, width = $6, height = $7, updated_at = $9
             "#,
-            asset.id,
-            &asset.name,
-            &asset.filename,
-            &asset.url,
-            asset.description.as_deref(),
-            asset.created_at,
-            asset.updated_at
-        )
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
+                asset.id,
+                &asset.name,
+                &asset.filename,
+                &asset.url,
+                asset.description.as_deref(),
+                asset.width,
+                asset.height,
+                asset.created_at,
+                asset.updated_at
+            )
+            .execute(&self.pool)
+            .await?;
+
+            Ok(())
+        })
+        .await
     }
 
-    pub async fn delete_asset(&self, id: &Uuid) -> Result<(), sqlx::Error> {
-        sqlx::query!("DELETE FROM assets WHERE id = $1", id)
+    /// Up to `limit` assets with no recorded size whose id sorts after
+    /// `after`, in id order. Pages through the rows for the dimension
+    /// backfill; start with `Uuid::nil()`.
+    pub async fn get_assets_without_dimensions(
+        &self,
+        after: Uuid,
+        limit: i64,
+    ) -> Result<Vec<crate::asset::models::Asset>, DbError> {
+        self.timed("get_assets_without_dimensions", async {
+            sqlx::query_as(
+                r#"
+                SELECT id, name, filename, url, description, width, height, created_at, updated_at
+                FROM assets
+                WHERE (width IS NULL OR height IS NULL) AND id > $1
+                ORDER BY id
+                LIMIT $2
+                "#,
+            )
+            .bind(after)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    pub async fn set_asset_dimensions(
+        &self,
+        id: &Uuid,
+        width: i32,
+        height: i32,
+    ) -> Result<(), DbError> {
+        self.timed("set_asset_dimensions", async {
+            sqlx::query("UPDATE assets SET width = $2, height = $3 WHERE id = $1")
+                .bind(id)
+                .bind(width)
+                .bind(height)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Replace absolute URLs written by older versions with the relative
+    /// `/assets/serve/...` path. Returns the number of rows changed.
+    pub async fn relativize_asset_urls(&self) -> Result<u64, DbError> {
+        self.timed("relativize_asset_urls", async {
+            let result = sqlx::query(
+                r#"
+                UPDATE assets
+                SET url = '/assets/serve/' || filename, updated_at = NOW()
+                WHERE url LIKE 'http://%' OR url LIKE 'https://%'
+                "#,
+            )
             .execute(&self.pool)
             .await?;
+            Ok(result.rows_affected())
+        })
+        .await
+    }
+
+    pub async fn delete_asset(&self, id: &Uuid) -> Result<(), DbError> {
+        self.timed("delete_asset", async {
+            sqlx::query!("DELETE FROM assets WHERE id = $1", id)
+                .execute(&self.pool)
+                .await?;
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 }
@@ -0,0 +1,263 @@
+//! Builder shared by the `AppState` constructors and tests
+
+use moka::future::Cache;
+use sqlx::PgPool;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use super::circuit::CircuitBreaker;
+use super::metrics::DbMetrics;
+use super::AppState;
+use crate::asset::gallery::GalleryConfig;
+use crate::comment::CommentConfig;
+use crate::config::UploadConfig;
+use crate::http_client::{HttpClientConfig, HttpMetrics, RetryPolicy};
+use crate::organization::persistence::PersistenceWorker;
+use crate::posting::sanitize::SanitizeConfig;
+use crate::storage::{MeteredStorage, ObjectStorage};
+use crate::submission::SubmissionConfig;
+use crate::webhook::dispatcher::WebhookWorker;
+
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+const POST_CACHE_CAPACITY: u64 = 100;
+const ORGANIZATION_CACHE_CAPACITY: u64 = 10;
+const PERSIST_CHANNEL_CAPACITY: usize = 100;
+const WEBHOOK_CHANNEL_CAPACITY: usize = 256;
+
+/// Builds an `AppState` from an existing pool and storage backend.
+///
+/// Nothing here reads environment variables. The persistence and webhook
+/// workers are spawned on the current tokio runtime unless disabled.
+pub struct AppStateBuilder {
+    pool: Option<PgPool>,
+    storage: Option<Arc<dyn ObjectStorage + Send + Sync>>,
+    http_client: Option<reqwest::Client>,
+    db_metrics: Option<DbMetrics>,
+    circuit_breaker: Option<CircuitBreaker>,
+    http_metrics: Option<HttpMetrics>,
+    cache_ttl: Duration,
+    upload: UploadConfig,
+    gallery: GalleryConfig,
+    comments: CommentConfig,
+    submissions: SubmissionConfig,
+    sanitize: SanitizeConfig,
+    read_only: bool,
+    storage_quota: Option<u64>,
+    persistence: bool,
+    webhooks: bool,
+    webhook_retry: RetryPolicy,
+}
+
+impl Default for AppStateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AppStateBuilder {
+    pub fn new() -> Self {
+        Self {
+            pool: None,
+            storage: None,
+            http_client: None,
+            db_metrics: None,
+            circuit_breaker: None,
+            http_metrics: None,
+            cache_ttl: DEFAULT_CACHE_TTL,
+            upload: UploadConfig::default(),
+            gallery: GalleryConfig::default(),
+            comments: CommentConfig::default(),
+            submissions: SubmissionConfig::default(),
+            sanitize: SanitizeConfig::default(),
+            read_only: false,
+            storage_quota: None,
+            persistence: true,
+            webhooks: true,
+            webhook_retry: RetryPolicy::default(),
+        }
+    }
+
+    pub fn with_pool(mut self, pool: PgPool) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    pub fn with_storage(mut self, storage: Arc<dyn ObjectStorage + Send + Sync>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Share an HTTP client, e.g. the one the storage backend uses
+    pub fn with_http_client(mut self, http_client: reqwest::Client) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    pub fn with_db_metrics(mut self, db_metrics: DbMetrics) -> Self {
+        self.db_metrics = Some(db_metrics);
+        self
+    }
+
+    /// Breaker for the guarded reads (default 5 failures, 30s cooldown)
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    pub fn with_http_metrics(mut self, http_metrics: HttpMetrics) -> Self {
+        self.http_metrics = Some(http_metrics);
+        self
+    }
+
+    /// Time-to-live for the post and organization caches (default 10 minutes)
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    pub fn with_upload_config(mut self, upload: UploadConfig) -> Self {
+        self.upload = upload;
+        self
+    }
+
+    /// Folders shown in the public gallery (default none)
+    pub fn with_gallery_config(mut self, gallery: GalleryConfig) -> Self {
+        self.gallery = gallery;
+        self
+    }
+
+    /// Comment length and rate limits (default 5 per hour, 2000 characters)
+    pub fn with_comment_config(mut self, comments: CommentConfig) -> Self {
+        self.comments = comments;
+        self
+    }
+
+    /// Complaint form length and rate limits (default 3 per hour, 5000
+    /// characters)
+    pub fn with_submission_config(mut self, submissions: SubmissionConfig) -> Self {
+        self.submissions = submissions;
+        self
+    }
+
+    /// Tags kept in post excerpts (default: `DEFAULT_ALLOWED_TAGS`)
+    pub fn with_sanitize_config(mut self, sanitize: SanitizeConfig) -> Self {
+        self.sanitize = sanitize;
+        self
+    }
+
+    /// Start in read-only mode (default false)
+    pub fn with_read_only(mut self, enabled: bool) -> Self {
+        self.read_only = enabled;
+        self
+    }
+
+    /// Quota reported by the storage usage endpoint (default none)
+    pub fn with_storage_quota(mut self, quota_bytes: Option<u64>) -> Self {
+        self.storage_quota = quota_bytes;
+        self
+    }
+
+    /// Whether to spawn the organization persistence worker (default true)
+    pub fn with_persistence(mut self, enabled: bool) -> Self {
+        self.persistence = enabled;
+        self
+    }
+
+    /// Whether to spawn the webhook delivery worker (default true)
+    pub fn with_webhooks(mut self, enabled: bool) -> Self {
+        self.webhooks = enabled;
+        self
+    }
+
+    /// Retries for webhook deliveries (default: the HTTP client defaults)
+    pub fn with_webhook_retry(mut self, policy: RetryPolicy) -> Self {
+        self.webhook_retry = policy;
+        self
+    }
+
+    pub fn build(self) -> Result<AppState, Box<dyn std::error::Error>> {
+        let pool = self
+            .pool
+            .ok_or("AppStateBuilder requires a database pool")?;
+        let storage = self
+            .storage
+            .ok_or("AppStateBuilder requires a storage backend")?;
+        // Failed calls are counted the same way for every backend
+        let http_metrics = self.http_metrics.unwrap_or_default();
+        let storage: Arc<dyn ObjectStorage + Send + Sync> =
+            Arc::new(MeteredStorage::new(storage, http_metrics.clone()));
+        let http_client = match self.http_client {
+            Some(client) => client,
+            None => default_http_client()?,
+        };
+
+        let post_cache = Cache::builder()
+            .time_to_live(self.cache_ttl)
+            .max_capacity(POST_CACHE_CAPACITY)
+            .build();
+
+        let organization_cache = Cache::builder()
+            .time_to_live(self.cache_ttl)
+            .max_capacity(ORGANIZATION_CACHE_CAPACITY)
+            .build();
+
+        // Create channel for organization persistence worker
+        let (organization_persist_sender, receiver) = mpsc::channel(PERSIST_CHANNEL_CAPACITY);
+
+        let organization_persist_worker = if self.persistence {
+            Arc::new(PersistenceWorker::spawn(receiver, storage.clone()))
+        } else {
+            Arc::new(PersistenceWorker::disabled())
+        };
+
+        let (webhook_sender, receiver) = mpsc::channel(WEBHOOK_CHANNEL_CAPACITY);
+        let webhook_worker = if self.webhooks {
+            Arc::new(WebhookWorker::spawn(
+                receiver,
+                pool.clone(),
+                http_client.clone(),
+                self.webhook_retry,
+            ))
+        } else {
+            Arc::new(WebhookWorker::disabled())
+        };
+
+        Ok(AppState {
+            pool,
+            post_cache,
+            organization_cache,
+            organization_lock: Arc::default(),
+            http_client,
+            storage,
+            organization_persist_sender,
+            organization_persist_worker,
+            webhook_sender,
+            webhook_worker,
+            cache_metrics: crate::cache::CacheMetrics::new(),
+            db_metrics: self.db_metrics.unwrap_or_default(),
+            db_circuit: Arc::new(self.circuit_breaker.unwrap_or_default()),
+            http_metrics,
+            maintenance: Arc::new(crate::maintenance::Maintenance::default()),
+            storage_usage: Arc::new(crate::storage_usage::StorageUsage::new(self.storage_quota)),
+            changes: Arc::default(),
+            stats: Arc::default(),
+            upload: self.upload,
+            gallery: self.gallery,
+            comment_limiter: Arc::new(self.comments.rate_limiter()),
+            comments: self.comments,
+            submission_limiter: Arc::new(self.submissions.rate_limiter()),
+            submissions: self.submissions,
+            sanitize: self.sanitize,
+            submission_metrics: crate::submission::SubmissionMetrics::new(),
+            runtime_metrics: Arc::default(),
+            upload_sessions: Arc::default(),
+            read_only: Arc::new(AtomicBool::new(self.read_only)),
+        })
+    }
+}
+
+pub(crate) fn default_http_client() -> Result<reqwest::Client, reqwest::Error> {
+    HttpClientConfig::default().build_client()
+}
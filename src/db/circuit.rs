@@ -0,0 +1,197 @@
+//! Circuit breaker in front of the busiest database reads.
+//!
+//! After `DB_CIRCUIT_FAILURES` connection errors in a row the circuit
+//! opens, and guarded calls fail with [`DbError::Unavailable`] without
+//! touching the pool until `DB_CIRCUIT_COOLDOWN_SECS` have passed. The
+//! first call after that is let through as a probe: success closes the
+//! circuit, another connection error opens it for a new cooldown.
+
+use prometheus::{IntGauge, Opts, Registry};
+use serde::Serialize;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+use super::{AppState, DbError};
+
+pub const DEFAULT_CIRCUIT_FAILURES: u32 = 5;
+pub const DEFAULT_CIRCUIT_COOLDOWN_SECS: u64 = 30;
+
+/// State of the circuit as reported on `/readyz`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Calls go to the database
+    Closed,
+    /// Calls fail fast until the cooldown ends
+    Open,
+    /// One probe call is testing whether the database is back
+    HalfOpen,
+}
+
+impl CircuitState {
+    /// Value of the `db_circuit_state` gauge
+    fn gauge_value(self) -> i64 {
+        match self {
+            Self::Closed => 0,
+            Self::HalfOpen => 1,
+            Self::Open => 2,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum State {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen { probe_started: Instant },
+}
+
+impl State {
+    fn public(&self) -> CircuitState {
+        match self {
+            Self::Closed { .. } => CircuitState::Closed,
+            Self::Open { .. } => CircuitState::Open,
+            Self::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+}
+
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    state: Mutex<State>,
+    gauge: IntGauge,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_CIRCUIT_FAILURES,
+            Duration::from_secs(DEFAULT_CIRCUIT_COOLDOWN_SECS),
+        )
+    }
+}
+
+impl CircuitBreaker {
+    /// Open after `failure_threshold` connection errors in a row and stay
+    /// open for `cooldown`
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        let gauge = IntGauge::with_opts(
+            Opts::new(
+                "db_circuit_state",
+                "Database circuit breaker: 0 closed, 1 half-open, 2 open",
+            )
+            .namespace("cakung_barat_server"),
+        )
+        .expect("valid db circuit gauge");
+
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            state: Mutex::new(State::Closed { failures: 0 }),
+            gauge,
+        }
+    }
+
+    /// Register the gauge so it is exported on `/metrics`
+    pub fn register(&self, registry: &Registry) -> prometheus::Result<()> {
+        registry.register(Box::new(self.gauge.clone()))
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.lock().public()
+    }
+
+    /// Let a call through, or return the error to fail fast with
+    pub fn admit(&self) -> Result<(), DbError> {
+        let mut state = self.lock();
+        let now = Instant::now();
+        match *state {
+            State::Closed { .. } => Ok(()),
+            State::Open { until } if now < until => Err(DbError::Unavailable {
+                retry_after_secs: retry_after(until - now),
+            }),
+            // A probe that never reported back, e.g. because its request
+            // was cancelled, is replaced once a cooldown has passed
+            State::HalfOpen { probe_started } if now - probe_started < self.cooldown => {
+                Err(DbError::Unavailable {
+                    retry_after_secs: retry_after(self.cooldown - (now - probe_started)),
+                })
+            }
+            State::Open { .. } | State::HalfOpen { .. } => {
+                log::info!("Database circuit half-open, probing the database");
+                self.set(&mut state, State::HalfOpen { probe_started: now });
+                Ok(())
+            }
+        }
+    }
+
+    /// Record the outcome of an admitted call. Only connection errors
+    /// count as failures, any answer from the database closes the circuit.
+    pub fn record(&self, error: Option<&DbError>) {
+        let mut state = self.lock();
+        if !error.is_some_and(DbError::is_connection) {
+            if !matches!(*state, State::Closed { failures: 0 }) {
+                if !matches!(*state, State::Closed { .. }) {
+                    log::info!("Database circuit closed, the database is reachable again");
+                }
+                self.set(&mut state, State::Closed { failures: 0 });
+            }
+            return;
+        }
+
+        let failures = match *state {
+            State::Closed { failures } => failures + 1,
+            // The probe failed, or a call admitted before the circuit opened
+            State::HalfOpen { .. } | State::Open { .. } => self.failure_threshold,
+        };
+        if failures < self.failure_threshold {
+            self.set(&mut state, State::Closed { failures });
+            return;
+        }
+        if !matches!(*state, State::Open { .. }) {
+            log::warn!(
+                "Database circuit opened after {} connection errors, failing fast for {}s",
+                failures,
+                self.cooldown.as_secs()
+            );
+        }
+        self.set(
+            &mut state,
+            State::Open {
+                until: Instant::now() + self.cooldown,
+            },
+        );
+    }
+
+    fn set(&self, state: &mut State, next: State) {
+        self.gauge.set(next.public().gauge_value());
+        *state = next;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Whole seconds until `remaining` has passed, at least one
+fn retry_after(remaining: Duration) -> u64 {
+    (remaining.as_millis().div_ceil(1000) as u64).max(1)
+}
+
+impl AppState {
+    /// Run a database call through the circuit breaker. Wrap leaf calls
+    /// only: a guarded call nested in another would be refused while the
+    /// outer one is the half-open probe.
+    pub(crate) async fn guarded<T>(
+        &self,
+        call: impl Future<Output = Result<T, DbError>>,
+    ) -> Result<T, DbError> {
+        self.db_circuit.admit()?;
+        let result = call.await;
+        self.db_circuit.record(result.as_ref().err());
+        result
+    }
+}
@@ -0,0 +1,121 @@
+//! Database errors sorted by what a handler can tell the client
+
+/// SQLSTATE of a unique constraint or primary key violation
+const UNIQUE_VIOLATION: &str = "23505";
+/// SQLSTATE of a foreign key violation
+const FOREIGN_KEY_VIOLATION: &str = "23503";
+/// SQLSTATE class of connection exceptions
+const CONNECTION_EXCEPTION_CLASS: &str = "08";
+
+/// Error of an `AppState` database method. Converting a `sqlx::Error`
+/// classifies it by its Postgres error code, so a duplicate key can be
+/// answered with 409 and a dangling reference with 400 instead of 500.
+#[derive(Debug, thiserror::Error)]
+pub enum DbError {
+    /// A unique constraint or primary key rejected the write
+    #[error("unique constraint {} violated: {source}", constraint_name(.constraint))]
+    UniqueViolation {
+        constraint: Option<String>,
+        #[source]
+        source: sqlx::Error,
+    },
+    /// The write refers to a row that does not exist
+    #[error("foreign key {} violated: {source}", constraint_name(.constraint))]
+    ForeignKeyViolation {
+        constraint: Option<String>,
+        #[source]
+        source: sqlx::Error,
+    },
+    /// The database could not be reached or dropped the connection
+    #[error("database unavailable: {0}")]
+    Connection(#[source] sqlx::Error),
+    #[error(transparent)]
+    Other(sqlx::Error),
+    /// The circuit breaker is open and the call was not attempted, see
+    /// `crate::db::circuit`
+    #[error("database circuit open, retry in {retry_after_secs}s")]
+    Unavailable { retry_after_secs: u64 },
+}
+
+fn constraint_name(constraint: &Option<String>) -> &str {
+    constraint.as_deref().unwrap_or("(unnamed)")
+}
+
+impl From<sqlx::Error> for DbError {
+    fn from(source: sqlx::Error) -> Self {
+        match &source {
+            sqlx::Error::Database(db) => {
+                let code = db.code().unwrap_or_default();
+                let constraint = db.constraint().map(str::to_string);
+                if code == UNIQUE_VIOLATION {
+                    Self::UniqueViolation { constraint, source }
+                } else if code == FOREIGN_KEY_VIOLATION {
+                    Self::ForeignKeyViolation { constraint, source }
+                } else if code.starts_with(CONNECTION_EXCEPTION_CLASS) {
+                    Self::Connection(source)
+                } else {
+                    Self::Other(source)
+                }
+            }
+            sqlx::Error::Io(_)
+            | sqlx::Error::Tls(_)
+            | sqlx::Error::PoolTimedOut
+            | sqlx::Error::PoolClosed
+            | sqlx::Error::WorkerCrashed => Self::Connection(source),
+            _ => Self::Other(source),
+        }
+    }
+}
+
+impl DbError {
+    pub fn is_unique_violation(&self) -> bool {
+        matches!(self, Self::UniqueViolation { .. })
+    }
+
+    pub fn is_fk_violation(&self) -> bool {
+        matches!(self, Self::ForeignKeyViolation { .. })
+    }
+
+    /// Whether the database was unreachable rather than the query wrong
+    pub fn is_connection(&self) -> bool {
+        matches!(self, Self::Connection(_))
+    }
+
+    /// Whether the call was refused by the open circuit breaker
+    pub fn is_unavailable(&self) -> bool {
+        matches!(self, Self::Unavailable { .. })
+    }
+
+    /// Name of the violated constraint, when there is one
+    pub fn constraint(&self) -> Option<&str> {
+        match self {
+            Self::UniqueViolation { constraint, .. }
+            | Self::ForeignKeyViolation { constraint, .. } => constraint.as_deref(),
+            Self::Connection(_) | Self::Other(_) | Self::Unavailable { .. } => None,
+        }
+    }
+
+    /// The underlying sqlx error, for database calls made inside another
+    /// timed call. Converting it back gives the same `DbError`, except that
+    /// an open circuit comes back as a `Connection` error.
+    pub(crate) fn into_sqlx(self) -> sqlx::Error {
+        match self {
+            Self::UniqueViolation { source, .. }
+            | Self::ForeignKeyViolation { source, .. }
+            | Self::Connection(source)
+            | Self::Other(source) => source,
+            Self::Unavailable { .. } => sqlx::Error::PoolTimedOut,
+        }
+    }
+
+    /// The underlying sqlx error, if the call reached the pool
+    pub fn sqlx(&self) -> Option<&sqlx::Error> {
+        match self {
+            Self::UniqueViolation { source, .. }
+            | Self::ForeignKeyViolation { source, .. }
+            | Self::Connection(source)
+            | Self::Other(source) => Some(source),
+            Self::Unavailable { .. } => None,
+        }
+    }
+}
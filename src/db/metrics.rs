@@ -0,0 +1,82 @@
+//! Latency histogram and slow-query logging for database calls
+
+use prometheus::{HistogramOpts, HistogramVec, Registry};
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use super::{AppState, DbError};
+
+pub const DEFAULT_SLOW_QUERY_MS: u64 = 500;
+
+#[derive(Clone)]
+pub struct DbMetrics {
+    query_duration: HistogramVec,
+    slow_query_threshold: Duration,
+}
+
+impl Default for DbMetrics {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(DEFAULT_SLOW_QUERY_MS))
+    }
+}
+
+impl DbMetrics {
+    pub fn new(slow_query_threshold: Duration) -> Self {
+        let query_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "db_query_duration_seconds",
+                "Time spent in AppState database methods",
+            )
+            .namespace("cakung_barat_server"),
+            &["method"],
+        )
+        .expect("valid db query histogram");
+
+        Self {
+            query_duration,
+            slow_query_threshold,
+        }
+    }
+
+    /// Register the histogram so it is exported on `/metrics`
+    pub fn register(&self, registry: &Registry) -> prometheus::Result<()> {
+        registry.register(Box::new(self.query_duration.clone()))
+    }
+
+    pub fn observe(&self, method: &str, elapsed: Duration) {
+        self.query_duration
+            .with_label_values(&[method])
+            .observe(elapsed.as_secs_f64());
+
+        if elapsed >= self.slow_query_threshold {
+            log::warn!(
+                "Slow database call: {} took {}ms (threshold {}ms)",
+                method,
+                elapsed.as_millis(),
+                self.slow_query_threshold.as_millis()
+            );
+        }
+    }
+
+    /// Number of recorded samples for a method
+    pub fn sample_count(&self, method: &str) -> u64 {
+        self.query_duration
+            .with_label_values(&[method])
+            .get_sample_count()
+    }
+}
+
+impl AppState {
+    /// Run a database call, recording its duration under `method`.
+    /// Failed calls are recorded too.
+    pub(crate) async fn timed<T>(
+        &self,
+        method: &'static str,
+        call: impl Future<Output = Result<T, sqlx::Error>>,
+    ) -> Result<T, DbError> {
+        let started = Instant::now();
+        let result = call.await;
+        self.db_metrics.observe(method, started.elapsed());
+        result.map_err(DbError::from)
+    }
+}
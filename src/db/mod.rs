@@ -4,131 +4,180 @@
 //! - `asset` - Asset-related database operations
 //! - `posting` - Post/Posting-related database operations  
 //! - `admin` - Admin authentication database operations
+//! - `pool` - Connection pool settings and startup retry
+//! - `metrics` - Database call latency histogram
+//! - `builder` - `AppStateBuilder` used by the constructors
+//! - `error` - `DbError`, returned by the `AppState` database methods
+//! - `circuit` - Circuit breaker failing fast while the database is down
 
 mod admin;
 mod asset;
+pub mod builder;
+pub mod circuit;
+pub mod error;
+pub mod metrics;
+pub mod pool;
 mod posting;
 
-use dotenvy::dotenv;
 use moka::future::Cache;
 use sqlx::PgPool;
-use std::env;
 use std::sync::Arc;
-use std::time::Duration;
 use tokio::sync::mpsc;
 
+pub use builder::AppStateBuilder;
+pub use error::DbError;
+pub(crate) use posting::{link_posts_to_folder, POST_COLUMNS, POST_SOURCE};
+
 #[derive(Clone)]
 pub struct AppState {
     pub pool: PgPool,
-    pub post_cache: Cache<String, Vec<crate::posting::models::Post>>,
-    pub organization_cache: Cache<String, Vec<crate::organization::model::OrganizationMember>>,
+    pub post_cache: Cache<String, crate::cache::Cached<Vec<crate::posting::models::Post>>>,
+    pub organization_cache:
+        Cache<String, crate::cache::Cached<crate::organization::model::MemberMap>>,
+    /// Serializes changes to the organization members and holds the highest
+    /// id handed out, see `crate::organization::persistence`
+    pub organization_lock: Arc<tokio::sync::Mutex<i32>>,
     pub http_client: reqwest::Client,
     pub storage: Arc<dyn crate::storage::ObjectStorage + Send + Sync>,
     pub organization_persist_sender:
         mpsc::Sender<Vec<crate::organization::model::OrganizationMember>>,
+    pub organization_persist_worker: Arc<crate::organization::persistence::PersistenceWorker>,
+    /// Queue read by the webhook worker, see `crate::webhook`
+    pub webhook_sender: mpsc::Sender<crate::webhook::WebhookPayload>,
+    pub webhook_worker: Arc<crate::webhook::dispatcher::WebhookWorker>,
+    pub cache_metrics: crate::cache::CacheMetrics,
+    pub db_metrics: metrics::DbMetrics,
+    /// Guards the busiest reads, see `crate::db::circuit`
+    pub db_circuit: Arc<circuit::CircuitBreaker>,
+    /// Retry and failure counters shared with the storage backend
+    pub http_metrics: crate::http_client::HttpMetrics,
+    pub maintenance: Arc<crate::maintenance::Maintenance>,
+    /// Cached bucket scan and usage gauges, see `crate::storage_usage`
+    pub storage_usage: Arc<crate::storage_usage::StorageUsage>,
+    /// Recent changes followed by `GET /api/events`, see `crate::events`
+    pub changes: Arc<crate::events::ChangeFeed>,
+    /// Cached dashboard numbers, see `crate::stats`
+    pub stats: Arc<crate::stats::StatsCache>,
+    pub upload: crate::config::UploadConfig,
+    /// Folders listed by `GET /api/gallery`
+    pub gallery: crate::asset::gallery::GalleryConfig,
+    /// Limits on resident comments, see `crate::comment`
+    pub comments: crate::comment::CommentConfig,
+    /// Comment submissions per client IP
+    pub comment_limiter: Arc<crate::mcp::tools::rate_limit::RateLimiter>,
+    /// Limits on the complaint form, see `crate::submission`
+    pub submissions: crate::submission::SubmissionConfig,
+    /// Form submissions per client IP
+    pub submission_limiter: Arc<crate::mcp::tools::rate_limit::RateLimiter>,
+    /// Gauge of unresolved submissions
+    pub submission_metrics: crate::submission::SubmissionMetrics,
+    /// Tags kept in post excerpts, see `crate::posting::sanitize`
+    pub sanitize: crate::posting::sanitize::SanitizeConfig,
+    /// Sampled queue depths, see `crate::runtime_metrics`
+    pub runtime_metrics: Arc<crate::runtime_metrics::RuntimeMetrics>,
+    /// Open `PUT /api/assets/uploads/{id}` sessions, see
+    /// `crate::asset::upload_session`
+    pub upload_sessions: Arc<crate::asset::upload_session::UploadSessions>,
+    /// Rejects writes under `/api` while set, see `crate::read_only`
+    pub read_only: Arc<std::sync::atomic::AtomicBool>,
 }
 
 impl AppState {
     pub async fn new() -> Result<Self, Box<dyn std::error::Error>> {
         dotenvy::dotenv().ok(); // Load .env file
-        let supabase_config = crate::storage::SupabaseConfig::from_env()?;
-        Self::new_with_config(supabase_config).await
+        let config = crate::config::AppConfig::from_env()?;
+        Self::new_with_config(&config).await
     }
 
+    /// Connect to the database and storage described by `config` and start
+    /// the background maintenance and storage usage jobs.
     pub async fn new_with_config(
-        supabase_config: crate::storage::SupabaseConfig,
+        config: &crate::config::AppConfig,
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        dotenv().ok();
-        let database_url =
-            env::var("SUPABASE_DATABASE_URL").expect("SUPABASE_DATABASE_URL must be set");
-
-        let pool = sqlx::postgres::PgPoolOptions::new()
-            .max_connections(100)
-            .min_connections(10)
-            .acquire_timeout(std::time::Duration::from_secs(30))
-            .idle_timeout(std::time::Duration::from_secs(900))
-            .max_lifetime(std::time::Duration::from_secs(1800))
-            .connect(&database_url)
-            .await?;
-
-        let post_cache = Cache::builder()
-            .time_to_live(Duration::from_secs(10 * 60))
-            .max_capacity(100)
-            .build();
-
-        let organization_cache = Cache::builder()
-            .time_to_live(Duration::from_secs(10 * 60))
-            .max_capacity(10)
-            .build();
-
-        let http_client = reqwest::Client::builder()
-            .pool_idle_timeout(std::time::Duration::from_secs(900))
-            .user_agent("cakung-barat-server/1.0")
-            .build()
-            .expect("Failed to create reqwest client");
-
-        let storage = Arc::new(crate::storage::SupabaseStorage::new(
-            supabase_config,
+        let pool_config = &config.database.pool;
+        log::info!(
+            "Database pool: max {} / min {} connections, {}s acquire timeout",
+            pool_config.max_connections,
+            pool_config.min_connections,
+            pool_config.acquire_timeout_secs
+        );
+        let pool = pool::connect_with_retry(&config.database.url, pool_config).await?;
+        let db_metrics = metrics::DbMetrics::new(pool_config.slow_query_threshold());
+
+        let http_client = config.http.build_client()?;
+        let http_metrics = crate::http_client::HttpMetrics::new();
+        let storage = config.storage.build(
             http_client.clone(),
-        ));
-
-        // Create channel for organization persistence worker
-        let (organization_persist_sender, receiver) = mpsc::channel(100);
-
-        // Spawn background persistence worker
-        let storage_clone = storage.clone();
-        tokio::spawn(async move {
-            crate::organization::persistence::start_persistence_worker(receiver, storage_clone)
-                .await;
-        });
-
-        Ok(AppState {
-            pool,
-            post_cache,
-            organization_cache,
-            http_client,
-            storage,
-            organization_persist_sender,
-        })
+            crate::http_client::Retry::new(config.http.retry_policy(), http_metrics.clone()),
+            config.http.storage_max_concurrency,
+        );
+        match storage.health_check().await {
+            Ok(()) => log::info!("Storage health check passed"),
+            Err(e) if config.server.storage_strict_startup => {
+                return Err(format!("Storage health check failed: {}", e).into());
+            }
+            Err(e) => log::warn!(
+                "STORAGE HEALTH CHECK FAILED: {}. Uploads and asset serving will fail \
+                 until this is fixed; set STORAGE_STRICT_STARTUP=true to refuse to start.",
+                e
+            ),
+        }
+
+        let state = AppStateBuilder::new()
+            .with_pool(pool)
+            .with_storage(storage)
+            .with_http_client(http_client)
+            .with_db_metrics(db_metrics)
+            .with_circuit_breaker(pool_config.circuit_breaker())
+            .with_http_metrics(http_metrics)
+            .with_upload_config(config.upload.clone())
+            .with_gallery_config(config.gallery.clone())
+            .with_comment_config(config.comments.clone())
+            .with_submission_config(config.submissions.clone())
+            .with_sanitize_config(config.sanitize.clone())
+            .with_read_only(config.server.read_only)
+            .with_storage_quota(config.storage_usage.quota_bytes)
+            .with_webhook_retry(config.http.retry_policy())
+            .build()?;
+        state.start_maintenance(config.maintenance.clone());
+        state.start_storage_usage(&config.storage_usage);
+        state.start_runtime_metrics(crate::runtime_metrics::SAMPLE_INTERVAL);
+        state.refresh_open_submissions().await;
+        Ok(state)
     }
 
+    /// Build a state around an existing pool and storage backend, with the
+    /// persistence worker running. Use [`AppState::builder`] for more control.
     pub async fn new_with_pool_and_storage(
         pool: sqlx::PgPool,
         storage: Arc<dyn crate::storage::ObjectStorage + Send + Sync>,
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        let post_cache = Cache::builder()
-            .time_to_live(Duration::from_secs(10 * 60))
-            .max_capacity(100)
-            .build();
+        AppStateBuilder::new()
+            .with_pool(pool)
+            .with_storage(storage)
+            .build()
+    }
 
-        let organization_cache = Cache::builder()
-            .time_to_live(Duration::from_secs(10 * 60))
-            .max_capacity(10)
-            .build();
+    pub fn builder() -> AppStateBuilder {
+        AppStateBuilder::new()
+    }
 
-        let http_client = reqwest::Client::builder()
-            .pool_idle_timeout(std::time::Duration::from_secs(900))
-            .user_agent("cakung-barat-server/1.0")
-            .build()
-            .expect("Failed to create reqwest client");
+    /// Release resources before exit: stop the background jobs, deliver
+    /// queued webhooks, close the database pool, then flush pending
+    /// organization writes to storage.
+    pub async fn shutdown(&self) {
+        self.maintenance.stop();
+        self.storage_usage.stop();
+        self.runtime_metrics.stop();
 
-        // Create channel for organization persistence worker
-        let (organization_persist_sender, receiver) = mpsc::channel(100);
+        // Webhook delivery needs the pool for subscribers and dead letters
+        log::info!("Flushing webhook worker");
+        self.webhook_worker.flush().await;
 
-        // Spawn background persistence worker
-        let storage_clone = storage.clone();
-        tokio::spawn(async move {
-            crate::organization::persistence::start_persistence_worker(receiver, storage_clone)
-                .await;
-        });
+        log::info!("Closing database pool");
+        self.pool.close().await;
 
-        Ok(AppState {
-            pool,
-            post_cache,
-            organization_cache,
-            http_client,
-            storage,
-            organization_persist_sender,
-        })
+        log::info!("Flushing organization persistence worker");
+        self.organization_persist_worker.flush().await;
     }
 }
@@ -0,0 +1,191 @@
+//! Database pool settings and startup connection retry
+
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use std::time::Duration;
+
+use super::circuit::CircuitBreaker;
+use crate::config::parse_positive;
+
+const DEFAULT_MAX_CONNECTIONS: u32 = 10;
+const DEFAULT_MIN_CONNECTIONS: u32 = 1;
+const DEFAULT_ACQUIRE_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_CONNECT_ATTEMPTS: u32 = 5;
+const DEFAULT_CONNECT_BACKOFF_MS: u64 = 500;
+/// Upper bound for a single backoff delay
+const MAX_CONNECT_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DbPoolConfig {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout_secs: u64,
+    /// Total connection attempts at startup, including the first one
+    pub connect_attempts: u32,
+    /// Delay before the first retry, doubled after each failure
+    pub connect_backoff_ms: u64,
+    /// Database calls slower than this are logged at warn level
+    pub slow_query_ms: u64,
+    /// Connection errors in a row that open the circuit breaker
+    pub circuit_failures: u32,
+    /// How long the open circuit fails fast before probing again
+    pub circuit_cooldown_secs: u64,
+}
+
+impl Default for DbPoolConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            min_connections: DEFAULT_MIN_CONNECTIONS,
+            acquire_timeout_secs: DEFAULT_ACQUIRE_TIMEOUT_SECS,
+            connect_attempts: DEFAULT_CONNECT_ATTEMPTS,
+            connect_backoff_ms: DEFAULT_CONNECT_BACKOFF_MS,
+            slow_query_ms: super::metrics::DEFAULT_SLOW_QUERY_MS,
+            circuit_failures: super::circuit::DEFAULT_CIRCUIT_FAILURES,
+            circuit_cooldown_secs: super::circuit::DEFAULT_CIRCUIT_COOLDOWN_SECS,
+        }
+    }
+}
+
+impl DbPoolConfig {
+    /// Load from process environment
+    pub fn from_env() -> Result<Self, String> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// Load using a custom variable lookup. Without `DB_MIN_CONNECTIONS` the
+    /// minimum is capped at the configured maximum.
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let get = |key: &str| {
+            lookup(key)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let defaults = Self::default();
+
+        let max_connections = match get("DB_MAX_CONNECTIONS") {
+            Some(v) => parse_u32("DB_MAX_CONNECTIONS", &v)?,
+            None => defaults.max_connections,
+        };
+        let min_connections = match get("DB_MIN_CONNECTIONS") {
+            Some(v) => v.parse::<u32>().map_err(|_| {
+                format!(
+                    "DB_MIN_CONNECTIONS must be a non-negative integer, got '{}'",
+                    v
+                )
+            })?,
+            None => defaults.min_connections.min(max_connections),
+        };
+        if min_connections > max_connections {
+            return Err(format!(
+                "DB_MIN_CONNECTIONS ({}) must not exceed DB_MAX_CONNECTIONS ({})",
+                min_connections, max_connections
+            ));
+        }
+        let acquire_timeout_secs = match get("DB_ACQUIRE_TIMEOUT_SECS") {
+            Some(v) => parse_positive("DB_ACQUIRE_TIMEOUT_SECS", &v)? as u64,
+            None => defaults.acquire_timeout_secs,
+        };
+        let connect_attempts = match get("DB_CONNECT_ATTEMPTS") {
+            Some(v) => parse_u32("DB_CONNECT_ATTEMPTS", &v)?,
+            None => defaults.connect_attempts,
+        };
+        let connect_backoff_ms = match get("DB_CONNECT_BACKOFF_MS") {
+            Some(v) => parse_positive("DB_CONNECT_BACKOFF_MS", &v)? as u64,
+            None => defaults.connect_backoff_ms,
+        };
+        let slow_query_ms = match get("DB_SLOW_QUERY_MS") {
+            Some(v) => parse_positive("DB_SLOW_QUERY_MS", &v)? as u64,
+            None => defaults.slow_query_ms,
+        };
+        let circuit_failures = match get("DB_CIRCUIT_FAILURES") {
+            Some(v) => parse_u32("DB_CIRCUIT_FAILURES", &v)?,
+            None => defaults.circuit_failures,
+        };
+        let circuit_cooldown_secs = match get("DB_CIRCUIT_COOLDOWN_SECS") {
+            Some(v) => parse_positive("DB_CIRCUIT_COOLDOWN_SECS", &v)? as u64,
+            None => defaults.circuit_cooldown_secs,
+        };
+
+        Ok(Self {
+            max_connections,
+            min_connections,
+            acquire_timeout_secs,
+            connect_attempts,
+            connect_backoff_ms,
+            slow_query_ms,
+            circuit_failures,
+            circuit_cooldown_secs,
+        })
+    }
+
+    pub fn pool_options(&self) -> PgPoolOptions {
+        PgPoolOptions::new()
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .acquire_timeout(Duration::from_secs(self.acquire_timeout_secs))
+            .idle_timeout(Duration::from_secs(900))
+            .max_lifetime(Duration::from_secs(1800))
+    }
+
+    pub fn slow_query_threshold(&self) -> Duration {
+        Duration::from_millis(self.slow_query_ms)
+    }
+
+    pub fn circuit_breaker(&self) -> CircuitBreaker {
+        CircuitBreaker::new(
+            self.circuit_failures,
+            Duration::from_secs(self.circuit_cooldown_secs),
+        )
+    }
+
+    /// Backoff delay after the given failed attempt (1-based)
+    pub fn backoff_after(&self, attempt: u32) -> Duration {
+        let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+        Duration::from_millis(self.connect_backoff_ms.saturating_mul(factor))
+            .min(MAX_CONNECT_BACKOFF)
+    }
+}
+
+/// Connect to the database, retrying with exponential backoff. Invalid
+/// connection strings fail immediately since retrying cannot fix them.
+pub async fn connect_with_retry(
+    database_url: &str,
+    config: &DbPoolConfig,
+) -> Result<PgPool, sqlx::Error> {
+    let mut attempt = 1;
+    loop {
+        match config.pool_options().connect(database_url).await {
+            Ok(pool) => return Ok(pool),
+            Err(e @ sqlx::Error::Configuration(_)) => return Err(e),
+            Err(e) if attempt >= config.connect_attempts => {
+                log::error!(
+                    "Database connection failed after {} attempts: {}",
+                    attempt,
+                    e
+                );
+                return Err(e);
+            }
+            Err(e) => {
+                let delay = config.backoff_after(attempt);
+                log::warn!(
+                    "Database connection attempt {}/{} failed: {}. Retrying in {:?}",
+                    attempt,
+                    config.connect_attempts,
+                    e,
+                    delay
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+fn parse_u32(key: &str, value: &str) -> Result<u32, String> {
+    let n = parse_positive(key, value)?;
+    u32::try_from(n).map_err(|_| format!("{} is too large, got '{}'", key, value))
+}
@@ -1,57 +1,89 @@
 //! Posting/Post database operations
 
-use super::AppState;
+use super::{AppState, DbError};
+use crate::cache::{CacheKind, Cached};
+use crate::posting::models::DateRange;
 use uuid::Uuid;
 
+/// Columns of a `Post`, selected from [`POST_SOURCE`]
+pub(crate) const POST_COLUMNS: &str = "p.id, p.title, p.category, p.date, p.excerpt, \
+     COALESCE(f.name, p.folder_id) AS folder_id, p.created_at, p.updated_at, \
+     p.cover_asset_id, cover.filename AS cover_filename, \
+     ARRAY(SELECT t.lang FROM post_translations t WHERE t.post_id = p.id ORDER BY t.lang) \
+     AS translations_available, \
+     (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id AND c.status = 'approved') \
+     AS comment_count";
+
+/// `posts p` with its folder `f` and its `cover` asset: the picked one, or
+/// else the oldest image in the folder
+pub(crate) const POST_SOURCE: &str = r"posts p
+     LEFT JOIN folders f ON f.id = p.folder_uuid
+     LEFT JOIN assets cover ON cover.id = COALESCE(
+         p.cover_asset_id,
+         (SELECT a.id FROM asset_folders af JOIN assets a ON a.id = af.asset_id
+          WHERE af.folder_id = p.folder_uuid
+            AND lower(a.filename) ~ '\.(jpe?g|png|gif|webp|avif|bmp|svg)$'
+          ORDER BY a.created_at, a.id
+          LIMIT 1))";
+
 impl AppState {
     pub async fn get_post_by_id(
         &self,
         id: &Uuid,
-    ) -> Result<Option<crate::posting::models::Post>, sqlx::Error> {
-        sqlx::query_as!(
-            crate::posting::models::Post,
-            "SELECT id, title, category, date, excerpt, folder_id, created_at, updated_at FROM posts WHERE id = $1",
-            id
-        )
-        .fetch_optional(&self.pool)
-        .await
-        .map_err(|e| {
-            log::error!("Error getting post by id: {:?}", e);
-            e
+    ) -> Result<Option<crate::posting::models::Post>, DbError> {
+        self.timed("get_post_by_id", async {
+            sqlx::query_as(&format!(
+                "SELECT {} FROM {} WHERE p.id = $1",
+                POST_COLUMNS, POST_SOURCE
+            ))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| {
+                log::error!("Error getting post by id: {:?}", e);
+                e
+            })
         })
+        .await
     }
 
     pub async fn get_all_posts_cached(
         &self,
-    ) -> Result<Vec<crate::posting::models::Post>, sqlx::Error> {
+    ) -> Result<Vec<crate::posting::models::Post>, DbError> {
         let key = "all_posts";
-        if let Some(posts) = self.post_cache.get(key).await {
+        if let Some(cached) = self.post_cache.get(key).await {
             log::info!("Cache hit for all_posts");
-            return Ok(posts);
+            self.cache_metrics.record_hit(CacheKind::Posts);
+            return Ok(cached.value);
         }
 
         log::info!("Cache miss for all_posts");
+        self.cache_metrics.record_miss(CacheKind::Posts);
         let posts = self.get_all_posts().await?;
-        self.post_cache.insert(key.to_string(), posts.clone()).await;
+        self.post_cache
+            .insert(key.to_string(), Cached::new(posts.clone()))
+            .await;
+        self.refresh_cache_entries(CacheKind::Posts).await;
         Ok(posts)
     }
 
-    /// Get posts with optional category filter, sorting, and pagination.
-    /// Uses cache-first strategy - same cache as REST endpoints.
+    /// Get posts with optional category and date filters, sorting, and
+    /// pagination. Uses cache-first strategy - same cache as REST endpoints.
     pub async fn get_posts_filtered(
         &self,
         category: Option<&str>,
+        dates: DateRange,
         sort_latest_first: bool,
         limit: i32,
         offset: i32,
-    ) -> Result<Vec<crate::posting::models::Post>, sqlx::Error> {
+    ) -> Result<Vec<crate::posting::models::Post>, DbError> {
         // Reuse cache - same as REST endpoint
         let all_posts = self.get_all_posts_cached().await?;
 
-        // Apply category filter
+        // Apply category and date filters
         let filtered: Vec<_> = all_posts
             .into_iter()
-            .filter(|p| category.map_or(true, |c| p.category.eq_ignore_ascii_case(c)))
+            .filter(|p| matches_filter(p, category, dates))
             .collect();
 
         // Apply sorting
@@ -70,8 +102,9 @@ impl AppState {
             .collect();
 
         log::info!(
-            "Filtered posts: category={:?}, sort_latest={}, limit={}, offset={}, result_count={}",
+            "Filtered posts: category={:?}, dates={:?}, sort_latest={}, limit={}, offset={}, result_count={}",
             category,
+            dates,
             sort_latest_first,
             limit,
             offset,
@@ -83,7 +116,7 @@ impl AppState {
 
     /// Get distinct categories from all posts.
     /// Uses cache-first strategy - same cache as REST endpoints.
-    pub async fn get_distinct_categories(&self) -> Result<Vec<String>, sqlx::Error> {
+    pub async fn get_distinct_categories(&self) -> Result<Vec<String>, DbError> {
         let all_posts = self.get_all_posts_cached().await?;
 
         let mut categories: Vec<String> = all_posts.iter().map(|p| p.category.clone()).collect();
@@ -96,28 +129,80 @@ impl AppState {
         Ok(categories)
     }
 
-    /// Count posts with optional category filter.
+    /// Count posts with optional category and date filters.
     /// Uses cache-first strategy.
-    pub async fn count_posts_filtered(&self, category: Option<&str>) -> Result<usize, sqlx::Error> {
+    pub async fn count_posts_filtered(
+        &self,
+        category: Option<&str>,
+        dates: DateRange,
+    ) -> Result<usize, DbError> {
         let all_posts = self.get_all_posts_cached().await?;
 
         let count = all_posts
             .iter()
-            .filter(|p| category.map_or(true, |c| p.category.eq_ignore_ascii_case(c)))
+            .filter(|p| matches_filter(p, category, dates))
             .count();
 
         Ok(count)
     }
 
+    /// Posts whose title or excerpt contains `query`, ignoring case, newest
+    /// first. Returns one page and the total number of matches.
+    /// Uses cache-first strategy.
+    pub async fn search_posts(
+        &self,
+        query: &str,
+        limit: i32,
+        offset: i32,
+    ) -> Result<(Vec<crate::posting::models::Post>, usize), DbError> {
+        let all_posts = self.get_all_posts_cached().await?;
+        let needle = query.to_lowercase();
+
+        let mut matches: Vec<_> = all_posts
+            .into_iter()
+            .filter(|p| {
+                p.title.to_lowercase().contains(&needle)
+                    || p.excerpt.to_lowercase().contains(&needle)
+            })
+            .collect();
+        matches.sort_by_key(|p| std::cmp::Reverse(p.created_at));
+
+        let total = matches.len();
+        let page: Vec<_> = matches
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect();
+
+        log::info!(
+            "Searched posts: query={:?}, limit={}, offset={}, total={}",
+            query,
+            limit,
+            offset,
+            total
+        );
+
+        Ok((page, total))
+    }
+
+    /// One page of posts, newest first, with titles and excerpts in `lang`
+    /// where translated and, with `embedded_assets`, that many assets of
+    /// each post
     pub async fn get_posts_smart_cached(
         &self,
         limit: i32,
         offset: i32,
-    ) -> Result<Vec<crate::posting::models::Post>, sqlx::Error> {
+        lang: &str,
+        embedded_assets: Option<i64>,
+    ) -> Result<Vec<crate::posting::models::Post>, DbError> {
         let page = (offset / limit) + 1;
 
         if page == 1 && limit <= 50 {
-            match self.get_all_posts_cached().await {
+            let cached = match embedded_assets {
+                Some(assets) => self.get_all_posts_cached_with_assets(lang, assets).await,
+                None => self.get_all_posts_cached_in(lang).await,
+            };
+            match cached {
                 Ok(posts) => {
                     // Apply pagination to cached results
                     let paginated_posts: Vec<crate::posting::models::Post> = posts
@@ -137,12 +222,27 @@ impl AppState {
                         e
                     );
                     // Fall back to database if cache fails
-                    self.get_posts_paginated(limit, offset).await
+                    self.get_posts_page(limit, offset, lang, embedded_assets).await
                 }
             }
         } else {
             // For other pages or larger limits, go directly to database
-            self.get_posts_paginated(limit, offset).await
+            self.get_posts_page(limit, offset, lang, embedded_assets).await
+        }
+    }
+
+    async fn get_posts_page(
+        &self,
+        limit: i32,
+        offset: i32,
+        lang: &str,
+        embedded_assets: Option<i64>,
+    ) -> Result<Vec<crate::posting::models::Post>, DbError> {
+        let posts = self.get_posts_paginated(limit, offset).await?;
+        let posts = self.translate_posts(posts, lang).await?;
+        match embedded_assets {
+            Some(assets) => self.embed_post_assets(posts, assets).await,
+            None => Ok(posts),
         }
     }
 
@@ -150,324 +250,501 @@ impl AppState {
         &self,
         limit: i32,
         offset: i32,
-    ) -> Result<Vec<crate::posting::models::Post>, sqlx::Error> {
-        sqlx::query_as!(
-            crate::posting::models::Post,
-            "SELECT p.id, p.title, p.category, p.date, p.excerpt, p.folder_id, p.created_at, p.updated_at
-             FROM posts p
-             ORDER BY p.created_at DESC
-             LIMIT $1 OFFSET $2",
-            i64::from(limit),
-            i64::from(offset)
-        )
-        .fetch_all(&self.pool)
+    ) -> Result<Vec<crate::posting::models::Post>, DbError> {
+        self.guarded(self.timed("get_posts_paginated", async {
+            sqlx::query_as(&format!(
+                "SELECT {} FROM {} ORDER BY p.created_at DESC LIMIT $1 OFFSET $2",
+                POST_COLUMNS, POST_SOURCE
+            ))
+            .bind(i64::from(limit))
+            .bind(i64::from(offset))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| {
+                log::error!("Error getting paginated posts: {:?}", e);
+                e
+            })
+        }))
         .await
-        .map_err(|e| {
-            log::error!("Error getting paginated posts: {:?}", e);
-            e
-        })
     }
 
-    pub async fn get_all_posts(&self) -> Result<Vec<crate::posting::models::Post>, sqlx::Error> {
-        sqlx::query_as!(
-            crate::posting::models::Post,
-            "SELECT p.id, p.title, p.category, p.date, p.excerpt, p.folder_id, p.created_at, p.updated_at
-             FROM posts p
-             ORDER BY p.created_at DESC"
-        )
-        .fetch_all(&self.pool)
+    pub async fn get_all_posts(&self) -> Result<Vec<crate::posting::models::Post>, DbError> {
+        self.guarded(self.timed("get_all_posts", async {
+            sqlx::query_as(&format!(
+                "SELECT {} FROM {} ORDER BY p.created_at DESC",
+                POST_COLUMNS, POST_SOURCE
+            ))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| {
+                log::error!("Error getting all posts: {:?}", e);
+                e
+            })
+        }))
         .await
-        .map_err(|e| {
-            log::error!("Error getting all posts: {:?}", e);
-            e
-        })
     }
 
     pub async fn insert_post(
         &self,
         post: &crate::posting::models::Post,
-    ) -> Result<(), sqlx::Error> {
-        sqlx::query!(
-            r#"
-            INSERT INTO posts (id, title, category, date, excerpt, folder_id, created_at, updated_at)
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+    ) -> Result<(), DbError> {
+        self.timed("insert_post", async {
+            sqlx::query!(
+                r#"
+            INSERT INTO posts (id, title, category, date, excerpt, folder_id, folder_uuid, created_at, updated_at, cover_asset_id)
+             VALUES ($1, $2, $3, $4, $5, $6, (SELECT id FROM folders WHERE name = $6), $7, $8, $9)
             "#,
-            post.id,
-            &post.title,
-            &post.category,
-            post.date,
-            &post.excerpt,
-            post.folder_id.as_deref(),
-            post.created_at,
-            post.updated_at
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| {
-            log::error!("Error inserting post record: {:?}", e);
-            e
-        })?;
+                post.id,
+                &post.title,
+                &post.category,
+                post.date,
+                &post.excerpt,
+                post.folder_id.as_deref(),
+                post.created_at,
+                post.updated_at,
+                post.cover_asset_id
+            )
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                log::error!("Error inserting post record: {:?}", e);
+                e
+            })?;
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 
     pub async fn update_post(
         &self,
         post: &crate::posting::models::Post,
-    ) -> Result<(), sqlx::Error> {
-        sqlx::query!(
-            r#"
+    ) -> Result<(), DbError> {
+        self.timed("update_post", async {
+            sqlx::query!(
+                r#"
             UPDATE posts
-             SET title = $2, category = $3, date = $4, excerpt = $5, folder_id = $6, updated_at = $7
+             SET title = $2, category = $3, date = $4, excerpt = $5, folder_id = $6,
+                 folder_uuid = (SELECT id FROM folders WHERE name = $6), updated_at = $7,
+                 cover_asset_id = $8
              WHERE id = $1
             "#,
-            post.id,
-            &post.title,
-            &post.category,
-            post.date,
-            &post.excerpt,
-            post.folder_id.as_deref(),
-            post.updated_at
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| {
-            log::error!("Error updating post record: {:?}", e);
-            e
-        })?;
-
-        self.post_cache.invalidate("all_posts").await;
-        Ok(())
-    }
-
-    pub async fn delete_post(&self, id: &Uuid) -> Result<(), sqlx::Error> {
-        sqlx::query!("DELETE FROM posts WHERE id = $1", id)
+                post.id,
+                &post.title,
+                &post.category,
+                post.date,
+                &post.excerpt,
+                post.folder_id.as_deref(),
+                post.updated_at,
+                post.cover_asset_id
+            )
             .execute(&self.pool)
             .await
             .map_err(|e| {
-                log::error!("Error deleting post: {:?}", e);
+                log::error!("Error updating post record: {:?}", e);
                 e
             })?;
 
-        self.post_cache.invalidate("all_posts").await;
-        Ok(())
+            self.post_cache.invalidate_all();
+            self.refresh_cache_entries(CacheKind::Posts).await;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn delete_post(&self, id: &Uuid) -> Result<(), DbError> {
+        self.timed("delete_post", async {
+            sqlx::query!("DELETE FROM posts WHERE id = $1", id)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| {
+                    log::error!("Error deleting post: {:?}", e);
+                    e
+                })?;
+
+            self.post_cache.invalidate_all();
+            self.refresh_cache_entries(CacheKind::Posts).await;
+            Ok(())
+        })
+        .await
     }
 
     pub async fn get_folder_contents(
         &self,
         folder_name: &str,
-    ) -> Result<Option<Vec<Uuid>>, sqlx::Error> {
-        log::debug!("Attempting to get contents for folder: {}", folder_name);
+    ) -> Result<Option<Vec<Uuid>>, DbError> {
+        self.timed("get_folder_contents", async {
+            log::debug!("Attempting to get contents for folder: {}", folder_name);
+
+            let folder_row = sqlx::query!("SELECT id FROM folders WHERE name = $1", folder_name)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| {
+                    log::error!("Error getting folder: {:?}", e);
+                    e
+                })?;
+
+            if let Some(folder_record) = folder_row {
+                let asset_rows = sqlx::query!(
+                    "SELECT asset_id FROM asset_folders WHERE folder_id = $1",
+                    folder_record.id
+                )
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| {
+                    log::error!("Error getting folder assets: {:?}", e);
+                    e
+                })?;
+
+                let asset_ids: Vec<Uuid> = asset_rows.into_iter().map(|row| row.asset_id).collect();
+
+                log::info!(
+                    "Retrieved {} assets from folder: {}",
+                    asset_ids.len(),
+                    folder_name
+                );
+                Ok(Some(asset_ids))
+            } else {
+                log::debug!("Folder not found: {}", folder_name);
+                Ok(None)
+            }
+        })
+        .await
+    }
 
-        let folder_row = sqlx::query!("SELECT id FROM folders WHERE name = $1", folder_name)
-            .fetch_optional(&self.pool)
-            .await
-            .map_err(|e| {
-                log::error!("Error getting folder: {:?}", e);
-                e
-            })?;
+    /// Name of the folder called `folder_name` ignoring case, if any
+    pub async fn find_folder_name(
+        &self,
+        folder_name: &str,
+    ) -> Result<Option<String>, DbError> {
+        self.timed("find_folder_name", async {
+            sqlx::query_scalar("SELECT name FROM folders WHERE lower(name) = lower($1) LIMIT 1")
+                .bind(folder_name)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
 
-        if let Some(folder_record) = folder_row {
-            let asset_rows = sqlx::query!(
-                "SELECT asset_id FROM asset_folders WHERE folder_id = $1",
-                folder_record.id
+    /// Delete the folder row called `folder_name` and its asset links,
+    /// keeping the assets. Returns how many links were removed.
+    pub async fn dissolve_folder(&self, folder_name: &str) -> Result<u64, DbError> {
+        self.timed("dissolve_folder", async {
+            let mut tx = self.pool.begin().await?;
+            let unlinked = sqlx::query(
+                "DELETE FROM asset_folders WHERE folder_id IN (SELECT id FROM folders WHERE name = $1)",
             )
+            .bind(folder_name)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            sqlx::query("DELETE FROM folders WHERE name = $1")
+                .bind(folder_name)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            Ok(unlinked)
+        })
+        .await
+    }
+
+    /// Delete post folder rows that no asset is linked to and no post
+    /// refers to any more. Returns the names removed.
+    pub async fn delete_orphaned_post_folders(&self) -> Result<Vec<String>, DbError> {
+        self.timed("delete_orphaned_post_folders", async {
+            sqlx::query_scalar(
+                r#"
+                DELETE FROM folders f
+                WHERE f.name LIKE $1
+                  AND NOT EXISTS (SELECT 1 FROM asset_folders af WHERE af.folder_id = f.id)
+                  AND NOT EXISTS (
+                      SELECT 1 FROM posts p WHERE p.folder_uuid = f.id OR p.folder_id = f.name
+                  )
+                RETURNING f.name
+                "#,
+            )
+            .bind(format!("{}%", crate::asset::folder_name::POST_FOLDER_PREFIX))
             .fetch_all(&self.pool)
             .await
-            .map_err(|e| {
-                log::error!("Error getting folder assets: {:?}", e);
-                e
-            })?;
+        })
+        .await
+    }
 
-            let asset_ids: Vec<Uuid> = asset_rows.into_iter().map(|row| row.asset_id).collect();
+    /// Replace the asset links of each folder with the given ids, creating
+    /// missing folders, in one transaction
+    pub async fn replace_folder_links(
+        &self,
+        links: &[(String, Vec<Uuid>)],
+    ) -> Result<(), DbError> {
+        self.timed("replace_folder_links", async {
+            let mut tx = self.pool.begin().await?;
+            for (folder_name, asset_ids) in links {
+                let folder_id: Uuid = sqlx::query_scalar(
+                    "INSERT INTO folders (name) VALUES ($1) ON CONFLICT (name) DO UPDATE SET name = $1 RETURNING id",
+                )
+                .bind(folder_name)
+                .fetch_one(&mut *tx)
+                .await?;
+                link_posts_to_folder(&mut tx, folder_id, folder_name).await?;
+                sqlx::query("DELETE FROM asset_folders WHERE folder_id = $1")
+                    .bind(folder_id)
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query(
+                    "INSERT INTO asset_folders (folder_id, asset_id) SELECT $1, UNNEST($2::uuid[]) ON CONFLICT DO NOTHING",
+                )
+                .bind(folder_id)
+                .bind(asset_ids)
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await?;
 
-            log::info!(
-                "Retrieved {} assets from folder: {}",
-                asset_ids.len(),
-                folder_name
-            );
-            Ok(Some(asset_ids))
-        } else {
-            log::debug!("Folder not found: {}", folder_name);
-            Ok(None)
-        }
+            // Posts list the assets of their folder
+            self.post_cache.invalidate_all();
+            self.refresh_cache_entries(CacheKind::Posts).await;
+            Ok(())
+        })
+        .await
     }
 
     pub async fn insert_folder_contents(
         &self,
         folder_name: &str,
         contents: &Vec<Uuid>,
-    ) -> Result<(), sqlx::Error> {
-        log::debug!(
-            "Attempting to insert folder contents for folder: {}, with {} assets",
-            folder_name,
-            contents.len()
-        );
+    ) -> Result<(), DbError> {
+        self.timed("insert_folder_contents", async {
+            log::debug!(
+                "Attempting to insert folder contents for folder: {}, with {} assets",
+                folder_name,
+                contents.len()
+            );
 
-        let folder_record = sqlx::query!("INSERT INTO folders (name) VALUES ($1) ON CONFLICT (name) DO UPDATE SET name = $1 RETURNING id", folder_name)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| {
-                log::error!("Error upserting folder: {:?}", e);
+            let folder_record = sqlx::query!("INSERT INTO folders (name) VALUES ($1) ON CONFLICT (name) DO UPDATE SET name = $1 RETURNING id", folder_name)
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    log::error!("Error upserting folder: {:?}", e);
+                    e
+                })?;
+            let folder_id = folder_record.id;
+            log::debug!(
+                "Got/created folder with ID: {} for name: {}",
+                folder_id,
+                folder_name
+            );
+
+            let mut tx = self.pool.begin().await.map_err(|e| {
+                log::error!("Error beginning transaction: {:?}", e);
                 e
             })?;
-        let folder_id = folder_record.id;
-        log::debug!(
-            "Got/created folder with ID: {} for name: {}",
-            folder_id,
-            folder_name
-        );
 
-        let mut tx = self.pool.begin().await.map_err(|e| {
-            log::error!("Error beginning transaction: {:?}", e);
-            e
-        })?;
+            link_posts_to_folder(&mut tx, folder_id, folder_name)
+                .await
+                .map_err(|e| {
+                    log::error!("Error linking posts to folder: {:?}", e);
+                    e
+                })?;
+
+            sqlx::query!("DELETE FROM asset_folders WHERE folder_id = $1", folder_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| {
+                    log::error!("Error deleting asset folders: {:?}", e);
+                    e
+                })?;
+
+            for asset_id in contents {
+                sqlx::query!(
+                    "INSERT INTO asset_folders (folder_id, asset_id) VALUES ($1, $2)",
+                    folder_id,
+                    asset_id
+                )
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| {
+                    log::error!("Error inserting asset folder: {:?}", e);
+                    e
+                })?;
+                log::debug!(
+                    "Associated asset ID: {} with folder ID: {}",
+                    asset_id,
+                    folder_id
+                );
+            }
 
-        sqlx::query!("DELETE FROM asset_folders WHERE folder_id = $1", folder_id)
-            .execute(&mut *tx)
-            .await
-            .map_err(|e| {
-                log::error!("Error deleting asset folders: {:?}", e);
+            tx.commit().await.map_err(|e| {
+                log::error!("Error committing transaction: {:?}", e);
                 e
             })?;
 
-        for asset_id in contents {
-            sqlx::query!(
-                "INSERT INTO asset_folders (folder_id, asset_id) VALUES ($1, $2)",
-                folder_id,
-                asset_id
-            )
-            .execute(&mut *tx)
-            .await
-            .map_err(|e| {
-                log::error!("Error inserting asset folder: {:?}", e);
-                e
-            })?;
-            log::debug!(
-                "Associated asset ID: {} with folder ID: {}",
-                asset_id,
-                folder_id
+            // Posts without a picked cover show the first image of their folder
+            self.post_cache.invalidate_all();
+            self.refresh_cache_entries(CacheKind::Posts).await;
+            log::info!(
+                "Successfully updated folder contents for folder: {}, with {} assets",
+                folder_name,
+                contents.len()
             );
-        }
-
-        tx.commit().await.map_err(|e| {
-            log::error!("Error committing transaction: {:?}", e);
-            e
-        })?;
-        log::info!(
-            "Successfully updated folder contents for folder: {}, with {} assets",
-            folder_name,
-            contents.len()
-        );
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 
-    pub async fn get_posting_by_id_with_assets(
-        &self,
-        id: &Uuid,
-    ) -> Result<Option<crate::posting::models::PostWithAssets>, sqlx::Error> {
-        let post = sqlx::query_as!(
-            crate::posting::models::Post,
-            "SELECT id, title, category, date, excerpt, folder_id, created_at, updated_at FROM posts WHERE id = $1",
-            id
+    /// Assets linked to the folder of the post, found through the folder id
+    /// so a renamed folder keeps its post
+    async fn get_post_asset_ids(&self, post_id: &Uuid) -> Result<Vec<Uuid>, DbError> {
+        sqlx::query_scalar!(
+            "SELECT af.asset_id FROM posts p JOIN asset_folders af ON af.folder_id = p.folder_uuid WHERE p.id = $1",
+            post_id
         )
-        .fetch_optional(&self.pool)
+        .fetch_all(&self.pool)
         .await
         .map_err(|e| {
-            log::error!("Error getting post by id: {:?}", e);
-            e
-        })?;
-
-        if let Some(post) = post {
-            let mut asset_ids = Vec::new();
+            log::error!("Error getting post assets: {:?}", e);
+            e.into()
+        })
+    }
 
-            if let Some(folder_name) = &post.folder_id {
-                if let Some(folder_asset_ids) = self.get_folder_contents(folder_name).await? {
-                    asset_ids = folder_asset_ids;
-                }
+    pub async fn get_posting_by_id_with_assets(
+        &self,
+        id: &Uuid,
+    ) -> Result<Option<crate::posting::models::PostWithAssets>, DbError> {
+        self.timed("get_posting_by_id_with_assets", async {
+            let post = self.get_post_by_id(id).await.map_err(DbError::into_sqlx)?;
+
+            if let Some(post) = post {
+                let asset_ids = self
+                    .get_post_asset_ids(&post.id)
+                    .await
+                    .map_err(DbError::into_sqlx)?;
+
+                Ok(Some(crate::posting::models::PostWithAssets {
+                    id: post.id,
+                    title: post.title,
+                    category: post.category,
+                    date: post.date,
+                    excerpt: post.excerpt,
+                    folder_id: post.folder_id,
+                    created_at: post.created_at,
+                    updated_at: post.updated_at,
+                    asset_ids,
+                    cover_asset_id: post.cover_asset_id,
+                    cover_filename: post.cover_filename,
+                    translations_available: post.translations_available,
+                    comment_count: post.comment_count,
+                }))
+            } else {
+                Ok(None)
             }
-
-            Ok(Some(crate::posting::models::PostWithAssets {
-                id: post.id,
-                title: post.title,
-                category: post.category,
-                date: post.date,
-                excerpt: post.excerpt,
-                folder_id: post.folder_id,
-                created_at: post.created_at,
-                updated_at: post.updated_at,
-                asset_ids,
-            }))
-        } else {
-            Ok(None)
-        }
+        })
+        .await
     }
 
+    /// Write `post` and the asset links of its folder. The cover is left
+    /// as stored: callers pass a post read earlier, whose cover asset may
+    /// have been deleted since.
     pub async fn upsert_posting_with_assets(
         &self,
         post: &crate::posting::models::PostWithAssets,
-    ) -> Result<(), sqlx::Error> {
-        sqlx::query!(
-            r#"
-            INSERT INTO posts (id, title, category, date, excerpt, folder_id, created_at, updated_at)
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+    ) -> Result<(), DbError> {
+        self.timed("upsert_posting_with_assets", async {
+            sqlx::query!(
+                r#"
+            INSERT INTO posts (id, title, category, date, excerpt, folder_id, folder_uuid, created_at, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, (SELECT id FROM folders WHERE name = $6), $7, $8)
              ON CONFLICT (id)
-             DO UPDATE SET title = $2, category = $3, date = $4, excerpt = $5, folder_id = $6, updated_at = $7
+             DO UPDATE SET title = $2, category = $3, date = $4, excerpt = $5, folder_id = $6,
+                 folder_uuid = EXCLUDED.folder_uuid, updated_at = $7
             "#,
-            post.id,
-            &post.title,
-            &post.category,
-            post.date,
-            &post.excerpt,
-            post.folder_id.as_deref(),
-            post.created_at,
-            post.updated_at
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| {
-            log::error!("Error upserting post record: {:?}", e);
-            e
-        })?;
+                post.id,
+                &post.title,
+                &post.category,
+                post.date,
+                &post.excerpt,
+                post.folder_id.as_deref(),
+                post.created_at,
+                post.updated_at
+            )
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                log::error!("Error upserting post record: {:?}", e);
+                e
+            })?;
 
-        if let Some(folder_name) = &post.folder_id {
-            if !post.asset_ids.is_empty() {
-                self.insert_folder_contents(folder_name, &post.asset_ids)
-                    .await?;
+            if let Some(folder_name) = &post.folder_id {
+                if !post.asset_ids.is_empty() {
+                    self.insert_folder_contents(folder_name, &post.asset_ids)
+                        .await
+                        .map_err(DbError::into_sqlx)?;
+                }
             }
-        }
 
-        self.post_cache.invalidate("all_posts").await;
-        Ok(())
+            self.post_cache.invalidate_all();
+            self.refresh_cache_entries(CacheKind::Posts).await;
+            Ok(())
+        })
+        .await
     }
 
+    /// All posts with the assets of their folders, newest first, in one
+    /// query. With `containing_asset` only the posts whose folder holds
+    /// that asset are returned.
     pub async fn get_all_postings_with_assets(
         &self,
-    ) -> Result<Vec<crate::posting::models::PostWithAssets>, sqlx::Error> {
-        let posts = self.get_all_posts().await?;
-
-        let mut result = Vec::new();
-        for post in posts {
-            let mut asset_ids = Vec::new();
-            if let Some(folder_name) = &post.folder_id {
-                if let Some(folder_asset_ids) = self.get_folder_contents(folder_name).await? {
-                    asset_ids = folder_asset_ids;
-                }
-            }
+        containing_asset: Option<Uuid>,
+    ) -> Result<Vec<crate::posting::models::PostWithAssets>, DbError> {
+        self.timed("get_all_postings_with_assets", async {
+            sqlx::query_as(&format!(
+                r#"
+                SELECT {},
+                       COALESCE(array_agg(af.asset_id) FILTER (WHERE af.asset_id IS NOT NULL), '{{}}') AS asset_ids
+                FROM {}
+                LEFT JOIN asset_folders af ON af.folder_id = p.folder_uuid
+                WHERE $1::uuid IS NULL
+                   OR EXISTS (
+                       SELECT 1 FROM asset_folders c
+                       WHERE c.folder_id = p.folder_uuid AND c.asset_id = $1
+                   )
+                GROUP BY p.id, f.name, cover.filename
+                ORDER BY p.created_at DESC
+                "#,
+                POST_COLUMNS, POST_SOURCE
+            ))
+            .bind(containing_asset)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| {
+                log::error!("Error getting posts with assets: {:?}", e);
+                e
+            })
+        })
+        .await
+    }
+}
 
-            result.push(crate::posting::models::PostWithAssets {
-                id: post.id,
-                title: post.title.clone(),
-                category: post.category.clone(),
-                date: post.date,
-                excerpt: post.excerpt.clone(),
-                folder_id: post.folder_id.clone(),
-                created_at: post.created_at,
-                updated_at: post.updated_at,
-                asset_ids,
-            });
-        }
+/// Point posts that name `folder_name` but have no folder row yet at
+/// `folder_id`. Posts are created before their folder, which only appears
+/// with the first asset.
+pub(crate) async fn link_posts_to_folder(
+    conn: &mut sqlx::PgConnection,
+    folder_id: Uuid,
+    folder_name: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE posts SET folder_uuid = $1 WHERE folder_id = $2 AND folder_uuid IS NULL")
+        .bind(folder_id)
+        .bind(folder_name)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
 
-        Ok(result)
-    }
+/// Whether `post` is in `category` (ignoring case) and dated within `dates`
+fn matches_filter(
+    post: &crate::posting::models::Post,
+    category: Option<&str>,
+    dates: DateRange,
+) -> bool {
+    category.is_none_or(|c| post.category.eq_ignore_ascii_case(c)) && dates.contains(post.date)
 }
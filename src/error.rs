@@ -0,0 +1,182 @@
+//! Typed handler error rendered as `ErrorResponse` JSON
+
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+
+use crate::asset::upload_session::UploadSessionError;
+use crate::db::DbError;
+use crate::posting::multipart_parser::MultipartParseError;
+use crate::{ErrorCode, ErrorResponse};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    Unauthorized(String),
+    #[error("{0}")]
+    PayloadTooLarge(String),
+    /// Sent with a `Retry-After` header
+    #[error("{message}")]
+    TooManyRequests { message: String, retry_after_secs: u64 },
+    /// A dependency is down, sent with a `Retry-After` header
+    #[error("{message}")]
+    ServiceUnavailable { message: String, retry_after_secs: u64 },
+    /// Storage backend failure. The message is returned to the client.
+    #[error("{0}")]
+    Storage(String),
+    /// Database failure. Only `context` is returned to the client, the
+    /// underlying error is logged.
+    #[error("{context}: {source}")]
+    Database {
+        context: String,
+        #[source]
+        source: DbError,
+    },
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl ApiError {
+    /// `map_err` helper attaching a client-facing message to a database
+    /// error. A duplicate key is answered with 409, a reference to a
+    /// missing row with 400, an open circuit breaker with 503 and anything
+    /// else with 500.
+    pub fn database<E: Into<DbError>>(context: &str) -> impl FnOnce(E) -> Self + '_ {
+        move |source| Self::from_db(context, source.into())
+    }
+
+    fn from_db(context: &str, source: DbError) -> Self {
+        match &source {
+            DbError::UniqueViolation { .. } => {
+                log::info!("{}: {}", context, source);
+                Self::Conflict(format!("{}: conflicts with an existing record", context))
+            }
+            DbError::ForeignKeyViolation { .. } => {
+                log::info!("{}: {}", context, source);
+                Self::BadRequest(format!("{}: refers to a record that does not exist", context))
+            }
+            DbError::Unavailable { retry_after_secs } => Self::ServiceUnavailable {
+                message: format!("{}: database temporarily unavailable", context),
+                retry_after_secs: *retry_after_secs,
+            },
+            DbError::Connection(_) | DbError::Other(_) => Self::Database {
+                context: context.to_string(),
+                source,
+            },
+        }
+    }
+
+    /// `map_err` helper replacing a storage error with a client-facing message
+    pub fn storage(context: &str) -> impl FnOnce(String) -> Self + '_ {
+        move |e| {
+            log::error!("{}: {}", context, e);
+            Self::Storage(context.to_string())
+        }
+    }
+
+    fn error_code(&self) -> ErrorCode {
+        match self {
+            Self::NotFound(_) => ErrorCode::NotFound,
+            Self::BadRequest(_) => ErrorCode::BadRequest,
+            Self::Conflict(_) => ErrorCode::Conflict,
+            Self::Unauthorized(_) => ErrorCode::Unauthorized,
+            Self::PayloadTooLarge(_) => ErrorCode::PayloadTooLarge,
+            Self::TooManyRequests { .. } => ErrorCode::TooManyRequests,
+            Self::ServiceUnavailable { .. } => ErrorCode::ServiceUnavailable,
+            Self::Storage(_) | Self::Database { .. } | Self::Internal(_) => {
+                ErrorCode::InternalServerError
+            }
+        }
+    }
+
+    /// The message returned to the client
+    pub fn message(&self) -> String {
+        match self {
+            Self::Database { context, .. } => context.clone(),
+            other => other.to_string(),
+        }
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(source: sqlx::Error) -> Self {
+        Self::from(DbError::from(source))
+    }
+}
+
+impl From<DbError> for ApiError {
+    fn from(source: DbError) -> Self {
+        Self::from_db("Database error", source)
+    }
+}
+
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        Self::Storage(message)
+    }
+}
+
+impl From<UploadSessionError> for ApiError {
+    fn from(error: UploadSessionError) -> Self {
+        match error {
+            UploadSessionError::NotFound(_) => Self::NotFound(error.to_string()),
+            UploadSessionError::Busy(_) | UploadSessionError::Incomplete(_) => {
+                Self::Conflict(error.to_string())
+            }
+        }
+    }
+}
+
+impl From<MultipartParseError> for ApiError {
+    fn from(error: MultipartParseError) -> Self {
+        match error {
+            MultipartParseError::FieldError(e)
+            | MultipartParseError::MetadataError(e)
+            | MultipartParseError::Utf8Error(e)
+            | MultipartParseError::SerializationError(e) => Self::BadRequest(e),
+            MultipartParseError::IoError(e) => Self::Internal(e),
+        }
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::Conflict(_) => StatusCode::CONFLICT,
+            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+            Self::ServiceUnavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Storage(_) | Self::Database { .. } | Self::Internal(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        if self.status_code().is_server_error() {
+            log::error!("{}", self);
+        }
+        let mut response = HttpResponse::build(self.status_code());
+        if let Self::TooManyRequests {
+            retry_after_secs, ..
+        }
+        | Self::ServiceUnavailable {
+            retry_after_secs, ..
+        } = self
+        {
+            response.insert_header((actix_web::http::header::RETRY_AFTER, *retry_after_secs));
+        }
+        response.json(ErrorResponse::new(
+            self.error_code().as_str(),
+            &self.message(),
+        ))
+    }
+}
@@ -0,0 +1,59 @@
+//! Extractor error handlers and fallback routes that answer with `ErrorResponse`
+//! JSON instead of actix's default plain-text bodies.
+
+use actix_web::error::{InternalError, JsonPayloadError, PathError, QueryPayloadError};
+use actix_web::{web, Error, HttpRequest, HttpResponse};
+
+use crate::ErrorResponse;
+
+/// Default JSON body limit (matches actix's built-in default)
+pub const DEFAULT_JSON_PAYLOAD_LIMIT: usize = 2 * 1024 * 1024;
+
+/// JSON extractor config with a size limit and structured errors
+pub fn json_config(limit: usize) -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(limit)
+        .error_handler(json_error_handler)
+}
+
+/// Path extractor config with structured errors
+pub fn path_config() -> web::PathConfig {
+    web::PathConfig::default().error_handler(path_error_handler)
+}
+
+/// Query extractor config with structured errors
+pub fn query_config() -> web::QueryConfig {
+    web::QueryConfig::default().error_handler(query_error_handler)
+}
+
+fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> Error {
+    let message = err.to_string();
+    let response = match &err {
+        JsonPayloadError::OverflowKnownLength { .. } | JsonPayloadError::Overflow { .. } => {
+            HttpResponse::PayloadTooLarge().json(ErrorResponse::new("PayloadTooLarge", &message))
+        }
+        JsonPayloadError::ContentType => HttpResponse::UnsupportedMediaType()
+            .json(ErrorResponse::new("UnsupportedMediaType", &message)),
+        _ => HttpResponse::BadRequest().json(ErrorResponse::bad_request(&message)),
+    };
+    InternalError::from_response(err, response).into()
+}
+
+fn path_error_handler(err: PathError, _req: &HttpRequest) -> Error {
+    let response = HttpResponse::BadRequest().json(ErrorResponse::bad_request(&err.to_string()));
+    InternalError::from_response(err, response).into()
+}
+
+fn query_error_handler(err: QueryPayloadError, _req: &HttpRequest) -> Error {
+    let response = HttpResponse::BadRequest().json(ErrorResponse::bad_request(&err.to_string()));
+    InternalError::from_response(err, response).into()
+}
+
+/// Fallback for unknown routes
+pub async fn not_found(req: HttpRequest) -> HttpResponse {
+    HttpResponse::NotFound().json(ErrorResponse::not_found(&format!(
+        "No route for {} {}",
+        req.method(),
+        req.path()
+    )))
+}
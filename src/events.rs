@@ -0,0 +1,184 @@
+//! Live feed of content changes for the admin UI.
+//!
+//! Handlers call [`AppState::publish_change`] after a successful mutation.
+//! Admins following `GET /api/events` receive `{"type", "id"}` messages over
+//! SSE, numbered so that a reconnecting client can send `Last-Event-ID` and
+//! get the changes it missed from the last [`REPLAY_CAPACITY`] events.
+
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::future::ready;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time::MissedTickBehavior;
+use tokio_stream::wrappers::{BroadcastStream, IntervalStream};
+use uuid::Uuid;
+
+use crate::auth::validate_request_token;
+use crate::webhook::WebhookEvent;
+use crate::AppState;
+
+/// Events kept for `Last-Event-ID` replay
+pub const REPLAY_CAPACITY: usize = 100;
+/// Comment line sent while nothing changes, so proxies keep the stream open
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(25);
+
+/// One change, as sent in the `data` line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChangeEvent {
+    /// Sequence number, sent as the SSE `id`
+    #[serde(skip)]
+    pub seq: u64,
+    #[serde(rename = "type")]
+    pub kind: WebhookEvent,
+    /// The post or asset that changed
+    pub id: Uuid,
+}
+
+impl ChangeEvent {
+    fn to_sse(&self) -> web::Bytes {
+        let data = serde_json::to_string(self).unwrap_or_default();
+        web::Bytes::from(format!("id: {}\ndata: {}\n\n", self.seq, data))
+    }
+}
+
+struct Replay {
+    next_seq: u64,
+    recent: VecDeque<Arc<ChangeEvent>>,
+}
+
+/// Broadcast channel and replay buffer, held by `AppState`
+pub struct ChangeFeed {
+    sender: broadcast::Sender<Arc<ChangeEvent>>,
+    replay: Mutex<Replay>,
+}
+
+impl Default for ChangeFeed {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(REPLAY_CAPACITY).0,
+            replay: Mutex::new(Replay {
+                next_seq: 1,
+                recent: VecDeque::with_capacity(REPLAY_CAPACITY),
+            }),
+        }
+    }
+}
+
+impl ChangeFeed {
+    /// Number the change, keep it for replay and send it to every follower
+    pub fn publish(&self, kind: WebhookEvent, id: Uuid) {
+        let mut replay = self.replay.lock().unwrap_or_else(|e| e.into_inner());
+        let event = Arc::new(ChangeEvent {
+            seq: replay.next_seq,
+            kind,
+            id,
+        });
+        replay.next_seq += 1;
+        if replay.recent.len() == REPLAY_CAPACITY {
+            replay.recent.pop_front();
+        }
+        replay.recent.push_back(event.clone());
+        // No followers is not an error
+        let _ = self.sender.send(event);
+    }
+
+    /// Buffered events after `last_seen`, and a receiver for the ones that
+    /// follow them. Without `last_seen` nothing is replayed.
+    pub fn subscribe(
+        &self,
+        last_seen: Option<u64>,
+    ) -> (Vec<Arc<ChangeEvent>>, broadcast::Receiver<Arc<ChangeEvent>>) {
+        // Holding the lock keeps `publish` from slipping an event between
+        // the two halves
+        let replay = self.replay.lock().unwrap_or_else(|e| e.into_inner());
+        let missed = match last_seen {
+            Some(last_seen) => replay
+                .recent
+                .iter()
+                .filter(|event| event.seq > last_seen)
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+        (missed, self.sender.subscribe())
+    }
+}
+
+impl AppState {
+    /// Announce a successful mutation to admin UIs and webhooks. `data` is
+    /// the webhook payload; the feed only carries the id.
+    pub fn publish_change<T: Serialize>(&self, event: WebhookEvent, id: Uuid, data: &T) {
+        self.changes.publish(event, id);
+        self.notify_webhooks(event, data);
+    }
+}
+
+fn last_event_id(req: &HttpRequest) -> Option<u64> {
+    req.headers()
+        .get("Last-Event-ID")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// Stream of post and asset changes as server-sent events (protected)
+///
+/// Each message is `{"type": "post.updated", "id": "..."}` with an SSE `id`.
+/// Send the last one back in `Last-Event-ID` when reconnecting to receive
+/// the changes missed meanwhile, if they are among the last 100. A comment
+/// line is sent every 25 seconds while nothing changes. The stream ends when
+/// the client falls too far behind; reconnecting catches up.
+#[utoipa::path(
+    operation_id = "streamChanges",
+    get,
+    path = "/api/events",
+    tag = "Admin",
+    params(("Last-Event-ID" = Option<u64>, Header, description = "Id of the last event received")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "SSE stream of content changes", content_type = "text/event-stream"),
+        (status = 401, response = crate::UnauthorizedResponse)
+    )
+)]
+pub async fn stream_changes(req: HttpRequest, state: web::Data<AppState>) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    let (missed, receiver) = state.changes.subscribe(last_event_id(&req));
+
+    // `None` marks the end of the stream
+    let changes = stream::iter(missed.into_iter().map(|event| Some(event.to_sse())))
+        .chain(
+            BroadcastStream::new(receiver)
+                // A lagging client is cut off and catches up on reconnect
+                .take_while(|received| ready(received.is_ok()))
+                .map(|received| received.ok().map(|event| event.to_sse())),
+        )
+        .chain(stream::once(ready(None)));
+
+    let mut ticks = tokio::time::interval_at(
+        tokio::time::Instant::now() + HEARTBEAT_INTERVAL,
+        HEARTBEAT_INTERVAL,
+    );
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let heartbeats =
+        IntervalStream::new(ticks).map(|_| Some(web::Bytes::from_static(b": heartbeat\n\n")));
+
+    let stream = stream::select(changes, heartbeats)
+        .take_while(|frame| ready(frame.is_some()))
+        .filter_map(|frame| ready(frame.map(Ok::<_, actix_web::Error>)));
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .insert_header(("Connection", "keep-alive"))
+        .streaming(stream)
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/events").route(web::get().to(stream_changes)));
+}
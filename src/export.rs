@@ -0,0 +1,259 @@
+//! CSV exports for the monthly reports to the kecamatan.
+//!
+//! Rows are written to the response as they are read from the database,
+//! so a large table is never held in memory. Every file starts with a
+//! UTF-8 byte order mark, without which Excel opens it as ANSI and
+//! mangles Indonesian names.
+
+use actix_web::http::header;
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use chrono::{DateTime, NaiveDate, Utc};
+use futures::{Stream, StreamExt};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::borrow::Cow;
+use std::io;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use utoipa::IntoParams;
+
+use crate::asset::download::content_disposition;
+use crate::auth::validate_request_token;
+use crate::error::ApiError;
+use crate::AppState;
+
+/// Written before the header row
+pub const BOM: &str = "\u{feff}";
+pub const POSTING_COLUMNS: &[&str] = &["title", "category", "date", "asset_count"];
+pub const ASSET_COLUMNS: &[&str] = &["name", "filename", "folders", "created_at"];
+/// CSV chunks waiting for a slow client
+const EXPORT_CHANNEL_CAPACITY: usize = 4;
+/// Rows are sent once this many bytes are buffered
+const CHUNK_BYTES: usize = 16 * 1024;
+
+const POSTINGS_SQL: &str = r#"
+    SELECT p.title, p.category, p.date,
+           (SELECT COUNT(*) FROM asset_folders af WHERE af.folder_id = p.folder_uuid) AS asset_count
+    FROM posts p
+    WHERE ($1::date IS NULL OR p.date >= $1) AND ($2::date IS NULL OR p.date <= $2)
+    ORDER BY p.date, p.created_at
+"#;
+
+const ASSETS_SQL: &str = r#"
+    SELECT a.name, a.filename,
+           COALESCE(string_agg(f.name, '; ' ORDER BY f.name), '') AS folders,
+           a.created_at
+    FROM assets a
+    LEFT JOIN asset_folders af ON af.asset_id = a.id
+    LEFT JOIN folders f ON f.id = af.folder_id
+    GROUP BY a.id
+    ORDER BY a.created_at, a.id
+"#;
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct PostingExportQuery {
+    /// First post date to include, `YYYY-MM-DD`
+    pub from: Option<NaiveDate>,
+    /// Last post date to include, `YYYY-MM-DD`
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(sqlx::FromRow)]
+struct PostingRow {
+    title: String,
+    category: String,
+    date: NaiveDate,
+    asset_count: i64,
+}
+
+impl PostingRow {
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.title.clone(),
+            self.category.clone(),
+            self.date.to_string(),
+            self.asset_count.to_string(),
+        ]
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct AssetRow {
+    name: String,
+    filename: String,
+    folders: String,
+    created_at: Option<DateTime<Utc>>,
+}
+
+impl AssetRow {
+    fn fields(&self) -> Vec<String> {
+        let created_at = self.created_at.map(|at| {
+            at.with_timezone(&crate::timezone::app_timezone())
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        });
+        vec![
+            self.name.clone(),
+            self.filename.clone(),
+            self.folders.clone(),
+            created_at.unwrap_or_default(),
+        ]
+    }
+}
+
+/// `field` as a CSV field: quoted, with quotes doubled, when it holds a
+/// comma, a quote or a line break
+pub fn escape_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// One CSV record, ending in CRLF
+pub fn csv_row<S: AsRef<str>>(fields: &[S]) -> String {
+    let mut row = fields
+        .iter()
+        .map(|field| escape_field(field.as_ref()))
+        .collect::<Vec<_>>()
+        .join(",");
+    row.push_str("\r\n");
+    row
+}
+
+/// Name of the downloaded file, e.g. `cakung-barat-postings-2024-08-31.csv`
+pub fn export_filename(table: &str, date: NaiveDate) -> String {
+    format!("cakung-barat-{}-{}.csv", table, date.format("%Y-%m-%d"))
+}
+
+/// Write the BOM, the header and a record per row to `sender`
+async fn write_csv<R>(
+    rows: impl Stream<Item = Result<R, sqlx::Error>>,
+    columns: &[&str],
+    fields: impl Fn(&R) -> Vec<String>,
+    sender: &mpsc::Sender<io::Result<web::Bytes>>,
+) -> io::Result<()> {
+    let mut buffer = format!("{}{}", BOM, csv_row(columns));
+    let mut rows = std::pin::pin!(rows);
+    while let Some(row) = rows.next().await {
+        let row = row.map_err(io::Error::other)?;
+        buffer.push_str(&csv_row(&fields(&row)));
+        if buffer.len() >= CHUNK_BYTES {
+            send(sender, std::mem::take(&mut buffer)).await?;
+        }
+    }
+    send(sender, buffer).await
+}
+
+async fn send(sender: &mpsc::Sender<io::Result<web::Bytes>>, chunk: String) -> io::Result<()> {
+    if chunk.is_empty() {
+        return Ok(());
+    }
+    sender.send(Ok(web::Bytes::from(chunk))).await.map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::BrokenPipe,
+            "client stopped reading the export",
+        )
+    })
+}
+
+/// Stream the CSV written by `write` as a download named after `table`
+fn csv_response<F, Fut>(table: &str, pool: PgPool, write: F) -> HttpResponse
+where
+    F: FnOnce(PgPool, mpsc::Sender<io::Result<web::Bytes>>) -> Fut,
+    Fut: std::future::Future<Output = ()> + 'static,
+{
+    let (sender, receiver) = mpsc::channel(EXPORT_CHANNEL_CAPACITY);
+    actix_web::rt::spawn(write(pool, sender));
+
+    let filename = export_filename(table, crate::timezone::today());
+    HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            content_disposition(false, &filename),
+        ))
+        .streaming(ReceiverStream::new(receiver))
+}
+
+/// Posts as CSV, optionally limited to a date range (protected)
+///
+/// Columns: `title`, `category`, `date` and `asset_count`, the number of
+/// assets in the post's folder. Ordered by date.
+#[utoipa::path(
+    operation_id = "exportPostingsCsv",
+    get,
+    path = "/api/admin/export/postings.csv",
+    tag = "Admin",
+    params(PostingExportQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Posts as CSV", content_type = "text/csv"),
+        (status = 400, description = "`from` is after `to`", body = crate::ErrorResponse),
+        (status = 401, response = crate::UnauthorizedResponse)
+    )
+)]
+pub async fn export_postings(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<PostingExportQuery>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+    let PostingExportQuery { from, to } = query.into_inner();
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            return ApiError::BadRequest(format!("from ({}) is after to ({})", from, to))
+                .error_response();
+        }
+    }
+
+    csv_response("postings", state.pool.clone(), move |pool, sender| async move {
+        let rows = sqlx::query_as::<_, PostingRow>(POSTINGS_SQL)
+            .bind(from)
+            .bind(to)
+            .fetch(&pool);
+        if let Err(e) = write_csv(rows, POSTING_COLUMNS, PostingRow::fields, &sender).await {
+            log::error!("Posting export stopped: {}", e);
+            // Ends the response early
+            let _ = sender.send(Err(e)).await;
+        }
+    })
+}
+
+/// Assets as CSV (protected)
+///
+/// Columns: `name`, `filename`, `folders` (separated by `; `) and
+/// `created_at` in the server's timezone. Oldest first.
+#[utoipa::path(
+    operation_id = "exportAssetsCsv",
+    get,
+    path = "/api/admin/export/assets.csv",
+    tag = "Admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Assets as CSV", content_type = "text/csv"),
+        (status = 401, response = crate::UnauthorizedResponse)
+    )
+)]
+pub async fn export_assets(req: HttpRequest, state: web::Data<AppState>) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    csv_response("assets", state.pool.clone(), |pool, sender| async move {
+        let rows = sqlx::query_as::<_, AssetRow>(ASSETS_SQL).fetch(&pool);
+        if let Err(e) = write_csv(rows, ASSET_COLUMNS, AssetRow::fields, &sender).await {
+            log::error!("Asset export stopped: {}", e);
+            // Ends the response early
+            let _ = sender.send(Err(e)).await;
+        }
+    })
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/admin/export/postings.csv").route(web::get().to(export_postings)))
+        .service(web::resource("/admin/export/assets.csv").route(web::get().to(export_assets)));
+}
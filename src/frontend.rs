@@ -0,0 +1,120 @@
+//! The built frontend, served from `FRONTEND_DIST_PATH` by the same binary.
+//!
+//! Files of the bundle are mounted at `/` after every server route, so the
+//! API always wins. A path that matches no file gets `index.html`, letting
+//! the client-side router handle it, except under `/api` and `/mcp`, which
+//! keep the JSON 404. Hashed build outputs are cached for a year; anything
+//! else, `index.html` included, is revalidated on every load.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use actix_files::{Files, NamedFile};
+use actix_web::body::MessageBody;
+use actix_web::dev::{fn_service, ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderValue, CACHE_CONTROL};
+use actix_web::http::StatusCode;
+use actix_web::middleware::{from_fn, Next};
+use actix_web::{web, Error};
+
+/// Page served for paths that match no file
+pub const INDEX_FILE: &str = "index.html";
+/// Paths that belong to the server, answered with JSON 404 instead of the page
+const SERVER_PREFIXES: &[&str] = &["/api", "/mcp"];
+const CACHE_IMMUTABLE: &str = "public, max-age=31536000, immutable";
+const CACHE_REVALIDATE: &str = "no-cache";
+
+#[derive(Debug, Clone)]
+pub struct Frontend {
+    dist: PathBuf,
+}
+
+impl Frontend {
+    /// The bundle in `dist`, which must contain `index.html`
+    pub fn new(dist: impl Into<PathBuf>) -> io::Result<Self> {
+        let dist = dist.into();
+        let index = dist.join(INDEX_FILE);
+        if !index.is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("FRONTEND_DIST_PATH has no {}", index.display()),
+            ));
+        }
+        Ok(Self { dist })
+    }
+
+    pub fn dist(&self) -> &Path {
+        &self.dist
+    }
+
+    /// Mount the bundle at `/`. Register it after the server routes.
+    pub fn config(&self, cfg: &mut web::ServiceConfig) {
+        let index = self.dist.join(INDEX_FILE);
+        cfg.service(
+            web::scope("").wrap(from_fn(cache_headers)).service(
+                Files::new("/", &self.dist)
+                    .index_file(INDEX_FILE)
+                    .default_handler(fn_service(move |req: ServiceRequest| {
+                        spa_fallback(req, index.clone())
+                    })),
+            ),
+        );
+    }
+}
+
+/// Whether `path` belongs to the server rather than the frontend
+pub fn is_server_path(path: &str) -> bool {
+    SERVER_PREFIXES.iter().any(|prefix| {
+        path.strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+/// Whether the file at `path` has a content hash in its name, like the
+/// `index-B2x9Kq1a.js` or `app.3f2a1b9c.css` of a build. Such a name
+/// changes with the content, so the file can be cached for good.
+pub fn is_hashed_asset(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or_default();
+    let Some((stem, _extension)) = name.rsplit_once('.') else {
+        return false;
+    };
+    let Some((_, hash)) = stem.rsplit_once(['-', '.']) else {
+        return false;
+    };
+    (8..=32).contains(&hash.len())
+        && hash.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && (hash.chars().any(|c| c.is_ascii_digit())
+            || (hash.chars().any(|c| c.is_ascii_uppercase())
+                && hash.chars().any(|c| c.is_ascii_lowercase())))
+}
+
+/// `index.html` for client-side routes, the JSON 404 for server paths
+async fn spa_fallback(req: ServiceRequest, index: PathBuf) -> Result<ServiceResponse, Error> {
+    let (req, _) = req.into_parts();
+    if is_server_path(req.path()) {
+        let response = crate::error_handlers::not_found(req.clone()).await;
+        return Ok(ServiceResponse::new(req, response));
+    }
+
+    let mut response = NamedFile::open_async(&index).await?.into_response(&req);
+    response
+        .headers_mut()
+        .insert(CACHE_CONTROL, HeaderValue::from_static(CACHE_REVALIDATE));
+    Ok(ServiceResponse::new(req, response))
+}
+
+/// Set `Cache-Control` on files served from the bundle
+async fn cache_headers(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let hashed = is_hashed_asset(req.path());
+    let mut res = next.call(req).await?;
+    let served = res.status().is_success() || res.status() == StatusCode::NOT_MODIFIED;
+    if served && !res.headers().contains_key(CACHE_CONTROL) {
+        let value = if hashed { CACHE_IMMUTABLE } else { CACHE_REVALIDATE };
+        res.headers_mut()
+            .insert(CACHE_CONTROL, HeaderValue::from_static(value));
+    }
+    Ok(res)
+}
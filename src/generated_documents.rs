@@ -0,0 +1,270 @@
+//! Log of letters issued through the MCP document tools.
+//!
+//! Every delivered letter leaves a row in `generated_documents` so the
+//! kelurahan can report how many letters of each kind were issued per
+//! month. The requester's NIK is never stored, only its HMAC-SHA256 under a
+//! server-side salt.
+
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use chrono::{DateTime, NaiveDate, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::auth::validate_request_token;
+use crate::error::ApiError;
+use crate::db::DbError;
+use crate::AppState;
+
+const DEFAULT_LIST_LIMIT: i64 = 20;
+const MAX_LIST_LIMIT: i64 = 100;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocumentLogConfig {
+    /// Key for the NIK hashes. None uses a random key per process, so
+    /// hashes of the same NIK differ across restarts.
+    pub nik_salt: Option<String>,
+}
+
+impl DocumentLogConfig {
+    /// Load using a custom variable lookup
+    pub fn from_lookup<F>(lookup: F) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let nik_salt = lookup("DOCUMENT_NIK_SALT")
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+        Self { nik_salt }
+    }
+}
+
+/// Hashes NIKs for the log so the same requester can be counted once
+/// without keeping the number itself
+#[derive(Clone)]
+pub struct NikHasher {
+    key: Vec<u8>,
+}
+
+impl NikHasher {
+    /// `None` draws a random key, see [`DocumentLogConfig::nik_salt`]
+    pub fn new(salt: Option<&str>) -> Self {
+        let key = match salt {
+            Some(salt) => salt.as_bytes().to_vec(),
+            None => std::iter::repeat_with(|| fastrand::u8(..))
+                .take(32)
+                .collect(),
+        };
+        Self { key }
+    }
+
+    /// Hex HMAC-SHA256 of the trimmed NIK
+    pub fn hash(&self, nik: &str) -> String {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(nik.trim().as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+impl std::fmt::Debug for NikHasher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NikHasher").finish_non_exhaustive()
+    }
+}
+
+/// A letter about to be written to the log
+#[derive(Debug, Clone)]
+pub struct NewGeneratedDocument {
+    pub tool_name: String,
+    pub requester_name: String,
+    pub requester_nik_hash: Option<String>,
+    pub filename: String,
+    /// `inline` or `url`
+    pub delivered_via: String,
+}
+
+/// A logged letter. The NIK hash is not exposed.
+#[derive(Debug, Clone, Serialize, ToSchema, sqlx::FromRow)]
+pub struct GeneratedDocumentRecord {
+    pub id: Uuid,
+    pub tool_name: String,
+    pub requester_name: String,
+    pub filename: String,
+    pub delivered_via: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Letters issued by one tool in one month
+#[derive(Debug, Clone, Serialize, ToSchema, sqlx::FromRow)]
+pub struct DocumentMonthCount {
+    /// `YYYY-MM`
+    pub month: String,
+    pub tool_name: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DocumentStatsResponse {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub months: Vec<DocumentMonthCount>,
+    pub total: i64,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DocumentStatsQuery {
+    /// First day counted (inclusive), e.g. 2025-01-01
+    pub from: Option<NaiveDate>,
+    /// Last day counted (inclusive)
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DocumentListQuery {
+    /// Entries returned, newest first (default 20, max 100)
+    pub limit: Option<i64>,
+}
+
+impl AppState {
+    pub async fn record_generated_document(
+        &self,
+        document: &NewGeneratedDocument,
+    ) -> Result<(), DbError> {
+        sqlx::query(
+            "INSERT INTO generated_documents \
+             (tool_name, requester_name, requester_nik_hash, filename, delivered_via) \
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(&document.tool_name)
+        .bind(&document.requester_name)
+        .bind(&document.requester_nik_hash)
+        .bind(&document.filename)
+        .bind(&document.delivered_via)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Letters per tool per month between `from` and `to`, both inclusive
+    pub async fn generated_document_stats(
+        &self,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<Vec<DocumentMonthCount>, DbError> {
+        sqlx::query_as::<_, DocumentMonthCount>(
+            "SELECT to_char(date_trunc('month', created_at), 'YYYY-MM') AS month, \
+                    tool_name, COUNT(*) AS count \
+             FROM generated_documents \
+             WHERE ($1::date IS NULL OR created_at >= $1::date) \
+               AND ($2::date IS NULL OR created_at < $2::date + 1) \
+             GROUP BY 1, 2 \
+             ORDER BY 1, 2",
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DbError::from)
+    }
+
+    pub async fn recent_generated_documents(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<GeneratedDocumentRecord>, DbError> {
+        sqlx::query_as::<_, GeneratedDocumentRecord>(
+            "SELECT id, tool_name, requester_name, filename, delivered_via, created_at \
+             FROM generated_documents \
+             ORDER BY created_at DESC \
+             LIMIT $1",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DbError::from)
+    }
+}
+
+/// Letters issued per tool per month (protected)
+#[utoipa::path(
+    operation_id = "getDocumentStats",
+    get,
+    path = "/api/admin/documents/stats",
+    tag = "Admin",
+    params(DocumentStatsQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Counts per tool per month", body = DocumentStatsResponse),
+        (status = 400, description = "`from` is after `to`"),
+        (status = 401, response = crate::UnauthorizedResponse)
+    )
+)]
+pub async fn get_document_stats(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<DocumentStatsQuery>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from > to {
+            return ApiError::BadRequest("`from` must not be after `to`".to_string())
+                .error_response();
+        }
+    }
+
+    match state.generated_document_stats(query.from, query.to).await {
+        Ok(months) => HttpResponse::Ok().json(DocumentStatsResponse {
+            from: query.from,
+            to: query.to,
+            total: months.iter().map(|m| m.count).sum(),
+            months,
+        }),
+        Err(e) => ApiError::database("Failed to count generated documents")(e).error_response(),
+    }
+}
+
+/// Most recently issued letters (protected)
+#[utoipa::path(
+    operation_id = "listGeneratedDocuments",
+    get,
+    path = "/api/admin/documents",
+    tag = "Admin",
+    params(DocumentListQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Logged letters, newest first", body = Vec<GeneratedDocumentRecord>),
+        (status = 400, description = "Limit out of range"),
+        (status = 401, response = crate::UnauthorizedResponse)
+    )
+)]
+pub async fn list_documents(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<DocumentListQuery>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_LIST_LIMIT);
+    if !(1..=MAX_LIST_LIMIT).contains(&limit) {
+        return ApiError::BadRequest(format!("limit must be between 1 and {}", MAX_LIST_LIMIT))
+            .error_response();
+    }
+
+    match state.recent_generated_documents(limit).await {
+        Ok(documents) => HttpResponse::Ok().json(documents),
+        Err(e) => ApiError::database("Failed to list generated documents")(e).error_response(),
+    }
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/admin/documents")
+            .service(web::resource("").route(web::get().to(list_documents)))
+            .service(web::resource("/stats").route(web::get().to(get_document_stats))),
+    );
+}
@@ -0,0 +1,72 @@
+//! Readiness endpoint
+
+use actix_web::{web, HttpResponse, Responder};
+use serde::Serialize;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+use crate::db::circuit::CircuitState;
+use crate::maintenance::MaintenanceStatus;
+use crate::AppState;
+
+const DATABASE_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+const STORAGE_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    /// `ready` or `unavailable`
+    pub status: String,
+    pub database: bool,
+    /// Circuit breaker of the busiest reads. While `open` they answer
+    /// 503 without querying the database.
+    pub database_circuit: CircuitState,
+    pub storage: bool,
+    /// Why the storage check failed, if it did
+    pub storage_error: Option<String>,
+    pub maintenance: MaintenanceStatus,
+}
+
+/// Readiness probe. Returns 503 when the database or the storage bucket is
+/// unavailable.
+#[utoipa::path(
+    operation_id = "getReadiness",
+    get,
+    path = "/readyz",
+    tag = "Health",
+    responses(
+        (status = 200, description = "Ready to serve traffic", body = ReadinessResponse),
+        (status = 503, description = "A dependency is unavailable", body = ReadinessResponse)
+    )
+)]
+pub async fn readyz(state: web::Data<AppState>) -> impl Responder {
+    let database = matches!(
+        tokio::time::timeout(
+            DATABASE_CHECK_TIMEOUT,
+            sqlx::query("SELECT 1").execute(&state.pool)
+        )
+        .await,
+        Ok(Ok(_))
+    );
+    let storage_error =
+        match tokio::time::timeout(STORAGE_CHECK_TIMEOUT, state.storage.health_check()).await {
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => Some(e),
+            Err(_) => Some("Storage health check timed out".to_string()),
+        };
+    let ready = database && storage_error.is_none();
+
+    let response = ReadinessResponse {
+        status: if ready { "ready" } else { "unavailable" }.to_string(),
+        database,
+        database_circuit: state.db_circuit.state(),
+        storage: storage_error.is_none(),
+        storage_error,
+        maintenance: state.maintenance.status(),
+    };
+
+    if ready {
+        HttpResponse::Ok().json(response)
+    } else {
+        HttpResponse::ServiceUnavailable().json(response)
+    }
+}
@@ -0,0 +1,305 @@
+//! Outbound HTTP client settings and retries for storage calls.
+//!
+//! Every request has a connect and a total timeout so a hung upstream cannot
+//! pin a worker. Idempotent storage calls go through [`Retry::send`], which
+//! retries timeouts, connection errors and 5xx responses but never 4xx.
+
+use prometheus::{HistogramOpts, HistogramTimer, HistogramVec, IntCounterVec, Opts, Registry};
+use std::time::Duration;
+
+use crate::config::parse_positive;
+
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 5;
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_POOL_MAX_IDLE_PER_HOST: usize = 16;
+const DEFAULT_RETRY_ATTEMPTS: u32 = 3;
+const DEFAULT_RETRY_BACKOFF_MS: u64 = 200;
+const DEFAULT_STORAGE_MAX_CONCURRENCY: usize = 8;
+/// Upper bound for a single retry delay
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpClientConfig {
+    pub connect_timeout_secs: u64,
+    /// Total time for a request, including reading the response body
+    pub request_timeout_secs: u64,
+    /// Idle connections kept open per host
+    pub pool_max_idle_per_host: usize,
+    /// Total attempts for retried calls, including the first one
+    pub retry_attempts: u32,
+    /// Delay before the first retry, doubled after each failure
+    pub retry_backoff_ms: u64,
+    /// Storage uploads, downloads and deletes allowed in flight at once
+    pub storage_max_concurrency: usize,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: DEFAULT_CONNECT_TIMEOUT_SECS,
+            request_timeout_secs: DEFAULT_REQUEST_TIMEOUT_SECS,
+            pool_max_idle_per_host: DEFAULT_POOL_MAX_IDLE_PER_HOST,
+            retry_attempts: DEFAULT_RETRY_ATTEMPTS,
+            retry_backoff_ms: DEFAULT_RETRY_BACKOFF_MS,
+            storage_max_concurrency: DEFAULT_STORAGE_MAX_CONCURRENCY,
+        }
+    }
+}
+
+impl HttpClientConfig {
+    /// Load using a custom variable lookup. Every timeout, retry and
+    /// concurrency setting must be a positive integer.
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let get = |key: &str| {
+            lookup(key)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let defaults = Self::default();
+
+        let connect_timeout_secs = match get("HTTP_CONNECT_TIMEOUT_SECS") {
+            Some(v) => parse_positive("HTTP_CONNECT_TIMEOUT_SECS", &v)? as u64,
+            None => defaults.connect_timeout_secs,
+        };
+        let request_timeout_secs = match get("HTTP_REQUEST_TIMEOUT_SECS") {
+            Some(v) => parse_positive("HTTP_REQUEST_TIMEOUT_SECS", &v)? as u64,
+            None => defaults.request_timeout_secs,
+        };
+        let pool_max_idle_per_host = match get("HTTP_POOL_MAX_IDLE_PER_HOST") {
+            Some(v) => parse_positive("HTTP_POOL_MAX_IDLE_PER_HOST", &v)?,
+            None => defaults.pool_max_idle_per_host,
+        };
+        let retry_attempts = match get("HTTP_RETRY_ATTEMPTS") {
+            Some(v) => u32::try_from(parse_positive("HTTP_RETRY_ATTEMPTS", &v)?)
+                .map_err(|_| format!("HTTP_RETRY_ATTEMPTS is too large, got '{}'", v))?,
+            None => defaults.retry_attempts,
+        };
+        let retry_backoff_ms = match get("HTTP_RETRY_BACKOFF_MS") {
+            Some(v) => parse_positive("HTTP_RETRY_BACKOFF_MS", &v)? as u64,
+            None => defaults.retry_backoff_ms,
+        };
+        let storage_max_concurrency = match get("STORAGE_MAX_CONCURRENCY") {
+            Some(v) => parse_positive("STORAGE_MAX_CONCURRENCY", &v)?,
+            None => defaults.storage_max_concurrency,
+        };
+
+        Ok(Self {
+            connect_timeout_secs,
+            request_timeout_secs,
+            pool_max_idle_per_host,
+            retry_attempts,
+            retry_backoff_ms,
+            storage_max_concurrency,
+        })
+    }
+
+    pub fn build_client(&self) -> Result<reqwest::Client, reqwest::Error> {
+        reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(self.connect_timeout_secs))
+            .timeout(Duration::from_secs(self.request_timeout_secs))
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .pool_idle_timeout(Duration::from_secs(900))
+            .user_agent("cakung-barat-server/1.0")
+            .build()
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            attempts: self.retry_attempts,
+            backoff: Duration::from_millis(self.retry_backoff_ms),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first one
+    pub attempts: u32,
+    /// Base delay before the first retry
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        HttpClientConfig::default().retry_policy()
+    }
+}
+
+impl RetryPolicy {
+    /// Delay after the given failed attempt (1-based): exponential, capped,
+    /// with up to half of it replaced by random jitter
+    pub fn backoff_after(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self.backoff.saturating_mul(factor).min(MAX_RETRY_BACKOFF);
+        let half = delay / 2;
+        half + half.mul_f64(fastrand::f64())
+    }
+}
+
+/// Retries, rate limiting, failures and latency of storage calls, per
+/// operation
+#[derive(Clone)]
+pub struct HttpMetrics {
+    retries: IntCounterVec,
+    rate_limited: IntCounterVec,
+    failures: IntCounterVec,
+    duration: HistogramVec,
+}
+
+impl Default for HttpMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HttpMetrics {
+    pub fn new() -> Self {
+        let retries = IntCounterVec::new(
+            Opts::new(
+                "storage_request_retries_total",
+                "Storage requests retried after a timeout or 5xx response",
+            )
+            .namespace("cakung_barat_server"),
+            &["operation"],
+        )
+        .expect("valid storage retry metric");
+        let rate_limited = IntCounterVec::new(
+            Opts::new(
+                "storage_rate_limited_total",
+                "Storage requests answered with 429 Too Many Requests",
+            )
+            .namespace("cakung_barat_server"),
+            &["operation"],
+        )
+        .expect("valid storage rate limit metric");
+        let failures = IntCounterVec::new(
+            Opts::new(
+                "storage_operation_failures_total",
+                "Storage operations that returned an error, on any backend",
+            )
+            .namespace("cakung_barat_server"),
+            &["operation"],
+        )
+        .expect("valid storage failure metric");
+        let duration = HistogramVec::new(
+            HistogramOpts::new(
+                "storage_operation_duration_seconds",
+                "Storage operation latency, including retries",
+            )
+            .namespace("cakung_barat_server")
+            .buckets(vec![0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0]),
+            &["operation"],
+        )
+        .expect("valid storage duration metric");
+
+        Self {
+            retries,
+            rate_limited,
+            failures,
+            duration,
+        }
+    }
+
+    /// Register the metrics so they are exported on `/metrics`
+    pub fn register(&self, registry: &Registry) -> prometheus::Result<()> {
+        registry.register(Box::new(self.retries.clone()))?;
+        registry.register(Box::new(self.rate_limited.clone()))?;
+        registry.register(Box::new(self.failures.clone()))?;
+        registry.register(Box::new(self.duration.clone()))
+    }
+
+    pub fn retry_count(&self, operation: &str) -> u64 {
+        self.retries.with_label_values(&[operation]).get()
+    }
+
+    pub fn rate_limited_count(&self, operation: &str) -> u64 {
+        self.rate_limited.with_label_values(&[operation]).get()
+    }
+
+    pub fn record_failure(&self, operation: &str) {
+        self.failures.with_label_values(&[operation]).inc();
+    }
+
+    pub fn failure_count(&self, operation: &str) -> u64 {
+        self.failures.with_label_values(&[operation]).get()
+    }
+
+    /// Number of recorded operations of this kind
+    pub fn operation_count(&self, operation: &str) -> u64 {
+        self.duration
+            .with_label_values(&[operation])
+            .get_sample_count()
+    }
+
+    /// Timer observing into the latency histogram when dropped
+    pub fn start_timer(&self, operation: &str) -> HistogramTimer {
+        self.duration.with_label_values(&[operation]).start_timer()
+    }
+}
+
+/// Retry policy together with the counter it reports to
+#[derive(Clone, Default)]
+pub struct Retry {
+    pub policy: RetryPolicy,
+    pub metrics: HttpMetrics,
+}
+
+impl Retry {
+    pub fn new(policy: RetryPolicy, metrics: HttpMetrics) -> Self {
+        Self { policy, metrics }
+    }
+
+    /// Send the request built by `request`, retrying timeouts, connection
+    /// errors and 5xx responses. The last response or error is returned
+    /// once attempts run out; 4xx responses are returned immediately.
+    pub async fn send<F>(
+        &self,
+        operation: &'static str,
+        request: F,
+    ) -> Result<reqwest::Response, reqwest::Error>
+    where
+        F: Fn() -> reqwest::RequestBuilder,
+    {
+        let mut attempt = 1;
+        loop {
+            let reason = match request().send().await {
+                Ok(response) if response.status().is_server_error() => {
+                    if attempt >= self.policy.attempts {
+                        return Ok(response);
+                    }
+                    format!("status {}", response.status())
+                }
+                Ok(response) => {
+                    if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                        log::warn!("Storage {} was rate limited", operation);
+                        self.metrics
+                            .rate_limited
+                            .with_label_values(&[operation])
+                            .inc();
+                    }
+                    return Ok(response);
+                }
+                Err(e) if (e.is_timeout() || e.is_connect()) && attempt < self.policy.attempts => {
+                    e.to_string()
+                }
+                Err(e) => return Err(e),
+            };
+
+            let delay = self.policy.backoff_after(attempt);
+            log::warn!(
+                "Storage {} attempt {}/{} failed ({}), retrying in {:?}",
+                operation,
+                attempt,
+                self.policy.attempts,
+                reason,
+                delay
+            );
+            self.metrics.retries.with_label_values(&[operation]).inc();
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
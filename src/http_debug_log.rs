@@ -0,0 +1,156 @@
+//! Opt-in logging of failed API calls, enabled with `DEBUG_HTTP_LOG`.
+//!
+//! Each `/api` response with status 400 or above produces one warning with
+//! the method, path, status, duration, request id and the start of the
+//! request body. The body is copied while the handler reads it, so nothing
+//! extra is buffered, and a body the handler never read is logged empty.
+//! Multipart bodies are not captured, headers are not logged, and the values
+//! of `password` and `authorization` fields are replaced.
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::http::header::{self, HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpMessage};
+use futures::StreamExt;
+use regex::Regex;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::OnceLock;
+use std::time::Instant;
+use uuid::Uuid;
+
+/// Target of the log records, for `RUST_LOG` filters
+pub const LOG_TARGET: &str = "cakung_barat_server::http_debug";
+/// Bytes of the request body included in a record
+pub const MAX_LOGGED_BODY: usize = 2048;
+/// Request id sent by the client, or generated and returned on failed calls
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+const REDACTED: &str = "[REDACTED]";
+
+fn json_secret() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    // The value may be cut off at the end of the captured body
+    PATTERN.get_or_init(|| {
+        Regex::new(
+            r#"(?i)("[^"]*(?:password|authorization)[^"]*"\s*:\s*)(?:"(?:[^"\\]|\\.)*(?:"|$)|[^,}\]\s]+)"#,
+        )
+        .expect("valid regex")
+    })
+}
+
+fn form_secret() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?i)((?:^|&)[^=&]*(?:password|authorization)[^=&]*=)[^&]*")
+            .expect("valid regex")
+    })
+}
+
+/// Replace the values of password and authorization fields in a JSON body,
+/// or in a form body when `content_type` is urlencoded
+pub fn redact_body(content_type: &str, body: &str) -> String {
+    if content_type.starts_with("application/x-www-form-urlencoded") {
+        form_secret()
+            .replace_all(body, format!("${{1}}{}", REDACTED))
+            .into_owned()
+    } else {
+        json_secret()
+            .replace_all(body, format!("${{1}}\"{}\"", REDACTED))
+            .into_owned()
+    }
+}
+
+#[derive(Default)]
+struct CapturedBody {
+    bytes: Vec<u8>,
+    truncated: bool,
+}
+
+/// Pass the payload through, keeping a copy of its first bytes
+fn capture(payload: Payload, captured: Rc<RefCell<CapturedBody>>) -> Payload {
+    let stream = payload.map(move |chunk| {
+        if let Ok(bytes) = &chunk {
+            let mut captured = captured.borrow_mut();
+            let room = MAX_LOGGED_BODY.saturating_sub(captured.bytes.len());
+            captured.truncated |= bytes.len() > room;
+            captured
+                .bytes
+                .extend_from_slice(&bytes[..bytes.len().min(room)]);
+        }
+        chunk
+    });
+    Payload::Stream {
+        payload: Box::pin(stream),
+    }
+}
+
+/// Middleware logging failed `/api` calls with the body the client sent
+pub async fn log_failed_requests(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    if !req.path().starts_with("/api/") {
+        return next.call(req).await;
+    }
+
+    let started = Instant::now();
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let multipart = content_type.starts_with("multipart/");
+    let captured = Rc::new(RefCell::new(CapturedBody::default()));
+    if !multipart {
+        let payload = req.take_payload();
+        req.set_payload(capture(payload, captured.clone()));
+    }
+    let method = req.method().clone();
+    let path = req.path().to_string();
+
+    let mut result = next.call(req).await;
+    let status = match &result {
+        Ok(response) => response.status(),
+        Err(e) => e.as_response_error().status_code(),
+    };
+    if status.as_u16() < 400 {
+        return result;
+    }
+
+    let body = if multipart {
+        "<multipart not captured>".to_string()
+    } else {
+        let captured = captured.borrow();
+        let mut body = redact_body(&content_type, &String::from_utf8_lossy(&captured.bytes));
+        if captured.truncated {
+            body.push_str("...");
+        }
+        body
+    };
+    log::warn!(
+        target: LOG_TARGET,
+        "{} {} -> {} in {} ms, request id {}, body: {}",
+        method,
+        path,
+        status.as_u16(),
+        started.elapsed().as_millis(),
+        request_id,
+        body
+    );
+
+    if let (Ok(response), Ok(value)) = (&mut result, HeaderValue::from_str(&request_id)) {
+        response
+            .headers_mut()
+            .insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+    }
+    result
+}
@@ -0,0 +1,118 @@
+//! Indonesian formatting of dates and numbers.
+//!
+//! Month names and number words come from the tables below, never from the
+//! system locale, so the output is the same on every machine. Timestamps
+//! are shown in the app time zone, see [`crate::timezone`].
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use chrono_tz::Tz;
+
+use crate::timezone::{app_timezone, today};
+
+/// Indonesian month names, January first.
+pub const MONTHS: [&str; 12] = [
+    "Januari",
+    "Februari",
+    "Maret",
+    "April",
+    "Mei",
+    "Juni",
+    "Juli",
+    "Agustus",
+    "September",
+    "Oktober",
+    "November",
+    "Desember",
+];
+
+/// Words for 0 to 9
+const DIGITS: [&str; 10] = [
+    "nol", "satu", "dua", "tiga", "empat", "lima", "enam", "tujuh", "delapan", "sembilan",
+];
+
+/// Words for each power of a thousand, starting at 10^3
+const SCALES: [&str; 6] = [
+    "ribu",
+    "juta",
+    "miliar",
+    "triliun",
+    "kuadriliun",
+    "kuintiliun",
+];
+
+/// `date` as written in letters, e.g. "12 November 2025"
+pub fn format_date_id(date: NaiveDate) -> String {
+    format!(
+        "{} {} {}",
+        date.day(),
+        MONTHS[date.month0() as usize],
+        date.year()
+    )
+}
+
+/// Today's date in the app time zone, as written in letters
+pub fn today_id() -> String {
+    format_date_id(today())
+}
+
+/// `instant` in the app time zone, e.g. "12 November 2025 14.30 WIB"
+pub fn format_datetime_id(instant: DateTime<Utc>) -> String {
+    format_datetime_id_in(instant, app_timezone())
+}
+
+/// `instant` in `tz`, with the zone's abbreviation
+pub fn format_datetime_id_in(instant: DateTime<Utc>, tz: Tz) -> String {
+    let local = instant.with_timezone(&tz);
+    format!(
+        "{} {}",
+        format_date_id(local.date_naive()),
+        local.format("%H.%M %Z")
+    )
+}
+
+/// `number` in words, e.g. 1500000 is "satu juta lima ratus ribu"
+pub fn terbilang(number: u64) -> String {
+    if number == 0 {
+        return DIGITS[0].to_string();
+    }
+
+    let mut groups = Vec::new();
+    let mut rest = number;
+    while rest > 0 {
+        groups.push((rest % 1000) as usize);
+        rest /= 1000;
+    }
+
+    let mut words: Vec<String> = Vec::new();
+    for (scale, &group) in groups.iter().enumerate().rev() {
+        match (scale, group) {
+            (_, 0) => {}
+            (0, _) => words.push(below_thousand(group)),
+            // 1000 is "seribu", but 10^6 is "satu juta"
+            (1, 1) => words.push("seribu".to_string()),
+            _ => words.push(format!("{} {}", below_thousand(group), SCALES[scale - 1])),
+        }
+    }
+    words.join(" ")
+}
+
+/// Words for 1 to 999
+fn below_thousand(number: usize) -> String {
+    let (hundreds, rest) = (number / 100, number % 100);
+    let mut words = Vec::new();
+    match hundreds {
+        0 => {}
+        1 => words.push("seratus".to_string()),
+        _ => words.push(format!("{} ratus", DIGITS[hundreds])),
+    }
+    match rest {
+        0 => {}
+        1..=9 => words.push(DIGITS[rest].to_string()),
+        10 => words.push("sepuluh".to_string()),
+        11 => words.push("sebelas".to_string()),
+        12..=19 => words.push(format!("{} belas", DIGITS[rest % 10])),
+        _ if rest % 10 == 0 => words.push(format!("{} puluh", DIGITS[rest / 10])),
+        _ => words.push(format!("{} puluh {}", DIGITS[rest / 10], DIGITS[rest % 10])),
+    }
+    words.join(" ")
+}
@@ -1,25 +1,90 @@
-use actix_cors::Cors;
-use actix_web::middleware::Compress;
-use actix_web::{http::header, web, App, HttpServer};
-use actix_web_prometheus::PrometheusMetricsBuilder;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceFactory, ServiceRequest, ServiceResponse};
+use actix_web::middleware::{from_fn, Compress, Condition};
+use actix_web::{web, App, HttpServer};
 use chrono;
-use dotenvy;
 use serde::{Deserialize, Serialize};
-use utoipa::{OpenApi, ToSchema};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi, ToResponse, ToSchema};
 use utoipa_swagger_ui::SwaggerUi;
 
 pub mod asset;
 pub mod auth;
+pub mod backup;
+pub mod cache;
+pub mod comment;
+pub mod compression;
+pub mod config;
 pub mod db;
+pub mod error;
+pub mod error_handlers;
+pub mod events;
+pub mod export;
+pub mod frontend;
+pub mod generated_documents;
+pub mod health;
+pub mod http_client;
+pub mod http_debug_log;
+pub mod i18n;
+pub mod maintenance;
 pub mod mcp;
+pub mod method_routing;
+pub mod openapi_servers;
 pub mod organization;
 pub mod posting;
+pub mod read_only;
+pub mod request_metrics;
+pub mod runtime_metrics;
+pub mod stats;
 pub mod storage;
+pub mod storage_usage;
+pub mod submission;
+pub mod timezone;
+pub mod webhook;
 
-pub use crate::db::AppState;
+pub use crate::db::{AppState, AppStateBuilder};
+pub use crate::error::ApiError;
+
+/// Machine-readable `error` value of an [`ErrorResponse`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum ErrorCode {
+    BadRequest,
+    Unauthorized,
+    NotFound,
+    Conflict,
+    PayloadTooLarge,
+    MethodNotAllowed,
+    UnsupportedMediaType,
+    TooManyRequests,
+    InternalServerError,
+    ServiceUnavailable,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::BadRequest => "BadRequest",
+            Self::Unauthorized => "Unauthorized",
+            Self::NotFound => "NotFound",
+            Self::Conflict => "Conflict",
+            Self::PayloadTooLarge => "PayloadTooLarge",
+            Self::MethodNotAllowed => "MethodNotAllowed",
+            Self::UnsupportedMediaType => "UnsupportedMediaType",
+            Self::TooManyRequests => "TooManyRequests",
+            Self::InternalServerError => "InternalServerError",
+            Self::ServiceUnavailable => "ServiceUnavailable",
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "error": "NotFound",
+    "message": "Post not found",
+    "timestamp": "2025-01-15T08:30:00+00:00"
+}))]
 pub struct ErrorResponse {
+    #[schema(value_type = ErrorCode)]
     pub error: String,
     pub message: String,
     pub timestamp: String,
@@ -35,199 +100,596 @@ impl ErrorResponse {
     }
 
     pub fn not_found(message: &str) -> Self {
-        Self::new("NotFound", message)
+        Self::new(ErrorCode::NotFound.as_str(), message)
     }
 
     pub fn bad_request(message: &str) -> Self {
-        Self::new("BadRequest", message)
+        Self::new(ErrorCode::BadRequest.as_str(), message)
     }
 
     pub fn internal_error(message: &str) -> Self {
-        Self::new("InternalServerError", message)
+        Self::new(ErrorCode::InternalServerError.as_str(), message)
     }
 }
 
-pub async fn run() -> std::io::Result<()> {
-    unsafe {
-        std::env::set_var("RUST_LOG", "info");
-    }
-    env_logger::init();
-
-    #[derive(OpenApi)]
-    #[openapi(
-        paths(
-            crate::posting::handlers::get_all_postings,
-            crate::posting::handlers::create_posting,
-            crate::posting::handlers::get_posting_by_id,
-            crate::posting::handlers::update_posting,
-            crate::posting::handlers::delete_posting,
-            crate::asset::handlers::upload_asset,
-            crate::asset::handlers::upload_asset_to_post,
-            crate::asset::handlers::delete_asset,
-            crate::asset::handlers::get_asset_by_id,
-            crate::asset::handlers::get_all_assets_structured,
-            crate::asset::handlers::create_folder_handler,
-            crate::asset::handlers::list_folder_handler,
-            crate::asset::handlers::get_assets_by_ids,
-            crate::organization::routes::get_all_members,
-            crate::organization::routes::create_member,
-            crate::organization::routes::update_member,
-            crate::organization::routes::delete_member
-        ),
-        components(
-            schemas(
-                posting::models::PostWithAssets,
-                posting::models::Post,
-                asset::models::Asset,
-                posting::models::CreatePostingRequest,
-                posting::models::UpdatePostingRequest,
-                asset::handlers::UploadAssetRequest,
-                asset::handlers::CreateFolderRequest,
-                asset::handlers::GetAssetsByIdsRequest,
-                posting::handlers::PostingResponse,
-                asset::handlers::AllAssetsResponse,
-                asset::handlers::FolderWithAssets,
-                storage::FolderContent,
-                ErrorResponse,
-                organization::model::OrganizationMember,
-                organization::model::CreateMemberRequest,
-                organization::model::UpdateMemberRequest,
-                auth::model::AdminInfo,
-                auth::model::LoginRequest,
-                auth::model::TokenResponse,
-                auth::model::RefreshRequest,
-                auth::model::CreateAdminRequest,
-                auth::model::AuthStatusResponse,
-            )
-        ),
-        tags(
-            (name = "Posting Service", description = "Posting CRUD endpoints."),
-            (name = "Asset Service", description = "Asset and Folder endpoints."),
-            (name = "Organization", description = "Organization Structure endpoints."),
-            (name = "Authentication", description = "Admin authentication endpoints.")
+/// Missing, invalid or expired admin token
+#[derive(ToResponse)]
+#[response(example = json!({
+    "error": "Unauthorized",
+    "message": "Invalid or expired token",
+    "timestamp": "2025-01-15T08:30:00+00:00"
+}))]
+pub struct UnauthorizedResponse(pub ErrorResponse);
+
+/// OpenAPI description of the REST API, served at `/api-doc/openapi.json`
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        crate::posting::handlers::get_all_postings,
+        crate::posting::handlers::create_posting,
+        crate::posting::handlers::get_posting_by_id,
+        crate::posting::handlers::update_posting,
+        crate::posting::handlers::delete_posting,
+        crate::posting::search::search_postings,
+        crate::posting::translation::put_posting_translation,
+        crate::posting::sanitize::get_rendered_posting,
+        crate::comment::handlers::create_comment,
+        crate::comment::handlers::list_post_comments,
+        crate::comment::handlers::list_comments,
+        crate::comment::handlers::approve_comment,
+        crate::comment::handlers::reject_comment,
+        crate::comment::handlers::delete_comment,
+        crate::submission::handlers::create_submission,
+        crate::submission::handlers::list_submissions,
+        crate::submission::handlers::get_submission,
+        crate::submission::handlers::assign_submission,
+        crate::submission::handlers::resolve_submission,
+        crate::asset::handlers::upload_asset,
+        crate::asset::handlers::upload_asset_to_post,
+        crate::asset::handlers::start_upload,
+        crate::asset::handlers::receive_upload,
+        crate::asset::handlers::get_upload_progress,
+        crate::asset::handlers::finalize_upload,
+        crate::asset::handlers::delete_asset,
+        crate::asset::handlers::get_asset_by_id,
+        crate::asset::handlers::get_all_assets_structured,
+        crate::asset::handlers::get_gallery,
+        crate::asset::handlers::create_folder_handler,
+        crate::asset::handlers::list_folder_handler,
+        crate::asset::handlers::dissolve_others_folder,
+        crate::asset::handlers::relativize_asset_urls,
+        crate::asset::handlers::backfill_asset_dimensions,
+        crate::asset::handlers::rebuild_asset_folders,
+        crate::asset::handlers::get_assets_by_ids,
+        crate::organization::routes::get_all_members,
+        crate::organization::routes::create_member,
+        crate::organization::routes::update_member,
+        crate::organization::routes::delete_member,
+        crate::organization::avatar::get_member_avatar,
+        crate::cache::handlers::get_cache_stats,
+        crate::cache::handlers::clear_cache,
+        crate::cache::handlers::list_cache_entries,
+        crate::cache::handlers::delete_cache_entry,
+        crate::health::readyz,
+        crate::read_only::set_read_only,
+        crate::maintenance::clean_up_post_folders,
+        crate::storage_usage::get_storage_usage,
+        crate::stats::get_dashboard_stats,
+        crate::export::export_postings,
+        crate::export::export_assets,
+        crate::events::stream_changes,
+        crate::webhook::handlers::list_webhooks,
+        crate::webhook::handlers::create_webhook,
+        crate::webhook::handlers::update_webhook,
+        crate::webhook::handlers::delete_webhook,
+        crate::webhook::handlers::list_dead_letters,
+        crate::backup::handlers::create_backup,
+        crate::backup::handlers::restore_backup,
+        crate::mcp::template_handlers::list_templates,
+        crate::mcp::template_handlers::replace_template,
+        crate::mcp::tool_handlers::refresh_tools,
+        crate::mcp::tool_handlers::set_browse_tools,
+        crate::generated_documents::get_document_stats,
+        crate::generated_documents::list_documents,
+        crate::auth::handlers::get_auth_status,
+        crate::auth::handlers::login,
+        crate::auth::handlers::refresh_token,
+        crate::auth::handlers::get_session,
+        crate::auth::handlers::delete_session,
+        crate::auth::handlers::list_admins,
+        crate::auth::handlers::create_admin,
+        crate::auth::handlers::update_admin,
+        crate::auth::handlers::delete_admin,
+        crate::mcp::streamable::post_handler,
+        crate::mcp::streamable::get_handler,
+        crate::mcp::streamable::delete_handler,
+        crate::mcp::handlers::sse_handler,
+        crate::mcp::handlers::rpc_handler
+    ),
+    components(
+        schemas(
+            posting::models::PostWithAssets,
+            posting::models::Post,
+            posting::models::PostDisplay,
+            asset::models::Asset,
+            posting::models::CreatePostingRequest,
+            posting::models::UpdatePostingRequest,
+            asset::handlers::UploadAssetRequest,
+            asset::handlers::CreateFolderRequest,
+            asset::handlers::GetAssetsByIdsRequest,
+            posting::handlers::PostingResponse,
+            posting::search::SearchHit,
+            posting::search::SearchResponse,
+            posting::translation::PostTranslation,
+            posting::translation::TranslationRequest,
+            posting::sanitize::RenderedPosting,
+            comment::Comment,
+            comment::CommentStatus,
+            comment::PublicComment,
+            comment::CreateCommentRequest,
+            comment::SubmittedComment,
+            submission::Submission,
+            submission::SubmissionStatus,
+            submission::SubmissionForm,
+            submission::SubmittedForm,
+            submission::AssignSubmissionRequest,
+            submission::ResolveSubmissionRequest,
+            asset::handlers::AllAssetsResponse,
+            asset::handlers::FolderWithAssets,
+            asset::handlers::UploadedFile,
+            asset::handlers::FailedUpload,
+            asset::handlers::DissolveOthersResponse,
+            asset::handlers::RelativizeUrlsResponse,
+            asset::handlers::BackfillDimensionsResponse,
+            asset::folder_rebuild::FolderRebuild,
+            asset::folder_rebuild::RebuildFoldersResponse,
+            asset::gallery::GalleryAsset,
+            asset::gallery::GalleryResponse,
+            asset::handlers::StartUploadRequest,
+            asset::handlers::FolderInclude,
+            asset::handlers::FolderAssetCount,
+            asset::handlers::FolderListing,
+            asset::models::AssetSummary,
+            asset::upload_session::UploadProgress,
+            storage::FolderContent,
+            ErrorResponse,
+            ErrorCode,
+            organization::model::OrganizationMember,
+            organization::model::MemberResponse,
+            organization::model::CreateMemberRequest,
+            organization::model::UpdateMemberRequest,
+            auth::model::AdminInfo,
+            auth::model::LoginRequest,
+            auth::model::TokenResponse,
+            auth::model::RefreshRequest,
+            auth::model::CreateAdminRequest,
+            auth::model::UpdateAdminRequest,
+            auth::model::AuthStatusResponse,
+            auth::model::SessionInfoResponse,
+            cache::CacheStatsResponse,
+            cache::CacheCounters,
+            cache::CacheEntryInfo,
+            health::ReadinessResponse,
+            maintenance::MaintenanceStatus,
+            db::circuit::CircuitState,
+            maintenance::PostFolderCleanup,
+            read_only::SetReadOnlyRequest,
+            read_only::ReadOnlyResponse,
+            storage_usage::StorageUsageReport,
+            storage_usage::FolderUsage,
+            stats::DashboardStats,
+            stats::CategoryCount,
+            webhook::WebhookEvent,
+            webhook::Webhook,
+            webhook::CreatedWebhook,
+            webhook::CreateWebhookRequest,
+            webhook::UpdateWebhookRequest,
+            webhook::WebhookDeadLetter,
+            backup::RestoreReport,
+            backup::RestoreCounts,
+            mcp::generators::TemplateInfo,
+            mcp::tool_handlers::SetBrowseToolsRequest,
+            mcp::tool_handlers::BrowseToolsResponse,
+            mcp::tool_handlers::ToolListRefreshResponse,
+            generated_documents::GeneratedDocumentRecord,
+            generated_documents::DocumentMonthCount,
+            generated_documents::DocumentStatsResponse,
         ),
-        servers(
-            (url = "https://cakung-barat-server-1065513777845.asia-southeast2.run.app", description = "Production server"),
-            (url = "https://5w4m7wvp-8080.asse.devtunnels.ms", description = "Staging server"),
-            (url = "http://127.0.0.1:8080", description = "Localhost Staging server")
+        responses(UnauthorizedResponse)
+    ),
+    tags(
+        (name = "Posting Service", description = "Posting CRUD endpoints."),
+        (name = "Asset Service", description = "Asset and Folder endpoints."),
+        (name = "Organization", description = "Organization Structure endpoints."),
+        (name = "Submission Service", description = "Complaint and contact form."),
+        (name = "Authentication", description = "Admin authentication endpoints."),
+        (name = "Cache", description = "Cache statistics and maintenance endpoints."),
+        (name = "Health", description = "Readiness probe."),
+        (name = "Admin", description = "Server administration endpoints."),
+        (name = "MCP", description = "Model Context Protocol transports (JSON-RPC 2.0).")
+    ),
+    modifiers(&SecurityAddon)
+)]
+pub struct ApiDoc;
+
+/// The OpenAPI document served at `/api-doc/openapi.json`, listing the
+/// servers from `crate::openapi_servers`
+pub fn api_doc() -> utoipa::openapi::OpenApi {
+    let mut doc = ApiDoc::openapi();
+    openapi_servers::openapi_servers().modify(&mut doc);
+    doc
+}
+
+/// Registers the security schemes the paths refer to
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(
+                crate::auth::API_KEY_HEADER,
+            ))),
+        );
+    }
+}
+
+/// An app wrapped in the server's middleware stack, innermost first. Shared
+/// with the tests so responses pass through the same layers, `Compress`
+/// included.
+pub fn middleware(
+    config: &config::ServerConfig,
+) -> App<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<impl MessageBody>,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    App::new()
+        .wrap(from_fn(method_routing::method_routing))
+        .wrap(from_fn(read_only::read_only_guard))
+        .wrap(Condition::new(
+            config.debug_http_log,
+            from_fn(http_debug_log::log_failed_requests),
+        ))
+        .wrap(from_fn(compression::compression_policy))
+        .wrap(Compress::default())
+        .wrap(from_fn(request_metrics::track_requests))
+        .wrap(from_fn(auth::metrics_auth_guard))
+        .wrap(config.cors())
+}
+
+/// Every route the server serves, including the OpenAPI document. Shared
+/// with the tests so they exercise the same route table.
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.configure(mcp::config)
+        .service(
+            web::scope("/api")
+                .configure(organization::routes::config)
+                .configure(auth::handlers::config) // Register auth routes
+                .configure(cache::handlers::config)
+                .configure(read_only::config)
+                .configure(maintenance::config)
+                .configure(storage_usage::config)
+                .configure(stats::config)
+                .configure(export::config)
+                .configure(events::config)
+                .configure(webhook::handlers::config)
+                .configure(backup::handlers::config)
+                .configure(mcp::template_handlers::config)
+                .configure(mcp::tool_handlers::config)
+                .configure(generated_documents::config)
+                .configure(comment::handlers::config)
+                .configure(submission::handlers::config)
+                .service(
+                    web::resource("/postings")
+                        .route(web::get().to(posting::handlers::get_all_postings))
+                        .route(web::post().to(posting::handlers::create_posting)),
+                )
+                .service(
+                    web::resource("/search")
+                        .route(web::get().to(posting::search::search_postings)),
+                )
+                .service(
+                    web::resource("/postings/{id}")
+                        .route(web::get().to(posting::handlers::get_posting_by_id))
+                        .route(web::put().to(posting::handlers::update_posting))
+                        .route(web::delete().to(posting::handlers::delete_posting)),
+                )
+                .service(
+                    web::resource("/postings/{id}/rendered")
+                        .route(web::get().to(posting::sanitize::get_rendered_posting)),
+                )
+                .service(
+                    web::resource("/postings/{id}/translations/{lang}")
+                        .route(web::put().to(posting::translation::put_posting_translation)),
+                )
+                .service(
+                    web::resource("/assets")
+                        .route(web::get().to(asset::handlers::get_all_assets_structured))
+                        .route(web::post().to(asset::handlers::upload_asset)),
+                )
+                .service(
+                    web::resource("/gallery").route(web::get().to(asset::handlers::get_gallery)),
+                )
+                .service(
+                    web::resource("/assets/posts/{post_id}")
+                        .route(web::post().to(asset::handlers::upload_asset_to_post)),
+                )
+                .service(
+                    web::resource("/admin/folders/others/dissolve")
+                        .route(web::post().to(asset::handlers::dissolve_others_folder)),
+                )
+                .service(
+                    web::resource("/admin/assets/relativize-urls")
+                        .route(web::post().to(asset::handlers::relativize_asset_urls)),
+                )
+                .service(
+                    web::resource("/admin/assets/backfill-dimensions")
+                        .route(web::post().to(asset::handlers::backfill_asset_dimensions)),
+                )
+                .service(
+                    web::resource("/admin/assets/rebuild-folders")
+                        .route(web::post().to(asset::handlers::rebuild_asset_folders)),
+                )
+                .service(
+                    web::resource("/assets/folders")
+                        .route(web::post().to(asset::handlers::create_folder_handler)),
+                )
+                .service(
+                    web::resource("/assets/folders/{folder_name:.*}")
+                        .route(web::get().to(asset::handlers::list_folder_handler)),
+                )
+                .service(
+                    web::resource("/assets/uploads")
+                        .route(web::post().to(asset::handlers::start_upload)),
+                )
+                .service(
+                    web::resource("/assets/uploads/{id}")
+                        .route(web::put().to(asset::handlers::receive_upload)),
+                )
+                .service(
+                    web::resource("/assets/uploads/{id}/progress")
+                        .route(web::get().to(asset::handlers::get_upload_progress)),
+                )
+                .service(
+                    web::resource("/assets/uploads/{id}/finalize")
+                        .route(web::post().to(asset::handlers::finalize_upload)),
+                )
+                .service(
+                    web::resource("/assets/by-ids")
+                        .route(web::post().to(asset::handlers::get_assets_by_ids)),
+                )
+                .service(
+                    web::resource("/assets/{id}")
+                        .route(web::get().to(asset::handlers::get_asset_by_id))
+                        .route(web::delete().to(asset::handlers::delete_asset)),
+                ),
         )
-    )]
-    struct ApiDoc;
+        .service(web::resource("/readyz").route(web::get().to(health::readyz)))
+        .service(
+            web::resource("/assets/serve/{filename:.*}")
+                .route(web::get().to(asset::handlers::serve_asset)),
+        )
+        .service(SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-doc/openapi.json", api_doc()));
+}
 
-    dotenvy::dotenv().ok(); // Load .env file
-    let supabase_config = crate::storage::SupabaseConfig::from_env().unwrap();
-    let app_state = match AppState::new_with_config(supabase_config).await {
+/// Start the server. Logging must already be initialized.
+pub async fn run(config: config::AppConfig) -> std::io::Result<()> {
+    auth::init_jwt_secret(&config.jwt);
+    if let Err(e) = auth::init_dummy_password_hash() {
+        log::error!("{}", e);
+        std::process::exit(1);
+    }
+    timezone::init_timezone(&config.timezone);
+    if let Err(e) = maintenance::init_temp_dir(&config.maintenance.temp_dir) {
+        log::error!(
+            "Failed to create temp dir {}: {}",
+            config.maintenance.temp_dir.display(),
+            e
+        );
+        std::process::exit(1);
+    }
+    openapi_servers::init_openapi_servers(config.server.openapi_servers.clone());
+    let server_config = config.server.clone();
+    let app_state = match AppState::new_with_config(&config).await {
         Ok(state) => web::Data::new(state),
         Err(e) => {
             log::error!("Failed to connect to database. Please check your SUPABASE_DATABASE_URL in .env and ensure the database is running. Error: {}", e);
             std::process::exit(1);
         }
     };
+    asset::public_url::init_public_urls(asset::public_url::PublicUrls::from_config(
+        config.server.public_base_url.as_deref(),
+        config.server.public_url_from_storage,
+        app_state.storage.clone(),
+    ));
 
     // Initialize MCP service
-    let mcp_registry = match mcp::tools::ToolRegistry::new() {
-        Ok(registry) => registry,
+    let mcp_registry = match mcp::tools::ToolRegistry::with_config(&config.generation) {
+        Ok(registry) => registry
+            .with_public_base_url(config.server.public_base_url.clone())
+            .with_nik_salt(config.documents.nik_salt.as_deref())
+            .with_rate_limits(config.mcp_rate_limits.clone()),
         Err(e) => {
             log::error!("Failed to initialize MCP tool registry: {}", e);
             std::process::exit(1);
         }
     };
+    if config.documents.nik_salt.is_none() {
+        log::warn!("DOCUMENT_NIK_SALT not set, NIK hashes in the document log change on restart");
+    }
     let mcp_service = mcp::McpService::new(mcp_registry);
     // Pass app_state to McpState for database access in async tools
-    let mcp_state = web::Data::new(std::sync::Arc::new(mcp::McpState::new(
-        mcp_service,
-        app_state.clone(),
-    )));
+    if config.mcp_auth.api_keys.is_empty() {
+        log::info!("MCP_API_KEYS not set, MCP calls require an admin access token");
+    }
+    let mcp_state = web::Data::new(std::sync::Arc::new(
+        mcp::McpState::new(mcp_service, app_state.clone())
+            .with_auth(config.mcp_auth.clone())
+            .with_sessions(&config.mcp_sessions),
+    ));
+
+    let metrics_registry = prometheus::Registry::new();
+    if let Err(e) = app_state.cache_metrics.register(&metrics_registry) {
+        log::error!("Failed to register cache metrics: {}", e);
+    }
+    if let Err(e) = app_state.db_metrics.register(&metrics_registry) {
+        log::error!("Failed to register database metrics: {}", e);
+    }
+    if let Err(e) = app_state.db_circuit.register(&metrics_registry) {
+        log::error!("Failed to register database circuit metrics: {}", e);
+    }
+    if let Err(e) = app_state.http_metrics.register(&metrics_registry) {
+        log::error!("Failed to register storage retry metrics: {}", e);
+    }
+    if let Err(e) = app_state.storage_usage.register(&metrics_registry) {
+        log::error!("Failed to register storage usage metrics: {}", e);
+    }
+    if let Err(e) = app_state.submission_metrics.register(&metrics_registry) {
+        log::error!("Failed to register submission metrics: {}", e);
+    }
+    if let Err(e) = app_state.runtime_metrics.register(&metrics_registry) {
+        log::error!("Failed to register runtime metrics: {}", e);
+    }
+    if let Err(e) = mcp_state
+        .service
+        .registry()
+        .call_metrics()
+        .register(&metrics_registry)
+    {
+        log::error!("Failed to register MCP tool call metrics: {}", e);
+    }
+
+    let request_metrics = web::Data::new(
+        request_metrics::RequestMetrics::new(metrics_registry)
+            .expect("Failed to register HTTP request metrics"),
+    );
+
+    let metrics_auth = config.metrics_auth;
+    if metrics_auth.is_none() {
+        log::warn!("METRICS_AUTH not set, /metrics is publicly accessible");
+    }
+    if server_config.debug_http_log {
+        log::warn!("DEBUG_HTTP_LOG enabled, bodies of failed API calls are logged");
+    }
 
-    let prometheus = PrometheusMetricsBuilder::new("cakung_barat_server")
-        .endpoint("/metrics")
-        .build()
-        .expect("Failed to create Prometheus metrics middleware");
+    let frontend = match &server_config.frontend_dist_path {
+        Some(dist) => {
+            let frontend = frontend::Frontend::new(dist)?;
+            log::info!("Serving the frontend from {}", frontend.dist().display());
+            Some(frontend)
+        }
+        None => None,
+    };
 
-    log::info!("Starting server at http://0.0.0.0:8080");
+    log::info!(
+        "Starting server at http://{}:{}",
+        server_config.host,
+        server_config.port
+    );
 
-    HttpServer::new(move || {
+    let app_config = server_config.clone();
+    let shutdown_state = app_state.clone();
+    let mut server = HttpServer::new(move || {
         let app_state = app_state.clone();
-        let prometheus = prometheus.clone();
-        let cors = Cors::default()
-            .allowed_origin("https://cakung-barat-server-1065513777845.asia-southeast2.run.app")
-            .allowed_origin("https://tsfarizi.github.io")
-            .allowed_origin("http://localhost:5173")
-            .allowed_origin("http://localhost:3000")
-            .allowed_origin("http://localhost:8080")
-            .allowed_origin("http://127.0.0.1:8080")
-            .allowed_methods(vec!["GET", "POST", "PUT", "DELETE", "OPTIONS"])
-            .allowed_headers(vec![
-                header::AUTHORIZATION,
-                header::ACCEPT,
-                header::CONTENT_TYPE,
-            ])
-            .supports_credentials()
-            .max_age(3600);
+        let request_metrics = request_metrics.clone();
 
         let mcp_state = mcp_state.clone();
-        App::new()
-            .wrap(Compress::default())
-            .wrap(prometheus)
-            .wrap(cors)
+        let metrics_auth = metrics_auth.clone();
+        let frontend = frontend.clone();
+        let json_payload_limit = app_config.json_payload_limit;
+        middleware(&app_config)
             .app_data(app_state)
             .app_data(mcp_state)
-            .configure(mcp::config)
-            .service(
-                web::scope("/api")
-                    .configure(organization::routes::config)
-                    .configure(auth::handlers::config) // Register auth routes
-                    .service(
-                        web::resource("/postings")
-                            .route(web::get().to(posting::handlers::get_all_postings))
-                            .route(web::post().to(posting::handlers::create_posting)),
-                    )
-                    .service(
-                        web::resource("/postings/{id}")
-                            .route(web::get().to(posting::handlers::get_posting_by_id))
-                            .route(web::put().to(posting::handlers::update_posting))
-                            .route(web::delete().to(posting::handlers::delete_posting)),
-                    )
-                    .service(
-                        web::resource("/assets")
-                            .route(web::get().to(asset::handlers::get_all_assets_structured))
-                            .route(web::post().to(asset::handlers::upload_asset)),
-                    )
-                    .service(
-                        web::resource("/assets/posts/{post_id}")
-                            .route(web::post().to(asset::handlers::upload_asset_to_post)),
-                    )
-                    .service(
-                        web::resource("/assets/folders")
-                            .route(web::post().to(asset::handlers::create_folder_handler)),
-                    )
-                    .service(
-                        web::resource("/assets/folders/{folder_name:.*}")
-                            .route(web::get().to(asset::handlers::list_folder_handler)),
-                    )
-                    .service(
-                        web::resource("/assets/by-ids")
-                            .route(web::post().to(asset::handlers::get_assets_by_ids)),
-                    )
-                    .service(
-                        web::resource("/assets/{id}")
-                            .route(web::get().to(asset::handlers::get_asset_by_id))
-                            .route(web::delete().to(asset::handlers::delete_asset)),
-                    ),
-            )
-            .service(
-                web::resource("/assets/serve/{filename:.*}")
-                    .route(web::get().to(asset::handlers::serve_asset)),
-            )
-            .service(
-                SwaggerUi::new("/swagger-ui/{_:.*}")
-                    .url("/api-doc/openapi.json", ApiDoc::openapi()),
-            )
+            .app_data(request_metrics)
+            .app_data(error_handlers::json_config(json_payload_limit))
+            .app_data(error_handlers::path_config())
+            .app_data(error_handlers::query_config())
+            .configure(|cfg| {
+                if let Some(metrics_auth) = metrics_auth {
+                    cfg.app_data(web::Data::new(metrics_auth));
+                }
+            })
+            .configure(request_metrics::config)
+            .configure(routes)
+            .configure(|cfg| {
+                if let Some(frontend) = frontend {
+                    frontend.config(cfg);
+                }
+            })
+            .default_service(web::route().to(error_handlers::not_found))
     })
     .backlog(8192)
-    .max_connections(25000)
-    .keep_alive(actix_web::http::KeepAlive::Os)
-    .bind(("0.0.0.0", 8080))?
-    .run()
-    .await
+    .max_connections(server_config.max_connections)
+    .keep_alive(actix_web::http::KeepAlive::Os);
+
+    if let Some(workers) = server_config.workers {
+        server = server.workers(workers);
+    }
+
+    let server = server
+        .shutdown_timeout(server_config.shutdown_timeout_secs)
+        .disable_signals()
+        .bind((server_config.host.as_str(), server_config.port))?
+        .run();
+
+    // Stop accepting connections on SIGTERM/SIGINT and let in-flight requests drain
+    let server_handle = server.handle();
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        log::info!("Shutdown signal received, draining in-flight requests");
+        server_handle.stop(true).await;
+    });
+
+    let result = server.await;
+
+    shutdown_state.shutdown().await;
+    log::info!("Shutdown complete");
+
+    result
+}
+
+/// Resolves when the process receives SIGINT (Ctrl+C) or SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            log::error!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                log::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
 }
@@ -1,6 +1,21 @@
+use cakung_barat_server::config::AppConfig;
 use cakung_barat_server::run;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    run().await
-}
\ No newline at end of file
+    unsafe {
+        std::env::set_var("RUST_LOG", "info");
+    }
+    env_logger::init();
+
+    dotenvy::dotenv().ok(); // Load .env file
+    let config = match AppConfig::from_env() {
+        Ok(config) => config,
+        Err(e) => {
+            log::error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    run(config).await
+}
@@ -0,0 +1,501 @@
+//! Periodic maintenance: stale temp file sweep, orphaned bucket objects,
+//! empty post folders and expired generated documents.
+//!
+//! Temp files are normally removed on drop, but a panic or an OOM kill leaves
+//! them behind. The server creates them in its own directory, see
+//! [`temp_dir`], so the sweep never touches files of other programs. Uploads that reach the bucket but never get an `assets` row
+//! (the insert failed, or the process died in between) are optionally
+//! removed as well. Every post gets a folder when created, which outlives
+//! the post; folders of deleted posts that never held an asset are removed.
+//! Letters stored by the MCP document tools are deleted once they are older
+//! than the configured retention.
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+use utoipa::ToSchema;
+
+use crate::asset::heic::kept_original;
+use crate::auth::validate_request_token;
+use crate::db::DbError;
+use crate::mcp::tools::delivery::GENERATED_PREFIX;
+use crate::{ApiError, AppState, ErrorResponse};
+
+const DEFAULT_INTERVAL_SECS: u64 = 60 * 60;
+const DEFAULT_GENERATED_RETENTION_DAYS: u64 = 7;
+/// Temp entries older than this are considered abandoned
+pub const TEMP_FILE_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+/// Prefix `tempfile` uses for `NamedTempFile` and `TempDir`
+const TEMP_FILE_PREFIX: &str = ".tmp";
+/// Directory under the system temp dir that holds this server's temp files
+const APP_TEMP_DIR: &str = "cakung-barat-server";
+
+static TEMP_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// `cakung-barat-server` under the system temp dir
+pub fn default_temp_dir() -> PathBuf {
+    std::env::temp_dir().join(APP_TEMP_DIR)
+}
+
+/// Create `dir` and use it for temp files. Only the first call has an effect.
+pub fn init_temp_dir(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let _ = TEMP_DIR.set(dir.to_path_buf());
+    Ok(())
+}
+
+/// Directory to create temp files in, the one the maintenance job sweeps.
+/// Before [`init_temp_dir`] it is [`default_temp_dir`], created on first use.
+pub fn temp_dir() -> &'static Path {
+    TEMP_DIR.get_or_init(|| {
+        let dir = default_temp_dir();
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            log::warn!("Failed to create temp dir {}: {}", dir.display(), e);
+        }
+        dir
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaintenanceConfig {
+    /// Time between runs, None disables the job
+    pub interval: Option<Duration>,
+    /// Directory the server creates its temp files in, and sweeps
+    pub temp_dir: PathBuf,
+    /// Delete bucket objects that have no `assets` row
+    pub reconcile_bucket: bool,
+    /// Delete empty folders of deleted posts
+    pub remove_post_folders: bool,
+    /// Age at which generated documents are deleted, None keeps them
+    pub generated_retention: Option<Duration>,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            interval: Some(Duration::from_secs(DEFAULT_INTERVAL_SECS)),
+            temp_dir: default_temp_dir(),
+            reconcile_bucket: false,
+            remove_post_folders: true,
+            generated_retention: Some(Duration::from_secs(
+                DEFAULT_GENERATED_RETENTION_DAYS * 24 * 60 * 60,
+            )),
+        }
+    }
+}
+
+impl MaintenanceConfig {
+    /// Load from process environment
+    pub fn from_env() -> Result<Self, String> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// Load using a custom variable lookup. `MAINTENANCE_INTERVAL_SECS=0`
+    /// disables the job.
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let get = |key: &str| {
+            lookup(key)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let defaults = Self::default();
+
+        let interval = match get("MAINTENANCE_INTERVAL_SECS") {
+            Some(v) => match v.parse::<u64>() {
+                Ok(0) => None,
+                Ok(secs) => Some(Duration::from_secs(secs)),
+                Err(_) => {
+                    return Err(format!(
+                        "MAINTENANCE_INTERVAL_SECS must be a non-negative integer, got '{}'",
+                        v
+                    ))
+                }
+            },
+            None => defaults.interval,
+        };
+        let temp_dir = get("TEMP_CLEANUP_DIR")
+            .map(PathBuf::from)
+            .unwrap_or(defaults.temp_dir);
+        // Other programs keep their files in the shared temp dir
+        if temp_dir == std::env::temp_dir() || temp_dir.parent().is_none() {
+            return Err(format!(
+                "TEMP_CLEANUP_DIR must be a directory of its own, not '{}'",
+                temp_dir.display()
+            ));
+        }
+        let reconcile_bucket = match get("MAINTENANCE_RECONCILE_BUCKET") {
+            Some(v) => match v.to_ascii_lowercase().as_str() {
+                "true" | "1" | "yes" => true,
+                "false" | "0" | "no" => false,
+                _ => {
+                    return Err(format!(
+                        "MAINTENANCE_RECONCILE_BUCKET must be true or false, got '{}'",
+                        v
+                    ))
+                }
+            },
+            None => defaults.reconcile_bucket,
+        };
+        let remove_post_folders = match get("MAINTENANCE_REMOVE_POST_FOLDERS") {
+            Some(v) => crate::config::parse_bool("MAINTENANCE_REMOVE_POST_FOLDERS", &v)?,
+            None => defaults.remove_post_folders,
+        };
+        let generated_retention = match get("GENERATED_DOCUMENT_RETENTION_DAYS") {
+            Some(v) => match v.parse::<u64>() {
+                Ok(0) => None,
+                Ok(days) => Some(Duration::from_secs(days * 24 * 60 * 60)),
+                Err(_) => return Err(format!(
+                    "GENERATED_DOCUMENT_RETENTION_DAYS must be a non-negative integer, got '{}'",
+                    v
+                )),
+            },
+            None => defaults.generated_retention,
+        };
+
+        Ok(Self {
+            interval,
+            temp_dir,
+            reconcile_bucket,
+            remove_post_folders,
+            generated_retention,
+        })
+    }
+}
+
+/// Outcome of the most recent maintenance run
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct MaintenanceStatus {
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_run_ok: Option<bool>,
+    pub temp_files_removed: usize,
+    pub orphaned_objects_removed: usize,
+    pub post_folders_removed: usize,
+    pub generated_documents_removed: usize,
+    pub last_error: Option<String>,
+}
+
+/// Outcome of [`AppState::remove_orphaned_post_folders`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct PostFolderCleanup {
+    /// Folder rows deleted
+    pub folders_removed: usize,
+    /// Storage placeholders deleted along with them
+    pub placeholders_removed: usize,
+}
+
+/// Maintenance state held by `AppState`
+#[derive(Default)]
+pub struct Maintenance {
+    status: RwLock<MaintenanceStatus>,
+    /// Orphans seen on the previous run, deleted if still orphaned on the next
+    orphan_candidates: Mutex<HashSet<String>>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Maintenance {
+    pub fn status(&self) -> MaintenanceStatus {
+        self.status
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Stop the periodic task, if one was started
+    pub fn stop(&self) {
+        let handle = self.handle.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(handle) = handle {
+            handle.abort();
+        }
+    }
+}
+
+/// Remove `tempfile` entries in `dir` last modified more than `max_age` before
+/// `now`. Returns the number of entries removed.
+pub fn sweep_temp_dir(dir: &Path, max_age: Duration, now: SystemTime) -> std::io::Result<usize> {
+    let mut removed = 0;
+
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with(TEMP_FILE_PREFIX)
+        {
+            continue;
+        }
+
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+        let age = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok());
+        if age.is_none_or(|age| age <= max_age) {
+            continue;
+        }
+
+        let path = entry.path();
+        let result = if metadata.is_dir() {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        match result {
+            Ok(()) => {
+                log::info!("Removed stale temp entry {}", path.display());
+                removed += 1;
+            }
+            Err(e) => log::warn!("Failed to remove temp entry {}: {}", path.display(), e),
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Asset uploads are stored at the bucket root as `<uuid>_<name>.<ext>`
+fn is_asset_object(name: &str) -> bool {
+    name.get(..36)
+        .is_some_and(|prefix| uuid::Uuid::parse_str(prefix).is_ok())
+        && name[36..].starts_with('_')
+}
+
+impl AppState {
+    /// Run one maintenance pass and record its status
+    pub async fn run_maintenance(&self, config: &MaintenanceConfig) -> MaintenanceStatus {
+        let mut status = MaintenanceStatus {
+            last_run_at: Some(Utc::now()),
+            ..Default::default()
+        };
+        let mut errors = Vec::new();
+
+        let temp_dir = config.temp_dir.clone();
+        let sweep = tokio::task::spawn_blocking(move || {
+            sweep_temp_dir(&temp_dir, TEMP_FILE_MAX_AGE, SystemTime::now())
+        })
+        .await;
+        match sweep {
+            Ok(Ok(removed)) => status.temp_files_removed = removed,
+            Ok(Err(e)) => errors.push(format!("Temp dir sweep failed: {}", e)),
+            Err(e) => errors.push(format!("Temp dir sweep panicked: {}", e)),
+        }
+
+        if config.reconcile_bucket {
+            match self.reconcile_bucket().await {
+                Ok(removed) => status.orphaned_objects_removed = removed,
+                Err(e) => errors.push(format!("Bucket reconciliation failed: {}", e)),
+            }
+        }
+
+        if config.remove_post_folders {
+            match self.remove_orphaned_post_folders().await {
+                Ok(cleanup) => status.post_folders_removed = cleanup.folders_removed,
+                Err(e) => errors.push(format!("Post folder cleanup failed: {}", e)),
+            }
+        }
+
+        if let Some(retention) = config.generated_retention {
+            match self.remove_expired_documents(retention).await {
+                Ok(removed) => status.generated_documents_removed = removed,
+                Err(e) => errors.push(format!("Generated document cleanup failed: {}", e)),
+            }
+        }
+
+        status.last_run_ok = Some(errors.is_empty());
+        if !errors.is_empty() {
+            let message = errors.join("; ");
+            log::warn!("Maintenance run finished with errors: {}", message);
+            status.last_error = Some(message);
+        }
+
+        *self
+            .maintenance
+            .status
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = status.clone();
+        status
+    }
+
+    /// Delete asset objects without an `assets` row. The bucket listing has no
+    /// creation time, so an object is only deleted once it has been orphaned
+    /// on two consecutive runs; in-flight uploads get at least one interval
+    /// to insert their row.
+    async fn reconcile_bucket(&self) -> Result<usize, String> {
+        let objects = self.storage.list_folder_contents("").await?;
+        let known: HashSet<String> = self
+            .get_all_assets()
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .flat_map(|asset| {
+                let original = kept_original(&asset.filename);
+                std::iter::once(asset.filename).chain(original)
+            })
+            .collect();
+
+        let orphans: HashSet<String> = objects
+            .into_iter()
+            .filter(|object| object.is_file && is_asset_object(&object.name))
+            .map(|object| object.name)
+            .filter(|name| !known.contains(name))
+            .collect();
+
+        let previous = std::mem::replace(
+            &mut *self
+                .maintenance
+                .orphan_candidates
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()),
+            orphans.clone(),
+        );
+
+        let mut removed = 0;
+        for name in orphans.intersection(&previous) {
+            match self.storage.delete_file(name).await {
+                Ok(()) => {
+                    log::info!("Removed orphaned bucket object {}", name);
+                    removed += 1;
+                }
+                Err(e) => log::warn!("Failed to remove orphaned object {}: {}", name, e),
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Delete post folders holding no assets whose post is gone, with their
+    /// storage placeholders. A placeholder that cannot be removed is only
+    /// logged, since its row is already gone.
+    pub async fn remove_orphaned_post_folders(&self) -> Result<PostFolderCleanup, DbError> {
+        let names = self.delete_orphaned_post_folders().await?;
+
+        let mut placeholders_removed = 0;
+        for name in &names {
+            match self.storage.remove_folder(name).await {
+                Ok(true) => placeholders_removed += 1,
+                Ok(false) => {}
+                Err(e) => log::warn!("Failed to remove storage folder {}: {}", name, e),
+            }
+        }
+        if !names.is_empty() {
+            log::info!(
+                "Removed {} empty post folders and {} storage placeholders",
+                names.len(),
+                placeholders_removed
+            );
+        }
+        Ok(PostFolderCleanup {
+            folders_removed: names.len(),
+            placeholders_removed,
+        })
+    }
+
+    /// Delete generated documents created more than `retention` ago, both the
+    /// object and its `assets` row.
+    async fn remove_expired_documents(&self, retention: Duration) -> Result<usize, String> {
+        let retention = chrono::Duration::from_std(retention).map_err(|e| e.to_string())?;
+        let cutoff = Utc::now() - retention;
+        let pattern = format!("{}/%", GENERATED_PREFIX);
+
+        let expired: Vec<(uuid::Uuid, String)> = self
+            .timed("select_expired_documents", async {
+                sqlx::query_as(
+                    "SELECT id, filename FROM assets WHERE filename LIKE $1 AND created_at < $2",
+                )
+                .bind(&pattern)
+                .bind(cutoff)
+                .fetch_all(&self.pool)
+                .await
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut removed = 0;
+        for (id, filename) in expired {
+            if let Err(e) = self.storage.delete_file(&filename).await {
+                log::warn!("Failed to remove generated document {}: {}", filename, e);
+                continue;
+            }
+            match self.delete_asset(&id).await {
+                Ok(()) => {
+                    log::info!("Removed expired generated document {}", filename);
+                    removed += 1;
+                }
+                Err(e) => log::warn!("Failed to delete asset row for {}: {}", filename, e),
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Spawn the periodic maintenance task. Does nothing when the interval is
+    /// disabled or a task is already running.
+    pub fn start_maintenance(&self, config: MaintenanceConfig) {
+        let Some(interval) = config.interval else {
+            log::info!("Maintenance job disabled");
+            return;
+        };
+
+        let mut handle = self
+            .maintenance
+            .handle
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if handle.is_some() {
+            return;
+        }
+
+        let state = self.clone();
+        *handle = Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                state.run_maintenance(&config).await;
+            }
+        }));
+        log::info!("Maintenance job started, interval {:?}", interval);
+    }
+}
+
+/// Remove empty folders of deleted posts now instead of waiting for the
+/// next maintenance run (protected)
+#[utoipa::path(
+    operation_id = "cleanUpPostFolders",
+    post,
+    path = "/api/admin/maintenance/post-folders",
+    tag = "Admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Empty post folders removed", body = PostFolderCleanup),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+    )
+)]
+pub async fn clean_up_post_folders(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    if let Err(e) = validate_request_token(&req) {
+        return Ok(e.error_response());
+    }
+
+    let cleanup = state
+        .remove_orphaned_post_folders()
+        .await
+        .map_err(ApiError::database("Failed to remove post folders"))?;
+    Ok(HttpResponse::Ok().json(cleanup))
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/admin/maintenance/post-folders")
+            .route(web::post().to(clean_up_post_folders)),
+    );
+}
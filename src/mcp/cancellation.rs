@@ -0,0 +1,97 @@
+//! In-flight MCP requests and `notifications/cancelled`.
+//!
+//! Every request with an id is registered while it runs. A cancel
+//! notification from the same session trips its token: tools stop at the
+//! next stage boundary and the response is dropped instead of sent.
+
+use dashmap::DashMap;
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+/// Method of the notification clients send to cancel a request
+pub const CANCELLED_NOTIFICATION: &str = "notifications/cancelled";
+
+/// Request ids are only unique per client, so they are scoped by session
+type RequestKey = (String, String);
+
+/// Requests currently being handled, keyed by session and request id
+#[derive(Default)]
+pub struct InFlightRequests {
+    requests: DashMap<RequestKey, (u64, CancellationToken)>,
+    next_entry: AtomicU64,
+}
+
+impl InFlightRequests {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track request `id`; the entry is removed when the guard drops
+    pub fn register(self: &Arc<Self>, session: Option<&str>, id: &Value) -> InFlightGuard {
+        let key = request_key(session, id);
+        let entry = self.next_entry.fetch_add(1, Ordering::Relaxed);
+        let token = CancellationToken::new();
+        self.requests.insert(key.clone(), (entry, token.clone()));
+        InFlightGuard {
+            requests: Arc::clone(self),
+            key,
+            entry,
+            token,
+        }
+    }
+
+    /// Cancel request `id`, `false` when it is not running
+    pub fn cancel(&self, session: Option<&str>, id: &Value) -> bool {
+        match self.requests.get(&request_key(session, id)) {
+            Some(request) => {
+                request.1.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+}
+
+/// Registration of one running request
+pub struct InFlightGuard {
+    requests: Arc<InFlightRequests>,
+    key: RequestKey,
+    entry: u64,
+    token: CancellationToken,
+}
+
+impl InFlightGuard {
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        // A later request may have reused the id; leave its entry alone
+        self.requests
+            .requests
+            .remove_if(&self.key, |_, (entry, _)| *entry == self.entry);
+    }
+}
+
+/// `requestId` of a `notifications/cancelled` message
+pub fn cancelled_request_id(params: Option<&Value>) -> Option<&Value> {
+    params?
+        .get("requestId")
+        .filter(|id| id.is_string() || id.is_number())
+}
+
+fn request_key(session: Option<&str>, id: &Value) -> RequestKey {
+    (session.unwrap_or_default().to_string(), id.to_string())
+}
@@ -70,6 +70,7 @@ impl ContentBuilder {
         ToolResult {
             content: self.items,
             is_error: self.is_error,
+            structured_content: None,
         }
     }
 }
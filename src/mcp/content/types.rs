@@ -4,6 +4,7 @@ use base64::Engine;
 use base64::engine::general_purpose::STANDARD as BASE64;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 /// Metadata for file content.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -185,6 +186,13 @@ pub struct ToolResult {
     /// Whether this result represents an error
     #[serde(rename = "isError")]
     pub is_error: bool,
+    /// Machine-readable result, matching the tool's `outputSchema`
+    #[serde(
+        rename = "structuredContent",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub structured_content: Option<Value>,
 }
 
 impl ToolResult {
@@ -193,6 +201,18 @@ impl ToolResult {
         Self {
             content,
             is_error: false,
+            structured_content: None,
+        }
+    }
+
+    /// Create success with structured content. The value is repeated as
+    /// JSON text after the summary for clients that only read `content`.
+    pub fn structured(summary: impl Into<String>, value: Value) -> Self {
+        let json_text = serde_json::to_string_pretty(&value).unwrap_or_else(|_| "{}".to_string());
+        Self {
+            content: vec![ContentItem::text(summary), ContentItem::text(json_text)],
+            is_error: false,
+            structured_content: Some(value),
         }
     }
 
@@ -201,6 +221,7 @@ impl ToolResult {
         Self {
             content: vec![ContentItem::text(message)],
             is_error: true,
+            structured_content: None,
         }
     }
 
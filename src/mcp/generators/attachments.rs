@@ -0,0 +1,154 @@
+//! Images embedded in generated letters.
+//!
+//! A request may point at an uploaded pas foto and a scanned signature by
+//! asset id. The registry downloads them before rendering, the engine hands
+//! them to Typst as files next to the main source, and the templates show
+//! `lampiran.foto` / `lampiran.ttd` whenever they are not `none`.
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::common::escape_typst_string;
+use crate::mcp::content::file::detect_mime_from_bytes;
+
+/// Asset ids of the images to embed, shared by every letter request.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+pub struct Lampiran {
+    #[serde(default)]
+    #[schemars(
+        with = "Option<String>",
+        extend("format" = "uuid"),
+        description = "ID aset pas foto pemohon (PNG/JPEG), opsional"
+    )]
+    pub foto_asset_id: Option<Uuid>,
+    #[serde(default)]
+    #[schemars(
+        with = "Option<String>",
+        extend("format" = "uuid"),
+        description = "ID aset scan tanda tangan pemohon (PNG/JPEG), opsional"
+    )]
+    pub ttd_asset_id: Option<Uuid>,
+}
+
+impl Lampiran {
+    /// Referenced images with the kind each one is used as
+    pub fn ids(&self) -> impl Iterator<Item = (AttachmentKind, Uuid)> {
+        [
+            (AttachmentKind::Foto, self.foto_asset_id),
+            (AttachmentKind::Ttd, self.ttd_asset_id),
+        ]
+        .into_iter()
+        .filter_map(|(kind, id)| Some((kind, id?)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids().next().is_none()
+    }
+}
+
+/// Where an image goes on the letter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachmentKind {
+    /// Pas foto of the applicant
+    Foto,
+    /// Scanned signature of the applicant
+    Ttd,
+}
+
+impl AttachmentKind {
+    /// Request field holding the asset id
+    pub fn field(self) -> &'static str {
+        match self {
+            Self::Foto => "foto_asset_id",
+            Self::Ttd => "ttd_asset_id",
+        }
+    }
+
+    fn stem(self) -> &'static str {
+        match self {
+            Self::Foto => "foto",
+            Self::Ttd => "ttd",
+        }
+    }
+}
+
+/// A downloaded image, checked to be one Typst can embed
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    kind: AttachmentKind,
+    extension: &'static str,
+    data: Vec<u8>,
+}
+
+impl Attachment {
+    /// `None` unless `data` is a PNG or JPEG image
+    pub fn new(kind: AttachmentKind, data: Vec<u8>) -> Option<Self> {
+        let extension = match detect_mime_from_bytes(&data)? {
+            "image/png" => "png",
+            "image/jpeg" => "jpg",
+            _ => return None,
+        };
+        Some(Self {
+            kind,
+            extension,
+            data,
+        })
+    }
+
+    pub fn kind(&self) -> AttachmentKind {
+        self.kind
+    }
+
+    /// Path the templates load the image from, e.g. `foto.png`
+    pub fn path(&self) -> String {
+        format!("{}.{}", self.kind.stem(), self.extension)
+    }
+}
+
+/// Images available to one rendering
+#[derive(Debug, Clone, Default)]
+pub struct Attachments {
+    foto: Option<Attachment>,
+    ttd: Option<Attachment>,
+}
+
+impl Attachments {
+    /// Add `attachment`, replacing any earlier image of the same kind
+    pub fn insert(&mut self, attachment: Attachment) {
+        match attachment.kind {
+            AttachmentKind::Foto => self.foto = Some(attachment),
+            AttachmentKind::Ttd => self.ttd = Some(attachment),
+        }
+    }
+
+    pub fn get(&self, kind: AttachmentKind) -> Option<&Attachment> {
+        match kind {
+            AttachmentKind::Foto => self.foto.as_ref(),
+            AttachmentKind::Ttd => self.ttd.as_ref(),
+        }
+    }
+
+    /// Contents of the image stored under `path`, as requested by Typst
+    pub fn file(&self, path: &str) -> Option<&[u8]> {
+        [&self.foto, &self.ttd]
+            .into_iter()
+            .flatten()
+            .find(|attachment| attachment.path() == path)
+            .map(|attachment| attachment.data.as_slice())
+    }
+
+    /// Value of the `lampiran` template parameter, e.g.
+    /// `(foto: "foto.png", ttd: none)`
+    pub fn typst_value(&self) -> String {
+        let path = |kind| match self.get(kind) {
+            Some(attachment) => format!("\"{}\"", escape_typst_string(&attachment.path())),
+            None => "none".to_string(),
+        };
+        format!(
+            "(foto: {}, ttd: {})",
+            path(AttachmentKind::Foto),
+            path(AttachmentKind::Ttd)
+        )
+    }
+}
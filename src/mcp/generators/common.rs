@@ -1,35 +1,10 @@
 //! Common utilities for document generation.
 //!
-//! Shared helpers for template rendering, date formatting, and PDF compilation.
+//! Shared helpers for template rendering and PDF compilation. Dates are
+//! formatted by [`crate::i18n`].
 
-use chrono::{Datelike, Local};
 use std::path::Path;
 
-/// Format current date in Indonesian format (e.g., "30 Desember 2025").
-pub fn format_indonesian_date() -> String {
-    let now = Local::now().date_naive();
-    let months = [
-        "Januari",
-        "Februari",
-        "Maret",
-        "April",
-        "Mei",
-        "Juni",
-        "Juli",
-        "Agustus",
-        "September",
-        "Oktober",
-        "November",
-        "Desember",
-    ];
-
-    let day = now.day();
-    let month = months[(now.month0() as usize).min(months.len() - 1)];
-    let year = now.year();
-
-    format!("{day} {month} {year}")
-}
-
 /// Escape special characters for Typst strings.
 pub fn escape_typst_string(value: &str) -> String {
     value
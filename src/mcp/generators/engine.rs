@@ -1,16 +1,29 @@
 //! Typst rendering engine.
 //!
-//! Handles the low-level details of writing Typst source to temporary files,
-//! invoking the compiler, and managing the output PDF.
+//! Compiles the generated Typst source in-process with the `typst` crate and
+//! exports the result with `typst-pdf`, so no Typst CLI is needed at runtime.
 
+use chrono::{Datelike, Duration, Utc};
+use comemo::Prehashed;
 use std::fs;
-use std::process::Command;
-use tempfile::tempdir;
-use tempfile::TempDir;
+use std::sync::OnceLock;
+use typst::diag::{FileError, FileResult, SourceDiagnostic};
+use typst::eval::Tracer;
+use typst::foundations::{Bytes, Datetime, Smart};
+use typst::syntax::{FileId, Source, VirtualPath};
+use typst::text::{Font, FontBook};
+use typst::{Library, World, WorldExt};
 
-use super::common::{format_indonesian_date, sanitize_filename};
+use crate::i18n::today_id;
+
+use super::attachments::Attachments;
+use super::common::{get_static_dir, sanitize_filename};
 use super::{GeneratedDocument, GeneratorError};
 
+/// Directory under `static/` scanned for extra fonts, e.g. Times New Roman
+/// which cannot be redistributed with the binary.
+const FONTS_DIR: &str = "fonts";
+
 /// Stateless engine for rendering Typst templates to PDF.
 pub struct TypstRenderEngine;
 
@@ -28,28 +41,33 @@ impl TypstRenderEngine {
         output_name_base: &str,
         date_override: Option<String>,
     ) -> Result<GeneratedDocument, GeneratorError> {
-        let tanggal = date_override.unwrap_or_else(format_indonesian_date);
-
-        // Create temp directory for compilation context
-        let temp_dir = tempdir().map_err(GeneratorError::TempDir)?;
-        let typ_path = temp_dir.path().join(template_filename);
-        
-        // Write the source to the temp file
-        fs::write(&typ_path, typst_source).map_err(GeneratorError::WriteTypst)?;
-
-        // Define output filename
-        let safe_name = sanitize_filename(output_name_base, "document");
-        let output_filename = format!("output-{}.pdf", safe_name);
-        
-        // Compile
-        let pdf = compile_typst_to_pdf(&temp_dir, template_filename, &output_filename)?;
-
-        // Construct final filename
+        Self::render_with_attachments(
+            template_filename,
+            typst_source,
+            output_name_base,
+            date_override,
+            &Attachments::default(),
+        )
+    }
+
+    /// Like [`Self::render`], with `attachments` readable by the source
+    /// under their [`path`](super::Attachment::path).
+    pub fn render_with_attachments(
+        template_filename: &str,
+        typst_source: &str,
+        output_name_base: &str,
+        date_override: Option<String>,
+        attachments: &Attachments,
+    ) -> Result<GeneratedDocument, GeneratorError> {
+        let tanggal = date_override.unwrap_or_else(today_id);
+
+        let pdf = compile_with_attachments(template_filename, typst_source, attachments)?;
+
         // We use the base name to create a nice filename for the user
         let final_filename = format!(
-            "{}-{}.pdf", 
+            "{}-{}.pdf",
             sanitize_filename(template_filename.trim_end_matches(".typ"), "surat"),
-            safe_name
+            sanitize_filename(output_name_base, "document")
         );
 
         Ok(GeneratedDocument {
@@ -60,27 +78,150 @@ impl TypstRenderEngine {
     }
 }
 
-/// Compile a Typst source file to PDF.
-fn compile_typst_to_pdf(
-    temp_dir: &TempDir,
+/// Compile a Typst source string to PDF bytes.
+pub fn compile_typst_to_pdf(
     typ_filename: &str,
-    output_filename: &str,
+    typst_source: &str,
 ) -> Result<Vec<u8>, GeneratorError> {
-    let typ_path = temp_dir.path().join(typ_filename);
-    let output_path = temp_dir.path().join(output_filename);
-
-    let status = Command::new("typst")
-        .arg("compile")
-        .arg(&typ_path)
-        .arg(&output_path)
-        .current_dir(temp_dir.path())
-        .status()
-        .map_err(GeneratorError::TypstIo)?;
-
-    if !status.success() {
-        let code = status.code().unwrap_or(-1);
-        return Err(GeneratorError::TypstExit(code));
+    compile_with_attachments(typ_filename, typst_source, &Attachments::default())
+}
+
+/// Compile a Typst source string that may load `attachments` to PDF bytes.
+pub fn compile_with_attachments(
+    typ_filename: &str,
+    typst_source: &str,
+    attachments: &Attachments,
+) -> Result<Vec<u8>, GeneratorError> {
+    let world = TemplateWorld::new(typ_filename, typst_source, attachments);
+    let mut tracer = Tracer::new();
+
+    let document = typst::compile(&world, &mut tracer)
+        .map_err(|diagnostics| GeneratorError::Compile(world.describe(&diagnostics)))?;
+
+    Ok(typst_pdf::pdf(&document, Smart::Auto, None))
+}
+
+/// Fonts shared by every compilation, loaded once per process.
+struct FontSet {
+    book: Prehashed<FontBook>,
+    fonts: Vec<Font>,
+}
+
+fn library() -> &'static Prehashed<Library> {
+    static LIBRARY: OnceLock<Prehashed<Library>> = OnceLock::new();
+    LIBRARY.get_or_init(|| Prehashed::new(Library::default()))
+}
+
+fn font_set() -> &'static FontSet {
+    static FONTS: OnceLock<FontSet> = OnceLock::new();
+    FONTS.get_or_init(|| {
+        let mut fonts: Vec<Font> = typst_assets::fonts()
+            .flat_map(|data| Font::iter(Bytes::from_static(data)))
+            .collect();
+
+        if let Ok(entries) = fs::read_dir(get_static_dir().join(FONTS_DIR)) {
+            for path in entries.flatten().map(|entry| entry.path()) {
+                let is_font = path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| {
+                        matches!(ext.to_ascii_lowercase().as_str(), "ttf" | "otf" | "ttc")
+                    });
+                if !is_font {
+                    continue;
+                }
+                match fs::read(&path) {
+                    Ok(data) => fonts.extend(Font::iter(Bytes::from(data))),
+                    Err(e) => log::warn!("Failed to load font {}: {}", path.display(), e),
+                }
+            }
+        }
+
+        let book = FontBook::from_fonts(&fonts);
+        FontSet {
+            book: Prehashed::new(book),
+            fonts,
+        }
+    })
+}
+
+/// A Typst world holding a single in-memory source file and the images
+/// it may embed.
+struct TemplateWorld<'a> {
+    main: Source,
+    attachments: &'a Attachments,
+}
+
+impl<'a> TemplateWorld<'a> {
+    fn new(typ_filename: &str, typst_source: &str, attachments: &'a Attachments) -> Self {
+        let id = FileId::new(None, VirtualPath::new(typ_filename));
+        Self {
+            main: Source::new(id, typst_source.to_string()),
+            attachments,
+        }
     }
 
-    fs::read(&output_path).map_err(GeneratorError::ReadPdf)
-}
\ No newline at end of file
+    /// Turn compile diagnostics into a single readable message.
+    fn describe(&self, diagnostics: &[SourceDiagnostic]) -> String {
+        diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let line = self
+                    .range(diagnostic.span)
+                    .and_then(|range| self.main.byte_to_line(range.start));
+                let mut message = match line {
+                    Some(line) => format!("line {}: {}", line + 1, diagnostic.message),
+                    None => diagnostic.message.to_string(),
+                };
+                for hint in &diagnostic.hints {
+                    message.push_str(&format!(" (hint: {})", hint));
+                }
+                message
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+impl World for TemplateWorld<'_> {
+    fn library(&self) -> &Prehashed<Library> {
+        library()
+    }
+
+    fn book(&self) -> &Prehashed<FontBook> {
+        &font_set().book
+    }
+
+    fn main(&self) -> Source {
+        self.main.clone()
+    }
+
+    fn source(&self, id: FileId) -> FileResult<Source> {
+        if id == self.main.id() {
+            Ok(self.main.clone())
+        } else {
+            Err(FileError::NotFound(id.vpath().as_rootless_path().into()))
+        }
+    }
+
+    fn file(&self, id: FileId) -> FileResult<Bytes> {
+        let path = id.vpath().as_rootless_path();
+        path.to_str()
+            .filter(|_| id.package().is_none())
+            .and_then(|path| self.attachments.file(path))
+            .map(|data| Bytes::from(data.to_vec()))
+            .ok_or_else(|| FileError::NotFound(path.into()))
+    }
+
+    fn font(&self, index: usize) -> Option<Font> {
+        font_set().fonts.get(index).cloned()
+    }
+
+    fn today(&self, offset: Option<i64>) -> Option<Datetime> {
+        let date = match offset {
+            Some(hours) => (Utc::now() + Duration::hours(hours)).date_naive(),
+            None => crate::timezone::today(),
+        };
+        Datetime::from_ymd(date.year(), date.month() as u8, date.day() as u8)
+    }
+}
@@ -0,0 +1,105 @@
+//! Concurrency cap for document generation.
+//!
+//! Typst compilation is CPU-bound and takes hundreds of milliseconds, so it
+//! runs on the blocking thread pool and at most `max_concurrent` documents
+//! are compiled at once. Callers that cannot get a slot within
+//! `queue_timeout` fail fast instead of piling up.
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+use super::{GeneratedDocument, GeneratorError};
+
+const DEFAULT_MAX_CONCURRENT: usize = 2;
+const DEFAULT_QUEUE_TIMEOUT_SECS: u64 = 30;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenerationConfig {
+    /// Documents compiled at the same time
+    pub max_concurrent: usize,
+    /// How long a request waits for a free slot
+    pub queue_timeout: Duration,
+}
+
+impl Default for GenerationConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: DEFAULT_MAX_CONCURRENT,
+            queue_timeout: Duration::from_secs(DEFAULT_QUEUE_TIMEOUT_SECS),
+        }
+    }
+}
+
+impl GenerationConfig {
+    /// Load using a custom variable lookup
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let get = |key: &str| {
+            lookup(key)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let defaults = Self::default();
+
+        let max_concurrent = get("MCP_MAX_CONCURRENT_GENERATIONS")
+            .map(|v| crate::config::parse_positive("MCP_MAX_CONCURRENT_GENERATIONS", &v))
+            .transpose()?
+            .unwrap_or(defaults.max_concurrent);
+        let queue_timeout = get("MCP_GENERATION_QUEUE_TIMEOUT_SECS")
+            .map(|v| crate::config::parse_positive("MCP_GENERATION_QUEUE_TIMEOUT_SECS", &v))
+            .transpose()?
+            .map(|secs| Duration::from_secs(secs as u64))
+            .unwrap_or(defaults.queue_timeout);
+
+        Ok(Self {
+            max_concurrent,
+            queue_timeout,
+        })
+    }
+}
+
+/// Runs generation jobs on the blocking pool, a bounded number at a time.
+#[derive(Debug, Clone)]
+pub struct GenerationLimiter {
+    permits: Arc<Semaphore>,
+    queue_timeout: Duration,
+}
+
+impl Default for GenerationLimiter {
+    fn default() -> Self {
+        Self::new(&GenerationConfig::default())
+    }
+}
+
+impl GenerationLimiter {
+    pub fn new(config: &GenerationConfig) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(config.max_concurrent)),
+            queue_timeout: config.queue_timeout,
+        }
+    }
+
+    /// Wait for a free slot, then run `job` on a blocking thread
+    pub async fn run<F>(&self, job: F) -> Result<GeneratedDocument, GeneratorError>
+    where
+        F: FnOnce() -> Result<GeneratedDocument, GeneratorError> + Send + 'static,
+    {
+        let permit = tokio::time::timeout(
+            self.queue_timeout,
+            Arc::clone(&self.permits).acquire_owned(),
+        )
+        .await
+        .map_err(|_| GeneratorError::QueueTimeout(self.queue_timeout))?
+        .map_err(|e| GeneratorError::Task(e.to_string()))?;
+
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            job()
+        })
+        .await
+        .map_err(|e| GeneratorError::Task(e.to_string()))?
+    }
+}
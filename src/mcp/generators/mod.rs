@@ -4,20 +4,35 @@
 //! - `SuratTidakMampu` - SKTM (Surat Keterangan Tidak Mampu)
 //! - `SuratKpr` - Surat Pernyataan Belum Memiliki Rumah
 //! - `SuratNibNpwp` - Surat Pernyataan Akan Mengurus NIB & NPWP
+//! - `SuratDomisili` - Surat Keterangan Domisili
+//! - `SuratUsaha` - SKU (Surat Keterangan Usaha)
+//! - `SuratSkck` - Surat Pengantar SKCK
 
+pub mod attachments;
 pub mod common;
 pub mod engine;
+pub mod limiter;
+pub mod surat_domisili;
 pub mod surat_kpr;
 pub mod surat_nib_npwp;
+pub mod surat_skck;
 pub mod surat_tidak_mampu;
+pub mod surat_usaha;
+pub mod templates;
 pub mod traits;
 pub mod validation;
 
+pub use attachments::{Attachment, AttachmentKind, Attachments, Lampiran};
 pub use engine::TypstRenderEngine;
+pub use limiter::{GenerationConfig, GenerationLimiter};
+pub use surat_domisili::{SuratDomisiliGenerator, SuratDomisiliRequest};
 pub use surat_kpr::{SuratKprGenerator, SuratKprRequest};
 pub use surat_nib_npwp::{SuratNibNpwpGenerator, SuratNibNpwpRequest};
+pub use surat_skck::{SuratPengantarSkckGenerator, SuratPengantarSkckRequest};
 pub use surat_tidak_mampu::{SuratTidakMampuGenerator, SuratTidakMampuRequest};
-pub use traits::{Generator, Validator};
+pub use surat_usaha::{SuratUsahaGenerator, SuratUsahaRequest};
+pub use templates::{TemplateInfo, TemplateStore};
+pub use traits::{Attachable, Generator, Requester, Validator};
 
 use thiserror::Error;
 
@@ -26,16 +41,14 @@ use thiserror::Error;
 pub enum GeneratorError {
     #[error("failed to load Typst template: {0}")]
     TemplateIo(#[source] std::io::Error),
-    #[error("failed to create temporary directory: {0}")]
-    TempDir(#[source] std::io::Error),
-    #[error("failed to write Typst source: {0}")]
-    WriteTypst(#[source] std::io::Error),
-    #[error("Typst CLI execution failed: {0}")]
-    TypstIo(#[source] std::io::Error),
-    #[error("Typst CLI exited with status {0}")]
-    TypstExit(i32),
-    #[error("failed to read generated PDF: {0}")]
-    ReadPdf(#[source] std::io::Error),
+    #[error("Typst compilation failed: {0}")]
+    Compile(String),
+    #[error("no free generation slot after {0:?}")]
+    QueueTimeout(std::time::Duration),
+    #[error("generation task failed: {0}")]
+    Task(String),
+    #[error("generation cancelled")]
+    Cancelled,
 }
 
 /// Result of a successful document generation.
@@ -0,0 +1,296 @@
+//! Generator for Surat Keterangan Domisili.
+//!
+//! This generator creates the statement a citizen signs when applying for a
+//! domicile letter, e.g. when the address on their KTP differs from where
+//! they actually live.
+
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::i18n::today_id;
+
+use super::attachments::{Attachments, Lampiran};
+use super::common::{escape_typst_string, get_static_dir};
+use super::engine::TypstRenderEngine;
+use super::templates::TemplateStore;
+use super::surat_tidak_mampu::PengisiData;
+use super::traits::{Attachable, Generator, Requester, Validator};
+use super::validation::normalize_rt_rw;
+use super::{GeneratedDocument, GeneratorError};
+
+/// Template in the static directory, see [`TemplateStore`]
+pub const TEMPLATE_FILE: &str = "keterangan_domisili.typ";
+
+/// Data domisili (tempat tinggal saat ini).
+#[derive(Debug, Deserialize, Default)]
+pub struct DomisiliData {
+    /// Alamat tempat tinggal saat ini
+    pub alamat: String,
+    /// RT/RW, contoh: 003/005
+    pub rt_rw: String,
+    /// Lama tinggal di alamat domisili, contoh: 2 tahun
+    pub lama_tinggal: String,
+    /// Keperluan surat keterangan domisili
+    pub keperluan: String,
+}
+
+/// Metadata surat domisili.
+#[derive(Debug, Deserialize, Default)]
+pub struct SuratDomisiliMeta {
+    pub kelurahan: String,
+    #[serde(default)]
+    pub tanggal: Option<String>,
+}
+
+/// Request untuk membuat Surat Keterangan Domisili.
+#[derive(Debug, Deserialize, Default)]
+pub struct SuratDomisiliRequest {
+    pub pengisi: PengisiData,
+    pub domisili: DomisiliData,
+    pub meta: SuratDomisiliMeta,
+    #[serde(flatten)]
+    pub lampiran: Lampiran,
+}
+
+impl Requester for SuratDomisiliRequest {
+    fn requester(&self) -> (&str, &str) {
+        (&self.pengisi.nama, &self.pengisi.nik)
+    }
+}
+
+impl Attachable for SuratDomisiliRequest {
+    fn lampiran(&self) -> &Lampiran {
+        &self.lampiran
+    }
+}
+
+impl Validator for SuratDomisiliRequest {
+    /// Validate all input data and return descriptive errors if invalid.
+    fn validate(&self) -> Result<(), String> {
+        use super::validation::*;
+
+        let mut errors = ValidationErrors::new();
+
+        // Validate pengisi data
+        validate_required(
+            &self.pengisi.nama,
+            "pengisi.nama",
+            "Nama Pengisi",
+            &mut errors,
+        );
+        validate_nik(&self.pengisi.nik, "pengisi.nik", &mut errors);
+        validate_ttl(&self.pengisi.ttl, "pengisi.ttl", &mut errors);
+        validate_required(
+            &self.pengisi.agama,
+            "pengisi.agama",
+            "Agama Pengisi",
+            &mut errors,
+        );
+        validate_required(
+            &self.pengisi.pekerjaan,
+            "pengisi.pekerjaan",
+            "Pekerjaan Pengisi",
+            &mut errors,
+        );
+        validate_required(
+            &self.pengisi.alamat,
+            "pengisi.alamat",
+            "Alamat Pengisi",
+            &mut errors,
+        );
+        validate_phone(&self.pengisi.telp, "pengisi.telp", &mut errors);
+
+        // Validate domisili data
+        validate_required(
+            &self.domisili.alamat,
+            "domisili.alamat",
+            "Alamat Domisili",
+            &mut errors,
+        );
+        validate_rt_rw(&self.domisili.rt_rw, "domisili.rt_rw", &mut errors);
+        validate_required(
+            &self.domisili.lama_tinggal,
+            "domisili.lama_tinggal",
+            "Lama Tinggal",
+            &mut errors,
+        );
+        validate_required(
+            &self.domisili.keperluan,
+            "domisili.keperluan",
+            "Keperluan",
+            &mut errors,
+        );
+
+        // Validate meta
+        validate_required(
+            &self.meta.kelurahan,
+            "meta.kelurahan",
+            "Nama Kelurahan",
+            &mut errors,
+        );
+
+        errors.into_result()
+    }
+}
+
+// Inherent impl for compatibility
+impl SuratDomisiliRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        Validator::validate(self)
+    }
+
+    /// Filled-in request used to dry-run a replacement template
+    pub fn sample() -> Self {
+        Self {
+            pengisi: PengisiData::sample(),
+            domisili: DomisiliData {
+                alamat: "Jl. Cakung Barat No. 1".to_string(),
+                rt_rw: "003/005".to_string(),
+                lama_tinggal: "5 tahun".to_string(),
+                keperluan: "Melamar pekerjaan".to_string(),
+            },
+            meta: SuratDomisiliMeta {
+                kelurahan: "Cakung Barat".to_string(),
+                tanggal: None,
+            },
+            lampiran: Lampiran::default(),
+        }
+    }
+}
+
+/// Generator untuk Surat Keterangan Domisili.
+pub struct SuratDomisiliGenerator {
+    templates: Arc<TemplateStore>,
+}
+
+impl SuratDomisiliGenerator {
+    /// Create a new generator instance.
+    pub fn new() -> Result<Self, GeneratorError> {
+        Self::with_store(Arc::new(TemplateStore::new(get_static_dir())))
+    }
+
+    /// Create a generator reading its template through `templates`.
+    pub fn with_store(templates: Arc<TemplateStore>) -> Result<Self, GeneratorError> {
+        // Fail at startup instead of on the first call
+        templates.get(TEMPLATE_FILE)?;
+        Ok(Self { templates })
+    }
+
+    /// Render `request` with `template` instead of the stored one. Used to
+    /// try out a replacement template before it is saved.
+    pub fn generate_with_template(
+        &self,
+        template: &str,
+        request: SuratDomisiliRequest,
+        attachments: &Attachments,
+    ) -> Result<GeneratedDocument, GeneratorError> {
+        let tanggal = request
+            .meta
+            .tanggal
+            .clone()
+            .unwrap_or_else(today_id);
+
+        let typst_source = self.render_template(template, &request, &tanggal, attachments);
+
+        TypstRenderEngine::render_with_attachments(
+            TEMPLATE_FILE,
+            &typst_source,
+            &request.pengisi.nama,
+            Some(tanggal),
+            attachments,
+        )
+    }
+
+    fn render_template(
+        &self,
+        template: &str,
+        request: &SuratDomisiliRequest,
+        tanggal: &str,
+        attachments: &Attachments,
+    ) -> String {
+        let pengisi = &request.pengisi;
+        let domisili = &request.domisili;
+        let meta = &request.meta;
+        let jk_str = if pengisi.jk { "Laki-laki" } else { "Perempuan" };
+
+        format!(
+            r#"#let surat_keterangan_domisili(
+  pengisi: (
+    nama: "{}",
+    nik: "{}",
+    ttl: "{}",
+    jk: "{}",
+    agama: "{}",
+    pekerjaan: "{}",
+    alamat: "{}",
+    telp: "{}",
+  ),
+  domisili: (
+    alamat: "{}",
+    rt_rw: "{}",
+    lama_tinggal: "{}",
+    keperluan: "{}",
+  ),
+  meta: (
+    kelurahan: "{}",
+    tanggal: "{}",
+  ),
+  lampiran: {},
+) = {{
+{}
+
+#surat_keterangan_domisili()
+"#,
+            escape_typst_string(&pengisi.nama),
+            escape_typst_string(&pengisi.nik),
+            escape_typst_string(&pengisi.ttl),
+            escape_typst_string(jk_str),
+            escape_typst_string(&pengisi.agama),
+            escape_typst_string(&pengisi.pekerjaan),
+            escape_typst_string(&pengisi.alamat),
+            escape_typst_string(&pengisi.telp),
+            escape_typst_string(&domisili.alamat),
+            escape_typst_string(
+                &normalize_rt_rw(&domisili.rt_rw).unwrap_or_else(|| domisili.rt_rw.clone())
+            ),
+            escape_typst_string(&domisili.lama_tinggal),
+            escape_typst_string(&domisili.keperluan),
+            escape_typst_string(&meta.kelurahan),
+            escape_typst_string(tanggal),
+            attachments.typst_value(),
+            Self::extract_function_body(template),
+        )
+    }
+
+    fn extract_function_body(template: &str) -> String {
+        if let Some(start) = template.find(") = {") {
+            let body_start = start + 5;
+            if let Some(end) = template.rfind("#surat_keterangan_domisili()") {
+                return template[body_start..end].to_string();
+            }
+        }
+        template.to_string()
+    }
+}
+
+impl Generator<SuratDomisiliRequest> for SuratDomisiliGenerator {
+    /// Generate the document with the images the request refers to.
+    fn generate_with_attachments(
+        &self,
+        request: SuratDomisiliRequest,
+        attachments: &Attachments,
+    ) -> Result<GeneratedDocument, GeneratorError> {
+        let template = self.templates.get(TEMPLATE_FILE)?;
+        self.generate_with_template(&template, request, attachments)
+    }
+}
+
+// Inherent impl for compatibility
+impl SuratDomisiliGenerator {
+    pub fn generate(
+        &self,
+        request: SuratDomisiliRequest,
+    ) -> Result<GeneratedDocument, GeneratorError> {
+        Generator::generate(self, request)
+    }
+}
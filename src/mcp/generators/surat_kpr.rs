@@ -3,45 +3,79 @@
 //! This generator creates a statement letter for citizens who need to prove
 //! they don't own a house yet, typically for KPR (mortgage) applications.
 
+use schemars::JsonSchema;
 use serde::Deserialize;
-use std::fs;
+use std::sync::Arc;
 
-use super::common::{escape_typst_string, format_indonesian_date, get_static_dir};
+use crate::i18n::today_id;
+
+use super::attachments::{Attachments, Lampiran};
+use super::common::{escape_typst_string, get_static_dir};
 use super::engine::TypstRenderEngine;
-use super::traits::{Generator, Validator};
+use super::surat_tidak_mampu::PengisiData;
+use super::templates::TemplateStore;
+use super::traits::{Attachable, Generator, Requester, Validator};
 use super::{GeneratedDocument, GeneratorError};
 
-const TEMPLATE_FILE: &str = "kpr_belum_memiliki_rumah.typ";
+/// Template in the static directory, see [`TemplateStore`]
+pub const TEMPLATE_FILE: &str = "kpr_belum_memiliki_rumah.typ";
 
 /// Data pemohon KPR.
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Default, JsonSchema)]
 pub struct KprData {
+    #[schemars(description = "Nama lengkap pemohon")]
     pub nama: String,
+    #[schemars(description = "NIK (16 digit)")]
     pub nik: String,
     /// Tempat dan tanggal lahir
+    #[schemars(description = "Tempat, Tanggal Lahir")]
     pub ttl: String,
     /// Jenis kelamin (true: Laki-laki, false: Perempuan)
+    #[schemars(description = "Jenis Kelamin (true = Laki-laki, false = Perempuan). Jika input user tidak jelas, tanyakan kembali.")]
     pub jk: bool,
+    #[schemars(description = "Agama")]
     pub agama: String,
+    #[schemars(description = "Pekerjaan")]
     pub pekerjaan: String,
+    #[schemars(description = "Alamat lengkap")]
     pub alamat: String,
+    #[schemars(description = "Nomor telepon/HP")]
     pub telp: String,
 }
 
 /// Metadata surat KPR.
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Default, JsonSchema)]
 pub struct SuratKprMeta {
+    #[schemars(description = "Nama kelurahan")]
     pub kelurahan: String,
+    #[schemars(description = "Nama bank tujuan KPR")]
     pub bank_tujuan: String,
     #[serde(default)]
+    #[schemars(description = "Tanggal surat (opsional, default: hari ini)")]
     pub tanggal: Option<String>,
 }
 
 /// Request untuk membuat Surat Pernyataan Belum Memiliki Rumah.
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Default, JsonSchema)]
 pub struct SuratKprRequest {
+    #[schemars(description = "Data pemohon KPR")]
     pub data: KprData,
+    #[schemars(description = "Metadata surat")]
     pub meta: SuratKprMeta,
+    #[serde(flatten)]
+    pub lampiran: Lampiran,
+}
+
+impl Requester for SuratKprRequest {
+    fn requester(&self) -> (&str, &str) {
+        (&self.data.nama, &self.data.nik)
+    }
+}
+
+impl Attachable for SuratKprRequest {
+    fn lampiran(&self) -> &Lampiran {
+        &self.lampiran
+    }
 }
 
 impl Validator for SuratKprRequest {
@@ -90,22 +124,81 @@ impl SuratKprRequest {
     pub fn validate(&self) -> Result<(), String> {
         Validator::validate(self)
     }
+
+    /// Filled-in request used to dry-run a replacement template
+    pub fn sample() -> Self {
+        let pemohon = PengisiData::sample();
+        Self {
+            data: KprData {
+                nama: pemohon.nama,
+                nik: pemohon.nik,
+                ttl: pemohon.ttl,
+                jk: pemohon.jk,
+                agama: pemohon.agama,
+                pekerjaan: pemohon.pekerjaan,
+                alamat: pemohon.alamat,
+                telp: pemohon.telp,
+            },
+            meta: SuratKprMeta {
+                kelurahan: "Cakung Barat".to_string(),
+                bank_tujuan: "Bank Tabungan Negara".to_string(),
+                tanggal: None,
+            },
+            lampiran: Lampiran::default(),
+        }
+    }
 }
 
 /// Generator untuk Surat Pernyataan Belum Memiliki Rumah.
 pub struct SuratKprGenerator {
-    template: String,
+    templates: Arc<TemplateStore>,
 }
 
 impl SuratKprGenerator {
     /// Create a new generator instance.
     pub fn new() -> Result<Self, GeneratorError> {
-        let template_path = get_static_dir().join(TEMPLATE_FILE);
-        let template = fs::read_to_string(&template_path).map_err(GeneratorError::TemplateIo)?;
-        Ok(Self { template })
+        Self::with_store(Arc::new(TemplateStore::new(get_static_dir())))
+    }
+
+    /// Create a generator reading its template through `templates`.
+    pub fn with_store(templates: Arc<TemplateStore>) -> Result<Self, GeneratorError> {
+        // Fail at startup instead of on the first call
+        templates.get(TEMPLATE_FILE)?;
+        Ok(Self { templates })
+    }
+
+    /// Render `request` with `template` instead of the stored one. Used to
+    /// try out a replacement template before it is saved.
+    pub fn generate_with_template(
+        &self,
+        template: &str,
+        request: SuratKprRequest,
+        attachments: &Attachments,
+    ) -> Result<GeneratedDocument, GeneratorError> {
+        let tanggal = request
+            .meta
+            .tanggal
+            .clone()
+            .unwrap_or_else(today_id);
+
+        let typst_source = self.render_template(template, &request, &tanggal, attachments);
+
+        TypstRenderEngine::render_with_attachments(
+            TEMPLATE_FILE,
+            &typst_source,
+            &request.data.nama,
+            Some(tanggal),
+            attachments,
+        )
     }
 
-    fn render_template(&self, request: &SuratKprRequest, tanggal: &str) -> String {
+    fn render_template(
+        &self,
+        template: &str,
+        request: &SuratKprRequest,
+        tanggal: &str,
+        attachments: &Attachments,
+    ) -> String {
         let data = &request.data;
         let meta = &request.meta;
         let jk_str = if data.jk { "Laki-laki" } else { "Perempuan" };
@@ -127,6 +220,7 @@ impl SuratKprGenerator {
     bank_tujuan: "{}",
     tanggal: "{}",
   ),
+  lampiran: {},
 ) = {{
 {}
 
@@ -143,38 +237,31 @@ impl SuratKprGenerator {
             escape_typst_string(&meta.kelurahan),
             escape_typst_string(&meta.bank_tujuan),
             escape_typst_string(tanggal),
-            self.extract_function_body(),
+            attachments.typst_value(),
+            Self::extract_function_body(template),
         )
     }
 
-    fn extract_function_body(&self) -> String {
-        if let Some(start) = self.template.find(") = {") {
+    fn extract_function_body(template: &str) -> String {
+        if let Some(start) = template.find(") = {") {
             let body_start = start + 5;
-            if let Some(end) = self.template.rfind("#surat_pernyataan_kpr()") {
-                return self.template[body_start..end].to_string();
+            if let Some(end) = template.rfind("#surat_pernyataan_kpr()") {
+                return template[body_start..end].to_string();
             }
         }
-        self.template.clone()
+        template.to_string()
     }
 }
 
 impl Generator<SuratKprRequest> for SuratKprGenerator {
-    /// Generate the document from the request data.
-    fn generate(&self, request: SuratKprRequest) -> Result<GeneratedDocument, GeneratorError> {
-        let tanggal = request
-            .meta
-            .tanggal
-            .clone()
-            .unwrap_or_else(format_indonesian_date);
-
-        let typst_source = self.render_template(&request, &tanggal);
-
-        TypstRenderEngine::render(
-            TEMPLATE_FILE,
-            &typst_source,
-            &request.data.nama,
-            Some(tanggal),
-        )
+    /// Generate the document with the images the request refers to.
+    fn generate_with_attachments(
+        &self,
+        request: SuratKprRequest,
+        attachments: &Attachments,
+    ) -> Result<GeneratedDocument, GeneratorError> {
+        let template = self.templates.get(TEMPLATE_FILE)?;
+        self.generate_with_template(&template, request, attachments)
     }
 }
 
@@ -3,41 +3,71 @@
 //! This generator creates a statement letter for business owners who commit
 //! to registering for NIB (Nomor Induk Berusaha) and NPWP (tax ID).
 
+use schemars::JsonSchema;
 use serde::Deserialize;
-use std::fs;
+use std::sync::Arc;
 
-use super::common::{escape_typst_string, format_indonesian_date, get_static_dir};
+use crate::i18n::today_id;
+
+use super::attachments::{Attachments, Lampiran};
+use super::common::{escape_typst_string, get_static_dir};
 use super::engine::TypstRenderEngine;
-use super::traits::{Generator, Validator};
+use super::templates::TemplateStore;
+use super::traits::{Attachable, Generator, Requester, Validator};
 use super::{GeneratedDocument, GeneratorError};
 
-const TEMPLATE_FILE: &str = "surat_pernyataan_akan_mengurus_nib_npwp.typ";
+/// Template in the static directory, see [`TemplateStore`]
+pub const TEMPLATE_FILE: &str = "surat_pernyataan_akan_mengurus_nib_npwp.typ";
 
 /// Data pelaku usaha.
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Default, JsonSchema)]
 pub struct NibNpwpData {
+    #[schemars(description = "Nama lengkap pelaku usaha")]
     pub nama: String,
+    #[schemars(description = "NIK (16 digit)")]
     pub nik: String,
+    #[schemars(description = "Jabatan dalam usaha (mis: Pemilik, Direktur)")]
     pub jabatan: String,
+    #[schemars(description = "Bidang usaha (mis: Perdagangan, Jasa)")]
     pub bidang_usaha: String,
+    #[schemars(description = "Deskripsi kegiatan usaha")]
     pub kegiatan_usaha: String,
+    #[schemars(description = "Jenis usaha (Usaha Mikro/Kecil/Menengah)")]
     pub jenis_usaha: String,
+    #[schemars(description = "Alamat lengkap lokasi usaha")]
     pub alamat_usaha: String,
 }
 
 /// Metadata surat NIB/NPWP.
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Default, JsonSchema)]
 pub struct SuratNibNpwpMeta {
     #[serde(default)]
+    #[schemars(description = "Tanggal surat (opsional, default: hari ini)")]
     pub tanggal: Option<String>,
 }
 
 /// Request untuk membuat Surat Pernyataan Akan Mengurus NIB & NPWP.
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Default, JsonSchema)]
 pub struct SuratNibNpwpRequest {
+    #[schemars(description = "Data pelaku usaha")]
     pub data: NibNpwpData,
     #[serde(default)]
+    #[schemars(description = "Metadata surat")]
     pub meta: SuratNibNpwpMeta,
+    #[serde(flatten)]
+    pub lampiran: Lampiran,
+}
+
+impl Requester for SuratNibNpwpRequest {
+    fn requester(&self) -> (&str, &str) {
+        (&self.data.nama, &self.data.nik)
+    }
+}
+
+impl Attachable for SuratNibNpwpRequest {
+    fn lampiran(&self) -> &Lampiran {
+        &self.lampiran
+    }
 }
 
 impl Validator for SuratNibNpwpRequest {
@@ -90,22 +120,75 @@ impl SuratNibNpwpRequest {
     pub fn validate(&self) -> Result<(), String> {
         Validator::validate(self)
     }
+
+    /// Filled-in request used to dry-run a replacement template
+    pub fn sample() -> Self {
+        Self {
+            data: NibNpwpData {
+                nama: "Budi Santoso".to_string(),
+                nik: "3175010101900001".to_string(),
+                jabatan: "Pemilik".to_string(),
+                bidang_usaha: "Perdagangan".to_string(),
+                kegiatan_usaha: "Penjualan makanan".to_string(),
+                jenis_usaha: "Warung makan".to_string(),
+                alamat_usaha: "Jl. Cakung Barat No. 1".to_string(),
+            },
+            meta: SuratNibNpwpMeta { tanggal: None },
+            lampiran: Lampiran::default(),
+        }
+    }
 }
 
 /// Generator untuk Surat Pernyataan Akan Mengurus NIB & NPWP.
 pub struct SuratNibNpwpGenerator {
-    template: String,
+    templates: Arc<TemplateStore>,
 }
 
 impl SuratNibNpwpGenerator {
     /// Create a new generator instance.
     pub fn new() -> Result<Self, GeneratorError> {
-        let template_path = get_static_dir().join(TEMPLATE_FILE);
-        let template = fs::read_to_string(&template_path).map_err(GeneratorError::TemplateIo)?;
-        Ok(Self { template })
+        Self::with_store(Arc::new(TemplateStore::new(get_static_dir())))
+    }
+
+    /// Create a generator reading its template through `templates`.
+    pub fn with_store(templates: Arc<TemplateStore>) -> Result<Self, GeneratorError> {
+        // Fail at startup instead of on the first call
+        templates.get(TEMPLATE_FILE)?;
+        Ok(Self { templates })
+    }
+
+    /// Render `request` with `template` instead of the stored one. Used to
+    /// try out a replacement template before it is saved.
+    pub fn generate_with_template(
+        &self,
+        template: &str,
+        request: SuratNibNpwpRequest,
+        attachments: &Attachments,
+    ) -> Result<GeneratedDocument, GeneratorError> {
+        let tanggal = request
+            .meta
+            .tanggal
+            .clone()
+            .unwrap_or_else(today_id);
+
+        let typst_source = self.render_template(template, &request, &tanggal, attachments);
+
+        TypstRenderEngine::render_with_attachments(
+            TEMPLATE_FILE,
+            &typst_source,
+            &request.data.nama,
+            Some(tanggal),
+            attachments,
+        )
     }
 
-    fn render_template(&self, request: &SuratNibNpwpRequest, tanggal: &str) -> String {
+    fn render_template(
+        &self,
+        template: &str,
+        request: &SuratNibNpwpRequest,
+        tanggal: &str,
+        attachments: &Attachments,
+    ) -> String {
         let data = &request.data;
 
         format!(
@@ -122,6 +205,7 @@ impl SuratNibNpwpGenerator {
   meta: (
     tanggal: "{}",
   ),
+  lampiran: {},
 ) = {{
 {}
 
@@ -135,41 +219,31 @@ impl SuratNibNpwpGenerator {
             escape_typst_string(&data.jenis_usaha),
             escape_typst_string(&data.alamat_usaha),
             escape_typst_string(tanggal),
-            self.extract_function_body(),
+            attachments.typst_value(),
+            Self::extract_function_body(template),
         )
     }
 
-    fn extract_function_body(&self) -> String {
-        if let Some(start) = self.template.find(") = {") {
+    fn extract_function_body(template: &str) -> String {
+        if let Some(start) = template.find(") = {") {
             let body_start = start + 5;
-            if let Some(end) = self.template.rfind("#surat_pernyataan_nib_npwp()") {
-                return self.template[body_start..end].to_string();
+            if let Some(end) = template.rfind("#surat_pernyataan_nib_npwp()") {
+                return template[body_start..end].to_string();
             }
         }
-        self.template.clone()
+        template.to_string()
     }
 }
 
 impl Generator<SuratNibNpwpRequest> for SuratNibNpwpGenerator {
-    /// Generate the document from the request data.
-    fn generate(
+    /// Generate the document with the images the request refers to.
+    fn generate_with_attachments(
         &self,
         request: SuratNibNpwpRequest,
+        attachments: &Attachments,
     ) -> Result<GeneratedDocument, GeneratorError> {
-        let tanggal = request
-            .meta
-            .tanggal
-            .clone()
-            .unwrap_or_else(format_indonesian_date);
-
-        let typst_source = self.render_template(&request, &tanggal);
-
-        TypstRenderEngine::render(
-            TEMPLATE_FILE,
-            &typst_source,
-            &request.data.nama,
-            Some(tanggal),
-        )
+        let template = self.templates.get(TEMPLATE_FILE)?;
+        self.generate_with_template(&template, request, attachments)
     }
 }
 
@@ -0,0 +1,286 @@
+//! Generator for Surat Pengantar SKCK.
+//!
+//! This generator creates the kelurahan introduction letter residents bring
+//! to the police when requesting a Surat Keterangan Catatan Kepolisian.
+
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::i18n::today_id;
+
+use super::attachments::{Attachments, Lampiran};
+use super::common::{escape_typst_string, get_static_dir};
+use super::engine::TypstRenderEngine;
+use super::templates::TemplateStore;
+use super::surat_tidak_mampu::PengisiData;
+use super::traits::{Attachable, Generator, Requester, Validator};
+use super::{GeneratedDocument, GeneratorError};
+
+/// Template in the static directory, see [`TemplateStore`]
+pub const TEMPLATE_FILE: &str = "skck.typ";
+
+/// Data permohonan SKCK.
+#[derive(Debug, Deserialize, Default)]
+pub struct SkckData {
+    /// Keperluan SKCK, contoh: Melamar pekerjaan
+    pub keperluan: String,
+    /// Instansi tujuan SKCK, contoh: PT Maju Jaya
+    pub tujuan_instansi: String,
+    /// Masa berlaku surat pengantar, contoh: 1 bulan
+    pub masa_berlaku: String,
+}
+
+/// Metadata surat pengantar SKCK.
+#[derive(Debug, Deserialize, Default)]
+pub struct SuratPengantarSkckMeta {
+    pub kelurahan: String,
+    #[serde(default)]
+    pub tanggal: Option<String>,
+}
+
+/// Request untuk membuat Surat Pengantar SKCK.
+#[derive(Debug, Deserialize, Default)]
+pub struct SuratPengantarSkckRequest {
+    pub pemohon: PengisiData,
+    pub skck: SkckData,
+    pub meta: SuratPengantarSkckMeta,
+    #[serde(flatten)]
+    pub lampiran: Lampiran,
+}
+
+impl Requester for SuratPengantarSkckRequest {
+    fn requester(&self) -> (&str, &str) {
+        (&self.pemohon.nama, &self.pemohon.nik)
+    }
+}
+
+impl Attachable for SuratPengantarSkckRequest {
+    fn lampiran(&self) -> &Lampiran {
+        &self.lampiran
+    }
+}
+
+impl Validator for SuratPengantarSkckRequest {
+    /// Validate all input data and return descriptive errors if invalid.
+    fn validate(&self) -> Result<(), String> {
+        use super::validation::*;
+
+        let mut errors = ValidationErrors::new();
+
+        // Validate pemohon data
+        validate_required(
+            &self.pemohon.nama,
+            "pemohon.nama",
+            "Nama Pemohon",
+            &mut errors,
+        );
+        validate_nik(&self.pemohon.nik, "pemohon.nik", &mut errors);
+        validate_ttl(&self.pemohon.ttl, "pemohon.ttl", &mut errors);
+        validate_required(
+            &self.pemohon.agama,
+            "pemohon.agama",
+            "Agama Pemohon",
+            &mut errors,
+        );
+        validate_required(
+            &self.pemohon.pekerjaan,
+            "pemohon.pekerjaan",
+            "Pekerjaan Pemohon",
+            &mut errors,
+        );
+        validate_required(
+            &self.pemohon.alamat,
+            "pemohon.alamat",
+            "Alamat Pemohon",
+            &mut errors,
+        );
+        validate_phone(&self.pemohon.telp, "pemohon.telp", &mut errors);
+
+        // Validate SKCK data
+        validate_required(
+            &self.skck.keperluan,
+            "skck.keperluan",
+            "Keperluan",
+            &mut errors,
+        );
+        validate_required(
+            &self.skck.tujuan_instansi,
+            "skck.tujuan_instansi",
+            "Tujuan Instansi",
+            &mut errors,
+        );
+        validate_required(
+            &self.skck.masa_berlaku,
+            "skck.masa_berlaku",
+            "Masa Berlaku",
+            &mut errors,
+        );
+
+        // Validate meta
+        validate_required(
+            &self.meta.kelurahan,
+            "meta.kelurahan",
+            "Nama Kelurahan",
+            &mut errors,
+        );
+
+        errors.into_result()
+    }
+}
+
+// Inherent impl for compatibility
+impl SuratPengantarSkckRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        Validator::validate(self)
+    }
+
+    /// Filled-in request used to dry-run a replacement template
+    pub fn sample() -> Self {
+        Self {
+            pemohon: PengisiData::sample(),
+            skck: SkckData {
+                keperluan: "Melamar pekerjaan".to_string(),
+                tujuan_instansi: "PT Sinar Abadi".to_string(),
+                masa_berlaku: "6 bulan".to_string(),
+            },
+            meta: SuratPengantarSkckMeta {
+                kelurahan: "Cakung Barat".to_string(),
+                tanggal: None,
+            },
+            lampiran: Lampiran::default(),
+        }
+    }
+}
+
+/// Generator untuk Surat Pengantar SKCK.
+pub struct SuratPengantarSkckGenerator {
+    templates: Arc<TemplateStore>,
+}
+
+impl SuratPengantarSkckGenerator {
+    /// Create a new generator instance.
+    pub fn new() -> Result<Self, GeneratorError> {
+        Self::with_store(Arc::new(TemplateStore::new(get_static_dir())))
+    }
+
+    /// Create a generator reading its template through `templates`.
+    pub fn with_store(templates: Arc<TemplateStore>) -> Result<Self, GeneratorError> {
+        // Fail at startup instead of on the first call
+        templates.get(TEMPLATE_FILE)?;
+        Ok(Self { templates })
+    }
+
+    /// Render `request` with `template` instead of the stored one. Used to
+    /// try out a replacement template before it is saved.
+    pub fn generate_with_template(
+        &self,
+        template: &str,
+        request: SuratPengantarSkckRequest,
+        attachments: &Attachments,
+    ) -> Result<GeneratedDocument, GeneratorError> {
+        let tanggal = request
+            .meta
+            .tanggal
+            .clone()
+            .unwrap_or_else(today_id);
+
+        let typst_source = self.render_template(template, &request, &tanggal, attachments);
+
+        TypstRenderEngine::render_with_attachments(
+            TEMPLATE_FILE,
+            &typst_source,
+            &request.pemohon.nama,
+            Some(tanggal),
+            attachments,
+        )
+    }
+
+    fn render_template(
+        &self,
+        template: &str,
+        request: &SuratPengantarSkckRequest,
+        tanggal: &str,
+        attachments: &Attachments,
+    ) -> String {
+        let pemohon = &request.pemohon;
+        let skck = &request.skck;
+        let meta = &request.meta;
+        let jk_str = if pemohon.jk { "Laki-laki" } else { "Perempuan" };
+
+        format!(
+            r#"#let surat_pengantar_skck(
+  pemohon: (
+    nama: "{}",
+    nik: "{}",
+    ttl: "{}",
+    jk: "{}",
+    agama: "{}",
+    pekerjaan: "{}",
+    alamat: "{}",
+    telp: "{}",
+  ),
+  skck: (
+    keperluan: "{}",
+    tujuan_instansi: "{}",
+    masa_berlaku: "{}",
+  ),
+  meta: (
+    kelurahan: "{}",
+    tanggal: "{}",
+  ),
+  lampiran: {},
+) = {{
+{}
+
+#surat_pengantar_skck()
+"#,
+            escape_typst_string(&pemohon.nama),
+            escape_typst_string(&pemohon.nik),
+            escape_typst_string(&pemohon.ttl),
+            escape_typst_string(jk_str),
+            escape_typst_string(&pemohon.agama),
+            escape_typst_string(&pemohon.pekerjaan),
+            escape_typst_string(&pemohon.alamat),
+            escape_typst_string(&pemohon.telp),
+            escape_typst_string(&skck.keperluan),
+            escape_typst_string(&skck.tujuan_instansi),
+            escape_typst_string(&skck.masa_berlaku),
+            escape_typst_string(&meta.kelurahan),
+            escape_typst_string(tanggal),
+            attachments.typst_value(),
+            Self::extract_function_body(template),
+        )
+    }
+
+    fn extract_function_body(template: &str) -> String {
+        if let Some(start) = template.find(") = {") {
+            let body_start = start + 5;
+            if let Some(end) = template.rfind("#surat_pengantar_skck()") {
+                return template[body_start..end].to_string();
+            }
+        }
+        template.to_string()
+    }
+}
+
+impl Generator<SuratPengantarSkckRequest> for SuratPengantarSkckGenerator {
+    /// Generate the document with the images the request refers to.
+    fn generate_with_attachments(
+        &self,
+        request: SuratPengantarSkckRequest,
+        attachments: &Attachments,
+    ) -> Result<GeneratedDocument, GeneratorError> {
+        let template = self.templates.get(TEMPLATE_FILE)?;
+        self.generate_with_template(&template, request, attachments)
+    }
+}
+
+// Inherent impl for compatibility
+impl SuratPengantarSkckGenerator {
+    pub fn generate(
+        &self,
+        request: SuratPengantarSkckRequest,
+    ) -> Result<GeneratedDocument, GeneratorError> {
+        Generator::generate(self, request)
+    }
+}
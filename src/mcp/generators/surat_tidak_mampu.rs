@@ -3,53 +3,94 @@
 //! This generator creates a statement letter for citizens who need to prove
 //! they are from a low-income family for social assistance purposes.
 
+use schemars::JsonSchema;
 use serde::Deserialize;
-use std::fs;
+use std::sync::Arc;
 
-use super::common::{escape_typst_string, format_indonesian_date, get_static_dir};
+use crate::i18n::today_id;
+
+use super::attachments::{Attachments, Lampiran};
+use super::common::{escape_typst_string, get_static_dir};
 use super::engine::TypstRenderEngine;
-use super::traits::{Generator, Validator};
+use super::templates::TemplateStore;
+use super::traits::{Attachable, Generator, Requester, Validator};
 use super::{GeneratedDocument, GeneratorError};
 
-const TEMPLATE_FILE: &str = "keterangan_tidak_mampu.typ";
+/// Template in the static directory, see [`TemplateStore`]
+pub const TEMPLATE_FILE: &str = "keterangan_tidak_mampu.typ";
 
 /// Data pengisi (orang yang mengisi formulir).
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Default, JsonSchema)]
 pub struct PengisiData {
+    #[schemars(description = "Nama lengkap pengisi")]
     pub nama: String,
+    #[schemars(description = "NIK (16 digit)")]
     pub nik: String,
     /// Tempat dan tanggal lahir
+    #[schemars(description = "Tempat, Tanggal Lahir")]
     pub ttl: String,
     /// Jenis kelamin (true: Laki-laki, false: Perempuan)
+    #[schemars(description = "Jenis Kelamin (true = Laki-laki, false = Perempuan). Jika input user tidak jelas, tanyakan kembali.")]
     pub jk: bool,
+    #[schemars(description = "Agama")]
     pub agama: String,
+    #[schemars(description = "Pekerjaan")]
     pub pekerjaan: String,
+    #[schemars(description = "Alamat lengkap")]
     pub alamat: String,
+    #[schemars(description = "Nomor telepon/HP")]
     pub telp: String,
 }
 
+impl PengisiData {
+    /// Applicant used by the sample requests of every generator
+    pub fn sample() -> Self {
+        Self {
+            nama: "Budi Santoso".to_string(),
+            nik: "3175010101900001".to_string(),
+            ttl: "Jakarta, 1 Januari 1990".to_string(),
+            jk: true,
+            agama: "Islam".to_string(),
+            pekerjaan: "Karyawan Swasta".to_string(),
+            alamat: "Jl. Cakung Barat No. 1".to_string(),
+            telp: "081234567890".to_string(),
+        }
+    }
+}
+
 /// Data subjek (orang yang dibuatkan surat).
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Default, JsonSchema)]
 pub struct SubjekData {
+    #[schemars(description = "Nama lengkap subjek")]
     pub nama: String,
+    #[schemars(description = "NIK (bila ada)")]
     pub nik: String,
+    #[schemars(description = "Tempat, Tanggal Lahir")]
     pub ttl: String,
+    #[schemars(description = "Jenis Kelamin (true = Laki-laki, false = Perempuan)")]
     pub jk: bool,
+    #[schemars(description = "Agama")]
     pub agama: String,
+    #[schemars(description = "Pekerjaan")]
     pub pekerjaan: String,
+    #[schemars(description = "Alamat")]
     pub alamat: String,
     /// Hubungan keluarga dengan pengisi
+    #[schemars(description = "Hubungan keluarga dengan pengisi")]
     pub hubungan: String,
 }
 
 /// Metadata surat.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct SuratTidakMampuMeta {
     /// True jika untuk diri sendiri, false jika untuk orang lain
     #[serde(default = "default_true")]
+    #[schemars(description = "True jika SKTM untuk diri sendiri, false jika untuk orang lain")]
     pub opsi_sendiri: bool,
+    #[schemars(description = "Nama kelurahan")]
     pub kelurahan: String,
     #[serde(default)]
+    #[schemars(description = "Tanggal surat (opsional, default: hari ini)")]
     pub tanggal: Option<String>,
 }
 
@@ -68,12 +109,29 @@ impl Default for SuratTidakMampuMeta {
 }
 
 /// Request untuk membuat Surat Pernyataan Tidak Mampu.
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Default, JsonSchema)]
 pub struct SuratTidakMampuRequest {
+    #[schemars(description = "Data orang yang mengisi/menandatangani surat")]
     pub pengisi: PengisiData,
     #[serde(default)]
+    #[schemars(description = "Data orang yang dibuatkan SKTM (jika berbeda dengan pengisi)")]
     pub subjek: SubjekData,
+    #[schemars(description = "Metadata surat")]
     pub meta: SuratTidakMampuMeta,
+    #[serde(flatten)]
+    pub lampiran: Lampiran,
+}
+
+impl Requester for SuratTidakMampuRequest {
+    fn requester(&self) -> (&str, &str) {
+        (&self.pengisi.nama, &self.pengisi.nik)
+    }
+}
+
+impl Attachable for SuratTidakMampuRequest {
+    fn lampiran(&self) -> &Lampiran {
+        &self.lampiran
+    }
 }
 
 impl Validator for SuratTidakMampuRequest {
@@ -162,22 +220,81 @@ impl SuratTidakMampuRequest {
     pub fn validate(&self) -> Result<(), String> {
         Validator::validate(self)
     }
+
+    /// Filled-in request used to dry-run a replacement template
+    pub fn sample() -> Self {
+        Self {
+            pengisi: PengisiData::sample(),
+            subjek: SubjekData {
+                nama: "Siti Rahayu".to_string(),
+                nik: "3175014102950002".to_string(),
+                ttl: "Jakarta, 1 Februari 1995".to_string(),
+                jk: false,
+                agama: "Islam".to_string(),
+                pekerjaan: "Pelajar".to_string(),
+                alamat: "Jl. Cakung Barat No. 1".to_string(),
+                hubungan: "Anak".to_string(),
+            },
+            meta: SuratTidakMampuMeta {
+                opsi_sendiri: false,
+                kelurahan: "Cakung Barat".to_string(),
+                tanggal: None,
+            },
+            lampiran: Lampiran::default(),
+        }
+    }
 }
 
 /// Generator untuk Surat Pernyataan Tidak Mampu.
 pub struct SuratTidakMampuGenerator {
-    template: String,
+    templates: Arc<TemplateStore>,
 }
 
 impl SuratTidakMampuGenerator {
     /// Create a new generator instance.
     pub fn new() -> Result<Self, GeneratorError> {
-        let template_path = get_static_dir().join(TEMPLATE_FILE);
-        let template = fs::read_to_string(&template_path).map_err(GeneratorError::TemplateIo)?;
-        Ok(Self { template })
+        Self::with_store(Arc::new(TemplateStore::new(get_static_dir())))
+    }
+
+    /// Create a generator reading its template through `templates`.
+    pub fn with_store(templates: Arc<TemplateStore>) -> Result<Self, GeneratorError> {
+        // Fail at startup instead of on the first call
+        templates.get(TEMPLATE_FILE)?;
+        Ok(Self { templates })
+    }
+
+    /// Render `request` with `template` instead of the stored one. Used to
+    /// try out a replacement template before it is saved.
+    pub fn generate_with_template(
+        &self,
+        template: &str,
+        request: SuratTidakMampuRequest,
+        attachments: &Attachments,
+    ) -> Result<GeneratedDocument, GeneratorError> {
+        let tanggal = request
+            .meta
+            .tanggal
+            .clone()
+            .unwrap_or_else(today_id);
+
+        let typst_source = self.render_template(template, &request, &tanggal, attachments);
+
+        TypstRenderEngine::render_with_attachments(
+            TEMPLATE_FILE,
+            &typst_source,
+            &request.pengisi.nama,
+            Some(tanggal),
+            attachments,
+        )
     }
 
-    fn render_template(&self, request: &SuratTidakMampuRequest, tanggal: &str) -> String {
+    fn render_template(
+        &self,
+        template: &str,
+        request: &SuratTidakMampuRequest,
+        tanggal: &str,
+        attachments: &Attachments,
+    ) -> String {
         // Generate the function call with all parameters
         let pengisi = &request.pengisi;
         let subjek = &request.subjek;
@@ -213,6 +330,7 @@ impl SuratTidakMampuGenerator {
     kelurahan: "{}",
     tanggal: "{}",
   ),
+  lampiran: {},
 ) = {{
 {}
 
@@ -237,45 +355,35 @@ impl SuratTidakMampuGenerator {
             if meta.opsi_sendiri { "true" } else { "false" },
             escape_typst_string(&meta.kelurahan),
             escape_typst_string(tanggal),
-            self.extract_function_body(),
+            attachments.typst_value(),
+            Self::extract_function_body(template),
         )
     }
 
     /// Extract the function body from the template (everything between { and the final }).
-    fn extract_function_body(&self) -> String {
+    fn extract_function_body(template: &str) -> String {
         // Find the opening brace after the function signature
-        if let Some(start) = self.template.find(") = {") {
+        if let Some(start) = template.find(") = {") {
             let body_start = start + 5; // Skip ") = {"
             // Find the last occurrence of the function call
-            if let Some(end) = self.template.rfind("#surat_pernyataan()") {
-                return self.template[body_start..end].to_string();
+            if let Some(end) = template.rfind("#surat_pernyataan()") {
+                return template[body_start..end].to_string();
             }
         }
         // Fallback: return template body without the function definition header
-        self.template.clone()
+        template.to_string()
     }
 }
 
 impl Generator<SuratTidakMampuRequest> for SuratTidakMampuGenerator {
-    /// Generate the document from the request data.
-    fn generate(
+    /// Generate the document with the images the request refers to.
+    fn generate_with_attachments(
         &self,
         request: SuratTidakMampuRequest,
+        attachments: &Attachments,
     ) -> Result<GeneratedDocument, GeneratorError> {
-        let tanggal = request
-            .meta
-            .tanggal
-            .clone()
-            .unwrap_or_else(format_indonesian_date);
-
-        let typst_source = self.render_template(&request, &tanggal);
-
-        TypstRenderEngine::render(
-            TEMPLATE_FILE,
-            &typst_source,
-            &request.pengisi.nama,
-            Some(tanggal),
-        )
+        let template = self.templates.get(TEMPLATE_FILE)?;
+        self.generate_with_template(&template, request, attachments)
     }
 }
 
@@ -0,0 +1,329 @@
+//! Generator for Surat Keterangan Usaha (SKU).
+//!
+//! This generator creates the statement a small-business owner signs when
+//! applying for an SKU, typically needed for bank loans or supplier
+//! registration.
+
+use chrono::Datelike;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::i18n::today_id;
+
+use super::attachments::{Attachments, Lampiran};
+use super::common::{escape_typst_string, get_static_dir};
+use super::engine::TypstRenderEngine;
+use super::templates::TemplateStore;
+use super::surat_tidak_mampu::PengisiData;
+use super::traits::{Attachable, Generator, Requester, Validator};
+use super::{GeneratedDocument, GeneratorError};
+
+/// Template in the static directory, see [`TemplateStore`]
+pub const TEMPLATE_FILE: &str = "keterangan_usaha.typ";
+/// Earliest year accepted as the start of a business
+const MIN_TAHUN_MULAI: i32 = 1945;
+
+/// Data usaha.
+#[derive(Debug, Deserialize, Default)]
+pub struct UsahaData {
+    pub nama_usaha: String,
+    pub jenis_usaha: String,
+    pub alamat_usaha: String,
+    /// Tahun usaha mulai berjalan, contoh: 2019
+    pub tahun_mulai: String,
+    /// Keperluan surat keterangan usaha
+    pub keperluan: String,
+}
+
+/// Metadata surat keterangan usaha.
+#[derive(Debug, Deserialize, Default)]
+pub struct SuratUsahaMeta {
+    pub kelurahan: String,
+    #[serde(default)]
+    pub tanggal: Option<String>,
+}
+
+/// Request untuk membuat Surat Keterangan Usaha.
+#[derive(Debug, Deserialize, Default)]
+pub struct SuratUsahaRequest {
+    pub pemilik: PengisiData,
+    pub usaha: UsahaData,
+    pub meta: SuratUsahaMeta,
+    #[serde(flatten)]
+    pub lampiran: Lampiran,
+}
+
+impl SuratUsahaRequest {
+    /// Lama usaha as printed on the letter, e.g. "Sejak tahun 2019 (6 tahun)"
+    pub fn lama_usaha(&self) -> String {
+        let tahun_mulai = self.usaha.tahun_mulai.trim();
+        match tahun_mulai.parse::<i32>() {
+            Ok(tahun) => {
+                let lama = crate::timezone::today().year() - tahun;
+                if lama < 1 {
+                    format!("Sejak tahun {} (kurang dari 1 tahun)", tahun)
+                } else {
+                    format!("Sejak tahun {} ({} tahun)", tahun, lama)
+                }
+            }
+            Err(_) => tahun_mulai.to_string(),
+        }
+    }
+}
+
+impl Requester for SuratUsahaRequest {
+    fn requester(&self) -> (&str, &str) {
+        (&self.pemilik.nama, &self.pemilik.nik)
+    }
+}
+
+impl Attachable for SuratUsahaRequest {
+    fn lampiran(&self) -> &Lampiran {
+        &self.lampiran
+    }
+}
+
+impl Validator for SuratUsahaRequest {
+    /// Validate all input data and return descriptive errors if invalid.
+    fn validate(&self) -> Result<(), String> {
+        use super::validation::*;
+
+        let mut errors = ValidationErrors::new();
+
+        // Validate pemilik data
+        validate_required(
+            &self.pemilik.nama,
+            "pemilik.nama",
+            "Nama Pemilik Usaha",
+            &mut errors,
+        );
+        validate_nik(&self.pemilik.nik, "pemilik.nik", &mut errors);
+        validate_ttl(&self.pemilik.ttl, "pemilik.ttl", &mut errors);
+        validate_required(
+            &self.pemilik.agama,
+            "pemilik.agama",
+            "Agama Pemilik",
+            &mut errors,
+        );
+        validate_required(
+            &self.pemilik.pekerjaan,
+            "pemilik.pekerjaan",
+            "Pekerjaan Pemilik",
+            &mut errors,
+        );
+        validate_required(
+            &self.pemilik.alamat,
+            "pemilik.alamat",
+            "Alamat Pemilik",
+            &mut errors,
+        );
+        validate_phone(&self.pemilik.telp, "pemilik.telp", &mut errors);
+
+        // Validate usaha data
+        validate_required(
+            &self.usaha.nama_usaha,
+            "usaha.nama_usaha",
+            "Nama Usaha",
+            &mut errors,
+        );
+        validate_required(
+            &self.usaha.jenis_usaha,
+            "usaha.jenis_usaha",
+            "Jenis Usaha",
+            &mut errors,
+        );
+        validate_required(
+            &self.usaha.alamat_usaha,
+            "usaha.alamat_usaha",
+            "Alamat Usaha",
+            &mut errors,
+        );
+        validate_year_range(
+            &self.usaha.tahun_mulai,
+            "usaha.tahun_mulai",
+            "Tahun Mulai Usaha",
+            MIN_TAHUN_MULAI,
+            crate::timezone::today().year(),
+            &mut errors,
+        );
+        validate_required(
+            &self.usaha.keperluan,
+            "usaha.keperluan",
+            "Keperluan",
+            &mut errors,
+        );
+
+        // Validate meta
+        validate_required(
+            &self.meta.kelurahan,
+            "meta.kelurahan",
+            "Nama Kelurahan",
+            &mut errors,
+        );
+
+        errors.into_result()
+    }
+}
+
+// Inherent impl for compatibility
+impl SuratUsahaRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        Validator::validate(self)
+    }
+
+    /// Filled-in request used to dry-run a replacement template
+    pub fn sample() -> Self {
+        Self {
+            pemilik: PengisiData::sample(),
+            usaha: UsahaData {
+                nama_usaha: "Warung Makan Sederhana".to_string(),
+                jenis_usaha: "Kuliner".to_string(),
+                alamat_usaha: "Jl. Cakung Barat No. 1".to_string(),
+                tahun_mulai: "2019".to_string(),
+                keperluan: "Pengajuan kredit usaha".to_string(),
+            },
+            meta: SuratUsahaMeta {
+                kelurahan: "Cakung Barat".to_string(),
+                tanggal: None,
+            },
+            lampiran: Lampiran::default(),
+        }
+    }
+}
+
+/// Generator untuk Surat Keterangan Usaha.
+pub struct SuratUsahaGenerator {
+    templates: Arc<TemplateStore>,
+}
+
+impl SuratUsahaGenerator {
+    /// Create a new generator instance.
+    pub fn new() -> Result<Self, GeneratorError> {
+        Self::with_store(Arc::new(TemplateStore::new(get_static_dir())))
+    }
+
+    /// Create a generator reading its template through `templates`.
+    pub fn with_store(templates: Arc<TemplateStore>) -> Result<Self, GeneratorError> {
+        // Fail at startup instead of on the first call
+        templates.get(TEMPLATE_FILE)?;
+        Ok(Self { templates })
+    }
+
+    /// Render `request` with `template` instead of the stored one. Used to
+    /// try out a replacement template before it is saved.
+    pub fn generate_with_template(
+        &self,
+        template: &str,
+        request: SuratUsahaRequest,
+        attachments: &Attachments,
+    ) -> Result<GeneratedDocument, GeneratorError> {
+        let tanggal = request
+            .meta
+            .tanggal
+            .clone()
+            .unwrap_or_else(today_id);
+
+        let typst_source = self.render_template(template, &request, &tanggal, attachments);
+
+        TypstRenderEngine::render_with_attachments(
+            TEMPLATE_FILE,
+            &typst_source,
+            &request.pemilik.nama,
+            Some(tanggal),
+            attachments,
+        )
+    }
+
+    fn render_template(
+        &self,
+        template: &str,
+        request: &SuratUsahaRequest,
+        tanggal: &str,
+        attachments: &Attachments,
+    ) -> String {
+        let pemilik = &request.pemilik;
+        let usaha = &request.usaha;
+        let meta = &request.meta;
+        let jk_str = if pemilik.jk { "Laki-laki" } else { "Perempuan" };
+
+        format!(
+            r#"#let surat_keterangan_usaha(
+  pemilik: (
+    nama: "{}",
+    nik: "{}",
+    ttl: "{}",
+    jk: "{}",
+    agama: "{}",
+    pekerjaan: "{}",
+    alamat: "{}",
+    telp: "{}",
+  ),
+  usaha: (
+    nama_usaha: "{}",
+    jenis_usaha: "{}",
+    alamat_usaha: "{}",
+    lama_usaha: "{}",
+    keperluan: "{}",
+  ),
+  meta: (
+    kelurahan: "{}",
+    tanggal: "{}",
+  ),
+  lampiran: {},
+) = {{
+{}
+
+#surat_keterangan_usaha()
+"#,
+            escape_typst_string(&pemilik.nama),
+            escape_typst_string(&pemilik.nik),
+            escape_typst_string(&pemilik.ttl),
+            escape_typst_string(jk_str),
+            escape_typst_string(&pemilik.agama),
+            escape_typst_string(&pemilik.pekerjaan),
+            escape_typst_string(&pemilik.alamat),
+            escape_typst_string(&pemilik.telp),
+            escape_typst_string(&usaha.nama_usaha),
+            escape_typst_string(&usaha.jenis_usaha),
+            escape_typst_string(&usaha.alamat_usaha),
+            escape_typst_string(&request.lama_usaha()),
+            escape_typst_string(&usaha.keperluan),
+            escape_typst_string(&meta.kelurahan),
+            escape_typst_string(tanggal),
+            attachments.typst_value(),
+            Self::extract_function_body(template),
+        )
+    }
+
+    fn extract_function_body(template: &str) -> String {
+        if let Some(start) = template.find(") = {") {
+            let body_start = start + 5;
+            if let Some(end) = template.rfind("#surat_keterangan_usaha()") {
+                return template[body_start..end].to_string();
+            }
+        }
+        template.to_string()
+    }
+}
+
+impl Generator<SuratUsahaRequest> for SuratUsahaGenerator {
+    /// Generate the document with the images the request refers to.
+    fn generate_with_attachments(
+        &self,
+        request: SuratUsahaRequest,
+        attachments: &Attachments,
+    ) -> Result<GeneratedDocument, GeneratorError> {
+        let template = self.templates.get(TEMPLATE_FILE)?;
+        self.generate_with_template(&template, request, attachments)
+    }
+}
+
+// Inherent impl for compatibility
+impl SuratUsahaGenerator {
+    pub fn generate(
+        &self,
+        request: SuratUsahaRequest,
+    ) -> Result<GeneratedDocument, GeneratorError> {
+        Generator::generate(self, request)
+    }
+}
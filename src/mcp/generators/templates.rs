@@ -0,0 +1,133 @@
+//! Typst templates read from disk and reloaded when they change.
+//!
+//! Generators ask the store for their template on every call. The cached
+//! copy is reused while the file keeps its modification time and size, so
+//! an edited template is picked up without a redeploy.
+
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use utoipa::ToSchema;
+
+use super::GeneratorError;
+
+const TEMPLATE_EXTENSION: &str = "typ";
+
+/// A template file with the hash of its current contents
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TemplateInfo {
+    pub name: String,
+    pub size: u64,
+    /// Hex-encoded SHA-256 of the contents
+    pub sha256: String,
+    pub modified_at: Option<DateTime<Utc>>,
+}
+
+struct CachedTemplate {
+    modified: Option<SystemTime>,
+    len: u64,
+    contents: Arc<str>,
+}
+
+/// Templates of one directory, cached in memory
+pub struct TemplateStore {
+    dir: PathBuf,
+    cache: RwLock<HashMap<String, CachedTemplate>>,
+    /// Held while a replacement is written, so staging files never collide
+    replacing: Mutex<()>,
+}
+
+impl TemplateStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            cache: RwLock::new(HashMap::new()),
+            replacing: Mutex::new(()),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Contents of template `name`, re-read when the file changed on disk
+    pub fn get(&self, name: &str) -> Result<Arc<str>, GeneratorError> {
+        let path = self.dir.join(name);
+        let metadata = fs::metadata(&path).map_err(GeneratorError::TemplateIo)?;
+        let modified = metadata.modified().ok();
+        if let Some(cached) = self.cache.read().get(name) {
+            if cached.modified == modified && cached.len == metadata.len() {
+                return Ok(Arc::clone(&cached.contents));
+            }
+        }
+
+        let contents: Arc<str> = fs::read_to_string(&path)
+            .map_err(GeneratorError::TemplateIo)?
+            .into();
+        log::info!("Loaded Typst template {}", path.display());
+        self.cache.write().insert(
+            name.to_string(),
+            CachedTemplate {
+                modified,
+                len: metadata.len(),
+                contents: Arc::clone(&contents),
+            },
+        );
+        Ok(contents)
+    }
+
+    /// Every `.typ` file in the directory, by name
+    pub fn list(&self) -> Result<Vec<TemplateInfo>, GeneratorError> {
+        let mut templates = Vec::new();
+        for entry in fs::read_dir(&self.dir).map_err(GeneratorError::TemplateIo)? {
+            let path = entry.map_err(GeneratorError::TemplateIo)?.path();
+            if !path.is_file()
+                || path.extension().and_then(|ext| ext.to_str()) != Some(TEMPLATE_EXTENSION)
+            {
+                continue;
+            }
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            templates.push(self.info(name)?);
+        }
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(templates)
+    }
+
+    /// Overwrite template `name` with `contents`.
+    ///
+    /// The new file is written next to the old one and renamed over it, so
+    /// a generator never reads a half-written template.
+    pub fn replace(&self, name: &str, contents: &str) -> Result<TemplateInfo, GeneratorError> {
+        let _replacing = self.replacing.lock();
+        let path = self.dir.join(name);
+        let staging = self.dir.join(format!(".{}.tmp", name));
+        fs::write(&staging, contents).map_err(GeneratorError::TemplateIo)?;
+        if let Err(e) = fs::rename(&staging, &path) {
+            let _ = fs::remove_file(&staging);
+            return Err(GeneratorError::TemplateIo(e));
+        }
+        log::info!("Replaced Typst template {}", path.display());
+        self.info(name)
+    }
+
+    fn info(&self, name: &str) -> Result<TemplateInfo, GeneratorError> {
+        let contents = self.get(name)?;
+        let modified = fs::metadata(self.dir.join(name))
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        Ok(TemplateInfo {
+            name: name.to_string(),
+            size: contents.len() as u64,
+            sha256: hex::encode(Sha256::digest(contents.as_bytes())),
+            modified_at: modified.map(DateTime::<Utc>::from),
+        })
+    }
+}
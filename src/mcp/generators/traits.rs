@@ -1,5 +1,6 @@
 //! Traits for generator system standardization.
 
+use super::attachments::{Attachments, Lampiran};
 use super::{GeneratedDocument, GeneratorError};
 
 /// Trait for validating request objects.
@@ -11,5 +12,26 @@ pub trait Validator {
 /// Trait for document generators.
 pub trait Generator<Req> {
     /// Generate a document from the request.
-    fn generate(&self, request: Req) -> Result<GeneratedDocument, GeneratorError>;
+    fn generate(&self, request: Req) -> Result<GeneratedDocument, GeneratorError> {
+        self.generate_with_attachments(request, &Attachments::default())
+    }
+
+    /// Generate a document embedding the images the request refers to.
+    fn generate_with_attachments(
+        &self,
+        request: Req,
+        attachments: &Attachments,
+    ) -> Result<GeneratedDocument, GeneratorError>;
+}
+
+/// Trait for requests made on behalf of a resident.
+pub trait Requester {
+    /// Name and NIK of the resident submitting the request.
+    fn requester(&self) -> (&str, &str);
+}
+
+/// Trait for requests that may embed uploaded images.
+pub trait Attachable {
+    /// Asset ids of the pas foto and signature to embed.
+    fn lampiran(&self) -> &Lampiran;
 }
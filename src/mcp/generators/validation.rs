@@ -3,8 +3,14 @@
 //! Provides clear, descriptive validation errors that are easy to understand
 //! for both AI (MCP server) and human users.
 
+use chrono::NaiveDate;
 use std::fmt;
 
+use crate::i18n::MONTHS;
+
+/// Oldest age accepted for a date of birth
+const MAX_AGE_YEARS: u32 = 130;
+
 /// Validation error with detailed, user-friendly messages.
 #[derive(Debug, Clone)]
 pub struct ValidationError {
@@ -50,6 +56,35 @@ impl ValidationError {
             .with_suggestion("Gunakan format nomor telepon Indonesia, contoh: 08123456789")
     }
 
+    /// Create error for invalid RT/RW format
+    pub fn invalid_rt_rw(field: &str, value: &str) -> Self {
+        Self::new(field, format!("Format RT/RW '{}' tidak valid", value))
+            .with_suggestion("Gunakan angka 1-999 untuk RT dan RW, contoh: 003/005")
+    }
+
+    /// Create error for invalid postal code
+    pub fn invalid_kode_pos(field: &str, value: &str) -> Self {
+        Self::new(field, format!("Kode pos '{}' tidak valid", value))
+            .with_suggestion("Kode pos terdiri dari 5 digit angka, contoh: 13910")
+    }
+
+    /// Create error for a date of birth that cannot be right
+    pub fn invalid_birth_date(field: &str, value: &str, reason: &str) -> Self {
+        Self::new(
+            field,
+            format!("Tanggal lahir '{}' tidak mungkin: {}", value, reason),
+        )
+        .with_suggestion("Periksa kembali tanggal lahir sesuai KTP")
+    }
+
+    /// Create error for a year outside the accepted range
+    pub fn invalid_year(field: &str, value: &str, min: i32, max: i32) -> Self {
+        Self::new(field, format!("Tahun '{}' tidak valid", value)).with_suggestion(format!(
+            "Gunakan tahun 4 digit antara {} dan {}, contoh: {}",
+            min, max, max
+        ))
+    }
+
     /// Create error for invalid date format
     pub fn invalid_date_format(field: &str, value: &str) -> Self {
         Self::new(field, format!("Format tanggal '{}' tidak valid", value)).with_suggestion(
@@ -177,16 +212,167 @@ pub fn validate_phone(value: &str, field: &str, errors: &mut ValidationErrors) {
     }
 }
 
-/// Validate tempat tanggal lahir format
-pub fn validate_ttl(value: &str, field: &str, errors: &mut ValidationErrors) {
+/// Validate tempat tanggal lahir ("Jakarta, 1 Januari 1990") and return the
+/// date of birth. Dates that do not exist, lie in the future or make the
+/// person older than 130 years are rejected.
+pub fn validate_ttl(value: &str, field: &str, errors: &mut ValidationErrors) -> Option<NaiveDate> {
     let trimmed = value.trim();
     if trimmed.is_empty() {
         errors.add(ValidationError::empty_field(field, "Tempat, Tanggal Lahir"));
-        return;
+        return None;
     }
 
-    // Should contain a comma separating place and date
-    if !trimmed.contains(',') {
+    // The place may itself contain commas, the date is after the last one
+    let date = trimmed
+        .rsplit_once(',')
+        .filter(|(place, _)| !place.trim().is_empty())
+        .and_then(|(_, date)| parse_indonesian_date(date));
+    let Some(date) = date else {
         errors.add(ValidationError::invalid_date_format(field, trimmed));
+        return None;
+    };
+
+    let today = crate::timezone::today();
+    if date > today {
+        errors.add(ValidationError::invalid_birth_date(
+            field,
+            trimmed,
+            "tanggal berada di masa depan",
+        ));
+        return None;
+    }
+    if today.years_since(date).unwrap_or(0) > MAX_AGE_YEARS {
+        errors.add(ValidationError::invalid_birth_date(
+            field,
+            trimmed,
+            &format!("usia lebih dari {} tahun", MAX_AGE_YEARS),
+        ));
+        return None;
+    }
+    Some(date)
+}
+
+/// Parse a date written as "1 Januari 1990", "1 Jan 1990" or "01-01-1990".
+/// Month names are matched case-insensitively, by their first three letters
+/// or more.
+pub fn parse_indonesian_date(value: &str) -> Option<NaiveDate> {
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    let (day, month, year) = match parts.as_slice() {
+        [day, month, year] => (*day, month_number(month)?, *year),
+        [numeric] => {
+            let mut numbers = numeric.split(['-', '/', '.']);
+            let (day, month, year) = (numbers.next()?, numbers.next()?, numbers.next()?);
+            if numbers.next().is_some() {
+                return None;
+            }
+            (day, month.parse().ok()?, year)
+        }
+        _ => return None,
+    };
+    if year.len() != 4 || !year.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    NaiveDate::from_ymd_opt(year.parse().ok()?, month, day.parse().ok()?)
+}
+
+fn month_number(name: &str) -> Option<u32> {
+    let name = name.to_lowercase();
+    if name.chars().count() < 3 {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|month| month.to_lowercase().starts_with(&name))
+        .map(|index| index as u32 + 1)
+}
+
+/// Zero-padded RT/RW ("003/005") from "3/5" or "003/005". None when either
+/// number is missing, zero or longer than three digits.
+pub fn normalize_rt_rw(value: &str) -> Option<String> {
+    let (rt, rw) = value.split_once('/')?;
+    join_rt_rw(rt, rw)
+}
+
+/// Zero-padded RT/RW from separately entered RT and RW numbers
+pub fn join_rt_rw(rt: &str, rw: &str) -> Option<String> {
+    Some(format!("{:03}/{:03}", rt_rw_number(rt)?, rt_rw_number(rw)?))
+}
+
+fn rt_rw_number(value: &str) -> Option<u16> {
+    let value = value.trim();
+    if !(1..=3).contains(&value.len()) || !value.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    value.parse().ok().filter(|number| *number > 0)
+}
+
+/// Validate RT/RW written as "RT/RW" and return it zero-padded
+pub fn validate_rt_rw(value: &str, field: &str, errors: &mut ValidationErrors) -> Option<String> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        errors.add(ValidationError::empty_field(field, "RT/RW"));
+        return None;
+    }
+
+    let normalized = normalize_rt_rw(trimmed);
+    if normalized.is_none() {
+        errors.add(ValidationError::invalid_rt_rw(field, trimmed));
+    }
+    normalized
+}
+
+/// Validate RT and RW given as separate fields and return "RT/RW" zero-padded
+pub fn validate_rt_rw_parts(
+    rt: &str,
+    rw: &str,
+    rt_field: &str,
+    rw_field: &str,
+    errors: &mut ValidationErrors,
+) -> Option<String> {
+    for (value, field, label) in [(rt, rt_field, "RT"), (rw, rw_field, "RW")] {
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            errors.add(ValidationError::empty_field(field, label));
+        } else if rt_rw_number(trimmed).is_none() {
+            errors.add(ValidationError::invalid_rt_rw(field, trimmed));
+        }
+    }
+    join_rt_rw(rt, rw)
+}
+
+/// Validate kode pos: five digits, the first of which is not zero
+pub fn validate_kode_pos(value: &str, field: &str, errors: &mut ValidationErrors) {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        errors.add(ValidationError::empty_field(field, "Kode Pos"));
+        return;
+    }
+
+    if trimmed.len() != 5
+        || !trimmed.chars().all(|c| c.is_ascii_digit())
+        || trimmed.starts_with('0')
+    {
+        errors.add(ValidationError::invalid_kode_pos(field, trimmed));
+    }
+}
+
+/// Validate a four-digit year between `min` and `max` inclusive
+pub fn validate_year_range(
+    value: &str,
+    field: &str,
+    label: &str,
+    min: i32,
+    max: i32,
+    errors: &mut ValidationErrors,
+) {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        errors.add(ValidationError::empty_field(field, label));
+        return;
+    }
+
+    match trimmed.parse::<i32>() {
+        Ok(year) if (min..=max).contains(&year) => {}
+        _ => errors.add(ValidationError::invalid_year(field, trimmed, min, max)),
     }
 }
@@ -1,55 +1,312 @@
-//! MCP Stateless HTTP Handlers for Actix-Web.
+//! MCP HTTP Handlers for Actix-Web.
 //!
-//! This implementation uses stateless HTTP POST for Cloud Run / serverless compatibility.
-//! No SSE connections are maintained - each request is independent.
+//! `/mcp` speaks the streamable HTTP transport, see `super::streamable`.
+//! `/sse` keeps the legacy HTTP+SSE transport: a plain POST is answered in
+//! the HTTP response, while clients that first open `GET /sse` receive an
+//! `endpoint` event with their session URL and get responses to POSTs on
+//! that URL on their own stream only.
+//!
+//! Both transports check credentials before dispatching, see
+//! [`McpAuth`].
 
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::http::header;
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use futures::stream::StreamExt;
+use serde::Deserialize;
+use serde_json::json;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
 
+use crate::auth::McpAuth;
 use crate::db::AppState;
-use crate::mcp::rpc::RpcRequest;
-use crate::mcp::service::McpService;
+use crate::mcp::cancellation::{self, InFlightRequests, CANCELLED_NOTIFICATION};
+use crate::mcp::rpc::{OutboundResponse, RpcRequest};
+use crate::mcp::service::{McpService, RequestContext};
+use crate::mcp::session::{
+    self, SessionConfig, SessionError, SessionGuard, SessionManager, StreamEvent,
+    DEFAULT_KEEPALIVE_INTERVAL,
+};
+use crate::mcp::streamable;
 
-/// MCP State for Actix-Web (stateless version)
+/// MCP State for Actix-Web
 /// Includes AppState for database access in async tools.
 pub struct McpState {
     pub service: McpService,
     pub app_state: web::Data<AppState>,
+    pub sessions: Arc<SessionManager>,
+    pub auth: McpAuth,
+    pub in_flight: Arc<InFlightRequests>,
+    /// Time between keepalives on open session streams
+    pub keepalive_interval: Duration,
 }
 
 impl McpState {
     pub fn new(service: McpService, app_state: web::Data<AppState>) -> Self {
-        Self { service, app_state }
+        Self {
+            service,
+            app_state,
+            sessions: Arc::new(SessionManager::default()),
+            auth: McpAuth::default(),
+            in_flight: Arc::new(InFlightRequests::new()),
+            keepalive_interval: DEFAULT_KEEPALIVE_INTERVAL,
+        }
+    }
+
+    /// Replace the session store with one using `config`
+    pub fn with_sessions(mut self, config: &SessionConfig) -> Self {
+        self.sessions = Arc::new(SessionManager::new(config.idle_timeout));
+        self.keepalive_interval = config.keepalive_interval;
+        self
+    }
+
+    pub fn with_auth(mut self, auth: McpAuth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Tell every open session to fetch `tools/list` again, returning how
+    /// many sessions were reached
+    pub fn notify_tools_changed(&self) -> usize {
+        let notification = json!({ "jsonrpc": "2.0", "method": TOOLS_LIST_CHANGED });
+        let reached = self.sessions.broadcast(&notification.to_string());
+        log::info!("Tool list change sent to {} MCP sessions", reached);
+        reached
+    }
+
+    /// Offer or withdraw the post browsing tools, notifying open sessions
+    /// when the tool list changes. Returns whether it changed.
+    pub fn set_browse_tools_enabled(&self, enabled: bool) -> bool {
+        let changed = self.service.registry().set_browse_tools_enabled(enabled);
+        if changed {
+            self.notify_tools_changed();
+        }
+        changed
     }
+
+    /// Whether `method` may run with the credentials on `req`
+    pub(crate) fn is_permitted(&self, req: &HttpRequest, method: &str) -> bool {
+        McpAuth::is_open(method) || self.auth.is_authorized(req)
+    }
+
+    /// Whom tool calls on `req` count against: its credentials, since one
+    /// key may open many sessions, else the session
+    pub(crate) fn caller(&self, req: &HttpRequest, session: Option<&str>) -> Option<String> {
+        self.auth
+            .caller(req)
+            .or_else(|| session.map(|id| format!("session:{}", id)))
+    }
+
+    /// Check credentials, then run the request. `Err` is the HTTP 401 sent
+    /// when `MCP_AUTH_HTTP_401` is set; otherwise a rejected request gets a
+    /// JSON-RPC error like any other.
+    pub(crate) async fn dispatch(
+        &self,
+        req: &HttpRequest,
+        request: RpcRequest,
+        session: Option<&str>,
+    ) -> Result<Option<OutboundResponse>, HttpResponse> {
+        if !self.is_permitted(req, &request.method) {
+            log::warn!("Rejected unauthenticated MCP request: {}", request.method);
+            if self.auth.http_401 {
+                return Err(HttpResponse::Unauthorized()
+                    .insert_header((header::WWW_AUTHENTICATE, "Bearer realm=\"mcp\""))
+                    .json(OutboundResponse::error(
+                        request.id,
+                        UNAUTHORIZED,
+                        UNAUTHORIZED_MESSAGE,
+                    )));
+            }
+            return Ok(Some(OutboundResponse::error(
+                request.id,
+                UNAUTHORIZED,
+                UNAUTHORIZED_MESSAGE,
+            )));
+        }
+
+        let notifier = session.and_then(|id| self.sessions.stream(id));
+        let caller = self.caller(req, session);
+        Ok(self.run(request, session, caller, notifier).await)
+    }
+
+    /// Run an authorized request, tracked so a `notifications/cancelled`
+    /// from the same session can stop it.
+    pub(crate) async fn run(
+        &self,
+        request: RpcRequest,
+        session: Option<&str>,
+        caller: Option<String>,
+        notifier: Option<mpsc::Sender<String>>,
+    ) -> Option<OutboundResponse> {
+        if request.method == CANCELLED_NOTIFICATION {
+            if let Some(id) = cancellation::cancelled_request_id(request.params.as_ref()) {
+                if self.in_flight.cancel(session, id) {
+                    log::info!("Cancelling MCP request {}", id);
+                }
+            }
+        }
+
+        let tracked = request
+            .id
+            .as_ref()
+            .map(|id| self.in_flight.register(session, id));
+        let context = RequestContext {
+            notifier,
+            cancellation: tracked
+                .as_ref()
+                .map(|guard| guard.token())
+                .unwrap_or_default(),
+            log_level: session.and_then(|id| self.sessions.log_level(id)),
+            caller,
+        };
+        self.service
+            .handle_request_with(request, &self.app_state, context)
+            .await
+    }
+}
+
+/// Notification telling clients their cached `tools/list` is stale
+pub const TOOLS_LIST_CHANGED: &str = "notifications/tools/list_changed";
+
+/// JSON-RPC error code for requests without valid credentials
+const UNAUTHORIZED: i64 = -32001;
+const UNAUTHORIZED_MESSAGE: &str =
+    "Autentikasi diperlukan: kirim token admin atau API key dengan scope mcp.";
+
+#[derive(Debug, Deserialize)]
+pub struct SessionQuery {
+    #[serde(default)]
+    pub session: Option<String>,
 }
 
-/// RPC handler - POST /mcp
-/// Handles JSON-RPC requests in stateless mode
+/// RPC handler - POST /sse
+/// Without `?session=` the response is returned directly; with it the
+/// response, and any progress before it, is pushed to that session's SSE
+/// stream.
+#[utoipa::path(
+    operation_id = "postSseMessage",
+    post,
+    path = "/sse",
+    tag = "MCP",
+    params(("session" = Option<String>, Query, description = "Session announced by GET /sse")),
+    request_body(content = Object, description = "JSON-RPC 2.0 request or notification"),
+    security(("bearer_auth" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "JSON-RPC response, for calls without a session", body = Object),
+        (status = 202, description = "Accepted; the response goes to the session stream"),
+        (status = 401, description = "Missing credentials, when MCP_AUTH_HTTP_401 is set"),
+        (status = 404, description = "Unknown session")
+    )
+)]
 pub async fn rpc_handler(
+    req: HttpRequest,
     state: web::Data<Arc<McpState>>,
+    query: web::Query<SessionQuery>,
     body: web::Json<RpcRequest>,
 ) -> impl Responder {
     log::info!("Received MCP request: {}", body.method);
 
-    // Pass AppState to service for async tool calls
-    if let Some(response) = state
-        .service
-        .handle_request(body.into_inner(), &state.app_state)
+    let session = query.into_inner().session;
+    if let Some(id) = &session {
+        if !state.sessions.contains(id) {
+            return session_error(SessionError::NotFound(id.clone()));
+        }
+    }
+
+    let response = match state
+        .dispatch(&req, body.into_inner(), session.as_deref())
         .await
     {
-        return HttpResponse::Ok()
+        Ok(response) => response,
+        Err(rejection) => return rejection,
+    };
+
+    match (session, response) {
+        (Some(id), Some(response)) => {
+            let message = serde_json::to_string(&response).unwrap();
+            match state.sessions.send(&id, message).await {
+                Ok(()) => HttpResponse::Accepted().finish(),
+                Err(e) => session_error(e),
+            }
+        }
+        (None, Some(response)) => HttpResponse::Ok()
             .content_type("application/json")
-            .json(response);
+            .json(response),
+        // Notifications return 202 Accepted
+        (_, None) => HttpResponse::Accepted().finish(),
     }
+}
 
-    // Notifications return 202 Accepted
-    HttpResponse::Accepted().finish()
+/// SSE handler - GET /sse
+/// Opens a session and announces its POST URL in the `endpoint` event.
+#[utoipa::path(
+    operation_id = "openSseStream",
+    get,
+    path = "/sse",
+    tag = "MCP",
+    responses(
+        (status = 200, description = "SSE stream starting with the `endpoint` event", content_type = "text/event-stream")
+    )
+)]
+pub async fn sse_handler(state: web::Data<Arc<McpState>>) -> impl Responder {
+    let (id, receiver) = state.sessions.open();
+    log::info!("MCP session {} opened", id);
+
+    let endpoint = format!("event: endpoint\ndata: /sse?session={}\n\n", id);
+    let guard = SessionGuard::new(Arc::clone(&state.sessions), id.clone());
+
+    let messages = session::keepalive_stream(
+        Arc::clone(&state.sessions),
+        id,
+        receiver,
+        state.keepalive_interval,
+    )
+    .map(move |event| {
+        let _session = &guard;
+        sse_event(event)
+    });
+    let stream =
+        futures::stream::once(async move { Ok::<_, actix_web::Error>(web::Bytes::from(endpoint)) })
+            .chain(messages);
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .insert_header(("Connection", "keep-alive"))
+        .streaming(stream)
+}
+
+/// A session stream event in SSE framing; keepalives are comment lines,
+/// which clients ignore
+pub(crate) fn sse_event(event: StreamEvent) -> Result<web::Bytes, actix_web::Error> {
+    Ok(web::Bytes::from(match event {
+        StreamEvent::Message(message) => format!("event: message\ndata: {}\n\n", message),
+        StreamEvent::Keepalive => ": keepalive\n\n".to_string(),
+    }))
+}
+
+pub(crate) fn session_error(error: SessionError) -> HttpResponse {
+    let response = OutboundResponse::error(None, -32001, error.to_string());
+    match error {
+        SessionError::NotFound(_) => HttpResponse::NotFound().json(response),
+        SessionError::Closed(_) => HttpResponse::Gone().json(response),
+        SessionError::NoStream(_) => HttpResponse::Conflict().json(response),
+    }
 }
 
-/// Configure MCP routes (stateless)
+/// Configure MCP routes
 pub fn config(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::resource("/mcp").route(web::post().to(rpc_handler)));
+    cfg.service(
+        web::resource("/mcp")
+            .route(web::post().to(streamable::post_handler))
+            .route(web::get().to(streamable::get_handler))
+            .route(web::delete().to(streamable::delete_handler)),
+    );
 
-    // Keep /sse route for backward compatibility (same as /mcp)
-    cfg.service(web::resource("/sse").route(web::post().to(rpc_handler)));
+    // Legacy HTTP+SSE transport
+    cfg.service(
+        web::resource("/sse")
+            .route(web::get().to(sse_handler))
+            .route(web::post().to(rpc_handler)),
+    );
 }
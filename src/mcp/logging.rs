@@ -0,0 +1,81 @@
+//! Client-visible log messages.
+//!
+//! Once a session sends `logging/setLevel`, warnings and errors raised
+//! while its tool calls run are pushed to its stream as
+//! `notifications/message`, so a client can see why a call failed without
+//! access to the server logs. Sessions that never set a level get none.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::mpsc;
+
+/// Syslog severities used by the MCP logging capability, least severe first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoggingLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+/// Minimum level a session asked for, shared by all of its requests
+#[derive(Debug, Default)]
+pub struct SessionLogLevel(Mutex<Option<LoggingLevel>>);
+
+impl SessionLogLevel {
+    pub fn get(&self) -> Option<LoggingLevel> {
+        *self.0.lock()
+    }
+
+    pub fn set(&self, level: LoggingLevel) {
+        *self.0.lock() = Some(level);
+    }
+}
+
+/// Sends log messages about one tool call to the stream of its caller
+#[derive(Debug, Clone)]
+pub struct ToolLogger {
+    logger: String,
+    min_level: LoggingLevel,
+    sender: mpsc::Sender<String>,
+}
+
+impl ToolLogger {
+    pub fn new(
+        logger: impl Into<String>,
+        min_level: LoggingLevel,
+        sender: mpsc::Sender<String>,
+    ) -> Self {
+        Self {
+            logger: logger.into(),
+            min_level,
+            sender,
+        }
+    }
+
+    /// Queue a message unless it is below the session's level. Like
+    /// progress, it is dropped rather than waited on when the stream is full.
+    pub fn log(&self, level: LoggingLevel, message: &str) {
+        if level < self.min_level {
+            return;
+        }
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/message",
+            "params": {
+                "level": level,
+                "logger": self.logger,
+                "data": message,
+            }
+        });
+        if self.sender.try_send(notification.to_string()).is_err() {
+            log::debug!("Dropped log notification from {}", self.logger);
+        }
+    }
+}
@@ -2,11 +2,20 @@
 //!
 //! Provides JSON-RPC 2.0 over HTTP/SSE for AI model integration.
 
+pub mod cancellation;
 pub mod content;
 pub mod generators;
 pub mod handlers;
+pub mod logging;
+pub mod progress;
+pub mod prompts;
+pub mod resources;
 pub mod rpc;
 pub mod service;
+pub mod session;
+pub mod streamable;
+pub mod template_handlers;
+pub mod tool_handlers;
 pub mod tools;
 
 pub use handlers::{config, McpState};
@@ -0,0 +1,77 @@
+//! Progress notifications for long-running tool calls.
+//!
+//! A `tools/call` whose params carry `_meta.progressToken` gets
+//! `notifications/progress` messages on the caller's stream while the
+//! document is validated, rendered and uploaded, before the final response.
+
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+
+/// Total used for the `progress` percentages
+const PROGRESS_TOTAL: u8 = 100;
+
+/// Coarse stages of document generation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressStage {
+    Validating,
+    Rendering,
+    Uploading,
+}
+
+impl ProgressStage {
+    fn progress(self) -> u8 {
+        match self {
+            Self::Validating => 10,
+            Self::Rendering => 30,
+            Self::Uploading => 80,
+        }
+    }
+
+    fn message(self) -> &'static str {
+        match self {
+            Self::Validating => "Memvalidasi data surat",
+            Self::Rendering => "Membuat dokumen PDF",
+            Self::Uploading => "Mengunggah dokumen",
+        }
+    }
+}
+
+/// Sends progress for one request to the stream of its caller
+#[derive(Debug, Clone)]
+pub struct ProgressReporter {
+    token: Value,
+    sender: mpsc::Sender<String>,
+}
+
+impl ProgressReporter {
+    pub fn new(token: Value, sender: mpsc::Sender<String>) -> Self {
+        Self { token, sender }
+    }
+
+    /// Queue a notification for `stage`. Never waits: progress is dropped
+    /// when the stream is full or gone, the call itself carries on.
+    pub fn report(&self, stage: ProgressStage) {
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/progress",
+            "params": {
+                "progressToken": self.token,
+                "progress": stage.progress(),
+                "total": PROGRESS_TOTAL,
+                "message": stage.message(),
+            }
+        });
+        if self.sender.try_send(notification.to_string()).is_err() {
+            log::debug!("Dropped progress notification for {}", self.token);
+        }
+    }
+}
+
+/// `_meta.progressToken` of a request's params, if the client asked for progress
+pub fn progress_token(params: Option<&Value>) -> Option<Value> {
+    params?
+        .get("_meta")?
+        .get("progressToken")
+        .filter(|token| token.is_string() || token.is_number())
+        .cloned()
+}
@@ -0,0 +1,219 @@
+//! MCP prompts for drafting letters.
+//!
+//! Each prompt walks the model through one document tool: which data to
+//! collect from the citizen and which tool to call once it is complete.
+//! `{nama}` and `{nik}` in a template are replaced by the prompt arguments
+//! when the client already knows them.
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::tools::{
+    surat_domisili, surat_kpr, surat_nib_npwp, surat_skck, surat_tidak_mampu, surat_usaha,
+};
+
+/// Shown in place of an argument the client did not supply
+const MISSING_ARGUMENT: &str = "(belum diketahui, tanyakan kepada warga)";
+
+/// Arguments every letter prompt accepts
+const CITIZEN_ARGUMENTS: &[ArgumentDefinition] = &[
+    ArgumentDefinition {
+        name: "nama",
+        description: "Nama lengkap warga, jika sudah diketahui",
+        required: false,
+    },
+    ArgumentDefinition {
+        name: "nik",
+        description: "NIK warga (16 digit), jika sudah diketahui",
+        required: false,
+    },
+];
+
+struct ArgumentDefinition {
+    name: &'static str,
+    description: &'static str,
+    required: bool,
+}
+
+struct PromptDefinition {
+    name: &'static str,
+    /// Letter name, e.g. "Surat Pengantar SKCK"
+    title: &'static str,
+    /// Document tool the prompt leads to
+    tool: &'static str,
+    /// Data to collect before calling `tool`
+    fields: &'static str,
+    arguments: &'static [ArgumentDefinition],
+}
+
+const PROMPTS: &[PromptDefinition] = &[
+    PromptDefinition {
+        name: "draft_sktm",
+        title: "Surat Keterangan Tidak Mampu (SKTM)",
+        tool: surat_tidak_mampu::TOOL_NAME,
+        fields: "nama lengkap, NIK, tempat/tanggal lahir, jenis kelamin, agama, pekerjaan, alamat sesuai KTP, nomor telepon",
+        arguments: CITIZEN_ARGUMENTS,
+    },
+    PromptDefinition {
+        name: "draft_kpr",
+        title: "Surat Keterangan Belum Memiliki Rumah untuk pengajuan KPR",
+        tool: surat_kpr::TOOL_NAME,
+        fields: "nama lengkap, NIK, tempat/tanggal lahir, jenis kelamin, agama, pekerjaan, alamat sesuai KTP, nomor telepon, bank tujuan KPR",
+        arguments: CITIZEN_ARGUMENTS,
+    },
+    PromptDefinition {
+        name: "draft_nib_npwp",
+        title: "Surat Pernyataan untuk pengurusan NIB dan NPWP",
+        tool: surat_nib_npwp::TOOL_NAME,
+        fields: "nama lengkap, NIK, jabatan, bidang usaha, kegiatan usaha, jenis usaha, alamat usaha",
+        arguments: CITIZEN_ARGUMENTS,
+    },
+    PromptDefinition {
+        name: "draft_domisili",
+        title: "Surat Keterangan Domisili",
+        tool: surat_domisili::TOOL_NAME,
+        fields: "nama lengkap, NIK, tempat/tanggal lahir, jenis kelamin, agama, pekerjaan, alamat sesuai KTP, nomor telepon, alamat domisili, RT/RW, lama tinggal, keperluan",
+        arguments: CITIZEN_ARGUMENTS,
+    },
+    PromptDefinition {
+        name: "draft_sku",
+        title: "Surat Keterangan Usaha (SKU)",
+        tool: surat_usaha::TOOL_NAME,
+        fields: "nama lengkap pemilik, NIK, tempat/tanggal lahir, jenis kelamin, agama, pekerjaan, alamat sesuai KTP, nomor telepon, nama usaha, jenis usaha, alamat usaha, tahun mulai usaha, keperluan",
+        arguments: CITIZEN_ARGUMENTS,
+    },
+    PromptDefinition {
+        name: "draft_skck",
+        title: "Surat Pengantar SKCK",
+        tool: surat_skck::TOOL_NAME,
+        fields: "nama lengkap, NIK, tempat/tanggal lahir, jenis kelamin, agama, pekerjaan, alamat sesuai KTP, nomor telepon, keperluan SKCK, instansi tujuan, masa berlaku surat pengantar",
+        arguments: CITIZEN_ARGUMENTS,
+    },
+];
+
+impl PromptDefinition {
+    fn description(&self) -> String {
+        format!("Memandu pembuatan {}", self.title)
+    }
+}
+
+const TEMPLATE: &str = "Saya ingin membuat {title}.\n\
+Nama warga: {nama}\n\
+NIK: {nik}\n\n\
+Kumpulkan data berikut dari warga: {fields}. \
+Tanyakan data yang belum ada satu per satu dan jangan mengarang data. \
+Setelah semua data lengkap, panggil tool `{tool}`.";
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PromptArgument {
+    pub name: String,
+    pub description: String,
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PromptDescriptor {
+    pub name: String,
+    pub description: String,
+    pub arguments: Vec<PromptArgument>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PromptContent {
+    #[serde(rename = "type")]
+    pub content_type: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PromptMessage {
+    pub role: String,
+    pub content: PromptContent,
+}
+
+/// Result of `prompts/get`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GetPromptResult {
+    pub description: String,
+    pub messages: Vec<PromptMessage>,
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum PromptError {
+    #[error("Prompt '{0}' tidak tersedia.")]
+    NotFound(String),
+    #[error("Argumen '{0}' wajib diisi.")]
+    MissingArgument(String),
+}
+
+/// Static registry of the letter-drafting prompts.
+#[derive(Debug, Clone, Default)]
+pub struct PromptRegistry;
+
+impl PromptRegistry {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Descriptors for prompts/list
+    pub fn list_prompts(&self) -> Vec<PromptDescriptor> {
+        PROMPTS
+            .iter()
+            .map(|prompt| PromptDescriptor {
+                name: prompt.name.to_string(),
+                description: prompt.description(),
+                arguments: prompt
+                    .arguments
+                    .iter()
+                    .map(|argument| PromptArgument {
+                        name: argument.name.to_string(),
+                        description: argument.description.to_string(),
+                        required: argument.required,
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Render a prompt's messages with `arguments` substituted
+    pub fn get_prompt(
+        &self,
+        name: &str,
+        arguments: &HashMap<String, String>,
+    ) -> Result<GetPromptResult, PromptError> {
+        let prompt = PROMPTS
+            .iter()
+            .find(|prompt| prompt.name == name)
+            .ok_or_else(|| PromptError::NotFound(name.to_string()))?;
+
+        let mut text = TEMPLATE
+            .replace("{title}", prompt.title)
+            .replace("{fields}", prompt.fields)
+            .replace("{tool}", prompt.tool);
+        for argument in prompt.arguments {
+            let value = arguments
+                .get(argument.name)
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty());
+            let value = match value {
+                Some(value) => value,
+                None if argument.required => {
+                    return Err(PromptError::MissingArgument(argument.name.to_string()))
+                }
+                None => MISSING_ARGUMENT,
+            };
+            text = text.replace(&format!("{{{}}}", argument.name), value);
+        }
+
+        Ok(GetPromptResult {
+            description: prompt.description(),
+            messages: vec![PromptMessage {
+                role: "user".to_string(),
+                content: PromptContent {
+                    content_type: "text".to_string(),
+                    text,
+                },
+            }],
+        })
+    }
+}
@@ -0,0 +1,159 @@
+//! MCP resources.
+//!
+//! Every posting is exposed as `posting://{id}` and read back as JSON.
+//! Documents stored with `delivery: "url"` are exposed as
+//! `document://{date}/{filename}` and read back as base64 PDF bytes.
+//! Postings come from the same cache as the REST endpoints; documents are
+//! listed straight from storage so nothing here needs the database.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::db::AppState;
+use crate::mcp::tools::delivery::GENERATED_PREFIX;
+
+pub const POSTING_SCHEME: &str = "posting://";
+pub const DOCUMENT_SCHEME: &str = "document://";
+
+/// Resources returned per `resources/list` page
+pub const RESOURCES_PAGE_SIZE: usize = 50;
+
+/// Only the newest date folders under `generated/` are listed
+const RECENT_DOCUMENT_DAYS: usize = 7;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ResourceDescriptor {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "mimeType")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+/// One entry of a `resources/read` result, either `text` or `blob` is set
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ResourceContents {
+    pub uri: String,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob: Option<String>,
+}
+
+/// All resources, postings first (latest first) then recent documents
+pub async fn list_resources(app_state: &AppState) -> Result<Vec<ResourceDescriptor>, String> {
+    let posts = app_state
+        .get_all_posts_cached()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut resources: Vec<ResourceDescriptor> = posts
+        .into_iter()
+        .map(|post| ResourceDescriptor {
+            uri: format!("{}{}", POSTING_SCHEME, post.id),
+            name: Some(post.title),
+            description: Some(post.category),
+            mime_type: Some("application/json".to_string()),
+        })
+        .collect();
+
+    resources.extend(list_documents(app_state).await?);
+    Ok(resources)
+}
+
+async fn list_documents(app_state: &AppState) -> Result<Vec<ResourceDescriptor>, String> {
+    let mut dates: Vec<String> = app_state
+        .storage
+        .list_folder_contents(GENERATED_PREFIX)
+        .await?
+        .into_iter()
+        .filter(|entry| !entry.is_file)
+        .map(|entry| entry.name)
+        .collect();
+    dates.sort_unstable_by(|a, b| b.cmp(a));
+    dates.truncate(RECENT_DOCUMENT_DAYS);
+
+    let mut documents = Vec::new();
+    for date in dates {
+        let folder = format!("{}/{}", GENERATED_PREFIX, date);
+        for entry in app_state.storage.list_folder_contents(&folder).await? {
+            if !entry.is_file {
+                continue;
+            }
+            documents.push(ResourceDescriptor {
+                uri: format!("{}{}/{}", DOCUMENT_SCHEME, date, entry.name),
+                description: Some(format!("Surat yang dibuat pada {}", date)),
+                name: Some(entry.name),
+                mime_type: Some("application/pdf".to_string()),
+            });
+        }
+    }
+    Ok(documents)
+}
+
+/// Read a resource by URI; `Ok(None)` when it does not exist
+pub async fn read_resource(
+    app_state: &AppState,
+    uri: &str,
+) -> Result<Option<ResourceContents>, String> {
+    if let Some(id) = uri.strip_prefix(POSTING_SCHEME) {
+        let Ok(id) = Uuid::parse_str(id) else {
+            return Ok(None);
+        };
+        let posts = app_state
+            .get_all_posts_cached()
+            .await
+            .map_err(|e| e.to_string())?;
+        let Some(post) = posts.into_iter().find(|post| post.id == id) else {
+            return Ok(None);
+        };
+
+        return Ok(Some(ResourceContents {
+            uri: uri.to_string(),
+            mime_type: "application/json".to_string(),
+            text: Some(serde_json::to_string(&post).map_err(|e| e.to_string())?),
+            blob: None,
+        }));
+    }
+
+    if let Some(path) = uri.strip_prefix(DOCUMENT_SCHEME) {
+        if path.is_empty() || path.split('/').any(|part| part.is_empty() || part == "..") {
+            return Ok(None);
+        }
+        let key = format!("{}/{}", GENERATED_PREFIX, path);
+        // Storage backends do not report "not found" separately
+        let pdf = match app_state.storage.download_file(&key).await {
+            Ok(pdf) => pdf,
+            Err(e) => {
+                log::warn!("Failed to read generated document {}: {}", key, e);
+                return Ok(None);
+            }
+        };
+
+        return Ok(Some(ResourceContents {
+            uri: uri.to_string(),
+            mime_type: "application/pdf".to_string(),
+            text: None,
+            blob: Some(BASE64.encode(pdf)),
+        }));
+    }
+
+    Ok(None)
+}
+
+/// Offset encoded in a `nextCursor`
+pub fn parse_cursor(cursor: Option<&str>) -> Result<usize, String> {
+    match cursor {
+        None => Ok(0),
+        Some(cursor) => cursor
+            .parse()
+            .map_err(|_| format!("Cursor '{}' tidak valid.", cursor)),
+    }
+}
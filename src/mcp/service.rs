@@ -1,36 +1,80 @@
 //! MCP Service - Core JSON-RPC 2.0 request handler.
 
 use crate::db::AppState;
+use crate::mcp::logging::{LoggingLevel, SessionLogLevel, ToolLogger};
+use crate::mcp::progress::{self, ProgressReporter};
+use crate::mcp::prompts::{PromptError, PromptRegistry};
+use crate::mcp::resources::{self, ResourceContents, ResourceDescriptor, RESOURCES_PAGE_SIZE};
 use crate::mcp::rpc::{OutboundResponse, RpcRequest};
+use crate::mcp::tools::registry::ToolContext;
 use crate::mcp::tools::ToolRegistry;
 use actix_web::web;
 use log::{info, warn};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::sync::Arc;
-
-pub const PROTOCOL_VERSION: &str = "2024-11-05";
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// Latest protocol revision, offered when the client asks for one we do not know
+pub const PROTOCOL_VERSION: &str = "2025-03-26";
+
+/// Revisions the server can speak, newest first
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &[PROTOCOL_VERSION, "2024-11-05"];
+
+/// Per-request hooks supplied by the transport
+#[derive(Debug, Clone, Default)]
+pub struct RequestContext {
+    /// Stream for notifications the request produces (tool call progress)
+    pub notifier: Option<mpsc::Sender<String>>,
+    /// Tripped by `notifications/cancelled`; the response is then dropped
+    pub cancellation: CancellationToken,
+    /// Log level of the caller's session, `None` outside a session
+    pub log_level: Option<Arc<SessionLogLevel>>,
+    /// Credentials or session the request came from, for tool quotas
+    pub caller: Option<String>,
+}
 
 /// Core MCP request handler.
 #[derive(Clone)]
 pub struct McpService {
     registry: Arc<ToolRegistry>,
+    prompts: Arc<PromptRegistry>,
 }
 
 impl McpService {
     pub fn new(registry: ToolRegistry) -> Self {
         Self {
             registry: Arc::new(registry),
+            prompts: Arc::new(PromptRegistry::new()),
         }
     }
 
+    pub fn registry(&self) -> &Arc<ToolRegistry> {
+        &self.registry
+    }
+
     /// Handle incoming JSON-RPC request.
     /// AppState is passed for async tools that need database access.
     pub async fn handle_request(
         &self,
         request: RpcRequest,
         app_state: &web::Data<AppState>,
+    ) -> Option<OutboundResponse> {
+        self.handle_request_with(request, app_state, RequestContext::default())
+            .await
+    }
+
+    /// Handle a request within `context`. Notifications it produces go to
+    /// `context.notifier` ahead of the response; a cancelled request gets
+    /// no response at all.
+    pub async fn handle_request_with(
+        &self,
+        request: RpcRequest,
+        app_state: &web::Data<AppState>,
+        context: RequestContext,
     ) -> Option<OutboundResponse> {
         if request.jsonrpc != "2.0" {
             warn!("received unsupported jsonrpc version: {}", request.jsonrpc);
@@ -45,22 +89,29 @@ impl McpService {
             method, params, id, ..
         } = request;
 
-        match method.as_str() {
+        let response = match method.as_str() {
             "initialize" => Some(self.handle_initialize(id, params)),
             "tools/list" => Some(self.handle_list_tools(id)),
-            "tools/call" => Some(self.handle_call_tool(id, params, app_state).await),
-            "resources/list" => Some(self.handle_resources_list(id)),
-            "resources/read" => Some(self.handle_resources_read(id, params)),
+            "tools/call" => Some(self.handle_call_tool(id, params, app_state, &context).await),
+            "resources/list" => Some(self.handle_resources_list(id, params, app_state).await),
+            "resources/read" => Some(self.handle_resources_read(id, params, app_state).await),
             "resources/templates/list" => Some(self.handle_resource_templates_list(id)),
             "prompts/list" => Some(self.handle_prompts_list(id)),
             "prompts/get" => Some(self.handle_prompts_get(id, params)),
+            "logging/setLevel" => Some(self.handle_set_level(id, params, &context)),
             "ping" => Some(OutboundResponse::success(id, json!({ "ok": true }))),
             method if method.starts_with("notifications/") => {
                 info!("received client notification: {}", method);
                 None
             }
             other => Some(OutboundResponse::method_not_found(id, other)),
+        };
+
+        if context.cancellation.is_cancelled() {
+            info!("dropping response to cancelled {} request", method);
+            return None;
         }
+        response
     }
 
     fn handle_initialize(&self, id: Option<Value>, params: Option<Value>) -> OutboundResponse {
@@ -79,8 +130,14 @@ impl McpService {
                 .unwrap_or_else(|| "unknown".into())
         );
 
+        let protocol_version = SUPPORTED_PROTOCOL_VERSIONS
+            .iter()
+            .find(|version| **version == parsed.protocol_version)
+            .copied()
+            .unwrap_or(PROTOCOL_VERSION);
+
         let result = InitializeResult {
-            protocol_version: PROTOCOL_VERSION.to_string(),
+            protocol_version: protocol_version.to_string(),
             server_info: ImplementationInfo {
                 name: env!("CARGO_PKG_NAME").to_string(),
                 version: env!("CARGO_PKG_VERSION").to_string(),
@@ -88,8 +145,16 @@ impl McpService {
             },
             capabilities: ServerCapabilities {
                 tools: ToolsCapability {
+                    list_changed: true,
+                },
+                resources: ResourcesCapability {
+                    subscribe: false,
+                    list_changed: false,
+                },
+                prompts: PromptsCapability {
                     list_changed: false,
                 },
+                logging: LoggingCapability {},
             },
         };
 
@@ -112,36 +177,129 @@ impl McpService {
         id: Option<Value>,
         params: Option<Value>,
         app_state: &web::Data<AppState>,
+        context: &RequestContext,
     ) -> OutboundResponse {
+        let progress = context
+            .notifier
+            .clone()
+            .zip(progress::progress_token(params.as_ref()))
+            .map(|(sender, token)| ProgressReporter::new(token, sender));
         let parsed: CallToolParams = match parse_params(params) {
             Ok(value) => value,
             Err(message) => return OutboundResponse::invalid_params(id, message),
         };
+        let logger = context
+            .notifier
+            .clone()
+            .zip(context.log_level.as_ref().and_then(|level| level.get()))
+            .map(|(sender, level)| ToolLogger::new(parsed.name.as_str(), level, sender));
 
         // Try async tool call first (for database tools), fall back to sync
         let result = self
             .registry
-            .call_tool_async(&parsed.name, parsed.arguments, app_state)
+            .call_tool_with_context(
+                &parsed.name,
+                parsed.arguments,
+                app_state,
+                &ToolContext {
+                    progress,
+                    logger,
+                    cancellation: context.cancellation.clone(),
+                    caller: context.caller.clone(),
+                },
+            )
             .await;
         OutboundResponse::success(id, serde_json::to_value(result).unwrap())
     }
 
-    fn handle_resources_list(&self, id: Option<Value>) -> OutboundResponse {
+    /// Handle logging/setLevel - applies to later tool calls of the session.
+    /// Outside a session there is no stream to log to, so it only succeeds.
+    fn handle_set_level(
+        &self,
+        id: Option<Value>,
+        params: Option<Value>,
+        context: &RequestContext,
+    ) -> OutboundResponse {
+        let parsed: SetLevelParams = match parse_params(params) {
+            Ok(value) => value,
+            Err(message) => return OutboundResponse::invalid_params(id, message),
+        };
+
+        if let Some(level) = &context.log_level {
+            level.set(parsed.level);
+        }
+        OutboundResponse::success(id, json!({}))
+    }
+
+    /// Handle resources/list - postings and recent generated documents,
+    /// paginated with an offset cursor.
+    async fn handle_resources_list(
+        &self,
+        id: Option<Value>,
+        params: Option<Value>,
+        app_state: &web::Data<AppState>,
+    ) -> OutboundResponse {
+        let parsed: ListResourcesParams = match params {
+            None => ListResourcesParams::default(),
+            Some(value) => match parse_params(Some(value)) {
+                Ok(value) => value,
+                Err(message) => return OutboundResponse::invalid_params(id, message),
+            },
+        };
+        let offset = match resources::parse_cursor(parsed.cursor.as_deref()) {
+            Ok(offset) => offset,
+            Err(message) => return OutboundResponse::invalid_params(id, message),
+        };
+
+        let all = match resources::list_resources(app_state).await {
+            Ok(all) => all,
+            Err(e) => {
+                warn!("failed to list resources: {}", e);
+                return OutboundResponse::error(id, -32000, "Gagal memuat daftar resource.");
+            }
+        };
+
+        let end = offset.saturating_add(RESOURCES_PAGE_SIZE);
+        let next_cursor = (end < all.len()).then(|| end.to_string());
         let payload = ListResourcesResult {
-            resources: Vec::new(),
-            next_cursor: None,
+            resources: all
+                .into_iter()
+                .skip(offset)
+                .take(RESOURCES_PAGE_SIZE)
+                .collect(),
+            next_cursor,
         };
         OutboundResponse::success(id, serde_json::to_value(payload).unwrap())
     }
 
-    fn handle_resources_read(&self, id: Option<Value>, params: Option<Value>) -> OutboundResponse {
+    async fn handle_resources_read(
+        &self,
+        id: Option<Value>,
+        params: Option<Value>,
+        app_state: &web::Data<AppState>,
+    ) -> OutboundResponse {
         let parsed: ResourceReadParams = match parse_params(params) {
             Ok(value) => value,
             Err(message) => return OutboundResponse::invalid_params(id, message),
         };
 
-        let message = format!("Resource '{}' tidak ditemukan.", parsed.uri);
-        OutboundResponse::error(id, -32000, message)
+        match resources::read_resource(app_state, &parsed.uri).await {
+            Ok(Some(contents)) => {
+                let payload = ReadResourceResult {
+                    contents: vec![contents],
+                };
+                OutboundResponse::success(id, serde_json::to_value(payload).unwrap())
+            }
+            Ok(None) => {
+                let message = format!("Resource '{}' tidak ditemukan.", parsed.uri);
+                OutboundResponse::error(id, -32000, message)
+            }
+            Err(e) => {
+                warn!("failed to read resource {}: {}", parsed.uri, e);
+                let message = format!("Gagal membaca resource '{}'.", parsed.uri);
+                OutboundResponse::error(id, -32000, message)
+            }
+        }
     }
 
     fn handle_resource_templates_list(&self, id: Option<Value>) -> OutboundResponse {
@@ -154,7 +312,7 @@ impl McpService {
 
     fn handle_prompts_list(&self, id: Option<Value>) -> OutboundResponse {
         let payload = PromptListResult {
-            prompts: Vec::new(),
+            prompts: self.prompts.list_prompts(),
             next_cursor: None,
         };
         OutboundResponse::success(id, serde_json::to_value(payload).unwrap())
@@ -166,8 +324,15 @@ impl McpService {
             Err(message) => return OutboundResponse::invalid_params(id, message),
         };
 
-        let message = format!("Prompt '{}' tidak tersedia.", parsed.name);
-        OutboundResponse::error(id, -32001, message)
+        match self.prompts.get_prompt(&parsed.name, &parsed.arguments) {
+            Ok(result) => OutboundResponse::success(id, serde_json::to_value(result).unwrap()),
+            Err(err @ PromptError::NotFound(_)) => {
+                OutboundResponse::error(id, -32001, err.to_string())
+            }
+            Err(err @ PromptError::MissingArgument(_)) => {
+                OutboundResponse::invalid_params(id, err.to_string())
+            }
+        }
     }
 }
 
@@ -176,7 +341,6 @@ impl McpService {
 // ============================================================================
 
 #[derive(Debug, Deserialize)]
-#[allow(dead_code)]
 struct InitializeParams {
     #[serde(rename = "protocolVersion")]
     protocol_version: String,
@@ -211,6 +375,9 @@ struct ImplementationInfo {
 #[derive(Debug, Serialize)]
 struct ServerCapabilities {
     tools: ToolsCapability,
+    resources: ResourcesCapability,
+    prompts: PromptsCapability,
+    logging: LoggingCapability,
 }
 
 #[derive(Debug, Serialize)]
@@ -219,6 +386,27 @@ struct ToolsCapability {
     list_changed: bool,
 }
 
+#[derive(Debug, Serialize)]
+struct ResourcesCapability {
+    subscribe: bool,
+    #[serde(rename = "listChanged")]
+    list_changed: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct PromptsCapability {
+    #[serde(rename = "listChanged")]
+    list_changed: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct LoggingCapability {}
+
+#[derive(Debug, Deserialize)]
+struct SetLevelParams {
+    level: LoggingLevel,
+}
+
 #[derive(Debug, Serialize)]
 struct ListToolsResult {
     tools: Vec<crate::mcp::tools::registry::ToolDescriptor>,
@@ -242,16 +430,10 @@ struct ListResourcesResult {
     next_cursor: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-struct ResourceDescriptor {
-    uri: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    description: Option<String>,
-    #[serde(rename = "mimeType")]
-    #[serde(skip_serializing_if = "Option::is_none")]
-    mime_type: Option<String>,
+#[derive(Debug, Default, Deserialize)]
+struct ListResourcesParams {
+    #[serde(default)]
+    cursor: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -259,6 +441,11 @@ struct ResourceReadParams {
     uri: String,
 }
 
+#[derive(Debug, Serialize)]
+struct ReadResourceResult {
+    contents: Vec<ResourceContents>,
+}
+
 #[derive(Debug, Serialize)]
 struct ResourceTemplateListResult {
     templates: Vec<Value>,
@@ -269,23 +456,17 @@ struct ResourceTemplateListResult {
 
 #[derive(Debug, Serialize)]
 struct PromptListResult {
-    prompts: Vec<PromptDescriptor>,
+    prompts: Vec<crate::mcp::prompts::PromptDescriptor>,
     #[serde(rename = "nextCursor")]
     #[serde(skip_serializing_if = "Option::is_none")]
     next_cursor: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-struct PromptDescriptor {
-    name: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    description: Option<String>,
-    arguments: Vec<Value>,
-}
-
 #[derive(Debug, Deserialize)]
 struct PromptGetParams {
     name: String,
+    #[serde(default)]
+    arguments: HashMap<String, String>,
 }
 
 fn parse_params<T: DeserializeOwned>(params: Option<Value>) -> Result<T, String> {
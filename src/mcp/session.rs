@@ -0,0 +1,302 @@
+//! SSE sessions for MCP clients.
+//!
+//! `GET /sse` opens a session and streams everything sent to it; POSTs that
+//! carry `?session={id}` have their response delivered to that stream only,
+//! so concurrent clients never see each other's messages. Sessions are
+//! removed when their stream is dropped, or once idle for longer than the
+//! TTL (checked whenever a session is opened or written to).
+//!
+//! Streamable HTTP sessions are created at `initialize` before any stream
+//! exists and get one when the client opens `GET /mcp`; opening it again
+//! replaces the previous stream.
+//!
+//! Open streams carry a keepalive every `keepalive_interval` so load
+//! balancers do not drop them as idle. Keepalives do not count as activity:
+//! each one also sweeps idle sessions, and a stream ends as soon as its
+//! session is gone.
+
+use dashmap::DashMap;
+use futures::stream::{self, Stream, StreamExt};
+use std::future::ready;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::time::MissedTickBehavior;
+use tokio_stream::wrappers::{IntervalStream, ReceiverStream};
+use uuid::Uuid;
+
+use super::logging::SessionLogLevel;
+
+/// Idle time after which a session is dropped
+pub const DEFAULT_SESSION_IDLE_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// Time between keepalives on an open stream, below the idle cutoff of
+/// Cloud Run's load balancer
+pub const DEFAULT_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(25);
+
+/// Messages buffered per session before senders wait
+const SESSION_BUFFER: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SessionError {
+    #[error("Session '{0}' tidak ditemukan.")]
+    NotFound(String),
+    #[error("Session '{0}' sudah ditutup.")]
+    Closed(String),
+    #[error("Session '{0}' belum memiliki stream.")]
+    NoStream(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionConfig {
+    /// Time between keepalives on an open stream
+    pub keepalive_interval: Duration,
+    /// Time without requests after which a session is closed
+    pub idle_timeout: Duration,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            keepalive_interval: DEFAULT_KEEPALIVE_INTERVAL,
+            idle_timeout: DEFAULT_SESSION_IDLE_TTL,
+        }
+    }
+}
+
+impl SessionConfig {
+    /// Load using a custom variable lookup
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let get = |key: &str| {
+            lookup(key)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let defaults = Self::default();
+
+        let keepalive_interval = get("MCP_SSE_KEEPALIVE_SECS")
+            .map(|v| crate::config::parse_positive("MCP_SSE_KEEPALIVE_SECS", &v))
+            .transpose()?
+            .map(|secs| Duration::from_secs(secs as u64))
+            .unwrap_or(defaults.keepalive_interval);
+        let idle_timeout = get("MCP_SESSION_IDLE_TIMEOUT_MINS")
+            .map(|v| crate::config::parse_positive("MCP_SESSION_IDLE_TIMEOUT_MINS", &v))
+            .transpose()?
+            .map(|mins| Duration::from_secs(mins as u64 * 60))
+            .unwrap_or(defaults.idle_timeout);
+
+        Ok(Self {
+            keepalive_interval,
+            idle_timeout,
+        })
+    }
+}
+
+/// What an open session stream carries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamEvent {
+    Message(String),
+    Keepalive,
+}
+
+struct Session {
+    /// `None` until a stream is attached
+    sender: Option<mpsc::Sender<String>>,
+    last_active: Instant,
+    /// Set by `logging/setLevel`
+    log_level: Arc<SessionLogLevel>,
+}
+
+/// Open SSE sessions keyed by session id.
+pub struct SessionManager {
+    sessions: DashMap<String, Session>,
+    idle_ttl: Duration,
+}
+
+impl Default for SessionManager {
+    fn default() -> Self {
+        Self::new(DEFAULT_SESSION_IDLE_TTL)
+    }
+}
+
+impl SessionManager {
+    pub fn new(idle_ttl: Duration) -> Self {
+        Self {
+            sessions: DashMap::new(),
+            idle_ttl,
+        }
+    }
+
+    /// Register a new session and return its id with the receiving end
+    pub fn open(&self) -> (String, mpsc::Receiver<String>) {
+        let id = self.create();
+        let receiver = self.attach_stream(&id).expect("session was just created");
+        (id, receiver)
+    }
+
+    /// Register a new session without a stream
+    pub fn create(&self) -> String {
+        self.remove_idle();
+
+        let id = Uuid::new_v4().simple().to_string();
+        self.sessions.insert(
+            id.clone(),
+            Session {
+                sender: None,
+                last_active: Instant::now(),
+                log_level: Arc::default(),
+            },
+        );
+        id
+    }
+
+    /// Give session `id` a new stream, ending the previous one if any
+    pub fn attach_stream(&self, id: &str) -> Result<mpsc::Receiver<String>, SessionError> {
+        let mut session = self
+            .sessions
+            .get_mut(id)
+            .ok_or_else(|| SessionError::NotFound(id.to_string()))?;
+        let (sender, receiver) = mpsc::channel(SESSION_BUFFER);
+        session.sender = Some(sender);
+        session.last_active = Instant::now();
+        Ok(receiver)
+    }
+
+    /// Mark a session as active, `false` when it does not exist
+    pub fn touch(&self, id: &str) -> bool {
+        self.remove_idle();
+        match self.sessions.get_mut(id) {
+            Some(mut session) => {
+                session.last_active = Instant::now();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Deliver `message` to the stream of session `id`
+    pub async fn send(&self, id: &str, message: String) -> Result<(), SessionError> {
+        self.remove_idle();
+
+        // Clone the sender so no map guard is held across the await
+        let sender = {
+            let mut session = self
+                .sessions
+                .get_mut(id)
+                .ok_or_else(|| SessionError::NotFound(id.to_string()))?;
+            session.last_active = Instant::now();
+            session
+                .sender
+                .clone()
+                .ok_or_else(|| SessionError::NoStream(id.to_string()))?
+        };
+
+        if sender.send(message).await.is_err() {
+            self.close(id);
+            return Err(SessionError::Closed(id.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Sender for the stream of session `id`, for notifications that must
+    /// not wait on a full stream
+    pub fn stream(&self, id: &str) -> Option<mpsc::Sender<String>> {
+        self.sessions.get(id)?.sender.clone()
+    }
+
+    /// Queue `message` on every open stream, returning how many sessions
+    /// it reached. Never waits: a full or closed stream misses it.
+    pub fn broadcast(&self, message: &str) -> usize {
+        self.remove_idle();
+        let senders: Vec<mpsc::Sender<String>> = self
+            .sessions
+            .iter()
+            .filter_map(|session| session.sender.clone())
+            .collect();
+        senders
+            .into_iter()
+            .filter(|sender| sender.try_send(message.to_string()).is_ok())
+            .count()
+    }
+
+    /// Log level of session `id`, shared with the requests that change it
+    pub fn log_level(&self, id: &str) -> Option<Arc<SessionLogLevel>> {
+        Some(Arc::clone(&self.sessions.get(id)?.log_level))
+    }
+
+    /// Remove a session, `false` when it did not exist
+    pub fn close(&self, id: &str) -> bool {
+        let removed = self.sessions.remove(id).is_some();
+        if removed {
+            log::info!("MCP session {} closed", id);
+        }
+        removed
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.sessions.contains_key(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Drop sessions idle for longer than the TTL, returning how many
+    pub fn remove_idle(&self) -> usize {
+        let before = self.sessions.len();
+        self.sessions
+            .retain(|_, session| session.last_active.elapsed() < self.idle_ttl);
+        before.saturating_sub(self.sessions.len())
+    }
+}
+
+/// Messages for session `id` from `receiver`, with a keepalive every
+/// `interval`. Ends when the session is closed, idles out, or gets a new
+/// stream.
+pub fn keepalive_stream(
+    sessions: Arc<SessionManager>,
+    id: String,
+    receiver: mpsc::Receiver<String>,
+    interval: Duration,
+) -> impl Stream<Item = StreamEvent> {
+    let mut ticks = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    // `None` marks the end of the stream
+    let messages = ReceiverStream::new(receiver)
+        .map(|message| Some(StreamEvent::Message(message)))
+        .chain(stream::once(ready(None)));
+    let keepalives = IntervalStream::new(ticks).map(move |_| {
+        sessions.remove_idle();
+        sessions.contains(&id).then_some(StreamEvent::Keepalive)
+    });
+
+    stream::select(messages, keepalives)
+        .take_while(|event| ready(event.is_some()))
+        .filter_map(ready)
+}
+
+/// Closes its session when dropped together with the SSE stream
+pub struct SessionGuard {
+    sessions: Arc<SessionManager>,
+    id: String,
+}
+
+impl SessionGuard {
+    pub fn new(sessions: Arc<SessionManager>, id: String) -> Self {
+        Self { sessions, id }
+    }
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        self.sessions.close(&self.id);
+    }
+}
@@ -0,0 +1,230 @@
+//! Streamable HTTP transport (MCP 2025-03-26) on `/mcp`.
+//!
+//! - `POST` dispatches one JSON-RPC message. The reply is JSON, or a
+//!   single-event SSE stream when the client only accepts
+//!   `text/event-stream`. A successful `initialize` opens a session and
+//!   returns its id in the `Mcp-Session-Id` header. A `tools/call` asking
+//!   for progress streams its `notifications/progress` ahead of the result
+//!   in that SSE reply, or on the session stream for JSON replies.
+//! - `GET` opens the server-push stream of a session.
+//! - `DELETE` ends a session.
+//!
+//! Requests without the header are still served statelessly, as before.
+
+use actix_web::http::header::ACCEPT;
+use actix_web::{web, HttpRequest, HttpResponse};
+use futures::stream::StreamExt;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+use super::handlers::{session_error, sse_event, McpState};
+use super::progress;
+use super::rpc::{OutboundResponse, RpcRequest};
+use super::session::{self, SessionError};
+
+pub const MCP_SESSION_HEADER: &str = "Mcp-Session-Id";
+
+/// Progress notifications buffered for an SSE reply
+const PROGRESS_BUFFER: usize = 16;
+
+/// POST /mcp
+#[utoipa::path(
+    operation_id = "postMcpMessage",
+    post,
+    path = "/mcp",
+    tag = "MCP",
+    params(("Mcp-Session-Id" = Option<String>, Header, description = "Session opened by `initialize`")),
+    request_body(content = Object, description = "JSON-RPC 2.0 request or notification"),
+    security(("bearer_auth" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "JSON-RPC response, as JSON or a single-event SSE stream", body = Object),
+        (status = 202, description = "Notification accepted"),
+        (status = 401, description = "Missing credentials, when MCP_AUTH_HTTP_401 is set"),
+        (status = 404, description = "Unknown session")
+    )
+)]
+pub async fn post_handler(
+    req: HttpRequest,
+    state: web::Data<Arc<McpState>>,
+    body: web::Json<RpcRequest>,
+) -> HttpResponse {
+    let request = body.into_inner();
+    log::info!("Received MCP request: {}", request.method);
+    let is_initialize = request.method == "initialize";
+
+    let session = session_id(&req);
+    if let Some(id) = &session {
+        if !state.sessions.touch(id) {
+            return session_error(SessionError::NotFound(id.clone()));
+        }
+    }
+
+    if wants_event_stream(&req)
+        && progress::progress_token(request.params.as_ref()).is_some()
+        && state.is_permitted(&req, &request.method)
+    {
+        let caller = state.caller(&req, session.as_deref());
+        return progress_stream(Arc::clone(&state), request, session, caller);
+    }
+
+    let response = match state.dispatch(&req, request, session.as_deref()).await {
+        Ok(Some(response)) => response,
+        // Notifications and client responses
+        Ok(None) => return HttpResponse::Accepted().finish(),
+        Err(rejection) => return rejection,
+    };
+
+    let session = match session {
+        None if is_initialize && response.error.is_none() => {
+            let id = state.sessions.create();
+            log::info!("MCP session {} opened", id);
+            Some(id)
+        }
+        session => session,
+    };
+
+    let mut builder = HttpResponse::Ok();
+    if let Some(id) = &session {
+        builder.insert_header((MCP_SESSION_HEADER, id.as_str()));
+    }
+
+    if wants_event_stream(&req) {
+        let event = web::Bytes::from(format!(
+            "event: message\ndata: {}\n\n",
+            serde_json::to_string(&response).unwrap()
+        ));
+        builder
+            .content_type("text/event-stream")
+            .insert_header(("Cache-Control", "no-cache"))
+            .streaming(futures::stream::once(async move {
+                Ok::<_, actix_web::Error>(event)
+            }))
+    } else {
+        builder.json(response)
+    }
+}
+
+/// SSE reply that carries progress while the request runs, then its response
+fn progress_stream(
+    state: Arc<McpState>,
+    request: RpcRequest,
+    session: Option<String>,
+    caller: Option<String>,
+) -> HttpResponse {
+    let (sender, receiver) = mpsc::channel(PROGRESS_BUFFER);
+    let notifier = sender.clone();
+    let scope = session.clone();
+    actix_web::rt::spawn(async move {
+        let response = state
+            .run(request, scope.as_deref(), caller, Some(notifier))
+            .await;
+        if let Some(response) = response {
+            let _ = sender.send(serde_json::to_string(&response).unwrap()).await;
+        }
+    });
+
+    let mut builder = HttpResponse::Ok();
+    if let Some(id) = &session {
+        builder.insert_header((MCP_SESSION_HEADER, id.as_str()));
+    }
+    builder
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(ReceiverStream::new(receiver).map(message_event))
+}
+
+/// GET /mcp
+#[utoipa::path(
+    operation_id = "openMcpStream",
+    get,
+    path = "/mcp",
+    tag = "MCP",
+    params(("Mcp-Session-Id" = String, Header, description = "Session opened by `initialize`")),
+    responses(
+        (status = 200, description = "Server-push SSE stream of the session", content_type = "text/event-stream"),
+        (status = 400, description = "Missing Mcp-Session-Id header"),
+        (status = 404, description = "Unknown session")
+    )
+)]
+pub async fn get_handler(req: HttpRequest, state: web::Data<Arc<McpState>>) -> HttpResponse {
+    let Some(id) = session_id(&req) else {
+        return missing_session();
+    };
+    let receiver = match state.sessions.attach_stream(&id) {
+        Ok(receiver) => receiver,
+        Err(e) => return session_error(e),
+    };
+
+    let stream = session::keepalive_stream(
+        Arc::clone(&state.sessions),
+        id.clone(),
+        receiver,
+        state.keepalive_interval,
+    )
+    .map(sse_event);
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .insert_header((MCP_SESSION_HEADER, id.as_str()))
+        .streaming(stream)
+}
+
+/// DELETE /mcp
+#[utoipa::path(
+    operation_id = "closeMcpSession",
+    delete,
+    path = "/mcp",
+    tag = "MCP",
+    params(("Mcp-Session-Id" = String, Header, description = "Session to end")),
+    responses(
+        (status = 204, description = "Session closed"),
+        (status = 400, description = "Missing Mcp-Session-Id header"),
+        (status = 404, description = "Unknown session")
+    )
+)]
+pub async fn delete_handler(req: HttpRequest, state: web::Data<Arc<McpState>>) -> HttpResponse {
+    let Some(id) = session_id(&req) else {
+        return missing_session();
+    };
+    if state.sessions.close(&id) {
+        HttpResponse::NoContent().finish()
+    } else {
+        session_error(SessionError::NotFound(id))
+    }
+}
+
+fn session_id(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get(MCP_SESSION_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// SSE only when the client does not accept JSON
+fn wants_event_stream(req: &HttpRequest) -> bool {
+    let accept = req
+        .headers()
+        .get(ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    accept.contains("text/event-stream") && !accept.contains("application/json")
+}
+
+fn message_event(message: String) -> Result<web::Bytes, actix_web::Error> {
+    Ok(web::Bytes::from(format!(
+        "event: message\ndata: {}\n\n",
+        message
+    )))
+}
+
+fn missing_session() -> HttpResponse {
+    HttpResponse::BadRequest().json(OutboundResponse::error(
+        None,
+        -32600,
+        format!("Header {} wajib diisi.", MCP_SESSION_HEADER),
+    ))
+}
@@ -0,0 +1,99 @@
+//! Admin endpoints for the Typst templates of the document tools.
+//!
+//! Replacing a template takes effect on the next call without a redeploy;
+//! the upload is rendered with sample data first and rejected if it fails.
+
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use std::sync::Arc;
+
+use super::generators::{GeneratorError, TemplateInfo};
+use super::McpState;
+use crate::auth::validate_request_token;
+use crate::error::ApiError;
+
+/// Template files with their size and content hash (protected)
+#[utoipa::path(
+    operation_id = "listTemplates",
+    get,
+    path = "/api/admin/templates",
+    tag = "Admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Template files", body = Vec<TemplateInfo>),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 500, description = "Template directory could not be read")
+    )
+)]
+pub async fn list_templates(req: HttpRequest, state: web::Data<Arc<McpState>>) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    let templates = Arc::clone(state.service.registry().templates());
+    match web::block(move || templates.list()).await {
+        Ok(Ok(templates)) => HttpResponse::Ok().json(templates),
+        Ok(Err(e)) => {
+            ApiError::Internal(format!("Failed to list templates: {}", e)).error_response()
+        }
+        Err(e) => ApiError::Internal(e.to_string()).error_response(),
+    }
+}
+
+/// Replace a template after a dry-run compile with sample data (protected)
+#[utoipa::path(
+    operation_id = "replaceTemplate",
+    put,
+    path = "/api/admin/templates/{name}",
+    tag = "Admin",
+    params(("name" = String, Path, description = "Template file name, e.g. skck.typ")),
+    request_body(content = String, content_type = "text/plain", description = "Typst source"),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Template replaced", body = TemplateInfo),
+        (status = 400, description = "Not UTF-8 or failed the dry-run compile"),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 404, description = "No document tool uses this template")
+    )
+)]
+pub async fn replace_template(
+    req: HttpRequest,
+    state: web::Data<Arc<McpState>>,
+    path: web::Path<String>,
+    body: web::Bytes,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    let name = path.into_inner();
+    let registry = state.service.registry();
+    if !registry.template_names().contains(&name.as_str()) {
+        return ApiError::NotFound(format!("Template '{}' not found", name)).error_response();
+    }
+    let contents = match String::from_utf8(body.to_vec()) {
+        Ok(contents) => contents,
+        Err(_) => {
+            return ApiError::BadRequest("Template must be UTF-8 text".to_string()).error_response()
+        }
+    };
+
+    match registry.replace_template(&name, contents).await {
+        Ok(info) => {
+            log::info!("Template {} replaced, sha256 {}", name, info.sha256);
+            HttpResponse::Ok().json(info)
+        }
+        Err(GeneratorError::Compile(e)) => {
+            ApiError::BadRequest(format!("Template failed the dry-run compile: {}", e))
+                .error_response()
+        }
+        Err(e) => ApiError::Internal(format!("Failed to replace template: {}", e)).error_response(),
+    }
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/admin/templates")
+            .service(web::resource("").route(web::get().to(list_templates)))
+            .service(web::resource("/{name}").route(web::put().to(replace_template))),
+    );
+}
@@ -0,0 +1,98 @@
+//! Admin endpoints for the set of tools MCP clients see.
+//!
+//! Clients cache `tools/list`; whenever the set changes here every open
+//! session gets `notifications/tools/list_changed` and fetches it again.
+
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use super::McpState;
+use crate::auth::validate_request_token;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetBrowseToolsRequest {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BrowseToolsResponse {
+    /// Whether the post browsing tools are offered
+    pub enabled: bool,
+    /// Whether the tool list changed and sessions were notified
+    pub changed: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ToolListRefreshResponse {
+    /// Open sessions the notification was queued on
+    pub notified: usize,
+}
+
+/// Tell open MCP sessions to reload the tool list (protected)
+#[utoipa::path(
+    operation_id = "refreshMcpTools",
+    post,
+    path = "/api/admin/mcp/tools/refresh",
+    tag = "Admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Notification sent", body = ToolListRefreshResponse),
+        (status = 401, response = crate::UnauthorizedResponse)
+    )
+)]
+pub async fn refresh_tools(req: HttpRequest, state: web::Data<Arc<McpState>>) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    HttpResponse::Ok().json(ToolListRefreshResponse {
+        notified: state.notify_tools_changed(),
+    })
+}
+
+/// Offer or withdraw the post browsing tools (protected)
+#[utoipa::path(
+    operation_id = "setMcpBrowseTools",
+    put,
+    path = "/api/admin/mcp/tools/browse",
+    tag = "Admin",
+    request_body = SetBrowseToolsRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Browse tools updated", body = BrowseToolsResponse),
+        (status = 401, response = crate::UnauthorizedResponse)
+    )
+)]
+pub async fn set_browse_tools(
+    req: HttpRequest,
+    state: web::Data<Arc<McpState>>,
+    body: web::Json<SetBrowseToolsRequest>,
+) -> impl Responder {
+    let claims = match validate_request_token(&req) {
+        Ok(claims) => claims,
+        Err(e) => return e.error_response(),
+    };
+
+    let changed = state.set_browse_tools_enabled(body.enabled);
+    if changed {
+        log::warn!(
+            "MCP browse tools {} by {}",
+            if body.enabled { "enabled" } else { "disabled" },
+            claims.username
+        );
+    }
+    HttpResponse::Ok().json(BrowseToolsResponse {
+        enabled: state.service.registry().browse_tools_enabled(),
+        changed,
+    })
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/admin/mcp/tools")
+            .service(web::resource("/refresh").route(web::post().to(refresh_tools)))
+            .service(web::resource("/browse").route(web::put().to(set_browse_tools))),
+    );
+}
@@ -0,0 +1,102 @@
+//! Loading the images a letter request refers to.
+//!
+//! `foto_asset_id` and `ttd_asset_id` name uploaded assets. Each is looked
+//! up, downloaded from storage and checked to be a PNG or JPEG before the
+//! letter is rendered, so a bad reference fails the call with a message
+//! naming the field instead of a Typst error.
+
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::registry::ToolDescriptor;
+use super::schema;
+use crate::asset::models::Asset;
+use crate::db::AppState;
+use crate::mcp::generators::{Attachment, AttachmentKind, Attachments, Lampiran};
+use crate::storage::ObjectStorage;
+
+/// Largest image accepted as an attachment
+pub const MAX_ATTACHMENT_BYTES: usize = 5 * 1024 * 1024;
+
+/// Extensions of the assets accepted as attachments
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg"];
+
+/// Add the attachment ids to a document tool's input schema
+pub fn with_attachment_options(mut descriptor: ToolDescriptor) -> ToolDescriptor {
+    let attachment_schema = schema::input_schema::<Lampiran>();
+    if let (Some(properties), Some(attachments)) = (
+        descriptor
+            .input_schema
+            .get_mut("properties")
+            .and_then(Value::as_object_mut),
+        attachment_schema["properties"].as_object(),
+    ) {
+        for (name, property) in attachments {
+            properties.insert(name.clone(), property.clone());
+        }
+    }
+    descriptor
+}
+
+/// Load every image `lampiran` refers to
+pub async fn load_attachments(
+    app_state: &AppState,
+    lampiran: &Lampiran,
+) -> Result<Attachments, String> {
+    let mut attachments = Attachments::default();
+    for (kind, id) in lampiran.ids() {
+        let asset = app_state.get_asset_by_id(&id).await.map_err(|e| {
+            log::error!("Failed to look up attachment asset {}: {}", id, e);
+            format!(
+                "{}: gagal memeriksa aset {}. Silakan coba lagi.",
+                kind.field(),
+                id
+            )
+        })?;
+        let attachment = load_attachment(app_state.storage.as_ref(), kind, id, asset).await?;
+        attachments.insert(attachment);
+    }
+    Ok(attachments)
+}
+
+/// Download `asset`, the asset stored under `id`, as the image for `kind`
+pub async fn load_attachment(
+    storage: &(dyn ObjectStorage + Send + Sync),
+    kind: AttachmentKind,
+    id: Uuid,
+    asset: Option<Asset>,
+) -> Result<Attachment, String> {
+    let field = kind.field();
+    let asset = asset.ok_or_else(|| format!("{}: aset {} tidak ditemukan.", field, id))?;
+
+    let is_image = asset
+        .filename
+        .rsplit_once('.')
+        .is_some_and(|(_, ext)| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()));
+    if !is_image {
+        return Err(format!(
+            "{}: aset {} ({}) bukan gambar. Gunakan foto PNG atau JPEG.",
+            field, id, asset.name
+        ));
+    }
+
+    let data = storage.download_file(&asset.filename).await.map_err(|e| {
+        log::error!("Failed to download attachment {}: {}", asset.filename, e);
+        format!("{}: gagal mengunduh aset {}.", field, id)
+    })?;
+    if data.len() > MAX_ATTACHMENT_BYTES {
+        return Err(format!(
+            "{}: ukuran aset {} melebihi {} MB.",
+            field,
+            id,
+            MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        ));
+    }
+
+    Attachment::new(kind, data).ok_or_else(|| {
+        format!(
+            "{}: isi aset {} bukan gambar PNG atau JPEG yang valid.",
+            field, id
+        )
+    })
+}
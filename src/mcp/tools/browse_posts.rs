@@ -6,10 +6,17 @@
 //! All tools use cache-first strategy - same cache as REST endpoints to avoid
 //! double database traffic.
 
+use chrono::NaiveDate;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::HashMap;
 
 use super::registry::ToolDescriptor;
+use super::schema;
+use crate::i18n::format_date_id;
+use crate::posting::models::DateRange;
+use crate::storage::ObjectStorage;
 
 // =============================================================================
 // Tool Names
@@ -18,6 +25,11 @@ use super::registry::ToolDescriptor;
 pub const LIST_POSTINGS_TOOL: &str = "list_postings";
 pub const GET_POSTING_DETAIL_TOOL: &str = "get_posting_detail";
 pub const LIST_CATEGORIES_TOOL: &str = "list_categories";
+pub const SEARCH_POSTINGS_TOOL: &str = "search_postings";
+pub const GET_POSTING_ASSETS_TOOL: &str = "get_posting_assets";
+
+/// Characters of context kept on each side of a match in a snippet
+const SNIPPET_CONTEXT_CHARS: usize = 40;
 
 // =============================================================================
 // Tool Descriptors
@@ -29,35 +41,46 @@ pub fn list_postings_descriptor() -> ToolDescriptor {
         description: concat!(
             "Melihat daftar postingan, berita, dan informasi terbaru di Kelurahan Cakung Barat. ",
             "Gunakan tool ini untuk mendapatkan update terkini mengenai kegiatan dan pengumuman kelurahan. ",
-            "Hasil bisa difilter berdasarkan kategori dan diurutkan berdasarkan tanggal. ",
+            "Hasil bisa difilter berdasarkan kategori, bulan, atau rentang tanggal postingan ",
+            "dan diurutkan berdasarkan tanggal. ",
             "Gunakan tool ini untuk: ",
             "(1) Melihat berita terbaru, ",
             "(2) Mencari informasi berdasarkan kategori tertentu, ",
-            "(3) Melihat daftar posting dengan pagination."
+            "(3) Melihat daftar posting dengan pagination, ",
+            "(4) Menyusun laporan kegiatan satu bulan (misalnya month \"2024-08\" untuk ",
+            "\"apa saja kegiatan bulan Agustus\") atau rentang tanggal dengan date_from/date_to. ",
+            "Periode yang dipakai dikembalikan di date_from dan date_to pada hasil."
         )
         .to_string(),
-        input_schema: json!({
+        input_schema: schema::input_schema::<ListPostingsRequest>(),
+        output_schema: Some(json!({
             "type": "object",
             "properties": {
-                "category": {
-                    "type": "string",
-                    "description": "Filter berdasarkan kategori (opsional). Gunakan list_categories untuk melihat kategori yang tersedia."
-                },
-                "sort_by": {
-                    "type": "string",
-                    "enum": ["latest", "oldest"],
-                    "description": "Urutan hasil (default: latest)"
+                "posts": {
+                    "type": "array",
+                    "items": post_list_item_schema()
                 },
-                "limit": {
+                "total": {
                     "type": "integer",
-                    "description": "Jumlah maksimal hasil (default: 10, max: 50)"
+                    "description": "Jumlah seluruh postingan yang cocok dengan filter"
                 },
-                "offset": {
-                    "type": "integer",
-                    "description": "Offset untuk pagination (default: 0)"
+                "limit": { "type": "integer" },
+                "offset": { "type": "integer" },
+                "has_more": {
+                    "type": "boolean",
+                    "description": "Masih ada postingan setelah halaman ini"
+                },
+                "date_from": {
+                    "type": ["string", "null"],
+                    "description": "Tanggal awal periode yang dipakai (YYYY-MM-DD), null jika tidak dibatasi"
+                },
+                "date_to": {
+                    "type": ["string", "null"],
+                    "description": "Tanggal akhir periode yang dipakai (YYYY-MM-DD), null jika tidak dibatasi"
                 }
-            }
-        }),
+            },
+            "required": ["posts", "total", "limit", "offset", "has_more", "date_from", "date_to"]
+        })),
     }
 }
 
@@ -70,16 +93,36 @@ pub fn get_posting_detail_descriptor() -> ToolDescriptor {
             "setelah menemukan ID posting dari list_postings."
         )
         .to_string(),
-        input_schema: json!({
+        input_schema: schema::input_schema::<GetPostingDetailRequest>(),
+        output_schema: Some(json!({
             "type": "object",
             "properties": {
-                "id": {
+                "id": { "type": "string" },
+                "title": { "type": "string" },
+                "category": { "type": "string" },
+                "date": {
                     "type": "string",
-                    "description": "ID postingan (format UUID)"
+                    "description": "Tanggal postingan (YYYY-MM-DD)"
+                },
+                "excerpt": { "type": "string" },
+                "created_at": { "type": ["string", "null"] },
+                "updated_at": { "type": ["string", "null"] },
+                "image_urls": {
+                    "type": "array",
+                    "items": { "type": "string" }
                 }
             },
-            "required": ["id"]
-        }),
+            "required": [
+                "id",
+                "title",
+                "category",
+                "date",
+                "excerpt",
+                "created_at",
+                "updated_at",
+                "image_urls"
+            ]
+        })),
     }
 }
 
@@ -96,22 +139,133 @@ pub fn list_categories_descriptor() -> ToolDescriptor {
             "type": "object",
             "properties": {}
         }),
+        output_schema: Some(json!({
+            "type": "object",
+            "properties": {
+                "categories": {
+                    "type": "array",
+                    "items": { "type": "string" }
+                },
+                "count": { "type": "integer" }
+            },
+            "required": ["categories", "count"]
+        })),
+    }
+}
+
+pub fn search_postings_descriptor() -> ToolDescriptor {
+    ToolDescriptor {
+        name: SEARCH_POSTINGS_TOOL.to_string(),
+        description: concat!(
+            "Mencari postingan, berita, dan pengumuman Kelurahan Cakung Barat berdasarkan kata kunci. ",
+            "Kata kunci dicocokkan dengan judul dan ringkasan postingan tanpa membedakan huruf besar/kecil. ",
+            "Gunakan tool ini untuk pertanyaan seperti \"cari pengumuman tentang posyandu\". ",
+            "Setiap hasil menyertakan potongan teks di sekitar kata kunci."
+        )
+        .to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Kata kunci pencarian (2-100 karakter)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Jumlah maksimal hasil (default: 10, max: 50)"
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "Offset untuk pagination (default: 0)"
+                }
+            },
+            "required": ["query"]
+        }),
+        output_schema: None,
     }
 }
 
+pub fn get_posting_assets_descriptor() -> ToolDescriptor {
+    ToolDescriptor {
+        name: GET_POSTING_ASSETS_TOOL.to_string(),
+        description: concat!(
+            "Melihat daftar foto dan file yang dilampirkan pada satu postingan berdasarkan ID. ",
+            "Setiap file menyertakan nama, tipe MIME, URL publik, dan ukuran. ",
+            "Gunakan tool ini untuk menampilkan foto kegiatan atau lampiran pengumuman ",
+            "setelah menemukan ID posting dari list_postings atau search_postings."
+        )
+        .to_string(),
+        input_schema: schema::input_schema::<GetPostingDetailRequest>(),
+        output_schema: None,
+    }
+}
+
+/// Schema of one [`PostListItem`]
+fn post_list_item_schema() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "title": { "type": "string" },
+            "category": { "type": "string" },
+            "date": { "type": "string" },
+            "excerpt": { "type": "string" },
+            "image_url": {
+                "type": ["string", "null"],
+                "description": "Gambar pertama dari folder postingan, jika ada"
+            },
+            "cover_url": {
+                "type": ["string", "null"],
+                "description": "Gambar sampul postingan: yang dipilih admin, atau gambar pertama di folder"
+            }
+        },
+        "required": ["id", "title", "category", "date", "excerpt", "image_url", "cover_url"]
+    })
+}
+
 // =============================================================================
 // Request/Response Types
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct ListPostingsRequest {
     #[serde(default)]
+    #[schemars(
+        description = "Filter berdasarkan kategori (opsional). Gunakan list_categories untuk melihat kategori yang tersedia."
+    )]
     pub category: Option<String>,
+    #[serde(default)]
+    #[schemars(
+        description = "Filter bulan postingan dengan format YYYY-MM, misalnya 2024-08 untuk Agustus 2024 (opsional). Tidak bisa digabung dengan date_from/date_to.",
+        extend("pattern" = "^[0-9]{4}-[0-9]{2}$")
+    )]
+    pub month: Option<String>,
+    #[serde(default)]
+    #[schemars(
+        description = "Tanggal postingan paling awal, YYYY-MM-DD (opsional, termasuk tanggal ini)",
+        extend("format" = "date")
+    )]
+    pub date_from: Option<String>,
+    #[serde(default)]
+    #[schemars(
+        description = "Tanggal postingan paling akhir, YYYY-MM-DD (opsional, termasuk tanggal ini)",
+        extend("format" = "date")
+    )]
+    pub date_to: Option<String>,
     #[serde(default = "default_sort_by")]
+    #[schemars(
+        description = "Urutan hasil (default: latest)",
+        extend("enum" = ["latest", "oldest"])
+    )]
     pub sort_by: String,
     #[serde(default = "default_limit")]
+    #[schemars(
+        description = "Jumlah maksimal hasil (default: 10, max: 50)",
+        range(min = 1, max = 50)
+    )]
     pub limit: i32,
     #[serde(default)]
+    #[schemars(description = "Offset untuk pagination (default: 0)", range(min = 0))]
     pub offset: i32,
 }
 
@@ -137,16 +291,159 @@ impl ListPostingsRequest {
         if self.sort_by != "latest" && self.sort_by != "oldest" {
             return Err("sort_by harus 'latest' atau 'oldest'".to_string());
         }
+        self.date_range()?;
         Ok(())
     }
 
+    /// Dates the listed posts must fall within: every day of `month`, or
+    /// `date_from` to `date_to`. A month together with either date is
+    /// rejected rather than guessing which one was meant.
+    pub fn date_range(&self) -> Result<DateRange, String> {
+        let date_from = self
+            .date_from
+            .as_deref()
+            .map(|d| parse_date(d, "date_from"));
+        let date_to = self.date_to.as_deref().map(|d| parse_date(d, "date_to"));
+
+        if let Some(month) = &self.month {
+            if date_from.is_some() || date_to.is_some() {
+                return Err(
+                    "Gunakan month atau date_from/date_to, tidak keduanya sekaligus".to_string(),
+                );
+            }
+            return parse_month(month);
+        }
+
+        let range = DateRange {
+            from: date_from.transpose()?,
+            to: date_to.transpose()?,
+        };
+        if let (Some(from), Some(to)) = (range.from, range.to) {
+            if from > to {
+                return Err(format!(
+                    "date_from ({}) tidak boleh setelah date_to ({})",
+                    from, to
+                ));
+            }
+        }
+        Ok(range)
+    }
+
     pub fn is_sort_latest(&self) -> bool {
         self.sort_by == "latest"
     }
 }
 
+/// All days of a `YYYY-MM` month
+pub fn parse_month(value: &str) -> Result<DateRange, String> {
+    let invalid = || {
+        format!(
+            "month '{}' tidak valid, gunakan format YYYY-MM (misalnya 2024-08)",
+            value
+        )
+    };
+    let (year, month) = value.trim().split_once('-').ok_or_else(invalid)?;
+    let digits =
+        |part: &str, len: usize| part.len() == len && part.chars().all(|c| c.is_ascii_digit());
+    if !digits(year, 4) || !digits(month, 2) {
+        return Err(invalid());
+    }
+    DateRange::month(
+        year.parse().map_err(|_| invalid())?,
+        month.parse().map_err(|_| invalid())?,
+    )
+    .ok_or_else(invalid)
+}
+
+fn parse_date(value: &str, field: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|_| {
+        format!(
+            "{} '{}' tidak valid, gunakan format YYYY-MM-DD",
+            field, value
+        )
+    })
+}
+
+/// `dates` written out in Indonesian for the tool summary, None when
+/// neither end is set
+pub fn describe_period(dates: DateRange) -> Option<String> {
+    match (dates.from, dates.to) {
+        (Some(from), Some(to)) => Some(format!(
+            "{} s.d. {}",
+            format_date_id(from),
+            format_date_id(to)
+        )),
+        (Some(from), None) => Some(format!("sejak {}", format_date_id(from))),
+        (None, Some(to)) => Some(format!("sampai {}", format_date_id(to))),
+        (None, None) => None,
+    }
+}
+
 #[derive(Debug, Deserialize)]
+pub struct SearchPostingsRequest {
+    pub query: String,
+    #[serde(default = "default_limit")]
+    pub limit: i32,
+    #[serde(default)]
+    pub offset: i32,
+}
+
+impl SearchPostingsRequest {
+    /// Validate and return the trimmed query
+    pub fn validate(&self) -> Result<&str, String> {
+        let query = self.query.trim();
+        let length = query.chars().count();
+        if length < 2 {
+            return Err("Kata kunci minimal 2 karakter".to_string());
+        }
+        if length > 100 {
+            return Err("Kata kunci maksimal 100 karakter".to_string());
+        }
+        if self.limit < 1 {
+            return Err("Limit harus lebih dari 0".to_string());
+        }
+        if self.limit > 50 {
+            return Err("Limit maksimal adalah 50".to_string());
+        }
+        if self.offset < 0 {
+            return Err("Offset tidak boleh negatif".to_string());
+        }
+        Ok(query)
+    }
+}
+
+/// Text around the first case-insensitive occurrence of `query` in `text`,
+/// with an ellipsis where it was cut. None when `query` does not occur.
+pub fn match_snippet(text: &str, query: &str) -> Option<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let needle: Vec<char> = query.chars().collect();
+    if needle.is_empty() {
+        return None;
+    }
+
+    let start = (0..=chars.len().checked_sub(needle.len())?).find(|&i| {
+        chars[i..i + needle.len()]
+            .iter()
+            .zip(&needle)
+            .all(|(a, b)| a.to_lowercase().eq(b.to_lowercase()))
+    })?;
+    let from = start.saturating_sub(SNIPPET_CONTEXT_CHARS);
+    let to = (start + needle.len() + SNIPPET_CONTEXT_CHARS).min(chars.len());
+
+    let mut snippet = String::new();
+    if from > 0 {
+        snippet.push('…');
+    }
+    snippet.extend(&chars[from..to]);
+    if to < chars.len() {
+        snippet.push('…');
+    }
+    Some(snippet)
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct GetPostingDetailRequest {
+    #[schemars(description = "ID postingan (format UUID)")]
     pub id: String,
 }
 
@@ -169,6 +466,7 @@ pub struct PostListItem {
     pub date: String,
     pub excerpt: String,
     pub image_url: Option<String>,
+    pub cover_url: Option<String>,
 }
 
 /// Response for list_postings tool
@@ -179,6 +477,29 @@ pub struct ListPostingsResponse {
     pub limit: i32,
     pub offset: i32,
     pub has_more: bool,
+    /// First day of the period the posts were filtered on
+    pub date_from: Option<NaiveDate>,
+    /// Last day of the period the posts were filtered on
+    pub date_to: Option<NaiveDate>,
+}
+
+/// A search hit: the list item plus the text around the match
+#[derive(Debug, Serialize)]
+pub struct SearchResultItem {
+    #[serde(flatten)]
+    pub post: PostListItem,
+    pub snippet: String,
+}
+
+/// Response for search_postings tool
+#[derive(Debug, Serialize)]
+pub struct SearchPostingsResponse {
+    pub query: String,
+    pub posts: Vec<SearchResultItem>,
+    pub total: usize,
+    pub limit: i32,
+    pub offset: i32,
+    pub has_more: bool,
 }
 
 /// Response for get_posting_detail tool
@@ -194,6 +515,62 @@ pub struct PostDetailResponse {
     pub image_urls: Vec<String>,
 }
 
+/// A file attached to a posting
+#[derive(Debug, Serialize)]
+pub struct PostingAssetItem {
+    pub id: String,
+    pub name: String,
+    pub filename: String,
+    pub mime_type: String,
+    pub url: String,
+    /// Size in bytes, None when the object is missing from storage
+    pub size: Option<u64>,
+}
+
+/// Response for get_posting_assets tool
+#[derive(Debug, Serialize)]
+pub struct PostingAssetsResponse {
+    pub posting_id: String,
+    pub title: String,
+    pub assets: Vec<PostingAssetItem>,
+    pub count: usize,
+}
+
+/// Sizes of the objects named in `filenames`, found by listing each parent
+/// folder once. Objects that are missing or cannot be listed are left out.
+pub async fn object_sizes(
+    storage: &(dyn ObjectStorage + Send + Sync),
+    filenames: &[&str],
+) -> HashMap<String, u64> {
+    let mut folders: HashMap<&str, Vec<&str>> = HashMap::new();
+    for filename in filenames {
+        let folder = filename.rsplit_once('/').map_or("", |(folder, _)| folder);
+        folders.entry(folder).or_default().push(filename);
+    }
+
+    let mut sizes = HashMap::new();
+    for (folder, names) in folders {
+        let contents = match storage.list_folder_contents(folder).await {
+            Ok(contents) => contents,
+            Err(err) => {
+                log::warn!("Failed to list folder '{}' for asset sizes: {}", folder, err);
+                continue;
+            }
+        };
+        for entry in contents.into_iter().filter(|entry| entry.is_file) {
+            let path = if folder.is_empty() {
+                entry.name
+            } else {
+                format!("{}/{}", folder, entry.name)
+            };
+            if let Some(size) = entry.size.filter(|_| names.contains(&path.as_str())) {
+                sizes.insert(path, size);
+            }
+        }
+    }
+    sizes
+}
+
 /// Response for list_categories tool
 #[derive(Debug, Serialize)]
 pub struct ListCategoriesResponse {
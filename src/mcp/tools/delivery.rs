@@ -0,0 +1,102 @@
+//! Delivery of generated documents.
+//!
+//! Document tools return the PDF inline as a base64 resource by default.
+//! With `delivery: "url"` the PDF is stored as an asset under
+//! `generated/{date}/` and only a download link is returned, which keeps
+//! large letters out of the JSON-RPC response.
+
+use chrono::NaiveDate;
+use serde_json::{json, Value};
+
+use super::registry::ToolDescriptor;
+use crate::mcp::generators::GeneratedDocument;
+use crate::storage::{upload_with_unique_name, ObjectStorage};
+
+/// Storage prefix for generated documents, cleaned up by the maintenance job
+pub const GENERATED_PREFIX: &str = "generated";
+
+/// How a generated document is handed back to the client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Delivery {
+    /// Base64 resource in the tool result
+    #[default]
+    Inline,
+    /// Stored as an asset, the result carries its URL
+    Url,
+}
+
+impl Delivery {
+    /// Read the optional `delivery` argument shared by all document tools
+    pub fn from_arguments(arguments: &Option<Value>) -> Result<Self, String> {
+        match arguments.as_ref().and_then(|args| args.get("delivery")) {
+            None | Some(Value::Null) => Ok(Self::Inline),
+            Some(Value::String(value)) if value == "inline" => Ok(Self::Inline),
+            Some(Value::String(value)) if value == "url" => Ok(Self::Url),
+            Some(other) => Err(format!(
+                "Argumen 'delivery' tidak valid: {}. Gunakan \"inline\" atau \"url\".",
+                other
+            )),
+        }
+    }
+
+    /// The argument value selecting this delivery
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Inline => "inline",
+            Self::Url => "url",
+        }
+    }
+}
+
+/// Add the `delivery` option to a document tool's input schema
+pub fn with_delivery_option(mut descriptor: ToolDescriptor) -> ToolDescriptor {
+    if let Some(properties) = descriptor
+        .input_schema
+        .get_mut("properties")
+        .and_then(Value::as_object_mut)
+    {
+        properties.insert(
+            "delivery".to_string(),
+            json!({
+                "type": "string",
+                "enum": ["inline", "url"],
+                "default": "inline",
+                "description": "Cara pengiriman PDF: \"inline\" (base64 di respons) atau \"url\" (disimpan di server, respons berisi link unduhan)"
+            }),
+        );
+    }
+    descriptor
+}
+
+/// Storage key for a generated document. Later attempts get a random
+/// suffix so an existing letter with the same name is never replaced.
+pub fn generated_key(date: NaiveDate, filename: &str, attempt: usize) -> String {
+    let name = if attempt == 0 {
+        filename.to_string()
+    } else {
+        let (stem, ext) = filename.rsplit_once('.').unwrap_or((filename, "pdf"));
+        format!("{}-{:08x}.{}", stem, fastrand::u32(..), ext)
+    };
+    format!("{}/{}/{}", GENERATED_PREFIX, date.format("%Y-%m-%d"), name)
+}
+
+/// Upload the PDF and return the key it was stored under
+pub async fn upload_document(
+    storage: &(dyn ObjectStorage + Send + Sync),
+    doc: &GeneratedDocument,
+    date: NaiveDate,
+) -> Result<String, String> {
+    upload_with_unique_name(storage, &doc.pdf, |attempt| {
+        generated_key(date, &doc.filename, attempt)
+    })
+    .await
+}
+
+/// Absolute link to `/assets/serve/...` when a public base URL is known,
+/// otherwise the relative path
+pub fn public_url(base_url: Option<&str>, asset_url: &str) -> String {
+    match base_url {
+        Some(base) => format!("{}{}", base.trim_end_matches('/'), asset_url),
+        None => asset_url.to_string(),
+    }
+}
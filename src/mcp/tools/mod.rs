@@ -5,11 +5,18 @@
 //! - Argument parsing and validation
 //! - Execution and result formatting
 
+pub mod attachments;
 pub mod browse_posts;
+pub mod delivery;
 pub mod organization;
+pub mod rate_limit;
 pub mod registry;
-mod surat_kpr;
-mod surat_nib_npwp;
-mod surat_tidak_mampu;
+pub(crate) mod schema;
+pub(crate) mod surat_domisili;
+pub(crate) mod surat_kpr;
+pub(crate) mod surat_nib_npwp;
+pub(crate) mod surat_skck;
+pub(crate) mod surat_tidak_mampu;
+pub(crate) mod surat_usaha;
 
 pub use registry::ToolRegistry;
@@ -1,24 +1,227 @@
-//! MCP Tool for organization structure.
+//! MCP Tools for the organization structure.
 //!
-//! Provides access to the organization structure of Kelurahan Cakung Barat.
+//! Provides access to the organization structure of Kelurahan Cakung Barat:
+//! the full nested tree, and a lookup of members by name, position or role.
+//! Both read the organization cache, same as the REST endpoint.
 
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+
 use super::registry::ToolDescriptor;
+use crate::organization::model::OrganizationMember;
+use crate::organization::tree::{self, OrganizationNode};
 
 pub const GET_ORGANIZATION_STRUCTURE_TOOL: &str = "get_organization_structure";
+pub const FIND_ORGANIZATION_MEMBER_TOOL: &str = "find_organization_member";
+
+/// Shown instead of an empty result while no structure has been entered
+pub const EMPTY_STRUCTURE_MESSAGE: &str = concat!(
+    "Struktur organisasi Kelurahan Cakung Barat belum tersedia. ",
+    "Data pengurus belum diisi oleh admin kelurahan; ",
+    "silakan hubungi kantor kelurahan untuk informasi pengurus."
+);
 
 pub fn get_organization_structure_descriptor() -> ToolDescriptor {
     ToolDescriptor {
         name: GET_ORGANIZATION_STRUCTURE_TOOL.to_string(),
         description: concat!(
             "Melihat struktur organisasi Kelurahan Cakung Barat. ",
-            "Tool ini mengembalikan daftar anggota organisasi beserta jabatan, peran, dan hirarkinya. ",
-            "Gunakan tool ini untuk mengetahui siapa yang menjabat posisi tertentu."
+            "Tool ini mengembalikan pohon anggota organisasi beserta jabatan, peran, dan bawahannya. ",
+            "Gunakan find_organization_member untuk mencari satu anggota atau jabatan tertentu."
         )
         .to_string(),
         input_schema: json!({
             "type": "object",
             "properties": {}
         }),
+        output_schema: Some(json!({
+            "type": "object",
+            "properties": {
+                "count": {
+                    "type": "integer",
+                    "description": "Jumlah seluruh anggota organisasi"
+                },
+                "roots": {
+                    "type": "array",
+                    "items": { "$ref": "#/$defs/node" }
+                }
+            },
+            "required": ["count", "roots"],
+            "$defs": {
+                "node": member_schema(
+                    "children",
+                    json!({
+                        "type": "array",
+                        "items": { "$ref": "#/$defs/node" },
+                        "description": "Anggota yang berada langsung di bawah jabatan ini"
+                    })
+                )
+            }
+        })),
+    }
+}
+
+pub fn find_organization_member_descriptor() -> ToolDescriptor {
+    ToolDescriptor {
+        name: FIND_ORGANIZATION_MEMBER_TOOL.to_string(),
+        description: concat!(
+            "Mencari anggota struktur organisasi Kelurahan Cakung Barat berdasarkan nama, jabatan, atau peran. ",
+            "Gunakan tool ini untuk pertanyaan seperti \"siapa ketua RT 03\" atau \"jabatan Pak Budi apa\". ",
+            "Setiap hasil menyertakan rantai atasan dari pucuk pimpinan hingga atasan langsungnya."
+        )
+        .to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Nama, jabatan, atau peran yang dicari (2-100 karakter). Semua kata harus ditemukan."
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Jumlah maksimal hasil (default: 10, max: 50)"
+                }
+            },
+            "required": ["query"]
+        }),
+        output_schema: Some(json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string" },
+                "members": {
+                    "type": "array",
+                    "items": member_schema(
+                        "ancestors",
+                        json!({
+                            "type": "array",
+                            "items": plain_member_schema(),
+                            "description": "Atasan dari pucuk pimpinan hingga atasan langsung"
+                        })
+                    )
+                },
+                "total": {
+                    "type": "integer",
+                    "description": "Jumlah seluruh anggota yang cocok"
+                }
+            },
+            "required": ["query", "members", "total"]
+        })),
     }
 }
+
+/// Schema of an [`OrganizationMember`] with one extra property
+fn member_schema(extra: &str, extra_schema: serde_json::Value) -> serde_json::Value {
+    let mut schema = plain_member_schema();
+    schema["properties"][extra] = extra_schema;
+    schema["required"]
+        .as_array_mut()
+        .expect("required is an array")
+        .push(json!(extra));
+    schema
+}
+
+fn plain_member_schema() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "id": { "type": "integer" },
+            "name": { "type": ["string", "null"] },
+            "position": { "type": "string" },
+            "photo": { "type": ["string", "null"] },
+            "parent_id": { "type": ["integer", "null"] },
+            "level": { "type": "integer" },
+            "role": { "type": "string" }
+        },
+        "required": ["id", "name", "position", "photo", "parent_id", "level", "role"]
+    })
+}
+
+// =============================================================================
+// Request/Response Types
+// =============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct FindOrganizationMemberRequest {
+    pub query: String,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+fn default_limit() -> usize {
+    10
+}
+
+impl FindOrganizationMemberRequest {
+    /// Validate and return the trimmed query
+    pub fn validate(&self) -> Result<&str, String> {
+        let query = self.query.trim();
+        let length = query.chars().count();
+        if length < 2 {
+            return Err("Kata kunci minimal 2 karakter".to_string());
+        }
+        if length > 100 {
+            return Err("Kata kunci maksimal 100 karakter".to_string());
+        }
+        if self.limit < 1 {
+            return Err("Limit harus lebih dari 0".to_string());
+        }
+        if self.limit > 50 {
+            return Err("Limit maksimal adalah 50".to_string());
+        }
+        Ok(query)
+    }
+}
+
+/// Response for get_organization_structure tool
+#[derive(Debug, Serialize)]
+pub struct OrganizationStructureResponse {
+    pub count: usize,
+    pub roots: Vec<OrganizationNode>,
+}
+
+/// A matching member and the chain of members above it
+#[derive(Debug, Serialize)]
+pub struct MemberMatch {
+    #[serde(flatten)]
+    pub member: OrganizationMember,
+    pub ancestors: Vec<OrganizationMember>,
+}
+
+/// Response for find_organization_member tool
+#[derive(Debug, Serialize)]
+pub struct FindOrganizationMemberResponse {
+    pub query: String,
+    pub members: Vec<MemberMatch>,
+    pub total: usize,
+}
+
+/// Members whose name, position or role together contain every word of
+/// `query`, case-insensitively, ordered by level then id
+pub fn find_members(members: &[OrganizationMember], query: &str) -> Vec<MemberMatch> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    let mut found: Vec<&OrganizationMember> = members
+        .iter()
+        .filter(|member| {
+            let haystack = format!(
+                "{} {} {}",
+                member.name.as_deref().unwrap_or_default(),
+                member.position,
+                member.role
+            )
+            .to_lowercase();
+            terms.iter().all(|term| haystack.contains(term.as_str()))
+        })
+        .collect();
+    found.sort_by_key(|m| (m.level, m.id));
+
+    found
+        .into_iter()
+        .map(|member| MemberMatch {
+            member: member.clone(),
+            ancestors: tree::ancestors(members, member.id)
+                .into_iter()
+                .cloned()
+                .collect(),
+        })
+        .collect()
+}
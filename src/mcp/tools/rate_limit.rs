@@ -0,0 +1,262 @@
+//! Hourly quotas on MCP tool calls.
+//!
+//! Calls are counted per caller and tool over a sliding one-hour window,
+//! so an agent stuck in a loop is cut off after its quota instead of
+//! rendering letters indefinitely. Document tools are expensive and get a
+//! small default quota; browsing tools a large one. `MCP_TOOL_RATE_LIMITS`
+//! overrides single tools.
+
+use dashmap::DashMap;
+use prometheus::{IntCounterVec, Opts, Registry};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_DOCUMENT_CALLS_PER_HOUR: usize = 30;
+pub const DEFAULT_BROWSE_CALLS_PER_HOUR: usize = 600;
+
+/// Window the quotas apply to
+pub const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    /// Calls per hour of each document generation tool
+    pub document_calls_per_hour: usize,
+    /// Calls per hour of each posting and organization tool
+    pub browse_calls_per_hour: usize,
+    /// Per-tool quotas taking precedence over the two above
+    pub tool_calls_per_hour: HashMap<String, usize>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            document_calls_per_hour: DEFAULT_DOCUMENT_CALLS_PER_HOUR,
+            browse_calls_per_hour: DEFAULT_BROWSE_CALLS_PER_HOUR,
+            tool_calls_per_hour: HashMap::new(),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// Load using a custom variable lookup. `MCP_TOOL_RATE_LIMITS` lists
+    /// `<tool>=<calls per hour>` pairs separated by commas.
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let get = |key: &str| {
+            lookup(key)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let defaults = Self::default();
+
+        let document_calls_per_hour = get("MCP_DOCUMENT_CALLS_PER_HOUR")
+            .map(|v| crate::config::parse_positive("MCP_DOCUMENT_CALLS_PER_HOUR", &v))
+            .transpose()?
+            .unwrap_or(defaults.document_calls_per_hour);
+        let browse_calls_per_hour = get("MCP_BROWSE_CALLS_PER_HOUR")
+            .map(|v| crate::config::parse_positive("MCP_BROWSE_CALLS_PER_HOUR", &v))
+            .transpose()?
+            .unwrap_or(defaults.browse_calls_per_hour);
+        let tool_calls_per_hour = match get("MCP_TOOL_RATE_LIMITS") {
+            Some(v) => parse_tool_limits(&v)?,
+            None => defaults.tool_calls_per_hour,
+        };
+
+        Ok(Self {
+            document_calls_per_hour,
+            browse_calls_per_hour,
+            tool_calls_per_hour,
+        })
+    }
+
+    /// Calls per hour allowed for `tool`
+    pub fn limit(&self, tool: &str, is_document: bool) -> usize {
+        match self.tool_calls_per_hour.get(tool) {
+            Some(limit) => *limit,
+            None if is_document => self.document_calls_per_hour,
+            None => self.browse_calls_per_hour,
+        }
+    }
+}
+
+fn parse_tool_limits(value: &str) -> Result<HashMap<String, usize>, String> {
+    let mut limits = HashMap::new();
+    for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (tool, limit) = entry
+            .split_once('=')
+            .map(|(tool, limit)| (tool.trim(), limit.trim()))
+            .filter(|(tool, _)| !tool.is_empty())
+            .ok_or_else(|| {
+                "MCP_TOOL_RATE_LIMITS entries must look like '<tool>=<calls per hour>'".to_string()
+            })?;
+        let limit = crate::config::parse_positive("MCP_TOOL_RATE_LIMITS", limit)?;
+        limits.insert(tool.to_string(), limit);
+    }
+    Ok(limits)
+}
+
+/// A call refused because its caller used up the tool's quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Throttled {
+    /// Calls per hour allowed for the tool
+    pub limit: usize,
+    /// Until the oldest counted call leaves the window
+    pub retry_after: Duration,
+}
+
+impl Throttled {
+    /// Whole seconds to wait, at least one
+    pub fn retry_after_secs(&self) -> u64 {
+        self.retry_after.as_secs() + u64::from(self.retry_after.subsec_nanos() > 0)
+    }
+}
+
+/// Counts recent calls per (caller, tool)
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    calls: DashMap<(String, String), VecDeque<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            calls: DashMap::new(),
+        }
+    }
+
+    /// Count a call of `tool` by `caller`, unless its quota is used up
+    pub fn check(&self, caller: &str, tool: &str, is_document: bool) -> Result<(), Throttled> {
+        self.check_at(caller, tool, is_document, Instant::now())
+    }
+
+    fn check_at(
+        &self,
+        caller: &str,
+        tool: &str,
+        is_document: bool,
+        now: Instant,
+    ) -> Result<(), Throttled> {
+        let limit = self.config.limit(tool, is_document);
+        let key = (caller.to_string(), tool.to_string());
+        if !self.calls.contains_key(&key) {
+            // A new caller; forget the ones whose calls have all expired
+            self.calls
+                .retain(|_, calls| calls.back().is_some_and(|t| now - *t < RATE_LIMIT_WINDOW));
+        }
+
+        let mut calls = self.calls.entry(key).or_default();
+        while calls.front().is_some_and(|t| now - *t >= RATE_LIMIT_WINDOW) {
+            calls.pop_front();
+        }
+        if calls.len() >= limit {
+            let oldest = *calls.front().expect("limit is positive");
+            return Err(Throttled {
+                limit,
+                retry_after: RATE_LIMIT_WINDOW - (now - oldest),
+            });
+        }
+        calls.push_back(now);
+        Ok(())
+    }
+}
+
+/// How a tool call ended, as recorded in `mcp_tool_calls_total`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallOutcome {
+    Success,
+    Error,
+    Throttled,
+}
+
+impl CallOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Error => "error",
+            Self::Throttled => "throttled",
+        }
+    }
+}
+
+/// Tool calls per tool and outcome
+#[derive(Clone)]
+pub struct ToolCallMetrics {
+    calls: IntCounterVec,
+}
+
+impl Default for ToolCallMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToolCallMetrics {
+    pub fn new() -> Self {
+        let calls = IntCounterVec::new(
+            Opts::new(
+                "mcp_tool_calls_total",
+                "MCP tool calls by tool and outcome (success, error, throttled)",
+            )
+            .namespace("cakung_barat_server"),
+            &["tool", "outcome"],
+        )
+        .expect("valid MCP tool call metric");
+        Self { calls }
+    }
+
+    /// Register the counter so it is exported on `/metrics`
+    pub fn register(&self, registry: &Registry) -> prometheus::Result<()> {
+        registry.register(Box::new(self.calls.clone()))
+    }
+
+    pub fn record(&self, tool: &str, outcome: CallOutcome) {
+        self.calls
+            .with_label_values(&[tool, outcome.as_str()])
+            .inc();
+    }
+
+    pub fn count(&self, tool: &str, outcome: CallOutcome) -> u64 {
+        self.calls
+            .with_label_values(&[tool, outcome.as_str()])
+            .get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calls_leave_the_window() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            document_calls_per_hour: 2,
+            ..RateLimitConfig::default()
+        });
+        let start = Instant::now();
+        let tool = "generate_surat_kpr_belum_punya_rumah";
+
+        assert!(limiter.check_at("a", tool, true, start).is_ok());
+        let second = start + Duration::from_secs(600);
+        assert!(limiter.check_at("a", tool, true, second).is_ok());
+        let throttled = limiter
+            .check_at("a", tool, true, start + Duration::from_secs(1200))
+            .unwrap_err();
+        assert_eq!(throttled.limit, 2);
+        assert_eq!(throttled.retry_after, Duration::from_secs(2400));
+
+        // Other callers and tools have their own quota
+        assert!(limiter.check_at("b", tool, true, second).is_ok());
+        assert!(limiter
+            .check_at("a", "list_postings", false, second)
+            .is_ok());
+
+        // Once the first call is an hour old, one more fits
+        let later = start + RATE_LIMIT_WINDOW;
+        assert!(limiter.check_at("a", tool, true, later).is_ok());
+        assert!(limiter.check_at("a", tool, true, later).is_err());
+    }
+}
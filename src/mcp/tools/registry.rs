@@ -5,65 +5,392 @@
 use actix_web::web;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 
+use crate::asset::models::Asset;
 use crate::db::AppState;
+use crate::generated_documents::{NewGeneratedDocument, NikHasher};
 use crate::mcp::content::{ContentItem, ToolResult};
+use crate::mcp::generators::common::get_static_dir;
 use crate::mcp::generators::{
-    GeneratedDocument, GeneratorError, SuratKprGenerator, SuratKprRequest, SuratNibNpwpGenerator,
-    SuratNibNpwpRequest, SuratTidakMampuGenerator, SuratTidakMampuRequest,
+    self, Attachable, Attachments, GeneratedDocument, GenerationConfig, GenerationLimiter,
+    Generator, GeneratorError, Lampiran,
+    SuratDomisiliGenerator, SuratDomisiliRequest, SuratKprGenerator, SuratKprRequest,
+    SuratNibNpwpGenerator, SuratNibNpwpRequest, SuratPengantarSkckGenerator,
+    SuratPengantarSkckRequest, SuratTidakMampuGenerator, SuratTidakMampuRequest,
+    Requester, SuratUsahaGenerator, SuratUsahaRequest, TemplateInfo, TemplateStore, Validator,
 };
+use crate::mcp::logging::{LoggingLevel, ToolLogger};
+use crate::mcp::progress::{ProgressReporter, ProgressStage};
+use crate::organization::tree;
+use crate::posting::models::Post;
 
+use super::attachments;
 use super::browse_posts::{
     self, GetPostingDetailRequest, ListCategoriesResponse, ListPostingsRequest,
-    ListPostingsResponse, PostDetailResponse, PostListItem,
+    ListPostingsResponse, PostDetailResponse, PostListItem, PostingAssetItem,
+    PostingAssetsResponse, SearchPostingsRequest, SearchPostingsResponse, SearchResultItem,
 };
-use super::organization;
+use super::delivery::{self, Delivery};
+use super::organization::{
+    self, FindOrganizationMemberRequest, FindOrganizationMemberResponse,
+    OrganizationStructureResponse,
+};
+use super::rate_limit::{CallOutcome, RateLimitConfig, RateLimiter, ToolCallMetrics};
+use super::surat_domisili;
 use super::surat_kpr;
 use super::surat_nib_npwp;
+use super::surat_skck;
 use super::surat_tidak_mampu;
+use super::surat_usaha;
 
 /// Tool descriptor conforming to MCP specification.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ToolDescriptor {
     pub name: String,
     pub description: String,
     #[serde(rename = "inputSchema")]
     pub input_schema: Value,
+    /// Schema of `structuredContent`, for tools that return it
+    #[serde(rename = "outputSchema", skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+}
+
+/// Result of a call stopped by `notifications/cancelled`. The service drops
+/// it instead of replying.
+const CANCELLED_MESSAGE: &str = "Permintaan dibatalkan oleh klien.";
+/// Result of `delivery: "url"` while the server is in read-only mode
+const READ_ONLY_MESSAGE: &str = "Server sedang dalam mode baca saja untuk pemeliharaan, \
+     surat tidak dapat disimpan. Gunakan delivery \"inline\" atau coba lagi nanti.";
+
+/// Quota shared by calls that carry no caller, see [`ToolContext::caller`]
+const ANONYMOUS_CALLER: &str = "anonymous";
+
+/// Hooks for a single tool call.
+#[derive(Debug, Clone, Default)]
+pub struct ToolContext {
+    /// Receives the stages of document generation
+    pub progress: Option<ProgressReporter>,
+    /// Receives warnings and errors for the caller
+    pub logger: Option<ToolLogger>,
+    /// Checked between stages; read-only tools are abandoned outright
+    pub cancellation: CancellationToken,
+    /// Whose quota the call counts against. Calls without one share a
+    /// single quota.
+    pub caller: Option<String>,
+}
+
+impl ToolContext {
+    fn report(&self, stage: ProgressStage) {
+        if let Some(progress) = &self.progress {
+            progress.report(stage);
+        }
+    }
+
+    fn log(&self, level: LoggingLevel, message: &str) {
+        if let Some(logger) = &self.logger {
+            logger.log(level, message);
+        }
+    }
+}
+
+/// Rendering work handed to the generation limiter.
+type Render = Box<dyn FnOnce() -> Result<GeneratedDocument, GeneratorError> + Send>;
+
+/// Rendering of a validated request, given the images it refers to.
+type RenderWith = Box<dyn FnOnce(Attachments) -> Result<GeneratedDocument, GeneratorError> + Send>;
+
+/// A validated document request, ready to render on a blocking thread.
+struct DocumentJob {
+    label: &'static str,
+    render: RenderWith,
+    lampiran: Lampiran,
+    requester_name: String,
+    requester_nik: String,
+}
+
+impl DocumentJob {
+    fn prepare<R, G>(
+        generator: &Arc<G>,
+        arguments: Option<Value>,
+        label: &'static str,
+    ) -> Result<Self, String>
+    where
+        R: for<'de> Deserialize<'de> + Validator + Requester + Attachable + Send + 'static,
+        G: Generator<R> + Send + Sync + 'static,
+    {
+        let request = parse_arguments::<R>(arguments)?;
+
+        // Validate input before processing
+        Validator::validate(&request)?;
+
+        let (name, nik) = request.requester();
+        let (requester_name, requester_nik) = (name.to_string(), nik.to_string());
+        let lampiran = request.lampiran().clone();
+        let generator = Arc::clone(generator);
+        Ok(Self {
+            label,
+            render: Box::new(move |attachments| {
+                generator.generate_with_attachments(request, &attachments)
+            }),
+            lampiran,
+            requester_name,
+            requester_nik,
+        })
+    }
 }
 
 /// Central registry for all MCP tools.
 pub struct ToolRegistry {
-    surat_tidak_mampu: SuratTidakMampuGenerator,
-    surat_kpr: SuratKprGenerator,
-    surat_nib_npwp: SuratNibNpwpGenerator,
+    surat_tidak_mampu: Arc<SuratTidakMampuGenerator>,
+    surat_kpr: Arc<SuratKprGenerator>,
+    surat_nib_npwp: Arc<SuratNibNpwpGenerator>,
+    surat_domisili: Arc<SuratDomisiliGenerator>,
+    surat_usaha: Arc<SuratUsahaGenerator>,
+    surat_skck: Arc<SuratPengantarSkckGenerator>,
+    limiter: GenerationLimiter,
+    /// Templates of the document tools, shared by their generators
+    templates: Arc<TemplateStore>,
+    /// Base for absolute download links, see [`Delivery::Url`]
+    public_base_url: Option<String>,
+    /// Hashes requester NIKs for the generated-letter log
+    nik_hasher: NikHasher,
+    /// Descriptors returned by `tools/list`, built once since some input
+    /// schemas are generated from the request types
+    tools: Vec<ToolDescriptor>,
+    /// Whether the post browsing tools are offered, toggled at runtime
+    browse_tools: AtomicBool,
+    /// Hourly quota per caller and tool
+    rate_limiter: RateLimiter,
+    call_metrics: ToolCallMetrics,
 }
 
 impl ToolRegistry {
     /// Create a new registry with all generators initialized.
     pub fn new() -> Result<Self, GeneratorError> {
+        Self::with_config(&GenerationConfig::default())
+    }
+
+    /// Create a registry whose document generation is capped per `config`.
+    pub fn with_config(config: &GenerationConfig) -> Result<Self, GeneratorError> {
+        Self::with_templates(config, Arc::new(TemplateStore::new(get_static_dir())))
+    }
+
+    /// Like [`Self::with_config`], reading the templates through `templates`.
+    pub fn with_templates(
+        config: &GenerationConfig,
+        templates: Arc<TemplateStore>,
+    ) -> Result<Self, GeneratorError> {
         Ok(Self {
-            surat_tidak_mampu: SuratTidakMampuGenerator::new()?,
-            surat_kpr: SuratKprGenerator::new()?,
-            surat_nib_npwp: SuratNibNpwpGenerator::new()?,
+            surat_tidak_mampu: Arc::new(SuratTidakMampuGenerator::with_store(Arc::clone(
+                &templates,
+            ))?),
+            surat_kpr: Arc::new(SuratKprGenerator::with_store(Arc::clone(&templates))?),
+            surat_nib_npwp: Arc::new(SuratNibNpwpGenerator::with_store(Arc::clone(
+                &templates,
+            ))?),
+            surat_domisili: Arc::new(SuratDomisiliGenerator::with_store(Arc::clone(
+                &templates,
+            ))?),
+            surat_usaha: Arc::new(SuratUsahaGenerator::with_store(Arc::clone(&templates))?),
+            surat_skck: Arc::new(SuratPengantarSkckGenerator::with_store(Arc::clone(
+                &templates,
+            ))?),
+            limiter: GenerationLimiter::new(config),
+            templates,
+            public_base_url: None,
+            nik_hasher: NikHasher::new(None),
+            tools: Self::descriptors(),
+            browse_tools: AtomicBool::new(true),
+            rate_limiter: RateLimiter::default(),
+            call_metrics: ToolCallMetrics::new(),
         })
     }
 
+    /// Make download links for stored documents absolute.
+    pub fn with_public_base_url(mut self, public_base_url: Option<String>) -> Self {
+        self.public_base_url = public_base_url;
+        self
+    }
+
+    /// Key the NIK hashes of the generated-letter log with `salt`.
+    pub fn with_nik_salt(mut self, salt: Option<&str>) -> Self {
+        self.nik_hasher = NikHasher::new(salt);
+        self
+    }
+
+    /// Limit how often each caller may call each tool.
+    pub fn with_rate_limits(mut self, config: RateLimitConfig) -> Self {
+        self.rate_limiter = RateLimiter::new(config);
+        self
+    }
+
+    /// Calls per tool and outcome, for `/metrics`
+    pub fn call_metrics(&self) -> &ToolCallMetrics {
+        &self.call_metrics
+    }
+
     /// List all available tools per MCP spec.
     pub fn list_tools(&self) -> Vec<ToolDescriptor> {
+        self.tools
+            .iter()
+            .filter(|tool| self.is_offered(&tool.name))
+            .cloned()
+            .collect()
+    }
+
+    /// Whether the post browsing tools are offered
+    pub fn browse_tools_enabled(&self) -> bool {
+        self.browse_tools.load(Ordering::Relaxed)
+    }
+
+    /// Offer or withdraw the post browsing tools. Returns whether the tool
+    /// list changed, so callers know to notify clients.
+    pub fn set_browse_tools_enabled(&self, enabled: bool) -> bool {
+        self.browse_tools.swap(enabled, Ordering::Relaxed) != enabled
+    }
+
+    /// Whether tool `name` is currently listed and callable
+    fn is_offered(&self, name: &str) -> bool {
+        !is_browse_tool(name) || self.browse_tools_enabled()
+    }
+
+    fn descriptors() -> Vec<ToolDescriptor> {
         vec![
             // Document generation tools
-            surat_tidak_mampu::descriptor(),
-            surat_kpr::descriptor(),
-            surat_nib_npwp::descriptor(),
+            attachments::with_attachment_options(delivery::with_delivery_option(
+                surat_tidak_mampu::descriptor(),
+            )),
+            attachments::with_attachment_options(delivery::with_delivery_option(
+                surat_kpr::descriptor(),
+            )),
+            attachments::with_attachment_options(delivery::with_delivery_option(
+                surat_nib_npwp::descriptor(),
+            )),
+            attachments::with_attachment_options(delivery::with_delivery_option(
+                surat_domisili::descriptor(),
+            )),
+            attachments::with_attachment_options(delivery::with_delivery_option(
+                surat_usaha::descriptor(),
+            )),
+            attachments::with_attachment_options(delivery::with_delivery_option(
+                surat_skck::descriptor(),
+            )),
             // Post browsing tools
             browse_posts::list_postings_descriptor(),
             browse_posts::get_posting_detail_descriptor(),
             browse_posts::list_categories_descriptor(),
+            browse_posts::search_postings_descriptor(),
+            browse_posts::get_posting_assets_descriptor(),
             // Organization tools
             organization::get_organization_structure_descriptor(),
+            organization::find_organization_member_descriptor(),
+        ]
+    }
+
+    pub fn templates(&self) -> &Arc<TemplateStore> {
+        &self.templates
+    }
+
+    /// Template files used by the document tools
+    pub fn template_names(&self) -> [&'static str; 6] {
+        [
+            generators::surat_tidak_mampu::TEMPLATE_FILE,
+            generators::surat_kpr::TEMPLATE_FILE,
+            generators::surat_nib_npwp::TEMPLATE_FILE,
+            generators::surat_domisili::TEMPLATE_FILE,
+            generators::surat_usaha::TEMPLATE_FILE,
+            generators::surat_skck::TEMPLATE_FILE,
         ]
     }
 
+    /// Save `contents` as template `name` once it renders the sample request
+    /// of its tool. A template that fails to compile is never stored.
+    pub async fn replace_template(
+        &self,
+        name: &str,
+        contents: String,
+    ) -> Result<TemplateInfo, GeneratorError> {
+        let dry_run = self.template_dry_run(name, contents.clone())?;
+        self.limiter.run(dry_run).await?;
+        self.templates.replace(name, &contents)
+    }
+
+    /// Rendering of the sample request of template `name`'s tool with `template`
+    fn template_dry_run(&self, name: &str, template: String) -> Result<Render, GeneratorError> {
+        let dry_run: Render = match name {
+            generators::surat_tidak_mampu::TEMPLATE_FILE => {
+                let generator = Arc::clone(&self.surat_tidak_mampu);
+                Box::new(move || {
+                    generator.generate_with_template(
+                        &template,
+                        SuratTidakMampuRequest::sample(),
+                        &Attachments::default(),
+                    )
+                })
+            }
+            generators::surat_kpr::TEMPLATE_FILE => {
+                let generator = Arc::clone(&self.surat_kpr);
+                Box::new(move || {
+                    generator.generate_with_template(
+                        &template,
+                        SuratKprRequest::sample(),
+                        &Attachments::default(),
+                    )
+                })
+            }
+            generators::surat_nib_npwp::TEMPLATE_FILE => {
+                let generator = Arc::clone(&self.surat_nib_npwp);
+                Box::new(move || {
+                    generator.generate_with_template(
+                        &template,
+                        SuratNibNpwpRequest::sample(),
+                        &Attachments::default(),
+                    )
+                })
+            }
+            generators::surat_domisili::TEMPLATE_FILE => {
+                let generator = Arc::clone(&self.surat_domisili);
+                Box::new(move || {
+                    generator.generate_with_template(
+                        &template,
+                        SuratDomisiliRequest::sample(),
+                        &Attachments::default(),
+                    )
+                })
+            }
+            generators::surat_usaha::TEMPLATE_FILE => {
+                let generator = Arc::clone(&self.surat_usaha);
+                Box::new(move || {
+                    generator.generate_with_template(
+                        &template,
+                        SuratUsahaRequest::sample(),
+                        &Attachments::default(),
+                    )
+                })
+            }
+            generators::surat_skck::TEMPLATE_FILE => {
+                let generator = Arc::clone(&self.surat_skck);
+                Box::new(move || {
+                    generator.generate_with_template(
+                        &template,
+                        SuratPengantarSkckRequest::sample(),
+                        &Attachments::default(),
+                    )
+                })
+            }
+            _ => {
+                return Err(GeneratorError::TemplateIo(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("no document tool uses template {}", name),
+                )))
+            }
+        };
+        Ok(dry_run)
+    }
+
     /// Call a tool by name with the given arguments (async version).
     /// Handles both sync document tools and async database tools.
     pub async fn call_tool_async(
@@ -72,103 +399,313 @@ impl ToolRegistry {
         arguments: Option<Value>,
         app_state: &web::Data<AppState>,
     ) -> ToolResult {
-        match name {
-            // Sync document generation tools
-            surat_tidak_mampu::TOOL_NAME => self.call_surat_tidak_mampu(arguments),
-            surat_kpr::TOOL_NAME => self.call_surat_kpr(arguments),
-            surat_nib_npwp::TOOL_NAME => self.call_surat_nib_npwp(arguments),
+        self.call_tool_with_context(name, arguments, app_state, &ToolContext::default())
+            .await
+    }
+
+    /// Like [`Self::call_tool_async`], reporting the stages of document
+    /// generation and stopping once `context.cancellation` is tripped.
+    /// Calls over the caller's quota for the tool fail without running.
+    pub async fn call_tool_with_context(
+        &self,
+        name: &str,
+        arguments: Option<Value>,
+        app_state: &web::Data<AppState>,
+        context: &ToolContext,
+    ) -> ToolResult {
+        // Unknown names fail below; they get no quota or metric labels
+        if !self.tools.iter().any(|tool| tool.name == name) {
+            return self.call_database_tool(name, arguments, app_state).await;
+        }
+        if !self.is_offered(name) {
+            return ToolResult::error(format!("Tool '{}' sedang dinonaktifkan.", name));
+        }
+
+        let is_document = is_document_tool(name);
+        let caller = context.caller.as_deref().unwrap_or(ANONYMOUS_CALLER);
+        if let Err(throttled) = self.rate_limiter.check(caller, name, is_document) {
+            self.call_metrics.record(name, CallOutcome::Throttled);
+            let message = format!(
+                "Batas pemanggilan {} tercapai ({} kali per jam). Coba lagi dalam {} detik.",
+                name,
+                throttled.limit,
+                throttled.retry_after_secs()
+            );
+            context.log(LoggingLevel::Warning, &message);
+            return ToolResult::error(message);
+        }
+
+        let result = if is_document {
+            // Rendered off the async executor
+            self.call_document_tool(name, arguments, app_state, context)
+                .await
+        } else {
+            // Read-only, so safe to drop at any await point
+            tokio::select! {
+                result = self.call_database_tool(name, arguments, app_state) => result,
+                _ = context.cancellation.cancelled() => ToolResult::error(CANCELLED_MESSAGE),
+            }
+        };
+        let outcome = if result.is_error {
+            CallOutcome::Error
+        } else {
+            CallOutcome::Success
+        };
+        self.call_metrics.record(name, outcome);
+        result
+    }
 
+    async fn call_database_tool(
+        &self,
+        name: &str,
+        arguments: Option<Value>,
+        app_state: &web::Data<AppState>,
+    ) -> ToolResult {
+        match name {
             // Async database tools
             browse_posts::LIST_POSTINGS_TOOL => self.call_list_postings(arguments, app_state).await,
             browse_posts::GET_POSTING_DETAIL_TOOL => {
                 self.call_get_posting_detail(arguments, app_state).await
             }
             browse_posts::LIST_CATEGORIES_TOOL => self.call_list_categories(app_state).await,
+            browse_posts::SEARCH_POSTINGS_TOOL => {
+                self.call_search_postings(arguments, app_state).await
+            }
+            browse_posts::GET_POSTING_ASSETS_TOOL => {
+                self.call_get_posting_assets(arguments, app_state).await
+            }
             organization::GET_ORGANIZATION_STRUCTURE_TOOL => {
                 self.call_get_organization_structure(app_state).await
             }
+            organization::FIND_ORGANIZATION_MEMBER_TOOL => {
+                self.call_find_organization_member(arguments, app_state)
+                    .await
+            }
 
             _ => ToolResult::error(format!(
-                "Tool '{}' tidak tersedia. Tools yang tersedia: {}, {}, {}, {}, {}, {}, {}",
+                "Tool '{}' tidak tersedia. Tools yang tersedia: {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}",
                 name,
                 surat_tidak_mampu::TOOL_NAME,
                 surat_kpr::TOOL_NAME,
                 surat_nib_npwp::TOOL_NAME,
+                surat_domisili::TOOL_NAME,
+                surat_usaha::TOOL_NAME,
+                surat_skck::TOOL_NAME,
                 browse_posts::LIST_POSTINGS_TOOL,
                 browse_posts::GET_POSTING_DETAIL_TOOL,
                 browse_posts::LIST_CATEGORIES_TOOL,
+                browse_posts::SEARCH_POSTINGS_TOOL,
+                browse_posts::GET_POSTING_ASSETS_TOOL,
                 organization::GET_ORGANIZATION_STRUCTURE_TOOL,
+                organization::FIND_ORGANIZATION_MEMBER_TOOL,
             )),
         }
     }
 
     /// Call a tool by name with the given arguments (sync version for backward compatibility).
+    /// Only document tools are available; they render on the calling thread and
+    /// always return the PDF inline.
     pub fn call_tool(&self, name: &str, arguments: Option<Value>) -> ToolResult {
+        match self.document_job(name, arguments) {
+            // Attachments are downloaded from storage, see `call_tool_async`
+            Ok(job) if !job.lampiran.is_empty() => ToolResult::error(
+                "Lampiran foto/tanda tangan hanya didukung lewat server MCP.".to_string(),
+            ),
+            Ok(job) => self.document_result((job.render)(Attachments::default()), job.label),
+            Err(err) => ToolResult::error(err),
+        }
+    }
+
+    // =========================================================================
+    // Document generation tools
+    // =========================================================================
+
+    /// Parse and validate a document request, returning the rendering work
+    /// without running it.
+    fn document_job(&self, name: &str, arguments: Option<Value>) -> Result<DocumentJob, String> {
         match name {
-            surat_tidak_mampu::TOOL_NAME => self.call_surat_tidak_mampu(arguments),
-            surat_kpr::TOOL_NAME => self.call_surat_kpr(arguments),
-            surat_nib_npwp::TOOL_NAME => self.call_surat_nib_npwp(arguments),
-            _ => ToolResult::error(format!(
-                "Tool '{}' tidak tersedia. Tools yang tersedia: {}, {}, {}",
+            surat_tidak_mampu::TOOL_NAME => DocumentJob::prepare::<SuratTidakMampuRequest, _>(
+                &self.surat_tidak_mampu,
+                arguments,
+                "Surat Pernyataan Tidak Mampu",
+            ),
+            surat_kpr::TOOL_NAME => DocumentJob::prepare::<SuratKprRequest, _>(
+                &self.surat_kpr,
+                arguments,
+                "Surat Pernyataan Belum Memiliki Rumah",
+            ),
+            surat_nib_npwp::TOOL_NAME => DocumentJob::prepare::<SuratNibNpwpRequest, _>(
+                &self.surat_nib_npwp,
+                arguments,
+                "Surat Pernyataan Akan Mengurus NIB & NPWP",
+            ),
+            surat_domisili::TOOL_NAME => DocumentJob::prepare::<SuratDomisiliRequest, _>(
+                &self.surat_domisili,
+                arguments,
+                "Surat Pernyataan Domisili",
+            ),
+            surat_usaha::TOOL_NAME => DocumentJob::prepare::<SuratUsahaRequest, _>(
+                &self.surat_usaha,
+                arguments,
+                "Surat Pernyataan Memiliki Usaha",
+            ),
+            surat_skck::TOOL_NAME => DocumentJob::prepare::<SuratPengantarSkckRequest, _>(
+                &self.surat_skck,
+                arguments,
+                "Surat Pengantar SKCK",
+            ),
+            _ => Err(format!(
+                "Tool '{}' tidak tersedia. Tools yang tersedia: {}, {}, {}, {}, {}, {}",
                 name,
                 surat_tidak_mampu::TOOL_NAME,
                 surat_kpr::TOOL_NAME,
-                surat_nib_npwp::TOOL_NAME
+                surat_nib_npwp::TOOL_NAME,
+                surat_domisili::TOOL_NAME,
+                surat_usaha::TOOL_NAME,
+                surat_skck::TOOL_NAME
             )),
         }
     }
 
-    // =========================================================================
-    // Sync document generation tools
-    // =========================================================================
-
-    fn call_surat_tidak_mampu(&self, arguments: Option<Value>) -> ToolResult {
-        let request = match parse_arguments::<SuratTidakMampuRequest>(arguments) {
-            Ok(req) => req,
-            Err(err) => return ToolResult::error(err),
+    /// Render a document on the blocking pool, bounded by the generation limiter.
+    async fn call_document_tool(
+        &self,
+        name: &str,
+        arguments: Option<Value>,
+        app_state: &web::Data<AppState>,
+        context: &ToolContext,
+    ) -> ToolResult {
+        context.report(ProgressStage::Validating);
+        let job = Delivery::from_arguments(&arguments)
+            .and_then(|delivery| Ok((delivery, self.document_job(name, arguments)?)));
+        let (delivery, job) = match job {
+            Ok(job) => job,
+            Err(err) => {
+                context.log(LoggingLevel::Warning, &err);
+                return ToolResult::error(err);
+            }
         };
-
-        // Validate input before processing
-        if let Err(validation_error) = request.validate() {
-            return ToolResult::error(validation_error);
-        }
-
-        match self.surat_tidak_mampu.generate(request) {
-            Ok(doc) => self.success_result(doc, "Surat Pernyataan Tidak Mampu"),
-            Err(err) => ToolResult::error(format!("Gagal membuat surat: {}", err)),
+        // Storing the letter is a write; inline delivery only renders it
+        let read_only = app_state.is_read_only();
+        if read_only && delivery == Delivery::Url {
+            return ToolResult::error(READ_ONLY_MESSAGE.to_string());
         }
-    }
-
-    fn call_surat_kpr(&self, arguments: Option<Value>) -> ToolResult {
-        let request = match parse_arguments::<SuratKprRequest>(arguments) {
-            Ok(req) => req,
-            Err(err) => return ToolResult::error(err),
+        let attachments = match attachments::load_attachments(app_state, &job.lampiran).await {
+            Ok(attachments) => attachments,
+            Err(err) => {
+                context.log(LoggingLevel::Warning, &err);
+                return ToolResult::error(err);
+            }
+        };
+        let label = job.label;
+        let render = job.render;
+        let record = NewGeneratedDocument {
+            tool_name: name.to_string(),
+            requester_name: job.requester_name,
+            requester_nik_hash: Some(job.requester_nik)
+                .filter(|nik| !nik.trim().is_empty())
+                .map(|nik| self.nik_hasher.hash(&nik)),
+            filename: String::new(),
+            delivered_via: delivery.as_str().to_string(),
+        };
+        // Runs on the blocking thread once a rendering slot is free, which
+        // may be after the client gave up
+        let rendering = context.clone();
+        let result = tokio::select! {
+            result = self.limiter.run(move || {
+                if rendering.cancellation.is_cancelled() {
+                    return Err(GeneratorError::Cancelled);
+                }
+                rendering.report(ProgressStage::Rendering);
+                render(attachments)
+            }) => result,
+            _ = context.cancellation.cancelled() => Err(GeneratorError::Cancelled),
         };
 
-        // Validate input before processing
-        if let Err(validation_error) = request.validate() {
-            return ToolResult::error(validation_error);
-        }
+        let filename = result.as_ref().ok().map(|doc| doc.filename.clone());
+        let tool_result = match (delivery, result) {
+            // An upload is not interrupted halfway, only skipped
+            (Delivery::Url, Ok(_)) if context.cancellation.is_cancelled() => {
+                ToolResult::error(CANCELLED_MESSAGE)
+            }
+            (Delivery::Url, Ok(doc)) => {
+                context.report(ProgressStage::Uploading);
+                self.store_document(doc, label, app_state, context).await
+            }
+            (_, result) => self.document_result(result, label),
+        };
 
-        match self.surat_kpr.generate(request) {
-            Ok(doc) => self.success_result(doc, "Surat Pernyataan Belum Memiliki Rumah"),
-            Err(err) => ToolResult::error(format!("Gagal membuat surat: {}", err)),
+        if let (false, false, Some(filename)) = (tool_result.is_error, read_only, filename) {
+            record_document(NewGeneratedDocument { filename, ..record }, app_state);
         }
+        tool_result
     }
 
-    fn call_surat_nib_npwp(&self, arguments: Option<Value>) -> ToolResult {
-        let request = match parse_arguments::<SuratNibNpwpRequest>(arguments) {
-            Ok(req) => req,
-            Err(err) => return ToolResult::error(err),
+    /// Save the PDF as an asset and reply with its download link.
+    async fn store_document(
+        &self,
+        doc: GeneratedDocument,
+        surat_type: &str,
+        app_state: &web::Data<AppState>,
+        context: &ToolContext,
+    ) -> ToolResult {
+        let today = crate::timezone::today();
+        let key = match delivery::upload_document(app_state.storage.as_ref(), &doc, today).await {
+            Ok(key) => key,
+            Err(err) => {
+                log::error!("Failed to upload generated document {}: {}", doc.filename, err);
+                context.log(
+                    LoggingLevel::Error,
+                    &format!("Gagal mengunggah {}: {}", doc.filename, err),
+                );
+                return ToolResult::error(format!("Gagal menyimpan surat: {}", err));
+            }
         };
 
-        // Validate input before processing
-        if let Err(validation_error) = request.validate() {
-            return ToolResult::error(validation_error);
+        let asset = Asset::new(
+            doc.filename.clone(),
+            key.clone(),
+            format!("/assets/serve/{}", key),
+            Some(format!("{} (dibuat melalui MCP)", surat_type)),
+        );
+        if let Err(err) = app_state.insert_asset(&asset).await {
+            log::error!("Failed to save generated document {}: {}", key, err);
+            context.log(
+                LoggingLevel::Error,
+                &format!("Gagal mencatat aset {}: {}", key, err),
+            );
+            if let Err(e) = app_state.storage.delete_file(&key).await {
+                log::warn!("Failed to remove unsaved document {}: {}", key, e);
+            }
+            return ToolResult::error(
+                "Gagal menyimpan surat ke database. Silakan coba lagi.".to_string(),
+            );
         }
 
-        match self.surat_nib_npwp.generate(request) {
-            Ok(doc) => self.success_result(doc, "Surat Pernyataan Akan Mengurus NIB & NPWP"),
+        let url = delivery::public_url(self.public_base_url.as_deref(), &asset.url);
+        let text = format!(
+            "{} berhasil dibuat.\nFile: {}\nTanggal: {}\nUkuran: {} KB\nUnduh: {}",
+            surat_type,
+            doc.filename,
+            doc.tanggal,
+            doc.pdf.len().div_ceil(1024),
+            url
+        );
+        ToolResult::success(vec![ContentItem::text(text)])
+    }
+
+    fn document_result(
+        &self,
+        result: Result<GeneratedDocument, GeneratorError>,
+        surat_type: &str,
+    ) -> ToolResult {
+        match result {
+            Ok(doc) => self.success_result(doc, surat_type),
+            Err(GeneratorError::Cancelled) => ToolResult::error(CANCELLED_MESSAGE),
+            Err(GeneratorError::QueueTimeout(_)) => ToolResult::error(
+                "Server sedang memproses banyak surat. Silakan coba lagi dalam beberapa saat."
+                    .to_string(),
+            ),
             Err(err) => ToolResult::error(format!("Gagal membuat surat: {}", err)),
         }
     }
@@ -202,11 +739,16 @@ impl ToolRegistry {
         if let Err(validation_error) = request.validate() {
             return ToolResult::error(validation_error);
         }
+        let dates = match request.date_range() {
+            Ok(dates) => dates,
+            Err(err) => return ToolResult::error(err),
+        };
 
         // Get filtered posts from cache-first database layer
         let posts = match app_state
             .get_posts_filtered(
                 request.category.as_deref(),
+                dates,
                 request.is_sort_latest(),
                 request.limit,
                 request.offset,
@@ -221,7 +763,7 @@ impl ToolRegistry {
 
         // Get total count for pagination info
         let total = match app_state
-            .count_posts_filtered(request.category.as_deref())
+            .count_posts_filtered(request.category.as_deref(), dates)
             .await
         {
             Ok(count) => count,
@@ -233,26 +775,7 @@ impl ToolRegistry {
         // Enrich posts with image URLs
         let mut posts_with_images = Vec::new();
         for post in posts {
-            let mut image_url = None;
-            if let Some(folder_name) = &post.folder_id {
-                // Try to get assets for the post's folder
-                if let Ok(Some(asset_ids)) = app_state.get_folder_contents(folder_name).await {
-                    if let Some(first_id) = asset_ids.first() {
-                        if let Ok(Some(asset)) = app_state.get_asset_by_id(first_id).await {
-                            image_url = Some(asset.url);
-                        }
-                    }
-                }
-            }
-
-            posts_with_images.push(PostListItem {
-                id: post.id.to_string(),
-                title: post.title,
-                category: post.category,
-                date: post.date.to_string(),
-                excerpt: post.excerpt,
-                image_url,
-            });
+            posts_with_images.push(list_item(post, app_state).await);
         }
 
         let response = ListPostingsResponse {
@@ -261,6 +784,62 @@ impl ToolRegistry {
             limit: request.limit,
             offset: request.offset,
             has_more: (request.offset as usize + request.limit as usize) < total,
+            date_from: dates.from,
+            date_to: dates.to,
+        };
+
+        let mut summary = format!(
+            "Menampilkan {} dari {} postingan.",
+            response.posts.len(),
+            response.total
+        );
+        if let Some(period) = browse_posts::describe_period(dates) {
+            summary.push_str(&format!(" Periode: {}.", period));
+        }
+        ToolResult::structured(summary, structured(&response))
+    }
+
+    async fn call_search_postings(
+        &self,
+        arguments: Option<Value>,
+        app_state: &web::Data<AppState>,
+    ) -> ToolResult {
+        let request = match parse_arguments::<SearchPostingsRequest>(arguments) {
+            Ok(req) => req,
+            Err(err) => return ToolResult::error(err),
+        };
+
+        let query = match request.validate() {
+            Ok(query) => query,
+            Err(err) => return ToolResult::error(err),
+        };
+
+        let (posts, total) = match app_state
+            .search_posts(query, request.limit, request.offset)
+            .await
+        {
+            Ok(result) => result,
+            Err(err) => return ToolResult::error(format!("Gagal mencari postingan: {}", err)),
+        };
+
+        let mut results = Vec::new();
+        for post in posts {
+            let snippet = browse_posts::match_snippet(&post.excerpt, query)
+                .or_else(|| browse_posts::match_snippet(&post.title, query))
+                .unwrap_or_default();
+            results.push(SearchResultItem {
+                post: list_item(post, app_state).await,
+                snippet,
+            });
+        }
+
+        let response = SearchPostingsResponse {
+            query: query.to_string(),
+            posts: results,
+            total,
+            limit: request.limit,
+            offset: request.offset,
+            has_more: (request.offset as usize + request.limit as usize) < total,
         };
 
         let json_text =
@@ -317,6 +896,72 @@ impl ToolRegistry {
             image_urls,
         };
 
+        let summary = format!(
+            "Postingan \"{}\" ({}, {}).",
+            response.title, response.category, response.date
+        );
+        ToolResult::structured(summary, structured(&response))
+    }
+
+    async fn call_get_posting_assets(
+        &self,
+        arguments: Option<Value>,
+        app_state: &web::Data<AppState>,
+    ) -> ToolResult {
+        let request = match parse_arguments::<GetPostingDetailRequest>(arguments) {
+            Ok(req) => req,
+            Err(err) => return ToolResult::error(err),
+        };
+
+        let uuid = match request.validate() {
+            Ok(id) => id,
+            Err(err) => return ToolResult::error(err),
+        };
+
+        let post_with_assets = match app_state.get_posting_by_id_with_assets(&uuid).await {
+            Ok(Some(post)) => post,
+            Ok(None) => {
+                return ToolResult::error(format!("Postingan dengan ID '{}' tidak ditemukan", uuid))
+            }
+            Err(err) => {
+                return ToolResult::error(format!("Gagal mengambil data postingan: {}", err))
+            }
+        };
+
+        let assets = match app_state
+            .get_assets_by_ids(&post_with_assets.asset_ids)
+            .await
+        {
+            Ok(assets) => assets,
+            Err(err) => {
+                return ToolResult::error(format!("Gagal mengambil lampiran postingan: {}", err))
+            }
+        };
+
+        let filenames: Vec<&str> = assets.iter().map(|a| a.filename.as_str()).collect();
+        let sizes = browse_posts::object_sizes(app_state.storage.as_ref(), &filenames).await;
+
+        let items: Vec<PostingAssetItem> = assets
+            .into_iter()
+            .map(|asset| PostingAssetItem {
+                id: asset.id.to_string(),
+                mime_type: mime_guess::from_path(&asset.filename)
+                    .first_or_octet_stream()
+                    .to_string(),
+                url: app_state.storage.get_asset_url(&asset.filename),
+                size: sizes.get(&asset.filename).copied(),
+                name: asset.name,
+                filename: asset.filename,
+            })
+            .collect();
+
+        let response = PostingAssetsResponse {
+            posting_id: post_with_assets.id.to_string(),
+            title: post_with_assets.title,
+            count: items.len(),
+            assets: items,
+        };
+
         let json_text =
             serde_json::to_string_pretty(&response).unwrap_or_else(|_| "{}".to_string());
 
@@ -336,10 +981,8 @@ impl ToolRegistry {
             categories,
         };
 
-        let json_text =
-            serde_json::to_string_pretty(&response).unwrap_or_else(|_| "{}".to_string());
-
-        ToolResult::success(vec![ContentItem::text(json_text)])
+        let summary = format!("Tersedia {} kategori postingan.", response.count);
+        ToolResult::structured(summary, structured(&response))
     }
 
     async fn call_get_organization_structure(&self, app_state: &web::Data<AppState>) -> ToolResult {
@@ -350,14 +993,139 @@ impl ToolRegistry {
             }
         };
 
-        let json_text =
-            serde_json::to_string_pretty(&members).unwrap_or_else(|_| "{}".to_string());
+        if members.is_empty() {
+            return ToolResult::success_text(organization::EMPTY_STRUCTURE_MESSAGE);
+        }
 
-        ToolResult::success(vec![ContentItem::text(json_text)])
+        let response = OrganizationStructureResponse {
+            count: members.len(),
+            roots: tree::build_tree(&members),
+        };
+
+        let summary = format!("Struktur organisasi dengan {} anggota.", response.count);
+        ToolResult::structured(summary, structured(&response))
+    }
+
+    async fn call_find_organization_member(
+        &self,
+        arguments: Option<Value>,
+        app_state: &web::Data<AppState>,
+    ) -> ToolResult {
+        let request = match parse_arguments::<FindOrganizationMemberRequest>(arguments) {
+            Ok(req) => req,
+            Err(err) => return ToolResult::error(err),
+        };
+
+        let query = match request.validate() {
+            Ok(query) => query,
+            Err(err) => return ToolResult::error(err),
+        };
+
+        let members = match app_state.get_organization_structure().await {
+            Ok(m) => m,
+            Err(err) => {
+                return ToolResult::error(format!("Gagal mengambil struktur organisasi: {}", err))
+            }
+        };
+
+        if members.is_empty() {
+            return ToolResult::success_text(organization::EMPTY_STRUCTURE_MESSAGE);
+        }
+
+        let mut found = organization::find_members(&members, query);
+        let total = found.len();
+        found.truncate(request.limit);
+
+        let summary = match total {
+            0 => format!(
+                "Tidak ada anggota organisasi yang cocok dengan \"{}\". Coba kata kunci lain, misalnya nama jabatan seperti \"Ketua RT\" atau \"Lurah\".",
+                query
+            ),
+            _ => format!(
+                "Ditemukan {} anggota organisasi yang cocok dengan \"{}\".",
+                total, query
+            ),
+        };
+        let response = FindOrganizationMemberResponse {
+            query: query.to_string(),
+            members: found,
+            total,
+        };
+        ToolResult::structured(summary, structured(&response))
     }
 }
 
+/// List entry for a post, with the first image of its folder if it has one
+async fn list_item(post: Post, app_state: &web::Data<AppState>) -> PostListItem {
+    let mut image_url = None;
+    if let Some(folder_name) = &post.folder_id {
+        // Try to get assets for the post's folder
+        if let Ok(Some(asset_ids)) = app_state.get_folder_contents(folder_name).await {
+            if let Some(first_id) = asset_ids.first() {
+                if let Ok(Some(asset)) = app_state.get_asset_by_id(first_id).await {
+                    image_url = Some(asset.url);
+                }
+            }
+        }
+    }
+
+    let cover_url = post.cover_url();
+    PostListItem {
+        id: post.id.to_string(),
+        title: post.title,
+        category: post.category,
+        date: post.date.to_string(),
+        excerpt: post.excerpt,
+        image_url,
+        cover_url,
+    }
+}
+
+/// Whether `name` is a post browsing tool, see [`ToolRegistry::browse_tools_enabled`]
+fn is_browse_tool(name: &str) -> bool {
+    matches!(
+        name,
+        browse_posts::LIST_POSTINGS_TOOL
+            | browse_posts::GET_POSTING_DETAIL_TOOL
+            | browse_posts::LIST_CATEGORIES_TOOL
+            | browse_posts::SEARCH_POSTINGS_TOOL
+            | browse_posts::GET_POSTING_ASSETS_TOOL
+    )
+}
+
+/// Whether `name` is a document generation tool, as opposed to a read-only one
+fn is_document_tool(name: &str) -> bool {
+    matches!(
+        name,
+        surat_tidak_mampu::TOOL_NAME
+            | surat_kpr::TOOL_NAME
+            | surat_nib_npwp::TOOL_NAME
+            | surat_domisili::TOOL_NAME
+            | surat_usaha::TOOL_NAME
+            | surat_skck::TOOL_NAME
+    )
+}
+
+/// `structuredContent` for a tool response
+fn structured<T: Serialize>(response: &T) -> Value {
+    serde_json::to_value(response).unwrap_or_else(|_| Value::Object(Default::default()))
+}
+
 fn parse_arguments<T: for<'de> Deserialize<'de>>(arguments: Option<Value>) -> Result<T, String> {
     let value = arguments.unwrap_or(Value::Null);
     serde_json::from_value(value).map_err(|err| format!("Argumen tidak valid: {}", err))
-}
\ No newline at end of file
+}
+/// Log a delivered letter in the background, so a slow database never
+/// holds back the response.
+fn record_document(document: NewGeneratedDocument, app_state: &web::Data<AppState>) {
+    let app_state = app_state.clone();
+    tokio::spawn(async move {
+        if let Err(err) = app_state.record_generated_document(&document).await {
+            log::warn!(
+                "Failed to log generated document {}: {}",
+                document.filename,
+                err
+            );
+        }
+    });
+}
@@ -0,0 +1,26 @@
+//! Input schemas derived from the request types.
+//!
+//! Tools whose arguments deserialize into a Rust struct take their
+//! `inputSchema` from the struct's `JsonSchema` derive, so the schema
+//! cannot list fields the struct does not accept or miss ones it requires.
+
+use schemars::generate::SchemaSettings;
+use schemars::JsonSchema;
+use serde_json::Value;
+
+/// `inputSchema` of a tool whose arguments deserialize into `T`.
+///
+/// Nested structs are inlined rather than referenced through `$defs`,
+/// since not every MCP client resolves references in tool schemas.
+pub fn input_schema<T: JsonSchema>() -> Value {
+    let mut schema = SchemaSettings::draft2020_12()
+        .with(|settings| {
+            settings.inline_subschemas = true;
+            settings.meta_schema = None;
+        })
+        .into_generator()
+        .into_root_schema_for::<T>();
+    schema.remove("title");
+    schema.remove("description");
+    schema.into()
+}
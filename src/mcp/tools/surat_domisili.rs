@@ -0,0 +1,86 @@
+//! Tool definition for Surat Keterangan Domisili.
+
+use serde_json::{Value, json};
+
+use super::registry::ToolDescriptor;
+
+pub const TOOL_NAME: &str = "generate_surat_domisili";
+
+/// Get the tool descriptor for MCP tools/list.
+pub fn descriptor() -> ToolDescriptor {
+    ToolDescriptor {
+        name: TOOL_NAME.to_string(),
+        description: concat!(
+            "Membuat Surat Pernyataan untuk pengurusan Surat Keterangan Domisili dalam format PDF. ",
+            "Surat ini digunakan warga yang tinggal di wilayah kelurahan tetapi alamat KTP-nya berbeda, ",
+            "misalnya untuk keperluan melamar kerja, membuka rekening bank, atau pendaftaran sekolah. ",
+            "[PENTING] INSTRUKSI PENGGUNAAN: ",
+            "(1) WAJIB tanyakan semua data kepada warga SEBELUM memanggil tool ini. ",
+            "(2) Data pengisi yang harus dikumpulkan: nama lengkap, NIK (16 digit), ",
+            "tempat/tanggal lahir, jenis kelamin, agama, pekerjaan, alamat sesuai KTP, nomor telepon. ",
+            "(3) Tanyakan juga alamat domisili saat ini, RT/RW (contoh: 003/005), ",
+            "sudah berapa lama tinggal di alamat tersebut, dan keperluan surat. ",
+            "(4) DILARANG menggunakan data contoh/dummy seperti 'John Doe' atau NIK palsu. ",
+            "(5) Jika data belum lengkap, minta warga melengkapinya terlebih dahulu."
+        ).to_string(),
+        input_schema: input_schema(),
+        output_schema: None,
+    }
+}
+
+fn input_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "pengisi": {
+                "type": "object",
+                "description": "Data orang yang mengisi/menandatangani surat",
+                "properties": {
+                    "nama": { "type": "string", "description": "Nama lengkap pengisi" },
+                    "nik": { "type": "string", "description": "NIK (16 digit)" },
+                    "ttl": { "type": "string", "description": "Tempat, Tanggal Lahir" },
+                    "jk": { "type": "boolean", "description": "Jenis Kelamin (true = Laki-laki, false = Perempuan). Jika input user tidak jelas, tanyakan kembali." },
+                    "agama": { "type": "string", "description": "Agama" },
+                    "pekerjaan": { "type": "string", "description": "Pekerjaan" },
+                    "alamat": { "type": "string", "description": "Alamat sesuai KTP" },
+                    "telp": { "type": "string", "description": "Nomor telepon/HP" }
+                },
+                "required": ["nama", "nik", "ttl", "jk", "agama", "pekerjaan", "alamat", "telp"]
+            },
+            "domisili": {
+                "type": "object",
+                "description": "Data tempat tinggal saat ini",
+                "properties": {
+                    "alamat": { "type": "string", "description": "Alamat domisili saat ini" },
+                    "rt_rw": { "type": "string", "description": "RT/RW domisili, contoh: 003/005" },
+                    "lama_tinggal": { "type": "string", "description": "Lama tinggal di alamat domisili, contoh: 2 tahun" },
+                    "keperluan": { "type": "string", "description": "Keperluan surat keterangan domisili" }
+                },
+                "required": ["alamat", "rt_rw", "lama_tinggal", "keperluan"]
+            },
+            "meta": {
+                "type": "object",
+                "description": "Metadata surat",
+                "properties": {
+                    "kelurahan": { "type": "string", "description": "Nama kelurahan" },
+                    "tanggal": { "type": "string", "description": "Tanggal surat (opsional, default: hari ini)" }
+                },
+                "required": ["kelurahan"]
+            }
+        },
+        "required": ["pengisi", "domisili", "meta"]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_descriptor() {
+        let desc = descriptor();
+        assert_eq!(desc.name, TOOL_NAME);
+        assert!(desc.description.contains("Domisili"));
+        assert!(desc.input_schema.get("properties").is_some());
+    }
+}
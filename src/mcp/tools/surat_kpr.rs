@@ -1,8 +1,9 @@
 //! Tool definition for Surat Pernyataan Belum Memiliki Rumah (KPR).
 
-use serde_json::{Value, json};
+use crate::mcp::generators::SuratKprRequest;
 
 use super::registry::ToolDescriptor;
+use super::schema;
 
 pub const TOOL_NAME: &str = "generate_surat_kpr_belum_punya_rumah";
 
@@ -21,44 +22,11 @@ pub fn descriptor() -> ToolDescriptor {
             "(4) DILARANG menggunakan data contoh/dummy seperti 'John Doe' atau NIK palsu. ",
             "(5) Jika data belum lengkap, minta warga melengkapinya terlebih dahulu."
         ).to_string(),
-        input_schema: input_schema(),
+        input_schema: schema::input_schema::<SuratKprRequest>(),
+        output_schema: None,
     }
 }
 
-fn input_schema() -> Value {
-    json!({
-        "type": "object",
-        "properties": {
-            "data": {
-                "type": "object",
-                "description": "Data pemohon KPR",
-                "properties": {
-                    "nama": { "type": "string", "description": "Nama lengkap pemohon" },
-                    "nik": { "type": "string", "description": "NIK (16 digit)" },
-                    "ttl": { "type": "string", "description": "Tempat, Tanggal Lahir" },
-                    "jk": { "type": "boolean", "description": "Jenis Kelamin (true = Laki-laki, false = Perempuan). Jika input user tidak jelas, tanyakan kembali." },
-                    "agama": { "type": "string", "description": "Agama" },
-                    "pekerjaan": { "type": "string", "description": "Pekerjaan" },
-                    "alamat": { "type": "string", "description": "Alamat lengkap" },
-                    "telp": { "type": "string", "description": "Nomor telepon/HP" }
-                },
-                "required": ["nama", "nik", "ttl", "jk", "agama", "pekerjaan", "alamat", "telp"]
-            },
-            "meta": {
-                "type": "object",
-                "description": "Metadata surat",
-                "properties": {
-                    "kelurahan": { "type": "string", "description": "Nama kelurahan" },
-                    "bank_tujuan": { "type": "string", "description": "Nama bank tujuan KPR" },
-                    "tanggal": { "type": "string", "description": "Tanggal surat (opsional, default: hari ini)" }
-                },
-                "required": ["kelurahan", "bank_tujuan"]
-            }
-        },
-        "required": ["data", "meta"]
-    })
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
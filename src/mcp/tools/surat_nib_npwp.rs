@@ -1,8 +1,9 @@
 //! Tool definition for Surat Pernyataan Akan Mengurus NIB & NPWP.
 
-use serde_json::{Value, json};
+use crate::mcp::generators::SuratNibNpwpRequest;
 
 use super::registry::ToolDescriptor;
+use super::schema;
 
 pub const TOOL_NAME: &str = "generate_surat_nib_npwp";
 
@@ -24,40 +25,11 @@ pub fn descriptor() -> ToolDescriptor {
             "(5) Jika data belum lengkap, minta warga melengkapinya terlebih dahulu."
         )
         .to_string(),
-        input_schema: input_schema(),
+        input_schema: schema::input_schema::<SuratNibNpwpRequest>(),
+        output_schema: None,
     }
 }
 
-fn input_schema() -> Value {
-    json!({
-        "type": "object",
-        "properties": {
-            "data": {
-                "type": "object",
-                "description": "Data pelaku usaha",
-                "properties": {
-                    "nama": { "type": "string", "description": "Nama lengkap pelaku usaha" },
-                    "nik": { "type": "string", "description": "NIK (16 digit)" },
-                    "jabatan": { "type": "string", "description": "Jabatan dalam usaha (mis: Pemilik, Direktur)" },
-                    "bidang_usaha": { "type": "string", "description": "Bidang usaha (mis: Perdagangan, Jasa)" },
-                    "kegiatan_usaha": { "type": "string", "description": "Deskripsi kegiatan usaha" },
-                    "jenis_usaha": { "type": "string", "description": "Jenis usaha (Usaha Mikro/Kecil/Menengah)" },
-                    "alamat_usaha": { "type": "string", "description": "Alamat lengkap lokasi usaha" }
-                },
-                "required": ["nama", "nik", "jabatan", "bidang_usaha", "kegiatan_usaha", "jenis_usaha", "alamat_usaha"]
-            },
-            "meta": {
-                "type": "object",
-                "description": "Metadata surat",
-                "properties": {
-                    "tanggal": { "type": "string", "description": "Tanggal surat (opsional, default: hari ini)" }
-                }
-            }
-        },
-        "required": ["data"]
-    })
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,85 @@
+//! Tool definition for Surat Pengantar SKCK.
+
+use serde_json::{json, Value};
+
+use super::registry::ToolDescriptor;
+
+pub const TOOL_NAME: &str = "generate_surat_pengantar_skck";
+
+/// Get the tool descriptor for MCP tools/list.
+pub fn descriptor() -> ToolDescriptor {
+    ToolDescriptor {
+        name: TOOL_NAME.to_string(),
+        description: concat!(
+            "Membuat Surat Pengantar dari kelurahan untuk pengurusan SKCK (Surat Keterangan Catatan Kepolisian) ",
+            "dalam format PDF. Surat ini dibawa warga ke Polsek/Polres saat mengajukan SKCK, ",
+            "misalnya untuk melamar kerja, mendaftar CPNS, atau keperluan studi. ",
+            "[PENTING] INSTRUKSI PENGGUNAAN: ",
+            "(1) WAJIB tanyakan semua data kepada warga SEBELUM memanggil tool ini. ",
+            "(2) Data pemohon yang harus dikumpulkan: nama lengkap, NIK (16 digit), ",
+            "tempat/tanggal lahir, jenis kelamin, agama, pekerjaan, alamat sesuai KTP, nomor telepon. ",
+            "(3) Tanyakan juga keperluan SKCK, instansi tujuan, ",
+            "dan masa berlaku surat pengantar (contoh: 1 bulan). ",
+            "(4) DILARANG menggunakan data contoh/dummy seperti 'John Doe' atau NIK palsu. ",
+            "(5) Jika data belum lengkap, minta warga melengkapinya terlebih dahulu."
+        ).to_string(),
+        input_schema: input_schema(),
+        output_schema: None,
+    }
+}
+
+fn input_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "pemohon": {
+                "type": "object",
+                "description": "Data warga yang mengajukan SKCK",
+                "properties": {
+                    "nama": { "type": "string", "description": "Nama lengkap pemohon" },
+                    "nik": { "type": "string", "description": "NIK (16 digit)" },
+                    "ttl": { "type": "string", "description": "Tempat, Tanggal Lahir" },
+                    "jk": { "type": "boolean", "description": "Jenis Kelamin (true = Laki-laki, false = Perempuan). Jika input user tidak jelas, tanyakan kembali." },
+                    "agama": { "type": "string", "description": "Agama" },
+                    "pekerjaan": { "type": "string", "description": "Pekerjaan" },
+                    "alamat": { "type": "string", "description": "Alamat sesuai KTP" },
+                    "telp": { "type": "string", "description": "Nomor telepon/HP" }
+                },
+                "required": ["nama", "nik", "ttl", "jk", "agama", "pekerjaan", "alamat", "telp"]
+            },
+            "skck": {
+                "type": "object",
+                "description": "Data permohonan SKCK",
+                "properties": {
+                    "keperluan": { "type": "string", "description": "Keperluan SKCK, contoh: Melamar pekerjaan" },
+                    "tujuan_instansi": { "type": "string", "description": "Instansi tujuan SKCK, contoh: PT Maju Jaya" },
+                    "masa_berlaku": { "type": "string", "description": "Masa berlaku surat pengantar, contoh: 1 bulan" }
+                },
+                "required": ["keperluan", "tujuan_instansi", "masa_berlaku"]
+            },
+            "meta": {
+                "type": "object",
+                "description": "Metadata surat",
+                "properties": {
+                    "kelurahan": { "type": "string", "description": "Nama kelurahan" },
+                    "tanggal": { "type": "string", "description": "Tanggal surat (opsional, default: hari ini)" }
+                },
+                "required": ["kelurahan"]
+            }
+        },
+        "required": ["pemohon", "skck", "meta"]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_descriptor() {
+        let desc = descriptor();
+        assert_eq!(desc.name, TOOL_NAME);
+        assert!(desc.description.contains("SKCK"));
+        assert!(desc.input_schema.get("properties").is_some());
+    }
+}
@@ -1,8 +1,9 @@
 //! Tool definition for Surat Pernyataan Tidak Mampu (SKTM).
 
-use serde_json::{Value, json};
+use crate::mcp::generators::SuratTidakMampuRequest;
 
 use super::registry::ToolDescriptor;
+use super::schema;
 
 pub const TOOL_NAME: &str = "generate_surat_tidak_mampu";
 
@@ -23,62 +24,11 @@ pub fn descriptor() -> ToolDescriptor {
             "(5) DILARANG menggunakan data contoh/dummy seperti 'John Doe' atau NIK palsu. ",
             "(6) Jika data belum lengkap, minta warga melengkapinya terlebih dahulu."
         ).to_string(),
-        input_schema: input_schema(),
+        input_schema: schema::input_schema::<SuratTidakMampuRequest>(),
+        output_schema: None,
     }
 }
 
-fn input_schema() -> Value {
-    json!({
-        "type": "object",
-        "properties": {
-            "pengisi": {
-                "type": "object",
-                "description": "Data orang yang mengisi/menandatangani surat",
-                "properties": {
-                    "nama": { "type": "string", "description": "Nama lengkap pengisi" },
-                    "nik": { "type": "string", "description": "NIK (16 digit)" },
-                    "ttl": { "type": "string", "description": "Tempat, Tanggal Lahir" },
-                    "jk": { "type": "boolean", "description": "Jenis Kelamin (true = Laki-laki, false = Perempuan). Jika input user tidak jelas, tanyakan kembali." },
-                    "agama": { "type": "string", "description": "Agama" },
-                    "pekerjaan": { "type": "string", "description": "Pekerjaan" },
-                    "alamat": { "type": "string", "description": "Alamat lengkap" },
-                    "telp": { "type": "string", "description": "Nomor telepon/HP" }
-                },
-                "required": ["nama", "nik", "ttl", "jk", "agama", "pekerjaan", "alamat", "telp"]
-            },
-            "subjek": {
-                "type": "object",
-                "description": "Data orang yang dibuatkan SKTM (jika berbeda dengan pengisi)",
-                "properties": {
-                    "nama": { "type": "string", "description": "Nama lengkap subjek" },
-                    "nik": { "type": "string", "description": "NIK (bila ada)" },
-                    "ttl": { "type": "string", "description": "Tempat, Tanggal Lahir" },
-                    "jk": { "type": "boolean", "description": "Jenis Kelamin (true = Laki-laki, false = Perempuan)" },
-                    "agama": { "type": "string", "description": "Agama" },
-                    "pekerjaan": { "type": "string", "description": "Pekerjaan" },
-                    "alamat": { "type": "string", "description": "Alamat" },
-                    "hubungan": { "type": "string", "description": "Hubungan keluarga dengan pengisi" }
-                }
-            },
-            "meta": {
-                "type": "object",
-                "description": "Metadata surat",
-                "properties": {
-                    "opsi_sendiri": {
-                        "type": "boolean",
-                        "description": "True jika SKTM untuk diri sendiri, false jika untuk orang lain",
-                        "default": true
-                    },
-                    "kelurahan": { "type": "string", "description": "Nama kelurahan" },
-                    "tanggal": { "type": "string", "description": "Tanggal surat (opsional, default: hari ini)" }
-                },
-                "required": ["kelurahan"]
-            }
-        },
-        "required": ["pengisi", "meta"]
-    })
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
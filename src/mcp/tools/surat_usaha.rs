@@ -0,0 +1,87 @@
+//! Tool definition for Surat Keterangan Usaha (SKU).
+
+use serde_json::{json, Value};
+
+use super::registry::ToolDescriptor;
+
+pub const TOOL_NAME: &str = "generate_surat_keterangan_usaha";
+
+/// Get the tool descriptor for MCP tools/list.
+pub fn descriptor() -> ToolDescriptor {
+    ToolDescriptor {
+        name: TOOL_NAME.to_string(),
+        description: concat!(
+            "Membuat Surat Pernyataan untuk pengurusan Surat Keterangan Usaha (SKU) dalam format PDF. ",
+            "Surat ini digunakan pelaku usaha kecil/mikro untuk keperluan pengajuan kredit usaha, ",
+            "pendaftaran mitra, atau bantuan modal usaha. ",
+            "[PENTING] INSTRUKSI PENGGUNAAN: ",
+            "(1) WAJIB tanyakan semua data kepada warga SEBELUM memanggil tool ini. ",
+            "(2) Data pemilik yang harus dikumpulkan: nama lengkap, NIK (16 digit), ",
+            "tempat/tanggal lahir, jenis kelamin, agama, pekerjaan, alamat, nomor telepon. ",
+            "(3) Tanyakan juga nama usaha, jenis usaha, alamat usaha, tahun mulai usaha, ",
+            "dan keperluan surat. ",
+            "(4) DILARANG menggunakan data contoh/dummy seperti 'John Doe' atau NIK palsu. ",
+            "(5) Jika data belum lengkap, minta warga melengkapinya terlebih dahulu."
+        ).to_string(),
+        input_schema: input_schema(),
+        output_schema: None,
+    }
+}
+
+fn input_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "pemilik": {
+                "type": "object",
+                "description": "Data pemilik usaha yang menandatangani surat",
+                "properties": {
+                    "nama": { "type": "string", "description": "Nama lengkap pemilik usaha" },
+                    "nik": { "type": "string", "description": "NIK (16 digit)" },
+                    "ttl": { "type": "string", "description": "Tempat, Tanggal Lahir" },
+                    "jk": { "type": "boolean", "description": "Jenis Kelamin (true = Laki-laki, false = Perempuan). Jika input user tidak jelas, tanyakan kembali." },
+                    "agama": { "type": "string", "description": "Agama" },
+                    "pekerjaan": { "type": "string", "description": "Pekerjaan" },
+                    "alamat": { "type": "string", "description": "Alamat sesuai KTP" },
+                    "telp": { "type": "string", "description": "Nomor telepon/HP" }
+                },
+                "required": ["nama", "nik", "ttl", "jk", "agama", "pekerjaan", "alamat", "telp"]
+            },
+            "usaha": {
+                "type": "object",
+                "description": "Data usaha",
+                "properties": {
+                    "nama_usaha": { "type": "string", "description": "Nama usaha, contoh: Warung Makan Bu Sri" },
+                    "jenis_usaha": { "type": "string", "description": "Jenis usaha, contoh: Kuliner" },
+                    "alamat_usaha": { "type": "string", "description": "Alamat lokasi usaha" },
+                    "tahun_mulai": { "type": "string", "description": "Tahun usaha mulai berjalan (lama usaha), contoh: 2019" },
+                    "keperluan": { "type": "string", "description": "Keperluan surat keterangan usaha" }
+                },
+                "required": ["nama_usaha", "jenis_usaha", "alamat_usaha", "tahun_mulai", "keperluan"]
+            },
+            "meta": {
+                "type": "object",
+                "description": "Metadata surat",
+                "properties": {
+                    "kelurahan": { "type": "string", "description": "Nama kelurahan" },
+                    "tanggal": { "type": "string", "description": "Tanggal surat (opsional, default: hari ini)" }
+                },
+                "required": ["kelurahan"]
+            }
+        },
+        "required": ["pemilik", "usaha", "meta"]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_descriptor() {
+        let desc = descriptor();
+        assert_eq!(desc.name, TOOL_NAME);
+        assert!(desc.description.contains("SKU"));
+        assert!(desc.input_schema.get("properties").is_some());
+    }
+}
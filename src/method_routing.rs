@@ -0,0 +1,111 @@
+//! `HEAD` support and `405 Method Not Allowed` answers for every route.
+//!
+//! A `HEAD` request is routed as `GET` and answered with the headers of
+//! the `GET` response, including its `Content-Length`, but no body. A
+//! path that exists without the requested method gets 405 with an `Allow`
+//! header and `ErrorResponse` JSON instead of actix's empty body.
+//!
+//! Only routes registered as `web::resource` learn their methods, so
+//! register every path that way rather than with `.route(path, ...)`,
+//! which answers other methods with 404.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use actix_web::body::{BodySize, BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header;
+use actix_web::http::{Method, StatusCode};
+use actix_web::middleware::Next;
+use actix_web::web::Bytes;
+use actix_web::{Error, HttpResponse};
+
+use crate::{ErrorCode, ErrorResponse};
+
+/// Body of a `HEAD` response: the size of the `GET` body, no content
+struct HeadBody(BodySize);
+
+impl MessageBody for HeadBody {
+    type Error = Error;
+
+    fn size(&self) -> BodySize {
+        self.0
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        Poll::Ready(None)
+    }
+}
+
+/// The methods in `allow`, with `HEAD` added when `GET` is there
+pub fn allowed_methods(allow: &str) -> Vec<String> {
+    let mut methods: Vec<String> = allow
+        .split(',')
+        .map(|method| method.trim().to_string())
+        .filter(|method| !method.is_empty())
+        .collect();
+    let get = methods
+        .iter()
+        .position(|method| method == Method::GET.as_str());
+    if let (Some(get), false) = (get, methods.iter().any(|m| m == Method::HEAD.as_str())) {
+        methods.insert(get + 1, Method::HEAD.to_string());
+    }
+    methods
+}
+
+/// 405 for `method` on `path`, listing the methods the path accepts
+pub fn method_not_allowed(method: &Method, path: &str, allowed: &[String]) -> HttpResponse {
+    let allow = allowed.join(", ");
+    let message = if allowed.is_empty() {
+        format!("{} is not allowed on {}", method, path)
+    } else {
+        format!("{} is not allowed on {}, use {}", method, path, allow)
+    };
+    let mut response = HttpResponse::MethodNotAllowed();
+    if !allowed.is_empty() {
+        response.insert_header((header::ALLOW, allow));
+    }
+    response.json(ErrorResponse::new(
+        ErrorCode::MethodNotAllowed.as_str(),
+        &message,
+    ))
+}
+
+/// Route `HEAD` as `GET` and give bare 405 responses an `ErrorResponse`
+/// body. Register it before (inside) every other middleware, so they
+/// still see the original method.
+pub async fn method_routing(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let method = req.method().clone();
+    let head = method == Method::HEAD;
+    if head {
+        req.head_mut().method = Method::GET;
+    }
+
+    let res = next.call(req).await?;
+    let res = if res.status() == StatusCode::METHOD_NOT_ALLOWED
+        && res.response().body().size() == BodySize::Sized(0)
+    {
+        let allowed = res
+            .headers()
+            .get(header::ALLOW)
+            .and_then(|allow| allow.to_str().ok())
+            .map(allowed_methods)
+            .unwrap_or_default();
+        let (req, _) = res.into_parts();
+        let response = method_not_allowed(&method, req.path(), &allowed);
+        ServiceResponse::new(req, response)
+    } else {
+        res.map_into_boxed_body()
+    };
+
+    if !head {
+        return Ok(res);
+    }
+    Ok(res.map_body(|_, body| BoxBody::new(HeadBody(body.size()))))
+}
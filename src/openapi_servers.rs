@@ -0,0 +1,102 @@
+//! Servers listed in the OpenAPI document.
+//!
+//! Swagger UI and generated clients send requests to the `servers` of
+//! `/api-doc/openapi.json`, so the list comes from the deployment rather
+//! than the source: `OPENAPI_SERVERS` takes comma-separated `url=description`
+//! pairs. Without it the document names `PUBLIC_BASE_URL`, the origin used
+//! for absolute links elsewhere, or else this server on localhost.
+
+use std::sync::OnceLock;
+
+use utoipa::openapi::server::ServerBuilder;
+use utoipa::openapi::OpenApi;
+use utoipa::Modify;
+
+/// One entry of the `servers` list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiServer {
+    /// Origin without trailing slash, or a path relative to the document
+    pub url: String,
+    pub description: Option<String>,
+}
+
+/// The `servers` list, applied to the document as a [`Modify`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenApiServers(pub Vec<ApiServer>);
+
+static OPENAPI_SERVERS: OnceLock<OpenApiServers> = OnceLock::new();
+
+/// Set the servers of the OpenAPI document. Only the first call has an
+/// effect.
+pub fn init_openapi_servers(servers: OpenApiServers) {
+    let _ = OPENAPI_SERVERS.set(servers);
+}
+
+/// The list from [`init_openapi_servers`], localhost on the default port
+/// before it is called
+pub fn openapi_servers() -> &'static OpenApiServers {
+    OPENAPI_SERVERS.get_or_init(|| crate::config::ServerConfig::default().openapi_servers)
+}
+
+impl OpenApiServers {
+    /// `public_base_url` when one is configured, else `http://localhost`
+    /// on `port`
+    pub fn fallback(public_base_url: Option<&str>, port: u16) -> Self {
+        let server = match public_base_url {
+            Some(base) => ApiServer {
+                url: base.trim_end_matches('/').to_string(),
+                description: Some("Public server".to_string()),
+            },
+            None => ApiServer {
+                url: format!("http://localhost:{}", port),
+                description: Some("Local server".to_string()),
+            },
+        };
+        Self(vec![server])
+    }
+
+    /// Parse the `OPENAPI_SERVERS` value, e.g.
+    /// `https://api.example.com=Production,http://localhost:8080=Local`.
+    /// The description is optional. None when the value lists no server.
+    pub fn parse(value: &str) -> Result<Option<Self>, String> {
+        let mut servers = Vec::new();
+        for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (url, description) = match entry.split_once('=') {
+                Some((url, description)) => (url.trim(), description.trim()),
+                None => (entry, ""),
+            };
+            if !(url.starts_with("http://") || url.starts_with("https://") || url.starts_with('/'))
+            {
+                return Err(format!(
+                    "OPENAPI_SERVERS entry '{}' must start with http://, https:// or /",
+                    entry
+                ));
+            }
+            let url = match url.trim_end_matches('/') {
+                "" => "/",
+                trimmed => trimmed,
+            };
+            servers.push(ApiServer {
+                url: url.to_string(),
+                description: (!description.is_empty()).then(|| description.to_string()),
+            });
+        }
+        Ok((!servers.is_empty()).then_some(Self(servers)))
+    }
+}
+
+impl Modify for OpenApiServers {
+    fn modify(&self, openapi: &mut OpenApi) {
+        openapi.servers = Some(
+            self.0
+                .iter()
+                .map(|server| {
+                    ServerBuilder::new()
+                        .url(&server.url)
+                        .description(server.description.as_deref())
+                        .build()
+                })
+                .collect(),
+        );
+    }
+}
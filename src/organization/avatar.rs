@@ -0,0 +1,127 @@
+//! Member avatars: the uploaded photo, or a generated initials avatar.
+//!
+//! Generated avatars are SVGs rendered on first request and kept in
+//! storage under `organization/avatars/`. The key carries a fingerprint of
+//! the initials, so renaming a member renders a fresh avatar.
+
+use crate::error::ApiError;
+use crate::{AppState, ErrorResponse};
+use actix_web::{web, HttpResponse};
+use sha2::{Digest, Sha256};
+
+/// Storage prefix of generated avatars
+pub const AVATAR_PREFIX: &str = "organization/avatars/";
+
+/// Browsers may reuse a generated avatar for a day
+const AVATAR_CACHE_CONTROL: &str = "public, max-age=86400";
+
+/// URL of the avatar of member `id`
+pub fn avatar_url(id: i32) -> String {
+    format!("/api/organization/members/{}/avatar", id)
+}
+
+/// Uppercase first letters of the first two words of `name`, or `?`
+pub fn initials(name: &str) -> String {
+    let initials: String = name
+        .split_whitespace()
+        .filter_map(|word| word.chars().find(|c| c.is_alphanumeric()))
+        .take(2)
+        .flat_map(char::to_uppercase)
+        .collect();
+    if initials.is_empty() {
+        "?".to_string()
+    } else {
+        initials
+    }
+}
+
+/// Background color of member `id`. The same id always gets the same color.
+pub fn avatar_color(id: i32) -> String {
+    let digest = Sha256::digest(id.to_be_bytes());
+    let hue = u16::from_be_bytes([digest[0], digest[1]]) % 360;
+    format!("hsl({}, 55%, 45%)", hue)
+}
+
+/// Storage key of the generated avatar of member `id` showing `initials`
+pub fn avatar_key(id: i32, initials: &str) -> String {
+    let digest = Sha256::digest(initials.as_bytes());
+    format!("{}{}-{}.svg", AVATAR_PREFIX, id, hex::encode(&digest[..4]))
+}
+
+/// Square SVG avatar of member `id` showing `initials`
+pub fn render_avatar(id: i32, initials: &str) -> String {
+    let text = initials
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="128" height="128" viewBox="0 0 128 128">"#,
+            r#"<rect width="128" height="128" fill="{}"/>"#,
+            r##"<text x="50%" y="50%" dy=".35em" text-anchor="middle" fill="#ffffff" "##,
+            r#"font-family="Helvetica, Arial, sans-serif" font-size="52" font-weight="600">{}</text>"#,
+            "</svg>"
+        ),
+        avatar_color(id),
+        text
+    )
+}
+
+#[utoipa::path(
+    operation_id = "getOrganizationMemberAvatar",
+    get,
+    path = "/api/organization/members/{id}/avatar",
+    tag = "Organization",
+    params(
+        ("id" = i32, Path, description = "Member ID")
+    ),
+    responses(
+        (status = 200, description = "Generated initials avatar", content_type = "image/svg+xml"),
+        (status = 307, description = "Redirect to the member's photo"),
+        (status = 404, description = "Member not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_member_avatar(
+    state: web::Data<AppState>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let id = path.into_inner();
+    let members = state
+        .get_organization_structure()
+        .await
+        .map_err(ApiError::Internal)?;
+    let member = members
+        .iter()
+        .find(|m| m.id == id)
+        .ok_or_else(|| ApiError::NotFound(format!("Member {} not found", id)))?;
+
+    if let Some(photo) = member
+        .photo
+        .as_deref()
+        .map(str::trim)
+        .filter(|photo| !photo.is_empty())
+    {
+        return Ok(HttpResponse::TemporaryRedirect()
+            .append_header(("Location", photo))
+            .finish());
+    }
+
+    let initials = initials(member.name.as_deref().unwrap_or(&member.position));
+    let key = avatar_key(id, &initials);
+    let svg = match state.storage.download_file(&key).await {
+        Ok(svg) => svg,
+        Err(_) => {
+            log::info!("Generating avatar for organization member {}", id);
+            let svg = render_avatar(id, &initials).into_bytes();
+            if let Err(e) = state.storage.upload_file(&key, &svg).await {
+                log::warn!("Failed to store avatar of member {}: {}", id, e);
+            }
+            svg
+        }
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type("image/svg+xml")
+        .insert_header(("Cache-Control", AVATAR_CACHE_CONTROL))
+        .body(svg))
+}
@@ -1,3 +1,5 @@
+pub mod avatar;
 pub mod model;
 pub mod persistence;
 pub mod routes;
+pub mod tree;
@@ -1,4 +1,6 @@
+use crate::organization::avatar::avatar_url;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use utoipa::ToSchema;
 
 #[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
@@ -12,6 +14,54 @@ pub struct OrganizationMember {
     pub role: String,
 }
 
+/// Members keyed by id, the form held in the organization cache. Changes
+/// touch one entry; the list form is built only for reads and persistence.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(transparent)]
+pub struct MemberMap(pub HashMap<i32, OrganizationMember>);
+
+impl From<Vec<OrganizationMember>> for MemberMap {
+    fn from(members: Vec<OrganizationMember>) -> Self {
+        Self(members.into_iter().map(|m| (m.id, m)).collect())
+    }
+}
+
+impl MemberMap {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The members as a list, ordered by id
+    pub fn to_vec(&self) -> Vec<OrganizationMember> {
+        let mut members: Vec<_> = self.0.values().cloned().collect();
+        members.sort_by_key(|m| m.id);
+        members
+    }
+}
+
+/// A member as returned by the API, with an image URL that always resolves
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct MemberResponse {
+    #[serde(flatten)]
+    pub member: OrganizationMember,
+    /// The photo when the member has one, a generated initials avatar otherwise
+    #[schema(example = "/api/organization/members/1/avatar")]
+    pub avatar_url: String,
+}
+
+impl From<OrganizationMember> for MemberResponse {
+    fn from(member: OrganizationMember) -> Self {
+        Self {
+            avatar_url: avatar_url(member.id),
+            member,
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, ToSchema)]
 pub struct CreateMemberRequest {
     pub name: String,
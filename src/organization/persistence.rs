@@ -2,12 +2,21 @@
 //!
 //! This module provides an async worker that persists organization data to Supabase Storage
 //! with debouncing to batch multiple writes.
+//!
+//! Members are cached as a [`MemberMap`] keyed by id. Creating, updating and
+//! deleting a member change one entry while holding `AppState::organization_lock`,
+//! which also assigns new ids, so concurrent requests cannot hand out the
+//! same id or overwrite each other's change.
 
-use crate::organization::model::OrganizationMember;
+use crate::cache::{CacheKind, Cached};
+use crate::organization::model::{
+    CreateMemberRequest, MemberMap, OrganizationMember, UpdateMemberRequest,
+};
 use crate::storage::ObjectStorage;
 use crate::AppState;
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, Mutex, MutexGuard, Notify};
+use tokio::task::JoinHandle;
 
 const ORGANIZATION_FILE: &str = "organization.json";
 pub const ORGANIZATION_CACHE_KEY: &str = "org_members";
@@ -18,23 +27,142 @@ impl AppState {
     /// This ensures we don't double-fetch from storage if data is already in memory.
     pub async fn get_organization_structure(&self) -> Result<Vec<OrganizationMember>, String> {
         // Try cache first
-        if let Some(members) = self.organization_cache.get(ORGANIZATION_CACHE_KEY).await {
+        if let Some(cached) = self.organization_cache.get(ORGANIZATION_CACHE_KEY).await {
             log::info!("Cache hit for organization members (via AppState)");
-            return Ok(members);
+            self.cache_metrics.record_hit(CacheKind::Organization);
+            return Ok(cached.value.to_vec());
         }
 
         log::info!("Cache miss for organization members (via AppState)");
+        self.cache_metrics.record_miss(CacheKind::Organization);
+
+        // Load under the write lock so a change made meanwhile is not
+        // overwritten by the older stored list
+        let _lock = self.organization_lock.lock().await;
+        if let Some(cached) = self.organization_cache.get(ORGANIZATION_CACHE_KEY).await {
+            return Ok(cached.value.to_vec());
+        }
+        match self.download_members().await? {
+            Some(members) => {
+                let list = members.to_vec();
+                self.cache_members(members).await;
+                Ok(list)
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Add a member built from `request` under a newly assigned id
+    pub async fn create_organization_member(
+        &self,
+        request: &CreateMemberRequest,
+    ) -> Result<OrganizationMember, String> {
+        let (mut last_id, mut members) = self.lock_members().await?;
+        // Ids are never reused, even after the newest member is deleted
+        let newest = members.0.keys().copied().max().unwrap_or(0);
+        *last_id = newest.max(*last_id) + 1;
+        let member = OrganizationMember {
+            id: *last_id,
+            name: Some(request.name.clone()),
+            position: request.position.clone(),
+            photo: Some(request.photo.clone()),
+            parent_id: request.parent_id,
+            level: request.level,
+            role: request.role.clone(),
+        };
+        members.0.insert(member.id, member.clone());
+        self.store_members(members).await;
+        Ok(member)
+    }
+
+    /// Apply the fields set in `request` to member `id`. None when there is
+    /// no such member.
+    pub async fn update_organization_member(
+        &self,
+        id: i32,
+        request: &UpdateMemberRequest,
+    ) -> Result<Option<OrganizationMember>, String> {
+        let (_lock, mut members) = self.lock_members().await?;
+        let Some(member) = members.0.get_mut(&id) else {
+            return Ok(None);
+        };
+        if let Some(name) = &request.name {
+            member.name = Some(name.clone());
+        }
+        if let Some(position) = &request.position {
+            member.position = position.clone();
+        }
+        if let Some(photo) = &request.photo {
+            member.photo = Some(photo.clone());
+        }
+        if let Some(parent_id) = request.parent_id {
+            member.parent_id = Some(parent_id);
+        }
+        if let Some(level) = request.level {
+            member.level = level;
+        }
+        if let Some(role) = &request.role {
+            member.role = role.clone();
+        }
+        let member = member.clone();
+        self.store_members(members).await;
+        Ok(Some(member))
+    }
+
+    /// Remove member `id`. Returns false when there is no such member.
+    pub async fn delete_organization_member(&self, id: i32) -> Result<bool, String> {
+        let (_lock, mut members) = self.lock_members().await?;
+        if members.0.remove(&id).is_none() {
+            return Ok(false);
+        }
+        self.store_members(members).await;
+        Ok(true)
+    }
 
-        // Fetch from storage
+    /// Replace every member when restoring a backup. organization.json is
+    /// written before returning, so a failed write is reported to the caller
+    /// instead of being logged by the persistence worker.
+    pub async fn restore_organization_members(
+        &self,
+        members: Vec<OrganizationMember>,
+    ) -> Result<(), String> {
+        let _lock = self.organization_lock.lock().await;
+        let bytes = serde_json::to_vec(&members)
+            .map_err(|e| format!("Failed to serialize organization data: {}", e))?;
+        self.storage
+            .upload_file(ORGANIZATION_FILE, &bytes)
+            .await
+            .map_err(|e| format!("Failed to write organization data: {}", e))?;
+        // Also queued, so an update still waiting in the worker cannot
+        // overwrite the restored file with older members
+        self.store_members(MemberMap::from(members)).await;
+        Ok(())
+    }
+
+    /// Take the write lock and load the current members. The guard holds the
+    /// last assigned id; keep it until the changed members are stored.
+    async fn lock_members(&self) -> Result<(MutexGuard<'_, i32>, MemberMap), String> {
+        let last_id = self.organization_lock.lock().await;
+        let members = match self.organization_cache.get(ORGANIZATION_CACHE_KEY).await {
+            Some(cached) => {
+                self.cache_metrics.record_hit(CacheKind::Organization);
+                cached.value
+            }
+            None => {
+                self.cache_metrics.record_miss(CacheKind::Organization);
+                self.download_members().await?.unwrap_or_default()
+            }
+        };
+        Ok((last_id, members))
+    }
+
+    /// The stored members, None when the file cannot be downloaded
+    async fn download_members(&self) -> Result<Option<MemberMap>, String> {
         match self.storage.download_file(ORGANIZATION_FILE).await {
             Ok(bytes) => {
                 let members: Vec<OrganizationMember> = serde_json::from_slice(&bytes)
                     .map_err(|e| format!("Failed to parse organization data: {}", e))?;
-
-                self.organization_cache
-                    .insert(ORGANIZATION_CACHE_KEY.to_string(), members.clone())
-                    .await;
-                Ok(members)
+                Ok(Some(MemberMap::from(members)))
             }
             Err(e) => {
                 // If file doesn't exist, return empty list
@@ -42,10 +170,35 @@ impl AppState {
                     "Failed to download organization data: {}. Assuming empty.",
                     e
                 );
-                Ok(Vec::new())
+                Ok(None)
             }
         }
     }
+
+    async fn cache_members(&self, members: MemberMap) {
+        self.organization_cache
+            .insert(ORGANIZATION_CACHE_KEY.to_string(), Cached::new(members))
+            .await;
+        self.refresh_cache_entries(CacheKind::Organization).await;
+    }
+
+    /// Cache `members` and queue them for the persistence worker. Callers
+    /// hold the write lock, so updates are queued in the order they were
+    /// made.
+    async fn store_members(&self, members: MemberMap) {
+        let list = members.to_vec();
+        self.cache_members(members).await;
+        log::info!("Organization cache updated with {} members", list.len());
+
+        // Send to background worker for async persistence to storage
+        // This makes the response fast while ensuring eventual consistency
+        if let Err(e) = self.organization_persist_sender.send(list).await {
+            log::error!("Failed to queue organization data for persistence: {}", e);
+            // Data will be available from cache until next restart
+        } else {
+            log::debug!("Organization data queued for background persistence");
+        }
+    }
 }
 
 /// Starts the background persistence worker.
@@ -53,12 +206,40 @@ impl AppState {
 /// The worker receives organization data via channel and persists it to storage.
 /// It uses debouncing to batch multiple writes within a short time window.
 pub async fn start_persistence_worker(
+    receiver: mpsc::Receiver<Vec<OrganizationMember>>,
+    storage: Arc<dyn ObjectStorage + Send + Sync>,
+) {
+    run_persistence_worker(receiver, storage, Arc::new(Notify::new())).await;
+}
+
+/// Same as [`start_persistence_worker`], but stops when `shutdown` is notified.
+///
+/// On shutdown the channel is closed, the debounce delay is cut short and any
+/// pending update is written before the worker returns.
+pub async fn run_persistence_worker(
     mut receiver: mpsc::Receiver<Vec<OrganizationMember>>,
     storage: Arc<dyn ObjectStorage + Send + Sync>,
+    shutdown: Arc<Notify>,
 ) {
     log::info!("Organization persistence worker started");
 
-    while let Some(members) = receiver.recv().await {
+    let mut stopping = false;
+    while !stopping {
+        let members = tokio::select! {
+            received = receiver.recv() => match received {
+                Some(members) => members,
+                None => break,
+            },
+            _ = shutdown.notified() => {
+                stopping = true;
+                receiver.close();
+                match receiver.try_recv() {
+                    Ok(members) => members,
+                    Err(_) => break,
+                }
+            }
+        };
+
         // Debounce: drain any pending messages to get the latest
         let mut latest = members;
         while let Ok(newer) = receiver.try_recv() {
@@ -67,7 +248,15 @@ pub async fn start_persistence_worker(
         }
 
         // Small delay to allow more batching if writes come in rapid succession
-        tokio::time::sleep(tokio::time::Duration::from_millis(DEBOUNCE_MS)).await;
+        if !stopping {
+            tokio::select! {
+                _ = tokio::time::sleep(tokio::time::Duration::from_millis(DEBOUNCE_MS)) => {}
+                _ = shutdown.notified() => {
+                    stopping = true;
+                    receiver.close();
+                }
+            }
+        }
 
         // Drain again after delay to capture any writes during the wait
         while let Ok(newer) = receiver.try_recv() {
@@ -75,26 +264,78 @@ pub async fn start_persistence_worker(
             latest = newer;
         }
 
-        // Persist to storage
-        match serde_json::to_vec(&latest) {
-            Ok(json_data) => {
-                if let Err(e) = storage.upload_file(ORGANIZATION_FILE, &json_data).await {
-                    log::error!("Failed to persist organization data to storage: {}", e);
-                } else {
-                    log::info!(
-                        "Organization data persisted to storage ({} members)",
-                        latest.len()
-                    );
-                }
-            }
-            Err(e) => {
-                log::error!(
-                    "Failed to serialize organization data for persistence: {}",
-                    e
+        persist_members(storage.as_ref(), &latest).await;
+    }
+
+    log::info!("Organization persistence worker stopped");
+}
+
+async fn persist_members(
+    storage: &(dyn ObjectStorage + Send + Sync),
+    members: &[OrganizationMember],
+) {
+    match serde_json::to_vec(members) {
+        Ok(json_data) => {
+            if let Err(e) = storage.upload_file(ORGANIZATION_FILE, &json_data).await {
+                log::error!("Failed to persist organization data to storage: {}", e);
+            } else {
+                log::info!(
+                    "Organization data persisted to storage ({} members)",
+                    members.len()
                 );
             }
         }
+        Err(e) => {
+            log::error!(
+                "Failed to serialize organization data for persistence: {}",
+                e
+            );
+        }
     }
+}
 
-    log::info!("Organization persistence worker stopped");
+/// Handle to a spawned persistence worker, used to flush it on shutdown.
+pub struct PersistenceWorker {
+    shutdown: Arc<Notify>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl PersistenceWorker {
+    /// Spawn the worker on the current runtime
+    pub fn spawn(
+        receiver: mpsc::Receiver<Vec<OrganizationMember>>,
+        storage: Arc<dyn ObjectStorage + Send + Sync>,
+    ) -> Self {
+        let shutdown = Arc::new(Notify::new());
+        let worker_shutdown = shutdown.clone();
+        let handle = tokio::spawn(async move {
+            run_persistence_worker(receiver, storage, worker_shutdown).await;
+        });
+
+        Self {
+            shutdown,
+            handle: Mutex::new(Some(handle)),
+        }
+    }
+
+    /// Handle for a state built without a worker. Updates sent to the
+    /// persistence channel are dropped and `flush` does nothing.
+    pub fn disabled() -> Self {
+        Self {
+            shutdown: Arc::new(Notify::new()),
+            handle: Mutex::new(None),
+        }
+    }
+
+    /// Ask the worker to write any pending update and wait for it to exit.
+    /// Calling this more than once is a no-op.
+    pub async fn flush(&self) {
+        let handle = self.handle.lock().await.take();
+        if let Some(handle) = handle {
+            self.shutdown.notify_one();
+            if let Err(e) = handle.await {
+                log::error!("Organization persistence worker panicked: {}", e);
+            }
+        }
+    }
 }
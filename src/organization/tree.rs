@@ -0,0 +1,92 @@
+//! Nested view of the flat organization member list.
+//!
+//! Members are stored flat with a `parent_id`; the tree is rebuilt on
+//! demand from whatever the organization cache holds.
+
+use crate::organization::model::OrganizationMember;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// A member together with the members reporting to it
+#[derive(Serialize, Debug, Clone)]
+pub struct OrganizationNode {
+    #[serde(flatten)]
+    pub member: OrganizationMember,
+    pub children: Vec<OrganizationNode>,
+}
+
+/// Nest `members` under their parents.
+///
+/// Members without a parent, or whose parent is missing, become roots.
+/// Siblings are ordered by level, then id. A `parent_id` cycle is broken at
+/// the first member of the cycle in input order, so every member appears
+/// exactly once.
+pub fn build_tree(members: &[OrganizationMember]) -> Vec<OrganizationNode> {
+    let ids: HashSet<i32> = members.iter().map(|m| m.id).collect();
+    let mut children: HashMap<i32, Vec<&OrganizationMember>> = HashMap::new();
+    for member in members {
+        if let Some(parent_id) = member.parent_id.filter(|id| ids.contains(id)) {
+            children.entry(parent_id).or_default().push(member);
+        }
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by_key(|m| (m.level, m.id));
+    }
+
+    let mut roots: Vec<&OrganizationMember> = members
+        .iter()
+        .filter(|m| m.parent_id.is_none_or(|id| !ids.contains(&id)))
+        .collect();
+    roots.sort_by_key(|m| (m.level, m.id));
+
+    let mut visited = HashSet::new();
+    let mut tree: Vec<OrganizationNode> = roots
+        .into_iter()
+        .filter_map(|root| build_node(root, &children, &mut visited))
+        .collect();
+    // Whatever is left hangs off a cycle
+    for member in members {
+        if let Some(node) = build_node(member, &children, &mut visited) {
+            tree.push(node);
+        }
+    }
+    tree
+}
+
+fn build_node(
+    member: &OrganizationMember,
+    children: &HashMap<i32, Vec<&OrganizationMember>>,
+    visited: &mut HashSet<i32>,
+) -> Option<OrganizationNode> {
+    if !visited.insert(member.id) {
+        return None;
+    }
+    let nested = children
+        .get(&member.id)
+        .into_iter()
+        .flatten()
+        .filter_map(|child| build_node(child, children, visited))
+        .collect();
+    Some(OrganizationNode {
+        member: member.clone(),
+        children: nested,
+    })
+}
+
+/// Ancestors of member `id`, from the top of the structure down to its
+/// direct parent. Empty for roots and unknown ids.
+pub fn ancestors(members: &[OrganizationMember], id: i32) -> Vec<&OrganizationMember> {
+    let by_id: HashMap<i32, &OrganizationMember> = members.iter().map(|m| (m.id, m)).collect();
+    let mut chain = Vec::new();
+    let mut seen = HashSet::from([id]);
+    let mut parent_id = by_id.get(&id).and_then(|m| m.parent_id);
+    while let Some(parent) = parent_id.and_then(|pid| by_id.get(&pid)) {
+        if !seen.insert(parent.id) {
+            break;
+        }
+        chain.push(*parent);
+        parent_id = parent.parent_id;
+    }
+    chain.reverse();
+    chain
+}
@@ -0,0 +1,147 @@
+//! Assets embedded in the post list.
+//!
+//! Cards on the homepage show a few images of each post. With
+//! `?include=assets:N` the first `N` assets of every post's folder are
+//! returned with the list, fetched for all posts in one query, instead of
+//! one `/assets/by-ids` call per card.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use super::models::Post;
+use super::translation::posts_cache_key;
+use crate::asset::models::AssetSummary;
+use crate::cache::{CacheKind, Cached};
+use crate::db::DbError;
+use crate::{ApiError, AppState};
+
+/// Most assets embedded per post
+pub const MAX_EMBEDDED_ASSETS: i64 = 10;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct IncludeQuery {
+    /// Comma-separated extras: `assets:N` embeds the first `N` (at most
+    /// 10) assets of each post as `assets`. `cover` is accepted as well,
+    /// the cover is always returned as `cover_url`.
+    #[param(example = "cover,assets:3")]
+    pub include: Option<String>,
+}
+
+impl IncludeQuery {
+    /// Assets to embed per post, if any
+    pub fn embedded_assets(&self) -> Result<Option<i64>, ApiError> {
+        match &self.include {
+            Some(include) => parse_include(include),
+            None => Ok(None),
+        }
+    }
+}
+
+/// The number of assets `include` asks to embed per post
+pub fn parse_include(include: &str) -> Result<Option<i64>, ApiError> {
+    let mut assets = None;
+    for item in include.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+        if item == "cover" {
+            continue;
+        }
+        let count = item
+            .strip_prefix("assets:")
+            .and_then(|count| count.parse::<i64>().ok())
+            .ok_or_else(|| {
+                ApiError::BadRequest(format!(
+                    "Unknown include {:?}, expected cover or assets:N",
+                    item
+                ))
+            })?;
+        if !(1..=MAX_EMBEDDED_ASSETS).contains(&count) {
+            return Err(ApiError::BadRequest(format!(
+                "assets:N must be between 1 and {}",
+                MAX_EMBEDDED_ASSETS
+            )));
+        }
+        assets = Some(count);
+    }
+    Ok(assets)
+}
+
+/// An asset of a post, as returned by the embedding query
+#[derive(sqlx::FromRow)]
+struct EmbeddedAsset {
+    post_id: Uuid,
+    id: Uuid,
+    filename: String,
+}
+
+impl AppState {
+    /// Set `assets` of every post to the first `limit` assets of its
+    /// folder, oldest first like the default cover
+    pub async fn embed_post_assets(
+        &self,
+        mut posts: Vec<Post>,
+        limit: i64,
+    ) -> Result<Vec<Post>, DbError> {
+        let post_ids: Vec<Uuid> = posts.iter().map(|post| post.id).collect();
+        let rows: Vec<EmbeddedAsset> = self
+            .guarded(self.timed("embed_post_assets", async {
+                sqlx::query_as(
+                    "SELECT p.id AS post_id, a.id, a.filename
+                     FROM posts p
+                     CROSS JOIN LATERAL (
+                         SELECT a.id, a.filename, a.created_at
+                         FROM asset_folders af
+                         JOIN assets a ON a.id = af.asset_id
+                         WHERE af.folder_id = p.folder_uuid
+                         ORDER BY a.created_at, a.id
+                         LIMIT $2
+                     ) a
+                     WHERE p.id = ANY($1)
+                     ORDER BY p.id, a.created_at, a.id",
+                )
+                .bind(&post_ids)
+                .bind(limit)
+                .fetch_all(&self.pool)
+                .await
+            }))
+            .await?;
+
+        let mut by_post: HashMap<Uuid, Vec<AssetSummary>> = HashMap::new();
+        for row in rows {
+            by_post
+                .entry(row.post_id)
+                .or_default()
+                .push(AssetSummary::new(row.id, &row.filename));
+        }
+        for post in &mut posts {
+            post.assets = Some(by_post.remove(&post.id).unwrap_or_default());
+        }
+        Ok(posts)
+    }
+
+    /// [`AppState::get_all_posts_cached_in`] with `limit` assets embedded
+    /// per post, cached per language and limit
+    pub async fn get_all_posts_cached_with_assets(
+        &self,
+        lang: &str,
+        limit: i64,
+    ) -> Result<Vec<Post>, DbError> {
+        let key = format!("{}:assets={}", posts_cache_key(lang), limit);
+        if let Some(cached) = self.post_cache.get(&key).await {
+            log::info!("Cache hit for {}", key);
+            self.cache_metrics.record_hit(CacheKind::Posts);
+            return Ok(cached.value);
+        }
+
+        log::info!("Cache miss for {}", key);
+        self.cache_metrics.record_miss(CacheKind::Posts);
+        let posts = self.get_all_posts_cached_in(lang).await?;
+        let posts = self.embed_post_assets(posts, limit).await?;
+        self.post_cache
+            .insert(key, Cached::new(posts.clone()))
+            .await;
+        self.refresh_cache_entries(CacheKind::Posts).await;
+        Ok(posts)
+    }
+}
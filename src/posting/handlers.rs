@@ -1,20 +1,28 @@
 use actix_web::{
-    HttpResponse, Responder,
+    dev::Payload,
     web::{self, Path, Query},
+    FromRequest, HttpMessage, HttpRequest, HttpResponse,
 };
-use log::{debug, error, info};
+use futures_util::future::LocalBoxFuture;
+use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{
-    ErrorResponse,
+    ApiError, ErrorResponse,
     db::AppState,
     posting::models::{CreatePostingRequest, Post, UpdatePostingRequest},
+    posting::embed::IncludeQuery,
+    posting::translation::{LangQuery, DEFAULT_LANG},
 };
 use chrono::{NaiveDate};
 use uuid::Uuid;
 
+use crate::asset::folder_name::post_folder_name;
+use crate::asset::handlers::delete_asset_by_id;
 use crate::posting::multipart_parser::MultipartParser;
+use crate::storage::upload_with_unique_name;
+use crate::webhook::WebhookEvent;
 
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -28,6 +36,10 @@ pub struct PostingResponse {
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
     pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
     pub asset_ids: Vec<Uuid>,  // Added for asset associations
+    pub cover_asset_id: Option<Uuid>,
+    pub cover_url: Option<String>,
+    pub translations_available: Vec<String>,
+    pub comment_count: i64,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -39,6 +51,28 @@ pub struct PaginationParams {
     pub limit: i32,
 }
 
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct FormatQuery {
+    /// `display` adds `display` with the dates written out in Indonesian,
+    /// e.g. "12 November 2025"
+    #[param(example = "display")]
+    pub format: Option<String>,
+}
+
+impl FormatQuery {
+    /// Whether the dates should be written out for display
+    pub fn display(&self) -> Result<bool, ApiError> {
+        match self.format.as_deref().map(str::trim) {
+            None | Some("") => Ok(false),
+            Some("display") => Ok(true),
+            Some(other) => Err(ApiError::BadRequest(format!(
+                "Unknown format {:?}, expected display",
+                other
+            ))),
+        }
+    }
+}
+
 fn default_page() -> i32 {
     1
 }
@@ -47,89 +81,172 @@ fn default_limit() -> i32 {
     20
 }
 
+/// Body of a create-posting request: multipart form when the content type is
+/// `multipart/*`, JSON otherwise. Unlike `web::Either`, JSON errors are not
+/// swallowed by the multipart fallback, so they reach the `JsonConfig` handler.
+pub enum CreatePostingPayload {
+    Json(web::Json<CreatePostingRequest>),
+    Multipart(actix_multipart::Multipart),
+}
 
+impl FromRequest for CreatePostingPayload {
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let is_multipart = req
+            .mime_type()
+            .ok()
+            .flatten()
+            .is_some_and(|mime| mime.type_() == "multipart");
+
+        if is_multipart {
+            let multipart = actix_multipart::Multipart::from_request(req, payload);
+            Box::pin(async move { multipart.await.map(Self::Multipart) })
+        } else {
+            let json = web::Json::<CreatePostingRequest>::from_request(req, payload);
+            Box::pin(async move { json.await.map(Self::Json) })
+        }
+    }
+}
+
+
+
+/// Reject a cover that is not an asset in the folder `folder_name`
+async fn check_cover_in_folder(
+    data: &AppState,
+    folder_name: Option<&str>,
+    cover_asset_id: Uuid,
+) -> Result<(), ApiError> {
+    let in_folder = match folder_name {
+        Some(folder_name) => data
+            .get_folder_contents(folder_name)
+            .await
+            .map_err(ApiError::database("Failed to check cover asset"))?
+            .is_some_and(|asset_ids| asset_ids.contains(&cover_asset_id)),
+        None => false,
+    };
+    if in_folder {
+        Ok(())
+    } else {
+        Err(ApiError::BadRequest(format!(
+            "Cover asset {} is not in the post's folder",
+            cover_asset_id
+        )))
+    }
+}
 
 #[utoipa::path(
+    operation_id = "listPostings",
     context_path = "/api",
     tag = "Posting Service",
     get,
     path = "/postings",
     responses(
         (status = 200, description = "List of posts with pagination", body = [Post]),
-        (status = 500, description = "Internal Server Error", body = ErrorResponse)
+        (status = 400, description = "Unsupported language, unknown include or format", body = ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = ErrorResponse),
+        (status = 503, description = "Database unavailable, retry after the Retry-After header", body = ErrorResponse)
     ),
     params(
         ("page" = Option<i32>, Query, description = "Page number (default: 1)"),
-        ("limit" = Option<i32>, Query, description = "Number of items per page (default: 20)")
+        ("limit" = Option<i32>, Query, description = "Number of items per page (default: 20)"),
+        LangQuery,
+        IncludeQuery,
+        FormatQuery
     )
 )]
-pub async fn get_all_postings(data: web::Data<AppState>, pagination: Query<PaginationParams>) -> impl Responder {
+pub async fn get_all_postings(
+    data: web::Data<AppState>,
+    pagination: Query<PaginationParams>,
+    lang: Query<LangQuery>,
+    include: Query<IncludeQuery>,
+    format: Query<FormatQuery>,
+) -> Result<HttpResponse, ApiError> {
     info!("Executing get_all_postings handler with pagination");
     debug!("Attempting to fetch posts with pagination: page={}, limit={}", pagination.page, pagination.limit);
+    let lang = lang.lang()?;
+    let embedded_assets = include.embedded_assets()?;
+    let display = format.display()?;
 
     let offset = (pagination.page - 1) * pagination.limit;
 
-    match data.get_posts_smart_cached(pagination.limit, offset).await {
-        Ok(posts) => {
-            info!(
-                "Successfully fetched {} posts using smart cache strategy.",
-                posts.len()
-            );
-            HttpResponse::Ok().json(posts)
-        }
-        Err(e) => {
-            error!("Failed to get posts: {}", e);
-            HttpResponse::InternalServerError()
-                .json(ErrorResponse::internal_error("Failed to retrieve posts"))
-        }
+    let mut posts = data
+        .get_posts_smart_cached(pagination.limit, offset, lang, embedded_assets)
+        .await
+        .map_err(ApiError::database("Failed to retrieve posts"))?;
+    if display {
+        posts.iter_mut().for_each(Post::format_for_display);
     }
+
+    info!(
+        "Successfully fetched {} posts using smart cache strategy.",
+        posts.len()
+    );
+    Ok(HttpResponse::Ok().json(posts))
 }
 
 #[utoipa::path(
+    operation_id = "getPosting",
     context_path = "/api",
     tag = "Posting Service",
     get,
     path = "/postings/{id}",
     responses(
         (status = 200, description = "Post found", body = Post),
+        (status = 400, description = "Unsupported language or format", body = ErrorResponse),
         (status = 404, description = "Post not found", body = ErrorResponse),
         (status = 500, description = "Internal Server Error", body = ErrorResponse)
     ),
     params(
-        ("id" = Uuid, Path, description = "ID of the post to retrieve")
+        ("id" = Uuid, Path, description = "ID of the post to retrieve"),
+        LangQuery,
+        FormatQuery
     )
 )]
-pub async fn get_posting_by_id(id: Path<Uuid>, data: web::Data<AppState>) -> impl Responder {
+pub async fn get_posting_by_id(
+    id: Path<Uuid>,
+    lang: Query<LangQuery>,
+    format: Query<FormatQuery>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
     let post_id = id.into_inner();
+    let lang = lang.lang()?;
+    let display = format.display()?;
     info!(
         "Executing get_posting_by_id handler for ID: {:?}",
         post_id
     );
     debug!("Attempting to fetch post with ID {:?}.", post_id);
-    match data.get_post_by_id(&post_id).await {
-        Ok(Some(post)) => {
-            info!("Successfully fetched post with ID: {:?}", post_id);
-            HttpResponse::Ok().json(post)
-        }
-        Ok(None) => {
+    let mut post = data
+        .get_post_by_id(&post_id)
+        .await
+        .map_err(ApiError::database("Failed to retrieve post"))?
+        .ok_or_else(|| {
             error!("Post not found in database for ID: {:?}", post_id);
-            HttpResponse::NotFound().json(ErrorResponse::not_found(&format!(
-                "Post with ID {:?} not found",
-                post_id
-            )))
-        }
-        Err(e) => {
-            error!(
-                "Failed to get post by ID '{}' from database: {}",
-                post_id, e
-            );
-            HttpResponse::InternalServerError()
-                .json(ErrorResponse::internal_error("Failed to retrieve post"))
+            ApiError::NotFound(format!("Post with ID {:?} not found", post_id))
+        })?;
+
+    if lang != DEFAULT_LANG {
+        let translation = data
+            .get_post_translation(&post_id, lang)
+            .await
+            .map_err(ApiError::database("Failed to retrieve translation"))?;
+        // Untranslated posts are shown in Indonesian
+        if let Some(translation) = translation {
+            post.translate(&translation);
         }
     }
+    if display {
+        post.format_for_display();
+    }
+
+    info!("Successfully fetched post with ID: {:?}", post_id);
+    Ok(HttpResponse::Ok().json(post))
 }
 
 #[utoipa::path(
+    operation_id = "createPosting",
     context_path = "/api",
     tag = "Posting Service",
     post,
@@ -138,62 +255,73 @@ pub async fn get_posting_by_id(id: Path<Uuid>, data: web::Data<AppState>) -> imp
     responses(
         (status = 201, description = "Post created successfully", body = Post),
         (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 413, description = "An attached file exceeds the upload size limit", body = ErrorResponse),
         (status = 500, description = "Internal Server Error", body = ErrorResponse)
     )
 )]
 pub async fn create_posting(
-    req: actix_web::web::Either<web::Json<CreatePostingRequest>, actix_multipart::Multipart>,
+    req: CreatePostingPayload,
     data: web::Data<AppState>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     info!("Executing create_posting handler");
     debug!("Received request to create post.");
 
     match req {
-        actix_web::web::Either::Left(json_req) => {
-            let folder_id = format!("posts/{}", Uuid::new_v4());
-
-            let new_post = Post::new(
+        CreatePostingPayload::Json(json_req) => {
+            let mut new_post = Post::new(
                 json_req.title.clone(),
                 json_req.category.clone(),
                 json_req.excerpt.clone(),
-                Some(folder_id),
+                None,
             );
+            let folder_id = post_folder_name(new_post.id);
 
-            debug!("Attempting to insert new post into database.");
-            if let Err(e) = data.insert_post(&new_post).await {
-                error!("Failed to insert new post into database: {}", e);
-                return HttpResponse::InternalServerError()
-                    .json(ErrorResponse::internal_error("Failed to create post"));
+            if let Some(cover_asset_id) = json_req.cover_asset_id {
+                check_cover_in_folder(&data, Some(&folder_id), cover_asset_id).await?;
             }
+            new_post.folder_id = Some(folder_id);
+
+            debug!("Attempting to insert new post into database.");
+            data.insert_post(&new_post)
+                .await
+                .map_err(ApiError::database("Failed to create post"))?;
 
             info!("New post created successfully with ID: {:?}", new_post.id);
-            HttpResponse::Created().json(new_post)
+            data.publish_change(WebhookEvent::PostCreated, new_post.id, &new_post);
+            Ok(HttpResponse::Created().json(new_post))
         }
-        actix_web::web::Either::Right(multipart) => {
+        CreatePostingPayload::Multipart(multipart) => {
             let parsed_data = match MultipartParser::parse_posting_multipart(multipart).await {
                 Ok(data) => data,
                 Err(e) => {
                     error!("Failed to parse multipart data: {}", e);
-                    return e.into();
+                    return Ok(e.into());
                 }
             };
+            for (file_data, original_filename) in &parsed_data.files_data {
+                data.upload
+                    .check_size(original_filename, file_data.len())
+                    .map_err(ApiError::PayloadTooLarge)?;
+            }
 
             // Create a new post with a folder for its assets
-            let folder_id = format!("posts/{}", Uuid::new_v4());
-            let new_post = Post::new(
+            let mut new_post = Post::new(
                 parsed_data.title,
                 parsed_data.category,
                 parsed_data.excerpt,
-                Some(folder_id.clone()),
+                None,
             );
+            let folder_id = post_folder_name(new_post.id);
+            if let Some(cover_asset_id) = parsed_data.cover_asset_id {
+                check_cover_in_folder(&data, Some(&folder_id), cover_asset_id).await?;
+            }
+            new_post.folder_id = Some(folder_id.clone());
 
             // Insert the post into the database
             debug!("Attempting to insert new post into database.");
-            if let Err(e) = data.insert_post(&new_post).await {
-                error!("Failed to insert new post into database: {}", e);
-                return HttpResponse::InternalServerError()
-                    .json(ErrorResponse::internal_error("Failed to create post"));
-            }
+            data.insert_post(&new_post)
+                .await
+                .map_err(ApiError::database("Failed to create post"))?;
 
             info!("New post created successfully with ID: {:?}", new_post.id);
 
@@ -206,22 +334,24 @@ pub async fn create_posting(
                     .and_then(std::ffi::OsStr::to_str)
                     .unwrap_or("dat");
 
-                let storage_filename = format!("{}_{:03}.{}",
-                    new_post.id,
-                    i,
-                    file_extension
-                );
-
-                let result = data.storage.upload_file(&storage_filename, &file_data).await;
+                // A taken name gets a random suffix instead of overwriting
+                let result = upload_with_unique_name(data.storage.as_ref(), file_data, |attempt| {
+                    if attempt == 0 {
+                        format!("{}_{:03}.{}", new_post.id, i, file_extension)
+                    } else {
+                        format!("{}_{:03}_{}.{}", new_post.id, i, &Uuid::new_v4().simple().to_string()[..8], file_extension)
+                    }
+                })
+                .await;
 
                 match result {
-                    Ok(_) => {
+                    Ok(storage_filename) => {
                         info!("File uploaded successfully to Supabase: {}", storage_filename);
 
                         let asset = crate::asset::models::Asset::new(
                             original_filename.clone(),
                             storage_filename.clone(),
-                            format!("/assets/serve/{}", storage_filename),
+                            crate::asset::public_url::serve_path(&storage_filename),
                             None,
                         );
 
@@ -230,6 +360,7 @@ pub async fn create_posting(
                             // Continue processing other files even if one fails
                             continue;
                         }
+                        data.publish_change(WebhookEvent::AssetUploaded, asset.id, &asset);
 
                         // Associate the asset with the post's folder
                         match data.get_folder_contents(&folder_id).await {
@@ -261,11 +392,18 @@ pub async fn create_posting(
                 }
             }
 
-            HttpResponse::Created().json(new_post)
+            // Read back for the default cover, the first uploaded image
+            let new_post = match data.get_post_by_id(&new_post.id).await {
+                Ok(Some(post)) => post,
+                _ => new_post,
+            };
+            data.publish_change(WebhookEvent::PostCreated, new_post.id, &new_post);
+            Ok(HttpResponse::Created().json(new_post))
         }
     }
 }
 #[utoipa::path(
+    operation_id = "updatePosting",
     context_path = "/api",
     tag = "Posting Service",
     put,
@@ -273,7 +411,7 @@ pub async fn create_posting(
     request_body = UpdatePostingRequest,
     responses(
         (status = 200, description = "Post updated successfully", body = Post),
-        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 400, description = "Invalid request, or the cover is not in the post's folder", body = ErrorResponse),
         (status = 404, description = "Post not found", body = ErrorResponse),
         (status = 500, description = "Internal Server Error", body = ErrorResponse)
     ),
@@ -285,7 +423,7 @@ pub async fn update_posting(
     id: Path<Uuid>,
     req: web::Json<UpdatePostingRequest>,
     data: web::Data<AppState>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let post_id = id.into_inner();
     info!("Executing update_posting handler for ID: {:?}", post_id);
 
@@ -293,58 +431,62 @@ pub async fn update_posting(
         "Attempting to fetch post with ID {:?} for update.",
         post_id
     );
-    match data.get_post_by_id(&post_id).await {
-        Ok(Some(mut post)) => {
-            info!(
-                "Found post with ID {:?}. Proceeding with update.",
-                post_id
-            );
-            if let Some(title) = &req.title {
-                debug!("Updating post title for id: {:?}", post_id);
-                post.title = title.clone();
-            }
-            if let Some(category) = &req.category {
-                debug!("Updating post category for id: {:?}", post_id);
-                post.category = category.clone();
-            }
-            if let Some(excerpt) = &req.excerpt {
-                debug!("Updating post excerpt for id: {:?}", post_id);
-                post.excerpt = excerpt.clone();
-            }
-            if let Some(folder_id) = &req.folder_id {
-                debug!("Updating post folder_id for id: {:?}", post_id);
-                post.folder_id = Some(folder_id.clone());
-            }
-
-            debug!(
-                "Attempting to update post with ID {:?} in database.",
-                post_id
-            );
-            if let Err(e) = data.update_post(&post).await {
-                error!("Failed to update post in database: {}", e);
-                return HttpResponse::InternalServerError()
-                    .json(ErrorResponse::internal_error("Failed to update post"));
-            }
-
-            info!("Post with id: {:?} updated successfully", post_id);
-            HttpResponse::Ok().json(post)
-        }
-        Ok(None) => {
+    let mut post = data
+        .get_post_by_id(&post_id)
+        .await
+        .map_err(ApiError::database("Failed to retrieve post for update"))?
+        .ok_or_else(|| {
             error!("Post not found for update: {:?}", post_id);
-            HttpResponse::NotFound().json(ErrorResponse::not_found(&format!(
-                "Post with ID {:?} not found",
-                post_id
-            )))
-        }
-        Err(e) => {
-            error!("Failed to retrieve post for update from database: {}", e);
-            HttpResponse::InternalServerError().json(ErrorResponse::internal_error(
-                "Failed to retrieve post for update",
-            ))
+            ApiError::NotFound(format!("Post with ID {:?} not found", post_id))
+        })?;
+
+    info!(
+        "Found post with ID {:?}. Proceeding with update.",
+        post_id
+    );
+    if let Some(title) = &req.title {
+        debug!("Updating post title for id: {:?}", post_id);
+        post.title = title.clone();
+    }
+    if let Some(category) = &req.category {
+        debug!("Updating post category for id: {:?}", post_id);
+        post.category = category.clone();
+    }
+    if let Some(excerpt) = &req.excerpt {
+        debug!("Updating post excerpt for id: {:?}", post_id);
+        post.excerpt = excerpt.clone();
+    }
+    if let Some(folder_id) = &req.folder_id {
+        debug!("Updating post folder_id for id: {:?}", post_id);
+        post.folder_id = Some(folder_id.clone());
+    }
+    if let Some(cover_asset_id) = req.cover_asset_id {
+        debug!("Updating post cover for id: {:?}", post_id);
+        if let Some(cover_asset_id) = cover_asset_id {
+            check_cover_in_folder(&data, post.folder_id.as_deref(), cover_asset_id).await?;
         }
+        post.cover_asset_id = cover_asset_id;
     }
+
+    debug!(
+        "Attempting to update post with ID {:?} in database.",
+        post_id
+    );
+    data.update_post(&post)
+        .await
+        .map_err(ApiError::database("Failed to update post"))?;
+
+    info!("Post with id: {:?} updated successfully", post_id);
+    // Read back for the cover, which depends on the folder's assets
+    let post = match data.get_post_by_id(&post_id).await {
+        Ok(Some(updated)) => updated,
+        _ => post,
+    };
+    data.publish_change(WebhookEvent::PostUpdated, post_id, &post);
+    Ok(HttpResponse::Ok().json(post))
 }
 #[utoipa::path(
+    operation_id = "deletePosting",
     context_path = "/api",
     tag = "Posting Service",
     delete,
@@ -358,30 +500,53 @@ pub async fn update_posting(
         ("id" = Uuid, Path, description = "ID of the post to delete")
     )
 )]
-pub async fn delete_posting(id: Path<Uuid>, data: web::Data<AppState>) -> impl Responder {
+pub async fn delete_posting(id: Path<Uuid>, data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
     let post_id = id.into_inner();
     info!("Executing delete_posting handler for ID: {:?}", post_id);
 
+    // Only the post's own folder goes with it. `folder_id` can be edited to
+    // name a shared folder, whose assets belong to others.
+    let own_folder = post_folder_name(post_id);
+    let folder_id = data
+        .get_post_by_id(&post_id)
+        .await
+        .map_err(ApiError::database("Failed to retrieve post"))?
+        .and_then(|post| post.folder_id)
+        .filter(|folder_id| *folder_id == own_folder);
+    let asset_ids = match &folder_id {
+        Some(folder_id) => data
+            .get_folder_contents(folder_id)
+            .await
+            .map_err(ApiError::database("Failed to retrieve post assets"))?
+            .unwrap_or_default(),
+        None => Vec::new(),
+    };
+
     debug!(
         "Attempting to delete post with ID {:?} from database.",
         post_id
     );
-    match data.delete_post(&post_id).await {
-        Ok(_) => {
-            info!(
-                "Post with id: {:?} deleted successfully from database.",
-                post_id
-            );
-            HttpResponse::NoContent().finish()
+    data.delete_post(&post_id)
+        .await
+        .map_err(ApiError::database("Failed to delete post"))?;
+
+    info!(
+        "Post with id: {:?} deleted successfully from database.",
+        post_id
+    );
+    data.publish_change(WebhookEvent::PostDeleted, post_id, &serde_json::json!({ "id": post_id }));
+
+    // The post is gone either way; leftover assets are only logged
+    for asset_id in asset_ids {
+        if let Err(e) = delete_asset_by_id(asset_id, data.clone()).await {
+            warn!("Failed to delete asset {:?} of post {:?}: {}", asset_id, post_id, e);
         }
-        Err(e) => {
-            error!(
-                "Failed to delete post with ID {:?} from database: {}",
-                post_id, e
-            );
-            HttpResponse::InternalServerError()
-                .json(ErrorResponse::internal_error("Failed to delete post"))
+    }
+    if let Some(folder_id) = folder_id {
+        if let Err(e) = data.storage.remove_folder(&folder_id).await {
+            warn!("Failed to remove storage folder {}: {}", folder_id, e);
         }
     }
+    Ok(HttpResponse::NoContent().finish())
 }
 
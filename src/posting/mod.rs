@@ -1,3 +1,7 @@
+pub mod embed;
 pub mod handlers;
 pub mod models;
 pub mod multipart_parser;
+pub mod sanitize;
+pub mod search;
+pub mod translation;
@@ -1,8 +1,12 @@
 use chrono::{NaiveDate, DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::asset::models::AssetSummary;
+use crate::asset::public_url::public_urls;
+use crate::i18n::{format_date_id, format_datetime_id};
+
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema, sqlx::FromRow)]
 pub struct Post {
     #[schema(example = "f1e2d3c4-b5a6-7890-1234-567890abcdef")]
@@ -19,9 +23,50 @@ pub struct Post {
     pub folder_id: Option<String>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    /// Asset picked as the card image, null to use the first image in the
+    /// post's folder
+    #[serde(default)]
+    pub cover_asset_id: Option<Uuid>,
+    /// Stored filename of the cover: the picked asset, else the first image
+    /// in the folder. Serialized as its public `cover_url`.
+    #[serde(
+        rename = "cover_url",
+        serialize_with = "serialize_cover_url",
+        skip_deserializing
+    )]
+    #[schema(value_type = Option<String>, example = "https://example.com/assets/serve/kerja-bakti.jpg")]
+    pub cover_filename: Option<String>,
+    /// Languages besides Indonesian the title and excerpt are available in
+    #[serde(default)]
+    #[schema(example = json!(["en"]))]
+    pub translations_available: Vec<String>,
+    /// Approved comments on the post
+    #[serde(default)]
+    #[schema(example = 3)]
+    pub comment_count: i64,
+    /// First assets of the post's folder, oldest first. Only present in
+    /// the post list with `include=assets:N`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[sqlx(skip)]
+    pub assets: Option<Vec<AssetSummary>>,
+    /// Dates written out in Indonesian. Only present with `format=display`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[sqlx(skip)]
+    pub display: Option<PostDisplay>,
 }
 
+/// Dates of a post as shown to readers, in the app time zone
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct PostDisplay {
+    #[schema(example = "5 November 2025")]
+    pub date: String,
+    #[schema(example = "5 November 2025 14.30 WIB")]
+    pub created_at: Option<String>,
+    #[schema(example = "6 November 2025 09.15 WIB")]
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema, sqlx::FromRow)]
 pub struct PostWithAssets {
     pub id: Uuid,
     pub title: String,
@@ -32,20 +77,42 @@ pub struct PostWithAssets {
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
     pub asset_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub cover_asset_id: Option<Uuid>,
+    #[serde(
+        rename = "cover_url",
+        serialize_with = "serialize_cover_url",
+        skip_deserializing
+    )]
+    #[schema(value_type = Option<String>)]
+    pub cover_filename: Option<String>,
+    #[serde(default)]
+    pub translations_available: Vec<String>,
+    #[serde(default)]
+    pub comment_count: i64,
 }
 
 
 
 #[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
-
+#[schema(example = json!({
+    "title": "Kerja Bakti Lingkungan RW 05",
+    "category": "Kegiatan",
+    "excerpt": "Warga RW 05 membersihkan saluran air menjelang musim hujan."
+}))]
 pub struct CreatePostingRequest {
-
+    #[schema(example = "Judul Posting")]
     pub title: String,
-
+    #[schema(example = "Kategori Posting")]
     pub category: String,
-
+    /// Markdown, stored as written. HTML in it is sanitized when rendered.
+    #[schema(example = "Ini adalah ringkasan postingan.")]
     pub excerpt: String,
-
+    /// Card image, an asset in the post's folder. A new post's folder has
+    /// no assets yet, so this is usually set later with an update.
+    #[serde(default)]
+    #[schema(example = "a1b2c3d4-e5f6-7890-1234-567890abcdef")]
+    pub cover_asset_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -54,13 +121,37 @@ pub struct UpdatePostingRequest {
     pub title: Option<String>,
     #[schema(example = "Kategori Posting Diperbarui")]
     pub category: Option<String>,
+    /// Markdown, stored as written. HTML in it is sanitized when rendered.
     #[schema(example = "Ini adalah ringkasan postingan yang sudah diperbarui.")]
     pub excerpt: Option<String>,
     #[schema(example = "posts/f1e2d3c4-b5a6-7890-1234-567890abcdef")]
     pub folder_id: Option<String>,
+    /// Card image, an asset in the post's folder. `null` goes back to the
+    /// first image in the folder; leaving the field out keeps the cover.
+    #[serde(default, deserialize_with = "deserialize_present")]
+    #[schema(value_type = Option<Uuid>, example = "a1b2c3d4-e5f6-7890-1234-567890abcdef")]
+    pub cover_asset_id: Option<Option<Uuid>>,
 }
 
+/// Distinguishes an explicit `null` (`Some(None)`) from a missing field
+/// (`None`, via `#[serde(default)]`)
+fn deserialize_present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
 
+fn serialize_cover_url<S: Serializer>(
+    filename: &Option<String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    filename
+        .as_deref()
+        .map(|filename| public_urls().resolve_filename(filename))
+        .serialize(serializer)
+}
 
 impl Post {
     pub fn new(title: String, category: String, excerpt: String, folder_id: Option<String>) -> Self {
@@ -68,11 +159,58 @@ impl Post {
             id: Uuid::new_v4(),
             title,
             category,
-            date: chrono::Local::now().date_naive(),
+            date: crate::timezone::today(),
             excerpt,
             folder_id,
             created_at: Some(Utc::now()),
             updated_at: Some(Utc::now()),
+            cover_asset_id: None,
+            cover_filename: None,
+            translations_available: Vec::new(),
+            comment_count: 0,
+            assets: None,
+            display: None,
         }
     }
+
+    /// Fill `display` with the post's dates written out in Indonesian
+    pub fn format_for_display(&mut self) {
+        self.display = Some(PostDisplay {
+            date: format_date_id(self.date),
+            created_at: self.created_at.map(format_datetime_id),
+            updated_at: self.updated_at.map(format_datetime_id),
+        });
+    }
+
+    /// Public URL of the cover image, if the post has one
+    pub fn cover_url(&self) -> Option<String> {
+        self.cover_filename
+            .as_deref()
+            .map(|filename| public_urls().resolve_filename(filename))
+    }
+}
+
+/// Inclusive range of post dates. A missing end leaves that side open.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl DateRange {
+    /// Every day of `month` (1-12) in `year`
+    pub fn month(year: i32, month: u32) -> Option<Self> {
+        let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+        let last = first
+            .checked_add_months(chrono::Months::new(1))?
+            .pred_opt()?;
+        Some(Self {
+            from: Some(first),
+            to: Some(last),
+        })
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.from.is_none_or(|from| date >= from) && self.to.is_none_or(|to| date <= to)
+    }
 }
@@ -3,7 +3,6 @@ use actix_web::HttpResponse;
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use log::{error};
 use sanitize_filename::sanitize;
 
 use crate::{ErrorResponse, posting::models::CreatePostingRequest};
@@ -13,9 +12,31 @@ pub struct ParsedMultipartData {
     pub title: String,
     pub category: String,
     pub excerpt: String,
+    pub cover_asset_id: Option<Uuid>,
     pub files_data: Vec<(Vec<u8>, String)>,
 }
 
+/// The fields of an asset upload
+#[derive(Debug)]
+pub struct ParsedAssetMultipart {
+    /// Contents and sanitized original name of each uploaded file
+    pub files: Vec<(Vec<u8>, String)>,
+    pub name: Option<String>,
+    pub posting_id: Option<Uuid>,
+    pub folders: Vec<String>,
+}
+
+/// The fields of a submission form, untrimmed
+#[derive(Debug, Default)]
+pub struct ParsedSubmissionMultipart {
+    pub nama: String,
+    pub nik: Option<String>,
+    pub kategori: String,
+    pub isi: String,
+    /// Contents and sanitized original name of the attached file
+    pub attachment: Option<(Vec<u8>, String)>,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum MultipartParseError {
     #[error("Multipart field error: {0}")]
@@ -54,6 +75,7 @@ impl MultipartParser {
         let mut title = String::new();
         let mut category = String::new();
         let mut excerpt = String::new();
+        let mut cover_asset_id = None;
         let mut files_data: Vec<(Vec<u8>, String)> = Vec::new();
 
         while let Some(item) = multipart.next().await {
@@ -81,6 +103,7 @@ impl MultipartParser {
                 title = metadata.title;
                 category = metadata.category;
                 excerpt = metadata.excerpt;
+                cover_asset_id = metadata.cover_asset_id;
             } else if name.starts_with("file") {
   
                 let mut file_buffer = Vec::new();
@@ -102,15 +125,16 @@ impl MultipartParser {
             title,
             category,
             excerpt,
+            cover_asset_id,
             files_data,
         })
     }
 
+    /// Parse an asset upload. Every non-empty `file` part is kept, in order.
     pub async fn parse_asset_multipart(
         mut multipart: Multipart,
-    ) -> Result<(Vec<u8>, String, Option<String>, Option<Uuid>, Vec<String>), MultipartParseError> {
-        let mut file_data = Vec::new();
-        let mut original_filename = String::new();
+    ) -> Result<ParsedAssetMultipart, MultipartParseError> {
+        let mut files: Vec<(Vec<u8>, String)> = Vec::new();
         let mut asset_name: Option<String> = None;
         let mut posting_id: Option<Uuid> = None;
         let mut folder_names: Vec<String> = Vec::new();
@@ -127,12 +151,17 @@ impl MultipartParser {
                     let filename = content_disposition.get_filename()
                         .ok_or_else(|| MultipartParseError::FieldError("No filename in file field".to_string()))?;
                     
-                    original_filename = sanitize(&filename).to_string();
+                    let original_filename = sanitize(filename).to_string();
 
+                    let mut file_data = Vec::new();
                     while let Some(chunk) = field.next().await {
                         let chunk_data = chunk.map_err(|e| MultipartParseError::IoError(e.to_string()))?;
                         file_data.extend_from_slice(&chunk_data);
                     }
+                    // Browsers send an empty part for a file input left blank
+                    if !file_data.is_empty() {
+                        files.push((file_data, original_filename));
+                    }
                 },
                 "posting_id" => {
                     let mut bytes = Vec::new();
@@ -176,10 +205,61 @@ impl MultipartParser {
             }
         }
 
-        if file_data.is_empty() {
+        if files.is_empty() {
             return Err(MultipartParseError::FieldError("No file data found in multipart payload".to_string()));
         }
 
-        Ok((file_data, original_filename, asset_name, posting_id, folder_names))
+        Ok(ParsedAssetMultipart {
+            files,
+            name: asset_name,
+            posting_id,
+            folders: folder_names,
+        })
+    }
+
+    /// Parse a submission form. Text parts are read by name, an empty
+    /// `attachment` part counts as no attachment and unknown parts are
+    /// ignored.
+    pub async fn parse_submission_multipart(
+        mut multipart: Multipart,
+    ) -> Result<ParsedSubmissionMultipart, MultipartParseError> {
+        let mut parsed = ParsedSubmissionMultipart::default();
+
+        while let Some(item) = multipart.next().await {
+            let mut field = item.map_err(|e| MultipartParseError::FieldError(e.to_string()))?;
+            let content_disposition = field.content_disposition()
+                .ok_or_else(|| MultipartParseError::FieldError("Content disposition not found".to_string()))?;
+            let field_name = content_disposition.get_name()
+                .ok_or_else(|| MultipartParseError::FieldError("Field name not found".to_string()))?
+                .to_string();
+            let filename = content_disposition.get_filename().map(sanitize);
+
+            let mut bytes = Vec::new();
+            while let Some(chunk) = field.next().await {
+                let chunk_data = chunk.map_err(|e| MultipartParseError::IoError(e.to_string()))?;
+                bytes.extend_from_slice(&chunk_data);
+            }
+
+            match field_name.as_str() {
+                "attachment" => {
+                    // Browsers send an empty part for a file input left blank
+                    if !bytes.is_empty() {
+                        let filename = filename.unwrap_or_else(|| "lampiran.dat".to_string());
+                        parsed.attachment = Some((bytes, filename));
+                    }
+                }
+                "nama" => parsed.nama = text(bytes)?,
+                "nik" => parsed.nik = Some(text(bytes)?),
+                "kategori" => parsed.kategori = text(bytes)?,
+                "isi" => parsed.isi = text(bytes)?,
+                _ => continue,
+            }
+        }
+
+        Ok(parsed)
     }
-}
\ No newline at end of file
+}
+
+fn text(bytes: Vec<u8>) -> Result<String, MultipartParseError> {
+    String::from_utf8(bytes).map_err(|e| MultipartParseError::Utf8Error(e.to_string()))
+}
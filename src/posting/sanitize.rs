@@ -0,0 +1,205 @@
+//! Rendering post excerpts from Markdown to sanitized HTML.
+//!
+//! Excerpts are stored as written, so plain-text readers such as search and
+//! the MCP tools see them unchanged. HTML pasted from Word or a browser is
+//! only made safe on output: `GET /api/postings/{id}/rendered` turns the
+//! stored Markdown into HTML and removes every tag outside the allowlist,
+//! together with event handler attributes and `javascript:` links.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use actix_web::{web, HttpResponse};
+use pulldown_cmark::{html, Options, Parser};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::translation::{LangQuery, DEFAULT_LANG};
+use crate::{ApiError, AppState};
+
+/// Tags kept by default, see the README
+pub const DEFAULT_ALLOWED_TAGS: &[&str] = &[
+    "p",
+    "br",
+    "hr",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "strong",
+    "b",
+    "em",
+    "i",
+    "u",
+    "s",
+    "del",
+    "sub",
+    "sup",
+    "blockquote",
+    "code",
+    "pre",
+    "a",
+    "img",
+    "ul",
+    "ol",
+    "li",
+    "table",
+    "thead",
+    "tbody",
+    "tr",
+    "th",
+    "td",
+];
+
+/// Tags whose content is dropped along with them; they can never be allowed
+const REMOVED_WITH_CONTENT: &[&str] = &["script", "style"];
+
+#[derive(Debug, Clone)]
+pub struct SanitizeConfig {
+    /// Lowercase names of the tags kept in rendered HTML
+    pub allowed_tags: Vec<String>,
+    /// Sanitizer built once from `allowed_tags`
+    cleaner: Arc<ammonia::Builder<'static>>,
+}
+
+impl Default for SanitizeConfig {
+    fn default() -> Self {
+        Self::new(DEFAULT_ALLOWED_TAGS.iter().map(|t| t.to_string()).collect())
+    }
+}
+
+impl SanitizeConfig {
+    /// Load using a custom variable lookup. `POSTING_ALLOWED_TAGS` replaces
+    /// the default allowlist with a comma-separated list of tag names.
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let Some(value) = lookup("POSTING_ALLOWED_TAGS").filter(|v| !v.trim().is_empty()) else {
+            return Ok(Self::default());
+        };
+
+        let mut allowed_tags = Vec::new();
+        for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let tag = entry.to_ascii_lowercase();
+            if !tag.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(format!(
+                    "POSTING_ALLOWED_TAGS entry '{}' is not a tag name",
+                    entry
+                ));
+            }
+            if REMOVED_WITH_CONTENT.contains(&tag.as_str()) {
+                return Err(format!("POSTING_ALLOWED_TAGS must not allow <{}>", tag));
+            }
+            if !allowed_tags.contains(&tag) {
+                allowed_tags.push(tag);
+            }
+        }
+        Ok(Self::new(allowed_tags))
+    }
+
+    fn new(allowed_tags: Vec<String>) -> Self {
+        // The sanitizer borrows tag names for 'static. The allowlist is read
+        // once at startup, so tags outside the default list are leaked.
+        let tags: HashSet<&'static str> = allowed_tags
+            .iter()
+            .map(|tag| {
+                let known = DEFAULT_ALLOWED_TAGS.iter().find(|t| **t == tag.as_str());
+                known
+                    .copied()
+                    .unwrap_or_else(|| &*Box::leak(tag.clone().into_boxed_str()))
+            })
+            .collect();
+        let mut cleaner = ammonia::Builder::default();
+        cleaner.tags(tags);
+        Self {
+            allowed_tags,
+            cleaner: Arc::new(cleaner),
+        }
+    }
+
+    /// `html` with every tag and attribute outside the allowlist removed
+    pub fn clean(&self, html: &str) -> String {
+        self.cleaner.clean(html).to_string()
+    }
+
+    /// `markdown` rendered to HTML, then sanitized. Tables and
+    /// ~~strikethrough~~ are supported.
+    pub fn render_markdown(&self, markdown: &str) -> String {
+        let parser = Parser::new_ext(
+            markdown,
+            Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH,
+        );
+        let mut rendered = String::new();
+        html::push_html(&mut rendered, parser);
+        self.clean(&rendered)
+    }
+}
+
+/// A post's excerpt as HTML
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RenderedPosting {
+    pub id: Uuid,
+    /// Language of the rendered text
+    #[schema(example = "id")]
+    pub lang: String,
+    pub title: String,
+    /// Sanitized HTML rendered from the Markdown excerpt
+    #[schema(example = "<p>Kerja bakti di <strong>RW 05</strong></p>\n")]
+    pub html: String,
+}
+
+/// Excerpt of a post rendered from Markdown to sanitized HTML
+#[utoipa::path(
+    operation_id = "getRenderedPosting",
+    context_path = "/api",
+    tag = "Posting Service",
+    get,
+    path = "/postings/{id}/rendered",
+    responses(
+        (status = 200, description = "Rendered excerpt", body = RenderedPosting),
+        (status = 400, description = "Unsupported language", body = crate::ErrorResponse),
+        (status = 404, description = "Post not found", body = crate::ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = crate::ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "ID of the post"),
+        LangQuery
+    )
+)]
+pub async fn get_rendered_posting(
+    id: web::Path<Uuid>,
+    lang: web::Query<LangQuery>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let post_id = id.into_inner();
+    let lang = lang.lang()?;
+
+    let mut post = data
+        .get_post_by_id(&post_id)
+        .await
+        .map_err(ApiError::database("Failed to retrieve post"))?
+        .ok_or_else(|| ApiError::NotFound(format!("Post with ID {:?} not found", post_id)))?;
+    // Untranslated posts are rendered in Indonesian
+    let mut rendered_lang = DEFAULT_LANG;
+    if lang != DEFAULT_LANG {
+        let translation = data
+            .get_post_translation(&post_id, lang)
+            .await
+            .map_err(ApiError::database("Failed to retrieve translation"))?;
+        if let Some(translation) = translation {
+            post.translate(&translation);
+            rendered_lang = lang;
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(RenderedPosting {
+        id: post.id,
+        lang: rendered_lang.to_string(),
+        title: post.title,
+        html: data.sanitize.render_markdown(&post.excerpt),
+    }))
+}
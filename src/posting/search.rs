@@ -0,0 +1,161 @@
+//! Ranked full-text search over posts.
+//!
+//! `posts.search_vector` is a generated column holding the title (weight A)
+//! and excerpt (weight B), indexed with GIN. Queries use the same
+//! `indonesian` text search configuration as the column when the server
+//! has it, and `simple` otherwise, so stemming matches on both sides.
+
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::models::Post;
+use crate::db::{DbError, POST_COLUMNS, POST_SOURCE};
+use crate::{ApiError, AppState};
+
+/// Results per page when `limit` is not given
+pub const DEFAULT_SEARCH_LIMIT: i64 = 20;
+/// Largest accepted `limit`
+pub const MAX_SEARCH_LIMIT: i64 = 100;
+
+/// Configuration used by the migration in `supabase_schema.sql`
+const TEXT_SEARCH_CONFIG: &str = "COALESCE(\
+     (SELECT oid::regconfig FROM pg_ts_config WHERE cfgname = 'indonesian'), \
+     'simple'::regconfig)";
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct SearchQuery {
+    /// Search terms. Supports `"quoted phrases"`, `or` and `-excluded` words
+    pub q: String,
+    /// Results returned, at most 100 (default: 20)
+    pub limit: Option<i64>,
+    /// Results skipped (default: 0)
+    pub offset: Option<i64>,
+}
+
+/// A post matching a search
+#[derive(Debug, Serialize, ToSchema, sqlx::FromRow)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub post: Post,
+    /// Higher is better; title matches outrank excerpt matches
+    pub rank: f32,
+    /// Excerpt fragment with matched words wrapped in `<mark>`. The excerpt
+    /// text itself is not HTML-escaped.
+    #[schema(example = "Jadwal <mark>posyandu</mark> balita bulan ini")]
+    pub snippet: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchResponse {
+    pub query: String,
+    pub limit: i64,
+    pub offset: i64,
+    /// Matches across all pages
+    pub total: i64,
+    /// Best match first
+    pub results: Vec<SearchHit>,
+}
+
+#[derive(sqlx::FromRow)]
+struct SearchRow {
+    #[sqlx(flatten)]
+    hit: SearchHit,
+    total: i64,
+}
+
+impl AppState {
+    /// One page of the posts matching `query`, best first, and the total
+    /// number of matches
+    pub async fn fulltext_search_posts(
+        &self,
+        query: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<SearchHit>, i64), DbError> {
+        let sql = format!(
+            r#"
+            WITH search AS (
+                SELECT config, websearch_to_tsquery(config, $1) AS query
+                FROM (SELECT {} AS config) c
+            )
+            SELECT {},
+                   ts_rank(p.search_vector, s.query) AS rank,
+                   ts_headline(s.config, p.excerpt, s.query,
+                               'StartSel=<mark>, StopSel=</mark>, MaxWords=35, MinWords=15') AS snippet,
+                   COUNT(*) OVER () AS total
+            FROM {}
+            CROSS JOIN search s
+            WHERE p.search_vector @@ s.query
+            ORDER BY rank DESC, p.date DESC, p.id
+            LIMIT $2 OFFSET $3
+            "#,
+            TEXT_SEARCH_CONFIG,
+            POST_COLUMNS,
+            POST_SOURCE
+        );
+        let rows: Vec<SearchRow> = self
+            .timed("fulltext_search_posts", async {
+                sqlx::query_as(&sql)
+                    .bind(query)
+                    .bind(limit)
+                    .bind(offset)
+                    .fetch_all(&self.pool)
+                    .await
+            })
+            .await?;
+
+        let total = rows.first().map_or(0, |row| row.total);
+        Ok((rows.into_iter().map(|row| row.hit).collect(), total))
+    }
+}
+
+/// Search posts by title and excerpt, best match first
+#[utoipa::path(
+    operation_id = "searchPostings",
+    context_path = "/api",
+    tag = "Posting Service",
+    get,
+    path = "/search",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "Ranked matches with highlighted snippets", body = SearchResponse),
+        (status = 400, description = "Empty query or limit/offset out of range", body = crate::ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = crate::ErrorResponse)
+    )
+)]
+pub async fn search_postings(
+    data: web::Data<AppState>,
+    query: web::Query<SearchQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let terms = query.q.trim();
+    if terms.is_empty() {
+        return Err(ApiError::BadRequest("q must not be empty".to_string()));
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+    if !(1..=MAX_SEARCH_LIMIT).contains(&limit) {
+        return Err(ApiError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_SEARCH_LIMIT
+        )));
+    }
+    let offset = query.offset.unwrap_or(0);
+    if offset < 0 {
+        return Err(ApiError::BadRequest(
+            "offset must not be negative".to_string(),
+        ));
+    }
+
+    let (results, total) = data
+        .fulltext_search_posts(terms, limit, offset)
+        .await
+        .map_err(ApiError::database("Failed to search posts"))?;
+    Ok(HttpResponse::Ok().json(SearchResponse {
+        query: terms.to_string(),
+        limit,
+        offset,
+        total,
+        results,
+    }))
+}
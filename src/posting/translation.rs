@@ -0,0 +1,258 @@
+//! Translated titles and excerpts of posts.
+//!
+//! A post is written in Indonesian. Other languages are stored per post in
+//! `post_translations` and picked with `?lang=`; a post without a
+//! translation in the requested language keeps its Indonesian text.
+
+use std::collections::HashMap;
+
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use super::models::Post;
+use crate::cache::{CacheKind, Cached};
+use crate::webhook::WebhookEvent;
+use crate::db::DbError;
+use crate::{ApiError, AppState};
+
+/// Language of the post itself
+pub const DEFAULT_LANG: &str = "id";
+/// Languages accepted by `?lang=`, the default first
+pub const SUPPORTED_LANGS: &[&str] = &[DEFAULT_LANG, "en"];
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct LangQuery {
+    /// Language of titles and excerpts, `id` (default) or `en`. Posts not
+    /// translated into it are returned in Indonesian.
+    pub lang: Option<String>,
+}
+
+impl LangQuery {
+    pub fn lang(&self) -> Result<&'static str, ApiError> {
+        match &self.lang {
+            Some(lang) => parse_lang(lang),
+            None => Ok(DEFAULT_LANG),
+        }
+    }
+}
+
+/// `lang` as one of [`SUPPORTED_LANGS`], ignoring case
+pub fn parse_lang(lang: &str) -> Result<&'static str, ApiError> {
+    SUPPORTED_LANGS
+        .iter()
+        .find(|supported| supported.eq_ignore_ascii_case(lang.trim()))
+        .copied()
+        .ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "Unsupported language {:?}, expected one of: {}",
+                lang,
+                SUPPORTED_LANGS.join(", ")
+            ))
+        })
+}
+
+/// Title and excerpt of a post in one language
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct PostTranslation {
+    pub post_id: Uuid,
+    #[schema(example = "en")]
+    pub lang: String,
+    #[schema(example = "Community Clean-up in RW 05")]
+    pub title: String,
+    #[schema(example = "Residents of RW 05 cleared the drains ahead of the rainy season.")]
+    pub excerpt: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TranslationRequest {
+    #[schema(example = "Community Clean-up in RW 05")]
+    pub title: String,
+    #[schema(example = "Residents of RW 05 cleared the drains ahead of the rainy season.")]
+    pub excerpt: String,
+}
+
+impl Post {
+    /// Use the title and excerpt of `translation`
+    pub fn translate(&mut self, translation: &PostTranslation) {
+        self.title.clone_from(&translation.title);
+        self.excerpt.clone_from(&translation.excerpt);
+    }
+}
+
+/// Key of the cached post list in `lang`. The Indonesian list keeps its
+/// original key.
+pub(crate) fn posts_cache_key(lang: &str) -> String {
+    if lang == DEFAULT_LANG {
+        "all_posts".to_string()
+    } else {
+        format!("all_posts:{}", lang)
+    }
+}
+
+impl AppState {
+    /// Store the `lang` version of a post, replacing an earlier one
+    pub async fn upsert_post_translation(
+        &self,
+        post_id: &Uuid,
+        lang: &str,
+        request: &TranslationRequest,
+    ) -> Result<PostTranslation, DbError> {
+        let translation = self
+            .timed("upsert_post_translation", async {
+                sqlx::query_as(
+                    "INSERT INTO post_translations (post_id, lang, title, excerpt)
+                     VALUES ($1, $2, $3, $4)
+                     ON CONFLICT (post_id, lang)
+                     DO UPDATE SET title = $3, excerpt = $4, updated_at = NOW()
+                     RETURNING post_id, lang, title, excerpt, created_at, updated_at",
+                )
+                .bind(post_id)
+                .bind(lang)
+                .bind(&request.title)
+                .bind(&request.excerpt)
+                .fetch_one(&self.pool)
+                .await
+            })
+            .await?;
+
+        self.post_cache.invalidate_all();
+        self.refresh_cache_entries(CacheKind::Posts).await;
+        Ok(translation)
+    }
+
+    pub async fn get_post_translation(
+        &self,
+        post_id: &Uuid,
+        lang: &str,
+    ) -> Result<Option<PostTranslation>, DbError> {
+        self.timed("get_post_translation", async {
+            sqlx::query_as(
+                "SELECT post_id, lang, title, excerpt, created_at, updated_at
+                 FROM post_translations WHERE post_id = $1 AND lang = $2",
+            )
+            .bind(post_id)
+            .bind(lang)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    /// `posts` with their `lang` titles and excerpts where translated
+    pub async fn translate_posts(
+        &self,
+        mut posts: Vec<Post>,
+        lang: &str,
+    ) -> Result<Vec<Post>, DbError> {
+        if lang == DEFAULT_LANG || posts.is_empty() {
+            return Ok(posts);
+        }
+        let post_ids: Vec<Uuid> = posts.iter().map(|post| post.id).collect();
+        let translations: Vec<PostTranslation> = self
+            .guarded(self.timed("translate_posts", async {
+                sqlx::query_as(
+                    "SELECT post_id, lang, title, excerpt, created_at, updated_at
+                     FROM post_translations WHERE lang = $1 AND post_id = ANY($2)",
+                )
+                .bind(lang)
+                .bind(&post_ids)
+                .fetch_all(&self.pool)
+                .await
+            }))
+            .await?;
+
+        let by_post: HashMap<Uuid, PostTranslation> = translations
+            .into_iter()
+            .map(|translation| (translation.post_id, translation))
+            .collect();
+        for post in &mut posts {
+            if let Some(translation) = by_post.get(&post.id) {
+                post.translate(translation);
+            }
+        }
+        Ok(posts)
+    }
+
+    /// [`AppState::get_all_posts_cached`] in `lang`, cached per language
+    pub async fn get_all_posts_cached_in(&self, lang: &str) -> Result<Vec<Post>, DbError> {
+        if lang == DEFAULT_LANG {
+            return self.get_all_posts_cached().await;
+        }
+        let key = posts_cache_key(lang);
+        if let Some(cached) = self.post_cache.get(&key).await {
+            log::info!("Cache hit for {}", key);
+            self.cache_metrics.record_hit(CacheKind::Posts);
+            return Ok(cached.value);
+        }
+
+        log::info!("Cache miss for {}", key);
+        self.cache_metrics.record_miss(CacheKind::Posts);
+        let posts = self.get_all_posts_cached().await?;
+        let posts = self.translate_posts(posts, lang).await?;
+        self.post_cache
+            .insert(key, Cached::new(posts.clone()))
+            .await;
+        self.refresh_cache_entries(CacheKind::Posts).await;
+        Ok(posts)
+    }
+}
+
+/// Add or replace the translation of a post into one language
+#[utoipa::path(
+    operation_id = "putPostingTranslation",
+    context_path = "/api",
+    tag = "Posting Service",
+    put,
+    path = "/postings/{id}/translations/{lang}",
+    request_body = TranslationRequest,
+    responses(
+        (status = 200, description = "Translation stored", body = PostTranslation),
+        (status = 400, description = "Unsupported language, Indonesian, or an empty title", body = crate::ErrorResponse),
+        (status = 404, description = "Post not found", body = crate::ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = crate::ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "ID of the post"),
+        ("lang" = String, Path, description = "Language of the translation, e.g. `en`")
+    )
+)]
+pub async fn put_posting_translation(
+    path: web::Path<(Uuid, String)>,
+    request: web::Json<TranslationRequest>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let (post_id, lang) = path.into_inner();
+    let lang = parse_lang(&lang)?;
+    if lang == DEFAULT_LANG {
+        return Err(ApiError::BadRequest(
+            "Indonesian is the post's own text, update the post instead".to_string(),
+        ));
+    }
+    if request.title.trim().is_empty() {
+        return Err(ApiError::BadRequest("title must not be empty".to_string()));
+    }
+
+    let mut post = data
+        .get_post_by_id(&post_id)
+        .await
+        .map_err(ApiError::database("Failed to retrieve post"))?
+        .ok_or_else(|| ApiError::NotFound(format!("Post with ID {:?} not found", post_id)))?;
+
+    let translation = data
+        .upsert_post_translation(&post_id, lang, &request)
+        .await
+        .map_err(ApiError::database("Failed to store translation"))?;
+
+    log::info!("Stored {} translation of post {:?}", lang, post_id);
+    if !post.translations_available.iter().any(|l| l == lang) {
+        post.translations_available.push(lang.to_string());
+        post.translations_available.sort();
+    }
+    data.publish_change(WebhookEvent::PostUpdated, post_id, &post);
+    Ok(HttpResponse::Ok().json(translation))
+}
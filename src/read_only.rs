@@ -0,0 +1,117 @@
+//! Read-only mode for maintenance windows.
+//!
+//! While enabled, writes under `/api` are rejected with 503 and a
+//! `Retry-After` header. Reads keep working and are served from the caches
+//! where possible. MCP document tools, which store letters, check
+//! [`AppState::is_read_only`] themselves, as `/mcp` is not under `/api`.
+
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::{header, Method};
+use actix_web::middleware::Next;
+use actix_web::{web, Error, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+use utoipa::ToSchema;
+
+use crate::auth::validate_request_token;
+use crate::AppState;
+
+pub const READ_ONLY_PATH: &str = "/api/admin/read-only";
+/// Seconds clients are asked to wait before retrying a rejected write
+const RETRY_AFTER_SECS: u64 = 120;
+/// Writes still allowed in read-only mode, so an admin can log in, keep the
+/// session alive and turn it off
+const EXEMPT_PATHS: &[&str] = &[READ_ONLY_PATH, "/api/auth/login", "/api/auth/refresh"];
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetReadOnlyRequest {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadOnlyResponse {
+    pub read_only: bool,
+}
+
+impl AppState {
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::Relaxed)
+    }
+
+    pub fn set_read_only(&self, enabled: bool) {
+        self.read_only.store(enabled, Ordering::Relaxed);
+    }
+}
+
+/// Returns true for requests rejected while read-only mode is on
+pub fn is_blocked_write(method: &Method, path: &str) -> bool {
+    matches!(
+        *method,
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    ) && path.starts_with("/api/")
+        && !EXEMPT_PATHS.contains(&path)
+}
+
+/// Middleware rejecting writes while read-only mode is on
+pub async fn read_only_guard(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let read_only = req
+        .app_data::<web::Data<AppState>>()
+        .is_some_and(|state| state.is_read_only());
+
+    if read_only && is_blocked_write(req.method(), req.path()) {
+        let response = HttpResponse::ServiceUnavailable()
+            .insert_header((header::RETRY_AFTER, RETRY_AFTER_SECS.to_string()))
+            .json(crate::ErrorResponse::new(
+                "ServiceUnavailable",
+                "The server is in read-only mode for maintenance",
+            ));
+        return Ok(req.into_response(response).map_into_right_body());
+    }
+
+    next.call(req)
+        .await
+        .map(ServiceResponse::map_into_left_body)
+}
+
+/// Turn read-only mode on or off (protected)
+#[utoipa::path(
+    operation_id = "setReadOnly",
+    post,
+    path = "/api/admin/read-only",
+    tag = "Admin",
+    request_body = SetReadOnlyRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Read-only mode updated", body = ReadOnlyResponse),
+        (status = 401, response = crate::UnauthorizedResponse)
+    )
+)]
+pub async fn set_read_only(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: web::Json<SetReadOnlyRequest>,
+) -> impl Responder {
+    let claims = match validate_request_token(&req) {
+        Ok(claims) => claims,
+        Err(e) => return e.error_response(),
+    };
+
+    state.set_read_only(body.enabled);
+    log::warn!(
+        "Read-only mode {} by {}",
+        if body.enabled { "enabled" } else { "disabled" },
+        claims.username
+    );
+
+    HttpResponse::Ok().json(ReadOnlyResponse {
+        read_only: state.is_read_only(),
+    })
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/admin/read-only").route(web::post().to(set_read_only)));
+}
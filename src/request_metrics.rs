@@ -0,0 +1,168 @@
+//! Request count, latency and in-flight gauge for the HTTP server, served
+//! with the other metrics on `/metrics`.
+//!
+//! Requests are labelled by the matched route template such as
+//! `/api/postings/{id}`, never the raw path, so ids in URLs do not create new
+//! series. Requests that match no route share the `unmatched` label, and
+//! unusual methods are counted as `OTHER`.
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::Method;
+use actix_web::middleware::Next;
+use actix_web::{web, Error, HttpResponse};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+use std::time::Instant;
+
+use crate::auth::METRICS_PATH;
+
+/// Route label of requests that matched no route
+pub const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Collectors for HTTP requests, and the registry `/metrics` renders
+#[derive(Clone)]
+pub struct RequestMetrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    duration: HistogramVec,
+    in_flight: IntGauge,
+}
+
+impl RequestMetrics {
+    /// Create the collectors and register them in `registry`, which also
+    /// holds every other metric exported on `/metrics`
+    pub fn new(registry: Registry) -> prometheus::Result<Self> {
+        let requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests served")
+                .namespace("cakung_barat_server"),
+            &["endpoint", "method", "status"],
+        )?;
+        let duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_requests_duration_seconds",
+                "Time from receiving a request until its response headers",
+            )
+            .namespace("cakung_barat_server"),
+            &["endpoint", "method", "status"],
+        )?;
+        let in_flight = IntGauge::with_opts(
+            Opts::new(
+                "http_requests_in_flight",
+                "Requests currently being handled",
+            )
+            .namespace("cakung_barat_server"),
+        )?;
+
+        registry.register(Box::new(requests.clone()))?;
+        registry.register(Box::new(duration.clone()))?;
+        registry.register(Box::new(in_flight.clone()))?;
+        Ok(Self {
+            registry,
+            requests,
+            duration,
+            in_flight,
+        })
+    }
+
+    fn observe(&self, route: &str, method: &Method, status: u16, started: Instant) {
+        let status = status.to_string();
+        let labels = [route, method_label(method), status.as_str()];
+        self.requests.with_label_values(&labels).inc();
+        self.duration
+            .with_label_values(&labels)
+            .observe(started.elapsed().as_secs_f64());
+    }
+
+    /// Requests currently being handled
+    pub fn in_flight(&self) -> i64 {
+        self.in_flight.get()
+    }
+
+    /// Every registered metric in the Prometheus text format
+    pub fn render(&self) -> Result<String, prometheus::Error> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+}
+
+/// Standard methods keep their name; anything else would let clients
+/// invent label values
+fn method_label(method: &Method) -> &'static str {
+    match *method {
+        Method::GET => "GET",
+        Method::POST => "POST",
+        Method::PUT => "PUT",
+        Method::PATCH => "PATCH",
+        Method::DELETE => "DELETE",
+        Method::HEAD => "HEAD",
+        Method::OPTIONS => "OPTIONS",
+        _ => "OTHER",
+    }
+}
+
+/// Lowers the in-flight gauge when the request finishes or is dropped
+struct InFlight(IntGauge);
+
+impl InFlight {
+    fn start(gauge: &IntGauge) -> Self {
+        gauge.inc();
+        Self(gauge.clone())
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+/// Middleware recording every request. Does nothing unless
+/// `web::Data<RequestMetrics>` is registered as app data.
+pub async fn track_requests(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let Some(metrics) = req.app_data::<web::Data<RequestMetrics>>().cloned() else {
+        return next.call(req).await;
+    };
+    let _in_flight = InFlight::start(&metrics.in_flight);
+    let started = Instant::now();
+    // Looked up in the resource map, so it is known before routing. The
+    // request must not be cloned here or the router cannot fill in its
+    // path parameters.
+    let route = req.match_pattern();
+    let method = req.method().clone();
+
+    let result = next.call(req).await;
+    let status = match &result {
+        Ok(response) => response.status(),
+        Err(e) => e.as_response_error().status_code(),
+    };
+    metrics.observe(
+        route.as_deref().unwrap_or(UNMATCHED_ROUTE),
+        &method,
+        status.as_u16(),
+        started,
+    );
+    result
+}
+
+/// Prometheus scrape endpoint
+pub async fn serve_metrics(metrics: web::Data<RequestMetrics>) -> HttpResponse {
+    match metrics.render() {
+        Ok(body) => HttpResponse::Ok()
+            .content_type(TextEncoder::new().format_type())
+            .body(body),
+        Err(e) => {
+            log::error!("Failed to encode metrics: {}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource(METRICS_PATH).route(web::get().to(serve_metrics)));
+}
@@ -0,0 +1,136 @@
+//! Gauges sampled in the background for `/metrics`.
+//!
+//! Cache entries expire and worker queues drain without passing through a
+//! handler, so cache sizes and queue depths are read every
+//! [`SAMPLE_INTERVAL`] by the task started in
+//! [`AppState::start_runtime_metrics`]. Storage failures and MCP tool calls
+//! are counted where they happen, see `crate::storage::metered` and
+//! `crate::mcp::tools::rate_limit::ToolCallMetrics`.
+
+use prometheus::{IntGaugeVec, Opts, Registry};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::cache::CacheKind;
+use crate::AppState;
+
+const METRICS_NAMESPACE: &str = "cakung_barat_server";
+/// How often the sampler reads cache sizes and queue depths
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Worker queues tracked by [`RuntimeMetrics`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerQueue {
+    OrganizationPersist,
+    Webhook,
+}
+
+impl WorkerQueue {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::OrganizationPersist => "organization_persist",
+            Self::Webhook => "webhook",
+        }
+    }
+}
+
+/// Sampled gauges held by `AppState`
+pub struct RuntimeMetrics {
+    queue_depth: IntGaugeVec,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Default for RuntimeMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RuntimeMetrics {
+    pub fn new() -> Self {
+        let queue_depth = IntGaugeVec::new(
+            Opts::new(
+                "worker_queue_depth",
+                "Messages waiting in a background worker's queue",
+            )
+            .namespace(METRICS_NAMESPACE),
+            &["queue"],
+        )
+        .expect("valid worker queue metric");
+
+        Self {
+            queue_depth,
+            handle: Mutex::new(None),
+        }
+    }
+
+    /// Register the metrics so they are exported on `/metrics`
+    pub fn register(&self, registry: &Registry) -> prometheus::Result<()> {
+        registry.register(Box::new(self.queue_depth.clone()))
+    }
+
+    pub fn set_queue_depth(&self, queue: WorkerQueue, depth: usize) {
+        self.queue_depth
+            .with_label_values(&[queue.label()])
+            .set(depth as i64);
+    }
+
+    pub fn queue_depth(&self, queue: WorkerQueue) -> u64 {
+        self.queue_depth
+            .with_label_values(&[queue.label()])
+            .get()
+            .max(0) as u64
+    }
+
+    /// Stop the sampler, if one was started
+    pub fn stop(&self) {
+        let handle = self.handle.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(handle) = handle {
+            handle.abort();
+        }
+    }
+}
+
+/// Messages sent on `sender` and not yet received
+fn queued<T>(sender: &mpsc::Sender<T>) -> usize {
+    sender.max_capacity() - sender.capacity()
+}
+
+impl AppState {
+    /// Read the cache sizes and queue depths into their gauges
+    pub async fn sample_runtime_metrics(&self) {
+        self.refresh_cache_entries(CacheKind::Posts).await;
+        self.refresh_cache_entries(CacheKind::Organization).await;
+        self.runtime_metrics.set_queue_depth(
+            WorkerQueue::OrganizationPersist,
+            queued(&self.organization_persist_sender),
+        );
+        self.runtime_metrics
+            .set_queue_depth(WorkerQueue::Webhook, queued(&self.webhook_sender));
+    }
+
+    /// Spawn the periodic sampler. Does nothing when a sampler is already
+    /// running.
+    pub fn start_runtime_metrics(&self, interval: Duration) {
+        let mut handle = self
+            .runtime_metrics
+            .handle
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if handle.is_some() {
+            return;
+        }
+
+        let state = self.clone();
+        *handle = Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                state.sample_runtime_metrics().await;
+            }
+        }));
+        log::info!("Runtime metrics sampler started, interval {:?}", interval);
+    }
+}
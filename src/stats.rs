@@ -0,0 +1,153 @@
+//! Summary numbers for the admin dashboard.
+//!
+//! Every count comes from one query with subselects, cached for
+//! [`STATS_MAX_AGE`]. Storage use is taken from the last bucket scan of
+//! `crate::storage_usage` rather than scanning again.
+
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+use crate::auth::validate_request_token;
+use crate::error::ApiError;
+use crate::AppState;
+
+/// How long computed stats are served before they are recomputed
+pub const STATS_MAX_AGE: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct CategoryCount {
+    #[schema(example = "Kegiatan")]
+    pub category: String,
+    pub posts: i64,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DashboardStats {
+    pub total_posts: i64,
+    /// Posts dated in the current calendar month
+    pub posts_this_month: i64,
+    pub total_assets: i64,
+    /// Distinct post categories
+    pub categories: i64,
+    pub admins: i64,
+    /// Bucket size from the last storage usage scan, null before the first
+    pub storage_used_bytes: Option<u64>,
+    pub storage_quota_bytes: Option<u64>,
+    /// Most used first
+    pub posts_by_category: Vec<CategoryCount>,
+    pub computed_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct StatsRow {
+    total_posts: i64,
+    posts_this_month: i64,
+    total_assets: i64,
+    categories: i64,
+    admins: i64,
+    posts_by_category: Json<Vec<CategoryCount>>,
+}
+
+/// Last computed stats, held by `AppState`
+#[derive(Default)]
+pub struct StatsCache {
+    stats: RwLock<Option<(Instant, DashboardStats)>>,
+}
+
+impl StatsCache {
+    /// The cached stats, if younger than [`STATS_MAX_AGE`]
+    fn cached(&self) -> Option<DashboardStats> {
+        self.stats
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .filter(|(at, _)| at.elapsed() < STATS_MAX_AGE)
+            .map(|(_, stats)| stats.clone())
+    }
+
+    fn store(&self, stats: &DashboardStats) {
+        *self.stats.write().unwrap_or_else(|e| e.into_inner()) =
+            Some((Instant::now(), stats.clone()));
+    }
+}
+
+impl AppState {
+    /// Dashboard numbers, served from the cache while fresh
+    pub async fn dashboard_stats(&self) -> Result<DashboardStats, ApiError> {
+        if let Some(stats) = self.stats.cached() {
+            return Ok(stats);
+        }
+
+        let row: StatsRow = self
+            .timed("dashboard_stats", async {
+                sqlx::query_as(
+                    r#"
+                    SELECT
+                        (SELECT COUNT(*) FROM posts) AS total_posts,
+                        (SELECT COUNT(*) FROM posts
+                         WHERE date >= date_trunc('month', CURRENT_DATE)::date) AS posts_this_month,
+                        (SELECT COUNT(*) FROM assets) AS total_assets,
+                        (SELECT COUNT(DISTINCT category) FROM posts) AS categories,
+                        (SELECT COUNT(*) FROM admins) AS admins,
+                        (SELECT COALESCE(
+                            json_agg(json_build_object('category', category, 'posts', posts)
+                                     ORDER BY posts DESC, category),
+                            '[]'::json)
+                         FROM (SELECT category, COUNT(*) AS posts FROM posts GROUP BY category) c
+                        ) AS posts_by_category
+                    "#,
+                )
+                .fetch_one(&self.pool)
+                .await
+            })
+            .await
+            .map_err(ApiError::database("Failed to compute dashboard stats"))?;
+
+        let stats = DashboardStats {
+            total_posts: row.total_posts,
+            posts_this_month: row.posts_this_month,
+            total_assets: row.total_assets,
+            categories: row.categories,
+            admins: row.admins,
+            storage_used_bytes: self.storage_usage.last_total_bytes(),
+            storage_quota_bytes: self.storage_usage.quota_bytes(),
+            posts_by_category: row.posts_by_category.0,
+            computed_at: Utc::now(),
+        };
+        self.stats.store(&stats);
+        Ok(stats)
+    }
+}
+
+/// Counts for the dashboard home page, cached for five minutes (protected)
+#[utoipa::path(
+    operation_id = "getDashboardStats",
+    get,
+    path = "/api/admin/stats",
+    tag = "Admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Dashboard statistics", body = DashboardStats),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 500, description = "Database query failed")
+    )
+)]
+pub async fn get_dashboard_stats(req: HttpRequest, state: web::Data<AppState>) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    match state.dashboard_stats().await {
+        Ok(stats) => HttpResponse::Ok().json(stats),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/admin/stats").route(web::get().to(get_dashboard_stats)));
+}
@@ -0,0 +1,225 @@
+//! Filesystem storage backend for development without Supabase credentials
+
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+
+use tokio::io::AsyncReadExt;
+
+use super::{FolderContent, ObjectStorage};
+
+/// Written and removed again by `health_check`
+const HEALTH_CHECK_FILE: &str = ".health-check";
+
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path of `name` under the root. Absolute paths and `..` components are
+    /// rejected so keys cannot escape the root directory.
+    fn resolve(&self, name: &str) -> Result<PathBuf, String> {
+        let relative = Path::new(name.trim_start_matches('/'));
+        if relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(format!("Invalid storage path: {}", name));
+        }
+        Ok(self.root.join(relative))
+    }
+
+    /// Resolve `name` and create its parent directories
+    async fn prepare_target(&self, name: &str) -> Result<PathBuf, String> {
+        let path = self.resolve(name)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("Failed to create directory for {}: {}", name, e))?;
+        }
+        Ok(path)
+    }
+}
+
+#[async_trait::async_trait]
+impl ObjectStorage for LocalStorage {
+    async fn upload_file(&self, filename: &str, file_data: &[u8]) -> Result<(), String> {
+        let path = self.prepare_target(filename).await?;
+        tokio::fs::write(&path, file_data)
+            .await
+            .map_err(|e| format!("Failed to write {}: {}", filename, e))?;
+        log::debug!("Stored {} at {}", filename, path.display());
+        Ok(())
+    }
+
+    async fn upload_new_file(&self, filename: &str, file_data: &[u8]) -> Result<bool, String> {
+        use tokio::io::AsyncWriteExt;
+
+        let path = self.prepare_target(filename).await?;
+        let mut file = match tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await
+        {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => return Ok(false),
+            Err(e) => return Err(format!("Failed to create {}: {}", filename, e)),
+        };
+        file.write_all(file_data)
+            .await
+            .map_err(|e| format!("Failed to write {}: {}", filename, e))?;
+        Ok(true)
+    }
+
+    async fn exists(&self, filename: &str) -> Result<bool, String> {
+        let path = self.resolve(filename)?;
+        match tokio::fs::metadata(&path).await {
+            Ok(metadata) => Ok(metadata.is_file()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(format!("Failed to check {}: {}", filename, e)),
+        }
+    }
+
+    async fn download_file(&self, filename: &str) -> Result<Vec<u8>, String> {
+        let path = self.resolve(filename)?;
+        tokio::fs::read(&path)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", filename, e))
+    }
+
+    async fn download_head(&self, filename: &str, len: usize) -> Result<Vec<u8>, String> {
+        let path = self.resolve(filename)?;
+        let file = tokio::fs::File::open(&path)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", filename, e))?;
+        let mut data = Vec::new();
+        file.take(len as u64)
+            .read_to_end(&mut data)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", filename, e))?;
+        Ok(data)
+    }
+
+    async fn delete_file(&self, filename: &str) -> Result<(), String> {
+        let path = self.resolve(filename)?;
+        tokio::fs::remove_file(&path)
+            .await
+            .map_err(|e| format!("Failed to delete {}: {}", filename, e))
+    }
+
+    async fn copy_file(&self, from: &str, to: &str) -> Result<(), String> {
+        let source = self.resolve(from)?;
+        let target = self.prepare_target(to).await?;
+        tokio::fs::copy(&source, &target)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Failed to copy {} to {}: {}", from, to, e))
+    }
+
+    async fn move_file(&self, from: &str, to: &str) -> Result<(), String> {
+        let source = self.resolve(from)?;
+        let target = self.prepare_target(to).await?;
+        tokio::fs::rename(&source, &target)
+            .await
+            .map_err(|e| format!("Failed to move {} to {}: {}", from, to, e))
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<usize, String> {
+        let path = self.resolve(super::checked_prefix(prefix)?)?;
+        let mut removed = 0;
+        let mut dirs = vec![path.clone()];
+        while let Some(dir) = dirs.pop() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(format!("Failed to list {}: {}", dir.display(), e)),
+            };
+            while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+                if entry.file_type().await.map_err(|e| e.to_string())?.is_dir() {
+                    dirs.push(entry.path());
+                } else {
+                    removed += 1;
+                }
+            }
+        }
+
+        match tokio::fs::remove_dir_all(&path).await {
+            Ok(()) => Ok(removed),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(format!("Failed to delete {}: {}", prefix, e)),
+        }
+    }
+
+    async fn create_folder(&self, folder_name: &str) -> Result<(), String> {
+        let path = self.resolve(folder_name)?;
+        tokio::fs::create_dir_all(&path)
+            .await
+            .map_err(|e| format!("Failed to create folder {}: {}", folder_name, e))
+    }
+
+    async fn remove_folder(&self, folder_name: &str) -> Result<bool, String> {
+        let path = self.resolve(folder_name)?;
+        match tokio::fs::remove_dir(&path).await {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+            // Files were stored in it; they are not ours to remove
+            Err(e) if e.kind() == ErrorKind::DirectoryNotEmpty => Ok(false),
+            Err(e) => Err(format!("Failed to remove folder {}: {}", folder_name, e)),
+        }
+    }
+
+    async fn list_folder_contents(&self, folder_name: &str) -> Result<Vec<FolderContent>, String> {
+        let path = self.resolve(folder_name)?;
+        let mut entries = match tokio::fs::read_dir(&path).await {
+            Ok(entries) => entries,
+            // Same as an empty prefix listing in Supabase
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to list {}: {}", folder_name, e)),
+        };
+
+        let mut contents = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+            let metadata = entry.metadata().await.map_err(|e| e.to_string())?;
+            contents.push(FolderContent {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                is_file: metadata.is_file(),
+                size: metadata.is_file().then_some(metadata.len()),
+            });
+        }
+        contents.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(contents)
+    }
+
+    async fn health_check(&self) -> Result<(), String> {
+        let probe = self.root.join(HEALTH_CHECK_FILE);
+        let result = async {
+            tokio::fs::create_dir_all(&self.root).await?;
+            tokio::fs::write(&probe, b"ok").await?;
+            tokio::fs::remove_file(&probe).await
+        }
+        .await;
+        result.map_err(|e| {
+            format!(
+                "Storage directory {} is not writable: {}",
+                self.root.display(),
+                e
+            )
+        })
+    }
+
+    fn get_asset_url(&self, filename: &str) -> String {
+        format!("/assets/serve/{}", filename)
+    }
+
+    fn local_path(&self, filename: &str) -> Option<PathBuf> {
+        self.resolve(filename).ok()
+    }
+}
@@ -0,0 +1,126 @@
+//! Storage wrapper counting failed operations for `/metrics`
+//!
+//! `AppStateBuilder` wraps every backend in [`MeteredStorage`], so failures
+//! are counted the same way for Supabase, S3 and local storage. Each call is
+//! passed straight to the inner backend, keeping its own overrides of the
+//! default trait methods.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{FolderContent, ObjectStorage};
+use crate::http_client::HttpMetrics;
+
+pub struct MeteredStorage {
+    inner: Arc<dyn ObjectStorage + Send + Sync>,
+    metrics: HttpMetrics,
+}
+
+impl MeteredStorage {
+    pub fn new(inner: Arc<dyn ObjectStorage + Send + Sync>, metrics: HttpMetrics) -> Self {
+        Self { inner, metrics }
+    }
+
+    /// Count `result` as a failed `operation` when it is an error
+    fn observe<T>(&self, operation: &str, result: Result<T, String>) -> Result<T, String> {
+        if result.is_err() {
+            self.metrics.record_failure(operation);
+        }
+        result
+    }
+}
+
+#[async_trait::async_trait]
+impl ObjectStorage for MeteredStorage {
+    async fn upload_file(&self, filename: &str, file_data: &[u8]) -> Result<(), String> {
+        let result = self.inner.upload_file(filename, file_data).await;
+        self.observe("upload", result)
+    }
+
+    async fn upload_new_file(&self, filename: &str, file_data: &[u8]) -> Result<bool, String> {
+        let result = self.inner.upload_new_file(filename, file_data).await;
+        self.observe("upload", result)
+    }
+
+    async fn exists(&self, filename: &str) -> Result<bool, String> {
+        let result = self.inner.exists(filename).await;
+        self.observe("exists", result)
+    }
+
+    async fn download_file(&self, filename: &str) -> Result<Vec<u8>, String> {
+        let result = self.inner.download_file(filename).await;
+        self.observe("download", result)
+    }
+
+    async fn download_head(&self, filename: &str, len: usize) -> Result<Vec<u8>, String> {
+        let result = self.inner.download_head(filename, len).await;
+        self.observe("download", result)
+    }
+
+    async fn delete_file(&self, filename: &str) -> Result<(), String> {
+        let result = self.inner.delete_file(filename).await;
+        self.observe("delete", result)
+    }
+
+    async fn copy_file(&self, from: &str, to: &str) -> Result<(), String> {
+        let result = self.inner.copy_file(from, to).await;
+        self.observe("copy", result)
+    }
+
+    async fn move_file(&self, from: &str, to: &str) -> Result<(), String> {
+        let result = self.inner.move_file(from, to).await;
+        self.observe("move", result)
+    }
+
+    async fn create_folder(&self, folder_name: &str) -> Result<(), String> {
+        let result = self.inner.create_folder(folder_name).await;
+        self.observe("create_folder", result)
+    }
+
+    async fn remove_folder(&self, folder_name: &str) -> Result<bool, String> {
+        let result = self.inner.remove_folder(folder_name).await;
+        self.observe("remove_folder", result)
+    }
+
+    async fn list_folder_contents(&self, folder_name: &str) -> Result<Vec<FolderContent>, String> {
+        let result = self.inner.list_folder_contents(folder_name).await;
+        self.observe("list", result)
+    }
+
+    fn get_asset_url(&self, filename: &str) -> String {
+        self.inner.get_asset_url(filename)
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<usize, String> {
+        let result = self.inner.delete_prefix(prefix).await;
+        self.observe("delete_prefix", result)
+    }
+
+    async fn health_check(&self) -> Result<(), String> {
+        let result = self.inner.health_check().await;
+        self.observe("health_check", result)
+    }
+
+    async fn get_signed_url(&self, filename: &str, ttl: Duration) -> Result<String, String> {
+        let result = self.inner.get_signed_url(filename, ttl).await;
+        self.observe("signed_url", result)
+    }
+
+    async fn get_download_url(
+        &self,
+        filename: &str,
+        ttl: Duration,
+        download_name: &str,
+    ) -> Result<String, String> {
+        let result = self
+            .inner
+            .get_download_url(filename, ttl, download_name)
+            .await;
+        self.observe("signed_url", result)
+    }
+
+    fn local_path(&self, filename: &str) -> Option<PathBuf> {
+        self.inner.local_path(filename)
+    }
+}
@@ -0,0 +1,1280 @@
+pub mod local;
+pub mod metered;
+pub mod s3;
+
+use log;
+use mime_guess;
+use moka::future::Cache;
+use moka::Expiry;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest;
+use sanitize_filename::sanitize;
+use serde_json::Value;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::config::parse_bool;
+use crate::http_client::Retry;
+
+pub use local::LocalStorage;
+pub use metered::MeteredStorage;
+pub use s3::{S3Config, S3Storage};
+
+const DEFAULT_LOCAL_STORAGE_PATH: &str = "storage";
+/// Signed URLs are dropped from the cache this long before they expire, so a
+/// redirect never hands out a URL that is about to stop working
+const SIGNED_URL_EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+const SIGNED_URL_CACHE_CAPACITY: u64 = 10_000;
+/// Entries per Supabase list request
+const LIST_PAGE_SIZE: usize = 100;
+/// Most keys Supabase accepts in one bulk delete
+const DELETE_BATCH_SIZE: usize = 1000;
+/// Characters left unescaped in an object key path segment
+const KEY_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, utoipa::ToSchema)]
+pub struct FolderContent {
+    pub name: String,
+    pub is_file: bool,
+    pub size: Option<u64>,
+}
+
+#[derive(Clone)]
+pub struct SupabaseConfig {
+    pub supabase_url: String,
+    pub supabase_anon_key: String,
+    pub bucket_name: String,
+    /// Used instead of the anon key for storage calls when set. Required
+    /// for a private bucket.
+    pub service_role_key: Option<String>,
+    /// When false, assets are served through signed URLs
+    pub bucket_public: bool,
+}
+
+impl fmt::Debug for SupabaseConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SupabaseConfig")
+            .field("supabase_url", &self.supabase_url)
+            .field("supabase_anon_key", &self.supabase_anon_key)
+            .field("bucket_name", &self.bucket_name)
+            .field(
+                "service_role_key",
+                &self.service_role_key.as_ref().map(|_| "<redacted>"),
+            )
+            .field("bucket_public", &self.bucket_public)
+            .finish()
+    }
+}
+
+impl SupabaseConfig {
+    /// Load from process environment. Prefer `AppConfig::from_env`, which
+    /// reports this section's errors together with every other section's.
+    pub fn from_env() -> Result<Self, String> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// Load using a custom variable lookup. All missing variables are named
+    /// in the error.
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        log::debug!("Loading Supabase configuration from environment");
+        let get = |key: &str| {
+            lookup(key)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        let supabase_url = get("SUPABASE_URL");
+        let supabase_anon_key = get("SUPABASE_ANON_KEY");
+        let bucket_name =
+            get("BUCKET_NAME").unwrap_or_else(|| "cakung-barat-supabase-bucket".to_string());
+        let service_role_key = get("SUPABASE_SERVICE_ROLE_KEY");
+        let bucket_public = match get("SUPABASE_BUCKET_PUBLIC") {
+            Some(v) => parse_bool("SUPABASE_BUCKET_PUBLIC", &v)?,
+            None => true,
+        };
+        if !bucket_public && service_role_key.is_none() {
+            return Err(
+                "SUPABASE_SERVICE_ROLE_KEY must be set when SUPABASE_BUCKET_PUBLIC=false"
+                    .to_string(),
+            );
+        }
+
+        match (supabase_url, supabase_anon_key) {
+            (Some(supabase_url), Some(supabase_anon_key)) => {
+                log::debug!(
+                    "Supabase configuration loaded successfully for bucket: {}",
+                    bucket_name
+                );
+                Ok(SupabaseConfig {
+                    supabase_url,
+                    supabase_anon_key,
+                    bucket_name,
+                    service_role_key,
+                    bucket_public,
+                })
+            }
+            (url, key) => {
+                let missing: Vec<&str> = [("SUPABASE_URL", url), ("SUPABASE_ANON_KEY", key)]
+                    .into_iter()
+                    .filter(|(_, value)| value.is_none())
+                    .map(|(name, _)| name)
+                    .collect();
+                Err(format!("{} must be set", missing.join(", ")))
+            }
+        }
+    }
+
+    /// Key for storage calls: the service role key when configured,
+    /// otherwise the anon key
+    pub fn service_key(&self) -> &str {
+        self.service_role_key
+            .as_deref()
+            .unwrap_or(&self.supabase_anon_key)
+    }
+}
+
+/// Storage backend chosen by `STORAGE_BACKEND`
+#[derive(Clone, Debug)]
+pub enum StorageConfig {
+    Supabase(SupabaseConfig),
+    /// Files under a local directory, for development
+    Local {
+        root: PathBuf,
+    },
+    /// AWS S3 or a compatible service such as MinIO
+    S3(S3Config),
+}
+
+impl StorageConfig {
+    /// `STORAGE_BACKEND=supabase` (default) requires the Supabase variables,
+    /// `s3` the `S3_*` variables; `local` only reads `LOCAL_STORAGE_PATH`.
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let get = |key: &str| {
+            lookup(key)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        match get("STORAGE_BACKEND")
+            .map(|v| v.to_ascii_lowercase())
+            .as_deref()
+        {
+            None | Some("supabase") => SupabaseConfig::from_lookup(&lookup).map(Self::Supabase),
+            Some("local") => Ok(Self::Local {
+                root: get("LOCAL_STORAGE_PATH")
+                    .unwrap_or_else(|| DEFAULT_LOCAL_STORAGE_PATH.to_string())
+                    .into(),
+            }),
+            Some("s3") => S3Config::from_lookup(&lookup).map(Self::S3),
+            Some(other) => Err(format!(
+                "STORAGE_BACKEND must be 'supabase', 's3' or 'local', got '{}'",
+                other
+            )),
+        }
+    }
+
+    /// Create the configured backend. `client` and `retry` are not used by
+    /// local storage, `max_concurrency` only by Supabase.
+    pub fn build(
+        &self,
+        client: reqwest::Client,
+        retry: Retry,
+        max_concurrency: usize,
+    ) -> Arc<dyn ObjectStorage + Send + Sync> {
+        match self {
+            Self::Supabase(config) => Arc::new(
+                SupabaseStorage::new(config.clone(), client)
+                    .with_retry(retry)
+                    .with_concurrency_limit(max_concurrency),
+            ),
+            Self::S3(config) => Arc::new(S3Storage::new(config.clone(), client).with_retry(retry)),
+            Self::Local { root } => {
+                log::info!("Using local storage at {}", root.display());
+                Arc::new(LocalStorage::new(root.clone()))
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+pub trait ObjectStorage {
+    /// Upload `file_data`, replacing any existing object with this name.
+    /// Meant for internal writes that are safe to repeat.
+    async fn upload_file(&self, filename: &str, file_data: &[u8]) -> Result<(), String>;
+
+    /// Upload without replacing an existing object. Returns `Ok(false)`,
+    /// having written nothing, when the name is taken. Backends without
+    /// conditional writes check `exists` first, which leaves a small window
+    /// for a concurrent writer of the same name.
+    async fn upload_new_file(&self, filename: &str, file_data: &[u8]) -> Result<bool, String> {
+        if self.exists(filename).await? {
+            return Ok(false);
+        }
+        self.upload_file(filename, file_data).await.map(|()| true)
+    }
+
+    /// Whether an object with this name exists. The default looks for it in
+    /// the listing of its folder.
+    async fn exists(&self, filename: &str) -> Result<bool, String> {
+        let (folder, name) = filename.rsplit_once('/').unwrap_or(("", filename));
+        Ok(self
+            .list_folder_contents(folder)
+            .await?
+            .iter()
+            .any(|item| item.is_file && item.name == name))
+    }
+    async fn download_file(&self, filename: &str) -> Result<Vec<u8>, String>;
+
+    /// The first `len` bytes of an object, or all of it when shorter. The
+    /// default downloads the whole object; backends override it with a
+    /// ranged read.
+    async fn download_head(&self, filename: &str, len: usize) -> Result<Vec<u8>, String> {
+        let mut data = self.download_file(filename).await?;
+        data.truncate(len);
+        Ok(data)
+    }
+
+    async fn delete_file(&self, filename: &str) -> Result<(), String>;
+
+    /// Copy an object within the bucket. The default goes through this
+    /// process; backends override it with a server-side copy.
+    async fn copy_file(&self, from: &str, to: &str) -> Result<(), String> {
+        let data = self.download_file(from).await?;
+        self.upload_file(to, &data).await
+    }
+
+    /// Move an object within the bucket, replacing any object at `to`
+    async fn move_file(&self, from: &str, to: &str) -> Result<(), String> {
+        self.copy_file(from, to).await?;
+        self.delete_file(from).await
+    }
+
+    async fn create_folder(&self, folder_name: &str) -> Result<(), String>;
+
+    /// Remove what [`Self::create_folder`] wrote for `folder_name`, keeping
+    /// any other object under it. Returns whether there was anything to
+    /// remove; backends without folder markers have nothing.
+    async fn remove_folder(&self, _folder_name: &str) -> Result<bool, String> {
+        Ok(false)
+    }
+
+    async fn list_folder_contents(&self, folder_name: &str) -> Result<Vec<FolderContent>, String>;
+    fn get_asset_url(&self, filename: &str) -> String;
+
+    /// Delete every object under `prefix`, including nested folders, and
+    /// return how many were removed. Empty and root prefixes are refused.
+    async fn delete_prefix(&self, prefix: &str) -> Result<usize, String> {
+        let prefix = checked_prefix(prefix)?;
+        let mut removed = 0;
+        let mut folders = vec![prefix.to_string()];
+        while let Some(folder) = folders.pop() {
+            for item in self.list_folder_contents(&folder).await? {
+                let path = format!("{}/{}", folder, item.name);
+                if item.is_file {
+                    self.delete_file(&path).await?;
+                    removed += 1;
+                } else {
+                    folders.push(path);
+                }
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Check that the bucket exists and the credentials are accepted.
+    /// Called at startup and by `/readyz`.
+    async fn health_check(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// URL granting read access to a private object for about `ttl`.
+    /// Backends without access control return the public URL.
+    async fn get_signed_url(&self, filename: &str, _ttl: Duration) -> Result<String, String> {
+        Ok(self.get_asset_url(filename))
+    }
+
+    /// Like [`Self::get_signed_url`], but the browser saves the object as
+    /// `download_name`. Backends without such an option ignore the name.
+    async fn get_download_url(
+        &self,
+        filename: &str,
+        ttl: Duration,
+        _download_name: &str,
+    ) -> Result<String, String> {
+        self.get_signed_url(filename, ttl).await
+    }
+
+    /// Path of the object on local disk, for backends that store files
+    /// locally. `serve_asset` streams these instead of redirecting.
+    fn local_path(&self, _filename: &str) -> Option<PathBuf> {
+        None
+    }
+}
+
+/// A signed URL and how long it may be handed out from the cache
+#[derive(Clone)]
+struct SignedUrl {
+    url: String,
+    cache_for: Duration,
+}
+
+struct SignedUrlExpiry;
+
+impl Expiry<(String, u64), SignedUrl> for SignedUrlExpiry {
+    fn expire_after_create(
+        &self,
+        _key: &(String, u64),
+        value: &SignedUrl,
+        _created_at: Instant,
+    ) -> Option<Duration> {
+        Some(value.cache_for)
+    }
+}
+
+/// Attempts at finding a free name in [`upload_with_unique_name`]
+const UNIQUE_NAME_ATTEMPTS: usize = 3;
+
+/// Upload a user file under a name from `make_name`, called with the attempt
+/// number. If the name is already taken, a new one is drawn and the upload
+/// retried. Returns the name the file was stored under.
+pub async fn upload_with_unique_name<F>(
+    storage: &(dyn ObjectStorage + Send + Sync),
+    file_data: &[u8],
+    mut make_name: F,
+) -> Result<String, String>
+where
+    F: FnMut(usize) -> String,
+{
+    for attempt in 0..UNIQUE_NAME_ATTEMPTS {
+        let name = make_name(attempt);
+        if storage.upload_new_file(&name, file_data).await? {
+            return Ok(name);
+        }
+        log::warn!("Storage name {} is taken, picking another", name);
+    }
+    Err(format!(
+        "No free storage name after {} attempts",
+        UNIQUE_NAME_ATTEMPTS
+    ))
+}
+
+/// Percent-encode an object key for use in a request path. `/` is kept as
+/// the folder separator, everything else outside the unreserved set is
+/// escaped so spaces, `#`, `?` and non-ASCII names reach the backend intact.
+pub fn encode_key(key: &str) -> String {
+    key.split('/')
+        .map(|segment| utf8_percent_encode(segment, KEY_SEGMENT).to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Trim slashes from a prefix about to be deleted, refusing one that would
+/// cover the whole bucket
+pub fn checked_prefix(prefix: &str) -> Result<&str, String> {
+    let trimmed = prefix.trim_matches('/');
+    if trimmed.is_empty() {
+        return Err(format!(
+            "Refusing to delete the bucket root (prefix '{}')",
+            prefix
+        ));
+    }
+    Ok(trimmed)
+}
+
+pub struct SupabaseStorage {
+    pub config: SupabaseConfig,
+    pub client: reqwest::Client,
+    /// Applied to uploads, downloads, deletes, listings and URL signing
+    pub retry: Retry,
+    /// Signed URLs keyed by filename and TTL in seconds
+    signed_urls: Cache<(String, u64), SignedUrl>,
+    /// Caps uploads, downloads and deletes in flight so bulk operations do
+    /// not trip Supabase rate limits
+    limiter: Arc<Semaphore>,
+}
+
+impl SupabaseStorage {
+    pub fn new(config: SupabaseConfig, client: reqwest::Client) -> Self {
+        Self {
+            config,
+            client,
+            retry: Retry::default(),
+            signed_urls: Cache::builder()
+                .max_capacity(SIGNED_URL_CACHE_CAPACITY)
+                .expire_after(SignedUrlExpiry)
+                .build(),
+            limiter: Arc::new(Semaphore::new(
+                crate::http_client::HttpClientConfig::default().storage_max_concurrency,
+            )),
+        }
+    }
+
+    pub fn with_retry(mut self, retry: Retry) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_concurrency_limit(mut self, limit: usize) -> Self {
+        self.limiter = Arc::new(Semaphore::new(limit.max(1)));
+        self
+    }
+
+    async fn permit(&self) -> SemaphorePermit<'_> {
+        self.limiter
+            .acquire()
+            .await
+            .expect("storage limiter is never closed")
+    }
+}
+
+#[async_trait::async_trait]
+impl ObjectStorage for SupabaseStorage {
+    async fn upload_file(&self, filename: &str, file_data: &[u8]) -> Result<(), String> {
+        let _permit = self.permit().await;
+        let _timer = self.retry.metrics.start_timer("upload");
+        upload_file_to_supabase(
+            filename,
+            file_data,
+            &self.client,
+            &self.config,
+            &self.retry,
+            true,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    async fn upload_new_file(&self, filename: &str, file_data: &[u8]) -> Result<bool, String> {
+        let _permit = self.permit().await;
+        let _timer = self.retry.metrics.start_timer("upload");
+        upload_file_to_supabase(
+            filename,
+            file_data,
+            &self.client,
+            &self.config,
+            &self.retry,
+            false,
+        )
+        .await
+    }
+
+    async fn exists(&self, filename: &str) -> Result<bool, String> {
+        let _permit = self.permit().await;
+        let _timer = self.retry.metrics.start_timer("exists");
+        file_exists_in_supabase(filename, &self.client, &self.config, &self.retry).await
+    }
+
+    async fn download_file(&self, filename: &str) -> Result<Vec<u8>, String> {
+        let _permit = self.permit().await;
+        let _timer = self.retry.metrics.start_timer("download");
+        download_file_from_supabase(filename, &self.client, &self.config, &self.retry).await
+    }
+
+    async fn download_head(&self, filename: &str, len: usize) -> Result<Vec<u8>, String> {
+        let _permit = self.permit().await;
+        let _timer = self.retry.metrics.start_timer("download");
+        download_range_from_supabase(filename, Some(len), &self.client, &self.config, &self.retry)
+            .await
+    }
+
+    async fn delete_file(&self, filename: &str) -> Result<(), String> {
+        let _permit = self.permit().await;
+        let _timer = self.retry.metrics.start_timer("delete");
+        delete_asset_file(filename, &self.client, &self.config, &self.retry).await
+    }
+
+    async fn copy_file(&self, from: &str, to: &str) -> Result<(), String> {
+        let _timer = self.retry.metrics.start_timer("copy");
+        copy_asset_file(from, to, &self.client, &self.config, &self.retry).await
+    }
+
+    async fn move_file(&self, from: &str, to: &str) -> Result<(), String> {
+        let _timer = self.retry.metrics.start_timer("move");
+        move_asset_file(from, to, &self.client, &self.config).await
+    }
+
+    async fn create_folder(&self, folder_name: &str) -> Result<(), String> {
+        if self.exists(&folder_placeholder(folder_name)).await? {
+            log::debug!("Folder {} already exists", folder_name);
+            return Ok(());
+        }
+        let _timer = self.retry.metrics.start_timer("create_folder");
+        create_folder(folder_name, &self.client, &self.config).await
+    }
+
+    async fn remove_folder(&self, folder_name: &str) -> Result<bool, String> {
+        let placeholder = folder_placeholder(folder_name);
+        if !self.exists(&placeholder).await? {
+            return Ok(false);
+        }
+        self.delete_file(&placeholder).await?;
+        Ok(true)
+    }
+
+    async fn list_folder_contents(&self, folder_name: &str) -> Result<Vec<FolderContent>, String> {
+        let _timer = self.retry.metrics.start_timer("list");
+        list_folder_contents(folder_name, &self.client, &self.config, &self.retry).await
+    }
+
+    fn get_asset_url(&self, filename: &str) -> String {
+        get_supabase_asset_url(filename, &self.config)
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<usize, String> {
+        let _permit = self.permit().await;
+        let _timer = self.retry.metrics.start_timer("delete_prefix");
+        delete_prefix_from_supabase(prefix, &self.client, &self.config, &self.retry).await
+    }
+
+    async fn health_check(&self) -> Result<(), String> {
+        check_bucket(&self.client, &self.config).await
+    }
+
+    async fn get_signed_url(&self, filename: &str, ttl: Duration) -> Result<String, String> {
+        if self.config.bucket_public {
+            return Ok(self.get_asset_url(filename));
+        }
+
+        let key = (filename.to_string(), ttl.as_secs());
+        if let Some(cached) = self.signed_urls.get(&key).await {
+            return Ok(cached.url);
+        }
+
+        let url = create_signed_url(filename, ttl, &self.client, &self.config, &self.retry).await?;
+        // Very short TTLs are not cached at all
+        let cache_for = ttl.saturating_sub(SIGNED_URL_EXPIRY_MARGIN.min(ttl / 2));
+        if !cache_for.is_zero() {
+            self.signed_urls
+                .insert(
+                    key,
+                    SignedUrl {
+                        url: url.clone(),
+                        cache_for,
+                    },
+                )
+                .await;
+        }
+        Ok(url)
+    }
+
+    async fn get_download_url(
+        &self,
+        filename: &str,
+        ttl: Duration,
+        download_name: &str,
+    ) -> Result<String, String> {
+        let url = self.get_signed_url(filename, ttl).await?;
+        let separator = if url.contains('?') { '&' } else { '?' };
+        Ok(format!(
+            "{}{}download={}",
+            url,
+            separator,
+            utf8_percent_encode(download_name, KEY_SEGMENT)
+        ))
+    }
+}
+
+/// Upload `file_data`. With `overwrite` an existing object is replaced;
+/// without it, `Ok(false)` is returned when the name is already taken.
+pub async fn upload_file_to_supabase(
+    filename: &str,
+    file_data: &[u8],
+    client: &reqwest::Client,
+    config: &SupabaseConfig,
+    retry: &Retry,
+    overwrite: bool,
+) -> Result<bool, String> {
+    log::info!(
+        "Attempting to upload asset file to Supabase storage: {}",
+        filename
+    );
+    log::debug!("Uploading file data to Supabase storage: {}", filename);
+
+    let upload_url = format!(
+        "{}/storage/v1/object/{}/{}",
+        config.supabase_url,
+        config.bucket_name,
+        encode_key(filename)
+    );
+    log::debug!("Supabase upload URL: {}", upload_url);
+
+    // Determine content type based on file extension for better compatibility
+    let content_type = mime_guess::from_path(filename)
+        .first_or_octet_stream()
+        .to_string();
+
+    let response = retry
+        .send("upload", || {
+            client
+                .post(&upload_url)
+                .header("Authorization", format!("Bearer {}", config.service_key()))
+                .header("apikey", config.service_key())
+                .header("Content-Type", &content_type) // Use appropriate content type based on file extension
+                .header("x-upsert", overwrite.to_string())
+                .body(file_data.to_vec())
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.status().is_success() {
+        log::info!(
+            "Successfully uploaded asset file to Supabase storage: {}",
+            filename
+        );
+        Ok(true)
+    } else {
+        let status = response.status();
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        // Storage reports a duplicate as 400 with statusCode "409" in the body
+        if !overwrite && (status == reqwest::StatusCode::CONFLICT || is_duplicate(&error_text)) {
+            log::info!("Object {} already exists in Supabase storage", filename);
+            return Ok(false);
+        }
+        log::error!(
+            "Upload failed for file {} with status: {}: {}",
+            filename,
+            status,
+            error_text
+        );
+        Err(format!("Upload failed with status: {}", status))
+    }
+}
+
+/// Whether a Supabase error body reports a missing object. The storage API
+/// answers 400 with `statusCode: "404"` in the body for those.
+fn is_not_found(error_text: &str) -> bool {
+    serde_json::from_str::<Value>(error_text)
+        .ok()
+        .and_then(|body| {
+            body.get("statusCode")
+                .map(|code| code == "404" || code == 404)
+        })
+        .unwrap_or(false)
+}
+
+fn is_duplicate(error_text: &str) -> bool {
+    serde_json::from_str::<Value>(error_text)
+        .ok()
+        .and_then(|body| {
+            body.get("statusCode")
+                .map(|code| code == "409" || code == 409)
+        })
+        .unwrap_or(false)
+        || error_text.contains("already exists")
+}
+
+/// Look `filename` up through the object info endpoint
+pub async fn file_exists_in_supabase(
+    filename: &str,
+    client: &reqwest::Client,
+    config: &SupabaseConfig,
+    retry: &Retry,
+) -> Result<bool, String> {
+    let info_url = format!(
+        "{}/storage/v1/object/info/{}/{}",
+        config.supabase_url,
+        config.bucket_name,
+        encode_key(filename)
+    );
+    log::debug!("Supabase object info URL: {}", info_url);
+
+    let response = retry
+        .send("exists", || {
+            client
+                .get(&info_url)
+                .header("Authorization", format!("Bearer {}", config.service_key()))
+                .header("apikey", config.service_key())
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(true);
+    }
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Ok(false);
+    }
+    let error_text = response
+        .text()
+        .await
+        .unwrap_or_else(|_| "Unknown error".to_string());
+    if is_not_found(&error_text) {
+        return Ok(false);
+    }
+    log::error!(
+        "Object info failed for file {} with status: {}: {}",
+        filename,
+        status,
+        error_text
+    );
+    Err(format!("Object info failed with status: {}", status))
+}
+
+pub async fn download_file_from_supabase(
+    filename: &str,
+    client: &reqwest::Client,
+    config: &SupabaseConfig,
+    retry: &Retry,
+) -> Result<Vec<u8>, String> {
+    download_range_from_supabase(filename, None, client, config, retry).await
+}
+
+/// Download an object, or only its first `len` bytes
+async fn download_range_from_supabase(
+    filename: &str,
+    len: Option<usize>,
+    client: &reqwest::Client,
+    config: &SupabaseConfig,
+    retry: &Retry,
+) -> Result<Vec<u8>, String> {
+    log::info!(
+        "Attempting to download file from Supabase storage: {}",
+        filename
+    );
+
+    let download_url = format!(
+        "{}/storage/v1/object/{}/{}",
+        config.supabase_url,
+        config.bucket_name,
+        encode_key(filename)
+    );
+    log::debug!("Supabase download URL: {}", download_url);
+
+    let response = retry
+        .send("download", || {
+            let request = client
+                .get(&download_url)
+                .header("Authorization", format!("Bearer {}", config.service_key()))
+                .header("apikey", config.service_key());
+            match len {
+                Some(len) => request.header("Range", byte_range(len)),
+                None => request,
+            }
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.status().is_success() {
+        log::info!(
+            "Successfully downloaded file from Supabase storage: {}",
+            filename
+        );
+        let bytes = response.bytes().await.map_err(|e| e.to_string())?;
+        // A server ignoring the range sends the whole object
+        let len = len.unwrap_or(bytes.len()).min(bytes.len());
+        Ok(bytes[..len].to_vec())
+    } else {
+        let status = response.status();
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        log::error!(
+            "Download failed for file {} with status: {}: {}",
+            filename,
+            status,
+            error_text
+        );
+        Err(format!("Download failed with status: {}", status))
+    }
+}
+
+/// `Range` header value asking for the first `len` bytes
+pub(crate) fn byte_range(len: usize) -> String {
+    format!("bytes=0-{}", len.saturating_sub(1))
+}
+
+pub async fn delete_asset_file(
+    filename: &str,
+    client: &reqwest::Client,
+    config: &SupabaseConfig,
+    retry: &Retry,
+) -> Result<(), String> {
+    log::info!(
+        "Attempting to delete asset file from Supabase storage: {}",
+        filename
+    );
+
+    let delete_url = format!(
+        "{}/storage/v1/object/{}/{}",
+        config.supabase_url,
+        config.bucket_name,
+        encode_key(filename)
+    );
+    log::debug!("Supabase delete URL: {}", delete_url);
+
+    let response = retry
+        .send("delete", || {
+            client
+                .delete(&delete_url)
+                .header("Authorization", format!("Bearer {}", config.service_key()))
+                .header("apikey", config.service_key())
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.status().is_success() {
+        log::info!(
+            "Successfully deleted asset file from Supabase storage: {}",
+            filename
+        );
+        Ok(())
+    } else {
+        log::error!(
+            "Delete failed for file {} with status: {}",
+            filename,
+            response.status()
+        );
+        Err(format!("Delete failed with status: {}", response.status()))
+    }
+}
+
+/// Delete all objects under `prefix`. Keys are collected with paginated
+/// listings first, then removed with the bulk delete endpoint.
+pub async fn delete_prefix_from_supabase(
+    prefix: &str,
+    client: &reqwest::Client,
+    config: &SupabaseConfig,
+    retry: &Retry,
+) -> Result<usize, String> {
+    let prefix = checked_prefix(prefix)?;
+    log::info!("Deleting everything under {} from Supabase storage", prefix);
+
+    let mut keys = Vec::new();
+    let mut folders = vec![prefix.to_string()];
+    while let Some(folder) = folders.pop() {
+        let mut offset = 0;
+        loop {
+            let page =
+                list_folder_page(&folder, LIST_PAGE_SIZE, offset, client, config, retry).await?;
+            let page_len = page.len();
+            for item in page {
+                let path = format!("{}/{}", folder, item.name);
+                if item.is_file {
+                    keys.push(path);
+                } else {
+                    folders.push(path);
+                }
+            }
+            if page_len < LIST_PAGE_SIZE {
+                break;
+            }
+            offset += page_len;
+        }
+    }
+
+    let delete_url = format!(
+        "{}/storage/v1/object/{}",
+        config.supabase_url, config.bucket_name
+    );
+    for batch in keys.chunks(DELETE_BATCH_SIZE) {
+        let body = serde_json::json!({ "prefixes": batch });
+        let response = retry
+            .send("delete", || {
+                client
+                    .delete(&delete_url)
+                    .header("Authorization", format!("Bearer {}", config.service_key()))
+                    .header("apikey", config.service_key())
+                    .json(&body)
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            log::error!(
+                "Bulk delete under {} failed with status: {}",
+                prefix,
+                status
+            );
+            return Err(format!("Bulk delete failed with status: {}", status));
+        }
+    }
+
+    log::info!("Deleted {} objects under {}", keys.len(), prefix);
+    Ok(keys.len())
+}
+
+/// Server-side copy within the bucket
+pub async fn copy_asset_file(
+    from: &str,
+    to: &str,
+    client: &reqwest::Client,
+    config: &SupabaseConfig,
+    retry: &Retry,
+) -> Result<(), String> {
+    log::info!("Copying {} to {} in Supabase storage", from, to);
+    let response = retry
+        .send("copy", || {
+            transfer_request("copy", from, to, client, config)
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+    transfer_result("Copy", from, to, response).await
+}
+
+/// Server-side move within the bucket. Not retried: a move that succeeded
+/// but timed out would fail on retry because the source is gone.
+pub async fn move_asset_file(
+    from: &str,
+    to: &str,
+    client: &reqwest::Client,
+    config: &SupabaseConfig,
+) -> Result<(), String> {
+    log::info!("Moving {} to {} in Supabase storage", from, to);
+    let response = transfer_request("move", from, to, client, config)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    transfer_result("Move", from, to, response).await
+}
+
+fn transfer_request(
+    action: &str,
+    from: &str,
+    to: &str,
+    client: &reqwest::Client,
+    config: &SupabaseConfig,
+) -> reqwest::RequestBuilder {
+    client
+        .post(format!(
+            "{}/storage/v1/object/{}",
+            config.supabase_url, action
+        ))
+        .header("Authorization", format!("Bearer {}", config.service_key()))
+        .header("apikey", config.service_key())
+        .json(&serde_json::json!({
+            "bucketId": config.bucket_name,
+            "sourceKey": from,
+            "destinationKey": to,
+        }))
+}
+
+async fn transfer_result(
+    action: &str,
+    from: &str,
+    to: &str,
+    response: reqwest::Response,
+) -> Result<(), String> {
+    let status = response.status();
+    if status.is_success() {
+        log::info!("{} of {} to {} succeeded", action, from, to);
+        return Ok(());
+    }
+
+    let error_text = response.text().await.unwrap_or_default();
+    log::error!(
+        "{} of {} to {} failed with status {}: {}",
+        action,
+        from,
+        to,
+        status,
+        error_text
+    );
+    // Storage reports a missing source as 400 with a not_found error body
+    if status == reqwest::StatusCode::NOT_FOUND || error_text.contains("not_found") {
+        Err(format!("{} failed: object {} not found", action, from))
+    } else {
+        Err(format!("{} failed with status: {}", action, status))
+    }
+}
+
+/// List one entry at the bucket root to verify the bucket and key. Storage
+/// wraps most errors in a 400 whose body carries the real status code.
+pub async fn check_bucket(client: &reqwest::Client, config: &SupabaseConfig) -> Result<(), String> {
+    let list_url = format!(
+        "{}/storage/v1/object/list/{}",
+        config.supabase_url, config.bucket_name
+    );
+    let response = client
+        .post(&list_url)
+        .header("Authorization", format!("Bearer {}", config.service_key()))
+        .header("apikey", config.service_key())
+        .json(&serde_json::json!({ "prefix": "", "limit": 1 }))
+        .send()
+        .await
+        .map_err(|e| format!("Supabase storage is unreachable: {}", e))?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+
+    let body: Value = response.json().await.unwrap_or(Value::Null);
+    let code = body
+        .get("statusCode")
+        .and_then(|c| match c {
+            Value::String(s) => s.parse::<u16>().ok(),
+            other => other.as_u64().and_then(|n| u16::try_from(n).ok()),
+        })
+        .unwrap_or(status.as_u16());
+    match code {
+        401 | 403 => Err(format!(
+            "Supabase rejected the storage credentials (status {}); check SUPABASE_ANON_KEY \
+             and SUPABASE_SERVICE_ROLE_KEY",
+            code
+        )),
+        404 => Err(format!(
+            "Bucket '{}' not found; check BUCKET_NAME",
+            config.bucket_name
+        )),
+        _ => Err(format!(
+            "Storage health check failed with status {}: {}",
+            status, body
+        )),
+    }
+}
+
+pub fn get_supabase_asset_url(filename: &str, config: &SupabaseConfig) -> String {
+    log::debug!("Generating Supabase asset URL for file: {}", filename);
+    let url = format!(
+        "{}/storage/v1/object/public/{}/{}",
+        config.supabase_url,
+        config.bucket_name,
+        encode_key(filename)
+    );
+    log::debug!("Generated Supabase asset URL: {}", url);
+    url
+}
+
+/// Ask Supabase for a URL to `filename` that stays valid for `ttl`
+pub async fn create_signed_url(
+    filename: &str,
+    ttl: Duration,
+    client: &reqwest::Client,
+    config: &SupabaseConfig,
+    retry: &Retry,
+) -> Result<String, String> {
+    log::debug!("Requesting signed URL for file: {}", filename);
+
+    let sign_url = format!(
+        "{}/storage/v1/object/sign/{}/{}",
+        config.supabase_url,
+        config.bucket_name,
+        encode_key(filename)
+    );
+    let body = serde_json::json!({ "expiresIn": ttl.as_secs().max(1) });
+
+    let response = retry
+        .send("sign", || {
+            client
+                .post(&sign_url)
+                .header("Authorization", format!("Bearer {}", config.service_key()))
+                .header("apikey", config.service_key())
+                .json(&body)
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        log::error!(
+            "Signing URL for file {} failed with status: {}",
+            filename,
+            status
+        );
+        return Err(format!("Signing URL failed with status: {}", status));
+    }
+
+    let signed: Value = response.json().await.map_err(|e| e.to_string())?;
+    let path = signed
+        .get("signedURL")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Sign response has no signedURL".to_string())?;
+    // The returned path is relative to the storage API root
+    Ok(format!("{}/storage/v1{}", config.supabase_url, path))
+}
+
+/// Name of the file marking an otherwise empty folder
+pub const FOLDER_PLACEHOLDER: &str = "placeholder.txt";
+
+/// Object written to make `folder_name` show up in listings
+fn folder_placeholder(folder_name: &str) -> String {
+    format!("{}/{}", sanitize(folder_name), FOLDER_PLACEHOLDER)
+}
+
+pub async fn create_folder(
+    folder_name: &str,
+    client: &reqwest::Client,
+    config: &SupabaseConfig,
+) -> Result<(), String> {
+    log::info!(
+        "Attempting to create folder in Supabase storage: {}",
+        folder_name
+    );
+
+    let placeholder_filename = folder_placeholder(folder_name);
+    let placeholder_data = b"Folder placeholder";
+    log::debug!(
+        "Creating folder with placeholder file: {}",
+        placeholder_filename
+    );
+
+    let upload_url = format!(
+        "{}/storage/v1/object/{}/{}",
+        config.supabase_url,
+        config.bucket_name,
+        encode_key(&placeholder_filename)
+    );
+    log::debug!("Supabase folder creation URL: {}", upload_url);
+
+    let response = client
+        .post(&upload_url)
+        .header("Authorization", format!("Bearer {}", config.service_key()))
+        .header("apikey", config.service_key())
+        .header("x-upsert", "true") // Creating an existing folder is not an error
+        .body(placeholder_data.to_vec())
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.status().is_success() {
+        log::info!(
+            "Successfully created folder in Supabase storage: {}",
+            folder_name
+        );
+        Ok(())
+    } else {
+        log::error!(
+            "Folder creation failed for {} with status: {}",
+            folder_name,
+            response.status()
+        );
+        Err(format!(
+            "Folder creation failed with status: {}",
+            response.status()
+        ))
+    }
+}
+
+/// Full folder listing, fetched a page at a time
+#[allow(dead_code)]
+pub async fn list_folder_contents(
+    folder_name: &str,
+    client: &reqwest::Client,
+    config: &SupabaseConfig,
+    retry: &Retry,
+) -> Result<Vec<FolderContent>, String> {
+    let mut contents = Vec::new();
+    loop {
+        let page = list_folder_page(
+            folder_name,
+            LIST_PAGE_SIZE,
+            contents.len(),
+            client,
+            config,
+            retry,
+        )
+        .await?;
+        let done = page.len() < LIST_PAGE_SIZE;
+        contents.extend(page);
+        if done {
+            return Ok(contents);
+        }
+    }
+}
+
+/// One page of a folder listing, starting at `offset`
+pub async fn list_folder_page(
+    folder_name: &str,
+    limit: usize,
+    offset: usize,
+    client: &reqwest::Client,
+    config: &SupabaseConfig,
+    retry: &Retry,
+) -> Result<Vec<FolderContent>, String> {
+    log::info!(
+        "Attempting to list contents of folder in Supabase storage: {}",
+        folder_name
+    );
+
+    let list_url = format!(
+        "{}/storage/v1/object/list/{}",
+        config.supabase_url, config.bucket_name
+    );
+    log::debug!("Supabase list URL: {}", list_url);
+
+    let body = serde_json::json!({
+        "prefix": folder_name,
+        "limit": limit,
+        "offset": offset
+    });
+
+    let response = retry
+        .send("list", || {
+            client
+                .post(&list_url)
+                .header("Authorization", format!("Bearer {}", config.service_key()))
+                .header("apikey", config.service_key())
+                .json(&body)
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.status().is_success() {
+        log::info!(
+            "Successfully retrieved folder contents from Supabase storage: {}",
+            folder_name
+        );
+        let response_text = response.text().await.map_err(|e| e.to_string())?;
+        let files: Vec<Value> = serde_json::from_str(&response_text).map_err(|e| e.to_string())?;
+        log::debug!("Found {} files in folder: {}", files.len(), folder_name);
+
+        let mut contents = Vec::new();
+        for file in files {
+            if let Some(name) = file.get("name") {
+                // Folders are listed with a null id
+                let is_file = file.get("id").is_some_and(|id| !id.is_null());
+                let size = file
+                    .get("metadata")
+                    .and_then(|m| m.get("size"))
+                    .and_then(|s| s.as_u64());
+
+                contents.push(FolderContent {
+                    name: name.as_str().unwrap_or("").to_string(),
+                    is_file,
+                    size,
+                });
+            }
+        }
+
+        log::info!(
+            "Successfully listed {} items from folder: {}",
+            contents.len(),
+            folder_name
+        );
+        Ok(contents)
+    } else {
+        let status = response.status();
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        log::error!(
+            "List folder contents failed for {} with status: {}",
+            folder_name,
+            status
+        );
+        Err(format!(
+            "List failed with status {}: {}",
+            status, error_text
+        ))
+    }
+}
@@ -0,0 +1,730 @@
+//! S3-compatible storage backend (AWS S3, MinIO).
+//!
+//! Requests are signed with AWS Signature Version 4 and use path-style URLs
+//! (`{endpoint}/{bucket}/{key}`), which MinIO requires and AWS accepts.
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::{Method, Url};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::time::Duration;
+
+use super::{byte_range, FolderContent, ObjectStorage};
+use crate::http_client::Retry;
+
+const DEFAULT_REGION: &str = "us-east-1";
+const DEFAULT_PRESIGN_EXPIRY_SECS: u64 = 60 * 60;
+/// Longest expiry S3 accepts for a presigned URL
+const MAX_PRESIGN_EXPIRY_SECS: u64 = 7 * 24 * 60 * 60;
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+/// Object written by `create_folder`, matching the Supabase backend
+const FOLDER_PLACEHOLDER: &str = "placeholder.txt";
+
+/// Characters left unescaped by SigV4 URI encoding
+const URI_UNRESERVED: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Object marking `folder_name` as created
+fn folder_placeholder(folder_name: &str) -> String {
+    format!(
+        "{}/{}",
+        folder_name.trim_end_matches('/'),
+        FOLDER_PLACEHOLDER
+    )
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct S3Config {
+    /// Base URL, e.g. `https://s3.ap-southeast-1.amazonaws.com` or `http://minio:9000`
+    pub endpoint: String,
+    pub bucket: String,
+    pub access_key: String,
+    pub secret_key: String,
+    pub region: String,
+    /// Lifetime of URLs returned by `get_asset_url`
+    pub presign_expiry_secs: u64,
+}
+
+impl fmt::Debug for S3Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("S3Config")
+            .field("endpoint", &self.endpoint)
+            .field("bucket", &self.bucket)
+            .field("access_key", &self.access_key)
+            .field("secret_key", &"<redacted>")
+            .field("region", &self.region)
+            .field("presign_expiry_secs", &self.presign_expiry_secs)
+            .finish()
+    }
+}
+
+impl S3Config {
+    /// Load using a custom variable lookup. All missing variables are named
+    /// in the error.
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let get = |key: &str| {
+            lookup(key)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        let endpoint = get("S3_ENDPOINT");
+        let bucket = get("S3_BUCKET");
+        let access_key = get("S3_ACCESS_KEY");
+        let secret_key = get("S3_SECRET_KEY");
+        let region = get("S3_REGION").unwrap_or_else(|| DEFAULT_REGION.to_string());
+        let presign_expiry_secs = match get("S3_PRESIGN_EXPIRY_SECS") {
+            Some(v) => match v.parse::<u64>() {
+                Ok(secs) if secs > 0 && secs <= MAX_PRESIGN_EXPIRY_SECS => secs,
+                _ => {
+                    return Err(format!(
+                        "S3_PRESIGN_EXPIRY_SECS must be between 1 and {}, got '{}'",
+                        MAX_PRESIGN_EXPIRY_SECS, v
+                    ))
+                }
+            },
+            None => DEFAULT_PRESIGN_EXPIRY_SECS,
+        };
+
+        match (endpoint, bucket, access_key, secret_key) {
+            (Some(endpoint), Some(bucket), Some(access_key), Some(secret_key)) => {
+                Url::parse(&endpoint)
+                    .map_err(|e| format!("S3_ENDPOINT is not a valid URL: {}", e))?;
+                Ok(Self {
+                    endpoint: endpoint.trim_end_matches('/').to_string(),
+                    bucket,
+                    access_key,
+                    secret_key,
+                    region,
+                    presign_expiry_secs,
+                })
+            }
+            (endpoint, bucket, access_key, secret_key) => {
+                let missing: Vec<&str> = [
+                    ("S3_ENDPOINT", endpoint.is_none()),
+                    ("S3_BUCKET", bucket.is_none()),
+                    ("S3_ACCESS_KEY", access_key.is_none()),
+                    ("S3_SECRET_KEY", secret_key.is_none()),
+                ]
+                .into_iter()
+                .filter(|(_, missing)| *missing)
+                .map(|(name, _)| name)
+                .collect();
+                Err(format!("{} must be set", missing.join(", ")))
+            }
+        }
+    }
+
+    fn signer(&self) -> SignatureV4<'_> {
+        SignatureV4 {
+            access_key: &self.access_key,
+            secret_key: &self.secret_key,
+            region: &self.region,
+        }
+    }
+
+    /// Path-style URL for an object key, or the bucket itself when empty
+    fn object_url(&self, key: &str) -> Result<Url, String> {
+        let encoded_key = super::encode_key(key);
+        let url = if encoded_key.is_empty() {
+            format!("{}/{}", self.endpoint, self.bucket)
+        } else {
+            format!("{}/{}/{}", self.endpoint, self.bucket, encoded_key)
+        };
+        Url::parse(&url).map_err(|e| format!("Invalid S3 object URL {}: {}", url, e))
+    }
+}
+
+/// AWS Signature Version 4 for the `s3` service
+pub struct SignatureV4<'a> {
+    pub access_key: &'a str,
+    pub secret_key: &'a str,
+    pub region: &'a str,
+}
+
+impl SignatureV4<'_> {
+    /// `Authorization` header value for a request. `headers` must include
+    /// every header to sign except `host`, which is taken from `url`.
+    pub fn authorization(
+        &self,
+        method: &Method,
+        url: &Url,
+        headers: &[(&str, &str)],
+        payload_hash: &str,
+        now: DateTime<Utc>,
+    ) -> String {
+        let mut signed: Vec<(String, String)> = headers
+            .iter()
+            .map(|(name, value)| (name.to_ascii_lowercase(), value.trim().to_string()))
+            .collect();
+        signed.push(("host".to_string(), host_header(url)));
+        signed.sort();
+
+        let signed_headers = signed
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_headers: String = signed
+            .iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value))
+            .collect();
+
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method.as_str(),
+            url.path(),
+            canonical_query(url),
+            canonical_headers,
+            signed_headers,
+            payload_hash
+        );
+        let signature = self.signature(&canonical_request, now);
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key,
+            self.scope(now),
+            signed_headers,
+            signature
+        )
+    }
+
+    /// Presigned URL valid for `expires_secs`, signing only the host header
+    pub fn presign(
+        &self,
+        method: &Method,
+        url: &Url,
+        expires_secs: u64,
+        now: DateTime<Utc>,
+    ) -> Url {
+        let mut url = url.clone();
+        let mut query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        query.extend([
+            (
+                "X-Amz-Algorithm".to_string(),
+                "AWS4-HMAC-SHA256".to_string(),
+            ),
+            (
+                "X-Amz-Credential".to_string(),
+                format!("{}/{}", self.access_key, self.scope(now)),
+            ),
+            ("X-Amz-Date".to_string(), amz_date(now)),
+            ("X-Amz-Expires".to_string(), expires_secs.to_string()),
+            ("X-Amz-SignedHeaders".to_string(), "host".to_string()),
+        ]);
+        url.set_query(Some(&encode_query(&query)));
+
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\n\nhost\n{}",
+            method.as_str(),
+            url.path(),
+            canonical_query(&url),
+            host_header(&url),
+            UNSIGNED_PAYLOAD
+        );
+        query.push((
+            "X-Amz-Signature".to_string(),
+            self.signature(&canonical_request, now),
+        ));
+        url.set_query(Some(&encode_query(&query)));
+        url
+    }
+
+    fn scope(&self, now: DateTime<Utc>) -> String {
+        format!("{}/{}/s3/aws4_request", now.format("%Y%m%d"), self.region)
+    }
+
+    fn signature(&self, canonical_request: &str, now: DateTime<Utc>) -> String {
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date(now),
+            self.scope(now),
+            sha256_hex(canonical_request.as_bytes())
+        );
+
+        let date_key = hmac_sha256(
+            format!("AWS4{}", self.secret_key).as_bytes(),
+            now.format("%Y%m%d").to_string().as_bytes(),
+        );
+        let region_key = hmac_sha256(&date_key, self.region.as_bytes());
+        let service_key = hmac_sha256(&region_key, b"s3");
+        let signing_key = hmac_sha256(&service_key, b"aws4_request");
+
+        hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()))
+    }
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn amz_date(now: DateTime<Utc>) -> String {
+    now.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn host_header(url: &Url) -> String {
+    let host = url.host_str().unwrap_or_default();
+    match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    }
+}
+
+/// Query parameters sorted and re-encoded with the SigV4 rules
+fn canonical_query(url: &Url) -> String {
+    let pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+    encode_query(&pairs)
+}
+
+/// Sorted, SigV4-encoded query string. Used for the URLs we send too, so
+/// the server's canonical form matches ours.
+fn encode_query(pairs: &[(String, String)]) -> String {
+    let mut encoded: Vec<(String, String)> = pairs
+        .iter()
+        .map(|(k, v)| {
+            (
+                utf8_percent_encode(k, URI_UNRESERVED).to_string(),
+                utf8_percent_encode(v, URI_UNRESERVED).to_string(),
+            )
+        })
+        .collect();
+    encoded.sort();
+    encoded
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListBucketResult {
+    #[serde(default)]
+    contents: Vec<ListedObject>,
+    #[serde(default)]
+    common_prefixes: Vec<ListedPrefix>,
+    #[serde(default)]
+    is_truncated: bool,
+    next_continuation_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListedObject {
+    key: String,
+    size: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListedPrefix {
+    prefix: String,
+}
+
+pub struct S3Storage {
+    pub config: S3Config,
+    pub client: reqwest::Client,
+    /// Applied to uploads, deletes and listings
+    pub retry: Retry,
+}
+
+impl S3Storage {
+    pub fn new(config: S3Config, client: reqwest::Client) -> Self {
+        Self {
+            config,
+            client,
+            retry: Retry::default(),
+        }
+    }
+
+    pub fn with_retry(mut self, retry: Retry) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Build a signed request. The signature is computed per call so a
+    /// retried request gets a fresh timestamp.
+    fn signed_request(&self, method: Method, url: &Url, body: &[u8]) -> reqwest::RequestBuilder {
+        self.signed_request_with_headers(method, url, body, &[])
+    }
+
+    /// Like `signed_request`, with extra headers that are sent and signed.
+    /// S3 rejects requests carrying unsigned `x-amz-*` headers.
+    fn signed_request_with_headers(
+        &self,
+        method: Method,
+        url: &Url,
+        body: &[u8],
+        extra_headers: &[(&str, &str)],
+    ) -> reqwest::RequestBuilder {
+        let now = Utc::now();
+        let payload_hash = sha256_hex(body);
+        let date = amz_date(now);
+        let mut headers = vec![
+            ("x-amz-content-sha256", payload_hash.as_str()),
+            ("x-amz-date", date.as_str()),
+        ];
+        headers.extend_from_slice(extra_headers);
+        let authorization =
+            self.config
+                .signer()
+                .authorization(&method, url, &headers, &payload_hash, now);
+
+        let mut request = self
+            .client
+            .request(method, url.clone())
+            .header("Authorization", authorization);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        request.body(body.to_vec())
+    }
+
+    /// PUT an object. Without `overwrite` the write is conditional and
+    /// `Ok(false)` means the key already exists.
+    async fn put_object(
+        &self,
+        operation: &'static str,
+        key: &str,
+        data: &[u8],
+        overwrite: bool,
+    ) -> Result<bool, String> {
+        let url = self.config.object_url(key)?;
+        let content_type = mime_guess::from_path(key)
+            .first_or_octet_stream()
+            .to_string();
+
+        let response = self
+            .retry
+            .send(operation, || {
+                let request = self
+                    .signed_request(Method::PUT, &url, data)
+                    .header("Content-Type", &content_type);
+                if overwrite {
+                    request
+                } else {
+                    request.header("If-None-Match", "*")
+                }
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if response.status().is_success() {
+            Ok(true)
+        } else if !overwrite && response.status() == reqwest::StatusCode::PRECONDITION_FAILED {
+            Ok(false)
+        } else {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            log::error!(
+                "S3 PUT {} failed with status {}: {}",
+                key,
+                status,
+                error_text
+            );
+            Err(format!("Upload failed with status: {}", status))
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ObjectStorage for S3Storage {
+    async fn upload_file(&self, filename: &str, file_data: &[u8]) -> Result<(), String> {
+        log::info!("Uploading {} to S3 bucket {}", filename, self.config.bucket);
+        self.put_object("upload", filename, file_data, true)
+            .await
+            .map(|_| ())
+    }
+
+    async fn upload_new_file(&self, filename: &str, file_data: &[u8]) -> Result<bool, String> {
+        self.put_object("upload", filename, file_data, false).await
+    }
+
+    async fn exists(&self, filename: &str) -> Result<bool, String> {
+        let url = self.config.object_url(filename)?;
+        let response = self
+            .retry
+            .send("exists", || self.signed_request(Method::HEAD, &url, &[]))
+            .await
+            .map_err(|e| e.to_string())?;
+
+        match response.status() {
+            status if status.is_success() => Ok(true),
+            reqwest::StatusCode::NOT_FOUND => Ok(false),
+            status => {
+                log::error!("S3 HEAD {} failed with status {}", filename, status);
+                Err(format!("Object lookup failed with status: {}", status))
+            }
+        }
+    }
+
+    async fn download_file(&self, filename: &str) -> Result<Vec<u8>, String> {
+        let url = self.config.object_url(filename)?;
+        let response = self
+            .signed_request(Method::GET, &url, &[])
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if response.status().is_success() {
+            let bytes = response.bytes().await.map_err(|e| e.to_string())?;
+            Ok(bytes.to_vec())
+        } else {
+            log::error!(
+                "S3 GET {} failed with status {}",
+                filename,
+                response.status()
+            );
+            Err(format!(
+                "Download failed with status: {}",
+                response.status()
+            ))
+        }
+    }
+
+    async fn download_head(&self, filename: &str, len: usize) -> Result<Vec<u8>, String> {
+        let url = self.config.object_url(filename)?;
+        let response = self
+            .signed_request(Method::GET, &url, &[])
+            .header("Range", byte_range(len))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if response.status().is_success() {
+            let bytes = response.bytes().await.map_err(|e| e.to_string())?;
+            Ok(bytes[..len.min(bytes.len())].to_vec())
+        } else {
+            Err(format!(
+                "Download failed with status: {}",
+                response.status()
+            ))
+        }
+    }
+
+    async fn delete_file(&self, filename: &str) -> Result<(), String> {
+        let url = self.config.object_url(filename)?;
+        let response = self
+            .retry
+            .send("delete", || self.signed_request(Method::DELETE, &url, &[]))
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if response.status().is_success() {
+            log::info!("Deleted {} from S3 bucket {}", filename, self.config.bucket);
+            Ok(())
+        } else {
+            log::error!(
+                "S3 DELETE {} failed with status {}",
+                filename,
+                response.status()
+            );
+            Err(format!("Delete failed with status: {}", response.status()))
+        }
+    }
+
+    async fn copy_file(&self, from: &str, to: &str) -> Result<(), String> {
+        let url = self.config.object_url(to)?;
+        let source = self.config.object_url(from)?;
+        let copy_source = source.path().to_string();
+
+        let response = self
+            .retry
+            .send("copy", || {
+                self.signed_request_with_headers(
+                    Method::PUT,
+                    &url,
+                    &[],
+                    &[("x-amz-copy-source", &copy_source)],
+                )
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        // CopyObject can fail after sending 200, with the error in the body
+        if status.is_success() && !body.contains("<Error>") {
+            log::info!(
+                "Copied {} to {} in S3 bucket {}",
+                from,
+                to,
+                self.config.bucket
+            );
+            Ok(())
+        } else {
+            log::error!(
+                "S3 copy {} -> {} failed with status {}: {}",
+                from,
+                to,
+                status,
+                body
+            );
+            Err(format!("Copy failed with status: {}", status))
+        }
+    }
+
+    async fn move_file(&self, from: &str, to: &str) -> Result<(), String> {
+        // S3 has no rename; copy then delete the source
+        self.copy_file(from, to).await?;
+        self.delete_file(from).await
+    }
+
+    async fn create_folder(&self, folder_name: &str) -> Result<(), String> {
+        let key = folder_placeholder(folder_name);
+        if self.exists(&key).await? {
+            log::debug!("Folder {} already exists", folder_name);
+            return Ok(());
+        }
+        self.put_object("create_folder", &key, b"Folder placeholder", true)
+            .await
+            .map(|_| ())
+    }
+
+    async fn remove_folder(&self, folder_name: &str) -> Result<bool, String> {
+        let key = folder_placeholder(folder_name);
+        if !self.exists(&key).await? {
+            return Ok(false);
+        }
+        self.delete_file(&key).await?;
+        Ok(true)
+    }
+
+    async fn list_folder_contents(&self, folder_name: &str) -> Result<Vec<FolderContent>, String> {
+        let folder = folder_name.trim_matches('/');
+        let prefix = if folder.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", folder)
+        };
+
+        let mut contents = Vec::new();
+        let mut continuation_token: Option<String> = None;
+        loop {
+            let mut url = self.config.object_url("")?;
+            let mut query = vec![
+                ("list-type".to_string(), "2".to_string()),
+                ("delimiter".to_string(), "/".to_string()),
+                ("prefix".to_string(), prefix.clone()),
+            ];
+            if let Some(token) = &continuation_token {
+                query.push(("continuation-token".to_string(), token.clone()));
+            }
+            url.set_query(Some(&encode_query(&query)));
+
+            let response = self
+                .retry
+                .send("list", || self.signed_request(Method::GET, &url, &[]))
+                .await
+                .map_err(|e| e.to_string())?;
+            if !response.status().is_success() {
+                log::error!(
+                    "S3 list of {} failed with status {}",
+                    folder_name,
+                    response.status()
+                );
+                return Err(format!("List failed with status: {}", response.status()));
+            }
+
+            let body = response.text().await.map_err(|e| e.to_string())?;
+            let page: ListBucketResult = quick_xml::de::from_str(&body)
+                .map_err(|e| format!("Invalid ListObjectsV2 response: {}", e))?;
+
+            contents.extend(page.common_prefixes.iter().filter_map(|p| {
+                let name = p.prefix.strip_prefix(&prefix)?.trim_end_matches('/');
+                Some(FolderContent {
+                    name: name.to_string(),
+                    is_file: false,
+                    size: None,
+                })
+            }));
+            // The folder's own marker key, if any, has an empty name
+            contents.extend(page.contents.iter().filter_map(|object| {
+                let name = object.key.strip_prefix(&prefix)?;
+                (!name.is_empty()).then(|| FolderContent {
+                    name: name.to_string(),
+                    is_file: true,
+                    size: object.size,
+                })
+            }));
+
+            match page.next_continuation_token {
+                Some(token) if page.is_truncated => continuation_token = Some(token),
+                _ => break,
+            }
+        }
+
+        Ok(contents)
+    }
+
+    fn get_asset_url(&self, filename: &str) -> String {
+        match self.config.object_url(filename) {
+            Ok(url) => self
+                .config
+                .signer()
+                .presign(
+                    &Method::GET,
+                    &url,
+                    self.config.presign_expiry_secs,
+                    Utc::now(),
+                )
+                .to_string(),
+            Err(e) => {
+                log::error!("{}", e);
+                String::new()
+            }
+        }
+    }
+
+    async fn health_check(&self) -> Result<(), String> {
+        let url = self.config.object_url("")?;
+        let response = self
+            .signed_request(Method::HEAD, &url, &[])
+            .send()
+            .await
+            .map_err(|e| format!("S3 endpoint {} is unreachable: {}", self.config.endpoint, e))?;
+
+        match response.status().as_u16() {
+            200..=299 => Ok(()),
+            401 | 403 => Err(format!(
+                "S3 rejected the credentials (status {}); check S3_ACCESS_KEY, S3_SECRET_KEY \
+                 and S3_REGION",
+                response.status()
+            )),
+            404 => Err(format!(
+                "Bucket '{}' not found; check S3_BUCKET",
+                self.config.bucket
+            )),
+            _ => Err(format!(
+                "Storage health check failed with status {}",
+                response.status()
+            )),
+        }
+    }
+
+    async fn get_signed_url(&self, filename: &str, ttl: Duration) -> Result<String, String> {
+        let url = self.config.object_url(filename)?;
+        let expires_secs = ttl.as_secs().clamp(1, MAX_PRESIGN_EXPIRY_SECS);
+        Ok(self
+            .config
+            .signer()
+            .presign(&Method::GET, &url, expires_secs, Utc::now())
+            .to_string())
+    }
+}
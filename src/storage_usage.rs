@@ -0,0 +1,392 @@
+//! Storage usage report and quota gauge.
+//!
+//! `assets` has no size column, so sizes come from a recursive listing of
+//! the bucket. Each object is matched to its `assets` row to build the
+//! per-folder breakdown; objects without a row and rows without an object
+//! are reported as drift. The scan is cached for an hour and refreshed by a
+//! periodic task that also updates the Prometheus gauges.
+
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use chrono::{DateTime, Utc};
+use prometheus::{IntGauge, Opts, Registry};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::auth::validate_request_token;
+use crate::error::ApiError;
+use crate::storage::ObjectStorage;
+use crate::AppState;
+
+const METRICS_NAMESPACE: &str = "cakung_barat_server";
+const DEFAULT_INTERVAL_SECS: u64 = 60 * 60;
+/// How long a bucket scan is served before the next request rescans
+pub const SCAN_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+/// Breakdown entry for assets not linked to any folder
+pub const UNFILED_FOLDER: &str = "unfiled";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageUsageConfig {
+    /// Reported alongside the usage, None means no quota
+    pub quota_bytes: Option<u64>,
+    /// Time between gauge refreshes, None disables the task
+    pub interval: Option<Duration>,
+}
+
+impl Default for StorageUsageConfig {
+    fn default() -> Self {
+        Self {
+            quota_bytes: None,
+            interval: Some(Duration::from_secs(DEFAULT_INTERVAL_SECS)),
+        }
+    }
+}
+
+impl StorageUsageConfig {
+    /// Load from process environment
+    pub fn from_env() -> Result<Self, String> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// Load using a custom variable lookup. `STORAGE_USAGE_INTERVAL_SECS=0`
+    /// disables the refresh task.
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let get = |key: &str| {
+            lookup(key)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let defaults = Self::default();
+
+        let quota_bytes = get("STORAGE_QUOTA_BYTES")
+            .map(|v| crate::config::parse_positive("STORAGE_QUOTA_BYTES", &v))
+            .transpose()?
+            .map(|n| n as u64);
+        let interval = match get("STORAGE_USAGE_INTERVAL_SECS") {
+            Some(v) => match v.parse::<u64>() {
+                Ok(0) => None,
+                Ok(secs) => Some(Duration::from_secs(secs)),
+                Err(_) => {
+                    return Err(format!(
+                        "STORAGE_USAGE_INTERVAL_SECS must be a non-negative integer, got '{}'",
+                        v
+                    ))
+                }
+            },
+            None => defaults.interval,
+        };
+
+        Ok(Self {
+            quota_bytes,
+            interval,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FolderUsage {
+    pub name: String,
+    pub bytes: u64,
+    pub assets: usize,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct StorageUsageReport {
+    /// Size of every object in the bucket
+    pub total_bytes: u64,
+    pub object_count: usize,
+    pub quota_bytes: Option<u64>,
+    /// `total_bytes / quota_bytes`, above 1.0 when over quota
+    pub quota_used: Option<f64>,
+    /// Bytes per asset folder. An asset in several folders counts towards
+    /// each of them.
+    pub folders: Vec<FolderUsage>,
+    /// Objects no `assets` row refers to
+    pub untracked_objects: usize,
+    pub untracked_bytes: u64,
+    /// Filenames of `assets` rows with no object in the bucket
+    pub missing_objects: Vec<String>,
+    pub scanned_at: DateTime<Utc>,
+}
+
+impl StorageUsageReport {
+    /// Build a report from bucket objects (`path`, size) and asset rows
+    /// (filename, folder name)
+    pub fn build(
+        objects: &[(String, u64)],
+        assets: &[(String, Option<String>)],
+        quota_bytes: Option<u64>,
+    ) -> Self {
+        let sizes: HashMap<&str, u64> = objects
+            .iter()
+            .map(|(name, size)| (name.as_str(), *size))
+            .collect();
+        let total_bytes = objects.iter().map(|(_, size)| size).sum();
+
+        let mut folders: BTreeMap<&str, FolderUsage> = BTreeMap::new();
+        let mut missing_objects = Vec::new();
+        for (filename, folder) in assets {
+            let folder = folder.as_deref().unwrap_or(UNFILED_FOLDER);
+            let usage = folders.entry(folder).or_insert_with(|| FolderUsage {
+                name: folder.to_string(),
+                bytes: 0,
+                assets: 0,
+            });
+            usage.assets += 1;
+            match sizes.get(filename.as_str()) {
+                Some(size) => usage.bytes += size,
+                None => missing_objects.push(filename.clone()),
+            }
+        }
+        missing_objects.sort();
+        missing_objects.dedup();
+
+        let tracked: HashSet<&str> = assets
+            .iter()
+            .map(|(filename, _)| filename.as_str())
+            .collect();
+        let untracked: Vec<u64> = objects
+            .iter()
+            .filter(|(name, _)| !tracked.contains(name.as_str()))
+            .map(|(_, size)| *size)
+            .collect();
+
+        Self {
+            total_bytes,
+            object_count: objects.len(),
+            quota_bytes,
+            quota_used: quota_bytes.map(|quota| total_bytes as f64 / quota as f64),
+            folders: folders.into_values().collect(),
+            untracked_objects: untracked.len(),
+            untracked_bytes: untracked.iter().sum(),
+            missing_objects,
+            scanned_at: Utc::now(),
+        }
+    }
+}
+
+/// List every object under `prefix`, recursing into subfolders. Returns
+/// full object paths with their sizes.
+pub async fn scan_bucket(
+    storage: &(dyn ObjectStorage + Send + Sync),
+    prefix: &str,
+) -> Result<Vec<(String, u64)>, String> {
+    let mut objects = Vec::new();
+    let mut pending = vec![prefix.trim_matches('/').to_string()];
+
+    while let Some(folder) = pending.pop() {
+        for entry in storage.list_folder_contents(&folder).await? {
+            let path = if folder.is_empty() {
+                entry.name
+            } else {
+                format!("{}/{}", folder, entry.name)
+            };
+            if entry.is_file {
+                objects.push((path, entry.size.unwrap_or(0)));
+            } else {
+                pending.push(path);
+            }
+        }
+    }
+    Ok(objects)
+}
+
+/// Usage state held by `AppState`
+pub struct StorageUsage {
+    quota_bytes: Option<u64>,
+    report: RwLock<Option<(Instant, StorageUsageReport)>>,
+    used_bytes: IntGauge,
+    quota_gauge: IntGauge,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Default for StorageUsage {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl StorageUsage {
+    pub fn new(quota_bytes: Option<u64>) -> Self {
+        let used_bytes = IntGauge::with_opts(
+            Opts::new(
+                "storage_used_bytes",
+                "Total size of the objects in the storage bucket",
+            )
+            .namespace(METRICS_NAMESPACE),
+        )
+        .expect("valid storage usage metric");
+        let quota_gauge = IntGauge::with_opts(
+            Opts::new(
+                "storage_quota_bytes",
+                "Configured storage quota, 0 when unset",
+            )
+            .namespace(METRICS_NAMESPACE),
+        )
+        .expect("valid storage quota metric");
+        quota_gauge.set(quota_bytes.map_or(0, |quota| quota as i64));
+
+        Self {
+            quota_bytes,
+            report: RwLock::new(None),
+            used_bytes,
+            quota_gauge,
+            handle: Mutex::new(None),
+        }
+    }
+
+    pub fn register(&self, registry: &Registry) -> prometheus::Result<()> {
+        registry.register(Box::new(self.used_bytes.clone()))?;
+        registry.register(Box::new(self.quota_gauge.clone()))?;
+        Ok(())
+    }
+
+    pub fn quota_bytes(&self) -> Option<u64> {
+        self.quota_bytes
+    }
+
+    /// Current value of the used-bytes gauge
+    pub fn used_bytes(&self) -> i64 {
+        self.used_bytes.get()
+    }
+
+    /// Bucket size found by the last scan, however old
+    pub fn last_total_bytes(&self) -> Option<u64> {
+        self.report
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .map(|(_, report)| report.total_bytes)
+    }
+
+    /// The last report, if it is younger than `SCAN_MAX_AGE`
+    fn cached(&self) -> Option<StorageUsageReport> {
+        self.report
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .filter(|(at, _)| at.elapsed() < SCAN_MAX_AGE)
+            .map(|(_, report)| report.clone())
+    }
+
+    fn store(&self, report: &StorageUsageReport) {
+        self.used_bytes.set(report.total_bytes as i64);
+        *self.report.write().unwrap_or_else(|e| e.into_inner()) =
+            Some((Instant::now(), report.clone()));
+    }
+
+    /// Stop the periodic task, if one was started
+    pub fn stop(&self) {
+        let handle = self.handle.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(handle) = handle {
+            handle.abort();
+        }
+    }
+}
+
+impl AppState {
+    /// Usage report, served from the last scan unless it is older than
+    /// `SCAN_MAX_AGE` or `refresh` is set
+    pub async fn storage_usage_report(
+        &self,
+        refresh: bool,
+    ) -> Result<StorageUsageReport, ApiError> {
+        if !refresh {
+            if let Some(report) = self.storage_usage.cached() {
+                return Ok(report);
+            }
+        }
+
+        let objects = scan_bucket(self.storage.as_ref(), "")
+            .await
+            .map_err(ApiError::storage("Failed to scan storage bucket"))?;
+        let assets: Vec<(String, Option<String>)> = sqlx::query_as(
+            "SELECT a.filename, f.name FROM assets a \
+             LEFT JOIN asset_folders af ON af.asset_id = a.id \
+             LEFT JOIN folders f ON f.id = af.folder_id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(ApiError::database("Failed to load assets"))?;
+
+        let report = StorageUsageReport::build(&objects, &assets, self.storage_usage.quota_bytes());
+        self.storage_usage.store(&report);
+        Ok(report)
+    }
+
+    /// Spawn the periodic usage refresh. Does nothing when the interval is
+    /// disabled or a task is already running.
+    pub fn start_storage_usage(&self, config: &StorageUsageConfig) {
+        let Some(interval) = config.interval else {
+            log::info!("Storage usage refresh disabled");
+            return;
+        };
+
+        let mut handle = self
+            .storage_usage
+            .handle
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if handle.is_some() {
+            return;
+        }
+
+        let state = self.clone();
+        *handle = Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = state.storage_usage_report(true).await {
+                    log::warn!("Storage usage refresh failed: {}", e);
+                }
+            }
+        }));
+        log::info!("Storage usage refresh started, interval {:?}", interval);
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct StorageUsageQuery {
+    /// Rescan the bucket instead of using the cached scan
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+/// Bucket usage with a per-folder breakdown and drift against `assets` (protected)
+#[utoipa::path(
+    operation_id = "getStorageUsage",
+    get,
+    path = "/api/admin/storage/usage",
+    tag = "Admin",
+    params(StorageUsageQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Storage usage report", body = StorageUsageReport),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 500, description = "Bucket scan or database query failed")
+    )
+)]
+pub async fn get_storage_usage(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<StorageUsageQuery>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    match state.storage_usage_report(query.refresh).await {
+        Ok(report) => HttpResponse::Ok().json(report),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/admin/storage/usage").route(web::get().to(get_storage_usage)));
+}
@@ -0,0 +1,365 @@
+//! Public submission form and admin inbox
+
+use actix_multipart::Multipart;
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use super::{
+    parse_category, AssignSubmissionRequest, ResolveSubmissionRequest, Submission,
+    SubmissionForm, SubmissionStatus, SubmissionUpdate, SubmittedForm, MAX_ASSIGNEE_LENGTH,
+    MAX_NAMA_LENGTH, RATE_LIMIT_KEY, SUBMISSION_CATEGORIES,
+};
+use crate::asset::folder_name::submission_folder_name;
+use crate::asset::handlers::save_asset;
+use crate::auth::validate_request_token;
+use crate::error::ApiError;
+use crate::posting::multipart_parser::{MultipartParser, ParsedSubmissionMultipart};
+use crate::AppState;
+
+const DEFAULT_SUBMISSION_LIST_LIMIT: i64 = 50;
+const MAX_SUBMISSION_LIST_LIMIT: i64 = 200;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct SubmissionListQuery {
+    /// Only submissions in this status, e.g. `open` for new ones
+    pub status: Option<SubmissionStatus>,
+    /// Only submissions in this category
+    pub kategori: Option<String>,
+    /// Submissions returned, newest first (default 50, max 200)
+    pub limit: Option<i64>,
+}
+
+/// The trimmed text fields of a submission form
+#[derive(Debug, PartialEq, Eq)]
+pub struct ValidSubmission<'a> {
+    pub nama: &'a str,
+    pub nik: Option<&'a str>,
+    pub kategori: &'static str,
+    pub isi: &'a str,
+}
+
+/// Trimmed `form` fields, or the first one that is missing or malformed
+pub fn validate_submission(
+    form: &ParsedSubmissionMultipart,
+    max_length: usize,
+) -> Result<ValidSubmission<'_>, ApiError> {
+    let nama = form.nama.trim();
+    if nama.is_empty() {
+        return Err(ApiError::BadRequest("nama must not be empty".to_string()));
+    }
+    if nama.chars().count() > MAX_NAMA_LENGTH {
+        return Err(ApiError::BadRequest(format!(
+            "nama must be at most {} characters",
+            MAX_NAMA_LENGTH
+        )));
+    }
+    let nik = form.nik.as_deref().map(str::trim).filter(|nik| !nik.is_empty());
+    if nik.is_some_and(|nik| nik.len() != 16 || !nik.chars().all(|c| c.is_ascii_digit())) {
+        return Err(ApiError::BadRequest("nik must be 16 digits".to_string()));
+    }
+    let kategori = parse_category(&form.kategori).ok_or_else(|| {
+        ApiError::BadRequest(format!(
+            "kategori must be one of: {}",
+            SUBMISSION_CATEGORIES.join(", ")
+        ))
+    })?;
+    let isi = form.isi.trim();
+    if isi.is_empty() {
+        return Err(ApiError::BadRequest("isi must not be empty".to_string()));
+    }
+    if isi.chars().count() > max_length {
+        return Err(ApiError::BadRequest(format!(
+            "isi must be at most {} characters",
+            max_length
+        )));
+    }
+    Ok(ValidSubmission {
+        nama,
+        nik,
+        kategori,
+        isi,
+    })
+}
+
+/// Send a complaint, suggestion or question to the kelurahan
+#[utoipa::path(
+    operation_id = "createSubmission",
+    context_path = "/api",
+    tag = "Submission Service",
+    post,
+    path = "/submissions",
+    request_body(content = SubmissionForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 202, description = "Submission received", body = SubmittedForm),
+        (status = 400, description = "Missing or malformed field", body = crate::ErrorResponse),
+        (status = 413, description = "Attachment exceeds the upload size limit", body = crate::ErrorResponse),
+        (status = 429, description = "Too many submissions from this address, see `Retry-After`", body = crate::ErrorResponse),
+        (status = 500, description = "Internal Server Error", body = crate::ErrorResponse)
+    )
+)]
+pub async fn create_submission(
+    req: HttpRequest,
+    payload: Multipart,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let form = MultipartParser::parse_submission_multipart(payload).await?;
+    let submission = validate_submission(&form, data.submissions.max_length)?;
+
+    // The connecting address. Forwarded headers are written by the client,
+    // which could claim a fresh address, and a fresh quota, every time.
+    let client = req
+        .peer_addr()
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    data.submission_limiter
+        .check(&client, RATE_LIMIT_KEY, false)
+        .map_err(|throttled| ApiError::TooManyRequests {
+            message: format!(
+                "At most {} submissions per hour, try again later",
+                throttled.limit
+            ),
+            retry_after_secs: throttled.retry_after_secs(),
+        })?;
+
+    let id = Uuid::new_v4();
+    let attachment_asset_id = match &form.attachment {
+        Some((file_data, filename)) => {
+            let asset = save_asset(&data, file_data, filename, None).await?;
+            data.insert_folder_contents(&submission_folder_name(id), &vec![asset.id])
+                .await
+                .map_err(ApiError::database("Failed to store attachment"))?;
+            Some(asset.id)
+        }
+        None => None,
+    };
+
+    let stored = data
+        .insert_submission(
+            &id,
+            submission.nama,
+            submission.nik,
+            submission.kategori,
+            submission.isi,
+            attachment_asset_id,
+        )
+        .await
+        .map_err(ApiError::database("Failed to store submission"))?;
+    log::info!("Submission {:?} received in {}", stored.id, stored.kategori);
+    Ok(HttpResponse::Accepted().json(SubmittedForm {
+        id: stored.id,
+        status: stored.status,
+    }))
+}
+
+/// Submissions, newest first, with the sender's details (protected)
+#[utoipa::path(
+    operation_id = "listSubmissions",
+    get,
+    path = "/api/admin/submissions",
+    tag = "Admin",
+    params(SubmissionListQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Submissions, newest first", body = Vec<Submission>),
+        (status = 400, description = "Unknown status or category, or limit out of range"),
+        (status = 401, response = crate::UnauthorizedResponse)
+    )
+)]
+pub async fn list_submissions(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<SubmissionListQuery>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_SUBMISSION_LIST_LIMIT);
+    if !(1..=MAX_SUBMISSION_LIST_LIMIT).contains(&limit) {
+        return ApiError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_SUBMISSION_LIST_LIMIT
+        ))
+        .error_response();
+    }
+    let kategori = match query.kategori.as_deref().map(parse_category) {
+        Some(None) => {
+            return ApiError::BadRequest(format!(
+                "kategori must be one of: {}",
+                SUBMISSION_CATEGORIES.join(", ")
+            ))
+            .error_response()
+        }
+        Some(kategori) => kategori,
+        None => None,
+    };
+
+    match state.list_submissions(query.status, kategori, limit).await {
+        Ok(submissions) => HttpResponse::Ok().json(submissions),
+        Err(e) => ApiError::database("Failed to list submissions")(e).error_response(),
+    }
+}
+
+/// One submission (protected)
+#[utoipa::path(
+    operation_id = "getSubmission",
+    get,
+    path = "/api/admin/submissions/{id}",
+    tag = "Admin",
+    params(("id" = Uuid, Path, description = "Submission ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The submission", body = Submission),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 404, description = "Submission not found")
+    )
+)]
+pub async fn get_submission(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    match state.get_submission(&path).await {
+        Ok(Some(submission)) => HttpResponse::Ok().json(submission),
+        Ok(None) => ApiError::NotFound("Submission not found".to_string()).error_response(),
+        Err(e) => ApiError::database("Failed to retrieve submission")(e).error_response(),
+    }
+}
+
+/// Apply `update`, telling a missing submission apart from one that cannot
+/// move to the new status
+async fn update(
+    state: &AppState,
+    id: &Uuid,
+    update: SubmissionUpdate<'_>,
+) -> Result<Submission, ApiError> {
+    let status = update.status;
+    if let Some(submission) = state
+        .update_submission(id, update)
+        .await
+        .map_err(ApiError::database("Failed to update submission"))?
+    {
+        log::info!("Submission {:?} {}", id, status.as_str());
+        return Ok(submission);
+    }
+
+    match state
+        .get_submission(id)
+        .await
+        .map_err(ApiError::database("Failed to update submission"))?
+    {
+        Some(submission) => Err(ApiError::Conflict(format!(
+            "Submission is already {}",
+            submission.status.as_str()
+        ))),
+        None => Err(ApiError::NotFound("Submission not found".to_string())),
+    }
+}
+
+/// Hand an open or in-progress submission to someone, marking it in
+/// progress (protected)
+#[utoipa::path(
+    operation_id = "assignSubmission",
+    post,
+    path = "/api/admin/submissions/{id}/assign",
+    tag = "Admin",
+    params(("id" = Uuid, Path, description = "Submission ID")),
+    request_body = AssignSubmissionRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Submission assigned", body = Submission),
+        (status = 400, description = "Empty or too long assignee"),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 404, description = "Submission not found"),
+        (status = 409, description = "Submission is already resolved")
+    )
+)]
+pub async fn assign_submission(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    request: web::Json<AssignSubmissionRequest>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+    let assigned_to = request.assigned_to.trim();
+    if assigned_to.is_empty() || assigned_to.chars().count() > MAX_ASSIGNEE_LENGTH {
+        return ApiError::BadRequest(format!(
+            "assigned_to must be between 1 and {} characters",
+            MAX_ASSIGNEE_LENGTH
+        ))
+        .error_response();
+    }
+
+    let change = SubmissionUpdate {
+        status: SubmissionStatus::InProgress,
+        assigned_to: Some(assigned_to),
+        resolution_notes: None,
+    };
+    match update(&state, &path, change).await {
+        Ok(submission) => HttpResponse::Ok().json(submission),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// Close a submission with notes on what was done (protected)
+#[utoipa::path(
+    operation_id = "resolveSubmission",
+    post,
+    path = "/api/admin/submissions/{id}/resolve",
+    tag = "Admin",
+    params(("id" = Uuid, Path, description = "Submission ID")),
+    request_body = ResolveSubmissionRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Submission resolved", body = Submission),
+        (status = 400, description = "Empty resolution notes"),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 404, description = "Submission not found"),
+        (status = 409, description = "Submission is already resolved")
+    )
+)]
+pub async fn resolve_submission(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    request: web::Json<ResolveSubmissionRequest>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+    let resolution_notes = request.resolution_notes.trim();
+    if resolution_notes.is_empty() {
+        return ApiError::BadRequest("resolution_notes must not be empty".to_string())
+            .error_response();
+    }
+
+    let change = SubmissionUpdate {
+        status: SubmissionStatus::Resolved,
+        assigned_to: None,
+        resolution_notes: Some(resolution_notes),
+    };
+    match update(&state, &path, change).await {
+        Ok(submission) => HttpResponse::Ok().json(submission),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/submissions").route(web::post().to(create_submission)))
+        .service(
+            web::scope("/admin/submissions")
+                .service(web::resource("").route(web::get().to(list_submissions)))
+                .service(web::resource("/{id}").route(web::get().to(get_submission)))
+                .service(web::resource("/{id}/assign").route(web::post().to(assign_submission)))
+                .service(
+                    web::resource("/{id}/resolve").route(web::post().to(resolve_submission)),
+                ),
+        );
+}
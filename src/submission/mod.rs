@@ -0,0 +1,389 @@
+//! Complaints and messages from residents ("Pengaduan").
+//!
+//! The public form posts to `POST /api/submissions`. Submissions start
+//! `open`, move to `in_progress` when an admin assigns them and end
+//! `resolved` with notes on what was done. An attachment goes through the
+//! asset pipeline into the folder `submissions/{id}`, which, being nested,
+//! can never be listed in the public gallery. The number of unresolved
+//! submissions is exported on `/metrics`.
+
+pub mod handlers;
+
+use chrono::{DateTime, Utc};
+use prometheus::{IntGauge, Opts, Registry};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::mcp::tools::rate_limit::{RateLimitConfig, RateLimiter};
+use crate::db::DbError;
+use crate::AppState;
+
+const METRICS_NAMESPACE: &str = "cakung_barat_server";
+
+pub const DEFAULT_SUBMISSIONS_PER_HOUR: usize = 3;
+pub const DEFAULT_MAX_SUBMISSION_LENGTH: usize = 5000;
+/// Longest accepted `nama`, in characters
+pub const MAX_NAMA_LENGTH: usize = 100;
+/// Longest accepted `assigned_to`, in characters
+pub const MAX_ASSIGNEE_LENGTH: usize = 100;
+/// Accepted `kategori` values
+pub const SUBMISSION_CATEGORIES: &[&str] = &[
+    "infrastruktur",
+    "kebersihan",
+    "keamanan",
+    "pelayanan",
+    "sosial",
+    "lainnya",
+];
+
+/// Name under which submissions are counted in the rate limiter
+const RATE_LIMIT_KEY: &str = "submit_form";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmissionConfig {
+    /// Submissions accepted per client IP and hour
+    pub per_ip_per_hour: usize,
+    /// Longest accepted `isi`, in characters
+    pub max_length: usize,
+}
+
+impl Default for SubmissionConfig {
+    fn default() -> Self {
+        Self {
+            per_ip_per_hour: DEFAULT_SUBMISSIONS_PER_HOUR,
+            max_length: DEFAULT_MAX_SUBMISSION_LENGTH,
+        }
+    }
+}
+
+impl SubmissionConfig {
+    /// Load using a custom variable lookup
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let get = |key: &str| {
+            lookup(key)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let defaults = Self::default();
+
+        let per_ip_per_hour = get("SUBMISSIONS_PER_HOUR")
+            .map(|v| crate::config::parse_positive("SUBMISSIONS_PER_HOUR", &v))
+            .transpose()?
+            .unwrap_or(defaults.per_ip_per_hour);
+        let max_length = get("SUBMISSION_MAX_LENGTH")
+            .map(|v| crate::config::parse_positive("SUBMISSION_MAX_LENGTH", &v))
+            .transpose()?
+            .unwrap_or(defaults.max_length);
+
+        Ok(Self {
+            per_ip_per_hour,
+            max_length,
+        })
+    }
+
+    /// Limiter counting submissions per client IP
+    pub fn rate_limiter(&self) -> RateLimiter {
+        RateLimiter::new(RateLimitConfig {
+            tool_calls_per_hour: [(RATE_LIMIT_KEY.to_string(), self.per_ip_per_hour)].into(),
+            ..RateLimitConfig::default()
+        })
+    }
+}
+
+/// Where a submission is in handling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum SubmissionStatus {
+    Open,
+    InProgress,
+    Resolved,
+}
+
+impl SubmissionStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::InProgress => "in_progress",
+            Self::Resolved => "resolved",
+        }
+    }
+
+    /// Whether handling may move a submission from `self` to `next`.
+    /// Assigning again hands an in-progress submission to someone else,
+    /// and a resolved submission is final.
+    pub fn can_become(self, next: SubmissionStatus) -> bool {
+        matches!(
+            (self, next),
+            (Self::Open, Self::InProgress)
+                | (Self::InProgress, Self::InProgress)
+                | (Self::Open, Self::Resolved)
+                | (Self::InProgress, Self::Resolved)
+        )
+    }
+
+    /// Statuses that may move to `self`
+    fn sources(self) -> Vec<&'static str> {
+        [Self::Open, Self::InProgress, Self::Resolved]
+            .into_iter()
+            .filter(|status| status.can_become(self))
+            .map(Self::as_str)
+            .collect()
+    }
+}
+
+/// `kategori` as one of [`SUBMISSION_CATEGORIES`], ignoring case
+pub fn parse_category(kategori: &str) -> Option<&'static str> {
+    SUBMISSION_CATEGORIES
+        .iter()
+        .find(|category| category.eq_ignore_ascii_case(kategori.trim()))
+        .copied()
+}
+
+/// A submission as admins see it
+#[derive(Debug, Clone, Serialize, ToSchema, sqlx::FromRow)]
+pub struct Submission {
+    pub id: Uuid,
+    #[schema(example = "Budi Santoso")]
+    pub nama: String,
+    #[schema(example = "3175071234560001")]
+    pub nik: Option<String>,
+    #[schema(example = "kebersihan")]
+    pub kategori: String,
+    #[schema(example = "Sampah di Jl. Raya Cakung belum diangkut sejak minggu lalu.")]
+    pub isi: String,
+    pub attachment_asset_id: Option<Uuid>,
+    /// Where the attachment is served, when there is one
+    #[schema(example = "/api/assets/serve/9b2f_foto_jpg.jpg")]
+    pub attachment_url: Option<String>,
+    pub status: SubmissionStatus,
+    /// Who is handling the submission
+    #[schema(example = "Pak RT 05")]
+    pub assigned_to: Option<String>,
+    /// What was done, set when resolved
+    #[schema(example = "Sampah sudah diangkut pada 12 Mei.")]
+    pub resolution_notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// The multipart form of `POST /api/submissions`, for the OpenAPI document
+#[derive(Debug, ToSchema)]
+pub struct SubmissionForm {
+    #[schema(example = "Budi Santoso")]
+    pub nama: String,
+    /// 16 digits
+    #[schema(example = "3175071234560001")]
+    pub nik: Option<String>,
+    /// One of `infrastruktur`, `kebersihan`, `keamanan`, `pelayanan`,
+    /// `sosial` or `lainnya`
+    #[schema(example = "kebersihan")]
+    pub kategori: String,
+    #[schema(example = "Sampah di Jl. Raya Cakung belum diangkut sejak minggu lalu.")]
+    pub isi: String,
+    /// Photo or document, within the upload size limit
+    #[schema(value_type = Option<String>, format = Binary)]
+    pub attachment: Option<Vec<u8>>,
+}
+
+/// Response to a submission
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SubmittedForm {
+    /// Reference the resident can quote when asking about it
+    pub id: Uuid,
+    /// Always `open`
+    pub status: SubmissionStatus,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AssignSubmissionRequest {
+    #[schema(example = "Pak RT 05")]
+    pub assigned_to: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ResolveSubmissionRequest {
+    #[schema(example = "Sampah sudah diangkut pada 12 Mei.")]
+    pub resolution_notes: String,
+}
+
+/// Gauge of submissions not resolved yet
+#[derive(Clone)]
+pub struct SubmissionMetrics {
+    open: IntGauge,
+}
+
+impl Default for SubmissionMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SubmissionMetrics {
+    pub fn new() -> Self {
+        let open = IntGauge::with_opts(
+            Opts::new(
+                "submissions_open",
+                "Submissions that are open or in progress",
+            )
+            .namespace(METRICS_NAMESPACE),
+        )
+        .expect("valid submission metric");
+        Self { open }
+    }
+
+    /// Register the gauge so it is exported on `/metrics`
+    pub fn register(&self, registry: &Registry) -> prometheus::Result<()> {
+        registry.register(Box::new(self.open.clone()))
+    }
+
+    /// Current value of the gauge
+    pub fn open(&self) -> i64 {
+        self.open.get()
+    }
+}
+
+const SUBMISSION_COLUMNS: &str = "s.id, s.nama, s.nik, s.kategori, s.isi, s.attachment_asset_id,
+     a.url AS attachment_url, s.status, s.assigned_to, s.resolution_notes,
+     s.created_at, s.updated_at, s.resolved_at";
+
+/// The attachment is joined for its URL
+const SUBMISSION_SOURCE: &str =
+    "submissions s LEFT JOIN assets a ON a.id = s.attachment_asset_id";
+
+/// The new values of a status change
+pub struct SubmissionUpdate<'a> {
+    pub status: SubmissionStatus,
+    pub assigned_to: Option<&'a str>,
+    pub resolution_notes: Option<&'a str>,
+}
+
+impl AppState {
+    /// Store an open submission under `id`
+    pub async fn insert_submission(
+        &self,
+        id: &Uuid,
+        nama: &str,
+        nik: Option<&str>,
+        kategori: &str,
+        isi: &str,
+        attachment_asset_id: Option<Uuid>,
+    ) -> Result<Submission, DbError> {
+        self.timed("insert_submission", async {
+            sqlx::query(
+                "INSERT INTO submissions (id, nama, nik, kategori, isi, attachment_asset_id)
+                 VALUES ($1, $2, $3, $4, $5, $6)",
+            )
+            .bind(id)
+            .bind(nama)
+            .bind(nik)
+            .bind(kategori)
+            .bind(isi)
+            .bind(attachment_asset_id)
+            .execute(&self.pool)
+            .await
+        })
+        .await?;
+        self.refresh_open_submissions().await;
+        self.get_submission(id)
+            .await?
+            .ok_or(DbError::Other(sqlx::Error::RowNotFound))
+    }
+
+    pub async fn get_submission(&self, id: &Uuid) -> Result<Option<Submission>, DbError> {
+        self.timed("get_submission", async {
+            sqlx::query_as(&format!(
+                "SELECT {} FROM {} WHERE s.id = $1",
+                SUBMISSION_COLUMNS, SUBMISSION_SOURCE
+            ))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    /// Submissions newest first, optionally only one status and category
+    pub async fn list_submissions(
+        &self,
+        status: Option<SubmissionStatus>,
+        kategori: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Submission>, DbError> {
+        self.timed("list_submissions", async {
+            sqlx::query_as(&format!(
+                "SELECT {} FROM {}
+                 WHERE ($1::text IS NULL OR s.status = $1)
+                   AND ($2::text IS NULL OR s.kategori = $2)
+                 ORDER BY s.created_at DESC, s.id
+                 LIMIT $3",
+                SUBMISSION_COLUMNS, SUBMISSION_SOURCE
+            ))
+            .bind(status.map(SubmissionStatus::as_str))
+            .bind(kategori)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    /// Apply `update` if [`SubmissionStatus::can_become`] allows it from
+    /// the current status. Fields left `None` keep their value. `None`
+    /// when the submission does not exist or cannot move to the status.
+    pub async fn update_submission(
+        &self,
+        id: &Uuid,
+        update: SubmissionUpdate<'_>,
+    ) -> Result<Option<Submission>, DbError> {
+        let updated = self
+            .timed("update_submission", async {
+                sqlx::query(
+                    "UPDATE submissions
+                     SET status = $2,
+                         assigned_to = COALESCE($3, assigned_to),
+                         resolution_notes = COALESCE($4, resolution_notes),
+                         resolved_at = CASE WHEN $2 = 'resolved' THEN NOW() END,
+                         updated_at = NOW()
+                     WHERE id = $1 AND status = ANY($5)",
+                )
+                .bind(id)
+                .bind(update.status.as_str())
+                .bind(update.assigned_to)
+                .bind(update.resolution_notes)
+                .bind(update.status.sources())
+                .execute(&self.pool)
+                .await
+            })
+            .await?
+            .rows_affected()
+            > 0;
+
+        if !updated {
+            return Ok(None);
+        }
+        self.refresh_open_submissions().await;
+        self.get_submission(id).await
+    }
+
+    /// Count the unresolved submissions into the `/metrics` gauge. A failed
+    /// count is logged and leaves the gauge as it was.
+    pub async fn refresh_open_submissions(&self) {
+        let count: Result<i64, DbError> = self
+            .timed("count_open_submissions", async {
+                sqlx::query_scalar("SELECT COUNT(*) FROM submissions WHERE status <> 'resolved'")
+                    .fetch_one(&self.pool)
+                    .await
+            })
+            .await;
+        match count {
+            Ok(count) => self.submission_metrics.open.set(count),
+            Err(e) => log::warn!("Failed to count open submissions: {}", e),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! Time zone for calendar dates.
+//!
+//! Timestamps are stored in UTC, but a post's `date` and the dates printed
+//! on generated letters are the day in Jakarta, not in whatever zone the
+//! container happens to run in. `APP_TIMEZONE` takes an IANA name and
+//! defaults to `Asia/Jakarta`.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use std::sync::OnceLock;
+
+pub const DEFAULT_TIMEZONE: Tz = chrono_tz::Asia::Jakarta;
+
+static APP_TIMEZONE: OnceLock<Tz> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimezoneConfig {
+    pub timezone: Tz,
+}
+
+impl Default for TimezoneConfig {
+    fn default() -> Self {
+        Self {
+            timezone: DEFAULT_TIMEZONE,
+        }
+    }
+}
+
+impl TimezoneConfig {
+    pub fn from_lookup<F>(lookup: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        match lookup("APP_TIMEZONE").map(|v| v.trim().to_string()) {
+            Some(v) if !v.is_empty() => v
+                .parse::<Tz>()
+                .map(|timezone| Self { timezone })
+                .map_err(|_| format!("APP_TIMEZONE must be an IANA time zone name, got {:?}", v)),
+            _ => Ok(Self::default()),
+        }
+    }
+}
+
+/// Set the zone used for dates. Only the first call has an effect.
+pub fn init_timezone(config: &TimezoneConfig) {
+    let _ = APP_TIMEZONE.set(config.timezone);
+}
+
+/// The zone from [`init_timezone`], `Asia/Jakarta` before it is called
+pub fn app_timezone() -> Tz {
+    *APP_TIMEZONE.get_or_init(|| DEFAULT_TIMEZONE)
+}
+
+/// Calendar date of `instant` in the app time zone
+pub fn date_at(instant: DateTime<Utc>) -> NaiveDate {
+    instant.with_timezone(&app_timezone()).date_naive()
+}
+
+/// Today's date in the app time zone
+pub fn today() -> NaiveDate {
+    date_at(Utc::now())
+}
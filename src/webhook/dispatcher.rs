@@ -0,0 +1,270 @@
+//! Background delivery of queued webhook events.
+//!
+//! Events are handled one at a time, so a subscriber sees them in the order
+//! they happened; the subscribers of one event are called concurrently.
+//! Timeouts, connection errors, 408, 429 and 5xx responses are retried with
+//! the HTTP retry backoff. A delivery that still fails is written to
+//! `webhook_dead_letters`.
+
+use hmac::{Hmac, Mac};
+use reqwest::StatusCode;
+use sha2::Sha256;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex, Notify};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use super::WebhookPayload;
+use crate::http_client::RetryPolicy;
+
+/// Header carrying `sha256=<hex HMAC-SHA256 of the body>`
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+/// Header carrying the event name, e.g. `post.created`
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+
+/// Value of [`SIGNATURE_HEADER`] for `body` signed with `secret`
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Why a delivery gave up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryFailure {
+    /// Attempts made, including the first
+    pub attempts: u32,
+    pub error: String,
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+}
+
+/// POST `body` to `url`, signed with `secret`, retrying as described in the
+/// module docs
+pub async fn deliver(
+    client: &reqwest::Client,
+    policy: &RetryPolicy,
+    url: &str,
+    secret: &str,
+    event: &str,
+    body: &[u8],
+) -> Result<(), DeliveryFailure> {
+    let signature = sign(secret, body);
+    let mut attempt = 1;
+    loop {
+        let result = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, &signature)
+            .header(EVENT_HEADER, event)
+            .body(body.to_vec())
+            .send()
+            .await;
+        let (error, retryable) = match result {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => (
+                format!("status {}", response.status()),
+                is_retryable(response.status()),
+            ),
+            Err(e) => (e.to_string(), e.is_timeout() || e.is_connect()),
+        };
+
+        if !retryable || attempt >= policy.attempts {
+            return Err(DeliveryFailure {
+                attempts: attempt,
+                error,
+            });
+        }
+        let delay = policy.backoff_after(attempt);
+        log::warn!(
+            "Webhook {} to {} attempt {}/{} failed ({}), retrying in {:?}",
+            event,
+            url,
+            attempt,
+            policy.attempts,
+            error,
+            delay
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct Subscriber {
+    id: Uuid,
+    url: String,
+    secret: String,
+}
+
+/// Send one event to every active webhook subscribed to it
+async fn dispatch(
+    pool: &PgPool,
+    client: &reqwest::Client,
+    policy: &RetryPolicy,
+    payload: &WebhookPayload,
+) {
+    let event = payload.event.as_str();
+    let subscribers = match sqlx::query_as::<_, Subscriber>(
+        "SELECT id, url, secret FROM webhooks WHERE active AND $1 = ANY(events)",
+    )
+    .bind(event)
+    .fetch_all(pool)
+    .await
+    {
+        Ok(subscribers) => subscribers,
+        Err(e) => {
+            log::error!("Failed to load webhooks for {}: {}", event, e);
+            return;
+        }
+    };
+    if subscribers.is_empty() {
+        return;
+    }
+    let body = match serde_json::to_vec(payload) {
+        Ok(body) => body,
+        Err(e) => {
+            log::error!("Failed to serialize {} webhook payload: {}", event, e);
+            return;
+        }
+    };
+
+    let body = body.as_slice();
+    let deliveries = subscribers.iter().map(|subscriber| async move {
+        let result = deliver(
+            client,
+            policy,
+            &subscriber.url,
+            &subscriber.secret,
+            event,
+            body,
+        )
+        .await;
+        (subscriber, result)
+    });
+    for (subscriber, result) in futures::future::join_all(deliveries).await {
+        match result {
+            Ok(()) => log::debug!("Webhook {} delivered to {}", event, subscriber.url),
+            Err(failure) => {
+                log::error!(
+                    "Webhook {} to {} failed after {} attempts: {}",
+                    event,
+                    subscriber.url,
+                    failure.attempts,
+                    failure.error
+                );
+                record_dead_letter(pool, subscriber, payload, &failure).await;
+            }
+        }
+    }
+}
+
+async fn record_dead_letter(
+    pool: &PgPool,
+    subscriber: &Subscriber,
+    payload: &WebhookPayload,
+    failure: &DeliveryFailure,
+) {
+    let result = sqlx::query(
+        "INSERT INTO webhook_dead_letters (webhook_id, url, event, payload, error, attempts) \
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(subscriber.id)
+    .bind(&subscriber.url)
+    .bind(payload.event.as_str())
+    .bind(sqlx::types::Json(payload))
+    .bind(&failure.error)
+    .bind(i32::try_from(failure.attempts).unwrap_or(i32::MAX))
+    .execute(pool)
+    .await;
+    if let Err(e) = result {
+        log::error!(
+            "Failed to record dead letter for webhook {}: {}",
+            subscriber.id,
+            e
+        );
+    }
+}
+
+/// Deliver queued events until the channel closes or `shutdown` is
+/// notified. On shutdown the events already queued are still delivered.
+pub async fn run_webhook_worker(
+    mut receiver: mpsc::Receiver<WebhookPayload>,
+    pool: PgPool,
+    client: reqwest::Client,
+    policy: RetryPolicy,
+    shutdown: Arc<Notify>,
+) {
+    log::info!("Webhook worker started");
+
+    loop {
+        tokio::select! {
+            received = receiver.recv() => match received {
+                Some(payload) => dispatch(&pool, &client, &policy, &payload).await,
+                None => break,
+            },
+            _ = shutdown.notified() => {
+                receiver.close();
+                while let Some(payload) = receiver.recv().await {
+                    dispatch(&pool, &client, &policy, &payload).await;
+                }
+                break;
+            }
+        }
+    }
+
+    log::info!("Webhook worker stopped");
+}
+
+/// Handle to a spawned webhook worker, used to flush it on shutdown
+pub struct WebhookWorker {
+    shutdown: Arc<Notify>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl WebhookWorker {
+    /// Spawn the worker on the current runtime
+    pub fn spawn(
+        receiver: mpsc::Receiver<WebhookPayload>,
+        pool: PgPool,
+        client: reqwest::Client,
+        policy: RetryPolicy,
+    ) -> Self {
+        let shutdown = Arc::new(Notify::new());
+        let worker_shutdown = shutdown.clone();
+        let handle = tokio::spawn(async move {
+            run_webhook_worker(receiver, pool, client, policy, worker_shutdown).await;
+        });
+
+        Self {
+            shutdown,
+            handle: Mutex::new(Some(handle)),
+        }
+    }
+
+    /// Handle for a state built without a worker. Queued events are dropped.
+    pub fn disabled() -> Self {
+        Self {
+            shutdown: Arc::new(Notify::new()),
+            handle: Mutex::new(None),
+        }
+    }
+
+    /// Deliver the events still queued and wait for the worker to exit.
+    /// Calling this more than once is a no-op.
+    pub async fn flush(&self) {
+        let handle = self.handle.lock().await.take();
+        if let Some(handle) = handle {
+            self.shutdown.notify_one();
+            if let Err(e) = handle.await {
+                log::error!("Webhook worker panicked: {}", e);
+            }
+        }
+    }
+}
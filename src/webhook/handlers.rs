@@ -0,0 +1,237 @@
+//! Admin endpoints for managing webhooks
+
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use super::{
+    validate_webhook_url, CreateWebhookRequest, CreatedWebhook, UpdateWebhookRequest, Webhook,
+    WebhookDeadLetter,
+};
+use crate::auth::validate_request_token;
+use crate::error::ApiError;
+use crate::AppState;
+
+const DEFAULT_DEAD_LETTER_LIMIT: i64 = 20;
+const MAX_DEAD_LETTER_LIMIT: i64 = 100;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DeadLetterQuery {
+    /// Entries returned, newest first (default 20, max 100)
+    pub limit: Option<i64>,
+}
+
+fn validate_secret(secret: Option<&str>) -> Result<(), ApiError> {
+    if secret.is_some_and(|secret| secret.trim().is_empty()) {
+        return Err(ApiError::BadRequest(
+            "Webhook secret cannot be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_create(request: &CreateWebhookRequest) -> Result<(), ApiError> {
+    validate_webhook_url(&request.url).map_err(ApiError::BadRequest)?;
+    if request.events.is_empty() {
+        return Err(ApiError::BadRequest(
+            "At least one event is required".to_string(),
+        ));
+    }
+    validate_secret(request.secret.as_deref())
+}
+
+fn validate_update(request: &UpdateWebhookRequest) -> Result<(), ApiError> {
+    if let Some(url) = &request.url {
+        validate_webhook_url(url).map_err(ApiError::BadRequest)?;
+    }
+    if request.events.as_ref().is_some_and(Vec::is_empty) {
+        return Err(ApiError::BadRequest(
+            "At least one event is required".to_string(),
+        ));
+    }
+    validate_secret(request.secret.as_deref())
+}
+
+/// List registered webhooks, without their secrets (protected)
+#[utoipa::path(
+    operation_id = "listWebhooks",
+    get,
+    path = "/api/admin/webhooks",
+    tag = "Admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Registered webhooks", body = Vec<Webhook>),
+        (status = 401, response = crate::UnauthorizedResponse)
+    )
+)]
+pub async fn list_webhooks(req: HttpRequest, state: web::Data<AppState>) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    match state.list_webhooks().await {
+        Ok(webhooks) => HttpResponse::Ok().json(webhooks),
+        Err(e) => ApiError::database("Failed to list webhooks")(e).error_response(),
+    }
+}
+
+/// Register a webhook. The response is the only place the secret is shown
+/// (protected)
+#[utoipa::path(
+    operation_id = "createWebhook",
+    post,
+    path = "/api/admin/webhooks",
+    tag = "Admin",
+    request_body = CreateWebhookRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Webhook registered", body = CreatedWebhook),
+        (status = 400, description = "Invalid URL, event or secret"),
+        (status = 401, response = crate::UnauthorizedResponse)
+    )
+)]
+pub async fn create_webhook(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: web::Json<CreateWebhookRequest>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+    if let Err(e) = validate_create(&body) {
+        return e.error_response();
+    }
+
+    match state.create_webhook(&body).await {
+        Ok(created) => {
+            log::info!(
+                "Webhook {} registered for {}",
+                created.webhook.id,
+                created.webhook.events.join(", ")
+            );
+            HttpResponse::Created().json(created)
+        }
+        Err(e) => ApiError::database("Failed to create webhook")(e).error_response(),
+    }
+}
+
+/// Change a webhook's URL, events, secret or active flag (protected)
+#[utoipa::path(
+    operation_id = "updateWebhook",
+    put,
+    path = "/api/admin/webhooks/{id}",
+    tag = "Admin",
+    params(("id" = Uuid, Path, description = "Webhook ID")),
+    request_body = UpdateWebhookRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Webhook updated", body = Webhook),
+        (status = 400, description = "Invalid URL, event or secret"),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 404, description = "Webhook not found")
+    )
+)]
+pub async fn update_webhook(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    body: web::Json<UpdateWebhookRequest>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+    if let Err(e) = validate_update(&body) {
+        return e.error_response();
+    }
+
+    match state.update_webhook(&path, &body).await {
+        Ok(Some(webhook)) => HttpResponse::Ok().json(webhook),
+        Ok(None) => ApiError::NotFound("Webhook not found".to_string()).error_response(),
+        Err(e) => ApiError::database("Failed to update webhook")(e).error_response(),
+    }
+}
+
+/// Remove a webhook and its dead letters (protected)
+#[utoipa::path(
+    operation_id = "deleteWebhook",
+    delete,
+    path = "/api/admin/webhooks/{id}",
+    tag = "Admin",
+    params(("id" = Uuid, Path, description = "Webhook ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Webhook deleted"),
+        (status = 401, response = crate::UnauthorizedResponse),
+        (status = 404, description = "Webhook not found")
+    )
+)]
+pub async fn delete_webhook(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+
+    match state.delete_webhook(&path).await {
+        Ok(true) => HttpResponse::NoContent().finish(),
+        Ok(false) => ApiError::NotFound("Webhook not found".to_string()).error_response(),
+        Err(e) => ApiError::database("Failed to delete webhook")(e).error_response(),
+    }
+}
+
+/// Deliveries that failed after every retry (protected)
+#[utoipa::path(
+    operation_id = "listWebhookDeadLetters",
+    get,
+    path = "/api/admin/webhooks/dead-letters",
+    tag = "Admin",
+    params(DeadLetterQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Failed deliveries, newest first", body = Vec<WebhookDeadLetter>),
+        (status = 400, description = "Limit out of range"),
+        (status = 401, response = crate::UnauthorizedResponse)
+    )
+)]
+pub async fn list_dead_letters(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<DeadLetterQuery>,
+) -> impl Responder {
+    if let Err(e) = validate_request_token(&req) {
+        return e.error_response();
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_DEAD_LETTER_LIMIT);
+    if !(1..=MAX_DEAD_LETTER_LIMIT).contains(&limit) {
+        return ApiError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_DEAD_LETTER_LIMIT
+        ))
+        .error_response();
+    }
+
+    match state.recent_webhook_dead_letters(limit).await {
+        Ok(letters) => HttpResponse::Ok().json(letters),
+        Err(e) => ApiError::database("Failed to list webhook dead letters")(e).error_response(),
+    }
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/admin/webhooks")
+            .service(
+                web::resource("")
+                    .route(web::get().to(list_webhooks))
+                    .route(web::post().to(create_webhook)),
+            )
+            .service(web::resource("/dead-letters").route(web::get().to(list_dead_letters)))
+            .service(
+                web::resource("/{id}")
+                    .route(web::put().to(update_webhook))
+                    .route(web::delete().to(delete_webhook)),
+            ),
+    );
+}
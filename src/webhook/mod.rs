@@ -0,0 +1,262 @@
+//! Outgoing webhooks for content changes.
+//!
+//! Admins register URLs under `/api/admin/webhooks` together with the events
+//! they want. Handlers queue events with [`AppState::notify_webhooks`],
+//! usually through `AppState::publish_change`, which never waits: delivery, retries and the dead-letter log are handled by
+//! [`dispatcher::WebhookWorker`] in the background.
+
+pub mod dispatcher;
+pub mod handlers;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::error::TrySendError;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::DbError;
+use crate::AppState;
+
+/// Change that subscribers can be notified about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum WebhookEvent {
+    #[serde(rename = "post.created")]
+    PostCreated,
+    #[serde(rename = "post.updated")]
+    PostUpdated,
+    #[serde(rename = "post.deleted")]
+    PostDeleted,
+    #[serde(rename = "asset.uploaded")]
+    AssetUploaded,
+    #[serde(rename = "asset.deleted")]
+    AssetDeleted,
+}
+
+impl WebhookEvent {
+    /// Name used in payloads, the `X-Webhook-Event` header and `webhooks.events`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PostCreated => "post.created",
+            Self::PostUpdated => "post.updated",
+            Self::PostDeleted => "post.deleted",
+            Self::AssetUploaded => "asset.uploaded",
+            Self::AssetDeleted => "asset.deleted",
+        }
+    }
+}
+
+/// A registered webhook. The secret is only returned when it is created.
+#[derive(Debug, Clone, Serialize, ToSchema, sqlx::FromRow)]
+pub struct Webhook {
+    pub id: Uuid,
+    #[schema(example = "https://bot.example.com/hooks/cakung-barat")]
+    pub url: String,
+    #[schema(example = json!(["post.created"]))]
+    pub events: Vec<String>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Response to creating a webhook
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedWebhook {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    /// Key for verifying `X-Signature`. Store it now, it is not shown again.
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    /// http or https URL receiving the POSTs
+    #[schema(example = "https://bot.example.com/hooks/cakung-barat")]
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    /// Signing key; a random one is generated when omitted
+    pub secret: Option<String>,
+    /// Defaults to true
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    pub events: Option<Vec<WebhookEvent>>,
+    /// Replaces the signing key
+    pub secret: Option<String>,
+    pub active: Option<bool>,
+}
+
+/// A delivery that failed after all attempts
+#[derive(Debug, Clone, Serialize, ToSchema, sqlx::FromRow)]
+pub struct WebhookDeadLetter {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub url: String,
+    #[schema(example = "post.created")]
+    pub event: String,
+    /// The body that was sent
+    pub payload: serde_json::Value,
+    /// Last status or connection error
+    pub error: String,
+    pub attempts: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Body POSTed to subscribers
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookPayload {
+    /// Unique per event, the same for every subscriber and every retry
+    pub id: Uuid,
+    pub event: WebhookEvent,
+    pub occurred_at: DateTime<Utc>,
+    /// The post or asset, or `{"id": ...}` for deletions
+    pub data: serde_json::Value,
+}
+
+/// Error message when `url` cannot receive webhooks
+pub fn validate_webhook_url(url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Webhook URL must use http or https".to_string());
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err("Webhook URL must have a host".to_string());
+    }
+    Ok(())
+}
+
+/// Event names for `webhooks.events`, without duplicates
+fn event_names(events: &[WebhookEvent]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for event in events {
+        if !names.iter().any(|name| name == event.as_str()) {
+            names.push(event.as_str().to_string());
+        }
+    }
+    names
+}
+
+/// Random signing key: 64 hex characters
+fn generate_secret() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+impl AppState {
+    /// Queue `event` for every subscribed webhook. Returns at once; when the
+    /// queue is full the event is dropped and logged.
+    pub fn notify_webhooks<T: Serialize>(&self, event: WebhookEvent, data: &T) {
+        let data = match serde_json::to_value(data) {
+            Ok(data) => data,
+            Err(e) => {
+                log::error!(
+                    "Failed to serialize {} webhook payload: {}",
+                    event.as_str(),
+                    e
+                );
+                return;
+            }
+        };
+        let payload = WebhookPayload {
+            id: Uuid::new_v4(),
+            event,
+            occurred_at: Utc::now(),
+            data,
+        };
+        match self.webhook_sender.try_send(payload) {
+            Ok(()) => {}
+            Err(TrySendError::Full(payload)) => {
+                log::warn!(
+                    "Webhook queue is full, dropping {} event {}",
+                    event.as_str(),
+                    payload.id
+                );
+            }
+            Err(TrySendError::Closed(_)) => {
+                log::debug!("Webhook worker not running, {} not sent", event.as_str());
+            }
+        }
+    }
+
+    pub async fn list_webhooks(&self) -> Result<Vec<Webhook>, DbError> {
+        sqlx::query_as::<_, Webhook>(
+            "SELECT id, url, events, active, created_at, updated_at \
+             FROM webhooks \
+             ORDER BY created_at",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DbError::from)
+    }
+
+    pub async fn create_webhook(
+        &self,
+        request: &CreateWebhookRequest,
+    ) -> Result<CreatedWebhook, DbError> {
+        let secret = request.secret.clone().unwrap_or_else(generate_secret);
+        let webhook = sqlx::query_as::<_, Webhook>(
+            "INSERT INTO webhooks (url, secret, events, active) \
+             VALUES ($1, $2, $3, $4) \
+             RETURNING id, url, events, active, created_at, updated_at",
+        )
+        .bind(&request.url)
+        .bind(&secret)
+        .bind(event_names(&request.events))
+        .bind(request.active.unwrap_or(true))
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(CreatedWebhook { webhook, secret })
+    }
+
+    /// Change the given fields. `None` when no webhook has that id.
+    pub async fn update_webhook(
+        &self,
+        id: &Uuid,
+        request: &UpdateWebhookRequest,
+    ) -> Result<Option<Webhook>, DbError> {
+        sqlx::query_as::<_, Webhook>(
+            "UPDATE webhooks SET \
+                 url = COALESCE($2, url), \
+                 events = COALESCE($3, events), \
+                 secret = COALESCE($4, secret), \
+                 active = COALESCE($5, active), \
+                 updated_at = NOW() \
+             WHERE id = $1 \
+             RETURNING id, url, events, active, created_at, updated_at",
+        )
+        .bind(id)
+        .bind(&request.url)
+        .bind(request.events.as_deref().map(event_names))
+        .bind(&request.secret)
+        .bind(request.active)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DbError::from)
+    }
+
+    /// Remove a webhook and its dead letters. False when it did not exist.
+    pub async fn delete_webhook(&self, id: &Uuid) -> Result<bool, DbError> {
+        let result = sqlx::query("DELETE FROM webhooks WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn recent_webhook_dead_letters(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<WebhookDeadLetter>, DbError> {
+        sqlx::query_as::<_, WebhookDeadLetter>(
+            "SELECT id, webhook_id, url, event, payload, error, attempts, created_at \
+             FROM webhook_dead_letters \
+             ORDER BY created_at DESC \
+             LIMIT $1",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DbError::from)
+    }
+}
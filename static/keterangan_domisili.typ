@@ -0,0 +1,87 @@
+#let surat_keterangan_domisili(
+  pengisi: (
+    nama: "........................................",
+    nik: "........................................",
+    ttl: "........................................",
+    jk: "........................................",
+    agama: "........................................",
+    pekerjaan: "........................................",
+    alamat: "........................................",
+    telp: "........................................",
+  ),
+  domisili: (
+    alamat: "........................................",
+    rt_rw: "........................................",
+    lama_tinggal: "........................................",
+    keperluan: "........................................",
+  ),
+  meta: (
+    kelurahan: "........................................",
+    tanggal: ".................... 2025",
+  ),
+  lampiran: (foto: none, ttd: none),
+) = {
+  set page(paper: "a4", margin: 2.5cm)
+  set text(font: "Times New Roman", size: 12pt)
+  set par(justify: true, leading: 0.65em)
+
+  let field(label, isi) = {
+    grid(
+      columns: (160pt, 10pt, 1fr),
+      gutter: 0.6em,
+      label, [:], isi,
+    )
+  }
+
+  align(center)[
+    #text(weight: "bold", size: 14pt)[SURAT PERNYATAAN DOMISILI]
+  ]
+
+  [Yang bertanda tangan dibawah ini:]
+
+  field([Nama], pengisi.nama)
+  field([NIK], pengisi.nik)
+  field([Tempat & Tgl Lahir], pengisi.ttl)
+  field([Jenis Kelamin], pengisi.jk)
+  field([Agama], pengisi.agama)
+  field([Pekerjaan], pengisi.pekerjaan)
+  field([Alamat sesuai KTP], pengisi.alamat)
+  field([No. Telp / HP], pengisi.telp)
+
+  [Menyatakan bahwa benar saya saat ini bertempat tinggal / berdomisili di:]
+
+  field([Alamat Domisili], domisili.alamat)
+  field([RT / RW], domisili.rt_rw)
+  field([Kelurahan], meta.kelurahan)
+  field([Lama Tinggal], domisili.lama_tinggal)
+
+  [dan bermaksud mengurus Surat Keterangan Domisili pada satuan pelaksana PTSP Kelurahan #meta.kelurahan untuk keperluan #domisili.keperluan.]
+
+  [Demikian surat pernyataan ini dibuat dengan sebenarnya dan apabila dikemudian hari terbukti surat pernyataan ini tidak benar dan/atau terjadi penyalahgunaan terkait layanan perizinan dan non perizinan yang diterbitkan maka saya bersedia dituntut sesuai dengan peraturan perundang-undangan yang berlaku dan dokumen yang telah diterbitkan dapat dibatalkan atau batal demi hukum.]
+
+  grid(
+    columns: (1fr, 1fr),
+    [
+      #if lampiran.foto != none {
+        image(lampiran.foto, width: 3cm, height: 4cm, fit: "cover")
+      }
+    ],
+    [
+      Jakarta, #meta.tanggal \
+      Yang membuat pernyataan,
+      #v(1.5cm)
+      #align(center)[
+        #rect(width: 60pt, height: 40pt, stroke: 0.5pt + gray)[
+          #set align(center + horizon)
+          #text(size: 8pt)[materai\ Rp. 10.000]
+        ]
+      ]
+      #if lampiran.ttd != none {
+        align(center, image(lampiran.ttd, height: 1.5cm))
+      }
+      ( #pengisi.nama )
+    ],
+  )
+}
+
+#surat_keterangan_domisili()
@@ -24,6 +24,7 @@
     kelurahan: "........................................",
     tanggal: ".................... 2025",
   ),
+  lampiran: (foto: none, ttd: none),
 ) = {
   set page(paper: "a4", margin: 2.5cm)
   set text(font: "Times New Roman", size: 12pt)
@@ -79,7 +80,11 @@
 
   grid(
     columns: (1fr, 1fr),
-    [],
+    [
+      #if lampiran.foto != none {
+        image(lampiran.foto, width: 3cm, height: 4cm, fit: "cover")
+      }
+    ],
     [
       Jakarta, #meta.tanggal \
       Yang membuat pernyataan,
@@ -90,6 +95,9 @@
           #text(size: 8pt)[materai\ Rp. 10.000]
         ]
       ]
+      #if lampiran.ttd != none {
+        align(center, image(lampiran.ttd, height: 1.5cm))
+      }
       ( #pengisi.nama )
     ],
   )
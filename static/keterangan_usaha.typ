@@ -0,0 +1,88 @@
+#let surat_keterangan_usaha(
+  pemilik: (
+    nama: "........................................",
+    nik: "........................................",
+    ttl: "........................................",
+    jk: "........................................",
+    agama: "........................................",
+    pekerjaan: "........................................",
+    alamat: "........................................",
+    telp: "........................................",
+  ),
+  usaha: (
+    nama_usaha: "........................................",
+    jenis_usaha: "........................................",
+    alamat_usaha: "........................................",
+    lama_usaha: "........................................",
+    keperluan: "........................................",
+  ),
+  meta: (
+    kelurahan: "........................................",
+    tanggal: ".................... 2025",
+  ),
+  lampiran: (foto: none, ttd: none),
+) = {
+  set page(paper: "a4", margin: 2.5cm)
+  set text(font: "Times New Roman", size: 12pt)
+  set par(justify: true, leading: 0.65em)
+
+  let field(label, isi) = {
+    grid(
+      columns: (160pt, 10pt, 1fr),
+      gutter: 0.6em,
+      label, [:], isi,
+    )
+  }
+
+  align(center)[
+    #text(weight: "bold", size: 14pt)[SURAT PERNYATAAN MEMILIKI USAHA]
+  ]
+
+  [Yang bertanda tangan dibawah ini:]
+
+  field([Nama], pemilik.nama)
+  field([NIK], pemilik.nik)
+  field([Tempat & Tgl Lahir], pemilik.ttl)
+  field([Jenis Kelamin], pemilik.jk)
+  field([Agama], pemilik.agama)
+  field([Pekerjaan], pemilik.pekerjaan)
+  field([Alamat], pemilik.alamat)
+  field([No. Telp / HP], pemilik.telp)
+
+  [Menyatakan bahwa benar saya memiliki dan menjalankan usaha sebagai berikut:]
+
+  field([Nama Usaha], usaha.nama_usaha)
+  field([Jenis Usaha], usaha.jenis_usaha)
+  field([Alamat Usaha], usaha.alamat_usaha)
+  field([Lama Usaha], usaha.lama_usaha)
+
+  [dan bermaksud mengurus Surat Keterangan Usaha pada satuan pelaksana PTSP Kelurahan #meta.kelurahan untuk keperluan #usaha.keperluan.]
+
+  [Demikian surat pernyataan ini dibuat dengan sebenarnya dan apabila dikemudian hari terbukti surat pernyataan ini tidak benar dan/atau terjadi penyalahgunaan terkait layanan perizinan dan non perizinan yang diterbitkan maka saya bersedia dituntut sesuai dengan peraturan perundang-undangan yang berlaku dan dokumen yang telah diterbitkan dapat dibatalkan atau batal demi hukum.]
+
+  grid(
+    columns: (1fr, 1fr),
+    [
+      #if lampiran.foto != none {
+        image(lampiran.foto, width: 3cm, height: 4cm, fit: "cover")
+      }
+    ],
+    [
+      Jakarta, #meta.tanggal \
+      Yang membuat pernyataan,
+      #v(1.5cm)
+      #align(center)[
+        #rect(width: 60pt, height: 40pt, stroke: 0.5pt + gray)[
+          #set align(center + horizon)
+          #text(size: 8pt)[materai\ Rp. 10.000]
+        ]
+      ]
+      #if lampiran.ttd != none {
+        align(center, image(lampiran.ttd, height: 1.5cm))
+      }
+      ( #pemilik.nama )
+    ],
+  )
+}
+
+#surat_keterangan_usaha()
@@ -14,6 +14,7 @@
     bank_tujuan: "........................................",
     tanggal: ".................... 2025",
   ),
+  lampiran: (foto: none, ttd: none),
 ) = {
   set page(paper: "a4", margin: (x: 2.5cm, y: 1.5cm))
   set text(font: "Times New Roman", size: 11pt)
@@ -60,7 +61,11 @@
   v(2em)
   grid(
     columns: (1fr, 1fr),
-    [],
+    [
+      #if lampiran.foto != none {
+        image(lampiran.foto, width: 3cm, height: 4cm, fit: "cover")
+      }
+    ],
     [
       Jakarta, #meta.tanggal \
       Yang membuat pernyataan,
@@ -72,6 +77,9 @@
         ]
       ]
       #v(0.8cm)
+      #if lampiran.ttd != none {
+        align(center, image(lampiran.ttd, height: 1.5cm))
+      }
       ( #data.nama )
     ],
   )
@@ -0,0 +1,84 @@
+#let surat_pengantar_skck(
+  pemohon: (
+    nama: "........................................",
+    nik: "........................................",
+    ttl: "........................................",
+    jk: "........................................",
+    agama: "........................................",
+    pekerjaan: "........................................",
+    alamat: "........................................",
+    telp: "........................................",
+  ),
+  skck: (
+    keperluan: "........................................",
+    tujuan_instansi: "........................................",
+    masa_berlaku: "........................................",
+  ),
+  meta: (
+    kelurahan: "........................................",
+    tanggal: ".................... 2025",
+  ),
+  lampiran: (foto: none, ttd: none),
+) = {
+  set page(paper: "a4", margin: 2.5cm)
+  set text(font: "Times New Roman", size: 12pt)
+  set par(justify: true, leading: 0.65em)
+
+  let field(label, isi) = {
+    grid(
+      columns: (160pt, 10pt, 1fr),
+      gutter: 0.6em,
+      label, [:], isi,
+    )
+  }
+
+  align(center)[
+    #text(weight: "bold", size: 14pt)[SURAT PENGANTAR]
+    #linebreak()
+    #text(weight: "bold")[PERMOHONAN SURAT KETERANGAN CATATAN KEPOLISIAN (SKCK)]
+  ]
+
+  [Yang bertanda tangan dibawah ini, Lurah #meta.kelurahan, dengan ini menerangkan bahwa:]
+
+  field([Nama], pemohon.nama)
+  field([NIK], pemohon.nik)
+  field([Tempat & Tgl Lahir], pemohon.ttl)
+  field([Jenis Kelamin], pemohon.jk)
+  field([Agama], pemohon.agama)
+  field([Pekerjaan], pemohon.pekerjaan)
+  field([Alamat], pemohon.alamat)
+  field([No. Telp / HP], pemohon.telp)
+
+  [Orang tersebut di atas adalah benar warga Kelurahan #meta.kelurahan dan sepanjang pengetahuan kami berkelakuan baik serta tidak pernah tersangkut perkara pidana. Surat pengantar ini diberikan untuk keperluan:]
+
+  field([Keperluan], skck.keperluan)
+  field([Ditujukan Kepada], skck.tujuan_instansi)
+  field([Masa Berlaku], skck.masa_berlaku)
+
+  [Demikian surat pengantar ini dibuat untuk dapat dipergunakan sebagaimana mestinya.]
+
+  if lampiran.foto != none {
+    image(lampiran.foto, width: 3cm, height: 4cm, fit: "cover")
+  }
+
+  grid(
+    columns: (1fr, 1fr),
+    [
+      Pemohon,
+      #if lampiran.ttd != none {
+        image(lampiran.ttd, height: 2cm)
+      } else {
+        v(2cm)
+      }
+      ( #pemohon.nama )
+    ],
+    [
+      Jakarta, #meta.tanggal \
+      Lurah #meta.kelurahan,
+      #v(2cm)
+      ( ........................................ )
+    ],
+  )
+}
+
+#surat_pengantar_skck()
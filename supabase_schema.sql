@@ -4,10 +4,15 @@ CREATE TABLE IF NOT EXISTS assets (
     filename TEXT NOT NULL,
     url TEXT NOT NULL,
     description TEXT,
+    width INTEGER,
+    height INTEGER,
     created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
     updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
 );
 
+ALTER TABLE assets ADD COLUMN IF NOT EXISTS width INTEGER;
+ALTER TABLE assets ADD COLUMN IF NOT EXISTS height INTEGER;
+
 CREATE TABLE IF NOT EXISTS posts (
     id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
     title TEXT NOT NULL,
@@ -19,6 +24,31 @@ CREATE TABLE IF NOT EXISTS posts (
     updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
 );
 
+-- Ranked full-text search, using the Indonesian configuration when the
+-- server has it (the search queries pick the same one)
+DO $$
+DECLARE
+    config TEXT := CASE
+        WHEN EXISTS (SELECT 1 FROM pg_ts_config WHERE cfgname = 'indonesian') THEN 'indonesian'
+        ELSE 'simple'
+    END;
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_name = 'posts' AND column_name = 'search_vector'
+    ) THEN
+        EXECUTE format(
+            'ALTER TABLE posts ADD COLUMN search_vector tsvector GENERATED ALWAYS AS ('
+            'setweight(to_tsvector(%1$L::regconfig, coalesce(title, '''')), ''A'') || '
+            'setweight(to_tsvector(%1$L::regconfig, coalesce(excerpt, '''')), ''B'')) STORED',
+            config
+        );
+    END IF;
+END
+$$;
+
+CREATE INDEX IF NOT EXISTS idx_posts_search_vector ON posts USING GIN (search_vector);
+
 CREATE TABLE IF NOT EXISTS posting_assets (
     posting_id UUID REFERENCES posts(id) ON DELETE CASCADE,
     asset_id UUID REFERENCES assets(id) ON DELETE CASCADE,
@@ -39,6 +69,67 @@ CREATE TABLE IF NOT EXISTS asset_folders (
     PRIMARY KEY (asset_id, folder_id)
 );
 
+-- Posts keep the folder name in folder_id for the API, but refer to the
+-- folder row by id so a rename does not detach them from their assets
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS folder_uuid UUID REFERENCES folders(id) ON DELETE SET NULL;
+
+UPDATE posts p
+SET folder_uuid = f.id
+FROM folders f
+WHERE p.folder_uuid IS NULL AND p.folder_id = f.name;
+
+CREATE INDEX IF NOT EXISTS idx_posts_folder_uuid ON posts(folder_uuid);
+
+-- Card image picked for the post. When null (or once the asset is
+-- deleted) the first image in the post's folder is used
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS cover_asset_id UUID REFERENCES assets(id) ON DELETE SET NULL;
+
+-- Other-language versions of a post's title and excerpt. Indonesian is
+-- the post itself and is never stored here.
+CREATE TABLE IF NOT EXISTS post_translations (
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    lang TEXT NOT NULL,
+    title TEXT NOT NULL,
+    excerpt TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (post_id, lang)
+);
+
+-- Resident comments, shown under the post once an admin approves them
+CREATE TABLE IF NOT EXISTS comments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    author_name TEXT NOT NULL,
+    contact TEXT,
+    body TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected')),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    moderated_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_comments_post_id_status ON comments(post_id, status);
+CREATE INDEX IF NOT EXISTS idx_comments_status_created_at ON comments(status, created_at);
+
+-- Complaints and messages from the public form. The optional attachment
+-- is an asset in the private folder submissions/{id}
+CREATE TABLE IF NOT EXISTS submissions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    nama TEXT NOT NULL,
+    nik TEXT,
+    kategori TEXT NOT NULL,
+    isi TEXT NOT NULL,
+    attachment_asset_id UUID REFERENCES assets(id) ON DELETE SET NULL,
+    status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'in_progress', 'resolved')),
+    assigned_to TEXT,
+    resolution_notes TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_submissions_status_created_at ON submissions(status, created_at);
+
 CREATE INDEX IF NOT EXISTS idx_assets_filename ON assets(filename);
 CREATE INDEX IF NOT EXISTS idx_posting_assets_posting_id ON posting_assets(posting_id);
 CREATE INDEX IF NOT EXISTS idx_posting_assets_asset_id ON posting_assets(asset_id);
@@ -61,4 +152,53 @@ CREATE TRIGGER update_assets_updated_at
 CREATE TRIGGER update_posts_updated_at
     BEFORE UPDATE ON posts
     FOR EACH ROW
-    EXECUTE FUNCTION update_updated_at_column();
\ No newline at end of file
+    EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE IF NOT EXISTS admins (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    username VARCHAR(255) UNIQUE NOT NULL,
+    password_hash VARCHAR(255) NOT NULL,
+    display_name VARCHAR(255),
+    refresh_token TEXT,
+    refresh_token_issued_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    created_by UUID REFERENCES admins(id) ON DELETE SET NULL
+);
+
+ALTER TABLE admins ADD COLUMN IF NOT EXISTS refresh_token_issued_at TIMESTAMP WITH TIME ZONE;
+
+CREATE TABLE IF NOT EXISTS generated_documents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tool_name TEXT NOT NULL,
+    requester_name TEXT NOT NULL,
+    requester_nik_hash TEXT,
+    filename TEXT NOT NULL,
+    delivered_via TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_generated_documents_created_at ON generated_documents(created_at);
+
+CREATE TABLE IF NOT EXISTS webhooks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    events TEXT[] NOT NULL DEFAULT '{}',
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS webhook_dead_letters (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    webhook_id UUID NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    event TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    error TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_dead_letters_created_at ON webhook_dead_letters(created_at);
@@ -1,8 +1,5 @@
 -- Initial seed data for the cakung-barat-server
 
--- Insert sample folders
-INSERT INTO folders (name) VALUES ('others');
-
 -- Insert sample assets (if needed)
 -- INSERT INTO assets (name, filename, url, description) VALUES 
 -- ('Sample Image', 'sample.jpg', '/assets/serve/sample.jpg', 'Sample asset for testing');
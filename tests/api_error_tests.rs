@@ -0,0 +1,173 @@
+//! Tests for ApiError rendering and handler error mapping
+
+mod common;
+
+use actix_web::{body::to_bytes, test, web, App, ResponseError};
+use cakung_barat_server::db::DbError;
+use cakung_barat_server::posting::multipart_parser::MultipartParseError;
+use cakung_barat_server::{asset, auth, posting, stats, ApiError};
+use serde_json::Value;
+
+async fn render(error: ApiError) -> (u16, Value) {
+    let response = error.error_response();
+    let status = response.status().as_u16();
+    let body = to_bytes(response.into_body()).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[actix_web::test]
+async fn test_status_codes_and_error_types() {
+    let cases = vec![
+        (ApiError::NotFound("Post not found".into()), 404, "NotFound"),
+        (ApiError::BadRequest("Bad".into()), 400, "BadRequest"),
+        (ApiError::Conflict("Exists".into()), 409, "Conflict"),
+        (
+            ApiError::Unauthorized("No token".into()),
+            401,
+            "Unauthorized",
+        ),
+        (
+            ApiError::Storage("Failed to upload file".into()),
+            500,
+            "InternalServerError",
+        ),
+        (ApiError::Internal("IO".into()), 500, "InternalServerError"),
+    ];
+
+    for (error, status, error_type) in cases {
+        let message = error.to_string();
+        let (actual_status, body) = render(error).await;
+        assert_eq!(actual_status, status);
+        assert_eq!(body["error"], error_type);
+        assert_eq!(body["message"], message);
+        assert!(body["timestamp"].is_string());
+    }
+}
+
+#[actix_web::test]
+async fn test_database_error_hides_source() {
+    let error = ApiError::database("Failed to retrieve posts")(sqlx::Error::RowNotFound);
+    let (status, body) = render(error).await;
+
+    assert_eq!(status, 500);
+    assert_eq!(body["error"], "InternalServerError");
+    assert_eq!(body["message"], "Failed to retrieve posts");
+
+    let (status, body) = render(ApiError::from(sqlx::Error::PoolTimedOut)).await;
+    assert_eq!(status, 500);
+    assert_eq!(body["message"], "Database error");
+}
+
+#[actix_web::test]
+async fn test_db_error_classification() {
+    let unreachable = DbError::from(sqlx::Error::PoolTimedOut);
+    assert!(unreachable.is_connection());
+    assert!(!unreachable.is_unique_violation() && !unreachable.is_fk_violation());
+
+    let missing = DbError::from(sqlx::Error::RowNotFound);
+    assert!(!missing.is_connection());
+    assert_eq!(missing.constraint(), None);
+
+    // Errors that are not constraint violations stay server errors
+    let (status, _) = render(ApiError::database("Failed to list posts")(unreachable)).await;
+    assert_eq!(status, 500);
+}
+
+#[actix_web::test]
+async fn test_conversions() {
+    let (status, body) = render(ApiError::from("bucket unavailable".to_string())).await;
+    assert_eq!(status, 500);
+    assert_eq!(body["message"], "bucket unavailable");
+
+    let (status, body) = render(ApiError::from(MultipartParseError::MetadataError(
+        "Missing file".into(),
+    )))
+    .await;
+    assert_eq!(status, 400);
+    assert_eq!(body["message"], "Missing file");
+
+    let (status, _) = render(ApiError::from(MultipartParseError::IoError("disk".into()))).await;
+    assert_eq!(status, 500);
+}
+
+#[actix_web::test]
+async fn test_handlers_map_database_failures() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::test_state()))
+            .route(
+                "/api/postings/{id}",
+                web::get().to(posting::handlers::get_posting_by_id),
+            )
+            .route(
+                "/api/assets/{id}",
+                web::get().to(asset::handlers::get_asset_by_id),
+            )
+            .route(
+                "/assets/serve/{filename:.*}",
+                web::get().to(asset::handlers::serve_asset),
+            )
+            .route(
+                "/api/auth/status",
+                web::get().to(auth::handlers::get_auth_status),
+            ),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/postings/{}", uuid::Uuid::new_v4()))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 500);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "InternalServerError");
+    assert_eq!(body["message"], "Failed to retrieve post");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/assets/{}", uuid::Uuid::new_v4()))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 500);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["message"], "Failed to retrieve asset");
+
+    // serve_asset keeps answering 404 when the lookup fails
+    let req = test::TestRequest::get()
+        .uri("/assets/serve/missing.png")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "NotFound");
+
+    // A failed admin count is an error, not "setup required"
+    let req = test::TestRequest::get()
+        .uri("/api/auth/status")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 500);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["message"], "Failed to check admin accounts");
+}
+
+#[actix_web::test]
+async fn test_missing_token_renders_error_response() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::test_state()))
+            .route(
+                "/api/admin/stats",
+                web::get().to(stats::get_dashboard_stats),
+            ),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/admin/stats")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "Unauthorized");
+    assert_eq!(body["message"], "Missing authorization token");
+}
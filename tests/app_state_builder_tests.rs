@@ -0,0 +1,147 @@
+//! Tests for AppStateBuilder
+
+mod common;
+
+use cakung_barat_server::cache::Cached;
+use cakung_barat_server::organization::model::OrganizationMember;
+use cakung_barat_server::storage::{FolderContent, ObjectStorage};
+use cakung_barat_server::{AppState, AppStateBuilder};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+struct CountingStorage {
+    uploads: AtomicUsize,
+}
+
+#[async_trait::async_trait]
+impl ObjectStorage for CountingStorage {
+    async fn upload_file(&self, _filename: &str, _file_data: &[u8]) -> Result<(), String> {
+        self.uploads.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn download_file(&self, _filename: &str) -> Result<Vec<u8>, String> {
+        Ok(vec![])
+    }
+
+    async fn delete_file(&self, _filename: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn create_folder(&self, _folder_name: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn list_folder_contents(&self, _folder_name: &str) -> Result<Vec<FolderContent>, String> {
+        Ok(vec![])
+    }
+
+    fn get_asset_url(&self, _filename: &str) -> String {
+        "http://mock-url".to_string()
+    }
+}
+
+fn storage() -> Arc<CountingStorage> {
+    Arc::new(CountingStorage {
+        uploads: AtomicUsize::new(0),
+    })
+}
+
+fn member(id: i32) -> OrganizationMember {
+    OrganizationMember {
+        id,
+        name: Some(format!("Member {}", id)),
+        position: "Staf".to_string(),
+        photo: None,
+        parent_id: None,
+        level: 1,
+        role: "staf".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_build_requires_pool_and_storage() {
+    assert!(AppStateBuilder::new()
+        .with_storage(storage())
+        .build()
+        .is_err());
+    assert!(AppStateBuilder::new()
+        .with_pool(common::unreachable_pool())
+        .build()
+        .is_err());
+    assert!(AppState::builder()
+        .with_pool(common::unreachable_pool())
+        .with_storage(storage())
+        .build()
+        .is_ok());
+}
+
+#[tokio::test]
+async fn test_without_persistence_worker_nothing_is_written() {
+    let storage = storage();
+    let state = AppStateBuilder::new()
+        .with_pool(common::unreachable_pool())
+        .with_storage(storage.clone())
+        .with_persistence(false)
+        .build()
+        .unwrap();
+
+    // No worker holds the receiving end
+    assert!(state
+        .organization_persist_sender
+        .send(vec![member(1)])
+        .await
+        .is_err());
+
+    state.shutdown().await;
+    assert_eq!(storage.uploads.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_with_persistence_worker_flushes_on_shutdown() {
+    let storage = storage();
+    let state = AppStateBuilder::new()
+        .with_pool(common::unreachable_pool())
+        .with_storage(storage.clone())
+        .build()
+        .unwrap();
+
+    state
+        .organization_persist_sender
+        .send(vec![member(1)])
+        .await
+        .unwrap();
+    state.shutdown().await;
+
+    assert_eq!(storage.uploads.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_cache_ttl_applies_to_both_caches() {
+    let state = AppStateBuilder::new()
+        .with_pool(common::unreachable_pool())
+        .with_storage(storage())
+        .with_persistence(false)
+        .with_cache_ttl(Duration::from_millis(50))
+        .build()
+        .unwrap();
+
+    state
+        .post_cache
+        .insert("all_posts".to_string(), Cached::new(vec![]))
+        .await;
+    state
+        .organization_cache
+        .insert(
+            "org_members".to_string(),
+            Cached::new(vec![member(1)].into()),
+        )
+        .await;
+    assert!(state.post_cache.get("all_posts").await.is_some());
+
+    tokio::time::sleep(Duration::from_millis(120)).await;
+
+    assert!(state.post_cache.get("all_posts").await.is_none());
+    assert!(state.organization_cache.get("org_members").await.is_none());
+}
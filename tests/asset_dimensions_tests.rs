@@ -0,0 +1,125 @@
+//! Tests for width and height of image assets
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::asset::dimensions::{image_dimensions, HEADER_BYTES};
+use cakung_barat_server::asset::handlers::backfill_asset_dimensions;
+use cakung_barat_server::asset::models::Asset;
+use cakung_barat_server::storage::{LocalStorage, ObjectStorage, SupabaseConfig, SupabaseStorage};
+use std::sync::Arc;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// 3x5 pixels
+const PNG: &[u8] = include_bytes!("fixtures/no_exif.png");
+/// 4x2 pixels, with an EXIF block in front of the frame header
+const JPEG: &[u8] = include_bytes!("fixtures/orientation_6_gps.jpg");
+
+fn asset(filename: &str) -> Asset {
+    Asset::new(
+        "Foto".to_string(),
+        filename.to_string(),
+        format!("/assets/serve/{}", filename),
+        None,
+    )
+}
+
+#[actix_web::test]
+async fn test_dimensions_come_from_the_header() {
+    assert_eq!(image_dimensions("peta.png", PNG), Some((3, 5)));
+    assert_eq!(image_dimensions("rumah.JPG", JPEG), Some((4, 2)));
+    assert_eq!(image_dimensions("peta.png", &PNG[..24]), Some((3, 5)));
+
+    // Not an image type, or not an image at all
+    assert_eq!(image_dimensions("peta.pdf", PNG), None);
+    assert_eq!(image_dimensions("rusak.png", b"bukan gambar"), None);
+    // The JPEG frame header comes after the EXIF block
+    assert_eq!(image_dimensions("rumah.jpg", &JPEG[..64]), None);
+}
+
+#[actix_web::test]
+async fn test_dimensions_appear_in_asset_json() {
+    let mut image = asset("abc_peta_png.png");
+    image.read_dimensions(PNG);
+    let json = serde_json::to_value(&image).unwrap();
+    assert_eq!(json["width"], 3);
+    assert_eq!(json["height"], 5);
+
+    let mut document = asset("abc_surat_pdf.pdf");
+    document.read_dimensions(b"%PDF-1.7");
+    let json = serde_json::to_value(&document).unwrap();
+    assert!(json["width"].is_null());
+    assert!(json["height"].is_null());
+}
+
+#[actix_web::test]
+async fn test_local_storage_reads_only_the_head() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = LocalStorage::new(dir.path());
+    storage.upload_file("rumah.jpg", JPEG).await.unwrap();
+
+    assert_eq!(
+        storage.download_head("rumah.jpg", 10).await.unwrap(),
+        &JPEG[..10]
+    );
+    assert_eq!(
+        storage
+            .download_head("rumah.jpg", HEADER_BYTES)
+            .await
+            .unwrap(),
+        JPEG
+    );
+    assert!(storage.download_head("hilang.jpg", 10).await.is_err());
+}
+
+#[actix_web::test]
+async fn test_supabase_reads_a_byte_range() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/storage/v1/object/bucket/posts/peta.png"))
+        .and(header("range", "bytes=0-23"))
+        .respond_with(ResponseTemplate::new(206).set_body_bytes(&PNG[..24]))
+        .expect(1)
+        .mount(&server)
+        .await;
+    // A server ignoring the range sends the whole object
+    Mock::given(method("GET"))
+        .and(path("/storage/v1/object/bucket/posts/besar.png"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(PNG))
+        .mount(&server)
+        .await;
+    let storage = SupabaseStorage::new(
+        SupabaseConfig {
+            supabase_url: server.uri(),
+            supabase_anon_key: "anon-key".to_string(),
+            bucket_name: "bucket".to_string(),
+            service_role_key: Some("service-key".to_string()),
+            bucket_public: true,
+        },
+        reqwest::Client::new(),
+    );
+
+    let head = storage.download_head("posts/peta.png", 24).await.unwrap();
+    assert_eq!(image_dimensions("peta.png", &head), Some((3, 5)));
+    let head = storage.download_head("posts/besar.png", 24).await.unwrap();
+    assert_eq!(head, &PNG[..24]);
+}
+
+#[actix_web::test]
+async fn test_backfill_requires_admin() {
+    let state = common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .build()
+        .unwrap();
+    let app = test::init_service(App::new().app_data(web::Data::new(state)).route(
+        "/api/admin/assets/backfill-dimensions",
+        web::post().to(backfill_asset_dimensions),
+    ))
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/admin/assets/backfill-dimensions")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+}
@@ -0,0 +1,90 @@
+//! Tests for the `Content-Disposition` of served assets
+
+use actix_web::http::header::{ContentDisposition, TryIntoHeaderValue};
+use cakung_barat_server::asset::download::{
+    content_disposition, download_filename, is_inline_by_default, ServeAssetQuery,
+};
+use cakung_barat_server::asset::models::Asset;
+
+fn asset(name: &str, filename: &str) -> Asset {
+    Asset::new(
+        name.to_string(),
+        filename.to_string(),
+        format!("/assets/serve/{}", filename),
+        None,
+    )
+}
+
+fn header(disposition: ContentDisposition) -> String {
+    disposition
+        .try_into_value()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn test_unicode_names_are_rfc5987_encoded() {
+    let surat = asset(
+        "Surat Keterangan Domisili – RW 05",
+        "0b6f6a1e-4c1d-4f43-9a56-2f7d1c1b9e10_surat_pdf.pdf",
+    );
+    let name = download_filename(&surat, None);
+    assert_eq!(name, "Surat Keterangan Domisili – RW 05.pdf");
+
+    assert_eq!(
+        header(content_disposition(false, &name)),
+        "attachment; filename=\"Surat Keterangan Domisili _ RW 05.pdf\"; \
+         filename*=UTF-8''Surat%20Keterangan%20Domisili%20%E2%80%93%20RW%2005.pdf"
+    );
+}
+
+#[test]
+fn test_ascii_names_have_no_extended_parameter() {
+    let name = download_filename(&asset("Laporan", "abc_laporan_docx.docx"), None);
+    assert_eq!(name, "Laporan.docx");
+    assert_eq!(
+        header(content_disposition(true, &name)),
+        "inline; filename=\"Laporan.docx\""
+    );
+}
+
+#[test]
+fn test_download_names() {
+    let foto = asset("foto.JPG", "abc_foto_jpg.jpg");
+    // The extension is not repeated, whatever its case
+    assert_eq!(download_filename(&foto, None), "foto.JPG");
+    assert_eq!(
+        download_filename(&foto, Some("Kerja Bakti")),
+        "Kerja Bakti.jpg"
+    );
+    // Path separators cannot leak into the saved name
+    assert_eq!(
+        download_filename(&foto, Some("../../etc/passwd")),
+        "....etcpasswd.jpg"
+    );
+    assert_eq!(download_filename(&foto, Some("  ")), "foto.JPG");
+}
+
+#[test]
+fn test_images_are_inline_by_default() {
+    assert!(is_inline_by_default("abc_foto_png.png"));
+    assert!(!is_inline_by_default("abc_surat_pdf.pdf"));
+    assert!(!is_inline_by_default("abc_laporan_docx.docx"));
+
+    let pdf = asset("Surat", "abc_surat_pdf.pdf");
+    let foto = asset("Foto", "abc_foto_png.png");
+    assert!(!ServeAssetQuery::default().inline(&pdf));
+    assert!(ServeAssetQuery::default().inline(&foto));
+    let download = ServeAssetQuery {
+        download: Some(true),
+        filename: None,
+    };
+    assert!(!download.inline(&foto));
+    let view = ServeAssetQuery {
+        download: Some(false),
+        filename: None,
+    };
+    assert!(view.inline(&pdf));
+}
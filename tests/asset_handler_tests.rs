@@ -28,4 +28,42 @@ mod asset_handler_tests {
         let request = GetAssetsByIdsRequest { ids: vec![] };
         assert_eq!(request.ids.len(), 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_folder_list_query_include() {
+        use actix_web::web::Query;
+        use cakung_barat_server::asset::handlers::{FolderInclude, FolderListQuery};
+
+        let query = Query::<FolderListQuery>::from_query("").unwrap();
+        assert_eq!(query.include.unwrap_or_default(), FolderInclude::Assets);
+        for (value, include) in [
+            ("assets", FolderInclude::Assets),
+            ("ids", FolderInclude::Ids),
+            ("count", FolderInclude::Count),
+        ] {
+            let query = Query::<FolderListQuery>::from_query(&format!("include={}", value)).unwrap();
+            assert_eq!(query.include, Some(include));
+        }
+        assert!(Query::<FolderListQuery>::from_query("include=names").is_err());
+        assert!(Query::<FolderListQuery>::from_query("include=COUNT").is_err());
+    }
+
+    #[test]
+    fn test_folder_listing_shapes() {
+        use cakung_barat_server::asset::handlers::{FolderAssetCount, FolderListing};
+
+        let id = Uuid::new_v4();
+        assert_eq!(
+            serde_json::to_value(FolderListing::Ids(vec![id])).unwrap(),
+            serde_json::json!([id])
+        );
+        assert_eq!(
+            serde_json::to_value(FolderListing::Count(FolderAssetCount {
+                name: "galeri".to_string(),
+                asset_count: 3,
+            }))
+            .unwrap(),
+            serde_json::json!({ "name": "galeri", "asset_count": 3 })
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! Tests for the relative `url` and absolute `public_url` of assets
+
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App};
+use cakung_barat_server::asset::handlers::relativize_asset_urls;
+use cakung_barat_server::asset::models::Asset;
+use cakung_barat_server::asset::public_url::{init_public_urls, PublicUrls};
+use cakung_barat_server::posting::models::Post;
+use cakung_barat_server::storage::LocalStorage;
+use serde_json::Value;
+use std::sync::Arc;
+
+fn new_asset() -> Asset {
+    Asset::new(
+        "Kerja Bakti".to_string(),
+        "abc_kerja_bakti.jpg".to_string(),
+        "/assets/serve/abc_kerja_bakti.jpg".to_string(),
+        None,
+    )
+}
+
+/// A row written before uploads stored relative paths
+fn legacy_asset() -> Asset {
+    Asset::new(
+        "Posyandu".to_string(),
+        "def_posyandu.png".to_string(),
+        "https://proyek.supabase.co/storage/v1/object/public/assets/def_posyandu.png".to_string(),
+        None,
+    )
+}
+
+#[actix_web::test]
+async fn test_relative_url_is_canonical() {
+    assert_eq!(
+        new_asset().relative_url(),
+        "/assets/serve/abc_kerja_bakti.jpg"
+    );
+    assert_eq!(
+        legacy_asset().relative_url(),
+        "/assets/serve/def_posyandu.png"
+    );
+}
+
+#[actix_web::test]
+async fn test_public_url_sources() {
+    let storage = Arc::new(LocalStorage::new(std::env::temp_dir()));
+
+    let base = PublicUrls::from_config(Some("https://cakungbarat.id"), false, storage.clone());
+    assert_eq!(
+        base.resolve(&new_asset()),
+        "https://cakungbarat.id/assets/serve/abc_kerja_bakti.jpg"
+    );
+    assert_eq!(
+        base.resolve(&legacy_asset()),
+        "https://cakungbarat.id/assets/serve/def_posyandu.png"
+    );
+
+    // The storage backend takes precedence when asked for
+    let from_storage =
+        PublicUrls::from_config(Some("https://cakungbarat.id"), true, storage.clone());
+    assert_eq!(
+        from_storage.resolve(&legacy_asset()),
+        "/assets/serve/def_posyandu.png"
+    );
+
+    let relative = PublicUrls::from_config(None, false, storage);
+    assert_eq!(
+        relative.resolve(&new_asset()),
+        "/assets/serve/abc_kerja_bakti.jpg"
+    );
+}
+
+#[actix_web::test]
+async fn test_responses_carry_both_urls() {
+    init_public_urls(PublicUrls::Base("https://cakungbarat.id".to_string()));
+
+    let json = serde_json::to_value(new_asset()).unwrap();
+    assert_eq!(json["url"], "/assets/serve/abc_kerja_bakti.jpg");
+    assert_eq!(
+        json["public_url"],
+        "https://cakungbarat.id/assets/serve/abc_kerja_bakti.jpg"
+    );
+    assert_eq!(json["filename"], "abc_kerja_bakti.jpg");
+    assert_eq!(
+        json["download_url"],
+        "https://cakungbarat.id/assets/serve/abc_kerja_bakti.jpg?download=true"
+    );
+
+    let json = serde_json::to_value(legacy_asset()).unwrap();
+    assert_eq!(json["url"], "/assets/serve/def_posyandu.png");
+    assert_eq!(
+        json["public_url"],
+        "https://cakungbarat.id/assets/serve/def_posyandu.png"
+    );
+
+    let mut post = Post::new(
+        "Kerja Bakti".to_string(),
+        "Kegiatan".to_string(),
+        "Kerja bakti RW 05".to_string(),
+        None,
+    );
+    assert_eq!(
+        serde_json::to_value(&post).unwrap()["cover_url"],
+        Value::Null
+    );
+    post.cover_filename = Some("abc_kerja_bakti.jpg".to_string());
+    let json = serde_json::to_value(&post).unwrap();
+    assert_eq!(
+        json["cover_url"],
+        "https://cakungbarat.id/assets/serve/abc_kerja_bakti.jpg"
+    );
+    assert!(json.get("cover_filename").is_none());
+}
+
+#[actix_web::test]
+async fn test_relativize_requires_admin() {
+    let app_state = common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .build()
+        .unwrap();
+    let app = test::init_service(App::new().app_data(web::Data::new(app_state)).route(
+        "/api/admin/assets/relativize-urls",
+        web::post().to(relativize_asset_urls),
+    ))
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/admin/assets/relativize-urls")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,164 @@
+//! Tests for uploading several files in one `POST /api/assets` request
+
+mod common;
+
+use actix_multipart::Multipart;
+use actix_web::http::{header, StatusCode};
+use actix_web::{test, web, App, HttpResponse};
+use cakung_barat_server::asset::handlers::{upload_asset, FailedUpload, UploadedFile};
+use cakung_barat_server::asset::models::Asset;
+use cakung_barat_server::config::UploadConfig;
+use cakung_barat_server::posting::multipart_parser::MultipartParser;
+use cakung_barat_server::storage::LocalStorage;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+const BOUNDARY: &str = "----cakung-barat-boundary";
+
+/// A multipart body with one part per `(name, filename, content)`
+fn multipart_body(parts: &[(&str, Option<&str>, &[u8])]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (name, filename, content) in parts {
+        body.extend_from_slice(format!("--{}\r\n", BOUNDARY).as_bytes());
+        let disposition = match filename {
+            Some(filename) => format!(
+                "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n\
+                 Content-Type: application/octet-stream\r\n",
+                name, filename
+            ),
+            None => format!("Content-Disposition: form-data; name=\"{}\"\r\n", name),
+        };
+        body.extend_from_slice(disposition.as_bytes());
+        body.extend_from_slice(b"\r\n");
+        body.extend_from_slice(content);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{}--\r\n", BOUNDARY).as_bytes());
+    body
+}
+
+fn upload(parts: &[(&str, Option<&str>, &[u8])]) -> test::TestRequest {
+    test::TestRequest::post()
+        .uri("/api/assets")
+        .insert_header((
+            header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={}", BOUNDARY),
+        ))
+        .set_payload(multipart_body(parts))
+}
+
+async fn parse(payload: Multipart) -> HttpResponse {
+    match MultipartParser::parse_asset_multipart(payload).await {
+        Ok(parsed) => HttpResponse::Ok().json(json!({
+            "files": parsed
+                .files
+                .iter()
+                .map(|(data, filename)| json!({ "filename": filename, "size": data.len() }))
+                .collect::<Vec<_>>(),
+            "name": parsed.name,
+            "folders": parsed.folders,
+        })),
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+#[actix_web::test]
+async fn test_parser_keeps_every_file() {
+    let app = test::init_service(App::new().route("/api/assets", web::post().to(parse))).await;
+
+    let parsed: Value = test::call_and_read_body_json(
+        &app,
+        upload(&[
+            ("file", Some("satu.png"), b"1111"),
+            ("folders", None, b"kegiatan, rapat"),
+            ("file", Some("dua.jpg"), b"22"),
+            ("file", Some("kosong.txt"), b""),
+            ("file", Some("tiga.pdf"), b"333"),
+        ])
+        .to_request(),
+    )
+    .await;
+    assert_eq!(
+        parsed["files"],
+        json!([
+            { "filename": "satu.png", "size": 4 },
+            { "filename": "dua.jpg", "size": 2 },
+            { "filename": "tiga.pdf", "size": 3 },
+        ])
+    );
+    assert_eq!(parsed["folders"], json!(["kegiatan", "rapat"]));
+
+    let resp = test::call_service(
+        &app,
+        upload(&[("name", None, b"Tanpa berkas")]).to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_every_file_of_an_upload_is_stored() {
+    let dir = tempfile::tempdir().unwrap();
+    // Nothing listens on port 1, so every asset insert fails quickly
+    let app_state = common::state_builder(Arc::new(LocalStorage::new(dir.path().to_path_buf())))
+        .with_upload_config(UploadConfig {
+            max_file_size: Some(8),
+            ..UploadConfig::default()
+        })
+        .build()
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state))
+            .route("/api/assets", web::post().to(upload_asset)),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        upload(&[
+            ("file", Some("satu.png"), b"1111"),
+            ("file", Some("terlalu-besar.jpg"), b"0123456789"),
+            ("file", Some("tiga.pdf"), b"333"),
+        ])
+        .to_request(),
+    )
+    .await;
+    // No file could be recorded, so the first file's error is returned
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["message"], "Failed to save asset");
+
+    // The files within the size limit still reached storage
+    let mut stored: Vec<String> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    stored.sort();
+    assert_eq!(stored.len(), 2, "{:?}", stored);
+    assert!(stored.iter().any(|name| name.ends_with("_satu_png.png")));
+    assert!(stored.iter().any(|name| name.ends_with("_tiga_pdf.pdf")));
+}
+
+#[actix_web::test]
+async fn test_upload_results_serialize_per_file() {
+    let asset = Asset::new(
+        "satu.png".to_string(),
+        "abc_satu_png.png".to_string(),
+        "/assets/serve/abc_satu_png.png".to_string(),
+        None,
+    );
+    let results = serde_json::to_value(vec![
+        UploadedFile::Uploaded(asset.clone()),
+        UploadedFile::Failed(FailedUpload {
+            filename: "dua.jpg".to_string(),
+            error: "File 'dua.jpg' is 10 bytes, the limit is 8 bytes".to_string(),
+        }),
+    ])
+    .unwrap();
+
+    // Successful entries keep the shape of a single-file response
+    assert_eq!(results[0], serde_json::to_value(&asset).unwrap());
+    assert_eq!(results[1]["filename"], "dua.jpg");
+    assert!(results[1]["error"].as_str().unwrap().contains("limit"));
+}
@@ -0,0 +1,195 @@
+//! Tests for the backup archive format and the backup endpoints
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::asset::models::Asset;
+use cakung_barat_server::backup::archive::{read_archive, read_files, ArchiveWriter};
+use cakung_barat_server::backup::{
+    handlers, BackupAdmin, BackupFolder, ContentSnapshot, ADMINS_FILE, ASSETS_FILE, FOLDERS_FILE,
+    MANIFEST_FILE, ORGANIZATION_FILE, POSTINGS_FILE, SCHEMA_VERSION,
+};
+use cakung_barat_server::organization::model::OrganizationMember;
+use cakung_barat_server::posting::models::Post;
+use cakung_barat_server::storage::LocalStorage;
+use std::io::Write;
+use std::sync::Arc;
+use uuid::Uuid;
+
+fn snapshot() -> ContentSnapshot {
+    let asset = Asset::new(
+        "Foto kegiatan".to_string(),
+        "foto.jpg".to_string(),
+        "/assets/serve/foto.jpg".to_string(),
+        None,
+    );
+    let folder = format!("posts/{}", Uuid::new_v4());
+    ContentSnapshot {
+        postings: vec![Post::new(
+            "Kerja bakti".to_string(),
+            "Kegiatan".to_string(),
+            "Kerja bakti hari Minggu".to_string(),
+            Some(folder.clone()),
+        )],
+        folders: vec![BackupFolder {
+            id: Uuid::new_v4(),
+            name: folder,
+            created_at: None,
+            asset_ids: vec![asset.id],
+        }],
+        assets: vec![asset],
+        organization: vec![OrganizationMember {
+            id: 1,
+            name: Some("Budi".to_string()),
+            position: "Lurah".to_string(),
+            photo: None,
+            parent_id: None,
+            level: 1,
+            role: "lurah".to_string(),
+        }],
+        admins: vec![BackupAdmin {
+            id: Uuid::new_v4(),
+            username: "admin".to_string(),
+            display_name: None,
+            created_at: None,
+            updated_at: None,
+            created_by: None,
+        }],
+    }
+}
+
+/// Archive with the JSON files of `snapshot`, `files` and, unless
+/// `manifest` is None, a manifest
+fn write_archive(
+    snapshot: &ContentSnapshot,
+    files: &[(&str, &[u8])],
+    manifest: Option<serde_json::Value>,
+) -> tempfile::NamedTempFile {
+    let mut archive = ArchiveWriter::default();
+    let mut out = Vec::new();
+    out.extend(
+        archive
+            .append_json(POSTINGS_FILE, &snapshot.postings)
+            .unwrap(),
+    );
+    out.extend(archive.append_json(ASSETS_FILE, &snapshot.assets).unwrap());
+    out.extend(
+        archive
+            .append_json(FOLDERS_FILE, &snapshot.folders)
+            .unwrap(),
+    );
+    out.extend(
+        archive
+            .append_json(ORGANIZATION_FILE, &snapshot.organization)
+            .unwrap(),
+    );
+    out.extend(archive.append_json(ADMINS_FILE, &snapshot.admins).unwrap());
+    for (path, data) in files {
+        out.extend(archive.append(path, data).unwrap());
+    }
+    if let Some(manifest) = manifest {
+        out.extend(archive.append_json(MANIFEST_FILE, &manifest).unwrap());
+    }
+    out.extend(archive.finish().unwrap());
+
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(&out).unwrap();
+    file
+}
+
+#[actix_web::test]
+async fn test_archive_round_trip() {
+    let snapshot = snapshot();
+    let manifest = serde_json::to_value(snapshot.manifest(1)).unwrap();
+    let file = write_archive(
+        &snapshot,
+        &[("files/foto.jpg", b"jpeg bytes")],
+        Some(manifest),
+    );
+
+    let contents = read_archive(file.path()).unwrap();
+    assert_eq!(contents.manifest.schema_version, SCHEMA_VERSION);
+    assert_eq!(contents.manifest.postings, 1);
+    assert_eq!(contents.manifest.files, 1);
+    assert_eq!(contents.snapshot.postings[0].id, snapshot.postings[0].id);
+    assert_eq!(
+        contents.snapshot.postings[0].folder_id,
+        snapshot.postings[0].folder_id
+    );
+    assert_eq!(contents.snapshot.assets[0].id, snapshot.assets[0].id);
+    assert_eq!(contents.snapshot.assets[0].url, "/assets/serve/foto.jpg");
+    assert_eq!(contents.snapshot.folders, snapshot.folders);
+    assert_eq!(contents.snapshot.organization[0].position, "Lurah");
+    assert_eq!(contents.snapshot.admins, snapshot.admins);
+    assert_eq!(contents.files, vec!["foto.jpg"]);
+
+    let mut files = Vec::new();
+    read_files(file.path(), |name, data| {
+        files.push((name, data));
+        true
+    })
+    .unwrap();
+    assert_eq!(
+        files,
+        vec![("foto.jpg".to_string(), b"jpeg bytes".to_vec())]
+    );
+}
+
+#[actix_web::test]
+async fn test_incomplete_or_newer_archives_are_refused() {
+    let snapshot = snapshot();
+    let file = write_archive(&snapshot, &[], None);
+    let error = read_archive(file.path()).unwrap_err();
+    assert!(error.contains(MANIFEST_FILE), "{}", error);
+
+    let mut manifest = serde_json::to_value(snapshot.manifest(0)).unwrap();
+    manifest["schema_version"] = (SCHEMA_VERSION + 1).into();
+    let file = write_archive(&snapshot, &[], Some(manifest));
+    let error = read_archive(file.path()).unwrap_err();
+    assert!(error.contains("newer"), "{}", error);
+
+    let mut not_gzip = tempfile::NamedTempFile::new().unwrap();
+    not_gzip.write_all(b"{}").unwrap();
+    assert!(read_archive(not_gzip.path()).is_err());
+}
+
+#[actix_web::test]
+async fn test_file_names_must_stay_in_storage() {
+    // `tar` refuses to write such a name, so the header is filled in by hand
+    let name = b"files/posts/../../etc/passwd";
+    let mut header = tar::Header::new_old();
+    header.as_old_mut().name[..name.len()].copy_from_slice(name);
+    header.set_size(1);
+    header.set_mode(0o644);
+    header.set_cksum();
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        Vec::new(),
+        flate2::Compression::default(),
+    ));
+    builder.append(&header, &b"x"[..]).unwrap();
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(&builder.into_inner().unwrap().finish().unwrap())
+        .unwrap();
+
+    let error = read_archive(file.path()).unwrap_err();
+    assert!(error.contains("Invalid file name"), "{}", error);
+}
+
+#[actix_web::test]
+async fn test_backup_endpoints_require_admin() {
+    let state = common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .build()
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(web::scope("/api").configure(handlers::config)),
+    )
+    .await;
+
+    for uri in ["/api/admin/backup", "/api/admin/restore?dry_run=true"] {
+        let req = test::TestRequest::post().uri(uri).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 401, "{}", uri);
+    }
+}
@@ -0,0 +1,260 @@
+//! Tests for cache hit/miss metrics and the cache admin endpoints
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::auth::generate_access_token;
+use cakung_barat_server::cache::{self, CacheKind, CacheMetrics, CacheTarget, Cached};
+use cakung_barat_server::organization::model::OrganizationMember;
+use cakung_barat_server::storage::{FolderContent, ObjectStorage};
+use cakung_barat_server::AppState;
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Storage serving a fixed organization.json and counting downloads
+struct OrganizationStorage {
+    downloads: AtomicUsize,
+}
+
+#[async_trait::async_trait]
+impl ObjectStorage for OrganizationStorage {
+    async fn upload_file(&self, _filename: &str, _file_data: &[u8]) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn download_file(&self, _filename: &str) -> Result<Vec<u8>, String> {
+        self.downloads.fetch_add(1, Ordering::SeqCst);
+        let members = vec![OrganizationMember {
+            id: 1,
+            name: Some("Lurah".to_string()),
+            position: "Lurah".to_string(),
+            photo: None,
+            parent_id: None,
+            level: 1,
+            role: "lurah".to_string(),
+        }];
+        Ok(serde_json::to_vec(&members).unwrap())
+    }
+
+    async fn delete_file(&self, _filename: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn create_folder(&self, _folder_name: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn list_folder_contents(&self, _folder_name: &str) -> Result<Vec<FolderContent>, String> {
+        Ok(vec![])
+    }
+
+    fn get_asset_url(&self, _filename: &str) -> String {
+        "http://mock-url".to_string()
+    }
+}
+
+async fn test_state() -> (AppState, Arc<OrganizationStorage>) {
+    let storage = Arc::new(OrganizationStorage {
+        downloads: AtomicUsize::new(0),
+    });
+    let state = common::state_builder(storage.clone()).build().unwrap();
+    (state, storage)
+}
+
+#[tokio::test]
+async fn test_organization_cache_counts_miss_then_hit() {
+    let (state, storage) = test_state().await;
+
+    state.get_organization_structure().await.unwrap();
+    assert_eq!(state.cache_metrics.misses(CacheKind::Organization), 1);
+    assert_eq!(state.cache_metrics.hits(CacheKind::Organization), 0);
+    assert_eq!(state.cache_metrics.entries(CacheKind::Organization), 1);
+
+    state.get_organization_structure().await.unwrap();
+    state.get_organization_structure().await.unwrap();
+    assert_eq!(state.cache_metrics.misses(CacheKind::Organization), 1);
+    assert_eq!(state.cache_metrics.hits(CacheKind::Organization), 2);
+    assert_eq!(storage.downloads.load(Ordering::SeqCst), 1);
+
+    // Posts counters are tracked separately
+    assert_eq!(state.cache_metrics.hits(CacheKind::Posts), 0);
+}
+
+#[tokio::test]
+async fn test_post_cache_counts_hit_and_miss() {
+    let (state, _) = test_state().await;
+
+    // Miss is recorded before the database is queried, even if the query fails
+    assert!(state.get_all_posts_cached().await.is_err());
+    assert_eq!(state.cache_metrics.misses(CacheKind::Posts), 1);
+    assert_eq!(state.cache_metrics.hits(CacheKind::Posts), 0);
+
+    state
+        .post_cache
+        .insert("all_posts".to_string(), Cached::new(vec![]))
+        .await;
+    assert!(state.get_all_posts_cached().await.unwrap().is_empty());
+    assert_eq!(state.cache_metrics.hits(CacheKind::Posts), 1);
+    assert_eq!(state.cache_metrics.misses(CacheKind::Posts), 1);
+}
+
+#[tokio::test]
+async fn test_clear_cache_forces_reload() {
+    let (state, storage) = test_state().await;
+
+    state.get_organization_structure().await.unwrap();
+    state.clear_cache(CacheTarget::Posts).await;
+    state.get_organization_structure().await.unwrap();
+    assert_eq!(storage.downloads.load(Ordering::SeqCst), 1);
+
+    state.clear_cache(CacheTarget::Organization).await;
+    assert_eq!(state.cache_metrics.entries(CacheKind::Organization), 0);
+    state.get_organization_structure().await.unwrap();
+    assert_eq!(storage.downloads.load(Ordering::SeqCst), 2);
+    assert_eq!(state.cache_metrics.misses(CacheKind::Organization), 2);
+}
+
+#[actix_web::test]
+async fn test_metrics_are_exported_by_registry() {
+    let metrics = CacheMetrics::new();
+    let registry = prometheus::Registry::new();
+    metrics.register(&registry).unwrap();
+
+    metrics.record_hit(CacheKind::Posts);
+    metrics.record_miss(CacheKind::Organization);
+    metrics.set_entries(CacheKind::Posts, 3);
+
+    let names: Vec<String> = registry
+        .gather()
+        .iter()
+        .map(|family| family.get_name().to_string())
+        .collect();
+    assert!(names.contains(&"cakung_barat_server_cache_hits_total".to_string()));
+    assert!(names.contains(&"cakung_barat_server_cache_misses_total".to_string()));
+    assert!(names.contains(&"cakung_barat_server_cache_entries".to_string()));
+}
+
+#[actix_web::test]
+async fn test_cache_admin_endpoints() {
+    let (state, _) = test_state().await;
+    let state = web::Data::new(state);
+    state.get_organization_structure().await.unwrap();
+    state.get_organization_structure().await.unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .service(web::scope("/api").configure(cache::handlers::config)),
+    )
+    .await;
+
+    // Requires a token
+    let req = test::TestRequest::get()
+        .uri("/api/admin/cache/stats")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+
+    let token = generate_access_token("admin-id", "admin").unwrap();
+    let auth = ("Authorization", format!("Bearer {}", token));
+
+    let req = test::TestRequest::get()
+        .uri("/api/admin/cache/stats")
+        .insert_header(auth.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["organization"]["hits"], 1);
+    assert_eq!(body["organization"]["misses"], 1);
+    assert_eq!(body["organization"]["entries"], 1);
+    assert_eq!(body["posts"]["entries"], 0);
+
+    let req = test::TestRequest::post()
+        .uri("/api/admin/cache/clear?cache=organization")
+        .insert_header(auth.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["organization"]["entries"], 0);
+    // Counters survive a clear
+    assert_eq!(body["organization"]["hits"], 1);
+
+    let req = test::TestRequest::post()
+        .uri("/api/admin/cache/clear?cache=sessions")
+        .insert_header(auth)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_cache_entries_can_be_listed_and_removed() {
+    let (state, _) = test_state().await;
+    let state = web::Data::new(state);
+    state.get_organization_structure().await.unwrap();
+    for key in ["all_posts", "stale_posts"] {
+        state
+            .post_cache
+            .insert(key.to_string(), Cached::new(vec![]))
+            .await;
+    }
+
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .service(web::scope("/api").configure(cache::handlers::config)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/admin/cache/entries")
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 401);
+
+    let token = generate_access_token("admin-id", "admin").unwrap();
+    let auth = ("Authorization", format!("Bearer {}", token));
+    let list = |cache: &str| {
+        test::TestRequest::get()
+            .uri(&format!("/api/admin/cache/entries?cache={}", cache))
+            .insert_header(auth.clone())
+            .to_request()
+    };
+
+    let body: Value = test::call_and_read_body_json(&app, list("all")).await;
+    let keys: Vec<&str> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["key"].as_str().unwrap())
+        .collect();
+    assert_eq!(keys, vec!["all_posts", "stale_posts", "org_members"]);
+    let members = &body[2];
+    assert_eq!(members["cache"], "organization");
+    assert_eq!(members["items"], 1);
+    assert!(members["size_bytes"].as_u64().unwrap() > 2);
+    let remaining = members["ttl_remaining_secs"].as_u64().unwrap();
+    assert!(remaining > 0 && remaining <= 600, "{}", remaining);
+
+    // The key is only looked up in the selected cache
+    let req = test::TestRequest::delete()
+        .uri("/api/admin/cache/entries/stale_posts?cache=organization")
+        .insert_header(auth.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+
+    let req = test::TestRequest::delete()
+        .uri("/api/admin/cache/entries/stale_posts")
+        .insert_header(auth.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 204);
+
+    let body: Value = test::call_and_read_body_json(&app, list("posts")).await;
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    assert_eq!(body[0]["key"], "all_posts");
+    assert!(state.post_cache.get("stale_posts").await.is_none());
+    assert!(state.organization_cache.get("org_members").await.is_some());
+    assert_eq!(state.cache_metrics.entries(CacheKind::Posts), 1);
+}
@@ -0,0 +1,187 @@
+//! Tests for the admin change feed at /api/events
+
+mod common;
+
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::{test, web, App};
+use cakung_barat_server::events::{stream_changes, ChangeFeed, REPLAY_CAPACITY};
+use cakung_barat_server::storage::LocalStorage;
+use cakung_barat_server::webhook::WebhookEvent;
+use serde_json::{json, Value};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+fn state() -> web::Data<cakung_barat_server::db::AppState> {
+    web::Data::new(
+        common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+            .build()
+            .unwrap(),
+    )
+}
+
+fn bearer() -> (&'static str, String) {
+    let token = cakung_barat_server::auth::generate_access_token("admin-id", "admin").unwrap();
+    ("Authorization", format!("Bearer {}", token))
+}
+
+/// Next SSE frame from a streaming body, `None` when nothing arrives in time
+async fn next_frame(body: &mut BoxBody) -> Option<String> {
+    let chunk = tokio::time::timeout(
+        Duration::from_millis(200),
+        futures::future::poll_fn(|cx| Pin::new(&mut *body).poll_next(cx)),
+    )
+    .await
+    .ok()??;
+    Some(String::from_utf8(chunk.unwrap().to_vec()).unwrap())
+}
+
+fn field<'a>(frame: &'a str, name: &str) -> &'a str {
+    frame
+        .lines()
+        .find_map(|line| line.strip_prefix(name)?.strip_prefix(": "))
+        .unwrap()
+}
+
+#[actix_web::test]
+async fn test_replay_starts_after_last_event_id() {
+    let feed = ChangeFeed::default();
+    let ids: Vec<Uuid> = (0..REPLAY_CAPACITY + 5).map(|_| Uuid::new_v4()).collect();
+    for id in &ids {
+        feed.publish(WebhookEvent::PostUpdated, *id);
+    }
+
+    let (missed, _) = feed.subscribe(Some(100));
+    assert_eq!(
+        missed.iter().map(|e| e.seq).collect::<Vec<_>>(),
+        vec![101, 102, 103, 104, 105]
+    );
+    assert_eq!(missed[0].id, ids[100]);
+
+    // Only the last 100 are kept
+    let (missed, _) = feed.subscribe(Some(0));
+    assert_eq!(missed.len(), REPLAY_CAPACITY);
+    assert_eq!(missed[0].seq, 6);
+
+    let (missed, _) = feed.subscribe(None);
+    assert!(missed.is_empty());
+}
+
+#[actix_web::test]
+async fn test_changes_arrive_on_the_stream() {
+    let state = state();
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .route("/api/events", web::get().to(stream_changes)),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/api/events")
+            .insert_header(bearer())
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "text/event-stream"
+    );
+    let mut body = resp.into_body();
+    assert_eq!(next_frame(&mut body).await, None);
+
+    let post_id = Uuid::new_v4();
+    state.publish_change(
+        WebhookEvent::PostUpdated,
+        post_id,
+        &json!({ "id": post_id }),
+    );
+    let frame = next_frame(&mut body).await.unwrap();
+    assert_eq!(field(&frame, "id"), "1");
+    let data: Value = serde_json::from_str(field(&frame, "data")).unwrap();
+    assert_eq!(data, json!({ "type": "post.updated", "id": post_id }));
+}
+
+#[actix_web::test]
+async fn test_reconnect_replays_missed_changes() {
+    let state = state();
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .route("/api/events", web::get().to(stream_changes)),
+    )
+    .await;
+
+    let created = Uuid::new_v4();
+    let deleted = Uuid::new_v4();
+    state.changes.publish(WebhookEvent::PostCreated, created);
+    state.changes.publish(WebhookEvent::PostDeleted, deleted);
+
+    let mut body = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/api/events")
+            .insert_header(bearer())
+            .insert_header(("Last-Event-ID", "1"))
+            .to_request(),
+    )
+    .await
+    .into_body();
+    let frame = next_frame(&mut body).await.unwrap();
+    assert_eq!(field(&frame, "id"), "2");
+    let data: Value = serde_json::from_str(field(&frame, "data")).unwrap();
+    assert_eq!(data, json!({ "type": "post.deleted", "id": deleted }));
+    assert_eq!(next_frame(&mut body).await, None);
+}
+
+#[actix_web::test]
+async fn test_stream_requires_admin() {
+    let app = test::init_service(
+        App::new()
+            .app_data(state())
+            .route("/api/events", web::get().to(stream_changes)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/api/events").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+}
+
+#[actix_web::test]
+async fn test_changes_arrive_through_the_server_stack_with_compression_accepted() {
+    let state = state();
+    let config = cakung_barat_server::config::ServerConfig::from_lookup(|_| None).unwrap();
+    let app = test::init_service(
+        cakung_barat_server::middleware(&config)
+            .app_data(state.clone())
+            .configure(cakung_barat_server::routes),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/api/events")
+            .insert_header(bearer())
+            .insert_header(("Accept-Encoding", "br, gzip"))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("content-encoding").unwrap(), "identity");
+    let mut body = resp.into_body().boxed();
+    assert_eq!(next_frame(&mut body).await, None);
+
+    let post_id = Uuid::new_v4();
+    state.changes.publish(WebhookEvent::PostCreated, post_id);
+    let frame = next_frame(&mut body)
+        .await
+        .expect("change held back by the encoder");
+    let data: Value = serde_json::from_str(field(&frame, "data")).unwrap();
+    assert_eq!(data, json!({ "type": "post.created", "id": post_id }));
+}
@@ -0,0 +1,171 @@
+//! Tests for comment submission checks and the moderation state machine
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::comment::handlers::config;
+use cakung_barat_server::comment::{CommentConfig, CommentStatus};
+use cakung_barat_server::storage::LocalStorage;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+fn state(comments: CommentConfig) -> cakung_barat_server::db::AppState {
+    // Every request here is answered before the database is reached
+    common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .with_comment_config(comments)
+        .build()
+        .unwrap()
+}
+
+#[actix_web::test]
+async fn test_moderation_transitions() {
+    use CommentStatus::*;
+
+    for (from, to, allowed) in [
+        (Pending, Approved, true),
+        (Pending, Rejected, true),
+        (Approved, Rejected, true),
+        (Rejected, Approved, true),
+        (Approved, Approved, false),
+        (Rejected, Rejected, false),
+        (Approved, Pending, false),
+        (Rejected, Pending, false),
+        (Pending, Pending, false),
+    ] {
+        assert_eq!(from.can_become(to), allowed, "{:?} -> {:?}", from, to);
+    }
+    assert_eq!(serde_json::to_value(Approved).unwrap(), "approved");
+}
+
+#[actix_web::test]
+async fn test_comment_config_from_lookup() {
+    let config = CommentConfig::from_lookup(|_| None).unwrap();
+    assert_eq!(config, CommentConfig::default());
+
+    let config = CommentConfig::from_lookup(|key| match key {
+        "COMMENTS_PER_HOUR" => Some("2".to_string()),
+        "COMMENT_MAX_LENGTH" => Some(" 500 ".to_string()),
+        _ => None,
+    })
+    .unwrap();
+    assert_eq!(config.per_ip_per_hour, 2);
+    assert_eq!(config.max_length, 500);
+
+    let err =
+        CommentConfig::from_lookup(|key| (key == "COMMENTS_PER_HOUR").then(|| "0".to_string()))
+            .unwrap_err();
+    assert!(err.contains("COMMENTS_PER_HOUR"), "{}", err);
+}
+
+#[actix_web::test]
+async fn test_invalid_comments_are_rejected() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state(CommentConfig {
+                max_length: 10,
+                ..CommentConfig::default()
+            })))
+            .service(web::scope("/api").configure(config)),
+    )
+    .await;
+
+    let uri = format!("/api/postings/{}/comments", uuid::Uuid::new_v4());
+    for body in [
+        json!({ "author_name": " ", "body": "Halo" }),
+        json!({ "author_name": "Bu Siti", "body": "  " }),
+        json!({ "author_name": "Bu Siti", "body": "Sebelas huruf" }),
+        json!({ "author_name": "x".repeat(101), "body": "Halo" }),
+        json!({ "author_name": "Bu Siti", "contact": "0".repeat(201), "body": "Halo" }),
+    ] {
+        let req = test::TestRequest::post()
+            .uri(&uri)
+            .set_json(&body)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 400, "{}", body);
+    }
+}
+
+#[actix_web::test]
+async fn test_honeypot_and_rate_limit() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state(CommentConfig {
+                per_ip_per_hour: 2,
+                ..CommentConfig::default()
+            })))
+            .service(web::scope("/api").configure(config)),
+    )
+    .await;
+
+    let uri = format!("/api/postings/{}/comments", uuid::Uuid::new_v4());
+    let bot = json!({
+        "author_name": "Promo",
+        "body": "Murah sekali",
+        "website": "https://spam.example.com"
+    });
+    let submit = |ip: &'static str| {
+        test::TestRequest::post()
+            .uri(&uri)
+            .peer_addr(format!("{}:40000", ip).parse().unwrap())
+            .set_json(&bot)
+            .to_request()
+    };
+
+    // A filled-in honeypot looks accepted but is never stored
+    for _ in 0..2 {
+        let resp = test::call_service(&app, submit("203.0.113.7")).await;
+        assert_eq!(resp.status(), 202);
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["status"], "pending");
+    }
+
+    let resp = test::call_service(&app, submit("203.0.113.7")).await;
+    assert_eq!(resp.status(), 429);
+    let retry_after: u64 = resp
+        .headers()
+        .get("Retry-After")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((3590..=3600).contains(&retry_after), "{}", retry_after);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "TooManyRequests");
+
+    // Claiming another address through a header does not reset the quota
+    let spoofed = test::TestRequest::post()
+        .uri(&uri)
+        .peer_addr("203.0.113.7:40001".parse().unwrap())
+        .insert_header(("X-Forwarded-For", "198.51.100.2"))
+        .set_json(&bot)
+        .to_request();
+    let resp = test::call_service(&app, spoofed).await;
+    assert_eq!(resp.status(), 429);
+
+    // Other addresses have their own quota
+    let resp = test::call_service(&app, submit("198.51.100.2")).await;
+    assert_eq!(resp.status(), 202);
+}
+
+#[actix_web::test]
+async fn test_moderation_requires_a_token() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state(CommentConfig::default())))
+            .service(web::scope("/api").configure(config)),
+    )
+    .await;
+
+    let id = uuid::Uuid::new_v4();
+    for req in [
+        test::TestRequest::get().uri("/api/admin/comments"),
+        test::TestRequest::post().uri(&format!("/api/admin/comments/{}/approve", id)),
+        test::TestRequest::post().uri(&format!("/api/admin/comments/{}/reject", id)),
+        test::TestRequest::delete().uri(&format!("/api/admin/comments/{}", id)),
+    ] {
+        let resp = test::call_service(&app, req.to_request()).await;
+        assert_eq!(resp.status(), 401);
+    }
+}
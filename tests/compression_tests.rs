@@ -0,0 +1,191 @@
+//! Tests for the response compression policy
+
+use actix_web::body::MessageBody;
+use actix_web::http::header;
+use actix_web::middleware::{from_fn, Compress};
+use actix_web::{test, web, App, HttpResponse};
+use cakung_barat_server::compression::{
+    compression_policy, is_event_stream, is_precompressed, is_upload_route,
+};
+use cakung_barat_server::config::ServerConfig;
+use futures::{stream, StreamExt};
+use std::pin::Pin;
+use std::time::Duration;
+
+const JSON_BODY: &str = r#"{"message":"Kelurahan Cakung Barat","items":["a","b","c","a","b","c"]}"#;
+
+macro_rules! test_app {
+    () => {
+        test::init_service(
+            App::new()
+                .wrap(from_fn(compression_policy))
+                .wrap(Compress::default())
+                .route(
+                    "/json",
+                    web::get().to(|| async {
+                        HttpResponse::Ok()
+                            .content_type("application/json")
+                            .body(JSON_BODY)
+                    }),
+                )
+                .route(
+                    "/photo.jpg",
+                    web::get().to(|| async {
+                        HttpResponse::Ok()
+                            .content_type("image/jpeg")
+                            .body(vec![0xFFu8; 512])
+                    }),
+                )
+                .route(
+                    "/archive.zip",
+                    web::get().to(|| async {
+                        HttpResponse::Ok()
+                            .content_type("application/zip")
+                            .body(vec![0u8; 512])
+                    }),
+                )
+                .route(
+                    "/api/assets",
+                    web::post().to(|| async {
+                        HttpResponse::Created()
+                            .content_type("application/json")
+                            .body(JSON_BODY)
+                    }),
+                ),
+        )
+        .await
+    };
+}
+
+fn content_encoding<B>(resp: &actix_web::dev::ServiceResponse<B>) -> Option<String> {
+    resp.headers()
+        .get(header::CONTENT_ENCODING)
+        .map(|v| v.to_str().unwrap().to_string())
+}
+
+#[actix_web::test]
+async fn test_json_is_brotli_encoded_when_preferred() {
+    let app = test_app!();
+
+    let req = test::TestRequest::get()
+        .uri("/json")
+        .insert_header((header::ACCEPT_ENCODING, "br"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(content_encoding(&resp).as_deref(), Some("br"));
+
+    let body = test::read_body(resp).await;
+    assert_ne!(body.as_ref(), JSON_BODY.as_bytes());
+}
+
+#[actix_web::test]
+async fn test_brotli_wins_content_negotiation() {
+    let app = test_app!();
+
+    let req = test::TestRequest::get()
+        .uri("/json")
+        .insert_header((header::ACCEPT_ENCODING, "gzip;q=0.8, br"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(content_encoding(&resp).as_deref(), Some("br"));
+
+    let req = test::TestRequest::get()
+        .uri("/json")
+        .insert_header((header::ACCEPT_ENCODING, "gzip"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(content_encoding(&resp).as_deref(), Some("gzip"));
+}
+
+#[actix_web::test]
+async fn test_images_and_archives_are_not_recompressed() {
+    let app = test_app!();
+
+    for uri in ["/photo.jpg", "/archive.zip"] {
+        let req = test::TestRequest::get()
+            .uri(uri)
+            .insert_header((header::ACCEPT_ENCODING, "br, gzip"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let encoding = content_encoding(&resp);
+        assert!(
+            encoding.is_none() || encoding.as_deref() == Some("identity"),
+            "{} was encoded as {:?}",
+            uri,
+            encoding
+        );
+        assert_eq!(test::read_body(resp).await.len(), 512);
+    }
+}
+
+#[actix_web::test]
+async fn test_upload_route_is_exempt() {
+    let app = test_app!();
+
+    let req = test::TestRequest::post()
+        .uri("/api/assets")
+        .insert_header((header::ACCEPT_ENCODING, "br, gzip"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(content_encoding(&resp).as_deref(), Some("identity"));
+    assert_eq!(test::read_body(resp).await.as_ref(), JSON_BODY.as_bytes());
+}
+
+#[actix_web::test]
+async fn test_event_stream_frames_arrive_unencoded_through_the_server_stack() {
+    let config = ServerConfig::from_lookup(|_| None).unwrap();
+    let app = test::init_service(cakung_barat_server::middleware(&config).route(
+        "/stream",
+        web::get().to(|| async {
+            // One frame, then the stream stays open like a live feed
+            let frames = stream::once(async {
+                Ok::<_, actix_web::Error>(web::Bytes::from_static(b"data: first\n\n"))
+            })
+            .chain(stream::pending());
+            HttpResponse::Ok()
+                .content_type("text/event-stream")
+                .streaming(frames)
+        }),
+    ))
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/stream")
+        .insert_header((header::ACCEPT_ENCODING, "br"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(content_encoding(&resp).as_deref(), Some("identity"));
+
+    let mut body = resp.into_body();
+    let chunk = tokio::time::timeout(
+        Duration::from_millis(500),
+        futures::future::poll_fn(|cx| Pin::new(&mut body).poll_next(cx)),
+    )
+    .await
+    .expect("frame held back by the encoder")
+    .unwrap();
+    assert!(matches!(chunk, Ok(bytes) if bytes.as_ref() == b"data: first\n\n"));
+}
+
+#[actix_web::test]
+async fn test_policy_helpers() {
+    assert!(is_precompressed("image/png"));
+    assert!(is_precompressed("video/mp4"));
+    assert!(is_precompressed("application/zip"));
+    assert!(is_precompressed("Application/GZIP; charset=binary"));
+    assert!(!is_precompressed("image/svg+xml"));
+    assert!(!is_precompressed("application/json"));
+    assert!(!is_precompressed("text/html; charset=utf-8"));
+    assert!(is_event_stream("text/event-stream"));
+    assert!(is_event_stream("Text/Event-Stream; charset=utf-8"));
+    assert!(!is_event_stream("text/plain"));
+
+    use actix_web::http::Method;
+    assert!(is_upload_route(&Method::POST, "/api/assets"));
+    assert!(is_upload_route(
+        &Method::POST,
+        "/api/assets/posts/4f1c2b4e-2d5a-4c1e-9d6b-1f2e3a4b5c6d"
+    ));
+    assert!(!is_upload_route(&Method::GET, "/api/assets"));
+    assert!(!is_upload_route(&Method::POST, "/api/assets/by-ids"));
+}
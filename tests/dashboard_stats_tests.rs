@@ -0,0 +1,27 @@
+//! Tests for the admin dashboard statistics endpoint
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::stats;
+use cakung_barat_server::storage::LocalStorage;
+use std::sync::Arc;
+
+#[actix_web::test]
+async fn test_stats_require_admin() {
+    let state = common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .build()
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(web::scope("/api").configure(stats::config)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/admin/stats")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+}
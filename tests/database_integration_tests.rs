@@ -1,9 +1,11 @@
 #[cfg(test)]
 mod database_integration_tests {
     use cakung_barat_server::asset::models::Asset;
+    use cakung_barat_server::comment::CommentStatus;
     use cakung_barat_server::db::AppState;
     use cakung_barat_server::posting::models::{Post, PostWithAssets};
-    use cakung_barat_server::storage::ObjectStorage;
+    use cakung_barat_server::posting::translation::TranslationRequest;
+    use cakung_barat_server::storage::{LocalStorage, ObjectStorage};
     use chrono::NaiveDate;
     use sqlx::PgPool;
     use std::sync::Arc;
@@ -193,6 +195,8 @@ mod database_integration_tests {
             filename: "test_file.jpg".to_string(),
             url: "/assets/serve/test_file.jpg".to_string(),
             description: Some("Updated description".to_string()),
+            width: None,
+            height: None,
             created_at: test_asset.created_at,
             updated_at: Some(chrono::Utc::now()),
         };
@@ -235,6 +239,12 @@ mod database_integration_tests {
             folder_id: Some("test_folder".to_string()),
             created_at: Some(chrono::Utc::now()),
             updated_at: Some(chrono::Utc::now()),
+            cover_asset_id: None,
+            cover_filename: None,
+            translations_available: Vec::new(),
+            comment_count: 0,
+            assets: None,
+            display: None,
         };
 
         // Test CREATE (Insert)
@@ -260,6 +270,12 @@ mod database_integration_tests {
             folder_id: Some("updated_folder".to_string()),
             created_at: test_post.created_at,
             updated_at: Some(chrono::Utc::now()),
+            cover_asset_id: None,
+            cover_filename: None,
+            translations_available: Vec::new(),
+            comment_count: 0,
+            assets: None,
+            display: None,
         };
 
         let update_result = app_state.update_post(&updated_post).await;
@@ -362,6 +378,12 @@ mod database_integration_tests {
             folder_id: Some(format!("posts/{}", Uuid::new_v4())),
             created_at: Some(chrono::Utc::now()),
             updated_at: Some(chrono::Utc::now()),
+            cover_asset_id: None,
+            cover_filename: None,
+            translations_available: Vec::new(),
+            comment_count: 0,
+            assets: None,
+            display: None,
         };
 
         app_state.insert_post(&test_post).await.unwrap();
@@ -377,6 +399,10 @@ mod database_integration_tests {
             created_at: test_post.created_at,
             updated_at: test_post.updated_at,
             asset_ids: vec![asset1.id, asset2.id],
+            cover_asset_id: None,
+            cover_filename: None,
+            translations_available: Vec::new(),
+            comment_count: 0,
         };
 
         // Test upsert with assets
@@ -437,6 +463,12 @@ mod database_integration_tests {
             folder_id: Some(format!("batch_folder_1_{}", Uuid::new_v4())),
             created_at: Some(chrono::Utc::now()),
             updated_at: Some(chrono::Utc::now()),
+            cover_asset_id: None,
+            cover_filename: None,
+            translations_available: Vec::new(),
+            comment_count: 0,
+            assets: None,
+            display: None,
         };
 
         let post2 = Post {
@@ -448,6 +480,12 @@ mod database_integration_tests {
             folder_id: Some(format!("batch_folder_2_{}", Uuid::new_v4())),
             created_at: Some(chrono::Utc::now()),
             updated_at: Some(chrono::Utc::now()),
+            cover_asset_id: None,
+            cover_filename: None,
+            translations_available: Vec::new(),
+            comment_count: 0,
+            assets: None,
+            display: None,
         };
 
         // Insert posts
@@ -469,4 +507,2052 @@ mod database_integration_tests {
         // Cleanup test data
         cleanup_test_data(&pool).await;
     }
+
+    #[actix_web::test]
+    async fn test_deleting_asset_with_missing_file_removes_row() {
+        use actix_web::{test, web, App};
+        use cakung_barat_server::asset::handlers;
+
+        let pool = setup_test_db().await;
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Arc::new(LocalStorage::new(dir.path()));
+        let app_state = AppState::new_with_pool_and_storage(pool.clone(), storage)
+            .await
+            .unwrap();
+        // The row exists, the file was never written
+        let filename = format!("{}_hilang.jpg", Uuid::new_v4());
+        let asset = Asset::new(
+            "Foto hilang".to_string(),
+            filename.clone(),
+            format!("/assets/serve/{}", filename),
+            None,
+        );
+        app_state.insert_asset(&asset).await.unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(app_state.clone()))
+                .route("/api/assets/{id}", web::delete().to(handlers::delete_asset)),
+        )
+        .await;
+
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::delete()
+                .uri(&format!("/api/assets/{}", asset.id))
+                .to_request(),
+        )
+        .await;
+
+        assert_eq!(resp.status(), actix_web::http::StatusCode::NO_CONTENT);
+        assert!(app_state
+            .get_asset_by_id(&asset.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_deleting_post_keeps_shared_folder_it_points_at() {
+        use actix_web::{test, web, App};
+        use cakung_barat_server::asset::folder_name::post_folder_name;
+        use cakung_barat_server::posting::handlers;
+
+        let pool = setup_test_db().await;
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Arc::new(LocalStorage::new(dir.path()));
+        let app_state = AppState::new_with_pool_and_storage(pool.clone(), storage.clone())
+            .await
+            .unwrap();
+
+        let stored_asset = |name: &str| {
+            let filename = format!("{}_{}.jpg", Uuid::new_v4(), name);
+            Asset::new(
+                name.to_string(),
+                filename.clone(),
+                format!("/assets/serve/{}", filename),
+                None,
+            )
+        };
+        // A post edited to point at the shared banner folder
+        let banner = stored_asset("banner");
+        storage.upload_file(&banner.filename, b"banner").await.unwrap();
+        app_state.insert_asset(&banner).await.unwrap();
+        app_state
+            .insert_folder_contents("banner", &vec![banner.id])
+            .await
+            .unwrap();
+        let borrowing = Post::new(
+            "Pengumuman".to_string(),
+            "Umum".to_string(),
+            "Memakai folder banner".to_string(),
+            Some("banner".to_string()),
+        );
+        app_state.insert_post(&borrowing).await.unwrap();
+        // A post with an attachment in its own folder
+        let photo = stored_asset("foto");
+        storage.upload_file(&photo.filename, b"foto").await.unwrap();
+        app_state.insert_asset(&photo).await.unwrap();
+        let mut owning = Post::new(
+            "Kerja bakti".to_string(),
+            "Kegiatan".to_string(),
+            "Kerja bakti RW 05".to_string(),
+            None,
+        );
+        owning.folder_id = Some(post_folder_name(owning.id));
+        app_state.insert_post(&owning).await.unwrap();
+        app_state
+            .insert_folder_contents(owning.folder_id.as_deref().unwrap(), &vec![photo.id])
+            .await
+            .unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(app_state.clone()))
+                .route(
+                    "/api/postings/{id}",
+                    web::delete().to(handlers::delete_posting),
+                ),
+        )
+        .await;
+        for post in [&borrowing, &owning] {
+            let resp = test::call_service(
+                &app,
+                test::TestRequest::delete()
+                    .uri(&format!("/api/postings/{}", post.id))
+                    .to_request(),
+            )
+            .await;
+            assert_eq!(resp.status(), actix_web::http::StatusCode::NO_CONTENT);
+        }
+
+        assert!(app_state.get_asset_by_id(&banner.id).await.unwrap().is_some());
+        assert!(storage.exists(&banner.filename).await.unwrap());
+        assert_eq!(
+            app_state.get_folder_contents("banner").await.unwrap(),
+            Some(vec![banner.id])
+        );
+        assert!(app_state.get_asset_by_id(&photo.id).await.unwrap().is_none());
+        assert!(!storage.exists(&photo.filename).await.unwrap());
+
+        cleanup_test_data(&pool).await;
+    }
+
+    #[actix_web::test]
+    async fn test_credential_failures_return_identical_response() {
+        use actix_web::{test, web, App};
+        use cakung_barat_server::auth::handlers;
+
+        let pool = setup_test_db().await;
+        let mock_storage = Arc::new(MockObjectStorage::new());
+        let app_state = AppState::new_with_pool_and_storage(pool.clone(), mock_storage)
+            .await
+            .unwrap();
+        let username = format!("petugas-{}", Uuid::new_v4());
+        let password_hash = bcrypt::hash("kata-sandi-benar", 4).unwrap();
+        let admin = app_state
+            .create_admin(&username, &password_hash, None, None)
+            .await
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(app_state.clone()))
+                .route("/api/auth/login", web::post().to(handlers::login)),
+        )
+        .await;
+
+        let login = |username: &str, password: &str| {
+            test::TestRequest::post()
+                .uri("/api/auth/login")
+                .set_json(serde_json::json!({ "username": username, "password": password }))
+                .to_request()
+        };
+        let unknown_user = test::call_service(
+            &app,
+            login(&format!("{}-tidak-ada", username), "kata-sandi-benar"),
+        )
+        .await;
+        let wrong_password = test::call_service(&app, login(&username, "kata-sandi-salah")).await;
+
+        assert_eq!(
+            unknown_user.status(),
+            actix_web::http::StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(unknown_user.status(), wrong_password.status());
+        let unknown_user = test::read_body(unknown_user).await;
+        let wrong_password = test::read_body(wrong_password).await;
+        assert_eq!(unknown_user, wrong_password);
+
+        app_state.delete_admin(&admin.id).await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn test_folderless_upload_is_listed_under_others() {
+        use actix_web::{test, web, App};
+        use cakung_barat_server::asset::handlers;
+
+        let pool = setup_test_db().await;
+        let mock_storage = Arc::new(MockObjectStorage::new());
+        let app_state = AppState::new_with_pool_and_storage(pool.clone(), mock_storage)
+            .await
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(app_state))
+                .route("/api/assets", web::post().to(handlers::upload_asset))
+                .route("/api/assets", web::get().to(handlers::get_all_assets_structured))
+                .route(
+                    "/api/assets/folders/{folder_name:.*}",
+                    web::get().to(handlers::list_folder_handler),
+                ),
+        )
+        .await;
+
+        let boundary = "integration-boundary";
+        let body = format!(
+            "--{b}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"lepas.png\"\r\n\r\ndata\r\n--{b}--\r\n",
+            b = boundary
+        );
+        let uploaded: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::post()
+                .uri("/api/assets")
+                .insert_header((
+                    "content-type",
+                    format!("multipart/form-data; boundary={}", boundary),
+                ))
+                .set_payload(body)
+                .to_request(),
+        )
+        .await;
+        let id = uploaded[0]["id"].as_str().unwrap().to_string();
+
+        let token = cakung_barat_server::auth::generate_access_token("admin-id", "admin").unwrap();
+        let structured: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::get()
+                .uri("/api/assets")
+                .insert_header(("Authorization", format!("Bearer {}", token)))
+                .to_request(),
+        )
+        .await;
+        let others: Vec<&serde_json::Value> = structured["folders"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|folder| folder["name"] == "others")
+            .collect();
+        assert_eq!(others.len(), 1, "{}", structured);
+        let listed: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::get()
+                .uri("/api/assets/folders/others")
+                .to_request(),
+        )
+        .await;
+        assert_eq!(others[0]["assets"], listed);
+        assert!(listed
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|asset| asset["id"] == id.as_str()));
+
+        cleanup_test_data(&pool).await;
+    }
+
+    #[actix_web::test]
+    async fn test_posting_excerpt_is_stored_as_written_and_sanitized_when_rendered() {
+        use actix_web::{test, web, App};
+        use cakung_barat_server::posting::{handlers, sanitize};
+
+        let pool = setup_test_db().await;
+        let mock_storage = Arc::new(MockObjectStorage::new());
+        let app_state = AppState::new_with_pool_and_storage(pool.clone(), mock_storage)
+            .await
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(app_state.clone()))
+                .route("/api/postings", web::post().to(handlers::create_posting))
+                .route(
+                    "/api/postings/{id}",
+                    web::put().to(handlers::update_posting),
+                )
+                .route(
+                    "/api/postings/{id}/rendered",
+                    web::get().to(sanitize::get_rendered_posting),
+                ),
+        )
+        .await;
+
+        let excerpt = "<p onmouseover=\"alert(1)\">Posyandu</p>\n\nBalita a < b <script>alert('xss')</script>";
+        let created: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::post()
+                .uri("/api/postings")
+                .set_json(serde_json::json!({
+                    "title": "Jadwal Posyandu",
+                    "category": "Kesehatan",
+                    "excerpt": excerpt
+                }))
+                .to_request(),
+        )
+        .await;
+        let id: Uuid = created["id"].as_str().unwrap().parse().unwrap();
+        assert_eq!(created["excerpt"], excerpt);
+        let stored = app_state.get_post_by_id(&id).await.unwrap().unwrap();
+        assert_eq!(stored.excerpt, excerpt);
+
+        let rendered: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::get()
+                .uri(&format!("/api/postings/{}/rendered", id))
+                .to_request(),
+        )
+        .await;
+        let html = rendered["html"].as_str().unwrap();
+        assert!(html.contains("<p>Posyandu</p>"), "{}", html);
+        assert!(html.contains("a &lt; b"), "{}", html);
+        assert!(!html.contains("alert"), "{}", html);
+
+        let table =
+            "| Jam | Kegiatan |\n|---|---|\n| 08.00 | Timbang <img src=x onerror=alert(2)> |";
+        let updated: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::put()
+                .uri(&format!("/api/postings/{}", id))
+                .set_json(serde_json::json!({ "excerpt": table }))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(updated["excerpt"], table);
+
+        let rendered: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::get()
+                .uri(&format!("/api/postings/{}/rendered", id))
+                .to_request(),
+        )
+        .await;
+        let html = rendered["html"].as_str().unwrap();
+        assert_eq!(rendered["lang"], "id");
+        assert!(html.contains("<th>Kegiatan</th>"), "{}", html);
+        assert!(html.contains("<td>08.00</td>"), "{}", html);
+        assert!(!html.contains("alert"), "{}", html);
+
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri(&format!("/api/postings/{}/rendered", Uuid::new_v4()))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), 404);
+
+        app_state.delete_post(&id).await.unwrap();
+        cleanup_test_data(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_dissolving_others_unlinks_its_assets() {
+        let pool = setup_test_db().await;
+        let mock_storage = Arc::new(MockObjectStorage::new());
+        let app_state = AppState::new_with_pool_and_storage(pool.clone(), mock_storage)
+            .await
+            .unwrap();
+
+        let asset = Asset::new(
+            "Legacy".to_string(),
+            format!("legacy_{}.jpg", Uuid::new_v4()),
+            "/assets/serve/legacy.jpg".to_string(),
+            None,
+        );
+        app_state.insert_asset(&asset).await.unwrap();
+        let mut others = app_state
+            .get_folder_contents("others")
+            .await
+            .unwrap()
+            .unwrap_or_default();
+        others.push(asset.id);
+        app_state
+            .insert_folder_contents("others", &others)
+            .await
+            .unwrap();
+
+        let unlinked = app_state.dissolve_folder("others").await.unwrap();
+        assert!(unlinked >= 1);
+        assert!(app_state.get_folder_contents("others").await.unwrap().is_none());
+        let unlinked_assets = app_state.get_unlinked_assets().await.unwrap();
+        assert!(unlinked_assets.iter().any(|a| a.id == asset.id));
+
+        cleanup_test_data(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_legacy_absolute_urls_are_relativized() {
+        let pool = setup_test_db().await;
+        let mock_storage = Arc::new(MockObjectStorage::new());
+        let app_state = AppState::new_with_pool_and_storage(pool.clone(), mock_storage)
+            .await
+            .unwrap();
+
+        let filename = format!("legacy_{}.jpg", Uuid::new_v4());
+        let legacy = Asset::new(
+            "Legacy".to_string(),
+            filename.clone(),
+            format!("https://proyek.supabase.co/storage/v1/object/public/assets/{}", filename),
+            None,
+        );
+        app_state.insert_asset(&legacy).await.unwrap();
+        let fresh = Asset::new(
+            "Fresh".to_string(),
+            format!("fresh_{}.jpg", Uuid::new_v4()),
+            "/assets/serve/fresh.jpg".to_string(),
+            None,
+        );
+        app_state.insert_asset(&fresh).await.unwrap();
+
+        let updated = app_state.relativize_asset_urls().await.unwrap();
+        assert!(updated >= 1);
+        let stored = app_state.get_asset_by_id(&legacy.id).await.unwrap().unwrap();
+        assert_eq!(stored.url, format!("/assets/serve/{}", filename));
+        let stored = app_state.get_asset_by_id(&fresh.id).await.unwrap().unwrap();
+        assert_eq!(stored.url, "/assets/serve/fresh.jpg");
+
+        cleanup_test_data(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_asset_dimensions_are_stored_and_backfilled() {
+        let pool = setup_test_db().await;
+        let mock_storage = Arc::new(MockObjectStorage::new());
+        let app_state = AppState::new_with_pool_and_storage(pool.clone(), mock_storage)
+            .await
+            .unwrap();
+
+        let mut photo = Asset::new(
+            "Foto".to_string(),
+            format!("foto_{}.png", Uuid::new_v4()),
+            "/assets/serve/foto.png".to_string(),
+            None,
+        );
+        photo.width = Some(1920);
+        photo.height = Some(1080);
+        app_state.insert_asset(&photo).await.unwrap();
+        let stored = app_state.get_asset_by_id(&photo.id).await.unwrap().unwrap();
+        assert_eq!((stored.width, stored.height), (Some(1920), Some(1080)));
+
+        let legacy = Asset::new(
+            "Lama".to_string(),
+            format!("lama_{}.png", Uuid::new_v4()),
+            "/assets/serve/lama.png".to_string(),
+            None,
+        );
+        app_state.insert_asset(&legacy).await.unwrap();
+        let mut pending = Vec::new();
+        let mut after = Uuid::nil();
+        loop {
+            let batch = app_state
+                .get_assets_without_dimensions(after, 2)
+                .await
+                .unwrap();
+            let Some(last) = batch.last() else { break };
+            after = last.id;
+            pending.extend(batch.into_iter().map(|asset| asset.id));
+        }
+        assert!(pending.contains(&legacy.id));
+        assert!(!pending.contains(&photo.id));
+
+        app_state
+            .set_asset_dimensions(&legacy.id, 3, 5)
+            .await
+            .unwrap();
+        let stored = app_state.get_asset_by_id(&legacy.id).await.unwrap().unwrap();
+        assert_eq!((stored.width, stored.height), (Some(3), Some(5)));
+
+        cleanup_test_data(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_empty_post_folders_of_deleted_posts_are_removed() {
+        let pool = setup_test_db().await;
+        let mock_storage = Arc::new(MockObjectStorage::new());
+        let app_state = AppState::new_with_pool_and_storage(pool.clone(), mock_storage)
+            .await
+            .unwrap();
+
+        // A post folder left by a deleted post
+        let orphan = format!("posts/{}", Uuid::new_v4());
+        app_state.insert_folder_contents(&orphan, &vec![]).await.unwrap();
+        // One still holding an asset
+        let asset = Asset::new(
+            "Foto".to_string(),
+            format!("foto_{}.jpg", Uuid::new_v4()),
+            "/assets/serve/foto.jpg".to_string(),
+            None,
+        );
+        app_state.insert_asset(&asset).await.unwrap();
+        let linked = format!("posts/{}", Uuid::new_v4());
+        app_state
+            .insert_folder_contents(&linked, &vec![asset.id])
+            .await
+            .unwrap();
+        // One belonging to a live post without images
+        let post_folder = format!("posts/{}", Uuid::new_v4());
+        let post = Post::new(
+            "Kerja Bakti".to_string(),
+            "Kegiatan".to_string(),
+            "Kerja bakti RW 05".to_string(),
+            Some(post_folder.clone()),
+        );
+        app_state.insert_post(&post).await.unwrap();
+        app_state
+            .insert_folder_contents(&post_folder, &vec![])
+            .await
+            .unwrap();
+        // Regular folders are never touched
+        let regular = format!("kosong-{}", Uuid::new_v4());
+        app_state.insert_folder_contents(&regular, &vec![]).await.unwrap();
+
+        let cleanup = app_state.remove_orphaned_post_folders().await.unwrap();
+        assert!(cleanup.folders_removed >= 1);
+        assert!(app_state.get_folder_contents(&orphan).await.unwrap().is_none());
+        for kept in [&linked, &post_folder, &regular] {
+            assert!(
+                app_state.get_folder_contents(kept).await.unwrap().is_some(),
+                "{}",
+                kept
+            );
+        }
+
+        cleanup_test_data(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_post_cover_falls_back_to_first_image() {
+        let pool = setup_test_db().await;
+        let mock_storage = Arc::new(MockObjectStorage::new());
+        let app_state = AppState::new_with_pool_and_storage(pool.clone(), mock_storage)
+            .await
+            .unwrap();
+
+        let now = chrono::Utc::now();
+        let asset = |name: &str, extension: &str, minutes_ago: i64| {
+            let filename = format!("{}_{}.{}", name, Uuid::new_v4(), extension);
+            let mut asset = Asset::new(
+                name.to_string(),
+                filename.clone(),
+                format!("/assets/serve/{}", filename),
+                None,
+            );
+            asset.created_at = Some(now - chrono::Duration::minutes(minutes_ago));
+            asset
+        };
+        // The oldest file is a PDF, which is never a default cover
+        let pdf = asset("undangan", "pdf", 30);
+        let first_image = asset("foto_1", "jpg", 20);
+        let second_image = asset("foto_2", "PNG", 10);
+        for a in [&pdf, &first_image, &second_image] {
+            app_state.insert_asset(a).await.unwrap();
+        }
+        let folder = format!("posts/{}", Uuid::new_v4());
+        let mut post = Post::new(
+            "Kerja Bakti".to_string(),
+            "Kegiatan".to_string(),
+            "Kerja bakti RW 05".to_string(),
+            Some(folder.clone()),
+        );
+        app_state.insert_post(&post).await.unwrap();
+        app_state
+            .insert_folder_contents(&folder, &vec![second_image.id, pdf.id, first_image.id])
+            .await
+            .unwrap();
+
+        let cover = |post: Post| (post.cover_asset_id, post.cover_filename);
+        let loaded = app_state.get_post_by_id(&post.id).await.unwrap().unwrap();
+        assert_eq!(cover(loaded), (None, Some(first_image.filename.clone())));
+
+        post.cover_asset_id = Some(second_image.id);
+        app_state.update_post(&post).await.unwrap();
+        let loaded = app_state.get_post_by_id(&post.id).await.unwrap().unwrap();
+        assert_eq!(
+            cover(loaded),
+            (Some(second_image.id), Some(second_image.filename.clone()))
+        );
+        let listed = app_state.get_all_posts().await.unwrap();
+        let listed = listed.into_iter().find(|p| p.id == post.id).unwrap();
+        assert_eq!(listed.cover_filename.as_ref(), Some(&second_image.filename));
+        let with_assets = app_state
+            .get_all_postings_with_assets(Some(second_image.id))
+            .await
+            .unwrap();
+        assert_eq!(with_assets.len(), 1);
+        assert_eq!(with_assets[0].cover_asset_id, Some(second_image.id));
+        assert_eq!(with_assets[0].asset_ids.len(), 3);
+
+        // Deleting the picked cover goes back to the first image
+        app_state.delete_asset(&second_image.id).await.unwrap();
+        let loaded = app_state.get_post_by_id(&post.id).await.unwrap().unwrap();
+        assert_eq!(cover(loaded), (None, Some(first_image.filename.clone())));
+
+        cleanup_test_data(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_translations_fall_back_to_indonesian() {
+        let pool = setup_test_db().await;
+        let mock_storage = Arc::new(MockObjectStorage::new());
+        let app_state = AppState::new_with_pool_and_storage(pool.clone(), mock_storage)
+            .await
+            .unwrap();
+
+        let translated = Post::new(
+            "Kerja Bakti RW 05".to_string(),
+            "Kegiatan".to_string(),
+            "Warga membersihkan saluran air".to_string(),
+            None,
+        );
+        let untranslated = Post::new(
+            "Posyandu Balita".to_string(),
+            "Kesehatan".to_string(),
+            "Jadwal posyandu bulan ini".to_string(),
+            None,
+        );
+        app_state.insert_post(&translated).await.unwrap();
+        app_state.insert_post(&untranslated).await.unwrap();
+
+        // Fill the English list cache before the translation exists
+        let listed = app_state.get_all_posts_cached_in("en").await.unwrap();
+        let post = listed.iter().find(|p| p.id == translated.id).unwrap();
+        assert_eq!(post.title, "Kerja Bakti RW 05");
+
+        let request = TranslationRequest {
+            title: "Community Clean-up in RW 05".to_string(),
+            excerpt: "Residents cleared the drains".to_string(),
+        };
+        app_state
+            .upsert_post_translation(&translated.id, "en", &request)
+            .await
+            .unwrap();
+
+        let loaded = app_state.get_post_by_id(&translated.id).await.unwrap().unwrap();
+        assert_eq!(loaded.title, "Kerja Bakti RW 05");
+        assert_eq!(loaded.translations_available, vec!["en"]);
+        let loaded = app_state.get_post_by_id(&untranslated.id).await.unwrap().unwrap();
+        assert!(loaded.translations_available.is_empty());
+
+        for posts in [
+            app_state.get_all_posts_cached_in("en").await.unwrap(),
+            app_state.get_posts_paginated(1000, 0).await.unwrap(),
+        ] {
+            let posts = app_state.translate_posts(posts, "en").await.unwrap();
+            let post = posts.iter().find(|p| p.id == translated.id).unwrap();
+            assert_eq!(post.title, "Community Clean-up in RW 05");
+            assert_eq!(post.excerpt, "Residents cleared the drains");
+            let post = posts.iter().find(|p| p.id == untranslated.id).unwrap();
+            assert_eq!(post.title, "Posyandu Balita");
+        }
+        let indonesian = app_state.get_all_posts_cached_in("id").await.unwrap();
+        let post = indonesian.iter().find(|p| p.id == translated.id).unwrap();
+        assert_eq!(post.title, "Kerja Bakti RW 05");
+
+        // A second write replaces the first
+        let request = TranslationRequest {
+            title: "Clean-up Day".to_string(),
+            excerpt: "Drains cleared".to_string(),
+        };
+        app_state
+            .upsert_post_translation(&translated.id, "en", &request)
+            .await
+            .unwrap();
+        let english = app_state
+            .get_post_translation(&translated.id, "en")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(english.title, "Clean-up Day");
+        let listed = app_state.get_all_posts_cached_in("en").await.unwrap();
+        let post = listed.iter().find(|p| p.id == translated.id).unwrap();
+        assert_eq!(post.title, "Clean-up Day");
+
+        app_state.delete_post(&translated.id).await.unwrap();
+        app_state.delete_post(&untranslated.id).await.unwrap();
+        assert!(app_state
+            .get_post_translation(&translated.id, "en")
+            .await
+            .unwrap()
+            .is_none());
+        cleanup_test_data(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_comments_are_published_after_approval() {
+        let pool = setup_test_db().await;
+        let mock_storage = Arc::new(MockObjectStorage::new());
+        let app_state = AppState::new_with_pool_and_storage(pool.clone(), mock_storage)
+            .await
+            .unwrap();
+
+        let post = Post::new(
+            "Pembagian Sembako".to_string(),
+            "Sosial".to_string(),
+            "Pembagian sembako di kantor kelurahan".to_string(),
+            None,
+        );
+        app_state.insert_post(&post).await.unwrap();
+        let comment_count = |posts: Vec<Post>| {
+            posts.into_iter().find(|p| p.id == post.id).unwrap().comment_count
+        };
+        assert_eq!(comment_count(app_state.get_all_posts_cached().await.unwrap()), 0);
+
+        let comment = app_state
+            .insert_comment(&post.id, "Pak Budi", Some("0812-0000-0000"), "Jam berapa dimulai?")
+            .await
+            .unwrap();
+        assert_eq!(comment.status, CommentStatus::Pending);
+        assert!(app_state.list_approved_comments(&post.id).await.unwrap().is_empty());
+
+        let approved = app_state
+            .moderate_comment(&comment.id, CommentStatus::Approved)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(approved.status, CommentStatus::Approved);
+        assert!(approved.moderated_at.is_some());
+        let public = app_state.list_approved_comments(&post.id).await.unwrap();
+        assert_eq!(public.len(), 1);
+        assert_eq!(public[0].body, "Jam berapa dimulai?");
+        // The cached list was dropped on approval
+        assert_eq!(comment_count(app_state.get_all_posts_cached().await.unwrap()), 1);
+        let listed = app_state.get_all_postings_with_assets(None).await.unwrap();
+        let listed = listed.into_iter().find(|p| p.id == post.id).unwrap();
+        assert_eq!(listed.comment_count, 1);
+
+        // Approving twice is not a transition
+        assert!(app_state
+            .moderate_comment(&comment.id, CommentStatus::Approved)
+            .await
+            .unwrap()
+            .is_none());
+        app_state
+            .moderate_comment(&comment.id, CommentStatus::Rejected)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(app_state.list_approved_comments(&post.id).await.unwrap().is_empty());
+        assert_eq!(comment_count(app_state.get_all_posts_cached().await.unwrap()), 0);
+        let queue = app_state
+            .list_comments(Some(CommentStatus::Rejected), 200)
+            .await
+            .unwrap();
+        assert!(queue.iter().any(|c| c.id == comment.id));
+
+        assert!(app_state.delete_comment(&comment.id).await.unwrap());
+        assert!(!app_state.delete_comment(&comment.id).await.unwrap());
+        assert!(app_state.get_comment(&comment.id).await.unwrap().is_none());
+
+        app_state.delete_post(&post.id).await.unwrap();
+        cleanup_test_data(&pool).await;
+    }
+
+    #[actix_web::test]
+    async fn test_submission_is_assigned_and_resolved() {
+        use actix_web::{test, web, App};
+        use cakung_barat_server::submission::{
+            handlers, SubmissionConfig, SubmissionStatus, SubmissionUpdate,
+        };
+
+        let pool = setup_test_db().await;
+        let app_state = AppState::builder()
+            .with_pool(pool.clone())
+            .with_storage(Arc::new(MockObjectStorage::new()))
+            .with_submission_config(SubmissionConfig {
+                per_ip_per_hour: 1,
+                ..SubmissionConfig::default()
+            })
+            .build()
+            .unwrap();
+        app_state.refresh_open_submissions().await;
+        let open_before = app_state.submission_metrics.open();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(app_state.clone()))
+                .configure(handlers::config)
+                .route(
+                    "/api/assets",
+                    web::get().to(cakung_barat_server::asset::handlers::get_all_assets_structured),
+                )
+                .route(
+                    "/api/assets/by-ids",
+                    web::post().to(cakung_barat_server::asset::handlers::get_assets_by_ids),
+                )
+                .route(
+                    "/api/assets/{id}",
+                    web::get().to(cakung_barat_server::asset::handlers::get_asset_by_id),
+                )
+                .route(
+                    "/assets/serve/{filename:.*}",
+                    web::get().to(cakung_barat_server::asset::handlers::serve_asset),
+                ),
+        )
+        .await;
+
+        let boundary = "integration-boundary";
+        let body = format!(
+            "--{b}\r\nContent-Disposition: form-data; name=\"nama\"\r\n\r\nBudi\r\n\
+             --{b}\r\nContent-Disposition: form-data; name=\"kategori\"\r\n\r\nKebersihan\r\n\
+             --{b}\r\nContent-Disposition: form-data; name=\"isi\"\r\n\r\nSampah menumpuk\r\n\
+             --{b}\r\nContent-Disposition: form-data; name=\"attachment\"; filename=\"sampah.png\"\r\n\r\ndata\r\n\
+             --{b}--\r\n",
+            b = boundary
+        );
+        let submit = |forwarded_for: &'static str| {
+            test::TestRequest::post()
+                .uri("/submissions")
+                .insert_header((
+                    "content-type",
+                    format!("multipart/form-data; boundary={}", boundary),
+                ))
+                .insert_header(("X-Forwarded-For", forwarded_for))
+                .peer_addr("192.0.2.44:50000".parse().unwrap())
+                .set_payload(body.clone())
+                .to_request()
+        };
+        let resp = test::call_service(&app, submit("192.0.2.44")).await;
+        assert_eq!(resp.status(), 202);
+        let submitted: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(submitted["status"], "open");
+        let id: Uuid = submitted["id"].as_str().unwrap().parse().unwrap();
+        assert_eq!(app_state.submission_metrics.open(), open_before + 1);
+
+        let resp = test::call_service(&app, submit("192.0.2.44")).await;
+        assert_eq!(resp.status(), 429);
+
+        // A forwarded address does not buy a fresh quota
+        let resp = test::call_service(&app, submit("198.51.100.9")).await;
+        assert_eq!(resp.status(), 429);
+
+        let submission = app_state.get_submission(&id).await.unwrap().unwrap();
+        assert_eq!(submission.kategori, "kebersihan");
+        assert_eq!(submission.status, SubmissionStatus::Open);
+        assert!(submission.attachment_url.is_some());
+        let attachment_id = submission.attachment_asset_id.unwrap();
+
+        // The attachment stays out of the asset listing
+        let token = cakung_barat_server::auth::generate_access_token("admin-id", "admin").unwrap();
+        let structured: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::get()
+                .uri("/api/assets")
+                .insert_header(("Authorization", format!("Bearer {}", token)))
+                .to_request(),
+        )
+        .await;
+        assert!(!structured.to_string().contains(&attachment_id.to_string()));
+
+        // Nor can it be read without a token
+        let attachment = app_state
+            .get_asset_by_id(&attachment_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let bearer = ("Authorization", format!("Bearer {}", token));
+        for authorized in [false, true] {
+            let with_token = |request: test::TestRequest| {
+                if authorized {
+                    request.insert_header(bearer.clone())
+                } else {
+                    request
+                }
+            };
+            let by_id = test::call_service(
+                &app,
+                with_token(test::TestRequest::get().uri(&format!("/api/assets/{}", attachment_id)))
+                    .to_request(),
+            )
+            .await;
+            let served = test::call_service(
+                &app,
+                with_token(
+                    test::TestRequest::get().uri(&format!("/assets/serve/{}", attachment.filename)),
+                )
+                .to_request(),
+            )
+            .await;
+            let by_ids: Vec<serde_json::Value> = test::call_and_read_body_json(
+                &app,
+                with_token(
+                    test::TestRequest::post()
+                        .uri("/api/assets/by-ids")
+                        .set_json(serde_json::json!({ "ids": [attachment_id] })),
+                )
+                .to_request(),
+            )
+            .await;
+            if authorized {
+                assert_eq!(by_id.status(), 200);
+                assert_ne!(served.status(), 401);
+                assert_eq!(by_ids.len(), 1);
+            } else {
+                assert_eq!(by_id.status(), 401);
+                assert_eq!(served.status(), 401);
+                assert!(by_ids.is_empty());
+            }
+        }
+
+        let assigned = app_state
+            .update_submission(
+                &id,
+                SubmissionUpdate {
+                    status: SubmissionStatus::InProgress,
+                    assigned_to: Some("Pak RT 05"),
+                    resolution_notes: None,
+                },
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(assigned.status, SubmissionStatus::InProgress);
+        assert_eq!(assigned.assigned_to.as_deref(), Some("Pak RT 05"));
+        let in_progress = app_state
+            .list_submissions(Some(SubmissionStatus::InProgress), Some("kebersihan"), 200)
+            .await
+            .unwrap();
+        assert!(in_progress.iter().any(|s| s.id == id));
+
+        let resolve = || SubmissionUpdate {
+            status: SubmissionStatus::Resolved,
+            assigned_to: None,
+            resolution_notes: Some("Sudah diangkut"),
+        };
+        let resolved = app_state.update_submission(&id, resolve()).await.unwrap().unwrap();
+        assert_eq!(resolved.status, SubmissionStatus::Resolved);
+        assert_eq!(resolved.assigned_to.as_deref(), Some("Pak RT 05"));
+        assert_eq!(resolved.resolution_notes.as_deref(), Some("Sudah diangkut"));
+        assert!(resolved.resolved_at.is_some());
+        assert_eq!(app_state.submission_metrics.open(), open_before);
+
+        // Resolved is final
+        assert!(app_state.update_submission(&id, resolve()).await.unwrap().is_none());
+
+        sqlx::query("DELETE FROM submissions WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        app_state.delete_asset(&attachment_id).await.unwrap();
+        cleanup_test_data(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_renamed_folder_keeps_post_assets() {
+        let pool = setup_test_db().await;
+        let mock_storage = Arc::new(MockObjectStorage::new());
+        let app_state = AppState::new_with_pool_and_storage(pool.clone(), mock_storage)
+            .await
+            .unwrap();
+
+        let asset = Asset::new(
+            "Foto".to_string(),
+            format!("foto_{}.jpg", Uuid::new_v4()),
+            "/assets/serve/foto.jpg".to_string(),
+            None,
+        );
+        app_state.insert_asset(&asset).await.unwrap();
+        // The post exists before its folder, as after a create without images
+        let folder = format!("posts/{}", Uuid::new_v4());
+        let post = Post::new(
+            "Posyandu".to_string(),
+            "Kesehatan".to_string(),
+            "Posyandu balita RW 03".to_string(),
+            Some(folder.clone()),
+        );
+        app_state.insert_post(&post).await.unwrap();
+        app_state
+            .insert_folder_contents(&folder, &vec![asset.id])
+            .await
+            .unwrap();
+
+        let renamed = format!("posts/{}", Uuid::new_v4());
+        sqlx::query("UPDATE folders SET name = $1 WHERE name = $2")
+            .bind(&renamed)
+            .bind(&folder)
+            .execute(&pool)
+            .await
+            .unwrap();
+        // A new folder taking the old name must not steal the post
+        app_state.insert_folder_contents(&folder, &vec![]).await.unwrap();
+
+        let with_assets = app_state
+            .get_posting_by_id_with_assets(&post.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(with_assets.asset_ids, vec![asset.id]);
+        assert_eq!(with_assets.folder_id.as_deref(), Some(renamed.as_str()));
+        let all = app_state.get_all_postings_with_assets(None).await.unwrap();
+        let listed = all.iter().find(|p| p.id == post.id).unwrap();
+        assert_eq!(listed.asset_ids, vec![asset.id]);
+
+        // Saving the post as read keeps it on the renamed folder
+        let mut updated = with_assets.clone();
+        updated.title = "Posyandu Balita".to_string();
+        updated.asset_ids.clear();
+        app_state.upsert_posting_with_assets(&updated).await.unwrap();
+        let reloaded = app_state
+            .get_posting_by_id_with_assets(&post.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reloaded.title, "Posyandu Balita");
+        assert_eq!(reloaded.asset_ids, vec![asset.id]);
+
+        cleanup_test_data(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_postings_with_assets_match_per_post_lookup() {
+        let pool = setup_test_db().await;
+        let mock_storage = Arc::new(MockObjectStorage::new());
+        let app_state = AppState::new_with_pool_and_storage(pool.clone(), mock_storage)
+            .await
+            .unwrap();
+
+        let mut assets = Vec::new();
+        for i in 0..3 {
+            let asset = Asset::new(
+                format!("Foto {}", i),
+                format!("foto_{}.jpg", Uuid::new_v4()),
+                "/assets/serve/foto.jpg".to_string(),
+                None,
+            );
+            app_state.insert_asset(&asset).await.unwrap();
+            assets.push(asset.id);
+        }
+        // Two posts sharing an asset, one without images, one without a folder
+        let shared = assets[0];
+        let seeded = [
+            vec![shared, assets[1]],
+            vec![shared, assets[2]],
+            vec![],
+        ];
+        let mut post_ids = Vec::new();
+        for (i, asset_ids) in seeded.iter().enumerate() {
+            let folder = format!("posts/{}", Uuid::new_v4());
+            let post = Post::new(
+                format!("Kegiatan {}", i),
+                "Kegiatan".to_string(),
+                "Kegiatan warga".to_string(),
+                Some(folder.clone()),
+            );
+            app_state.insert_post(&post).await.unwrap();
+            app_state
+                .insert_folder_contents(&folder, asset_ids)
+                .await
+                .unwrap();
+            post_ids.push(post.id);
+        }
+        let folderless = Post::new(
+            "Pengumuman".to_string(),
+            "Info".to_string(),
+            "Tanpa gambar".to_string(),
+            None,
+        );
+        app_state.insert_post(&folderless).await.unwrap();
+        post_ids.push(folderless.id);
+
+        // Same result as looking up the folder of each post in turn
+        let sorted = |mut ids: Vec<Uuid>| {
+            ids.sort();
+            ids
+        };
+        let all = app_state.get_all_postings_with_assets(None).await.unwrap();
+        for post in app_state.get_all_posts().await.unwrap() {
+            let listed = all.iter().find(|p| p.id == post.id).unwrap();
+            let expected = match &post.folder_id {
+                Some(folder) => app_state
+                    .get_folder_contents(folder)
+                    .await
+                    .unwrap()
+                    .unwrap_or_default(),
+                None => Vec::new(),
+            };
+            assert_eq!(sorted(listed.asset_ids.clone()), sorted(expected));
+            assert_eq!(listed.folder_id, post.folder_id);
+            assert_eq!(listed.title, post.title);
+        }
+
+        let holding: Vec<Uuid> = app_state
+            .get_all_postings_with_assets(Some(shared))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(sorted(holding), sorted(post_ids[..2].to_vec()));
+        let holding = app_state
+            .get_all_postings_with_assets(Some(assets[2]))
+            .await
+            .unwrap();
+        assert_eq!(holding.len(), 1);
+        assert_eq!(holding[0].id, post_ids[1]);
+        assert_eq!(sorted(holding[0].asset_ids.clone()), sorted(vec![shared, assets[2]]));
+
+        cleanup_test_data(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_post_folder_links_are_rebuilt_from_storage() {
+        let pool = setup_test_db().await;
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Arc::new(LocalStorage::new(dir.path()));
+        let app_state = AppState::new_with_pool_and_storage(pool.clone(), storage.clone())
+            .await
+            .unwrap();
+
+        let mut assets = Vec::new();
+        for name in ["kept", "missing", "unlinked"] {
+            let asset = Asset::new(
+                "Foto".to_string(),
+                format!("{}_{}.jpg", name, Uuid::new_v4()),
+                format!("/assets/serve/{}.jpg", name),
+                None,
+            );
+            app_state.insert_asset(&asset).await.unwrap();
+            assets.push(asset);
+        }
+        let [kept, missing, unlinked] = &assets[..] else {
+            unreachable!()
+        };
+        let folder = format!("posts/{}", Uuid::new_v4());
+        app_state
+            .insert_folder_contents(&folder, &vec![kept.id, missing.id])
+            .await
+            .unwrap();
+        for asset in [kept, unlinked] {
+            storage
+                .upload_file(&format!("{}/{}", folder, asset.filename), b"jpeg")
+                .await
+                .unwrap();
+        }
+
+        let report = app_state.rebuild_post_folders(true).await.unwrap();
+        let rebuild = report
+            .folders
+            .iter()
+            .find(|rebuild| rebuild.folder == folder)
+            .unwrap();
+        assert_eq!(rebuild.added, vec![unlinked.id]);
+        assert_eq!(rebuild.removed, vec![missing.id]);
+        // A dry run changes nothing
+        let linked = app_state.get_folder_contents(&folder).await.unwrap().unwrap();
+        assert!(linked.contains(&missing.id));
+
+        app_state.rebuild_post_folders(false).await.unwrap();
+        let mut linked = app_state.get_folder_contents(&folder).await.unwrap().unwrap();
+        linked.sort();
+        let mut expected = vec![kept.id, unlinked.id];
+        expected.sort();
+        assert_eq!(linked, expected);
+        let again = app_state.rebuild_post_folders(true).await.unwrap();
+        assert!(again.folders.iter().all(|rebuild| rebuild.folder != folder));
+
+        cleanup_test_data(&pool).await;
+    }
+
+    #[actix_web::test]
+    async fn test_gallery_lists_only_public_folders() {
+        use actix_web::{test, web, App};
+        use cakung_barat_server::asset::gallery::GalleryConfig;
+        use cakung_barat_server::asset::handlers;
+
+        let pool = setup_test_db().await;
+        let public = format!("galeri-{}", Uuid::new_v4());
+        let hidden = format!("rahasia-{}", Uuid::new_v4());
+        let app_state = AppState::builder()
+            .with_pool(pool.clone())
+            .with_storage(Arc::new(MockObjectStorage::new()))
+            .with_gallery_config(GalleryConfig {
+                public_folders: vec![public.clone()],
+            })
+            .build()
+            .unwrap();
+
+        let mut public_ids = Vec::new();
+        for (i, folder) in [&public, &public, &public, &hidden].into_iter().enumerate() {
+            let mut asset = Asset::new(
+                format!("Foto {}", i),
+                format!("foto_{}_{}.jpg", i, Uuid::new_v4()),
+                "/assets/serve/foto.jpg".to_string(),
+                None,
+            );
+            asset.created_at = Some(chrono::Utc::now() + chrono::Duration::seconds(i as i64));
+            app_state.insert_asset(&asset).await.unwrap();
+            let mut contents = app_state
+                .get_folder_contents(folder)
+                .await
+                .unwrap()
+                .unwrap_or_default();
+            contents.push(asset.id);
+            app_state.insert_folder_contents(folder, &contents).await.unwrap();
+            if folder == &public {
+                public_ids.push(asset.id.to_string());
+            }
+        }
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(app_state))
+                .route("/api/gallery", web::get().to(handlers::get_gallery)),
+        )
+        .await;
+
+        let mut listed = Vec::new();
+        for page in 1..=2 {
+            let body: serde_json::Value = test::call_and_read_body_json(
+                &app,
+                test::TestRequest::get()
+                    .uri(&format!("/api/gallery?limit=2&page={}", page))
+                    .to_request(),
+            )
+            .await;
+            assert_eq!(body["total"], 3);
+            for asset in body["assets"].as_array().unwrap() {
+                assert_eq!(asset["folder"], public.as_str(), "{}", body);
+                listed.push(asset["id"].as_str().unwrap().to_string());
+            }
+        }
+        // Newest first
+        public_ids.reverse();
+        assert_eq!(listed, public_ids);
+
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri(&format!("/api/gallery?folder={}", hidden))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), 404);
+
+        cleanup_test_data(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_dashboard_stats_count_seeded_rows() {
+        let pool = setup_test_db().await;
+        let state = |pool: &PgPool| {
+            AppState::builder()
+                .with_pool(pool.clone())
+                .with_storage(Arc::new(MockObjectStorage::new()))
+                .with_persistence(false)
+                .build()
+                .unwrap()
+        };
+        let before = state(&pool).dashboard_stats().await.unwrap();
+
+        let seed = state(&pool);
+        let category = format!("Statistik {}", Uuid::new_v4());
+        let today = chrono::Utc::now().date_naive();
+        for date in [today, today, today - chrono::Months::new(13)] {
+            let mut post = Post::new(
+                "Rapat RW".to_string(),
+                category.clone(),
+                "Rapat bulanan".to_string(),
+                None,
+            );
+            post.date = date;
+            seed.insert_post(&post).await.unwrap();
+        }
+        let asset = Asset::new(
+            "Foto".to_string(),
+            format!("statistik_{}.jpg", Uuid::new_v4()),
+            "/assets/serve/statistik.jpg".to_string(),
+            None,
+        );
+        seed.insert_asset(&asset).await.unwrap();
+
+        let app_state = state(&pool);
+        let after = app_state.dashboard_stats().await.unwrap();
+        // Other tests share the database, so only lower bounds hold
+        assert!(after.total_posts >= before.total_posts + 3);
+        assert!(after.posts_this_month >= before.posts_this_month + 2);
+        assert!(after.total_assets > before.total_assets);
+        assert!(after.categories > before.categories);
+        let seeded = after
+            .posts_by_category
+            .iter()
+            .find(|count| count.category == category)
+            .unwrap();
+        assert_eq!(seeded.posts, 3);
+        assert!(after.storage_used_bytes.is_none());
+
+        // Served from the cache
+        let cached = app_state.dashboard_stats().await.unwrap();
+        assert_eq!(cached.computed_at, after.computed_at);
+
+        cleanup_test_data(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_webhooks_deliver_events_and_record_dead_letters() {
+        use cakung_barat_server::http_client::RetryPolicy;
+        use cakung_barat_server::webhook::dispatcher::sign;
+        use cakung_barat_server::webhook::{
+            CreateWebhookRequest, UpdateWebhookRequest, WebhookEvent,
+        };
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let pool = setup_test_db().await;
+        let app_state = AppState::builder()
+            .with_pool(pool.clone())
+            .with_storage(Arc::new(MockObjectStorage::new()))
+            .with_persistence(false)
+            .with_webhook_retry(RetryPolicy {
+                attempts: 2,
+                backoff: std::time::Duration::from_millis(10),
+            })
+            .build()
+            .unwrap();
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/ok"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/down"))
+            .respond_with(ResponseTemplate::new(502))
+            .mount(&server)
+            .await;
+
+        let ok = app_state
+            .create_webhook(&CreateWebhookRequest {
+                url: format!("{}/ok", server.uri()),
+                events: vec![WebhookEvent::PostCreated],
+                secret: None,
+                active: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(ok.secret.len(), 64);
+        let down = app_state
+            .create_webhook(&CreateWebhookRequest {
+                url: format!("{}/down", server.uri()),
+                events: vec![WebhookEvent::PostCreated, WebhookEvent::PostCreated],
+                secret: Some("rahasia".to_string()),
+                active: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(down.webhook.events, vec!["post.created".to_string()]);
+
+        let post = Post::new(
+            "Kerja bakti".to_string(),
+            "Kegiatan".to_string(),
+            "Kerja bakti RW 05".to_string(),
+            None,
+        );
+        app_state.notify_webhooks(WebhookEvent::PostCreated, &post);
+        // Not subscribed by either webhook
+        app_state.notify_webhooks(WebhookEvent::PostDeleted, &post);
+        app_state.webhook_worker.flush().await;
+
+        let received = server.received_requests().await.unwrap();
+        let delivered: Vec<_> = received.iter().filter(|r| r.url.path() == "/ok").collect();
+        assert_eq!(delivered.len(), 1);
+        assert_eq!(
+            delivered[0].headers.get("X-Signature").unwrap(),
+            sign(&ok.secret, &delivered[0].body).as_str()
+        );
+        let body: serde_json::Value = serde_json::from_slice(&delivered[0].body).unwrap();
+        assert_eq!(body["event"], "post.created");
+        assert_eq!(body["data"]["id"], post.id.to_string());
+        assert_eq!(
+            received.iter().filter(|r| r.url.path() == "/down").count(),
+            2
+        );
+
+        let letters = app_state.recent_webhook_dead_letters(100).await.unwrap();
+        let letter = letters
+            .iter()
+            .find(|letter| letter.webhook_id == down.webhook.id)
+            .unwrap();
+        assert_eq!(letter.attempts, 2);
+        assert_eq!(letter.payload["data"]["id"], post.id.to_string());
+        assert!(letter.error.contains("502"), "{}", letter.error);
+
+        let updated = app_state
+            .update_webhook(
+                &down.webhook.id,
+                &UpdateWebhookRequest {
+                    url: None,
+                    events: None,
+                    secret: None,
+                    active: Some(false),
+                },
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!updated.active);
+        assert_eq!(updated.url, down.webhook.url);
+
+        assert!(app_state.delete_webhook(&ok.webhook.id).await.unwrap());
+        assert!(app_state.delete_webhook(&down.webhook.id).await.unwrap());
+        assert!(!app_state.delete_webhook(&down.webhook.id).await.unwrap());
+        let remaining = app_state.recent_webhook_dead_letters(100).await.unwrap();
+        assert!(remaining.iter().all(|l| l.webhook_id != down.webhook.id));
+
+        cleanup_test_data(&pool).await;
+    }
+
+    #[actix_web::test]
+    async fn test_post_update_reaches_change_feed() {
+        use actix_web::body::MessageBody;
+        use actix_web::{test, web, App};
+        use cakung_barat_server::events::stream_changes;
+        use cakung_barat_server::posting::handlers::update_posting;
+        use std::pin::Pin;
+
+        let pool = setup_test_db().await;
+        let app_state = web::Data::new(
+            AppState::builder()
+                .with_pool(pool.clone())
+                .with_storage(Arc::new(MockObjectStorage::new()))
+                .with_persistence(false)
+                .with_webhooks(false)
+                .build()
+                .unwrap(),
+        );
+        let post = Post::new(
+            "Posyandu".to_string(),
+            "Kesehatan".to_string(),
+            "Jadwal posyandu".to_string(),
+            None,
+        );
+        app_state.insert_post(&post).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(app_state.clone())
+                .route("/api/events", web::get().to(stream_changes))
+                .route("/api/postings/{id}", web::put().to(update_posting)),
+        )
+        .await;
+        let token = cakung_barat_server::auth::generate_access_token("admin-id", "admin").unwrap();
+        let mut events = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri("/api/events")
+                .insert_header(("Authorization", format!("Bearer {}", token)))
+                .to_request(),
+        )
+        .await
+        .into_body();
+
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::put()
+                .uri(&format!("/api/postings/{}", post.id))
+                .set_json(serde_json::json!({ "title": "Posyandu Balita" }))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), 200);
+
+        let chunk = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            futures::future::poll_fn(|cx| Pin::new(&mut events).poll_next(cx)),
+        )
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+        let frame = String::from_utf8(chunk.to_vec()).unwrap();
+        let data = frame
+            .lines()
+            .find_map(|line| line.strip_prefix("data: "))
+            .unwrap();
+        let data: serde_json::Value = serde_json::from_str(data).unwrap();
+        assert_eq!(
+            data,
+            serde_json::json!({ "type": "post.updated", "id": post.id })
+        );
+
+        cleanup_test_data(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_fulltext_search_ranks_title_matches_first() {
+        let pool = setup_test_db().await;
+        let app_state = AppState::builder()
+            .with_pool(pool.clone())
+            .with_storage(Arc::new(MockObjectStorage::new()))
+            .with_persistence(false)
+            .with_webhooks(false)
+            .build()
+            .unwrap();
+
+        // A word no other test uses, so the shared database does not matter
+        let word = format!("zq{}", &Uuid::new_v4().simple().to_string()[..10]);
+        let in_excerpt = Post::new(
+            "Berita warga".to_string(),
+            "Kegiatan".to_string(),
+            format!("Kegiatan {} di balai warga hari Minggu", word),
+            None,
+        );
+        let in_title = Post::new(
+            format!("Pengumuman {}", word),
+            "Pengumuman".to_string(),
+            "Informasi untuk seluruh warga".to_string(),
+            None,
+        );
+        app_state.insert_post(&in_excerpt).await.unwrap();
+        app_state.insert_post(&in_title).await.unwrap();
+
+        let (hits, total) = app_state.fulltext_search_posts(&word, 10, 0).await.unwrap();
+        assert_eq!(total, 2);
+        assert_eq!(hits[0].post.id, in_title.id);
+        assert_eq!(hits[1].post.id, in_excerpt.id);
+        assert!(hits[0].rank > hits[1].rank);
+        assert!(
+            hits[1].snippet.contains(&format!("<mark>{}</mark>", word)),
+            "{}",
+            hits[1].snippet
+        );
+
+        let (page, total) = app_state
+            .fulltext_search_posts(&format!("{} -pengumuman", word), 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(page[0].post.id, in_excerpt.id);
+
+        let (page, total) = app_state.fulltext_search_posts(&word, 1, 1).await.unwrap();
+        assert_eq!(total, 2);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].post.id, in_excerpt.id);
+
+        cleanup_test_data(&pool).await;
+    }
+
+    #[actix_web::test]
+    async fn test_backup_restore_round_trip() {
+        use actix_web::{test, web, App};
+        use cakung_barat_server::backup::archive::{read_archive, read_files, ArchiveWriter};
+        use cakung_barat_server::backup::{handlers, FILES_DIR, MANIFEST_FILE};
+        use std::io::Write;
+
+        let pool = setup_test_db().await;
+        let storage = Arc::new(MockObjectStorage::new());
+        let app_state = web::Data::new(
+            AppState::builder()
+                .with_pool(pool.clone())
+                .with_storage(storage.clone())
+                .with_persistence(false)
+                .with_webhooks(false)
+                .build()
+                .unwrap(),
+        );
+        let asset = Asset::new(
+            "Foto kerja bakti".to_string(),
+            format!("backup-{}.jpg", Uuid::new_v4()),
+            "/assets/serve/backup.jpg".to_string(),
+            None,
+        );
+        app_state.insert_asset(&asset).await.unwrap();
+        storage
+            .upload_file(&asset.filename, b"jpeg bytes")
+            .await
+            .unwrap();
+        let folder = format!("posts/{}", Uuid::new_v4());
+        app_state
+            .insert_folder_contents(&folder, &vec![asset.id])
+            .await
+            .unwrap();
+        let post = Post::new(
+            "Kerja bakti".to_string(),
+            "Kegiatan".to_string(),
+            "Kerja bakti hari Minggu".to_string(),
+            Some(folder.clone()),
+        );
+        app_state.insert_post(&post).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(app_state.clone())
+                .service(web::scope("/api").configure(handlers::config)),
+        )
+        .await;
+        let token = cakung_barat_server::auth::generate_access_token("admin-id", "admin").unwrap();
+        let auth = ("Authorization", format!("Bearer {}", token));
+
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::post()
+                .uri("/api/admin/backup?include_files=true")
+                .insert_header(auth.clone())
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(
+            resp.headers().get("content-type").unwrap(),
+            "application/gzip"
+        );
+        let mut backup = tempfile::NamedTempFile::new().unwrap();
+        backup.write_all(&test::read_body(resp).await).unwrap();
+
+        // Other tests share the database, so only this test's rows go back in
+        let contents = read_archive(backup.path()).unwrap();
+        assert!(contents.files.contains(&asset.filename));
+        let mut snapshot = contents.snapshot;
+        snapshot.postings.retain(|p| p.id == post.id);
+        snapshot.assets.retain(|a| a.id == asset.id);
+        snapshot.folders.retain(|f| f.name == folder);
+        snapshot.admins.clear();
+        assert_eq!(snapshot.postings.len(), 1);
+        assert_eq!(snapshot.folders[0].asset_ids, vec![asset.id]);
+        let mut file = None;
+        read_files(backup.path(), |name, data| {
+            if name == asset.filename {
+                file = Some(data);
+            }
+            true
+        })
+        .unwrap();
+
+        let mut writer = ArchiveWriter::default();
+        let mut archive = Vec::new();
+        archive.extend(
+            writer
+                .append_json("postings.json", &snapshot.postings)
+                .unwrap(),
+        );
+        archive.extend(writer.append_json("assets.json", &snapshot.assets).unwrap());
+        archive.extend(
+            writer
+                .append_json("folders.json", &snapshot.folders)
+                .unwrap(),
+        );
+        archive.extend(
+            writer
+                .append_json("organization.json", &snapshot.organization)
+                .unwrap(),
+        );
+        archive.extend(writer.append_json("admins.json", &snapshot.admins).unwrap());
+        archive.extend(
+            writer
+                .append(&format!("{}{}", FILES_DIR, asset.filename), &file.unwrap())
+                .unwrap(),
+        );
+        archive.extend(
+            writer
+                .append_json(MANIFEST_FILE, &snapshot.manifest(1))
+                .unwrap(),
+        );
+        archive.extend(writer.finish().unwrap());
+
+        // Lose everything the archive holds
+        app_state.delete_post(&post.id).await.unwrap();
+        app_state.dissolve_folder(&folder).await.unwrap();
+        app_state.delete_asset(&asset.id).await.unwrap();
+        storage.delete_file(&asset.filename).await.unwrap();
+
+        let restore = |dry_run: bool| {
+            test::TestRequest::post()
+                .uri(&format!("/api/admin/restore?dry_run={}", dry_run))
+                .insert_header(auth.clone())
+                .insert_header(("Content-Type", "application/gzip"))
+                .set_payload(archive.clone())
+                .to_request()
+        };
+        let report: serde_json::Value = test::call_and_read_body_json(&app, restore(true)).await;
+        assert_eq!(report["dry_run"], true);
+        assert_eq!(report["postings"]["created"], 1);
+        assert_eq!(report["assets"]["created"], 1);
+        assert_eq!(report["folders_created"], 1);
+        assert_eq!(report["folder_links_created"], 1);
+        assert_eq!(report["files"], 1);
+        assert!(app_state.get_post_by_id(&post.id).await.unwrap().is_none());
+        assert!(storage.download_file(&asset.filename).await.is_err());
+
+        let report: serde_json::Value = test::call_and_read_body_json(&app, restore(false)).await;
+        assert_eq!(report["dry_run"], false);
+        assert_eq!(report["postings"]["created"], 1);
+        assert_eq!(report["files"], 1);
+        let restored = app_state
+            .get_posting_by_id_with_assets(&post.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(restored.title, "Kerja bakti");
+        assert_eq!(restored.asset_ids, vec![asset.id]);
+        assert_eq!(
+            storage.download_file(&asset.filename).await.unwrap(),
+            b"jpeg bytes"
+        );
+
+        // Restoring again only overwrites
+        let report: serde_json::Value = test::call_and_read_body_json(&app, restore(false)).await;
+        assert_eq!(report["postings"]["created"], 0);
+        assert_eq!(report["postings"]["updated"], 1);
+        assert_eq!(report["folder_links_created"], 0);
+
+        cleanup_test_data(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_constraint_violations_map_to_client_errors() {
+        use actix_web::ResponseError;
+        use cakung_barat_server::ApiError;
+
+        let pool = setup_test_db().await;
+        let mock_storage = Arc::new(MockObjectStorage::new());
+        let app_state = AppState::new_with_pool_and_storage(pool.clone(), mock_storage)
+            .await
+            .unwrap();
+
+        let post = Post::new(
+            "Kerja Bakti".to_string(),
+            "Kegiatan".to_string(),
+            "Kerja bakti membersihkan saluran air".to_string(),
+            None,
+        );
+        app_state.insert_post(&post).await.unwrap();
+
+        // The same id again violates the primary key
+        let err = app_state.insert_post(&post).await.unwrap_err();
+        assert!(err.is_unique_violation(), "{:?}", err);
+        assert!(!err.is_fk_violation() && !err.is_connection());
+        assert_eq!(err.constraint(), Some("posts_pkey"));
+        let err = ApiError::database("Failed to create post")(err);
+        assert_eq!(err.status_code(), 409);
+        assert!(err.message().starts_with("Failed to create post"), "{}", err.message());
+
+        // A comment on a post that does not exist violates its foreign key
+        let err = app_state
+            .insert_comment(&Uuid::new_v4(), "Pak Budi", None, "Kapan dimulai?")
+            .await
+            .unwrap_err();
+        assert!(err.is_fk_violation(), "{:?}", err);
+        assert!(!err.is_unique_violation() && !err.is_connection());
+        let err = ApiError::database("Failed to store comment")(err);
+        assert_eq!(err.status_code(), 400);
+
+        app_state.delete_post(&post.id).await.unwrap();
+        cleanup_test_data(&pool).await;
+    }
+
+    #[actix_web::test]
+    async fn test_folder_listing_projections() {
+        use actix_web::{test, web, App};
+        use cakung_barat_server::asset::handlers;
+
+        let pool = setup_test_db().await;
+        let mock_storage = Arc::new(MockObjectStorage::new());
+        let app_state = AppState::new_with_pool_and_storage(pool.clone(), mock_storage)
+            .await
+            .unwrap();
+
+        let folder = format!("proyeksi-{}", Uuid::new_v4());
+        let mut ids = Vec::new();
+        for n in 0..2 {
+            let asset = Asset::new(
+                format!("Foto {}", n),
+                format!("proyeksi_{}.jpg", Uuid::new_v4()),
+                "/assets/serve/proyeksi.jpg".to_string(),
+                None,
+            );
+            app_state.insert_asset(&asset).await.unwrap();
+            ids.push(asset.id);
+        }
+        app_state.insert_folder_contents(&folder, &ids).await.unwrap();
+        let empty = format!("kosong-{}", Uuid::new_v4());
+        app_state.insert_folder_contents(&empty, &vec![]).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(app_state))
+                .app_data(cakung_barat_server::error_handlers::query_config())
+                .route(
+                    "/api/assets/folders/{folder_name:.*}",
+                    web::get().to(handlers::list_folder_handler),
+                ),
+        )
+        .await;
+        let get = |uri: String| test::TestRequest::get().uri(&uri).to_request();
+
+        for uri in [
+            format!("/api/assets/folders/{}", folder),
+            format!("/api/assets/folders/{}?include=assets", folder),
+        ] {
+            let assets: serde_json::Value = test::call_and_read_body_json(&app, get(uri)).await;
+            let mut listed: Vec<Uuid> = assets
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|asset| asset["id"].as_str().unwrap().parse().unwrap())
+                .collect();
+            listed.sort();
+            let mut expected = ids.clone();
+            expected.sort();
+            assert_eq!(listed, expected);
+        }
+
+        let mut listed: Vec<Uuid> = test::call_and_read_body_json(
+            &app,
+            get(format!("/api/assets/folders/{}?include=ids", folder)),
+        )
+        .await;
+        listed.sort();
+        let mut expected = ids.clone();
+        expected.sort();
+        assert_eq!(listed, expected);
+
+        let count: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            get(format!("/api/assets/folders/{}?include=count", folder)),
+        )
+        .await;
+        assert_eq!(count, serde_json::json!({ "name": folder, "asset_count": 2 }));
+        let count: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            get(format!("/api/assets/folders/{}?include=count", empty)),
+        )
+        .await;
+        assert_eq!(count["asset_count"], 0);
+        let count: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            get("/api/assets/folders/others?include=count".to_string()),
+        )
+        .await;
+        assert!(count["asset_count"].as_i64().unwrap() >= 0);
+
+        for include in ["assets", "ids", "count"] {
+            let resp = test::call_service(
+                &app,
+                get(format!("/api/assets/folders/tidak-ada-{}?include={}", Uuid::new_v4(), include)),
+            )
+            .await;
+            assert_eq!(resp.status(), 404, "include={}", include);
+        }
+        let resp = test::call_service(
+            &app,
+            get(format!("/api/assets/folders/{}?include=names", folder)),
+        )
+        .await;
+        assert_eq!(resp.status(), 400);
+
+        cleanup_test_data(&pool).await;
+    }
+
+    #[actix_web::test]
+    async fn test_post_list_embeds_first_assets() {
+        use actix_web::{test, web, App};
+        use cakung_barat_server::cache::CacheTarget;
+        use cakung_barat_server::posting::handlers::get_all_postings;
+
+        let pool = setup_test_db().await;
+        let mock_storage = Arc::new(MockObjectStorage::new());
+        let app_state = AppState::new_with_pool_and_storage(pool.clone(), mock_storage)
+            .await
+            .unwrap();
+
+        let folder = format!("posts/embed-{}", Uuid::new_v4());
+        let post = Post::new(
+            "Lomba Tujuh Belasan".to_string(),
+            "Kegiatan".to_string(),
+            "Lomba di lapangan RW 03".to_string(),
+            Some(folder.clone()),
+        );
+        app_state.insert_post(&post).await.unwrap();
+        // Added newest first, so the folder order differs from the insert order
+        let mut ids = Vec::new();
+        for n in 0..4 {
+            let mut asset = Asset::new(
+                format!("Foto {}", n),
+                format!("lomba_{}_{}.jpg", n, Uuid::new_v4()),
+                "/assets/serve/lomba.jpg".to_string(),
+                None,
+            );
+            asset.created_at = Some(chrono::Utc::now() - chrono::Duration::minutes(n));
+            app_state.insert_asset(&asset).await.unwrap();
+            ids.push(asset.id);
+        }
+        app_state.insert_folder_contents(&folder, &ids).await.unwrap();
+        ids.reverse();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(app_state.clone()))
+                .route("/api/postings", web::get().to(get_all_postings)),
+        )
+        .await;
+        let listed = |posts: serde_json::Value| {
+            posts
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|listed| listed["id"] == post.id.to_string())
+                .cloned()
+                .unwrap()
+        };
+
+        for uri in ["/api/postings?include=cover,assets:3", "/api/postings?limit=100&include=assets:3"] {
+            let posts: serde_json::Value =
+                test::call_and_read_body_json(&app, test::TestRequest::get().uri(uri).to_request())
+                    .await;
+            let embedded: Vec<Uuid> = listed(posts)["assets"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|asset| {
+                    assert_eq!(asset["mime"], "image/jpeg");
+                    asset["id"].as_str().unwrap().parse().unwrap()
+                })
+                .collect();
+            assert_eq!(embedded, ids[..3], "{}", uri);
+        }
+
+        let posts: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::get().uri("/api/postings").to_request(),
+        )
+        .await;
+        assert!(listed(posts).get("assets").is_none());
+        let posts: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::get().uri("/api/postings?include=assets:10").to_request(),
+        )
+        .await;
+        assert_eq!(listed(posts)["assets"].as_array().unwrap().len(), 4);
+
+        let keys: Vec<String> = app_state
+            .cache_entries(CacheTarget::Posts)
+            .await
+            .into_iter()
+            .map(|entry| entry.key)
+            .collect();
+        for key in ["all_posts", "all_posts:assets=3", "all_posts:assets=10"] {
+            assert!(keys.iter().any(|k| k == key), "{} not in {:?}", key, keys);
+        }
+
+        app_state.delete_post(&post.id).await.unwrap();
+        cleanup_test_data(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_recovers_when_the_database_answers() {
+        use cakung_barat_server::db::circuit::{CircuitBreaker, CircuitState};
+        use cakung_barat_server::db::DbError;
+
+        let pool = setup_test_db().await;
+        let cooldown = std::time::Duration::from_millis(100);
+        let app_state = AppState::builder()
+            .with_pool(pool.clone())
+            .with_storage(Arc::new(MockObjectStorage::new()))
+            .with_circuit_breaker(CircuitBreaker::new(2, cooldown))
+            .with_persistence(false)
+            .with_webhooks(false)
+            .build()
+            .unwrap();
+
+        // Two dropped connections open the circuit
+        for _ in 0..2 {
+            app_state.db_circuit.admit().unwrap();
+            app_state
+                .db_circuit
+                .record(Some(&DbError::from(sqlx::Error::PoolTimedOut)));
+        }
+        assert!(app_state.get_all_posts().await.unwrap_err().is_unavailable());
+        assert!(app_state.get_all_assets().await.unwrap_err().is_unavailable());
+
+        // The probe after the cooldown finds the database and closes it
+        tokio::time::sleep(cooldown).await;
+        app_state.get_all_posts().await.unwrap();
+        assert_eq!(app_state.db_circuit.state(), CircuitState::Closed);
+        app_state.get_all_assets().await.unwrap();
+
+        cleanup_test_data(&pool).await;
+    }
+
+    #[actix_web::test]
+    async fn test_postings_export_filters_by_date_and_escapes_titles() {
+        use actix_web::{test, web, App};
+        use cakung_barat_server::export::{self, BOM};
+
+        let pool = setup_test_db().await;
+        let app_state = AppState::new_with_pool_and_storage(pool.clone(), Arc::new(MockObjectStorage::new()))
+            .await
+            .unwrap();
+
+        // Old dates and a unique category keep other tests' posts out
+        let category = format!("Laporan {}", Uuid::new_v4());
+        let folder = format!("posts/export-{}", Uuid::new_v4());
+        let mut posts = Vec::new();
+        for (title, date, folder) in [
+            ("Rapat RW 03, \"Tujuh Belasan\"\nBalai warga", (1987, 1, 10), Some(folder.clone())),
+            ("Kerja bakti", (1987, 1, 31), None),
+            ("Posyandu", (1987, 2, 1), None),
+        ] {
+            let mut post = Post::new(title.to_string(), category.clone(), "Ringkasan".to_string(), folder);
+            post.date = NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap();
+            app_state.insert_post(&post).await.unwrap();
+            posts.push(post);
+        }
+        let mut ids = Vec::new();
+        for n in 0..2 {
+            let asset = Asset::new(
+                format!("Foto {}", n),
+                format!("export_{}_{}.jpg", n, Uuid::new_v4()),
+                "/assets/serve/export.jpg".to_string(),
+                None,
+            );
+            app_state.insert_asset(&asset).await.unwrap();
+            ids.push(asset.id);
+        }
+        app_state.insert_folder_contents(&folder, &ids).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(app_state.clone()))
+                .service(web::scope("/api").configure(export::config)),
+        )
+        .await;
+        let token = cakung_barat_server::auth::generate_access_token("admin-id", "admin").unwrap();
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri("/api/admin/export/postings.csv?from=1987-01-01&to=1987-01-31")
+                .insert_header(("Authorization", format!("Bearer {}", token)))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(resp.headers().get("content-type").unwrap(), "text/csv; charset=utf-8");
+        let disposition = resp.headers().get("content-disposition").unwrap().to_str().unwrap();
+        assert!(disposition.contains("cakung-barat-postings-"), "{}", disposition);
+
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        let body = body.strip_prefix(BOM).expect("starts with a BOM");
+        assert!(body.starts_with("title,category,date,asset_count\r\n"), "{}", body);
+        let records: Vec<&str> = body
+            .split("\r\n")
+            .filter(|record| record.contains(&category))
+            .collect();
+        assert_eq!(
+            records,
+            [
+                format!("\"Rapat RW 03, \"\"Tujuh Belasan\"\"\nBalai warga\",{},1987-01-10,2", category),
+                format!("Kerja bakti,{},1987-01-31,0", category),
+            ]
+        );
+
+        for post in &posts {
+            app_state.delete_post(&post.id).await.unwrap();
+        }
+        cleanup_test_data(&pool).await;
+    }
 }
@@ -0,0 +1,159 @@
+//! Tests for the database circuit breaker
+
+mod common;
+
+use actix_web::http::header;
+use actix_web::{test, web, App};
+use cakung_barat_server::db::circuit::{CircuitBreaker, CircuitState};
+use cakung_barat_server::db::{AppState, DbError};
+use cakung_barat_server::storage::LocalStorage;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+fn connection_error() -> DbError {
+    DbError::from(sqlx::Error::PoolClosed)
+}
+
+/// A state whose pool is closed, so every query fails with a connection error
+async fn closed_pool_state(breaker: CircuitBreaker) -> AppState {
+    let pool = common::unreachable_pool();
+    pool.close().await;
+    common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .with_pool(pool)
+        .with_circuit_breaker(breaker)
+        .build()
+        .unwrap()
+}
+
+#[actix_web::test]
+async fn test_opens_after_consecutive_connection_errors() {
+    let breaker = CircuitBreaker::new(3, Duration::from_secs(30));
+
+    for _ in 0..2 {
+        breaker.admit().unwrap();
+        breaker.record(Some(&connection_error()));
+    }
+    // A query error means the database answered, which resets the count
+    breaker.admit().unwrap();
+    breaker.record(Some(&DbError::from(sqlx::Error::RowNotFound)));
+    for _ in 0..2 {
+        breaker.admit().unwrap();
+        breaker.record(Some(&connection_error()));
+    }
+    assert_eq!(breaker.state(), CircuitState::Closed);
+
+    breaker.admit().unwrap();
+    breaker.record(Some(&connection_error()));
+    assert_eq!(breaker.state(), CircuitState::Open);
+    match breaker.admit().unwrap_err() {
+        DbError::Unavailable { retry_after_secs } => {
+            assert!(
+                (29..=30).contains(&retry_after_secs),
+                "{}",
+                retry_after_secs
+            )
+        }
+        other => panic!("expected Unavailable, got {:?}", other),
+    }
+}
+
+#[actix_web::test]
+async fn test_half_open_probe_closes_or_reopens() {
+    let cooldown = Duration::from_millis(50);
+    let breaker = CircuitBreaker::new(1, cooldown);
+    breaker.admit().unwrap();
+    breaker.record(Some(&connection_error()));
+    assert!(breaker.admit().unwrap_err().is_unavailable());
+
+    // A failed probe opens the circuit again
+    tokio::time::sleep(cooldown).await;
+    breaker.admit().unwrap();
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    assert!(breaker.admit().is_err(), "only one probe at a time");
+    breaker.record(Some(&connection_error()));
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    // A successful probe closes it
+    tokio::time::sleep(cooldown).await;
+    breaker.admit().unwrap();
+    breaker.record(None);
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    breaker.admit().unwrap();
+}
+
+#[actix_web::test]
+async fn test_gauge_follows_the_state() {
+    let registry = prometheus::Registry::new();
+    let breaker = CircuitBreaker::new(1, Duration::from_secs(30));
+    breaker.register(&registry).unwrap();
+    let gauge = || {
+        registry
+            .gather()
+            .into_iter()
+            .find(|family| family.get_name() == "cakung_barat_server_db_circuit_state")
+            .map(|family| family.get_metric()[0].get_gauge().get_value())
+            .unwrap()
+    };
+
+    assert_eq!(gauge(), 0.0);
+    breaker.admit().unwrap();
+    breaker.record(Some(&connection_error()));
+    assert_eq!(gauge(), 2.0);
+}
+
+#[actix_web::test]
+async fn test_closed_pool_fails_fast_with_503() {
+    let state = closed_pool_state(CircuitBreaker::new(2, Duration::from_secs(30))).await;
+
+    for _ in 0..2 {
+        assert!(state.get_all_posts().await.unwrap_err().is_connection());
+    }
+    assert_eq!(state.db_circuit.state(), CircuitState::Open);
+    let started = Instant::now();
+    assert!(state.get_all_assets().await.unwrap_err().is_unavailable());
+    assert!(started.elapsed() < Duration::from_millis(50));
+
+    let app = test::init_service(
+        App::new().app_data(web::Data::new(state)).service(
+            web::scope("/api")
+                .route(
+                    "/postings",
+                    web::get().to(cakung_barat_server::posting::handlers::get_all_postings),
+                )
+                .route(
+                    "/assets/serve/{filename}",
+                    web::get().to(cakung_barat_server::asset::handlers::serve_asset),
+                ),
+        ),
+    )
+    .await;
+    for uri in ["/api/postings", "/api/assets/serve/foto.jpg"] {
+        let resp = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        assert_eq!(resp.status(), 503, "{}", uri);
+        let retry_after: u64 = resp
+            .headers()
+            .get(header::RETRY_AFTER)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((1..=30).contains(&retry_after), "{}", retry_after);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"], "ServiceUnavailable", "{}", uri);
+    }
+}
+
+#[actix_web::test]
+async fn test_closed_pool_is_probed_after_the_cooldown() {
+    let cooldown = Duration::from_millis(100);
+    let state = closed_pool_state(CircuitBreaker::new(1, cooldown)).await;
+
+    assert!(state.get_all_posts().await.unwrap_err().is_connection());
+    assert!(state.get_all_posts().await.unwrap_err().is_unavailable());
+
+    // The probe reaches the pool, and the pool is still closed
+    tokio::time::sleep(cooldown).await;
+    assert!(state.get_all_posts().await.unwrap_err().is_connection());
+    assert_eq!(state.db_circuit.state(), CircuitState::Open);
+}
@@ -0,0 +1,70 @@
+//! Tests for the database call latency histogram
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::db::metrics::DbMetrics;
+use cakung_barat_server::{asset, posting};
+use std::time::Duration;
+
+#[actix_web::test]
+async fn test_handler_call_records_db_histogram_samples() {
+    let state = web::Data::new(common::test_state());
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .route(
+                "/api/postings/{id}",
+                web::get().to(posting::handlers::get_posting_by_id),
+            )
+            .route(
+                "/api/assets/{id}",
+                web::get().to(asset::handlers::get_asset_by_id),
+            ),
+    )
+    .await;
+
+    assert_eq!(state.db_metrics.sample_count("get_post_by_id"), 0);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/postings/{}", uuid::Uuid::new_v4()))
+        .to_request();
+    test::call_service(&app, req).await;
+    assert_eq!(state.db_metrics.sample_count("get_post_by_id"), 1);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/assets/{}", uuid::Uuid::new_v4()))
+        .to_request();
+    test::call_service(&app, req).await;
+    assert_eq!(state.db_metrics.sample_count("get_asset_by_id"), 1);
+    assert_eq!(state.db_metrics.sample_count("get_post_by_id"), 1);
+}
+
+#[actix_web::test]
+async fn test_empty_id_list_skips_database() {
+    let state = web::Data::new(common::test_state());
+
+    let assets = state.get_assets_by_ids(&vec![]).await.unwrap();
+
+    assert!(assets.is_empty());
+    assert_eq!(state.db_metrics.sample_count("get_assets_by_ids"), 0);
+}
+
+#[actix_web::test]
+async fn test_histogram_is_exported_by_registry() {
+    let metrics = DbMetrics::new(Duration::from_millis(1));
+    let registry = prometheus::Registry::new();
+    metrics.register(&registry).unwrap();
+
+    // Above the threshold: recorded and logged as slow
+    metrics.observe("get_all_posts", Duration::from_millis(5));
+
+    let family = registry
+        .gather()
+        .into_iter()
+        .find(|family| family.get_name() == "cakung_barat_server_db_query_duration_seconds")
+        .expect("histogram registered");
+    let metric = &family.get_metric()[0];
+    assert_eq!(metric.get_label()[0].get_value(), "get_all_posts");
+    assert_eq!(metric.get_histogram().get_sample_count(), 1);
+}
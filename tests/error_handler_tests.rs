@@ -0,0 +1,128 @@
+//! Tests for structured JSON errors from extractors and unknown routes
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::{error_handlers, posting};
+use serde_json::Value;
+
+macro_rules! test_app {
+    ($limit:expr) => {
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new(common::test_state()))
+                .app_data(error_handlers::json_config($limit))
+                .app_data(error_handlers::path_config())
+                .app_data(error_handlers::query_config())
+                .service(
+                    web::scope("/api")
+                        .service(
+                            web::resource("/postings")
+                                .route(web::get().to(posting::handlers::get_all_postings))
+                                .route(web::post().to(posting::handlers::create_posting)),
+                        )
+                        .service(
+                            web::resource("/postings/{id}")
+                                .route(web::get().to(posting::handlers::get_posting_by_id)),
+                        ),
+                )
+                .default_service(web::route().to(error_handlers::not_found)),
+        )
+        .await
+    };
+}
+
+#[actix_web::test]
+async fn test_malformed_json_returns_error_response() {
+    let app = test_app!(error_handlers::DEFAULT_JSON_PAYLOAD_LIMIT);
+
+    let req = test::TestRequest::post()
+        .uri("/api/postings")
+        .insert_header(("Content-Type", "application/json"))
+        .set_payload("{ \"title\": \"Rapat\", ")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "BadRequest");
+    assert!(!body["message"].as_str().unwrap().is_empty());
+    assert!(body["timestamp"].is_string());
+}
+
+#[actix_web::test]
+async fn test_missing_json_field_returns_error_response() {
+    let app = test_app!(error_handlers::DEFAULT_JSON_PAYLOAD_LIMIT);
+
+    let req = test::TestRequest::post()
+        .uri("/api/postings")
+        .set_json(serde_json::json!({ "title": "Rapat" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "BadRequest");
+    assert!(body["message"].as_str().unwrap().contains("category"));
+}
+
+#[actix_web::test]
+async fn test_json_payload_limit() {
+    let app = test_app!(16);
+
+    let req = test::TestRequest::post()
+        .uri("/api/postings")
+        .set_json(serde_json::json!({
+            "title": "A title that is long enough",
+            "category": "Berita",
+            "excerpt": "Isi"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 413);
+
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "PayloadTooLarge");
+}
+
+#[actix_web::test]
+async fn test_invalid_uuid_path_returns_error_response() {
+    let app = test_app!(error_handlers::DEFAULT_JSON_PAYLOAD_LIMIT);
+
+    let req = test::TestRequest::get()
+        .uri("/api/postings/not-a-uuid")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "BadRequest");
+}
+
+#[actix_web::test]
+async fn test_invalid_query_returns_error_response() {
+    let app = test_app!(error_handlers::DEFAULT_JSON_PAYLOAD_LIMIT);
+
+    let req = test::TestRequest::get()
+        .uri("/api/postings?page=abc")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "BadRequest");
+}
+
+#[actix_web::test]
+async fn test_unknown_route_returns_not_found_json() {
+    let app = test_app!(error_handlers::DEFAULT_JSON_PAYLOAD_LIMIT);
+
+    let req = test::TestRequest::get()
+        .uri("/api/nothing-here")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "NotFound");
+}
@@ -0,0 +1,141 @@
+//! Tests for the orientation fix and EXIF removal of JPEG uploads
+
+mod common;
+
+use actix_web::http::header;
+use actix_web::{test, web, App};
+use cakung_barat_server::asset::exif_cleanup::{apply_orientation, strip_exif};
+use cakung_barat_server::asset::handlers::upload_asset;
+use cakung_barat_server::config::UploadConfig;
+use cakung_barat_server::storage::LocalStorage;
+use image::{DynamicImage, GenericImageView, RgbImage};
+use std::io::Cursor;
+use std::sync::Arc;
+
+/// 4x2 pixels, red top-left corner, EXIF orientation 6 (rotate 90° clockwise)
+/// and GPS coordinates
+const PORTRAIT: &[u8] = include_bytes!("fixtures/orientation_6_gps.jpg");
+/// 3x5 pixels, no metadata
+const PNG: &[u8] = include_bytes!("fixtures/no_exif.png");
+
+const BOUNDARY: &str = "----cakung-barat-boundary";
+
+fn has_exif(data: &[u8]) -> bool {
+    exif::Reader::new()
+        .read_from_container(&mut Cursor::new(data))
+        .is_ok()
+}
+
+/// Upload `content` as `filename` and return the stored bytes. The database
+/// is unreachable, so only the storage side of the upload happens.
+async fn upload_and_read(upload: UploadConfig, filename: &str, content: &[u8]) -> Vec<u8> {
+    let dir = tempfile::tempdir().unwrap();
+    let app_state = common::state_builder(Arc::new(LocalStorage::new(dir.path().to_path_buf())))
+        .with_upload_config(upload)
+        .build()
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state))
+            .route("/api/assets", web::post().to(upload_asset)),
+    )
+    .await;
+
+    let mut body = format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\n\
+         Content-Type: application/octet-stream\r\n\r\n",
+        BOUNDARY, filename
+    )
+    .into_bytes();
+    body.extend_from_slice(content);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", BOUNDARY).as_bytes());
+    let req = test::TestRequest::post()
+        .uri("/api/assets")
+        .insert_header((
+            header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={}", BOUNDARY),
+        ))
+        .set_payload(body)
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let entry = std::fs::read_dir(dir.path()).unwrap().next().unwrap();
+    std::fs::read(entry.unwrap().path()).unwrap()
+}
+
+#[actix_web::test]
+async fn test_portrait_photo_is_turned_upright_without_exif() {
+    assert!(has_exif(PORTRAIT));
+    assert_eq!(
+        image::load_from_memory(PORTRAIT).unwrap().dimensions(),
+        (4, 2)
+    );
+
+    let stripped = strip_exif(PORTRAIT).unwrap();
+    assert!(!has_exif(&stripped));
+    let upright = image::load_from_memory(&stripped).unwrap();
+    assert_eq!(upright.dimensions(), (2, 4));
+    // The red corner is now at the top right
+    assert!(
+        upright.get_pixel(1, 0)[0] > 200,
+        "{:?}",
+        upright.get_pixel(1, 0)
+    );
+    assert!(
+        upright.get_pixel(0, 0)[0] < 100,
+        "{:?}",
+        upright.get_pixel(0, 0)
+    );
+}
+
+#[actix_web::test]
+async fn test_files_without_exif_pass_through() {
+    assert_eq!(strip_exif(PNG), None);
+    assert_eq!(strip_exif(b"not an image"), None);
+
+    // Already stripped, so nothing left to do
+    let stripped = strip_exif(PORTRAIT).unwrap();
+    assert_eq!(strip_exif(&stripped), None);
+
+    // EXIF in front of data that is not a decodable JPEG
+    let mut truncated = PORTRAIT.to_vec();
+    truncated.truncate(PORTRAIT.len() - 200);
+    assert_eq!(strip_exif(&truncated), None);
+}
+
+#[actix_web::test]
+async fn test_every_orientation() {
+    let image = DynamicImage::ImageRgb8(RgbImage::new(4, 2));
+    for orientation in 1..=4 {
+        assert_eq!(
+            apply_orientation(image.clone(), orientation).dimensions(),
+            (4, 2)
+        );
+    }
+    for orientation in 5..=8 {
+        assert_eq!(
+            apply_orientation(image.clone(), orientation).dimensions(),
+            (2, 4)
+        );
+    }
+}
+
+#[actix_web::test]
+async fn test_uploads_are_stripped_unless_disabled() {
+    let stored = upload_and_read(UploadConfig::default(), "rumah.jpg", PORTRAIT).await;
+    assert!(!has_exif(&stored));
+    assert_eq!(
+        image::load_from_memory(&stored).unwrap().dimensions(),
+        (2, 4)
+    );
+
+    let stored = upload_and_read(UploadConfig::default(), "peta.png", PNG).await;
+    assert_eq!(stored, PNG);
+
+    let disabled = UploadConfig {
+        strip_exif: false,
+        ..UploadConfig::default()
+    };
+    let stored = upload_and_read(disabled, "rumah.jpg", PORTRAIT).await;
+    assert_eq!(stored, PORTRAIT);
+}
@@ -0,0 +1,84 @@
+//! Tests for the CSV exports
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::auth::generate_access_token;
+use cakung_barat_server::export::{config, csv_row, escape_field, export_filename};
+use cakung_barat_server::storage::LocalStorage;
+use cakung_barat_server::AppState;
+use chrono::NaiveDate;
+use std::sync::Arc;
+
+fn app_state() -> web::Data<AppState> {
+    // Every request here is answered before the database is reached
+    web::Data::new(
+        common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+            .build()
+            .unwrap(),
+    )
+}
+
+#[actix_web::test]
+async fn test_fields_are_escaped() {
+    assert_eq!(escape_field("Kerja bakti"), "Kerja bakti");
+    assert_eq!(escape_field("RW 03, RW 04"), "\"RW 03, RW 04\"");
+    assert_eq!(
+        escape_field("Lomba \"Tujuh Belasan\""),
+        "\"Lomba \"\"Tujuh Belasan\"\"\""
+    );
+    assert_eq!(
+        escape_field("Baris satu\nbaris dua"),
+        "\"Baris satu\nbaris dua\""
+    );
+    assert_eq!(escape_field("Akhir\r"), "\"Akhir\r\"");
+    assert_eq!(escape_field(""), "");
+
+    assert_eq!(
+        csv_row(&["Rapat, \"RW\"", "Kegiatan", "2024-08-17", "3"]),
+        "\"Rapat, \"\"RW\"\"\",Kegiatan,2024-08-17,3\r\n"
+    );
+}
+
+#[actix_web::test]
+async fn test_filename_is_dated() {
+    let date = NaiveDate::from_ymd_opt(2024, 8, 31).unwrap();
+    assert_eq!(
+        export_filename("postings", date),
+        "cakung-barat-postings-2024-08-31.csv"
+    );
+}
+
+#[actix_web::test]
+async fn test_exports_require_a_token_and_a_valid_range() {
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state())
+            .service(web::scope("/api").configure(config)),
+    )
+    .await;
+
+    for uri in [
+        "/api/admin/export/postings.csv",
+        "/api/admin/export/assets.csv",
+    ] {
+        let resp = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        assert_eq!(resp.status(), 401, "{}", uri);
+    }
+
+    let token = generate_access_token("admin-id", "admin").unwrap();
+    for uri in [
+        "/api/admin/export/postings.csv?from=2024-09-01&to=2024-08-01",
+        "/api/admin/export/postings.csv?from=kemarin",
+    ] {
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri(uri)
+                .insert_header(("Authorization", format!("Bearer {}", token)))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), 400, "{}", uri);
+    }
+}
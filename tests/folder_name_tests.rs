@@ -0,0 +1,197 @@
+//! Tests for folder name validation and the virtual "others" folder
+
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App};
+use cakung_barat_server::asset::folder_name::{
+    is_internal_folder, normalize_folder_name, post_folder_name, submission_folder_name,
+    FolderNameError, MAX_FOLDER_NAME_CHARS,
+};
+use cakung_barat_server::asset::handlers::{
+    create_folder_handler, dissolve_others_folder, list_folder_handler,
+};
+use cakung_barat_server::storage::LocalStorage;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[actix_web::test]
+async fn test_names_are_normalized() {
+    let cases = [
+        ("berita", "berita"),
+        ("  Berita  ", "berita"),
+        ("BERITA", "berita"),
+        ("Kegiatan RT 05", "kegiatan rt 05"),
+        ("foto_kegiatan-2024", "foto_kegiatan-2024"),
+        ("Rapat (Januari), 2024", "rapat (januari), 2024"),
+        ("v1.2", "v1.2"),
+        // "e" followed by a combining acute accent composes to "é"
+        ("Cafe\u{301}", "caf\u{e9}"),
+        ("Caf\u{c9}", "caf\u{e9}"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(
+            normalize_folder_name(input).as_deref(),
+            Ok(expected),
+            "{:?}",
+            input
+        );
+    }
+    let longest = "a".repeat(MAX_FOLDER_NAME_CHARS);
+    assert_eq!(normalize_folder_name(&longest), Ok(longest.clone()));
+}
+
+#[actix_web::test]
+async fn test_invalid_names_are_rejected() {
+    let too_long = "a".repeat(MAX_FOLDER_NAME_CHARS + 1);
+    let cases = [
+        ("", FolderNameError::Empty),
+        ("   ", FolderNameError::Empty),
+        (
+            too_long.as_str(),
+            FolderNameError::TooLong(MAX_FOLDER_NAME_CHARS),
+        ),
+        ("posts/abc", FolderNameError::Separator),
+        ("../../x", FolderNameError::Separator),
+        ("a\\b", FolderNameError::Separator),
+        ("..", FolderNameError::DotSegment),
+        (".", FolderNameError::DotSegment),
+        ("a..b", FolderNameError::DotSegment),
+        ("berita\u{0}", FolderNameError::ControlCharacter),
+        ("be\trita", FolderNameError::ControlCharacter),
+        ("berita 🎉", FolderNameError::UnsupportedCharacter('🎉')),
+        ("50%", FolderNameError::UnsupportedCharacter('%')),
+        ("a?b", FolderNameError::UnsupportedCharacter('?')),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(normalize_folder_name(input), Err(expected), "{:?}", input);
+    }
+}
+
+#[actix_web::test]
+async fn test_post_folders_are_namespaced() {
+    let id = Uuid::new_v4();
+    assert_eq!(post_folder_name(id), format!("posts/{}", id));
+}
+
+#[actix_web::test]
+async fn test_create_folder_reports_the_broken_rule() {
+    let app_state = common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .build()
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state))
+            .route("/api/assets/folders", web::post().to(create_folder_handler)),
+    )
+    .await;
+
+    for (name, message) in [
+        ("../../x", "cannot contain '/'"),
+        ("berita 🎉", "cannot contain '🎉'"),
+        ("  ", "cannot be empty"),
+    ] {
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::post()
+                .uri("/api/assets/folders")
+                .set_json(json!({ "folder_name": name }))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{:?}", name);
+        let body: Value = test::read_body_json(resp).await;
+        assert!(
+            body["message"].as_str().unwrap().contains(message),
+            "{:?}: {}",
+            name,
+            body
+        );
+    }
+}
+
+#[actix_web::test]
+async fn test_others_is_virtual() {
+    let app_state = common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .build()
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state))
+            .route("/api/assets/folders", web::post().to(create_folder_handler))
+            .route(
+                "/api/admin/folders/others/dissolve",
+                web::post().to(dissolve_others_folder),
+            ),
+    )
+    .await;
+
+    // "others" always exists, so it cannot be created as a real folder
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/assets/folders")
+            .set_json(json!({ "folder_name": " Others " }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/admin/folders/others/dissolve")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn test_internal_folders_are_listed_only_to_admins() {
+    let app_state = common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .build()
+        .unwrap();
+    let app = test::init_service(App::new().app_data(web::Data::new(app_state)).route(
+        "/api/assets/folders/{folder_name:.*}",
+        web::get().to(list_folder_handler),
+    ))
+    .await;
+
+    let internal = [
+        "others".to_string(),
+        post_folder_name(Uuid::new_v4()),
+        submission_folder_name(Uuid::new_v4()),
+    ];
+    for folder in &internal {
+        assert!(is_internal_folder(folder), "{}", folder);
+        for include in ["", "?include=ids", "?include=count"] {
+            let resp = test::call_service(
+                &app,
+                test::TestRequest::get()
+                    .uri(&format!("/api/assets/folders/{}{}", folder, include))
+                    .to_request(),
+            )
+            .await;
+            assert_eq!(
+                resp.status(),
+                StatusCode::UNAUTHORIZED,
+                "{}{}",
+                folder,
+                include
+            );
+        }
+    }
+
+    // Other folders stay public; the lookup itself fails without a database
+    assert!(!is_internal_folder("galeri"));
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/api/assets/folders/galeri")
+            .to_request(),
+    )
+    .await;
+    assert_ne!(resp.status(), StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,118 @@
+//! Tests for rebuilding post folder links from storage
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::asset::folder_rebuild::{plan_rebuild, FolderRebuild};
+use cakung_barat_server::asset::handlers::rebuild_asset_folders;
+use cakung_barat_server::asset::models::Asset;
+use cakung_barat_server::storage::LocalStorage;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn asset(filename: &str) -> Asset {
+    Asset::new(
+        "Foto".to_string(),
+        filename.to_string(),
+        format!("/assets/serve/{}", filename),
+        None,
+    )
+}
+
+fn objects(paths: &[&str]) -> Vec<String> {
+    paths.iter().map(|path| path.to_string()).collect()
+}
+
+#[actix_web::test]
+async fn test_plan_reports_added_and_removed_links() {
+    let kept = asset("a_001.jpg");
+    let missing = asset("b_002.jpg");
+    let new = asset("c_003.jpg");
+    let current = HashMap::from([("posts/a".to_string(), vec![kept.id, missing.id])]);
+
+    let (folders, unmatched) = plan_rebuild(
+        &objects(&[
+            "posts/a/a_001.jpg",
+            "posts/a/c_003.jpg",
+            "posts/a/asing.jpg",
+        ]),
+        &[kept.clone(), missing.clone(), new.clone()],
+        &current,
+    );
+    assert_eq!(
+        folders,
+        vec![FolderRebuild {
+            folder: "posts/a".to_string(),
+            added: vec![new.id],
+            removed: vec![missing.id],
+        }]
+    );
+    assert_eq!(unmatched, vec!["posts/a/asing.jpg".to_string()]);
+}
+
+#[actix_web::test]
+async fn test_plan_matches_full_paths_and_groups_by_folder() {
+    let nested = asset("posts/b/lampiran/surat.pdf");
+    let first = asset("x_001.png");
+    let second = asset("y_001.png");
+
+    let (folders, unmatched) = plan_rebuild(
+        &objects(&[
+            "posts/b/lampiran/surat.pdf",
+            "posts/c/x_001.png",
+            "posts/c/y_001.png",
+        ]),
+        &[nested.clone(), first.clone(), second.clone()],
+        &HashMap::new(),
+    );
+    assert!(unmatched.is_empty());
+    assert_eq!(folders.len(), 2);
+    assert_eq!(folders[0].folder, "posts/b");
+    assert_eq!(folders[0].added, vec![nested.id]);
+    assert_eq!(folders[1].folder, "posts/c");
+    assert_eq!(folders[1].added, vec![first.id, second.id]);
+}
+
+#[actix_web::test]
+async fn test_plan_leaves_unchanged_and_unknown_folders_alone() {
+    let linked = asset("a_001.jpg");
+    let other = asset("z_009.jpg");
+    let current = HashMap::from([
+        ("posts/a".to_string(), vec![linked.id]),
+        ("posts/d".to_string(), vec![other.id]),
+    ]);
+
+    let (folders, unmatched) = plan_rebuild(
+        &objects(&[
+            "posts/a/a_001.jpg",
+            "posts/a/placeholder.txt",
+            // Only a placeholder and an unknown file: links stay as they are
+            "posts/d/placeholder.txt",
+            "posts/d/asing.jpg",
+            // Not inside a post folder
+            "posts/lepas.jpg",
+        ]),
+        &[linked, other],
+        &current,
+    );
+    assert!(folders.is_empty());
+    assert_eq!(unmatched, vec!["posts/d/asing.jpg".to_string()]);
+}
+
+#[actix_web::test]
+async fn test_rebuild_requires_admin() {
+    let state = common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .build()
+        .unwrap();
+    let app = test::init_service(App::new().app_data(web::Data::new(state)).route(
+        "/api/admin/assets/rebuild-folders",
+        web::post().to(rebuild_asset_folders),
+    ))
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/admin/assets/rebuild-folders?dry_run=true")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+}
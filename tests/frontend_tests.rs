@@ -0,0 +1,118 @@
+//! Tests for serving the frontend bundle with the SPA fallback
+
+use actix_web::http::header::CACHE_CONTROL;
+use actix_web::{test, web, App, HttpResponse};
+use cakung_barat_server::error_handlers;
+use cakung_barat_server::frontend::{is_hashed_asset, is_server_path, Frontend};
+use serde_json::Value;
+
+const INDEX: &str = "<!doctype html><div id=\"app\"></div>";
+const SCRIPT: &str = "console.log('kelurahan')";
+
+fn dist() -> tempfile::TempDir {
+    let dist = tempfile::tempdir().unwrap();
+    std::fs::write(dist.path().join("index.html"), INDEX).unwrap();
+    std::fs::write(dist.path().join("favicon.ico"), "ico").unwrap();
+    std::fs::create_dir(dist.path().join("assets")).unwrap();
+    std::fs::write(dist.path().join("assets/index-B2x9Kq1a.js"), SCRIPT).unwrap();
+    dist
+}
+
+macro_rules! test_app {
+    ($frontend:expr) => {
+        test::init_service(
+            App::new()
+                .service(web::scope("/api").route(
+                    "/health",
+                    web::get().to(|| async { HttpResponse::Ok().json("ok") }),
+                ))
+                .route("/mcp", web::post().to(HttpResponse::Ok))
+                .configure(|cfg| $frontend.config(cfg))
+                .default_service(web::route().to(error_handlers::not_found)),
+        )
+        .await
+    };
+}
+
+#[actix_web::test]
+async fn test_hashed_asset_names() {
+    for path in [
+        "/assets/index-B2x9Kq1a.js",
+        "/assets/app.3f2a1b9c.css",
+        "/assets/vendor-react-DiwrgTda.js",
+        "/logo-1a2b3c4d5e.svg",
+    ] {
+        assert!(is_hashed_asset(path), "{}", path);
+    }
+    for path in [
+        "/",
+        "/index.html",
+        "/favicon.ico",
+        "/assets/jquery-bootstrap.js",
+        "/berita/kerja-bakti-rw-05",
+        "/assets/index-B2x9.js",
+    ] {
+        assert!(!is_hashed_asset(path), "{}", path);
+    }
+}
+
+#[actix_web::test]
+async fn test_server_paths() {
+    for path in ["/api", "/api/postings", "/mcp", "/mcp/sessions"] {
+        assert!(is_server_path(path), "{}", path);
+    }
+    for path in ["/", "/apiary", "/berita/api", "/mcp-info"] {
+        assert!(!is_server_path(path), "{}", path);
+    }
+}
+
+#[actix_web::test]
+async fn test_dist_without_index_is_rejected() {
+    let dist = tempfile::tempdir().unwrap();
+    assert!(Frontend::new(dist.path()).is_err());
+    assert!(Frontend::new(dist.path().join("missing")).is_err());
+}
+
+#[actix_web::test]
+async fn test_files_are_served_with_cache_headers() {
+    let dist = dist();
+    let frontend = Frontend::new(dist.path()).unwrap();
+    let app = test_app!(frontend);
+
+    for (uri, body, cache_control) in [
+        ("/", INDEX, "no-cache"),
+        ("/index.html", INDEX, "no-cache"),
+        ("/favicon.ico", "ico", "no-cache"),
+        ("/assets/index-B2x9Kq1a.js", SCRIPT, "public, max-age=31536000, immutable"),
+    ] {
+        let resp = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        assert_eq!(resp.status(), 200, "{}", uri);
+        assert_eq!(resp.headers().get(CACHE_CONTROL).unwrap(), cache_control, "{}", uri);
+        assert_eq!(test::read_body(resp).await, body.as_bytes(), "{}", uri);
+    }
+}
+
+#[actix_web::test]
+async fn test_unknown_paths_fall_back_to_index() {
+    let dist = dist();
+    let frontend = Frontend::new(dist.path()).unwrap();
+    let app = test_app!(frontend);
+
+    // Client-side routes, including ones that look like hashed files
+    for uri in ["/berita/kerja-bakti-rw-05", "/struktur-organisasi", "/assets/index-C3y0Lr2b.js"] {
+        let resp = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        assert_eq!(resp.status(), 200, "{}", uri);
+        assert_eq!(resp.headers().get(CACHE_CONTROL).unwrap(), "no-cache", "{}", uri);
+        assert_eq!(test::read_body(resp).await, INDEX.as_bytes(), "{}", uri);
+    }
+
+    // The API keeps its routes and its JSON 404
+    let resp = test::call_service(&app, test::TestRequest::get().uri("/api/health").to_request()).await;
+    assert_eq!(resp.status(), 200);
+    for uri in ["/api/does-not-exist", "/api", "/mcp/unknown"] {
+        let resp = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        assert_eq!(resp.status(), 404, "{}", uri);
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"], "NotFound", "{}", uri);
+    }
+}
@@ -0,0 +1,92 @@
+//! Tests for the public gallery and its folder whitelist
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::asset::gallery::GalleryConfig;
+use cakung_barat_server::asset::handlers::{get_all_assets_structured, get_gallery};
+use cakung_barat_server::storage::LocalStorage;
+use std::sync::Arc;
+
+fn config(value: &str) -> Result<GalleryConfig, String> {
+    GalleryConfig::from_lookup(|key| (key == "PUBLIC_FOLDERS").then(|| value.to_string()))
+}
+
+fn state(gallery: GalleryConfig) -> cakung_barat_server::db::AppState {
+    common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .with_gallery_config(gallery)
+        .build()
+        .unwrap()
+}
+
+#[actix_web::test]
+async fn test_public_folders_are_normalized() {
+    let gallery = config(" Galeri ,banner,, galeri").unwrap();
+    assert_eq!(gallery.public_folders, vec!["galeri", "banner"]);
+    assert_eq!(gallery.public_name("GALERI"), Some("galeri".to_string()));
+    assert_eq!(gallery.public_name("organization"), None);
+
+    assert!(config("").unwrap().public_folders.is_empty());
+    let err = config("galeri,posts/0f8c7e52").unwrap_err();
+    assert!(err.contains("posts/0f8c7e52"), "{}", err);
+}
+
+#[actix_web::test]
+async fn test_gallery_hides_folders_outside_the_whitelist() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state(config("galeri").unwrap())))
+            .route("/api/gallery", web::get().to(get_gallery)),
+    )
+    .await;
+
+    for folder in ["organization", "posts%2F0f8c7e52", "others"] {
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/gallery?folder={}", folder))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 404, "{}", folder);
+    }
+
+    for query in ["page=0", "limit=0", "limit=101"] {
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/gallery?{}", query))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 400, "{}", query);
+    }
+}
+
+#[actix_web::test]
+async fn test_gallery_without_public_folders_is_empty() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state(GalleryConfig::default())))
+            .route("/api/gallery", web::get().to(get_gallery)),
+    )
+    .await;
+
+    let body: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/api/gallery").to_request(),
+    )
+    .await;
+    assert_eq!(body["total"], 0);
+    assert_eq!(body["page"], 1);
+    assert_eq!(body["limit"], 20);
+    assert_eq!(body["assets"], serde_json::json!([]));
+}
+
+#[actix_web::test]
+async fn test_asset_listing_requires_admin() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state(GalleryConfig::default())))
+            .route("/api/assets", web::get().to(get_all_assets_structured)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/api/assets").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+}
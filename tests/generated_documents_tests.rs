@@ -0,0 +1,98 @@
+//! Tests for the generated-letter log and its admin endpoints
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::auth::generate_access_token;
+use cakung_barat_server::generated_documents::{DocumentLogConfig, NikHasher};
+use cakung_barat_server::mcp::generators::{Requester, SuratPengantarSkckRequest};
+use cakung_barat_server::storage::LocalStorage;
+use cakung_barat_server::AppState;
+use std::sync::Arc;
+
+const NIK: &str = "3175021234560001";
+
+fn bearer() -> (&'static str, String) {
+    let token = generate_access_token("admin-id", "admin").unwrap();
+    ("Authorization", format!("Bearer {}", token))
+}
+
+fn app_state() -> web::Data<AppState> {
+    web::Data::new(
+        common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+            .build()
+            .unwrap(),
+    )
+}
+
+#[actix_web::test]
+async fn test_nik_hash_is_keyed_and_hides_the_nik() {
+    let hasher = NikHasher::new(Some("garam-rahasia"));
+
+    let hash = hasher.hash(NIK);
+    assert_eq!(hash.len(), 64);
+    assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+    assert!(!hash.contains(NIK));
+    assert_eq!(hash, NikHasher::new(Some("garam-rahasia")).hash(NIK));
+    assert_eq!(hash, hasher.hash(&format!(" {} ", NIK)));
+    assert_ne!(hash, NikHasher::new(Some("garam-lain")).hash(NIK));
+    assert_ne!(hash, hasher.hash("3175021234560002"));
+}
+
+#[actix_web::test]
+async fn test_unsalted_hashes_differ_between_hashers() {
+    assert_ne!(
+        NikHasher::new(None).hash(NIK),
+        NikHasher::new(None).hash(NIK)
+    );
+}
+
+#[actix_web::test]
+async fn test_config_reads_the_salt() {
+    let config = DocumentLogConfig::from_lookup(|key| {
+        (key == "DOCUMENT_NIK_SALT").then(|| " garam ".to_string())
+    });
+    assert_eq!(config.nik_salt.as_deref(), Some("garam"));
+
+    let config = DocumentLogConfig::from_lookup(|_| Some("  ".to_string()));
+    assert_eq!(config, DocumentLogConfig::default());
+}
+
+#[actix_web::test]
+async fn test_requester_is_the_applicant() {
+    let request = SuratPengantarSkckRequest::sample();
+
+    let (name, nik) = request.requester();
+    assert_eq!(name, request.pemohon.nama);
+    assert_eq!(nik, request.pemohon.nik);
+}
+
+#[actix_web::test]
+async fn test_document_endpoints_reject_bad_requests() {
+    let app =
+        test::init_service(App::new().app_data(app_state()).service(
+            web::scope("/api").configure(cakung_barat_server::generated_documents::config),
+        ))
+        .await;
+
+    for uri in ["/api/admin/documents", "/api/admin/documents/stats"] {
+        let resp = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        assert_eq!(resp.status(), 401, "{}", uri);
+    }
+
+    for uri in [
+        "/api/admin/documents/stats?from=2025-03-01&to=2025-02-28",
+        "/api/admin/documents?limit=0",
+        "/api/admin/documents?limit=101",
+    ] {
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri(uri)
+                .insert_header(bearer())
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), 400, "{}", uri);
+    }
+}
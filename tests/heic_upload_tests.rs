@@ -0,0 +1,192 @@
+//! Tests for the JPEG conversion of HEIC uploads
+
+mod common;
+
+use actix_web::http::header;
+use actix_web::{test, web, App};
+use cakung_barat_server::asset::handlers::upload_asset;
+use cakung_barat_server::asset::heic::{
+    convert_to_jpeg, is_heic, jpeg_filename, kept_original, HeicConfig,
+};
+use cakung_barat_server::config::UploadConfig;
+use cakung_barat_server::storage::LocalStorage;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// `ftyp` and `meta` boxes of a HEIC file, without image data
+const HEIC_HEADER: &[u8] = include_bytes!("fixtures/heic_header.heic");
+
+const BOUNDARY: &str = "----cakung-barat-boundary";
+
+fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars: HashMap<String, String> = vars
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    move |key| vars.get(key).cloned()
+}
+
+/// A converter that writes a fixed JPEG, standing in for `heif-convert`
+#[cfg(unix)]
+fn fake_converter(dir: &Path) -> String {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join("fake-heif-convert");
+    std::fs::write(
+        &path,
+        "#!/bin/sh\nprintf '\\377\\330\\377\\340jpeg' > \"$2\"\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+fn stored_files(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Upload the fixture as `filename` and return the names in storage. The
+/// database is unreachable, so only the storage side of the upload happens.
+async fn upload_fixture(heic: HeicConfig, filename: &str) -> Vec<String> {
+    let dir = tempfile::tempdir().unwrap();
+    let app_state = common::state_builder(Arc::new(LocalStorage::new(dir.path().to_path_buf())))
+        .with_upload_config(UploadConfig {
+            heic,
+            ..UploadConfig::default()
+        })
+        .build()
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state))
+            .route("/api/assets", web::post().to(upload_asset)),
+    )
+    .await;
+
+    let mut body = format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\n\
+         Content-Type: application/octet-stream\r\n\r\n",
+        BOUNDARY, filename
+    )
+    .into_bytes();
+    body.extend_from_slice(HEIC_HEADER);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", BOUNDARY).as_bytes());
+    let req = test::TestRequest::post()
+        .uri("/api/assets")
+        .insert_header((
+            header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={}", BOUNDARY),
+        ))
+        .set_payload(body)
+        .to_request();
+    test::call_service(&app, req).await;
+
+    stored_files(dir.path())
+}
+
+#[actix_web::test]
+async fn test_heic_is_recognized_by_extension_or_brand() {
+    assert!(is_heic("IMG_0001.HEIC", b""));
+    assert!(is_heic("IMG_0001.heif", b""));
+    // iOS can hand over HEIC photos under a .jpg name
+    assert!(is_heic("IMG_0001.jpg", HEIC_HEADER));
+
+    let mut generic = HEIC_HEADER.to_vec();
+    generic[8..12].copy_from_slice(b"mif1");
+    generic[16..20].copy_from_slice(b"miaf");
+    assert!(is_heic("foto", &generic));
+
+    let mut avif = HEIC_HEADER.to_vec();
+    avif[8..12].copy_from_slice(b"avif");
+    avif[20..24].copy_from_slice(b"avif");
+    assert!(!is_heic("foto.avif", &avif));
+    assert!(!is_heic("foto.jpg", &[0xFF, 0xD8, 0xFF, 0xE0, 0, 0, 0, 0]));
+}
+
+#[actix_web::test]
+async fn test_converted_names() {
+    assert_eq!(jpeg_filename("IMG_0001.HEIC"), "IMG_0001.jpg");
+    assert_eq!(
+        kept_original("abc_IMG_0001_HEIC.jpg").as_deref(),
+        Some("abc_IMG_0001_HEIC.heic")
+    );
+    assert_eq!(kept_original("abc_foto_jpg.jpg"), None);
+    assert_eq!(kept_original("abc_foto_heic.png"), None);
+}
+
+#[actix_web::test]
+async fn test_config_from_lookup() {
+    assert_eq!(
+        HeicConfig::from_lookup(lookup(&[])).unwrap(),
+        HeicConfig::default()
+    );
+    let config = HeicConfig::from_lookup(lookup(&[
+        ("HEIC_CONVERT", "false"),
+        ("HEIC_KEEP_ORIGINAL", "true"),
+        ("HEIC_CONVERT_COMMAND", "magick"),
+    ]))
+    .unwrap();
+    assert!(!config.convert);
+    assert!(config.keep_original);
+    assert_eq!(config.command, "magick");
+    assert!(HeicConfig::from_lookup(lookup(&[("HEIC_KEEP_ORIGINAL", "kadang")])).is_err());
+}
+
+#[actix_web::test]
+async fn test_missing_converter_is_an_error() {
+    let config = HeicConfig {
+        command: "/nonexistent/heif-convert".to_string(),
+        ..HeicConfig::default()
+    };
+    let err = convert_to_jpeg(&config, HEIC_HEADER).await.unwrap_err();
+    assert!(err.contains("Failed to run"), "{}", err);
+}
+
+#[cfg(unix)]
+#[actix_web::test]
+async fn test_heic_upload_is_stored_as_jpeg() {
+    let bin = tempfile::tempdir().unwrap();
+    let heic = HeicConfig {
+        command: fake_converter(bin.path()),
+        ..HeicConfig::default()
+    };
+    let stored = upload_fixture(heic.clone(), "IMG_0001.HEIC").await;
+    assert_eq!(stored.len(), 1, "{:?}", stored);
+    assert!(stored[0].ends_with("_IMG_0001_HEIC.jpg"), "{:?}", stored);
+
+    let stored = upload_fixture(
+        HeicConfig {
+            keep_original: true,
+            ..heic
+        },
+        "IMG_0001.HEIC",
+    )
+    .await;
+    assert_eq!(stored.len(), 2, "{:?}", stored);
+    let jpeg = stored.iter().find(|name| name.ends_with(".jpg")).unwrap();
+    assert!(stored.contains(&kept_original(jpeg).unwrap()));
+}
+
+#[actix_web::test]
+async fn test_heic_that_fails_to_convert_is_stored_as_uploaded() {
+    let heic = HeicConfig {
+        command: "/nonexistent/heif-convert".to_string(),
+        ..HeicConfig::default()
+    };
+    let stored = upload_fixture(heic, "IMG_0001.HEIC").await;
+    assert_eq!(stored.len(), 1, "{:?}", stored);
+    assert!(stored[0].ends_with("_IMG_0001_HEIC.HEIC"), "{:?}", stored);
+
+    let disabled = HeicConfig {
+        convert: false,
+        ..HeicConfig::default()
+    };
+    let stored = upload_fixture(disabled, "IMG_0001.HEIC").await;
+    assert!(stored[0].ends_with(".HEIC"), "{:?}", stored);
+}
@@ -0,0 +1,147 @@
+//! Tests for the DEBUG_HTTP_LOG middleware logging failed API calls
+
+use actix_web::middleware::from_fn;
+use actix_web::{test, web, App, HttpResponse};
+use cakung_barat_server::http_debug_log::{
+    log_failed_requests, redact_body, LOG_TARGET, MAX_LOGGED_BODY, REQUEST_ID_HEADER,
+};
+use serde::Deserialize;
+use std::sync::{Mutex, Once};
+
+static RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct CaptureLogger;
+
+impl log::Log for CaptureLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.target() == LOG_TARGET
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            RECORDS.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn capture_logs() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        log::set_logger(&CaptureLogger).unwrap();
+        log::set_max_level(log::LevelFilter::Warn);
+    });
+}
+
+/// Records of one request; tests run in parallel, so each uses its own path
+fn records_for(path: &str) -> Vec<String> {
+    RECORDS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|record| record.contains(&format!(" {} ", path)))
+        .cloned()
+        .collect()
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Registration {
+    username: String,
+    age: u32,
+}
+
+async fn register(_: web::Json<Registration>) -> HttpResponse {
+    HttpResponse::Created().finish()
+}
+
+fn app() -> App<
+    impl actix_web::dev::ServiceFactory<
+        actix_web::dev::ServiceRequest,
+        Config = (),
+        Response = actix_web::dev::ServiceResponse<impl actix_web::body::MessageBody>,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    capture_logs();
+    App::new()
+        .wrap(from_fn(log_failed_requests))
+        .route("/api/register/{case}", web::post().to(register))
+}
+
+#[actix_web::test]
+async fn test_bad_request_logs_redacted_body() {
+    let app = test::init_service(app()).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/register/bad")
+        .insert_header(("Authorization", "Bearer secret-token"))
+        .insert_header((REQUEST_ID_HEADER, "req-42"))
+        .set_json(serde_json::json!({ "username": "admin", "password": "hunter2" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+    assert_eq!(resp.headers().get(REQUEST_ID_HEADER).unwrap(), "req-42");
+
+    let records = records_for("/api/register/bad");
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert!(record.starts_with("POST /api/register/bad -> 400 in "));
+    assert!(record.contains("request id req-42"));
+    assert!(record.contains(r#""username":"admin""#));
+    assert!(record.contains(r#""password":"[REDACTED]""#));
+    assert!(!record.contains("hunter2"));
+    assert!(!record.contains("secret-token"));
+}
+
+#[actix_web::test]
+async fn test_successful_request_is_not_logged() {
+    let app = test::init_service(app()).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/register/ok")
+        .set_json(serde_json::json!({ "username": "admin", "age": 30 }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    assert!(resp.headers().get(REQUEST_ID_HEADER).is_none());
+    assert!(records_for("/api/register/ok").is_empty());
+}
+
+#[actix_web::test]
+async fn test_logged_body_is_truncated() {
+    let app = test::init_service(app()).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/register/large")
+        .set_json(serde_json::json!({ "username": "a".repeat(10_000) }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    let records = records_for("/api/register/large");
+    let body = records[0].split_once("body: ").unwrap().1;
+    assert_eq!(body.len(), MAX_LOGGED_BODY + "...".len());
+}
+
+#[actix_web::test]
+async fn test_redact_body() {
+    assert_eq!(
+        redact_body("application/json", r#"{"new_password": 123, "name": "x"}"#),
+        r#"{"new_password": "[REDACTED]", "name": "x"}"#
+    );
+    // Cut off in the middle of the value
+    assert_eq!(
+        redact_body("application/json", r#"{"password":"hunt"#),
+        r#"{"password":"[REDACTED]""#
+    );
+    assert_eq!(
+        redact_body(
+            "application/x-www-form-urlencoded",
+            "username=admin&password=hunter2&Authorization=x"
+        ),
+        "username=admin&password=[REDACTED]&Authorization=[REDACTED]"
+    );
+}
@@ -0,0 +1,165 @@
+//! Tests for storage request timeouts and retries against a mock Supabase
+
+use cakung_barat_server::http_client::{HttpClientConfig, HttpMetrics, Retry, RetryPolicy};
+use cakung_barat_server::storage::{ObjectStorage, SupabaseConfig, SupabaseStorage};
+use std::collections::HashMap;
+use std::time::Duration;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const UPLOAD_PATH: &str = "/storage/v1/object/bucket/file.png";
+
+fn storage(server: &MockServer, timeout: Duration) -> SupabaseStorage {
+    let config = SupabaseConfig {
+        supabase_url: server.uri(),
+        supabase_anon_key: "anon-key".to_string(),
+        bucket_name: "bucket".to_string(),
+        service_role_key: None,
+        bucket_public: true,
+    };
+    let client = reqwest::Client::builder().timeout(timeout).build().unwrap();
+    let policy = RetryPolicy {
+        attempts: 3,
+        backoff: Duration::from_millis(10),
+    };
+    SupabaseStorage::new(config, client).with_retry(Retry::new(policy, HttpMetrics::new()))
+}
+
+#[tokio::test]
+async fn test_upload_recovers_after_server_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(UPLOAD_PATH))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .expect(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(UPLOAD_PATH))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let storage = storage(&server, Duration::from_secs(5));
+
+    storage.upload_file("file.png", b"data").await.unwrap();
+
+    assert_eq!(storage.retry.metrics.retry_count("upload"), 2);
+}
+
+#[tokio::test]
+async fn test_upload_retries_after_timeout() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(UPLOAD_PATH))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(2)))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(UPLOAD_PATH))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    let storage = storage(&server, Duration::from_millis(200));
+
+    storage.upload_file("file.png", b"data").await.unwrap();
+
+    assert_eq!(storage.retry.metrics.retry_count("upload"), 1);
+}
+
+#[tokio::test]
+async fn test_client_errors_are_not_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("DELETE"))
+        .and(path(UPLOAD_PATH))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let storage = storage(&server, Duration::from_secs(5));
+
+    let err = storage.delete_file("file.png").await.unwrap_err();
+
+    assert!(err.contains("404"), "{}", err);
+    assert_eq!(storage.retry.metrics.retry_count("delete"), 0);
+}
+
+#[tokio::test]
+async fn test_gives_up_after_configured_attempts() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/storage/v1/object/list/bucket"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(3)
+        .mount(&server)
+        .await;
+    let storage = storage(&server, Duration::from_secs(5));
+
+    assert!(storage.list_folder_contents("posts").await.is_err());
+    assert_eq!(storage.retry.metrics.retry_count("list"), 2);
+}
+
+#[tokio::test]
+async fn test_list_recovers_after_server_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/storage/v1/object/list/bucket"))
+        .respond_with(ResponseTemplate::new(502))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/storage/v1/object/list/bucket"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            {"name": "a.png", "id": "1", "metadata": {"size": 3}}
+        ])))
+        .mount(&server)
+        .await;
+    let storage = storage(&server, Duration::from_secs(5));
+
+    let contents = storage.list_folder_contents("posts").await.unwrap();
+
+    assert_eq!(contents.len(), 1);
+    assert_eq!(contents[0].size, Some(3));
+}
+
+#[test]
+fn test_backoff_is_jittered_and_capped() {
+    let policy = RetryPolicy {
+        attempts: 10,
+        backoff: Duration::from_millis(200),
+    };
+
+    for _ in 0..20 {
+        let first = policy.backoff_after(1);
+        assert!(first >= Duration::from_millis(100) && first <= Duration::from_millis(200));
+        let second = policy.backoff_after(2);
+        assert!(second >= Duration::from_millis(200) && second <= Duration::from_millis(400));
+    }
+    assert!(policy.backoff_after(30) <= Duration::from_secs(5));
+}
+
+#[test]
+fn test_config_from_lookup() {
+    let vars: HashMap<&str, &str> = [
+        ("HTTP_CONNECT_TIMEOUT_SECS", "2"),
+        ("HTTP_REQUEST_TIMEOUT_SECS", "10"),
+        ("HTTP_RETRY_ATTEMPTS", "5"),
+    ]
+    .into_iter()
+    .collect();
+    let config = HttpClientConfig::from_lookup(|key| vars.get(key).map(|v| v.to_string())).unwrap();
+
+    assert_eq!(config.connect_timeout_secs, 2);
+    assert_eq!(config.request_timeout_secs, 10);
+    assert_eq!(config.retry_attempts, 5);
+    assert_eq!(config.retry_policy().attempts, 5);
+    assert!(config.build_client().is_ok());
+
+    let invalid = HttpClientConfig::from_lookup(|key| {
+        (key == "HTTP_REQUEST_TIMEOUT_SECS").then(|| "0".to_string())
+    });
+    assert!(invalid.is_err());
+}
@@ -0,0 +1,151 @@
+//! Tests for Indonesian date and number formatting
+
+use cakung_barat_server::i18n::{
+    format_date_id, format_datetime_id, format_datetime_id_in, terbilang, MONTHS,
+};
+use cakung_barat_server::posting::handlers::FormatQuery;
+use cakung_barat_server::posting::models::Post;
+use chrono::{NaiveDate, TimeZone, Utc};
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+#[test]
+fn test_every_month_is_written_in_indonesian() {
+    let expected = [
+        "15 Januari 2025",
+        "15 Februari 2025",
+        "15 Maret 2025",
+        "15 April 2025",
+        "15 Mei 2025",
+        "15 Juni 2025",
+        "15 Juli 2025",
+        "15 Agustus 2025",
+        "15 September 2025",
+        "15 Oktober 2025",
+        "15 November 2025",
+        "15 Desember 2025",
+    ];
+    for (month, expected) in (1..=12).zip(expected) {
+        assert_eq!(format_date_id(date(2025, month, 15)), expected);
+    }
+    assert_eq!(MONTHS.len(), 12);
+}
+
+#[test]
+fn test_dates_are_not_padded() {
+    assert_eq!(format_date_id(date(2025, 11, 12)), "12 November 2025");
+    assert_eq!(format_date_id(date(2026, 1, 1)), "1 Januari 2026");
+    assert_eq!(format_date_id(date(2025, 12, 31)), "31 Desember 2025");
+}
+
+#[test]
+fn test_leap_day() {
+    assert_eq!(format_date_id(date(2024, 2, 29)), "29 Februari 2024");
+    assert_eq!(format_date_id(date(2000, 2, 29)), "29 Februari 2000");
+    assert!(NaiveDate::from_ymd_opt(2025, 2, 29).is_none());
+}
+
+#[test]
+fn test_datetime_is_shown_in_the_zone() {
+    // 23:30 UTC on New Year's Eve is 06:30 on 1 January in Jakarta
+    let late = Utc.with_ymd_and_hms(2025, 12, 31, 23, 30, 0).unwrap();
+    assert_eq!(format_datetime_id(late), "1 Januari 2026 06.30 WIB");
+    assert_eq!(
+        format_datetime_id_in(late, chrono_tz::Asia::Makassar),
+        "1 Januari 2026 07.30 WITA"
+    );
+    assert_eq!(
+        format_datetime_id_in(late, chrono_tz::Asia::Jayapura),
+        "1 Januari 2026 08.30 WIT"
+    );
+
+    let afternoon = Utc.with_ymd_and_hms(2025, 11, 12, 7, 5, 0).unwrap();
+    assert_eq!(format_datetime_id(afternoon), "12 November 2025 14.05 WIB");
+}
+
+#[test]
+fn test_terbilang_small_numbers() {
+    let expected = [
+        (0, "nol"),
+        (1, "satu"),
+        (9, "sembilan"),
+        (10, "sepuluh"),
+        (11, "sebelas"),
+        (12, "dua belas"),
+        (19, "sembilan belas"),
+        (20, "dua puluh"),
+        (21, "dua puluh satu"),
+        (99, "sembilan puluh sembilan"),
+        (100, "seratus"),
+        (101, "seratus satu"),
+        (111, "seratus sebelas"),
+        (200, "dua ratus"),
+        (999, "sembilan ratus sembilan puluh sembilan"),
+    ];
+    for (number, words) in expected {
+        assert_eq!(terbilang(number), words, "{}", number);
+    }
+}
+
+#[test]
+fn test_terbilang_large_numbers() {
+    let expected = [
+        (1_000, "seribu"),
+        (1_001, "seribu satu"),
+        (1_100, "seribu seratus"),
+        (2_000, "dua ribu"),
+        (10_000, "sepuluh ribu"),
+        (11_000, "sebelas ribu"),
+        (100_000, "seratus ribu"),
+        (101_000, "seratus satu ribu"),
+        (1_000_000, "satu juta"),
+        (1_500_000, "satu juta lima ratus ribu"),
+        (1_001_000, "satu juta seribu"),
+        (2_000_001, "dua juta satu"),
+        (1_000_000_000, "satu miliar"),
+        (1_000_000_000_000, "satu triliun"),
+        (
+            12_345_678,
+            "dua belas juta tiga ratus empat puluh lima ribu enam ratus tujuh puluh delapan",
+        ),
+    ];
+    for (number, words) in expected {
+        assert_eq!(terbilang(number), words, "{}", number);
+    }
+    assert!(terbilang(u64::MAX).starts_with("delapan belas kuintiliun"));
+}
+
+#[test]
+fn test_post_dates_for_display() {
+    let mut post = Post::new(
+        "Kerja bakti".to_string(),
+        "Kegiatan".to_string(),
+        "Kerja bakti hari Minggu".to_string(),
+        None,
+    );
+    post.date = date(2025, 11, 12);
+    post.created_at = Some(Utc.with_ymd_and_hms(2025, 11, 12, 2, 0, 0).unwrap());
+    post.updated_at = None;
+
+    let json = serde_json::to_value(&post).unwrap();
+    assert!(json.get("display").is_none());
+
+    post.format_for_display();
+    let json = serde_json::to_value(&post).unwrap();
+    assert_eq!(json["date"], "2025-11-12");
+    assert_eq!(json["display"]["date"], "12 November 2025");
+    assert_eq!(json["display"]["created_at"], "12 November 2025 09.00 WIB");
+    assert!(json["display"]["updated_at"].is_null());
+}
+
+#[test]
+fn test_format_query() {
+    let query = |format: Option<&str>| FormatQuery {
+        format: format.map(str::to_string),
+    };
+    assert!(!query(None).display().unwrap());
+    assert!(query(Some("display")).display().unwrap());
+    assert!(query(Some("iso")).display().is_err());
+}
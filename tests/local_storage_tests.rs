@@ -0,0 +1,169 @@
+//! Tests for the local filesystem storage backend
+
+use cakung_barat_server::storage::{LocalStorage, ObjectStorage, StorageConfig};
+
+#[tokio::test]
+async fn test_upload_download_and_delete() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = LocalStorage::new(dir.path());
+
+    storage
+        .upload_file("posts/abc/photo.png", b"image bytes")
+        .await
+        .unwrap();
+
+    assert!(dir.path().join("posts/abc/photo.png").is_file());
+    assert_eq!(
+        storage.download_file("posts/abc/photo.png").await.unwrap(),
+        b"image bytes"
+    );
+
+    storage.delete_file("posts/abc/photo.png").await.unwrap();
+    assert!(!dir.path().join("posts/abc/photo.png").exists());
+    assert!(storage.download_file("posts/abc/photo.png").await.is_err());
+    assert!(storage.delete_file("posts/abc/photo.png").await.is_err());
+}
+
+#[tokio::test]
+async fn test_upload_overwrites_existing_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = LocalStorage::new(dir.path());
+
+    storage
+        .upload_file("organization.json", b"[]")
+        .await
+        .unwrap();
+    storage
+        .upload_file("organization.json", b"[{}]")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        storage.download_file("organization.json").await.unwrap(),
+        b"[{}]"
+    );
+}
+
+#[tokio::test]
+async fn test_create_and_list_folder() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = LocalStorage::new(dir.path());
+
+    storage.create_folder("gallery").await.unwrap();
+    assert!(dir.path().join("gallery").is_dir());
+    assert!(storage
+        .list_folder_contents("gallery")
+        .await
+        .unwrap()
+        .is_empty());
+
+    storage
+        .upload_file("gallery/b.jpg", b"12345")
+        .await
+        .unwrap();
+    storage.upload_file("gallery/a.jpg", b"123").await.unwrap();
+    storage.create_folder("gallery/nested").await.unwrap();
+
+    let contents = storage.list_folder_contents("gallery").await.unwrap();
+    let names: Vec<&str> = contents.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["a.jpg", "b.jpg", "nested"]);
+    assert!(contents[0].is_file);
+    assert_eq!(contents[0].size, Some(3));
+    assert!(!contents[2].is_file);
+    assert_eq!(contents[2].size, None);
+}
+
+#[tokio::test]
+async fn test_remove_folder_keeps_stored_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = LocalStorage::new(dir.path());
+
+    storage.create_folder("posts/kosong").await.unwrap();
+    assert!(storage.remove_folder("posts/kosong").await.unwrap());
+    assert!(!dir.path().join("posts/kosong").exists());
+    assert!(!storage.remove_folder("posts/kosong").await.unwrap());
+
+    storage
+        .upload_file("posts/isi/a.jpg", b"123")
+        .await
+        .unwrap();
+    assert!(!storage.remove_folder("posts/isi").await.unwrap());
+    assert!(dir.path().join("posts/isi/a.jpg").is_file());
+}
+
+#[tokio::test]
+async fn test_missing_folder_lists_empty() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = LocalStorage::new(dir.path());
+
+    assert!(storage
+        .list_folder_contents("missing")
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_paths_cannot_escape_root() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("root");
+    let storage = LocalStorage::new(&root);
+
+    assert!(storage.upload_file("../outside.txt", b"x").await.is_err());
+    assert!(storage.download_file("a/../../etc/passwd").await.is_err());
+    assert!(!dir.path().join("outside.txt").exists());
+
+    // A leading slash is treated as relative to the root
+    storage.upload_file("/inside.txt", b"x").await.unwrap();
+    assert!(root.join("inside.txt").is_file());
+}
+
+#[tokio::test]
+async fn test_urls_point_at_serve_route() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = LocalStorage::new(dir.path());
+
+    assert_eq!(storage.get_asset_url("a_b.png"), "/assets/serve/a_b.png");
+    assert_eq!(
+        storage.local_path("a_b.png"),
+        Some(dir.path().join("a_b.png"))
+    );
+    assert_eq!(storage.local_path("../a_b.png"), None);
+}
+
+#[tokio::test]
+async fn test_config_builds_local_backend() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = StorageConfig::from_lookup(|key| match key {
+        "STORAGE_BACKEND" => Some("Local".to_string()),
+        "LOCAL_STORAGE_PATH" => Some(dir.path().to_string_lossy().into_owned()),
+        _ => None,
+    })
+    .unwrap();
+
+    let storage = config.build(reqwest::Client::new(), Default::default(), 8);
+    storage.upload_file("file.txt", b"ok").await.unwrap();
+
+    assert!(dir.path().join("file.txt").is_file());
+}
+
+#[tokio::test]
+async fn test_copy_and_move_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = LocalStorage::new(dir.path());
+    storage.upload_file("a.png", b"data").await.unwrap();
+
+    storage.copy_file("a.png", "copies/a.png").await.unwrap();
+    assert_eq!(storage.download_file("a.png").await.unwrap(), b"data");
+    assert_eq!(
+        storage.download_file("copies/a.png").await.unwrap(),
+        b"data"
+    );
+
+    storage.move_file("a.png", "moved/b.png").await.unwrap();
+    assert!(!dir.path().join("a.png").exists());
+    assert_eq!(storage.download_file("moved/b.png").await.unwrap(), b"data");
+
+    assert!(storage.move_file("a.png", "c.png").await.is_err());
+    assert!(storage.copy_file("../a.png", "c.png").await.is_err());
+}
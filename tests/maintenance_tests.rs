@@ -0,0 +1,206 @@
+//! Tests for the maintenance job: temp-dir sweep, config, /readyz status and
+//! the post folder cleanup endpoint
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::health;
+use cakung_barat_server::maintenance::{
+    self, sweep_temp_dir, MaintenanceConfig, TEMP_FILE_MAX_AGE,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+fn backdate(path: &Path, age: Duration) {
+    let modified = SystemTime::now() - age;
+    File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(modified)
+        .unwrap();
+}
+
+#[actix_web::test]
+async fn test_sweep_removes_only_old_temp_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let old_temp = dir.path().join(".tmpAbC123");
+    let fresh_temp = dir.path().join(".tmpXyZ789");
+    let old_other = dir.path().join("organization.json");
+    for path in [&old_temp, &fresh_temp, &old_other] {
+        std::fs::write(path, b"data").unwrap();
+    }
+    backdate(&old_temp, Duration::from_secs(2 * 60 * 60));
+    backdate(&old_other, Duration::from_secs(2 * 60 * 60));
+
+    let removed = sweep_temp_dir(dir.path(), TEMP_FILE_MAX_AGE, SystemTime::now()).unwrap();
+
+    assert_eq!(removed, 1);
+    assert!(!old_temp.exists());
+    assert!(fresh_temp.exists(), "recent temp files are still in use");
+    assert!(old_other.exists(), "non-tempfile entries are left alone");
+}
+
+#[actix_web::test]
+async fn test_sweep_removes_abandoned_temp_dirs() {
+    let dir = tempfile::tempdir().unwrap();
+    let abandoned = dir.path().join(".tmpTypst1");
+    std::fs::create_dir(&abandoned).unwrap();
+    std::fs::write(abandoned.join("surat.typ"), b"#set page()").unwrap();
+
+    // Nothing is old enough yet
+    assert_eq!(
+        sweep_temp_dir(dir.path(), TEMP_FILE_MAX_AGE, SystemTime::now()).unwrap(),
+        0
+    );
+
+    let later = SystemTime::now() + Duration::from_secs(2 * 60 * 60);
+    assert_eq!(
+        sweep_temp_dir(dir.path(), TEMP_FILE_MAX_AGE, later).unwrap(),
+        1
+    );
+    assert!(!abandoned.exists());
+}
+
+#[actix_web::test]
+async fn test_sweep_missing_dir_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("missing");
+    assert!(sweep_temp_dir(&missing, TEMP_FILE_MAX_AGE, SystemTime::now()).is_err());
+}
+
+fn config_from(vars: &[(&str, &str)]) -> Result<MaintenanceConfig, String> {
+    let vars: HashMap<String, String> = vars
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    MaintenanceConfig::from_lookup(|key| vars.get(key).cloned())
+}
+
+#[actix_web::test]
+async fn test_config_from_env() {
+    let defaults = config_from(&[]).unwrap();
+    assert_eq!(defaults.interval, Some(Duration::from_secs(3600)));
+    assert_eq!(
+        defaults.temp_dir,
+        std::env::temp_dir().join("cakung-barat-server")
+    );
+    assert!(!defaults.reconcile_bucket);
+    assert!(defaults.remove_post_folders);
+    assert_eq!(
+        defaults.generated_retention,
+        Some(Duration::from_secs(7 * 24 * 60 * 60))
+    );
+
+    let config = config_from(&[
+        ("MAINTENANCE_INTERVAL_SECS", "600"),
+        ("TEMP_CLEANUP_DIR", "/var/tmp/uploads"),
+        ("MAINTENANCE_RECONCILE_BUCKET", "true"),
+        ("MAINTENANCE_REMOVE_POST_FOLDERS", "false"),
+        ("GENERATED_DOCUMENT_RETENTION_DAYS", "30"),
+    ])
+    .unwrap();
+    assert_eq!(config.interval, Some(Duration::from_secs(600)));
+    assert_eq!(config.temp_dir, Path::new("/var/tmp/uploads"));
+    assert!(config.reconcile_bucket);
+    assert!(!config.remove_post_folders);
+    assert_eq!(
+        config.generated_retention,
+        Some(Duration::from_secs(30 * 24 * 60 * 60))
+    );
+
+    let disabled = config_from(&[
+        ("MAINTENANCE_INTERVAL_SECS", "0"),
+        ("GENERATED_DOCUMENT_RETENTION_DAYS", "0"),
+    ])
+    .unwrap();
+    assert_eq!(disabled.interval, None);
+    assert_eq!(disabled.generated_retention, None);
+
+    assert!(config_from(&[("MAINTENANCE_INTERVAL_SECS", "hourly")]).is_err());
+    assert!(config_from(&[("MAINTENANCE_RECONCILE_BUCKET", "maybe")]).is_err());
+    assert!(config_from(&[("GENERATED_DOCUMENT_RETENTION_DAYS", "week")]).is_err());
+}
+
+#[actix_web::test]
+async fn test_shared_temp_dir_is_not_swept() {
+    let system = std::env::temp_dir();
+    let err = config_from(&[("TEMP_CLEANUP_DIR", system.to_str().unwrap())]).unwrap_err();
+    assert!(err.contains("TEMP_CLEANUP_DIR"), "{}", err);
+    assert!(config_from(&[("TEMP_CLEANUP_DIR", "/")]).is_err());
+
+    // Temp files are created in the directory that is swept
+    let dir = maintenance::temp_dir();
+    assert!(dir.is_dir());
+    assert_ne!(dir, system);
+    let file = tempfile::NamedTempFile::new_in(dir).unwrap();
+    assert_eq!(file.path().parent(), Some(dir));
+}
+
+#[actix_web::test]
+async fn test_readyz_reports_last_maintenance_run() {
+    let state = web::Data::new(
+        common::state_builder(Arc::new(common::NoopStorage))
+            .build()
+            .unwrap(),
+    );
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .route("/readyz", web::get().to(health::readyz)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/readyz").to_request();
+    let body: Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert!(body["maintenance"]["last_run_at"].is_null());
+
+    let dir = tempfile::tempdir().unwrap();
+    let stale = dir.path().join(".tmpStale");
+    std::fs::write(&stale, b"data").unwrap();
+    backdate(&stale, Duration::from_secs(2 * 60 * 60));
+    let config = MaintenanceConfig {
+        interval: None,
+        temp_dir: dir.path().to_path_buf(),
+        reconcile_bucket: false,
+        remove_post_folders: false,
+        generated_retention: None,
+    };
+    let status = state.run_maintenance(&config).await;
+    assert_eq!(status.temp_files_removed, 1);
+    assert_eq!(status.last_run_ok, Some(true));
+
+    let req = test::TestRequest::get().uri("/readyz").to_request();
+    let resp = test::call_service(&app, req).await;
+    // The database is unreachable in this test
+    assert_eq!(resp.status(), 503);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["status"], "unavailable");
+    assert_eq!(body["database"], false);
+    assert_eq!(body["maintenance"]["temp_files_removed"], 1);
+    assert_eq!(body["maintenance"]["last_run_ok"], true);
+    assert!(body["maintenance"]["last_run_at"].is_string());
+}
+
+#[actix_web::test]
+async fn test_post_folder_cleanup_requires_admin() {
+    let state = common::state_builder(Arc::new(common::NoopStorage))
+        .build()
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(web::scope("/api").configure(maintenance::config)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/admin/maintenance/post-folders")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+}
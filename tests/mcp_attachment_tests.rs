@@ -0,0 +1,175 @@
+//! Tests for embedding uploaded images in generated letters
+
+use base64::Engine;
+use cakung_barat_server::asset::models::Asset;
+use cakung_barat_server::mcp::generators::{
+    Attachment, AttachmentKind, Attachments, Generator, Lampiran, SuratPengantarSkckGenerator,
+    SuratPengantarSkckRequest, SuratTidakMampuGenerator, SuratTidakMampuRequest,
+};
+use cakung_barat_server::mcp::tools::attachments::load_attachment;
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use cakung_barat_server::storage::{LocalStorage, ObjectStorage};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+/// A 1x1 transparent PNG
+fn png() -> Vec<u8> {
+    base64::engine::general_purpose::STANDARD
+        .decode("iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==")
+        .unwrap()
+}
+
+fn asset(filename: &str) -> Asset {
+    Asset::new(
+        "Pas foto".to_string(),
+        filename.to_string(),
+        format!("/assets/serve/{}", filename),
+        None,
+    )
+}
+
+fn tidak_mampu_arguments() -> Value {
+    let person = json!({
+        "nama": "Siti Rahayu",
+        "nik": "3175014102950002",
+        "ttl": "Jakarta, 1 Februari 1995",
+        "jk": false,
+        "agama": "Islam",
+        "pekerjaan": "Pelajar",
+        "alamat": "Jl. Cakung Barat No. 1",
+        "telp": "081234567890"
+    });
+    let mut subjek = person.clone();
+    subjek["hubungan"] = "Anak".into();
+    json!({
+        "pengisi": person,
+        "subjek": subjek,
+        "meta": { "opsi_sendiri": false, "kelurahan": "Cakung Barat" }
+    })
+}
+
+fn attachments() -> Attachments {
+    let mut attachments = Attachments::default();
+    attachments.insert(Attachment::new(AttachmentKind::Foto, png()).unwrap());
+    attachments.insert(Attachment::new(AttachmentKind::Ttd, png()).unwrap());
+    attachments
+}
+
+#[actix_web::test]
+async fn test_load_attachment_reads_stored_image() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = LocalStorage::new(dir.path().to_path_buf());
+    storage.upload_file("foto.PNG", &png()).await.unwrap();
+
+    let attachment = load_attachment(
+        &storage,
+        AttachmentKind::Foto,
+        Uuid::new_v4(),
+        Some(asset("foto.PNG")),
+    )
+    .await
+    .unwrap();
+    assert_eq!(attachment.kind(), AttachmentKind::Foto);
+    assert_eq!(attachment.path(), "foto.png");
+}
+
+#[actix_web::test]
+async fn test_load_attachment_errors_name_the_field() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = LocalStorage::new(dir.path().to_path_buf());
+    storage
+        .upload_file("bukan.jpg", b"plain text")
+        .await
+        .unwrap();
+    let id = Uuid::new_v4();
+
+    let missing = load_attachment(&storage, AttachmentKind::Foto, id, None)
+        .await
+        .unwrap_err();
+    assert!(missing.starts_with("foto_asset_id:"), "{}", missing);
+    assert!(missing.contains("tidak ditemukan"), "{}", missing);
+
+    let document = load_attachment(&storage, AttachmentKind::Ttd, id, Some(asset("scan.pdf")))
+        .await
+        .unwrap_err();
+    assert!(document.starts_with("ttd_asset_id:"), "{}", document);
+    assert!(document.contains("bukan gambar"), "{}", document);
+
+    let invalid = load_attachment(&storage, AttachmentKind::Ttd, id, Some(asset("bukan.jpg")))
+        .await
+        .unwrap_err();
+    assert!(invalid.contains("bukan gambar PNG atau JPEG"), "{}", invalid);
+}
+
+#[test]
+fn test_attachments_are_served_by_path() {
+    let attachments = attachments();
+    assert_eq!(attachments.file("foto.png"), Some(png().as_slice()));
+    assert!(attachments.file("foto.jpg").is_none());
+    assert_eq!(
+        attachments.typst_value(),
+        "(foto: \"foto.png\", ttd: \"ttd.png\")"
+    );
+    assert_eq!(
+        Attachments::default().typst_value(),
+        "(foto: none, ttd: none)"
+    );
+    assert!(Attachment::new(AttachmentKind::Foto, b"GIF89a".to_vec()).is_none());
+}
+
+#[test]
+fn test_letters_render_with_attachments() {
+    let skck = SuratPengantarSkckGenerator::new()
+        .unwrap()
+        .generate_with_attachments(SuratPengantarSkckRequest::sample(), &attachments())
+        .unwrap();
+    assert!(skck.pdf.starts_with(b"%PDF"));
+
+    let tidak_mampu = SuratTidakMampuGenerator::new()
+        .unwrap()
+        .generate_with_attachments(SuratTidakMampuRequest::sample(), &attachments())
+        .unwrap();
+    assert!(tidak_mampu.pdf.starts_with(b"%PDF"));
+}
+
+#[test]
+fn test_lampiran_is_optional_in_requests() {
+    let mut request = tidak_mampu_arguments();
+    let parsed: SuratTidakMampuRequest = serde_json::from_value(request.clone()).unwrap();
+    assert!(parsed.lampiran.is_empty());
+
+    let id = Uuid::new_v4();
+    request["foto_asset_id"] = id.to_string().into();
+    let parsed: SuratTidakMampuRequest = serde_json::from_value(request).unwrap();
+    assert_eq!(
+        parsed.lampiran,
+        Lampiran {
+            foto_asset_id: Some(id),
+            ttd_asset_id: None,
+        }
+    );
+}
+
+#[test]
+fn test_document_tools_list_attachment_ids() {
+    let tools = ToolRegistry::new().unwrap().list_tools();
+    for tool in tools
+        .iter()
+        .filter(|tool| tool.name.starts_with("generate_"))
+    {
+        for field in ["foto_asset_id", "ttd_asset_id"] {
+            let property = &tool.input_schema["properties"][field];
+            assert_eq!(property["format"], "uuid", "{}: {}", tool.name, field);
+        }
+    }
+}
+
+#[test]
+fn test_sync_call_rejects_attachments() {
+    let mut arguments = tidak_mampu_arguments();
+    arguments["foto_asset_id"] = Uuid::new_v4().to_string().into();
+    let result = ToolRegistry::new()
+        .unwrap()
+        .call_tool("generate_surat_tidak_mampu", Some(arguments));
+    assert!(result.is_error);
+}
@@ -0,0 +1,196 @@
+//! Tests for MCP endpoint authentication
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::auth::{generate_access_token, McpAuth};
+use cakung_barat_server::cache::Cached;
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use cakung_barat_server::mcp::{McpService, McpState};
+use cakung_barat_server::posting::models::Post;
+use cakung_barat_server::storage::LocalStorage;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+async fn mcp_state(auth: McpAuth) -> web::Data<Arc<McpState>> {
+    let app_state = common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .build()
+        .unwrap();
+    let post = Post::new(
+        "Posyandu Balita".to_string(),
+        "Kesehatan".to_string(),
+        "Posyandu di balai warga".to_string(),
+        None,
+    );
+    app_state
+        .post_cache
+        .insert("all_posts".to_string(), Cached::new(vec![post]))
+        .await;
+
+    let service = McpService::new(ToolRegistry::new().unwrap());
+    web::Data::new(Arc::new(
+        McpState::new(service, web::Data::new(app_state)).with_auth(auth),
+    ))
+}
+
+fn api_keys(value: &str) -> McpAuth {
+    McpAuth::from_lookup(|key| (key == "MCP_API_KEYS").then(|| value.to_string())).unwrap()
+}
+
+fn list_postings() -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": 7,
+        "method": "tools/call",
+        "params": { "name": "list_postings", "arguments": { "limit": 5 } }
+    })
+}
+
+#[actix_web::test]
+async fn test_parse_api_keys() {
+    let auth = api_keys("inspector:mcp, reporting:read, ops:read+mcp");
+    assert_eq!(auth.api_keys, vec!["inspector", "ops"]);
+    assert!(!auth.http_401);
+
+    let error = McpAuth::from_lookup(|key| (key == "MCP_API_KEYS").then(|| "inspector".into()));
+    assert!(error.unwrap_err().contains("MCP_API_KEYS"));
+
+    let error =
+        McpAuth::from_lookup(|key| (key == "MCP_AUTH_HTTP_401").then(|| "sometimes".into()));
+    assert!(error.unwrap_err().contains("MCP_AUTH_HTTP_401"));
+}
+
+#[actix_web::test]
+async fn test_tools_call_without_credentials_is_rejected() {
+    let state = mcp_state(McpAuth::default()).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(state)
+            .configure(cakung_barat_server::mcp::config),
+    )
+    .await;
+
+    for uri in ["/mcp", "/sse"] {
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::post()
+                .uri(uri)
+                .set_json(list_postings())
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), 200);
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["id"], 7);
+        assert_eq!(body["error"]["code"], -32001);
+        assert!(body["error"]["message"].as_str().unwrap().contains("mcp"));
+
+        // Discovery stays open
+        let body: Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::post()
+                .uri(uri)
+                .set_json(json!({ "jsonrpc": "2.0", "id": 1, "method": "ping" }))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(body["result"]["ok"], true);
+    }
+}
+
+#[actix_web::test]
+async fn test_tools_call_with_token_or_api_key() {
+    let state = mcp_state(api_keys("inspector:mcp,reporting:read")).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(state)
+            .configure(cakung_barat_server::mcp::config),
+    )
+    .await;
+    let token = generate_access_token("admin-id", "admin").unwrap();
+
+    let credentials = [
+        ("Authorization", format!("Bearer {}", token)),
+        ("Authorization", "Bearer inspector".to_string()),
+        ("X-API-Key", "inspector".to_string()),
+    ];
+    for header in &credentials {
+        for uri in ["/mcp", "/sse"] {
+            let body: Value = test::call_and_read_body_json(
+                &app,
+                test::TestRequest::post()
+                    .uri(uri)
+                    .insert_header(header.clone())
+                    .set_json(list_postings())
+                    .to_request(),
+            )
+            .await;
+            assert_eq!(
+                body["result"]["isError"], false,
+                "{} with {:?}",
+                uri, header
+            );
+            assert!(body["result"]["content"]
+                .to_string()
+                .contains("Posyandu Balita"));
+        }
+    }
+
+    // A key without the mcp scope is not accepted
+    let body: Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::post()
+            .uri("/mcp")
+            .insert_header(("X-API-Key", "reporting"))
+            .set_json(list_postings())
+            .to_request(),
+    )
+    .await;
+    assert_eq!(body["error"]["code"], -32001);
+}
+
+#[actix_web::test]
+async fn test_http_401_mode() {
+    let auth = McpAuth {
+        http_401: true,
+        ..McpAuth::default()
+    };
+    let state = mcp_state(auth).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(state)
+            .configure(cakung_barat_server::mcp::config),
+    )
+    .await;
+
+    for uri in ["/mcp", "/sse"] {
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::post()
+                .uri(uri)
+                .set_json(list_postings())
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), 401);
+        assert_eq!(
+            resp.headers().get("WWW-Authenticate").unwrap(),
+            "Bearer realm=\"mcp\""
+        );
+    }
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/mcp")
+            .set_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": { "protocolVersion": "2025-03-26" }
+            }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+}
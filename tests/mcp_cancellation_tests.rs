@@ -0,0 +1,239 @@
+//! Tests for cancelling in-flight MCP requests
+
+mod common;
+
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::{test, web, App};
+use cakung_barat_server::auth::generate_access_token;
+use cakung_barat_server::mcp::cancellation::InFlightRequests;
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use cakung_barat_server::mcp::{McpService, McpState};
+use cakung_barat_server::storage::{FolderContent, ObjectStorage};
+use serde_json::{json, Value};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Storage whose uploads take long enough to cancel the call mid-way
+struct SlowStorage;
+
+#[async_trait::async_trait]
+impl ObjectStorage for SlowStorage {
+    async fn upload_file(&self, _filename: &str, _file_data: &[u8]) -> Result<(), String> {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        Ok(())
+    }
+
+    async fn upload_new_file(&self, filename: &str, file_data: &[u8]) -> Result<bool, String> {
+        self.upload_file(filename, file_data).await?;
+        Ok(true)
+    }
+
+    async fn download_file(&self, _filename: &str) -> Result<Vec<u8>, String> {
+        Err("not found".to_string())
+    }
+
+    async fn delete_file(&self, _filename: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn create_folder(&self, _folder_name: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn list_folder_contents(&self, _folder_name: &str) -> Result<Vec<FolderContent>, String> {
+        Ok(Vec::new())
+    }
+
+    fn get_asset_url(&self, filename: &str) -> String {
+        format!("mock://{}", filename)
+    }
+}
+
+fn mcp_state() -> web::Data<Arc<McpState>> {
+    let app_state = web::Data::new(
+        common::state_builder(Arc::new(SlowStorage))
+            .build()
+            .unwrap(),
+    );
+    let service = McpService::new(ToolRegistry::new().unwrap());
+    web::Data::new(Arc::new(McpState::new(service, app_state)))
+}
+
+fn generate_skck(id: u64) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "tools/call",
+        "params": {
+            "name": "generate_surat_pengantar_skck",
+            "arguments": {
+                "pemohon": {
+                    "nama": "Budi Santoso",
+                    "nik": "3175010101900001",
+                    "ttl": "Jakarta, 1 Januari 1990",
+                    "jk": true,
+                    "agama": "Islam",
+                    "pekerjaan": "Karyawan Swasta",
+                    "alamat": "Jl. Cakung Barat No. 1",
+                    "telp": "081234567890"
+                },
+                "skck": {
+                    "keperluan": "Melamar pekerjaan",
+                    "tujuan_instansi": "PT Sinar Abadi",
+                    "masa_berlaku": "1 bulan"
+                },
+                "meta": { "kelurahan": "Cakung Barat" },
+                "delivery": "url"
+            }
+        }
+    })
+}
+
+fn cancel(id: u64) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/cancelled",
+        "params": { "requestId": id, "reason": "Pengguna membatalkan" }
+    })
+}
+
+fn bearer() -> (&'static str, String) {
+    let token = generate_access_token("admin-id", "admin").unwrap();
+    ("Authorization", format!("Bearer {}", token))
+}
+
+async fn next_event(body: &mut BoxBody) -> Option<String> {
+    let chunk = tokio::time::timeout(
+        Duration::from_millis(300),
+        futures::future::poll_fn(|cx| Pin::new(&mut *body).poll_next(cx)),
+    )
+    .await
+    .ok()??;
+    Some(String::from_utf8(chunk.unwrap().to_vec()).unwrap())
+}
+
+#[actix_web::test]
+async fn test_in_flight_requests() {
+    let requests = Arc::new(InFlightRequests::new());
+    let first = requests.register(Some("a"), &json!(1));
+    let token = first.token();
+
+    // Scoped by session
+    assert!(!requests.cancel(Some("b"), &json!(1)));
+    assert!(!requests.cancel(Some("a"), &json!("1")));
+    assert!(requests.cancel(Some("a"), &json!(1)));
+    assert!(token.is_cancelled());
+
+    // A reused id outlives the guard of the earlier request
+    let second = requests.register(Some("a"), &json!(1));
+    drop(first);
+    assert_eq!(requests.len(), 1);
+    assert!(!second.token().is_cancelled());
+    drop(second);
+    assert!(requests.is_empty());
+}
+
+#[actix_web::test]
+async fn test_cancelled_call_gets_no_response() {
+    let state = mcp_state();
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .configure(cakung_barat_server::mcp::config),
+    )
+    .await;
+
+    let call = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/mcp")
+            .insert_header(bearer())
+            .set_json(generate_skck(9))
+            .to_request(),
+    );
+    let cancelling = async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(state.in_flight.len(), 1);
+        test::call_service(
+            &app,
+            test::TestRequest::post()
+                .uri("/mcp")
+                .set_json(cancel(9))
+                .to_request(),
+        )
+        .await
+    };
+    let (resp, cancel_resp) = futures::join!(call, cancelling);
+
+    assert_eq!(cancel_resp.status(), 202);
+    assert_eq!(resp.status(), 202);
+    assert!(test::read_body(resp).await.is_empty());
+    assert!(state.in_flight.is_empty());
+}
+
+#[actix_web::test]
+async fn test_cancelled_call_is_not_sent_to_session() {
+    let state = mcp_state();
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .configure(cakung_barat_server::mcp::config),
+    )
+    .await;
+
+    let mut stream = test::call_service(&app, test::TestRequest::get().uri("/sse").to_request())
+        .await
+        .into_body();
+    let endpoint = next_event(&mut stream).await.unwrap();
+    let url = endpoint
+        .lines()
+        .find_map(|line| line.strip_prefix("data: "))
+        .unwrap()
+        .to_string();
+
+    let call = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri(&url)
+            .insert_header(bearer())
+            .set_json(generate_skck(10))
+            .to_request(),
+    );
+    let cancelling = async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        // Another session cannot cancel it
+        test::call_service(
+            &app,
+            test::TestRequest::post()
+                .uri("/mcp")
+                .set_json(cancel(10))
+                .to_request(),
+        )
+        .await;
+        test::call_service(
+            &app,
+            test::TestRequest::post()
+                .uri(&url)
+                .set_json(cancel(10))
+                .to_request(),
+        )
+        .await
+    };
+    let (resp, _) = futures::join!(call, cancelling);
+    assert_eq!(resp.status(), 202);
+    assert!(next_event(&mut stream).await.is_none());
+
+    // Later requests on the session are answered as usual
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri(&url)
+            .set_json(json!({ "jsonrpc": "2.0", "id": 11, "method": "ping" }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 202);
+    let event = next_event(&mut stream).await.unwrap();
+    assert!(event.contains("\"id\":11"), "{}", event);
+}
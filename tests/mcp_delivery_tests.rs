@@ -0,0 +1,236 @@
+//! Tests for storing generated documents and returning a download URL
+
+mod common;
+
+use actix_web::web;
+use cakung_barat_server::mcp::generators::GeneratedDocument;
+use cakung_barat_server::mcp::tools::delivery::{
+    generated_key, public_url, upload_document, Delivery,
+};
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use cakung_barat_server::storage::{FolderContent, LocalStorage, ObjectStorage};
+use chrono::NaiveDate;
+use serde_json::json;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+/// Mock storage that records uploads and reports `taken` names as existing
+#[derive(Default)]
+struct MockStorage {
+    taken: HashSet<String>,
+    uploads: Mutex<Vec<(String, usize)>>,
+}
+
+#[async_trait::async_trait]
+impl ObjectStorage for MockStorage {
+    async fn upload_file(&self, filename: &str, file_data: &[u8]) -> Result<(), String> {
+        self.uploads
+            .lock()
+            .unwrap()
+            .push((filename.to_string(), file_data.len()));
+        Ok(())
+    }
+
+    async fn upload_new_file(&self, filename: &str, file_data: &[u8]) -> Result<bool, String> {
+        if self.taken.contains(filename) {
+            return Ok(false);
+        }
+        self.upload_file(filename, file_data).await?;
+        Ok(true)
+    }
+
+    async fn download_file(&self, _filename: &str) -> Result<Vec<u8>, String> {
+        Err("not found".to_string())
+    }
+
+    async fn delete_file(&self, _filename: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn create_folder(&self, _folder_name: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn list_folder_contents(&self, _folder_name: &str) -> Result<Vec<FolderContent>, String> {
+        Ok(Vec::new())
+    }
+
+    fn get_asset_url(&self, filename: &str) -> String {
+        format!("mock://{}", filename)
+    }
+}
+
+fn document() -> GeneratedDocument {
+    GeneratedDocument {
+        filename: "skck-budi-santoso.pdf".to_string(),
+        pdf: vec![0; 2048],
+        tanggal: "2 Januari 2025".to_string(),
+    }
+}
+
+fn date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 1, 2).unwrap()
+}
+
+#[actix_web::test]
+async fn test_delivery_argument() {
+    assert_eq!(Delivery::from_arguments(&None).unwrap(), Delivery::Inline);
+    assert_eq!(
+        Delivery::from_arguments(&Some(json!({ "meta": {} }))).unwrap(),
+        Delivery::Inline
+    );
+    assert_eq!(
+        Delivery::from_arguments(&Some(json!({ "delivery": "inline" }))).unwrap(),
+        Delivery::Inline
+    );
+    assert_eq!(
+        Delivery::from_arguments(&Some(json!({ "delivery": "url" }))).unwrap(),
+        Delivery::Url
+    );
+    assert!(Delivery::from_arguments(&Some(json!({ "delivery": "email" }))).is_err());
+}
+
+#[actix_web::test]
+async fn test_generated_key_is_dated() {
+    assert_eq!(
+        generated_key(date(), "skck-budi-santoso.pdf", 0),
+        "generated/2025-01-02/skck-budi-santoso.pdf"
+    );
+
+    let retry = generated_key(date(), "skck-budi-santoso.pdf", 1);
+    assert!(
+        retry.starts_with("generated/2025-01-02/skck-budi-santoso-"),
+        "{}",
+        retry
+    );
+    assert!(retry.ends_with(".pdf"), "{}", retry);
+}
+
+#[actix_web::test]
+async fn test_upload_document_uses_generated_prefix() {
+    let storage = MockStorage::default();
+
+    let key = upload_document(&storage, &document(), date())
+        .await
+        .unwrap();
+
+    assert_eq!(key, "generated/2025-01-02/skck-budi-santoso.pdf");
+    assert_eq!(*storage.uploads.lock().unwrap(), vec![(key, 2048)]);
+}
+
+#[actix_web::test]
+async fn test_upload_document_keeps_existing_letter() {
+    let storage = MockStorage {
+        taken: HashSet::from(["generated/2025-01-02/skck-budi-santoso.pdf".to_string()]),
+        ..Default::default()
+    };
+
+    let key = upload_document(&storage, &document(), date())
+        .await
+        .unwrap();
+
+    assert_ne!(key, "generated/2025-01-02/skck-budi-santoso.pdf");
+    assert!(key.starts_with("generated/2025-01-02/"));
+    assert_eq!(storage.uploads.lock().unwrap().len(), 1);
+}
+
+#[actix_web::test]
+async fn test_public_url_formatting() {
+    let path = "/assets/serve/generated/2025-01-02/skck-budi-santoso.pdf";
+
+    assert_eq!(public_url(None, path), path);
+    assert_eq!(
+        public_url(Some("https://kelurahan.example/"), path),
+        "https://kelurahan.example/assets/serve/generated/2025-01-02/skck-budi-santoso.pdf"
+    );
+}
+
+#[actix_web::test]
+async fn test_document_tools_offer_delivery_option() {
+    let registry = ToolRegistry::new().unwrap();
+
+    for tool in registry
+        .list_tools()
+        .into_iter()
+        .filter(|tool| tool.name.starts_with("generate_surat_"))
+    {
+        let delivery = &tool.input_schema["properties"]["delivery"];
+        assert_eq!(delivery["enum"], json!(["inline", "url"]), "{}", tool.name);
+    }
+}
+
+#[actix_web::test]
+async fn test_failed_asset_insert_removes_upload() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = web::Data::new(
+        common::state_builder(Arc::new(LocalStorage::new(dir.path())))
+            .build()
+            .unwrap(),
+    );
+    let registry = ToolRegistry::new().unwrap();
+    let arguments = json!({
+        "pemohon": {
+            "nama": "Budi Santoso",
+            "nik": "3175010101900001",
+            "ttl": "Jakarta, 1 Januari 1990",
+            "jk": true,
+            "agama": "Islam",
+            "pekerjaan": "Karyawan Swasta",
+            "alamat": "Jl. Cakung Barat No. 1",
+            "telp": "081234567890"
+        },
+        "skck": {
+            "keperluan": "Melamar pekerjaan",
+            "tujuan_instansi": "PT Sinar Abadi",
+            "masa_berlaku": "1 bulan"
+        },
+        "meta": { "kelurahan": "Cakung Barat" },
+        "delivery": "url"
+    });
+
+    let result = registry
+        .call_tool_async("generate_surat_pengantar_skck", Some(arguments), &state)
+        .await;
+
+    assert!(result.is_error);
+    let text = result.content[0].text.as_deref().unwrap();
+    assert!(text.contains("Gagal menyimpan surat"), "{}", text);
+    let leftovers: Vec<_> = walk(dir.path());
+    assert!(leftovers.is_empty(), "{:?}", leftovers);
+}
+
+#[actix_web::test]
+async fn test_invalid_delivery_is_rejected() {
+    let state = web::Data::new(
+        common::state_builder(Arc::new(MockStorage::default()))
+            .build()
+            .unwrap(),
+    );
+    let registry = ToolRegistry::new().unwrap();
+
+    let result = registry
+        .call_tool_async(
+            "generate_surat_pengantar_skck",
+            Some(json!({ "delivery": "fax" })),
+            &state,
+        )
+        .await;
+
+    assert!(result.is_error);
+    let text = result.content[0].text.as_deref().unwrap();
+    assert!(text.contains("delivery"), "{}", text);
+}
+
+/// Every file below `dir`
+fn walk(dir: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).unwrap().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(walk(&path));
+        } else {
+            files.push(path);
+        }
+    }
+    files
+}
@@ -0,0 +1,35 @@
+use cakung_barat_server::mcp::generators::engine::{compile_typst_to_pdf, TypstRenderEngine};
+use cakung_barat_server::mcp::generators::GeneratorError;
+
+#[test]
+fn test_compile_produces_pdf_in_process() {
+    let pdf = compile_typst_to_pdf("surat.typ", "= Surat\nIsi surat *penting*.").unwrap();
+    assert!(pdf.starts_with(b"%PDF"));
+    assert!(pdf.len() > 1024);
+}
+
+#[test]
+fn test_compile_error_reports_line_and_message() {
+    let source = "#let a = 1\n#undefined_function()\n";
+    let err = compile_typst_to_pdf("surat.typ", source).unwrap_err();
+
+    assert!(matches!(err, GeneratorError::Compile(_)));
+    let message = err.to_string();
+    assert!(message.contains("line 2"), "{}", message);
+    assert!(message.contains("unknown variable"), "{}", message);
+}
+
+#[test]
+fn test_render_names_output_after_template() {
+    let doc = TypstRenderEngine::render(
+        "keterangan_usaha.typ",
+        "Halo",
+        "Budi Santoso",
+        Some("1 Januari 2025".to_string()),
+    )
+    .unwrap();
+
+    assert_eq!(doc.filename, "keterangan-usaha-budi-santoso.pdf");
+    assert_eq!(doc.tanggal, "1 Januari 2025");
+    assert!(!doc.pdf.is_empty());
+}
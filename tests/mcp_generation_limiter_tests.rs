@@ -0,0 +1,102 @@
+use cakung_barat_server::mcp::generators::{
+    Attachments, GeneratedDocument, GenerationConfig, GenerationLimiter, Generator, GeneratorError,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Generator that records how many of its calls overlap
+#[derive(Default)]
+struct CountingGenerator {
+    running: AtomicUsize,
+    high_water_mark: AtomicUsize,
+}
+
+impl Generator<Duration> for CountingGenerator {
+    fn generate_with_attachments(
+        &self,
+        work: Duration,
+        _attachments: &Attachments,
+    ) -> Result<GeneratedDocument, GeneratorError> {
+        let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.high_water_mark.fetch_max(running, Ordering::SeqCst);
+        std::thread::sleep(work);
+        self.running.fetch_sub(1, Ordering::SeqCst);
+
+        Ok(GeneratedDocument {
+            filename: "test.pdf".to_string(),
+            pdf: b"%PDF-test".to_vec(),
+            tanggal: "1 Januari 2025".to_string(),
+        })
+    }
+}
+
+fn limiter(max_concurrent: usize, queue_timeout: Duration) -> GenerationLimiter {
+    GenerationLimiter::new(&GenerationConfig {
+        max_concurrent,
+        queue_timeout,
+    })
+}
+
+#[tokio::test]
+async fn test_concurrent_generations_respect_cap() {
+    let limiter = limiter(2, Duration::from_secs(10));
+    let generator = Arc::new(CountingGenerator::default());
+
+    let jobs = (0..8).map(|_| {
+        let generator = Arc::clone(&generator);
+        limiter.run(move || generator.generate(Duration::from_millis(50)))
+    });
+    let results = futures::future::join_all(jobs).await;
+
+    assert!(results.iter().all(|result| result.is_ok()));
+    assert_eq!(generator.high_water_mark.load(Ordering::SeqCst), 2);
+    assert_eq!(generator.running.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_queue_timeout_when_all_slots_busy() {
+    let limiter = limiter(1, Duration::from_millis(20));
+    let limiter = &limiter;
+    let generator = Arc::new(CountingGenerator::default());
+
+    let slow = {
+        let generator = Arc::clone(&generator);
+        limiter.run(move || generator.generate(Duration::from_millis(300)))
+    };
+    let queued = {
+        let generator = Arc::clone(&generator);
+        async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            limiter
+                .run(move || generator.generate(Duration::ZERO))
+                .await
+        }
+    };
+    let (slow, queued) = tokio::join!(slow, queued);
+
+    assert!(slow.is_ok());
+    assert!(matches!(queued, Err(GeneratorError::QueueTimeout(_))));
+}
+
+#[test]
+fn test_generation_config_defaults_and_overrides() {
+    let config = GenerationConfig::from_lookup(|_| None).unwrap();
+    assert_eq!(config, GenerationConfig::default());
+    assert_eq!(config.max_concurrent, 2);
+
+    let config = GenerationConfig::from_lookup(|key| match key {
+        "MCP_MAX_CONCURRENT_GENERATIONS" => Some("4".to_string()),
+        "MCP_GENERATION_QUEUE_TIMEOUT_SECS" => Some(" 5 ".to_string()),
+        _ => None,
+    })
+    .unwrap();
+    assert_eq!(config.max_concurrent, 4);
+    assert_eq!(config.queue_timeout, Duration::from_secs(5));
+
+    let err = GenerationConfig::from_lookup(|key| {
+        (key == "MCP_MAX_CONCURRENT_GENERATIONS").then(|| "0".to_string())
+    })
+    .unwrap_err();
+    assert!(err.contains("MCP_MAX_CONCURRENT_GENERATIONS"));
+}
@@ -1,4 +1,5 @@
-use cakung_barat_server::mcp::generators::common::{escape_typst_string, sanitize_filename, format_indonesian_date};
+use cakung_barat_server::i18n::today_id;
+use cakung_barat_server::mcp::generators::common::{escape_typst_string, sanitize_filename};
 
 #[test]
 fn test_escape_typst_string() {
@@ -18,8 +19,8 @@ fn test_sanitize_filename() {
 }
 
 #[test]
-fn test_format_indonesian_date() {
-    let date = format_indonesian_date();
+fn test_today_id() {
+    let date = today_id();
     // Should contain year
     assert!(date.contains("2025") || date.contains("2024") || date.contains("2026"));
 }
@@ -1,6 +1,10 @@
 use cakung_barat_server::mcp::generators::surat_kpr::{SuratKprGenerator, SuratKprRequest};
 use cakung_barat_server::mcp::generators::surat_nib_npwp::{SuratNibNpwpGenerator, SuratNibNpwpRequest};
 use cakung_barat_server::mcp::generators::surat_tidak_mampu::{SuratTidakMampuGenerator, SuratTidakMampuRequest};
+use cakung_barat_server::mcp::generators::surat_domisili::{SuratDomisiliGenerator, SuratDomisiliRequest};
+use cakung_barat_server::mcp::generators::surat_skck::{SuratPengantarSkckGenerator, SuratPengantarSkckRequest};
+use cakung_barat_server::mcp::generators::surat_usaha::{SuratUsahaGenerator, SuratUsahaRequest};
+use cakung_barat_server::mcp::tools::ToolRegistry;
 use serde_json;
 
 // SuratKpr Tests
@@ -94,3 +98,298 @@ fn test_surat_tidak_mampu_request_deserialization() {
     assert_eq!(request.pengisi.nama, "John Doe");
     assert!(request.meta.opsi_sendiri);
 }
+
+// SuratDomisili Tests
+
+fn surat_domisili_json(rt_rw: &str) -> serde_json::Value {
+    serde_json::json!({
+        "pengisi": {
+            "nama": "Siti Aminah",
+            "nik": "3175012345678901",
+            "ttl": "Jakarta, 2 Februari 1992",
+            "jk": false,
+            "agama": "Islam",
+            "pekerjaan": "Wiraswasta",
+            "alamat": "Jl. Kenanga No. 7, Bekasi",
+            "telp": "081234567890"
+        },
+        "domisili": {
+            "alamat": "Jl. Cakung Barat Raya No. 12",
+            "rt_rw": rt_rw,
+            "lama_tinggal": "3 tahun",
+            "keperluan": "Melamar pekerjaan"
+        },
+        "meta": {
+            "kelurahan": "Cakung Barat"
+        }
+    })
+}
+
+#[test]
+fn test_surat_domisili_new_generator() {
+    let result = SuratDomisiliGenerator::new();
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_surat_domisili_request_deserialization() {
+    let request: SuratDomisiliRequest =
+        serde_json::from_value(surat_domisili_json("003/005")).unwrap();
+    assert_eq!(request.pengisi.nama, "Siti Aminah");
+    assert_eq!(request.domisili.rt_rw, "003/005");
+    assert!(request.validate().is_ok());
+}
+
+#[test]
+fn test_surat_domisili_validation_reports_each_field() {
+    let mut json = surat_domisili_json("3-5");
+    json["pengisi"]["nik"] = serde_json::json!("12345");
+    json["domisili"]["keperluan"] = serde_json::json!(" ");
+    let request: SuratDomisiliRequest = serde_json::from_value(json).unwrap();
+
+    let err = request.validate().unwrap_err();
+    assert!(err.contains("3 kesalahan"), "{}", err);
+    assert!(err.contains("[pengisi.nik]"));
+    assert!(err.contains("[domisili.rt_rw]"));
+    assert!(err.contains("[domisili.keperluan]"));
+}
+
+#[test]
+fn test_surat_domisili_validation_reports_rt_rw_and_ttl() {
+    let mut json = surat_domisili_json("0/0");
+    json["pengisi"]["ttl"] = serde_json::json!("Jakarta, 31 April 1992");
+    json["pengisi"]["telp"] = serde_json::json!("0812");
+    let request: SuratDomisiliRequest = serde_json::from_value(json).unwrap();
+
+    let err = request.validate().unwrap_err();
+    assert!(err.contains("3 kesalahan"), "{}", err);
+    assert!(err.contains("[pengisi.ttl] Format tanggal"), "{}", err);
+    assert!(err.contains("[pengisi.telp]"));
+    assert!(err.contains("[domisili.rt_rw] Format RT/RW '0/0'"), "{}", err);
+}
+
+#[test]
+fn test_surat_tidak_mampu_validation_checks_both_birth_dates() {
+    let request: SuratTidakMampuRequest = serde_json::from_value(serde_json::json!({
+        "pengisi": {
+            "nama": "Budi Santoso",
+            "nik": "3175010101900001",
+            "ttl": "Jakarta, 1 Januari 1850",
+            "jk": true,
+            "agama": "Islam",
+            "pekerjaan": "Buruh",
+            "alamat": "Jl. Cakung Barat No. 1",
+            "telp": "081234567890"
+        },
+        "subjek": {
+            "nama": "Andi Santoso",
+            "nik": "",
+            "ttl": "Jakarta, 1 Januari 2999",
+            "jk": true,
+            "agama": "Islam",
+            "pekerjaan": "Pelajar",
+            "alamat": "Jl. Cakung Barat No. 1",
+            "hubungan": "Anak"
+        },
+        "meta": { "opsi_sendiri": false, "kelurahan": "Cakung Barat" }
+    }))
+    .unwrap();
+
+    let err = request.validate().unwrap_err();
+    assert!(err.contains("2 kesalahan"), "{}", err);
+    assert!(err.contains("[pengisi.ttl]") && err.contains("lebih dari 130 tahun"), "{}", err);
+    assert!(err.contains("[subjek.ttl]") && err.contains("masa depan"), "{}", err);
+}
+
+#[test]
+fn test_registry_lists_document_tools() {
+    let registry = ToolRegistry::new().unwrap();
+    let tools = registry.list_tools();
+    let document_tools: Vec<&str> = tools
+        .iter()
+        .map(|tool| tool.name.as_str())
+        .filter(|name| name.starts_with("generate_surat_"))
+        .collect();
+    assert_eq!(
+        document_tools,
+        vec![
+            "generate_surat_tidak_mampu",
+            "generate_surat_kpr_belum_punya_rumah",
+            "generate_surat_nib_npwp",
+            "generate_surat_domisili",
+            "generate_surat_keterangan_usaha",
+            "generate_surat_pengantar_skck",
+        ]
+    );
+}
+
+#[test]
+fn test_registry_rejects_invalid_surat_domisili() {
+    let registry = ToolRegistry::new().unwrap();
+    let result = registry.call_tool(
+        "generate_surat_domisili",
+        Some(surat_domisili_json("RT 3")),
+    );
+    assert!(result.is_error);
+    let text = result.content[0].text.as_deref().unwrap();
+    assert!(text.contains("Format RT/RW 'RT 3' tidak valid"), "{}", text);
+}
+
+// SuratUsaha Tests
+
+fn surat_usaha_json(tahun_mulai: &str) -> serde_json::Value {
+    serde_json::json!({
+        "pemilik": {
+            "nama": "Bambang Sutrisno",
+            "nik": "3175011203850002",
+            "ttl": "Jakarta, 12 Maret 1985",
+            "jk": true,
+            "agama": "Islam",
+            "pekerjaan": "Pedagang",
+            "alamat": "Jl. Cakung Barat Raya No. 30",
+            "telp": "081298765432"
+        },
+        "usaha": {
+            "nama_usaha": "Warung Sembako Berkah",
+            "jenis_usaha": "Perdagangan eceran",
+            "alamat_usaha": "Jl. Cakung Barat Raya No. 30",
+            "tahun_mulai": tahun_mulai,
+            "keperluan": "Pengajuan Kredit Usaha Rakyat"
+        },
+        "meta": {
+            "kelurahan": "Cakung Barat"
+        }
+    })
+}
+
+#[test]
+fn test_surat_usaha_new_generator() {
+    let result = SuratUsahaGenerator::new();
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_surat_usaha_request_deserialization() {
+    let request: SuratUsahaRequest = serde_json::from_value(surat_usaha_json("2019")).unwrap();
+    assert_eq!(request.pemilik.nama, "Bambang Sutrisno");
+    assert_eq!(request.usaha.tahun_mulai, "2019");
+    assert!(request.lama_usaha().starts_with("Sejak tahun 2019"));
+    assert!(request.validate().is_ok());
+}
+
+#[test]
+fn test_surat_usaha_validation_reports_each_field() {
+    let mut json = surat_usaha_json("3019");
+    json["pemilik"]["nik"] = serde_json::json!("12345");
+    json["usaha"]["nama_usaha"] = serde_json::json!("");
+    let request: SuratUsahaRequest = serde_json::from_value(json).unwrap();
+
+    let err = request.validate().unwrap_err();
+    assert!(err.contains("3 kesalahan"), "{}", err);
+    assert!(err.contains("[pemilik.nik]"));
+    assert!(err.contains("[usaha.nama_usaha]"));
+    assert!(err.contains("Tahun '3019' tidak valid"));
+}
+
+#[test]
+fn test_registry_rejects_invalid_surat_usaha() {
+    let registry = ToolRegistry::new().unwrap();
+    let result = registry.call_tool(
+        "generate_surat_keterangan_usaha",
+        Some(surat_usaha_json("lama")),
+    );
+    assert!(result.is_error);
+    let text = result.content[0].text.as_deref().unwrap();
+    assert!(text.contains("[usaha.tahun_mulai]"), "{}", text);
+}
+
+#[test]
+fn test_surat_usaha_renders_pdf() {
+    let generator = SuratUsahaGenerator::new().unwrap();
+    let request: SuratUsahaRequest = serde_json::from_value(surat_usaha_json("2019")).unwrap();
+    let doc = generator.generate(request).unwrap();
+
+    assert!(doc.filename.ends_with(".pdf"), "{}", doc.filename);
+    assert!(doc.pdf.starts_with(b"%PDF"));
+    assert!(doc.pdf.len() > 1024);
+}
+
+// SuratPengantarSkck Tests
+
+fn surat_skck_json() -> serde_json::Value {
+    serde_json::json!({
+        "pemohon": {
+            "nama": "Dewi Lestari Putri",
+            "nik": "3175014506980003",
+            "ttl": "Jakarta, 5 Juni 1998",
+            "jk": false,
+            "agama": "Kristen",
+            "pekerjaan": "Belum Bekerja",
+            "alamat": "Jl. Tipar Cakung No. 4",
+            "telp": "085711223344"
+        },
+        "skck": {
+            "keperluan": "Melamar pekerjaan",
+            "tujuan_instansi": "PT Sinar Abadi",
+            "masa_berlaku": "1 bulan"
+        },
+        "meta": {
+            "kelurahan": "Cakung Barat"
+        }
+    })
+}
+
+#[test]
+fn test_surat_skck_new_generator() {
+    let result = SuratPengantarSkckGenerator::new();
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_surat_skck_request_deserialization() {
+    let request: SuratPengantarSkckRequest = serde_json::from_value(surat_skck_json()).unwrap();
+    assert_eq!(request.pemohon.nama, "Dewi Lestari Putri");
+    assert_eq!(request.skck.tujuan_instansi, "PT Sinar Abadi");
+    assert!(request.validate().is_ok());
+}
+
+#[test]
+fn test_surat_skck_validation_reports_each_field() {
+    let mut json = surat_skck_json();
+    json["skck"]["tujuan_instansi"] = serde_json::json!("");
+    json["skck"]["masa_berlaku"] = serde_json::json!(" ");
+    let request: SuratPengantarSkckRequest = serde_json::from_value(json).unwrap();
+
+    let err = request.validate().unwrap_err();
+    assert!(err.contains("2 kesalahan"), "{}", err);
+    assert!(err.contains("[skck.tujuan_instansi]"));
+    assert!(err.contains("[skck.masa_berlaku]"));
+}
+
+#[test]
+fn test_surat_skck_descriptor_documents_every_field() {
+    let registry = ToolRegistry::new().unwrap();
+    let tool = registry
+        .list_tools()
+        .into_iter()
+        .find(|tool| tool.name == "generate_surat_pengantar_skck")
+        .unwrap();
+
+    for section in ["pemohon", "skck", "meta"] {
+        let properties = tool.input_schema["properties"][section]["properties"]
+            .as_object()
+            .unwrap();
+        for (field, schema) in properties {
+            assert!(schema["description"].is_string(), "{}.{}", section, field);
+        }
+    }
+}
+
+#[test]
+fn test_surat_skck_filename() {
+    let generator = SuratPengantarSkckGenerator::new().unwrap();
+    let request: SuratPengantarSkckRequest = serde_json::from_value(surat_skck_json()).unwrap();
+    let doc = generator.generate(request).unwrap();
+
+    assert_eq!(doc.filename, "skck-dewi-lestari-putri.pdf");
+}
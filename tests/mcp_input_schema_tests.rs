@@ -0,0 +1,182 @@
+//! Tests that generated input schemas agree with the request types
+
+use cakung_barat_server::mcp::generators::{
+    SuratKprRequest, SuratNibNpwpRequest, SuratTidakMampuRequest,
+};
+use cakung_barat_server::mcp::tools::browse_posts::{GetPostingDetailRequest, ListPostingsRequest};
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+type Parse = fn(Value) -> Result<(), String>;
+
+fn parse<T: DeserializeOwned>(value: Value) -> Result<(), String> {
+    serde_json::from_value::<T>(value)
+        .map(|_| ())
+        .map_err(|err| format!("Argumen tidak valid: {}", err))
+}
+
+/// Tools whose input schema is derived, with the type their arguments
+/// deserialize into
+const DERIVED: &[(&str, Parse)] = &[
+    (
+        "generate_surat_tidak_mampu",
+        parse::<SuratTidakMampuRequest>,
+    ),
+    (
+        "generate_surat_kpr_belum_punya_rumah",
+        parse::<SuratKprRequest>,
+    ),
+    ("generate_surat_nib_npwp", parse::<SuratNibNpwpRequest>),
+    ("list_postings", parse::<ListPostingsRequest>),
+    ("get_posting_detail", parse::<GetPostingDetailRequest>),
+    ("get_posting_assets", parse::<GetPostingDetailRequest>),
+];
+
+fn input_schema(name: &str) -> Value {
+    ToolRegistry::new()
+        .unwrap()
+        .list_tools()
+        .into_iter()
+        .find(|tool| tool.name == name)
+        .unwrap_or_else(|| panic!("tool {} not listed", name))
+        .input_schema
+}
+
+fn schema_type(schema: &Value) -> &str {
+    match &schema["type"] {
+        Value::String(ty) => ty,
+        Value::Array(types) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|ty| *ty != "null")
+            .unwrap_or("null"),
+        _ => panic!("schema without a type: {}", schema),
+    }
+}
+
+fn required(schema: &Value) -> Vec<&str> {
+    schema["required"]
+        .as_array()
+        .map(|fields| fields.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// A value valid per `schema`, with every optional property when `full`
+/// and only the required ones otherwise
+fn instance(schema: &Value, full: bool) -> Value {
+    if let Some(first) = schema["enum"].as_array().and_then(|values| values.first()) {
+        return first.clone();
+    }
+    match schema_type(schema) {
+        "object" => {
+            let required = required(schema);
+            let mut object = Map::new();
+            if let Some(properties) = schema["properties"].as_object() {
+                for (name, property) in properties {
+                    if full || required.contains(&name.as_str()) {
+                        object.insert(name.clone(), instance(property, full));
+                    }
+                }
+            }
+            Value::Object(object)
+        }
+        "string" if schema["format"] == "uuid" => {
+            Value::from("00000000-0000-0000-0000-000000000000")
+        }
+        "string" => Value::from("teks"),
+        "boolean" => Value::from(true),
+        "integer" => Value::from(schema["minimum"].as_i64().map_or(1, |min| min.max(1))),
+        "number" => Value::from(1.5),
+        "null" => Value::Null,
+        other => panic!("unexpected schema type {}", other),
+    }
+}
+
+/// Remove each property of `schema` in turn from the full instance and
+/// check that serde rejects the value exactly when the schema requires it
+fn check_required(schema: &Value, path: &[String], full: &Value, parse: Parse, tool: &str) {
+    let Some(properties) = schema["properties"].as_object() else {
+        return;
+    };
+    let required = required(schema);
+    for (name, property) in properties {
+        let mut value = full.clone();
+        let mut object = &mut value;
+        for segment in path {
+            object = &mut object[segment.as_str()];
+        }
+        object.as_object_mut().unwrap().remove(name);
+
+        let field = path
+            .iter()
+            .chain(std::iter::once(name))
+            .cloned()
+            .collect::<Vec<_>>();
+        let rejected = parse(value).is_err();
+        assert_eq!(
+            rejected,
+            required.contains(&name.as_str()),
+            "{}: missing {} {}",
+            tool,
+            field.join("."),
+            if rejected {
+                "was rejected"
+            } else {
+                "was accepted"
+            }
+        );
+
+        if schema_type(property) == "object" {
+            check_required(property, &field, full, parse, tool);
+        }
+    }
+}
+
+#[test]
+fn test_schema_valid_arguments_always_parse() {
+    for (tool, parse) in DERIVED {
+        let schema = input_schema(tool);
+        for full in [false, true] {
+            let arguments = instance(&schema, full);
+            if let Err(err) = parse(arguments.clone()) {
+                panic!("{}: {} rejected {}", tool, err, arguments);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_schema_required_fields_match_serde() {
+    for (tool, parse) in DERIVED {
+        let schema = input_schema(tool);
+        check_required(&schema, &[], &instance(&schema, true), *parse, tool);
+    }
+}
+
+#[test]
+fn test_derived_schemas_keep_descriptions_and_delivery() {
+    for (tool, _) in DERIVED {
+        let schema = input_schema(tool);
+        assert!(schema.get("$defs").is_none(), "{}", tool);
+        let mut pending = vec![schema];
+        while let Some(schema) = pending.pop() {
+            for (name, property) in schema["properties"].as_object().into_iter().flatten() {
+                assert!(property["description"].is_string(), "{}: {}", tool, name);
+                pending.push(property.clone());
+            }
+        }
+    }
+
+    let kpr = input_schema("generate_surat_kpr_belum_punya_rumah");
+    assert_eq!(
+        kpr["properties"]["data"]["properties"]["jk"]["type"],
+        "boolean"
+    );
+    assert_eq!(kpr["properties"]["delivery"]["default"], "inline");
+    let list = input_schema("list_postings");
+    assert_eq!(
+        list["properties"]["sort_by"]["enum"],
+        serde_json::json!(["latest", "oldest"])
+    );
+}
@@ -0,0 +1,236 @@
+//! Tests for the month and date range filters of list_postings
+
+mod common;
+
+use actix_web::web;
+use cakung_barat_server::cache::Cached;
+use cakung_barat_server::mcp::tools::browse_posts::{
+    parse_month, ListPostingsRequest, LIST_POSTINGS_TOOL,
+};
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use cakung_barat_server::posting::models::{DateRange, Post};
+use cakung_barat_server::storage::LocalStorage;
+use cakung_barat_server::AppState;
+use chrono::NaiveDate;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+fn date(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+}
+
+fn request(arguments: Value) -> ListPostingsRequest {
+    serde_json::from_value(arguments).unwrap()
+}
+
+async fn state_with_posts() -> web::Data<AppState> {
+    let state = common::state_builder(Arc::new(LocalStorage::new("storage")))
+        .build()
+        .unwrap();
+
+    let posts = [
+        ("Lomba 17 Agustus", "2024-08-17"),
+        ("Kerja bakti akhir Juli", "2024-07-31"),
+        ("Posyandu Agustus", "2024-08-01"),
+        ("Rapat RW", "2024-09-01"),
+        ("Malam tirakatan", "2024-08-31"),
+    ]
+    .into_iter()
+    .map(|(title, day)| {
+        let mut post = Post::new(
+            title.to_string(),
+            "Kegiatan".to_string(),
+            format!("{} di Cakung Barat", title),
+            None,
+        );
+        post.date = date(day);
+        post
+    })
+    .collect();
+    state
+        .post_cache
+        .insert("all_posts".to_string(), Cached::new(posts))
+        .await;
+    web::Data::new(state)
+}
+
+#[test]
+fn test_month_covers_every_day() {
+    assert_eq!(
+        parse_month("2024-08").unwrap(),
+        DateRange {
+            from: Some(date("2024-08-01")),
+            to: Some(date("2024-08-31")),
+        }
+    );
+    assert_eq!(parse_month("2024-02").unwrap().to, Some(date("2024-02-29")));
+    assert_eq!(parse_month("2023-02").unwrap().to, Some(date("2023-02-28")));
+    assert_eq!(
+        parse_month(" 2023-12 ").unwrap().to,
+        Some(date("2023-12-31"))
+    );
+}
+
+#[test]
+fn test_invalid_month_is_rejected() {
+    for month in [
+        "2024-13",
+        "2024-00",
+        "2024-8",
+        "24-08",
+        "2024/08",
+        "Agustus 2024",
+        "2024-08-01",
+        "",
+    ] {
+        let err = parse_month(month).unwrap_err();
+        assert!(err.contains("YYYY-MM"), "{}: {}", month, err);
+    }
+
+    let err = request(json!({ "month": "Agustus" }))
+        .validate()
+        .unwrap_err();
+    assert!(err.contains("Agustus"), "{}", err);
+}
+
+#[test]
+fn test_explicit_range() {
+    let range = request(json!({ "date_from": "2024-08-10", "date_to": "2024-08-20" }))
+        .date_range()
+        .unwrap();
+    assert_eq!(range.from, Some(date("2024-08-10")));
+    assert_eq!(range.to, Some(date("2024-08-20")));
+    assert!(range.contains(date("2024-08-20")));
+    assert!(!range.contains(date("2024-08-21")));
+
+    let open = request(json!({ "date_from": "2024-08-10" }))
+        .date_range()
+        .unwrap();
+    assert_eq!(open.to, None);
+    assert!(open.contains(date("2030-01-01")));
+
+    assert_eq!(
+        request(json!({})).date_range().unwrap(),
+        DateRange::default()
+    );
+
+    let err = request(json!({ "date_from": "2024-08-20", "date_to": "2024-08-10" }))
+        .validate()
+        .unwrap_err();
+    assert!(err.contains("date_from"), "{}", err);
+    let err = request(json!({ "date_to": "31-08-2024" }))
+        .validate()
+        .unwrap_err();
+    assert!(err.contains("date_to"), "{}", err);
+}
+
+#[test]
+fn test_month_with_explicit_range_is_rejected() {
+    for arguments in [
+        json!({ "month": "2024-08", "date_from": "2024-08-10" }),
+        json!({ "month": "2024-08", "date_to": "2024-08-20" }),
+        json!({ "month": "2024-08", "date_from": "2024-08-01", "date_to": "2024-08-31" }),
+    ] {
+        let err = request(arguments).validate().unwrap_err();
+        assert!(err.contains("tidak keduanya"), "{}", err);
+    }
+}
+
+#[actix_web::test]
+async fn test_list_postings_by_month() {
+    let state = state_with_posts().await;
+    let result = ToolRegistry::new()
+        .unwrap()
+        .call_tool_async(
+            LIST_POSTINGS_TOOL,
+            Some(json!({ "month": "2024-08", "sort_by": "oldest" })),
+            &state,
+        )
+        .await;
+
+    assert!(!result.is_error);
+    assert_eq!(
+        result.content[0].text.as_deref(),
+        Some("Menampilkan 3 dari 3 postingan. Periode: 1 Agustus 2024 s.d. 31 Agustus 2024.")
+    );
+    let structured = result.structured_content.unwrap();
+    assert_eq!(structured["total"], 3);
+    assert_eq!(structured["date_from"], "2024-08-01");
+    assert_eq!(structured["date_to"], "2024-08-31");
+    let mut titles: Vec<&str> = structured["posts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|post| post["title"].as_str().unwrap())
+        .collect();
+    titles.sort();
+    assert_eq!(
+        titles,
+        vec!["Lomba 17 Agustus", "Malam tirakatan", "Posyandu Agustus"]
+    );
+}
+
+#[actix_web::test]
+async fn test_list_postings_by_range() {
+    let state = state_with_posts().await;
+    let registry = ToolRegistry::new().unwrap();
+
+    let result = registry
+        .call_tool_async(
+            LIST_POSTINGS_TOOL,
+            Some(json!({ "date_from": "2024-08-31", "limit": 1 })),
+            &state,
+        )
+        .await;
+    assert!(!result.is_error);
+    assert_eq!(
+        result.content[0].text.as_deref(),
+        Some("Menampilkan 1 dari 2 postingan. Periode: sejak 31 Agustus 2024.")
+    );
+    let structured = result.structured_content.unwrap();
+    assert_eq!(structured["date_from"], "2024-08-31");
+    assert_eq!(structured["date_to"], Value::Null);
+    assert_eq!(structured["has_more"], true);
+
+    // Without a period every post is listed and both ends are null
+    let result = registry
+        .call_tool_async(LIST_POSTINGS_TOOL, Some(json!({ "limit": 50 })), &state)
+        .await;
+    assert_eq!(
+        result.content[0].text.as_deref(),
+        Some("Menampilkan 5 dari 5 postingan.")
+    );
+    let structured = result.structured_content.unwrap();
+    assert_eq!(structured["date_from"], Value::Null);
+    assert_eq!(structured["date_to"], Value::Null);
+
+    let result = registry
+        .call_tool_async(
+            LIST_POSTINGS_TOOL,
+            Some(json!({ "month": "2024-08", "date_to": "2024-08-15" })),
+            &state,
+        )
+        .await;
+    assert!(result.is_error);
+}
+
+#[test]
+fn test_descriptor_documents_period_arguments() {
+    let tool = ToolRegistry::new()
+        .unwrap()
+        .list_tools()
+        .into_iter()
+        .find(|tool| tool.name == LIST_POSTINGS_TOOL)
+        .unwrap();
+    let properties = &tool.input_schema["properties"];
+    assert_eq!(properties["month"]["pattern"], "^[0-9]{4}-[0-9]{2}$");
+    assert_eq!(properties["date_from"]["format"], "date");
+    assert!(properties["date_to"]["description"]
+        .as_str()
+        .unwrap()
+        .contains("YYYY-MM-DD"));
+    assert!(tool.description.contains("2024-08"));
+
+    let required = tool.output_schema.unwrap()["required"].clone();
+    assert!(required.as_array().unwrap().contains(&json!("date_from")));
+}
@@ -0,0 +1,204 @@
+//! Tests for client-visible log messages from MCP tool calls
+
+mod common;
+
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::{test, web, App};
+use cakung_barat_server::auth::generate_access_token;
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use cakung_barat_server::mcp::{McpService, McpState};
+use cakung_barat_server::storage::LocalStorage;
+use serde_json::{json, Value};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn mcp_state() -> web::Data<Arc<McpState>> {
+    let app_state = web::Data::new(
+        common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+            .build()
+            .unwrap(),
+    );
+    let service = McpService::new(ToolRegistry::new().unwrap());
+    web::Data::new(Arc::new(McpState::new(service, app_state)))
+}
+
+fn bearer() -> (&'static str, String) {
+    let token = generate_access_token("admin-id", "admin").unwrap();
+    ("Authorization", format!("Bearer {}", token))
+}
+
+fn set_level(id: u64, level: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "logging/setLevel",
+        "params": { "level": level }
+    })
+}
+
+/// A SKCK request whose applicant has a 3-digit NIK
+fn invalid_skck(id: u64) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "tools/call",
+        "params": {
+            "name": "generate_surat_pengantar_skck",
+            "arguments": {
+                "pemohon": {
+                    "nama": "Budi Santoso",
+                    "nik": "123",
+                    "ttl": "Jakarta, 1 Januari 1990",
+                    "jk": true,
+                    "agama": "Islam",
+                    "pekerjaan": "Karyawan Swasta",
+                    "alamat": "Jl. Cakung Barat No. 1",
+                    "telp": "081234567890"
+                },
+                "skck": {
+                    "keperluan": "Melamar pekerjaan",
+                    "tujuan_instansi": "PT Sinar Abadi",
+                    "masa_berlaku": "1 bulan"
+                },
+                "meta": { "kelurahan": "Cakung Barat" }
+            }
+        }
+    })
+}
+
+fn messages(body: &str) -> Vec<Value> {
+    body.split("\n\n")
+        .filter_map(|event| event.lines().find_map(|line| line.strip_prefix("data: ")))
+        .map(|data| serde_json::from_str(data).unwrap())
+        .collect()
+}
+
+async fn next_event(body: &mut BoxBody) -> Option<String> {
+    let chunk = tokio::time::timeout(
+        Duration::from_secs(30),
+        futures::future::poll_fn(|cx| Pin::new(&mut *body).poll_next(cx)),
+    )
+    .await
+    .ok()??;
+    Some(String::from_utf8(chunk.unwrap().to_vec()).unwrap())
+}
+
+/// Set `level` on a fresh legacy session, make an invalid SKCK call and
+/// return everything its stream received up to the call's response
+async fn invalid_call_with_level(level: &str) -> Vec<Value> {
+    let app = test::init_service(
+        App::new()
+            .app_data(mcp_state())
+            .configure(cakung_barat_server::mcp::config),
+    )
+    .await;
+
+    let mut stream = test::call_service(&app, test::TestRequest::get().uri("/sse").to_request())
+        .await
+        .into_body();
+    let endpoint = next_event(&mut stream).await.unwrap();
+    let url = endpoint
+        .lines()
+        .find_map(|line| line.strip_prefix("data: "))
+        .unwrap()
+        .to_string();
+
+    let mut received = Vec::new();
+    for request in [set_level(1, level), invalid_skck(2)] {
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::post()
+                .uri(&url)
+                .insert_header(bearer())
+                .set_json(request)
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), 202);
+
+        while let Some(event) = next_event(&mut stream).await {
+            received.extend(messages(&event));
+            if received.last().is_some_and(|m| m.get("id").is_some()) {
+                break;
+            }
+        }
+    }
+    received
+}
+
+fn log_messages(received: &[Value]) -> Vec<&Value> {
+    received
+        .iter()
+        .filter(|m| m["method"] == "notifications/message")
+        .collect()
+}
+
+#[actix_web::test]
+async fn test_initialize_declares_logging() {
+    let state = mcp_state();
+    let response = state
+        .service
+        .handle_request(
+            serde_json::from_value(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2025-03-26",
+                    "clientInfo": { "name": "test" }
+                }
+            }))
+            .unwrap(),
+            &state.app_state,
+        )
+        .await
+        .unwrap();
+
+    let result = serde_json::to_value(response).unwrap()["result"].clone();
+    assert!(result["capabilities"]["logging"].is_object(), "{}", result);
+}
+
+#[actix_web::test]
+async fn test_set_level_rejects_unknown_levels() {
+    let state = mcp_state();
+    let response = state
+        .service
+        .handle_request(
+            serde_json::from_value(set_level(1, "verbose")).unwrap(),
+            &state.app_state,
+        )
+        .await
+        .unwrap();
+
+    let response = serde_json::to_value(response).unwrap();
+    assert_eq!(response["error"]["code"], -32602, "{}", response);
+}
+
+#[actix_web::test]
+async fn test_validation_failure_is_logged_at_debug() {
+    let received = invalid_call_with_level("debug").await;
+
+    let logs = log_messages(&received);
+    assert_eq!(logs.len(), 1, "{:?}", received);
+    let params = &logs[0]["params"];
+    assert_eq!(params["level"], "warning");
+    assert_eq!(params["logger"], "generate_surat_pengantar_skck");
+    assert!(
+        params["data"].as_str().unwrap().contains("NIK"),
+        "{}",
+        params
+    );
+
+    let last = received.last().unwrap();
+    assert_eq!(last["id"], 2);
+    assert_eq!(last["result"]["isError"], true);
+}
+
+#[actix_web::test]
+async fn test_validation_failure_is_suppressed_at_error() {
+    let received = invalid_call_with_level("error").await;
+
+    assert!(log_messages(&received).is_empty(), "{:?}", received);
+    assert_eq!(received.last().unwrap()["result"]["isError"], true);
+}
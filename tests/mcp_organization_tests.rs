@@ -0,0 +1,223 @@
+//! Tests for the organization structure MCP tools
+
+mod common;
+
+use actix_web::web;
+use cakung_barat_server::cache::Cached;
+use cakung_barat_server::mcp::tools::organization::{
+    EMPTY_STRUCTURE_MESSAGE, FIND_ORGANIZATION_MEMBER_TOOL, GET_ORGANIZATION_STRUCTURE_TOOL,
+};
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use cakung_barat_server::organization::model::OrganizationMember;
+use cakung_barat_server::organization::tree::{ancestors, build_tree};
+use cakung_barat_server::storage::LocalStorage;
+use cakung_barat_server::AppState;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+async fn state_with_members(members: Vec<OrganizationMember>) -> web::Data<AppState> {
+    let storage = tempfile::tempdir().unwrap().keep();
+    let state = common::state_builder(Arc::new(LocalStorage::new(storage)))
+        .build()
+        .unwrap();
+    if !members.is_empty() {
+        state
+            .organization_cache
+            .insert("org_members".to_string(), Cached::new(members.into()))
+            .await;
+    }
+    web::Data::new(state)
+}
+
+fn member(
+    id: i32,
+    name: &str,
+    position: &str,
+    role: &str,
+    parent_id: Option<i32>,
+    level: i32,
+) -> OrganizationMember {
+    OrganizationMember {
+        id,
+        name: Some(name.to_string()),
+        position: position.to_string(),
+        photo: None,
+        parent_id,
+        level,
+        role: role.to_string(),
+    }
+}
+
+fn sample_members() -> Vec<OrganizationMember> {
+    vec![
+        member(4, "Siti Aminah", "Ketua RT 03", "RT", Some(3), 4),
+        member(1, "Budi Santoso", "Lurah", "Pimpinan", None, 1),
+        member(3, "Ahmad Fauzi", "Ketua RW 05", "RW", Some(2), 3),
+        member(
+            2,
+            "Dewi Lestari",
+            "Sekretaris Kelurahan",
+            "Staf",
+            Some(1),
+            2,
+        ),
+        member(5, "Rudi Hartono", "Ketua RT 04", "RT", Some(3), 4),
+    ]
+}
+
+async fn call(state: &web::Data<AppState>, name: &str, arguments: Option<Value>) -> (bool, Value) {
+    let result = ToolRegistry::new()
+        .unwrap()
+        .call_tool_async(name, arguments, state)
+        .await;
+    let summary = result.content[0].text.clone().unwrap();
+    (
+        result.is_error,
+        result.structured_content.unwrap_or(Value::String(summary)),
+    )
+}
+
+#[actix_web::test]
+async fn test_build_tree_nests_members() {
+    let tree = build_tree(&sample_members());
+
+    assert_eq!(tree.len(), 1);
+    assert_eq!(tree[0].member.position, "Lurah");
+    let rw = &tree[0].children[0].children[0];
+    assert_eq!(rw.member.position, "Ketua RW 05");
+    let rts: Vec<i32> = rw.children.iter().map(|n| n.member.id).collect();
+    assert_eq!(rts, vec![4, 5]);
+}
+
+#[actix_web::test]
+async fn test_build_tree_keeps_orphans_and_cycles() {
+    let members = vec![
+        member(1, "A", "Lurah", "Pimpinan", None, 1),
+        member(2, "B", "Ketua RW 01", "RW", Some(99), 3),
+        member(3, "C", "Ketua RT 01", "RT", Some(4), 4),
+        member(4, "D", "Ketua RT 02", "RT", Some(3), 4),
+    ];
+    let tree = build_tree(&members);
+
+    let roots: Vec<i32> = tree.iter().map(|n| n.member.id).collect();
+    assert_eq!(roots, vec![1, 2, 3]);
+    assert_eq!(tree[2].children[0].member.id, 4);
+    assert!(tree[2].children[0].children.is_empty());
+
+    let chain: Vec<i32> = ancestors(&members, 3).iter().map(|m| m.id).collect();
+    assert_eq!(chain, vec![4]);
+}
+
+#[actix_web::test]
+async fn test_ancestors_run_from_the_top() {
+    let members = sample_members();
+
+    let chain: Vec<&str> = ancestors(&members, 4)
+        .iter()
+        .map(|m| m.position.as_str())
+        .collect();
+    assert_eq!(chain, vec!["Lurah", "Sekretaris Kelurahan", "Ketua RW 05"]);
+    assert!(ancestors(&members, 1).is_empty());
+    assert!(ancestors(&members, 42).is_empty());
+}
+
+#[actix_web::test]
+async fn test_get_organization_structure_returns_tree() {
+    let state = state_with_members(sample_members()).await;
+
+    let (is_error, body) = call(&state, GET_ORGANIZATION_STRUCTURE_TOOL, None).await;
+
+    assert!(!is_error, "{}", body);
+    assert_eq!(body["count"], 5);
+    assert_eq!(body["roots"][0]["name"], "Budi Santoso");
+    assert_eq!(
+        body["roots"][0]["children"][0]["children"][0]["children"][1]["position"],
+        "Ketua RT 04"
+    );
+}
+
+#[actix_web::test]
+async fn test_find_member_by_position_with_ancestors() {
+    let state = state_with_members(sample_members()).await;
+
+    let (is_error, body) = call(
+        &state,
+        FIND_ORGANIZATION_MEMBER_TOOL,
+        Some(json!({ "query": "ketua rt 03" })),
+    )
+    .await;
+
+    assert!(!is_error, "{}", body);
+    assert_eq!(body["total"], 1);
+    let found = &body["members"][0];
+    assert_eq!(found["name"], "Siti Aminah");
+    let chain: Vec<&str> = found["ancestors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["position"].as_str().unwrap())
+        .collect();
+    assert_eq!(chain, vec!["Lurah", "Sekretaris Kelurahan", "Ketua RW 05"]);
+}
+
+#[actix_web::test]
+async fn test_find_member_by_name_and_role_with_limit() {
+    let state = state_with_members(sample_members()).await;
+
+    let (_, body) = call(
+        &state,
+        FIND_ORGANIZATION_MEMBER_TOOL,
+        Some(json!({ "query": "DEWI" })),
+    )
+    .await;
+    assert_eq!(body["members"][0]["position"], "Sekretaris Kelurahan");
+
+    let (_, body) = call(
+        &state,
+        FIND_ORGANIZATION_MEMBER_TOOL,
+        Some(json!({ "query": "rt", "limit": 1 })),
+    )
+    .await;
+    assert_eq!(body["total"], 2);
+    assert_eq!(body["members"].as_array().unwrap().len(), 1);
+}
+
+#[actix_web::test]
+async fn test_find_member_without_match_or_valid_query() {
+    let state = state_with_members(sample_members()).await;
+
+    let (is_error, body) = call(
+        &state,
+        FIND_ORGANIZATION_MEMBER_TOOL,
+        Some(json!({ "query": "bendahara" })),
+    )
+    .await;
+    assert!(!is_error);
+    assert_eq!(body["total"], 0);
+
+    let (is_error, body) = call(
+        &state,
+        FIND_ORGANIZATION_MEMBER_TOOL,
+        Some(json!({ "query": " x " })),
+    )
+    .await;
+    assert!(is_error);
+    assert_eq!(body, "Kata kunci minimal 2 karakter");
+}
+
+#[actix_web::test]
+async fn test_empty_structure_explains_itself() {
+    let state = state_with_members(Vec::new()).await;
+
+    for (name, arguments) in [
+        (GET_ORGANIZATION_STRUCTURE_TOOL, None),
+        (
+            FIND_ORGANIZATION_MEMBER_TOOL,
+            Some(json!({ "query": "ketua rt" })),
+        ),
+    ] {
+        let (is_error, body) = call(&state, name, arguments).await;
+        assert!(!is_error, "{}", name);
+        assert_eq!(body, EMPTY_STRUCTURE_MESSAGE);
+    }
+}
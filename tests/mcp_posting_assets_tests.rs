@@ -0,0 +1,72 @@
+//! Tests for the get_posting_assets MCP tool
+
+mod common;
+
+use actix_web::web;
+use cakung_barat_server::mcp::tools::browse_posts::{object_sizes, GET_POSTING_ASSETS_TOOL};
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use cakung_barat_server::storage::{LocalStorage, ObjectStorage};
+use serde_json::json;
+use std::sync::Arc;
+
+#[actix_web::test]
+async fn test_posting_assets_tool_is_listed() {
+    let registry = ToolRegistry::new().unwrap();
+
+    let tool = registry
+        .list_tools()
+        .into_iter()
+        .find(|tool| tool.name == GET_POSTING_ASSETS_TOOL)
+        .unwrap();
+
+    assert_eq!(tool.input_schema["required"], json!(["id"]));
+}
+
+#[actix_web::test]
+async fn test_invalid_posting_id_is_reported() {
+    let state = web::Data::new(
+        common::state_builder(Arc::new(LocalStorage::new("storage")))
+            .build()
+            .unwrap(),
+    );
+    let registry = ToolRegistry::new().unwrap();
+
+    let result = registry
+        .call_tool_async(
+            GET_POSTING_ASSETS_TOOL,
+            Some(json!({ "id": "bukan-uuid" })),
+            &state,
+        )
+        .await;
+
+    assert!(result.is_error);
+    let text = result.content[0].text.as_deref().unwrap();
+    assert!(text.contains("bukan format UUID yang valid"), "{}", text);
+
+    let result = registry
+        .call_tool_async(GET_POSTING_ASSETS_TOOL, None, &state)
+        .await;
+    assert!(result.is_error);
+}
+
+#[actix_web::test]
+async fn test_object_sizes_lists_each_folder_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = LocalStorage::new(dir.path());
+    storage.upload_file("photo.png", &[0; 10]).await.unwrap();
+    storage.upload_file("other.png", &[0; 99]).await.unwrap();
+    storage
+        .upload_file("posts/abc/lampiran.pdf", &[0; 25])
+        .await
+        .unwrap();
+
+    let sizes = object_sizes(
+        &storage,
+        &["photo.png", "posts/abc/lampiran.pdf", "missing.png"],
+    )
+    .await;
+
+    assert_eq!(sizes.len(), 2);
+    assert_eq!(sizes["photo.png"], 10);
+    assert_eq!(sizes["posts/abc/lampiran.pdf"], 25);
+}
@@ -0,0 +1,178 @@
+//! Tests for progress notifications during MCP document generation
+
+mod common;
+
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::{test, web, App};
+use cakung_barat_server::auth::generate_access_token;
+use cakung_barat_server::mcp::progress::progress_token;
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use cakung_barat_server::mcp::{McpService, McpState};
+use cakung_barat_server::storage::LocalStorage;
+use serde_json::{json, Value};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn mcp_state() -> web::Data<Arc<McpState>> {
+    let app_state = web::Data::new(
+        common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+            .build()
+            .unwrap(),
+    );
+    let service = McpService::new(ToolRegistry::new().unwrap());
+    web::Data::new(Arc::new(McpState::new(service, app_state)))
+}
+
+fn generate_skck(id: u64) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "tools/call",
+        "params": {
+            "name": "generate_surat_pengantar_skck",
+            "arguments": {
+                "pemohon": {
+                    "nama": "Budi Santoso",
+                    "nik": "3175010101900001",
+                    "ttl": "Jakarta, 1 Januari 1990",
+                    "jk": true,
+                    "agama": "Islam",
+                    "pekerjaan": "Karyawan Swasta",
+                    "alamat": "Jl. Cakung Barat No. 1",
+                    "telp": "081234567890"
+                },
+                "skck": {
+                    "keperluan": "Melamar pekerjaan",
+                    "tujuan_instansi": "PT Sinar Abadi",
+                    "masa_berlaku": "1 bulan"
+                },
+                "meta": { "kelurahan": "Cakung Barat" }
+            },
+            "_meta": { "progressToken": "skck-1" }
+        }
+    })
+}
+
+fn bearer() -> (&'static str, String) {
+    let token = generate_access_token("admin-id", "admin").unwrap();
+    ("Authorization", format!("Bearer {}", token))
+}
+
+/// Messages carried by an SSE body, in order
+fn messages(body: &str) -> Vec<Value> {
+    body.split("\n\n")
+        .filter_map(|event| event.lines().find_map(|line| line.strip_prefix("data: ")))
+        .map(|data| serde_json::from_str(data).unwrap())
+        .collect()
+}
+
+/// Progress notifications first, then the response with id `id`
+fn assert_progress_then_result(messages: &[Value], id: u64) {
+    let (last, progress) = messages.split_last().unwrap();
+    assert!(progress.len() >= 2, "{:?}", messages);
+    for notification in progress {
+        assert_eq!(notification["method"], "notifications/progress");
+        assert_eq!(notification["params"]["progressToken"], "skck-1");
+        assert_eq!(notification["params"]["total"], 100);
+    }
+    let steps: Vec<u64> = progress
+        .iter()
+        .map(|n| n["params"]["progress"].as_u64().unwrap())
+        .collect();
+    assert!(steps.windows(2).all(|w| w[0] < w[1]), "{:?}", steps);
+
+    assert_eq!(last["id"], id);
+    assert_eq!(last["result"]["isError"], false, "{}", last);
+}
+
+async fn next_event(body: &mut BoxBody) -> Option<String> {
+    let chunk = tokio::time::timeout(
+        Duration::from_secs(30),
+        futures::future::poll_fn(|cx| Pin::new(&mut *body).poll_next(cx)),
+    )
+    .await
+    .ok()??;
+    Some(String::from_utf8(chunk.unwrap().to_vec()).unwrap())
+}
+
+#[actix_web::test]
+async fn test_progress_token_is_read_from_meta() {
+    let request = generate_skck(1);
+    assert_eq!(progress_token(request.get("params")), Some(json!("skck-1")));
+    assert_eq!(
+        progress_token(Some(&json!({ "_meta": { "progressToken": 7 } }))),
+        Some(json!(7))
+    );
+    assert_eq!(progress_token(Some(&json!({ "name": "ping" }))), None);
+    assert_eq!(progress_token(None), None);
+}
+
+#[actix_web::test]
+async fn test_streamable_reply_carries_progress() {
+    let app = test::init_service(
+        App::new()
+            .app_data(mcp_state())
+            .configure(cakung_barat_server::mcp::config),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/mcp")
+            .insert_header(("Accept", "text/event-stream"))
+            .insert_header(bearer())
+            .set_json(generate_skck(5))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "text/event-stream"
+    );
+
+    let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    assert_progress_then_result(&messages(&body), 5);
+}
+
+#[actix_web::test]
+async fn test_legacy_session_stream_carries_progress() {
+    let app = test::init_service(
+        App::new()
+            .app_data(mcp_state())
+            .configure(cakung_barat_server::mcp::config),
+    )
+    .await;
+
+    let mut stream = test::call_service(&app, test::TestRequest::get().uri("/sse").to_request())
+        .await
+        .into_body();
+    let endpoint = next_event(&mut stream).await.unwrap();
+    let url = endpoint
+        .lines()
+        .find_map(|line| line.strip_prefix("data: "))
+        .unwrap()
+        .to_string();
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri(&url)
+            .insert_header(bearer())
+            .set_json(generate_skck(6))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 202);
+
+    let mut received = Vec::new();
+    while let Some(event) = next_event(&mut stream).await {
+        received.extend(messages(&event));
+        if received.last().is_some_and(|m| m.get("id").is_some()) {
+            break;
+        }
+    }
+    assert_progress_then_result(&received, 6);
+}
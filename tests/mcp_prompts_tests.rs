@@ -0,0 +1,171 @@
+//! Tests for the MCP letter-drafting prompts
+
+mod common;
+
+use actix_web::web;
+use cakung_barat_server::mcp::prompts::{PromptError, PromptRegistry};
+use cakung_barat_server::mcp::rpc::RpcRequest;
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use cakung_barat_server::mcp::McpService;
+use cakung_barat_server::storage::LocalStorage;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+async fn call(method: &str, params: Value) -> Value {
+    let dir = tempfile::tempdir().unwrap();
+    let state = web::Data::new(
+        common::state_builder(Arc::new(LocalStorage::new(dir.path())))
+            .build()
+            .unwrap(),
+    );
+    let service = McpService::new(ToolRegistry::new().unwrap());
+    let request = RpcRequest {
+        jsonrpc: "2.0".to_string(),
+        method: method.to_string(),
+        params: Some(params),
+        id: Some(json!(7)),
+    };
+    serde_json::to_value(service.handle_request(request, &state).await.unwrap()).unwrap()
+}
+
+#[test]
+fn test_prompt_list_serialization() {
+    let prompts = serde_json::to_value(PromptRegistry::new().list_prompts()).unwrap();
+
+    let names: Vec<&str> = prompts
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|prompt| prompt["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "draft_sktm",
+            "draft_kpr",
+            "draft_nib_npwp",
+            "draft_domisili",
+            "draft_sku",
+            "draft_skck",
+        ]
+    );
+    assert_eq!(
+        prompts[0],
+        json!({
+            "name": "draft_sktm",
+            "description": "Memandu pembuatan Surat Keterangan Tidak Mampu (SKTM)",
+            "arguments": [
+                {
+                    "name": "nama",
+                    "description": "Nama lengkap warga, jika sudah diketahui",
+                    "required": false
+                },
+                {
+                    "name": "nik",
+                    "description": "NIK warga (16 digit), jika sudah diketahui",
+                    "required": false
+                }
+            ]
+        })
+    );
+}
+
+#[test]
+fn test_prompt_get_substitutes_arguments() {
+    let arguments = HashMap::from([("nama".to_string(), "Budi Santoso".to_string())]);
+
+    let result = PromptRegistry::new()
+        .get_prompt("draft_skck", &arguments)
+        .unwrap();
+
+    assert_eq!(
+        serde_json::to_value(result).unwrap(),
+        json!({
+            "description": "Memandu pembuatan Surat Pengantar SKCK",
+            "messages": [
+                {
+                    "role": "user",
+                    "content": {
+                        "type": "text",
+                        "text": concat!(
+                            "Saya ingin membuat Surat Pengantar SKCK.\n",
+                            "Nama warga: Budi Santoso\n",
+                            "NIK: (belum diketahui, tanyakan kepada warga)\n\n",
+                            "Kumpulkan data berikut dari warga: nama lengkap, NIK, ",
+                            "tempat/tanggal lahir, jenis kelamin, agama, pekerjaan, ",
+                            "alamat sesuai KTP, nomor telepon, keperluan SKCK, instansi tujuan, ",
+                            "masa berlaku surat pengantar. ",
+                            "Tanyakan data yang belum ada satu per satu dan jangan mengarang data. ",
+                            "Setelah semua data lengkap, panggil tool `generate_surat_pengantar_skck`."
+                        )
+                    }
+                }
+            ]
+        })
+    );
+}
+
+#[test]
+fn test_prompts_reference_existing_tools() {
+    let tools: Vec<String> = ToolRegistry::new()
+        .unwrap()
+        .list_tools()
+        .into_iter()
+        .map(|tool| tool.name)
+        .collect();
+    let registry = PromptRegistry::new();
+
+    for prompt in registry.list_prompts() {
+        let result = registry.get_prompt(&prompt.name, &HashMap::new()).unwrap();
+        let text = &result.messages[0].content.text;
+        assert!(
+            tools
+                .iter()
+                .any(|tool| text.contains(&format!("`{}`", tool))),
+            "{}",
+            prompt.name
+        );
+    }
+}
+
+#[test]
+fn test_unknown_prompt() {
+    let err = PromptRegistry::new()
+        .get_prompt("draft_ktp", &HashMap::new())
+        .unwrap_err();
+    assert_eq!(err, PromptError::NotFound("draft_ktp".to_string()));
+}
+
+#[actix_web::test]
+async fn test_prompts_over_rpc() {
+    let list = call("prompts/list", json!({})).await;
+    assert_eq!(list["result"]["prompts"].as_array().unwrap().len(), 6);
+
+    let get = call(
+        "prompts/get",
+        json!({ "name": "draft_sktm", "arguments": { "nik": "3175010101900001" } }),
+    )
+    .await;
+    let text = get["result"]["messages"][0]["content"]["text"]
+        .as_str()
+        .unwrap();
+    assert!(text.contains("NIK: 3175010101900001"), "{}", text);
+
+    let unknown = call("prompts/get", json!({ "name": "draft_ktp" })).await;
+    assert_eq!(unknown["error"]["code"], -32001);
+    assert_eq!(
+        unknown["error"]["message"],
+        "Prompt 'draft_ktp' tidak tersedia."
+    );
+
+    let init = call(
+        "initialize",
+        json!({ "protocolVersion": "2024-11-05", "clientInfo": { "name": "test" } }),
+    )
+    .await;
+    assert_eq!(
+        init["result"]["capabilities"]["prompts"],
+        json!({ "listChanged": false })
+    );
+}
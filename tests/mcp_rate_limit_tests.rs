@@ -0,0 +1,184 @@
+//! Tests for the hourly quotas on MCP tool calls
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::auth::{generate_access_token, McpAuth};
+use cakung_barat_server::cache::Cached;
+use cakung_barat_server::mcp::tools::rate_limit::{CallOutcome, RateLimitConfig};
+use cakung_barat_server::mcp::tools::registry::ToolContext;
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use cakung_barat_server::mcp::{McpService, McpState};
+use cakung_barat_server::posting::models::Post;
+use cakung_barat_server::storage::LocalStorage;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+async fn app_state() -> web::Data<cakung_barat_server::AppState> {
+    let app_state = common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .build()
+        .unwrap();
+    let post = Post::new(
+        "Posyandu Balita".to_string(),
+        "Kesehatan".to_string(),
+        "Posyandu di balai warga".to_string(),
+        None,
+    );
+    app_state
+        .post_cache
+        .insert("all_posts".to_string(), Cached::new(vec![post]))
+        .await;
+    web::Data::new(app_state)
+}
+
+fn config(vars: &[(&str, &str)]) -> Result<RateLimitConfig, String> {
+    let vars: HashMap<_, _> = vars.iter().copied().collect();
+    RateLimitConfig::from_lookup(|key| vars.get(key).map(|v| v.to_string()))
+}
+
+fn list_postings() -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "tools/call",
+        "params": { "name": "list_postings", "arguments": { "limit": 5 } }
+    })
+}
+
+#[actix_web::test]
+async fn test_rate_limit_config() {
+    let defaults = config(&[]).unwrap();
+    assert_eq!(defaults, RateLimitConfig::default());
+    assert_eq!(defaults.limit("generate_surat_tidak_mampu", true), 30);
+    assert_eq!(defaults.limit("list_postings", false), 600);
+
+    let custom = config(&[
+        ("MCP_DOCUMENT_CALLS_PER_HOUR", "5"),
+        ("MCP_BROWSE_CALLS_PER_HOUR", "50"),
+        (
+            "MCP_TOOL_RATE_LIMITS",
+            "generate_surat_pengantar_skck=2, list_postings = 1000",
+        ),
+    ])
+    .unwrap();
+    assert_eq!(custom.limit("generate_surat_tidak_mampu", true), 5);
+    assert_eq!(custom.limit("generate_surat_pengantar_skck", true), 2);
+    assert_eq!(custom.limit("list_postings", false), 1000);
+    assert_eq!(custom.limit("list_categories", false), 50);
+
+    assert!(config(&[("MCP_DOCUMENT_CALLS_PER_HOUR", "0")]).is_err());
+    assert!(config(&[("MCP_TOOL_RATE_LIMITS", "list_postings")]).is_err());
+    assert!(config(&[("MCP_TOOL_RATE_LIMITS", "list_postings=lots")]).is_err());
+}
+
+#[actix_web::test]
+async fn test_document_tool_past_its_quota_is_throttled() {
+    let registry = ToolRegistry::new()
+        .unwrap()
+        .with_rate_limits(RateLimitConfig {
+            document_calls_per_hour: 2,
+            ..RateLimitConfig::default()
+        });
+    let app_state = app_state().await;
+    let tool = "generate_surat_tidak_mampu";
+    let context = |caller: &str| ToolContext {
+        caller: Some(caller.to_string()),
+        ..ToolContext::default()
+    };
+
+    // Invalid calls still count against the quota
+    for _ in 0..2 {
+        let result = registry
+            .call_tool_with_context(tool, None, &app_state, &context("key:a"))
+            .await;
+        assert!(result.is_error);
+        assert!(!serde_json::to_string(&result).unwrap().contains("Batas"));
+    }
+    let result = registry
+        .call_tool_with_context(tool, None, &app_state, &context("key:a"))
+        .await;
+    assert!(result.is_error);
+    let text = serde_json::to_string(&result.content).unwrap();
+    assert!(text.contains("Batas pemanggilan"), "{}", text);
+    assert!(text.contains("Coba lagi dalam"), "{}", text);
+
+    // Another caller is unaffected
+    let result = registry
+        .call_tool_with_context(tool, None, &app_state, &context("key:b"))
+        .await;
+    assert!(!serde_json::to_string(&result.content)
+        .unwrap()
+        .contains("Batas"));
+
+    let metrics = registry.call_metrics();
+    assert_eq!(metrics.count(tool, CallOutcome::Error), 3);
+    assert_eq!(metrics.count(tool, CallOutcome::Throttled), 1);
+    assert_eq!(metrics.count(tool, CallOutcome::Success), 0);
+
+    // Unknown tools are neither limited nor counted
+    let result = registry
+        .call_tool_with_context("no_such_tool", None, &app_state, &context("key:a"))
+        .await;
+    assert!(result.is_error);
+    assert_eq!(metrics.count("no_such_tool", CallOutcome::Error), 0);
+}
+
+#[actix_web::test]
+async fn test_quota_is_per_credential_over_http() {
+    let registry = ToolRegistry::new()
+        .unwrap()
+        .with_rate_limits(RateLimitConfig {
+            tool_calls_per_hour: HashMap::from([("list_postings".to_string(), 2)]),
+            ..RateLimitConfig::default()
+        });
+    let auth = McpAuth::from_lookup(|key| {
+        (key == "MCP_API_KEYS").then(|| "inspector:mcp,reporter:mcp".to_string())
+    })
+    .unwrap();
+    let state = web::Data::new(Arc::new(
+        McpState::new(McpService::new(registry), app_state().await).with_auth(auth),
+    ));
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .configure(cakung_barat_server::mcp::config),
+    )
+    .await;
+
+    let call = |header: (&'static str, String)| {
+        test::TestRequest::post()
+            .uri("/mcp")
+            .insert_header(header)
+            .set_json(list_postings())
+            .to_request()
+    };
+    let inspector = ("X-API-Key", "inspector".to_string());
+
+    // The same key counts the same whichever header carries it
+    for header in [
+        inspector.clone(),
+        ("Authorization", "Bearer inspector".to_string()),
+    ] {
+        let body: Value = test::call_and_read_body_json(&app, call(header)).await;
+        assert_eq!(body["result"]["isError"], false, "{}", body);
+    }
+    let body: Value = test::call_and_read_body_json(&app, call(inspector)).await;
+    assert_eq!(body["result"]["isError"], true, "{}", body);
+    assert!(body["result"]["content"]
+        .to_string()
+        .contains("2 kali per jam"));
+
+    let token = generate_access_token("admin-id", "admin").unwrap();
+    for header in [
+        ("X-API-Key", "reporter".to_string()),
+        ("Authorization", format!("Bearer {}", token)),
+    ] {
+        let body: Value = test::call_and_read_body_json(&app, call(header)).await;
+        assert_eq!(body["result"]["isError"], false, "{}", body);
+    }
+
+    let metrics = state.service.registry().call_metrics();
+    assert_eq!(metrics.count("list_postings", CallOutcome::Success), 4);
+    assert_eq!(metrics.count("list_postings", CallOutcome::Throttled), 1);
+}
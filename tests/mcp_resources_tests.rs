@@ -0,0 +1,173 @@
+//! Tests for the MCP resources/list and resources/read methods
+
+mod common;
+
+use actix_web::web;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use cakung_barat_server::cache::Cached;
+use cakung_barat_server::db::AppState;
+use cakung_barat_server::mcp::rpc::RpcRequest;
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use cakung_barat_server::mcp::McpService;
+use cakung_barat_server::posting::models::Post;
+use cakung_barat_server::storage::LocalStorage;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// State whose post cache is already warm, so no database is needed
+async fn state_with_posts(dir: &std::path::Path, posts: Vec<Post>) -> web::Data<AppState> {
+    let state = common::state_builder(Arc::new(LocalStorage::new(dir)))
+        .build()
+        .unwrap();
+    state
+        .post_cache
+        .insert("all_posts".to_string(), Cached::new(posts))
+        .await;
+    web::Data::new(state)
+}
+
+fn post(title: &str) -> Post {
+    Post::new(
+        title.to_string(),
+        "Pengumuman".to_string(),
+        "Ringkasan".to_string(),
+        None,
+    )
+}
+
+async fn call(
+    service: &McpService,
+    state: &web::Data<AppState>,
+    method: &str,
+    params: Value,
+) -> Value {
+    let request = RpcRequest {
+        jsonrpc: "2.0".to_string(),
+        method: method.to_string(),
+        params: Some(params),
+        id: Some(json!(1)),
+    };
+    let response = service.handle_request(request, state).await.unwrap();
+    serde_json::to_value(response).unwrap()
+}
+
+fn service() -> McpService {
+    McpService::new(ToolRegistry::new().unwrap())
+}
+
+#[actix_web::test]
+async fn test_resources_list_includes_postings_and_documents() {
+    let dir = tempfile::tempdir().unwrap();
+    let kerja_bakti = post("Kerja Bakti");
+    let state = state_with_posts(dir.path(), vec![kerja_bakti.clone()]).await;
+    state
+        .storage
+        .upload_file("generated/2025-01-02/skck-budi.pdf", b"%PDF-1")
+        .await
+        .unwrap();
+
+    let response = call(&service(), &state, "resources/list", json!({})).await;
+
+    let resources = response["result"]["resources"].as_array().unwrap();
+    assert_eq!(resources.len(), 2);
+    assert_eq!(resources[0]["uri"], format!("posting://{}", kerja_bakti.id));
+    assert_eq!(resources[0]["name"], "Kerja Bakti");
+    assert_eq!(resources[0]["mimeType"], "application/json");
+    assert_eq!(resources[1]["uri"], "document://2025-01-02/skck-budi.pdf");
+    assert_eq!(resources[1]["mimeType"], "application/pdf");
+    assert!(response["result"].get("nextCursor").is_none());
+}
+
+#[actix_web::test]
+async fn test_resources_list_paginates() {
+    let dir = tempfile::tempdir().unwrap();
+    let posts = (0..60).map(|i| post(&format!("Berita {}", i))).collect();
+    let state = state_with_posts(dir.path(), posts).await;
+    let service = service();
+
+    let first = call(&service, &state, "resources/list", json!({})).await;
+    assert_eq!(first["result"]["resources"].as_array().unwrap().len(), 50);
+    assert_eq!(first["result"]["nextCursor"], "50");
+
+    let second = call(
+        &service,
+        &state,
+        "resources/list",
+        json!({ "cursor": "50" }),
+    )
+    .await;
+    let resources = second["result"]["resources"].as_array().unwrap();
+    assert_eq!(resources.len(), 10);
+    assert_eq!(resources[0]["name"], "Berita 50");
+    assert!(second["result"].get("nextCursor").is_none());
+
+    let invalid = call(
+        &service,
+        &state,
+        "resources/list",
+        json!({ "cursor": "abc" }),
+    )
+    .await;
+    assert_eq!(invalid["error"]["code"], -32602);
+}
+
+#[actix_web::test]
+async fn test_resources_read_posting() {
+    let dir = tempfile::tempdir().unwrap();
+    let kerja_bakti = post("Kerja Bakti");
+    let state = state_with_posts(dir.path(), vec![kerja_bakti.clone()]).await;
+    let uri = format!("posting://{}", kerja_bakti.id);
+
+    let response = call(&service(), &state, "resources/read", json!({ "uri": uri })).await;
+
+    let contents = &response["result"]["contents"][0];
+    assert_eq!(contents["uri"], uri);
+    assert_eq!(contents["mimeType"], "application/json");
+    let posting: Value = serde_json::from_str(contents["text"].as_str().unwrap()).unwrap();
+    assert_eq!(posting["title"], "Kerja Bakti");
+}
+
+#[actix_web::test]
+async fn test_resources_read_document() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = state_with_posts(dir.path(), Vec::new()).await;
+    state
+        .storage
+        .upload_file("generated/2025-01-02/skck-budi.pdf", b"%PDF-1")
+        .await
+        .unwrap();
+
+    let response = call(
+        &service(),
+        &state,
+        "resources/read",
+        json!({ "uri": "document://2025-01-02/skck-budi.pdf" }),
+    )
+    .await;
+
+    let contents = &response["result"]["contents"][0];
+    assert_eq!(contents["mimeType"], "application/pdf");
+    let blob = BASE64.decode(contents["blob"].as_str().unwrap()).unwrap();
+    assert_eq!(blob, b"%PDF-1");
+}
+
+#[actix_web::test]
+async fn test_resources_read_miss() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = state_with_posts(dir.path(), vec![post("Kerja Bakti")]).await;
+    let service = service();
+
+    for uri in [
+        format!("posting://{}", uuid::Uuid::new_v4()),
+        "posting://bukan-uuid".to_string(),
+        "document://2025-01-02/tidak-ada.pdf".to_string(),
+        "document://../secret.pdf".to_string(),
+        "file:///etc/passwd".to_string(),
+    ] {
+        let response = call(&service, &state, "resources/read", json!({ "uri": uri })).await;
+        assert_eq!(response["error"]["code"], -32000, "{}", uri);
+        let message = response["error"]["message"].as_str().unwrap();
+        assert!(message.contains("tidak ditemukan"), "{}", message);
+    }
+}
@@ -0,0 +1,175 @@
+//! Tests for the search_postings MCP tool
+
+mod common;
+
+use actix_web::web;
+use cakung_barat_server::cache::Cached;
+use cakung_barat_server::mcp::tools::browse_posts::{match_snippet, SEARCH_POSTINGS_TOOL};
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use cakung_barat_server::posting::models::Post;
+use cakung_barat_server::storage::LocalStorage;
+use cakung_barat_server::AppState;
+use chrono::{NaiveDate, TimeZone, Utc};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use uuid::Uuid;
+
+fn post(title: &str, excerpt: &str, day: u32) -> Post {
+    Post {
+        id: Uuid::new_v4(),
+        title: title.to_string(),
+        category: "Pengumuman".to_string(),
+        date: NaiveDate::from_ymd_opt(2025, 1, day).unwrap(),
+        excerpt: excerpt.to_string(),
+        folder_id: None,
+        created_at: Some(Utc.with_ymd_and_hms(2025, 1, day, 8, 0, 0).unwrap()),
+        updated_at: None,
+        cover_asset_id: None,
+        cover_filename: None,
+        translations_available: Vec::new(),
+        comment_count: 0,
+        assets: None,
+        display: None,
+    }
+}
+
+/// State whose post cache is already filled, so searches never reach the
+/// (unreachable) database
+async fn state_with_posts(posts: Vec<Post>) -> web::Data<AppState> {
+    let state = common::state_builder(Arc::new(LocalStorage::new("storage")))
+        .build()
+        .unwrap();
+    state
+        .post_cache
+        .insert("all_posts".to_string(), Cached::new(posts))
+        .await;
+    web::Data::new(state)
+}
+
+fn sample_posts() -> Vec<Post> {
+    vec![
+        post(
+            "Jadwal Posyandu Balita",
+            "Posyandu Melati dibuka setiap Selasa di RW 03.",
+            1,
+        ),
+        post("Kerja Bakti", "Kerja bakti membersihkan saluran air.", 2),
+        post(
+            "Imunisasi Gratis",
+            "Imunisasi gratis di posyandu Mawar, diskon 50% untuk vitamin.",
+            3,
+        ),
+    ]
+}
+
+async fn search(state: &web::Data<AppState>, arguments: Value) -> (bool, Value) {
+    let registry = ToolRegistry::new().unwrap();
+    let result = registry
+        .call_tool_async(SEARCH_POSTINGS_TOOL, Some(arguments), state)
+        .await;
+    let text = result.content[0].text.clone().unwrap();
+    let body = serde_json::from_str(&text).unwrap_or(Value::String(text));
+    (result.is_error, body)
+}
+
+#[actix_web::test]
+async fn test_search_tool_is_listed() {
+    let registry = ToolRegistry::new().unwrap();
+    let tools = registry.list_tools();
+
+    assert_eq!(tools.len(), 13);
+    let search = tools
+        .iter()
+        .find(|tool| tool.name == SEARCH_POSTINGS_TOOL)
+        .unwrap();
+    assert_eq!(search.input_schema["required"], json!(["query"]));
+}
+
+#[actix_web::test]
+async fn test_search_matches_title_and_excerpt_ignoring_case() {
+    let state = state_with_posts(sample_posts()).await;
+
+    let (is_error, body) = search(&state, json!({ "query": "POSYANDU" })).await;
+
+    assert!(!is_error, "{}", body);
+    assert_eq!(body["total"], 2);
+    assert_eq!(body["has_more"], false);
+    let posts = body["posts"].as_array().unwrap();
+    assert_eq!(posts[0]["title"], "Imunisasi Gratis", "newest first");
+    assert_eq!(posts[1]["title"], "Jadwal Posyandu Balita");
+    assert!(posts[1]["snippet"]
+        .as_str()
+        .unwrap()
+        .contains("Posyandu Melati"));
+}
+
+#[actix_web::test]
+async fn test_search_paginates() {
+    let state = state_with_posts(sample_posts()).await;
+
+    let (_, body) = search(&state, json!({ "query": "posyandu", "limit": 1 })).await;
+
+    assert_eq!(body["total"], 2);
+    assert_eq!(body["posts"].as_array().unwrap().len(), 1);
+    assert_eq!(body["has_more"], true);
+}
+
+#[actix_web::test]
+async fn test_search_without_matches_returns_empty_result() {
+    let state = state_with_posts(sample_posts()).await;
+
+    let (is_error, body) = search(&state, json!({ "query": "pemilu" })).await;
+
+    assert!(!is_error);
+    assert_eq!(body["total"], 0);
+    assert_eq!(body["posts"], json!([]));
+}
+
+#[actix_web::test]
+async fn test_search_treats_wildcards_literally() {
+    let state = state_with_posts(sample_posts()).await;
+
+    let (_, body) = search(&state, json!({ "query": "50%" })).await;
+    assert_eq!(body["total"], 1);
+
+    let (_, body) = search(&state, json!({ "query": "%%" })).await;
+    assert_eq!(body["total"], 0);
+
+    let (_, body) = search(&state, json!({ "query": "__" })).await;
+    assert_eq!(body["total"], 0);
+}
+
+#[actix_web::test]
+async fn test_search_validates_query_length() {
+    let state = state_with_posts(sample_posts()).await;
+
+    let (is_error, body) = search(&state, json!({ "query": " a " })).await;
+    assert!(is_error);
+    assert!(body.as_str().unwrap().contains("minimal 2"), "{}", body);
+
+    let (is_error, _) = search(&state, json!({ "query": "x".repeat(101) })).await;
+    assert!(is_error);
+
+    let (is_error, _) = search(&state, json!({ "query": "posyandu", "limit": 0 })).await;
+    assert!(is_error);
+}
+
+#[actix_web::test]
+async fn test_snippet_trims_long_text() {
+    let text = format!("{} posyandu {}", "a".repeat(100), "b".repeat(100));
+
+    let snippet = match_snippet(&text, "Posyandu").unwrap();
+
+    assert!(
+        snippet.starts_with('…') && snippet.ends_with('…'),
+        "{}",
+        snippet
+    );
+    assert!(snippet.contains("posyandu"));
+    assert_eq!(snippet.chars().count(), 40 + "posyandu".len() + 40 + 2);
+    assert_eq!(match_snippet("Kerja bakti", "posyandu"), None);
+    assert_eq!(
+        match_snippet("Pelayanan ÉKTP", "éktp").as_deref(),
+        Some("Pelayanan ÉKTP")
+    );
+}
@@ -0,0 +1,283 @@
+//! Tests for per-session MCP SSE streams
+
+mod common;
+
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::{test, web, App};
+use cakung_barat_server::config::ServerConfig;
+use cakung_barat_server::mcp::session::{
+    keepalive_stream, SessionConfig, SessionError, SessionManager, StreamEvent,
+};
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use cakung_barat_server::mcp::{McpService, McpState};
+use cakung_barat_server::storage::LocalStorage;
+use futures::StreamExt;
+use serde_json::{json, Value};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+fn mcp_state() -> web::Data<Arc<McpState>> {
+    mcp_state_with(&SessionConfig::default())
+}
+
+fn mcp_state_with(config: &SessionConfig) -> web::Data<Arc<McpState>> {
+    let app_state = web::Data::new(
+        common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+            .build()
+            .unwrap(),
+    );
+    let service = McpService::new(ToolRegistry::new().unwrap());
+    web::Data::new(Arc::new(
+        McpState::new(service, app_state).with_sessions(config),
+    ))
+}
+
+/// Next SSE event from a streaming body, `None` when nothing arrives in time
+async fn next_event(body: &mut BoxBody) -> Option<String> {
+    let chunk = tokio::time::timeout(
+        Duration::from_millis(200),
+        futures::future::poll_fn(|cx| Pin::new(&mut *body).poll_next(cx)),
+    )
+    .await
+    .ok()??;
+    Some(String::from_utf8(chunk.unwrap().to_vec()).unwrap())
+}
+
+fn event_data(event: &str) -> &str {
+    event
+        .lines()
+        .find_map(|line| line.strip_prefix("data: "))
+        .unwrap()
+}
+
+fn ping(id: u64) -> Value {
+    json!({ "jsonrpc": "2.0", "method": "ping", "id": id })
+}
+
+#[actix_web::test]
+async fn test_sessions_are_isolated() {
+    let state = mcp_state();
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .configure(cakung_barat_server::mcp::config),
+    )
+    .await;
+
+    let mut first = test::call_service(&app, test::TestRequest::get().uri("/sse").to_request())
+        .await
+        .into_body();
+    let mut second = test::call_service(&app, test::TestRequest::get().uri("/sse").to_request())
+        .await
+        .into_body();
+
+    let first_endpoint = next_event(&mut first).await.unwrap();
+    let second_endpoint = next_event(&mut second).await.unwrap();
+    assert!(first_endpoint.starts_with("event: endpoint\n"));
+    let first_url = event_data(&first_endpoint).to_string();
+    let second_url = event_data(&second_endpoint).to_string();
+    assert!(first_url.starts_with("/sse?session="));
+    assert_ne!(first_url, second_url);
+    assert_eq!(state.sessions.len(), 2);
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri(&first_url)
+            .set_json(ping(1))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 202);
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri(&second_url)
+            .set_json(ping(2))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 202);
+
+    let message: Value =
+        serde_json::from_str(event_data(&next_event(&mut first).await.unwrap())).unwrap();
+    assert_eq!(message["id"], 1);
+    let message: Value =
+        serde_json::from_str(event_data(&next_event(&mut second).await.unwrap())).unwrap();
+    assert_eq!(message["id"], 2);
+
+    assert!(next_event(&mut first).await.is_none());
+    assert!(next_event(&mut second).await.is_none());
+
+    drop(first);
+    assert_eq!(state.sessions.len(), 1);
+}
+
+#[actix_web::test]
+async fn test_post_without_session_returns_response_inline() {
+    let state = mcp_state();
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .configure(cakung_barat_server::mcp::config),
+    )
+    .await;
+
+    let resp: Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::post()
+            .uri("/mcp")
+            .set_json(ping(3))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp["id"], 3);
+    assert_eq!(resp["result"]["ok"], true);
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/sse?session=unknown")
+            .set_json(ping(4))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_session_manager_lifecycle() {
+    let sessions = SessionManager::new(Duration::from_secs(60));
+    let (id, mut receiver) = sessions.open();
+
+    sessions.send(&id, "halo".to_string()).await.unwrap();
+    assert_eq!(receiver.recv().await.unwrap(), "halo");
+    assert_eq!(
+        sessions.send("missing", String::new()).await,
+        Err(SessionError::NotFound("missing".to_string()))
+    );
+
+    drop(receiver);
+    assert_eq!(
+        sessions.send(&id, String::new()).await,
+        Err(SessionError::Closed(id.clone()))
+    );
+    assert!(sessions.is_empty());
+}
+
+#[actix_web::test]
+async fn test_idle_sessions_expire() {
+    let sessions = SessionManager::new(Duration::from_millis(20));
+    let (id, _receiver) = sessions.open();
+
+    tokio::time::sleep(Duration::from_millis(40)).await;
+
+    assert_eq!(sessions.remove_idle(), 1);
+    assert!(!sessions.contains(&id));
+}
+
+#[actix_web::test]
+async fn test_session_config_reads_keepalive_and_timeout() {
+    let config = SessionConfig::from_lookup(|key| match key {
+        "MCP_SSE_KEEPALIVE_SECS" => Some("10".to_string()),
+        "MCP_SESSION_IDLE_TIMEOUT_MINS" => Some(" 5 ".to_string()),
+        _ => None,
+    })
+    .unwrap();
+    assert_eq!(config.keepalive_interval, Duration::from_secs(10));
+    assert_eq!(config.idle_timeout, Duration::from_secs(5 * 60));
+
+    assert_eq!(
+        SessionConfig::from_lookup(|_| None).unwrap(),
+        SessionConfig::default()
+    );
+    assert!(SessionConfig::from_lookup(|key| {
+        (key == "MCP_SSE_KEEPALIVE_SECS").then(|| "0".to_string())
+    })
+    .is_err());
+}
+
+#[actix_web::test]
+async fn test_sse_stream_sends_keepalives_at_the_interval() {
+    let state = mcp_state_with(&SessionConfig {
+        keepalive_interval: Duration::from_millis(50),
+        idle_timeout: Duration::from_secs(60),
+    });
+    // Through the server's own stack, so Compress would hold keepalives back
+    let config = ServerConfig::from_lookup(|_| None).unwrap();
+    let app = test::init_service(
+        cakung_barat_server::middleware(&config)
+            .app_data(state.clone())
+            .configure(cakung_barat_server::mcp::config),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/sse")
+            .insert_header(("Accept-Encoding", "br"))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.headers().get("content-encoding").unwrap(), "identity");
+    let mut stream = resp.into_body().boxed();
+    next_event(&mut stream).await.unwrap();
+
+    let start = Instant::now();
+    for _ in 0..3 {
+        assert_eq!(next_event(&mut stream).await.unwrap(), ": keepalive\n\n");
+    }
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(140), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_millis(600), "{:?}", elapsed);
+    assert_eq!(state.sessions.len(), 1);
+}
+
+#[actix_web::test]
+async fn test_keepalives_interleave_with_messages() {
+    let sessions = Arc::new(SessionManager::new(Duration::from_secs(60)));
+    let (id, receiver) = sessions.open();
+    let mut stream = Box::pin(keepalive_stream(
+        Arc::clone(&sessions),
+        id.clone(),
+        receiver,
+        Duration::from_millis(30),
+    ));
+
+    assert_eq!(stream.next().await, Some(StreamEvent::Keepalive));
+    sessions.send(&id, "halo".to_string()).await.unwrap();
+    assert_eq!(
+        stream.next().await,
+        Some(StreamEvent::Message("halo".to_string()))
+    );
+    assert_eq!(stream.next().await, Some(StreamEvent::Keepalive));
+
+    sessions.close(&id);
+    assert_eq!(stream.next().await, None);
+}
+
+#[actix_web::test]
+async fn test_idle_session_stream_is_closed() {
+    let state = mcp_state_with(&SessionConfig {
+        keepalive_interval: Duration::from_millis(30),
+        idle_timeout: Duration::from_millis(100),
+    });
+    let sessions = Arc::clone(&state.sessions);
+    let (id, receiver) = sessions.open();
+    let stream = keepalive_stream(
+        Arc::clone(&sessions),
+        id.clone(),
+        receiver,
+        state.keepalive_interval,
+    );
+
+    let events = tokio::time::timeout(Duration::from_secs(2), stream.collect::<Vec<_>>())
+        .await
+        .expect("stream of an idle session should end");
+
+    assert!(!events.is_empty());
+    assert!(events.iter().all(|event| *event == StreamEvent::Keepalive));
+    assert!(!sessions.contains(&id));
+}
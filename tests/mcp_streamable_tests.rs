@@ -0,0 +1,258 @@
+//! Integration tests for the streamable HTTP MCP transport on /mcp
+
+mod common;
+
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::{test, web, App};
+use cakung_barat_server::auth::generate_access_token;
+use cakung_barat_server::cache::Cached;
+use cakung_barat_server::mcp::streamable::MCP_SESSION_HEADER;
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use cakung_barat_server::mcp::{McpService, McpState};
+use cakung_barat_server::posting::models::Post;
+use cakung_barat_server::storage::LocalStorage;
+use serde_json::{json, Value};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+async fn mcp_state() -> web::Data<Arc<McpState>> {
+    let app_state = common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .build()
+        .unwrap();
+    let post = Post::new(
+        "Kerja Bakti RW 05".to_string(),
+        "Pengumuman".to_string(),
+        "Kerja bakti hari Minggu".to_string(),
+        None,
+    );
+    app_state
+        .post_cache
+        .insert("all_posts".to_string(), Cached::new(vec![post]))
+        .await;
+
+    let service = McpService::new(ToolRegistry::new().unwrap());
+    web::Data::new(Arc::new(McpState::new(service, web::Data::new(app_state))))
+}
+
+fn initialize() -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": "2025-03-26",
+            "clientInfo": { "name": "inspector", "version": "1.0" }
+        }
+    })
+}
+
+async fn next_chunk(body: &mut BoxBody) -> Option<String> {
+    let chunk = tokio::time::timeout(
+        Duration::from_millis(200),
+        futures::future::poll_fn(|cx| Pin::new(&mut *body).poll_next(cx)),
+    )
+    .await
+    .ok()??;
+    Some(String::from_utf8(chunk.unwrap().to_vec()).unwrap())
+}
+
+#[actix_web::test]
+async fn test_initialize_handshake_returns_session() {
+    let state = mcp_state().await;
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .configure(cakung_barat_server::mcp::config),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/mcp")
+            .insert_header(("Accept", "application/json, text/event-stream"))
+            .set_json(initialize())
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    let session = resp
+        .headers()
+        .get(MCP_SESSION_HEADER)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["result"]["protocolVersion"], "2025-03-26");
+    assert_eq!(state.sessions.len(), 1);
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/mcp")
+            .insert_header((MCP_SESSION_HEADER, session.as_str()))
+            .set_json(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 202);
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::delete()
+            .uri("/mcp")
+            .insert_header((MCP_SESSION_HEADER, session.as_str()))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 204);
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/mcp")
+            .insert_header((MCP_SESSION_HEADER, session.as_str()))
+            .set_json(json!({ "jsonrpc": "2.0", "id": 2, "method": "ping" }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_tools_call_over_event_stream() {
+    let state = mcp_state().await;
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .configure(cakung_barat_server::mcp::config),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/mcp")
+            .set_json(initialize())
+            .to_request(),
+    )
+    .await;
+    let session = resp
+        .headers()
+        .get(MCP_SESSION_HEADER)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    let token = generate_access_token("admin-id", "admin").unwrap();
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/mcp")
+            .insert_header((MCP_SESSION_HEADER, session.as_str()))
+            .insert_header(("Accept", "text/event-stream"))
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(json!({
+                "jsonrpc": "2.0",
+                "id": 3,
+                "method": "tools/call",
+                "params": { "name": "list_postings", "arguments": { "limit": 5 } }
+            }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "text/event-stream"
+    );
+    assert_eq!(resp.headers().get(MCP_SESSION_HEADER).unwrap(), &session);
+
+    let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    let data = body
+        .strip_prefix("event: message\ndata: ")
+        .and_then(|rest| rest.strip_suffix("\n\n"))
+        .unwrap();
+    let message: Value = serde_json::from_str(data).unwrap();
+    assert_eq!(message["id"], 3);
+    assert_eq!(message["result"]["isError"], false);
+    assert!(message["result"]["content"]
+        .to_string()
+        .contains("Kerja Bakti RW 05"));
+}
+
+#[actix_web::test]
+async fn test_server_push_stream_requires_session() {
+    let state = mcp_state().await;
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .configure(cakung_barat_server::mcp::config),
+    )
+    .await;
+
+    let resp = test::call_service(&app, test::TestRequest::get().uri("/mcp").to_request()).await;
+    assert_eq!(resp.status(), 400);
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/mcp")
+            .insert_header((MCP_SESSION_HEADER, "unknown"))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 404);
+
+    let session = state.sessions.create();
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/mcp")
+            .insert_header((MCP_SESSION_HEADER, session.as_str()))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    let mut body = resp.into_body();
+
+    state
+        .sessions
+        .send(
+            &session,
+            r#"{"jsonrpc":"2.0","method":"notifications/ping"}"#.to_string(),
+        )
+        .await
+        .unwrap();
+    let event = next_chunk(&mut body).await.unwrap();
+    assert_eq!(
+        event,
+        "event: message\ndata: {\"jsonrpc\":\"2.0\",\"method\":\"notifications/ping\"}\n\n"
+    );
+}
+
+#[actix_web::test]
+async fn test_legacy_protocol_version_is_kept() {
+    let state = mcp_state().await;
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .configure(cakung_barat_server::mcp::config),
+    )
+    .await;
+
+    let mut request = initialize();
+    request["params"]["protocolVersion"] = json!("2024-11-05");
+    let body: Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::post()
+            .uri("/sse")
+            .set_json(request)
+            .to_request(),
+    )
+    .await;
+    assert_eq!(body["result"]["protocolVersion"], "2024-11-05");
+}
@@ -0,0 +1,265 @@
+//! Tests for structuredContent and outputSchema on the browse tools
+
+mod common;
+
+use actix_web::web;
+use cakung_barat_server::cache::Cached;
+use cakung_barat_server::mcp::content::ToolResult;
+use cakung_barat_server::mcp::tools::browse_posts::{
+    PostDetailResponse, GET_POSTING_DETAIL_TOOL, LIST_CATEGORIES_TOOL, LIST_POSTINGS_TOOL,
+};
+use cakung_barat_server::mcp::tools::organization::{
+    FIND_ORGANIZATION_MEMBER_TOOL, GET_ORGANIZATION_STRUCTURE_TOOL,
+};
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use cakung_barat_server::organization::model::OrganizationMember;
+use cakung_barat_server::posting::models::Post;
+use cakung_barat_server::storage::LocalStorage;
+use cakung_barat_server::AppState;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+async fn state_with_posts(posts: Vec<Post>) -> web::Data<AppState> {
+    let state = common::state_builder(Arc::new(LocalStorage::new("storage")))
+        .build()
+        .unwrap();
+    state
+        .post_cache
+        .insert("all_posts".to_string(), Cached::new(posts))
+        .await;
+    web::Data::new(state)
+}
+
+fn sample_posts() -> Vec<Post> {
+    vec![
+        Post::new(
+            "Jadwal Posyandu".to_string(),
+            "Kesehatan".to_string(),
+            "Posyandu setiap Selasa".to_string(),
+            None,
+        ),
+        Post::new(
+            "Kerja Bakti".to_string(),
+            "Pengumuman".to_string(),
+            "Kerja bakti hari Minggu".to_string(),
+            None,
+        ),
+    ]
+}
+
+fn output_schema(name: &str) -> Value {
+    ToolRegistry::new()
+        .unwrap()
+        .list_tools()
+        .into_iter()
+        .find(|tool| tool.name == name)
+        .unwrap()
+        .output_schema
+        .unwrap()
+}
+
+/// Check `value` against the subset of JSON Schema used by the descriptors
+fn assert_matches(value: &Value, schema: &Value, path: &str) {
+    assert_matches_in(value, schema, schema, path);
+}
+
+fn assert_matches_in(value: &Value, schema: &Value, root: &Value, path: &str) {
+    let schema = match schema["$ref"].as_str() {
+        Some(reference) => &root["$defs"][reference.strip_prefix("#/$defs/").unwrap()],
+        None => schema,
+    };
+    let types: Vec<&str> = match &schema["type"] {
+        Value::String(t) => vec![t.as_str()],
+        Value::Array(ts) => ts.iter().map(|t| t.as_str().unwrap()).collect(),
+        other => panic!("{}: schema without type: {}", path, other),
+    };
+    let actual = match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    };
+    assert!(
+        types.contains(&actual),
+        "{}: {} is not {:?}",
+        path,
+        actual,
+        types
+    );
+
+    match value {
+        Value::Object(fields) => {
+            let properties = schema["properties"].as_object().unwrap();
+            let mut keys: Vec<&String> = fields.keys().collect();
+            let mut declared: Vec<&String> = properties.keys().collect();
+            keys.sort();
+            declared.sort();
+            assert_eq!(keys, declared, "{}: fields differ from schema", path);
+            for required in schema["required"].as_array().into_iter().flatten() {
+                assert!(fields.contains_key(required.as_str().unwrap()), "{}", path);
+            }
+            for (key, field) in fields {
+                assert_matches_in(field, &properties[key], root, &format!("{}.{}", path, key));
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                assert_matches_in(item, &schema["items"], root, &format!("{}[{}]", path, i));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The JSON text after the summary mirrors the structured content
+fn assert_text_mirrors_structure(result: &ToolResult) {
+    assert!(!result.is_error);
+    assert_eq!(result.content.len(), 2);
+    let text: Value = serde_json::from_str(result.content[1].text.as_deref().unwrap()).unwrap();
+    assert_eq!(Some(&text), result.structured_content.as_ref());
+}
+
+#[actix_web::test]
+async fn test_list_postings_returns_structured_content() {
+    let state = state_with_posts(sample_posts()).await;
+    let result = ToolRegistry::new()
+        .unwrap()
+        .call_tool_async(LIST_POSTINGS_TOOL, Some(json!({ "limit": 1 })), &state)
+        .await;
+
+    assert_text_mirrors_structure(&result);
+    assert_eq!(
+        result.content[0].text.as_deref(),
+        Some("Menampilkan 1 dari 2 postingan.")
+    );
+    let structured = result.structured_content.unwrap();
+    assert_eq!(structured["has_more"], true);
+    assert_matches(
+        &structured,
+        &output_schema(LIST_POSTINGS_TOOL),
+        "list_postings",
+    );
+}
+
+#[actix_web::test]
+async fn test_list_categories_returns_structured_content() {
+    let state = state_with_posts(sample_posts()).await;
+    let result = ToolRegistry::new()
+        .unwrap()
+        .call_tool_async(LIST_CATEGORIES_TOOL, None, &state)
+        .await;
+
+    assert_text_mirrors_structure(&result);
+    let structured = result.structured_content.unwrap();
+    assert_eq!(structured["categories"], json!(["Kesehatan", "Pengumuman"]));
+    assert_matches(
+        &structured,
+        &output_schema(LIST_CATEGORIES_TOOL),
+        "list_categories",
+    );
+}
+
+#[actix_web::test]
+async fn test_posting_detail_schema_matches_response() {
+    let response = PostDetailResponse {
+        id: "5f0c6c8e-8f5b-4a5e-9d7c-0d6f3c1b2a10".to_string(),
+        title: "Kerja Bakti".to_string(),
+        category: "Pengumuman".to_string(),
+        date: "2025-01-05".to_string(),
+        excerpt: "Kerja bakti hari Minggu".to_string(),
+        created_at: Some("2025-01-05T08:00:00+00:00".to_string()),
+        updated_at: None,
+        image_urls: vec!["/assets/serve/kerja-bakti.jpg".to_string()],
+    };
+
+    assert_matches(
+        &serde_json::to_value(&response).unwrap(),
+        &output_schema(GET_POSTING_DETAIL_TOOL),
+        "get_posting_detail",
+    );
+}
+
+fn member(id: i32, position: &str, parent_id: Option<i32>, level: i32) -> OrganizationMember {
+    OrganizationMember {
+        id,
+        name: Some(format!("Anggota {}", id)),
+        position: position.to_string(),
+        photo: None,
+        parent_id,
+        level,
+        role: "Pengurus".to_string(),
+    }
+}
+
+#[actix_web::test]
+async fn test_organization_tools_return_structured_content() {
+    let state = state_with_posts(Vec::new()).await;
+    state
+        .organization_cache
+        .insert(
+            "org_members".to_string(),
+            Cached::new(
+                vec![
+                    member(1, "Lurah", None, 1),
+                    member(2, "Sekretaris Kelurahan", Some(1), 2),
+                    member(3, "Ketua RW 01", Some(2), 3),
+                ]
+                .into(),
+            ),
+        )
+        .await;
+    let registry = ToolRegistry::new().unwrap();
+
+    let result = registry
+        .call_tool_async(GET_ORGANIZATION_STRUCTURE_TOOL, None, &state)
+        .await;
+    assert_text_mirrors_structure(&result);
+    assert_matches(
+        result.structured_content.as_ref().unwrap(),
+        &output_schema(GET_ORGANIZATION_STRUCTURE_TOOL),
+        "get_organization_structure",
+    );
+
+    let result = registry
+        .call_tool_async(
+            FIND_ORGANIZATION_MEMBER_TOOL,
+            Some(json!({ "query": "ketua rw" })),
+            &state,
+        )
+        .await;
+    assert_text_mirrors_structure(&result);
+    assert_matches(
+        result.structured_content.as_ref().unwrap(),
+        &output_schema(FIND_ORGANIZATION_MEMBER_TOOL),
+        "find_organization_member",
+    );
+}
+
+#[actix_web::test]
+async fn test_structured_content_is_optional() {
+    let tools = ToolRegistry::new().unwrap().list_tools();
+    let with_schema: Vec<&str> = tools
+        .iter()
+        .filter(|tool| tool.output_schema.is_some())
+        .map(|tool| tool.name.as_str())
+        .collect();
+    assert_eq!(
+        with_schema,
+        vec![
+            LIST_POSTINGS_TOOL,
+            GET_POSTING_DETAIL_TOOL,
+            LIST_CATEGORIES_TOOL,
+            GET_ORGANIZATION_STRUCTURE_TOOL,
+            FIND_ORGANIZATION_MEMBER_TOOL
+        ]
+    );
+
+    let plain = serde_json::to_value(tools.iter().find(|t| t.output_schema.is_none())).unwrap();
+    assert!(plain.get("outputSchema").is_none());
+
+    let text_only = serde_json::to_value(ToolResult::success_text("ok")).unwrap();
+    assert!(text_only.get("structuredContent").is_none());
+}
@@ -0,0 +1,212 @@
+//! Tests for template hot-reload and the template admin endpoints
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::auth::generate_access_token;
+use cakung_barat_server::mcp::generators::common::get_static_dir;
+use cakung_barat_server::mcp::generators::{GenerationConfig, GeneratorError, TemplateStore};
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use cakung_barat_server::mcp::{McpService, McpState};
+use cakung_barat_server::storage::LocalStorage;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const SKCK: &str = "skck.typ";
+
+/// A copy of the bundled templates that tests may overwrite
+fn template_dir() -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for entry in fs::read_dir(get_static_dir()).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|ext| ext == "typ") {
+            fs::copy(&path, dir.path().join(path.file_name().unwrap())).unwrap();
+        }
+    }
+    dir
+}
+
+fn registry(dir: &Path) -> ToolRegistry {
+    ToolRegistry::with_templates(
+        &GenerationConfig::default(),
+        Arc::new(TemplateStore::new(dir)),
+    )
+    .unwrap()
+}
+
+/// The SKCK template with a call to a function Typst does not know
+fn broken_skck(dir: &Path) -> String {
+    let template = fs::read_to_string(dir.join(SKCK)).unwrap();
+    template.replacen(") = {", ") = {\n  #fungsi_tidak_ada()\n", 1)
+}
+
+fn bearer() -> (&'static str, String) {
+    let token = generate_access_token("admin-id", "admin").unwrap();
+    ("Authorization", format!("Bearer {}", token))
+}
+
+fn mcp_state(dir: &Path) -> web::Data<Arc<McpState>> {
+    let app_state = web::Data::new(
+        common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+            .build()
+            .unwrap(),
+    );
+    let service = McpService::new(registry(dir));
+    web::Data::new(Arc::new(McpState::new(service, app_state)))
+}
+
+#[actix_web::test]
+async fn test_store_reloads_changed_template() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("surat.typ");
+    fs::write(&path, "= Versi satu").unwrap();
+    let store = TemplateStore::new(dir.path());
+
+    let first = store.get("surat.typ").unwrap();
+    assert_eq!(&*first, "= Versi satu");
+    assert!(Arc::ptr_eq(&first, &store.get("surat.typ").unwrap()));
+
+    fs::write(&path, "= Versi kedua, lebih panjang").unwrap();
+    assert_eq!(
+        &*store.get("surat.typ").unwrap(),
+        "= Versi kedua, lebih panjang"
+    );
+
+    fs::remove_file(&path).unwrap();
+    assert!(matches!(
+        store.get("surat.typ"),
+        Err(GeneratorError::TemplateIo(_))
+    ));
+}
+
+#[actix_web::test]
+async fn test_store_lists_templates_with_hashes() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("b.typ"), "abc").unwrap();
+    fs::write(dir.path().join("a.typ"), "").unwrap();
+    fs::write(dir.path().join("catatan.txt"), "bukan template").unwrap();
+    let store = TemplateStore::new(dir.path());
+
+    let info = store.replace("b.typ", "abc").unwrap();
+    assert_eq!(
+        info.sha256,
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+
+    let names: Vec<String> = store.list().unwrap().into_iter().map(|t| t.name).collect();
+    assert_eq!(names, vec!["a.typ", "b.typ"]);
+}
+
+#[actix_web::test]
+async fn test_replace_template_rejects_failed_dry_run() {
+    let dir = template_dir();
+    let original = fs::read_to_string(dir.path().join(SKCK)).unwrap();
+    let registry = registry(dir.path());
+
+    let result = registry
+        .replace_template(SKCK, broken_skck(dir.path()))
+        .await;
+
+    assert!(
+        matches!(result, Err(GeneratorError::Compile(_))),
+        "{:?}",
+        result
+    );
+    assert_eq!(fs::read_to_string(dir.path().join(SKCK)).unwrap(), original);
+}
+
+#[actix_web::test]
+async fn test_replaced_template_is_used_by_the_next_call() {
+    let dir = template_dir();
+    let registry = registry(dir.path());
+    let replacement = format!(
+        "{}\n// Revisi kop surat\n",
+        fs::read_to_string(dir.path().join(SKCK)).unwrap()
+    );
+
+    let info = registry
+        .replace_template(SKCK, replacement.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(info.size, replacement.len() as u64);
+    assert_eq!(
+        fs::read_to_string(dir.path().join(SKCK)).unwrap(),
+        replacement
+    );
+    assert_eq!(&*registry.templates().get(SKCK).unwrap(), replacement);
+}
+
+#[actix_web::test]
+async fn test_bundled_templates_pass_their_dry_run() {
+    let dir = template_dir();
+    let registry = registry(dir.path());
+
+    for name in registry.template_names() {
+        let contents = fs::read_to_string(dir.path().join(name)).unwrap();
+        let result = registry.replace_template(name, contents).await;
+        assert!(result.is_ok(), "{}: {:?}", name, result);
+    }
+}
+
+#[actix_web::test]
+async fn test_template_endpoints() {
+    let dir = template_dir();
+    let app = test::init_service(App::new().app_data(mcp_state(dir.path())).service(
+        web::scope("/api").configure(cakung_barat_server::mcp::template_handlers::config),
+    ))
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/api/admin/templates")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 401);
+
+    let body: Vec<Value> = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get()
+            .uri("/api/admin/templates")
+            .insert_header(bearer())
+            .to_request(),
+    )
+    .await;
+    assert_eq!(body.len(), 6);
+    assert!(body
+        .iter()
+        .all(|t| t["sha256"].as_str().unwrap().len() == 64));
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::put()
+            .uri("/api/admin/templates/lain.typ")
+            .insert_header(bearer())
+            .set_payload("= Surat")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 404);
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::put()
+            .uri("/api/admin/templates/skck.typ")
+            .insert_header(bearer())
+            .set_payload(broken_skck(dir.path()))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 400);
+    let body: Value = test::read_body_json(resp).await;
+    assert!(
+        body["message"].as_str().unwrap().contains("dry-run"),
+        "{}",
+        body
+    );
+}
@@ -0,0 +1,181 @@
+//! Tests for `notifications/tools/list_changed` on open MCP sessions
+
+mod common;
+
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::{test, web, App};
+use cakung_barat_server::auth::generate_access_token;
+use cakung_barat_server::mcp::session::SessionManager;
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use cakung_barat_server::mcp::{tool_handlers, McpService, McpState};
+use cakung_barat_server::storage::LocalStorage;
+use serde_json::{json, Value};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn mcp_state() -> web::Data<Arc<McpState>> {
+    let app_state = web::Data::new(
+        common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+            .build()
+            .unwrap(),
+    );
+    let service = McpService::new(ToolRegistry::new().unwrap());
+    web::Data::new(Arc::new(McpState::new(service, app_state)))
+}
+
+fn bearer() -> (&'static str, String) {
+    let token = generate_access_token("admin-id", "admin").unwrap();
+    ("Authorization", format!("Bearer {}", token))
+}
+
+/// Next SSE event from a streaming body, `None` when nothing arrives in time
+async fn next_event(body: &mut BoxBody) -> Option<String> {
+    let chunk = tokio::time::timeout(
+        Duration::from_millis(200),
+        futures::future::poll_fn(|cx| Pin::new(&mut *body).poll_next(cx)),
+    )
+    .await
+    .ok()??;
+    Some(String::from_utf8(chunk.unwrap().to_vec()).unwrap())
+}
+
+fn event_data(event: &str) -> Value {
+    let data = event
+        .lines()
+        .find_map(|line| line.strip_prefix("data: "))
+        .unwrap();
+    serde_json::from_str(data).unwrap()
+}
+
+fn tool_names(response: &Value) -> Vec<String> {
+    response["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[actix_web::test]
+async fn test_broadcast_reaches_every_open_stream() {
+    let sessions = SessionManager::default();
+    let (_, mut first) = sessions.open();
+    let (_, mut second) = sessions.open();
+    sessions.create();
+
+    assert_eq!(sessions.broadcast("pesan"), 2);
+    assert_eq!(first.recv().await.unwrap(), "pesan");
+    assert_eq!(second.recv().await.unwrap(), "pesan");
+}
+
+#[actix_web::test]
+async fn test_toggling_browse_tools_notifies_open_sessions() {
+    let state = mcp_state();
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .configure(cakung_barat_server::mcp::config)
+            .service(web::scope("/api").configure(tool_handlers::config)),
+    )
+    .await;
+
+    let init = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/sse")
+            .set_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": { "protocolVersion": "2024-11-05", "clientInfo": { "name": "test" } }
+            }))
+            .to_request(),
+    )
+    .await;
+    let init: Value = test::read_body_json(init).await;
+    assert_eq!(
+        init["result"]["capabilities"]["tools"],
+        json!({ "listChanged": true })
+    );
+
+    let mut stream = test::call_service(&app, test::TestRequest::get().uri("/sse").to_request())
+        .await
+        .into_body();
+    assert!(next_event(&mut stream)
+        .await
+        .unwrap()
+        .starts_with("event: endpoint\n"));
+
+    let list_tools = || {
+        test::TestRequest::post()
+            .uri("/sse")
+            .insert_header(bearer())
+            .set_json(json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }))
+            .to_request()
+    };
+    let before: Value = test::read_body_json(test::call_service(&app, list_tools()).await).await;
+    assert!(tool_names(&before).contains(&"list_postings".to_string()));
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::put()
+            .uri("/api/admin/mcp/tools/browse")
+            .insert_header(bearer())
+            .set_json(json!({ "enabled": false }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body, json!({ "enabled": false, "changed": true }));
+
+    let notification = event_data(&next_event(&mut stream).await.unwrap());
+    assert_eq!(
+        notification,
+        json!({ "jsonrpc": "2.0", "method": "notifications/tools/list_changed" })
+    );
+
+    let after: Value = test::read_body_json(test::call_service(&app, list_tools()).await).await;
+    let names = tool_names(&after);
+    assert_eq!(names.len(), tool_names(&before).len() - 5);
+    assert!(!names.contains(&"list_postings".to_string()));
+    assert!(names.contains(&"get_organization_structure".to_string()));
+
+    // Setting the same value again changes nothing and stays quiet
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::put()
+            .uri("/api/admin/mcp/tools/browse")
+            .insert_header(bearer())
+            .set_json(json!({ "enabled": false }))
+            .to_request(),
+    )
+    .await;
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["changed"], false);
+    assert!(next_event(&mut stream).await.is_none());
+
+    // A manual refresh always notifies
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/admin/mcp/tools/refresh")
+            .insert_header(bearer())
+            .to_request(),
+    )
+    .await;
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["notified"], 1);
+    let notification = event_data(&next_event(&mut stream).await.unwrap());
+    assert_eq!(notification["method"], "notifications/tools/list_changed");
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/admin/mcp/tools/refresh")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 401);
+}
@@ -1,4 +1,5 @@
-use cakung_barat_server::mcp::generators::validation::{ValidationErrors, ValidationError, validate_required, validate_nik};
+use cakung_barat_server::mcp::generators::validation::{ValidationErrors, ValidationError, validate_required, validate_nik, validate_rt_rw, validate_rt_rw_parts, validate_kode_pos, validate_ttl, validate_year_range, parse_indonesian_date};
+use chrono::{Datelike, Local, NaiveDate};
 
 #[test]
 fn test_validate_required_empty() {
@@ -45,3 +46,97 @@ fn test_validation_errors_message() {
     assert!(msg.contains("Nama tidak boleh kosong"));
     assert!(msg.contains("16 digit"));
 }
+
+#[test]
+fn test_validate_rt_rw_valid() {
+    for value in ["003/005", "3/5", " 12 / 001 "] {
+        let mut errors = ValidationErrors::new();
+        validate_rt_rw(value, "rt_rw", &mut errors);
+        assert!(errors.is_empty(), "{}", value);
+    }
+}
+
+#[test]
+fn test_validate_rt_rw_invalid() {
+    for value in ["", "003", "003-005", "RT 3/RW 5", "0003/005", "3/", "0/0", "003/000"] {
+        let mut errors = ValidationErrors::new();
+        validate_rt_rw(value, "rt_rw", &mut errors);
+        assert_eq!(errors.len(), 1, "{}", value);
+    }
+}
+
+#[test]
+fn test_validate_year_range() {
+    for (value, valid) in [("2019", true), (" 1990 ", true), ("1899", false), ("2101", false), ("19", false), ("", false)] {
+        let mut errors = ValidationErrors::new();
+        validate_year_range(value, "tahun", "Tahun", 1900, 2100, &mut errors);
+        assert_eq!(errors.is_empty(), valid, "{}", value);
+    }
+}
+
+#[test]
+fn test_validate_rt_rw_zero_pads() {
+    for (value, expected) in [("3/5", "003/005"), (" 12 / 1 ", "012/001"), ("003/005", "003/005")] {
+        let mut errors = ValidationErrors::new();
+        assert_eq!(validate_rt_rw(value, "rt_rw", &mut errors).as_deref(), Some(expected));
+    }
+}
+
+#[test]
+fn test_validate_rt_rw_parts() {
+    let mut errors = ValidationErrors::new();
+    assert_eq!(validate_rt_rw_parts("3", " 05 ", "rt", "rw", &mut errors).as_deref(), Some("003/005"));
+    assert!(errors.is_empty());
+
+    let mut errors = ValidationErrors::new();
+    assert_eq!(validate_rt_rw_parts("0", "", "rt", "rw", &mut errors), None);
+    let msg = errors.to_mcp_message();
+    assert!(msg.contains("2 kesalahan"), "{}", msg);
+    assert!(msg.contains("[rt]") && msg.contains("[rw] RW tidak boleh kosong"), "{}", msg);
+}
+
+#[test]
+fn test_validate_kode_pos() {
+    for (value, valid) in [("13910", true), (" 13910 ", true), ("1391", false), ("139100", false), ("0391a", false), ("01234", false), ("", false)] {
+        let mut errors = ValidationErrors::new();
+        validate_kode_pos(value, "kode_pos", &mut errors);
+        assert_eq!(errors.is_empty(), valid, "{}", value);
+    }
+}
+
+#[test]
+fn test_parse_indonesian_date() {
+    let date = NaiveDate::from_ymd_opt(1990, 8, 17);
+    for value in ["17 Agustus 1990", "17 agustus 1990", "17 Agu 1990", " 17  AGUSTUS  1990 ", "17-08-1990", "17/8/1990"] {
+        assert_eq!(parse_indonesian_date(value), date, "{}", value);
+    }
+    for value in ["30 Februari 1990", "17 Ag 1990", "17 August 1990", "17 Agustus 90", "1990-08-17", "Agustus 1990"] {
+        assert_eq!(parse_indonesian_date(value), None, "{}", value);
+    }
+}
+
+#[test]
+fn test_validate_ttl_returns_date_of_birth() {
+    let mut errors = ValidationErrors::new();
+    let date = validate_ttl("Kota Bekasi, Jawa Barat, 1 Januari 1990", "ttl", &mut errors);
+    assert!(errors.is_empty(), "{}", errors.to_mcp_message());
+    assert_eq!(date, NaiveDate::from_ymd_opt(1990, 1, 1));
+}
+
+#[test]
+fn test_validate_ttl_rejects_impossible_dates() {
+    let next_year = Local::now().year() + 1;
+    let future = format!("Jakarta, 1 Januari {}", next_year);
+    for (value, message) in [
+        ("Jakarta 1 Januari 1990", "Format tanggal"),
+        (", 1 Januari 1990", "Format tanggal"),
+        ("Jakarta, 31 April 1990", "Format tanggal"),
+        (future.as_str(), "masa depan"),
+        ("Jakarta, 1 Januari 1850", "lebih dari 130 tahun"),
+    ] {
+        let mut errors = ValidationErrors::new();
+        assert_eq!(validate_ttl(value, "ttl", &mut errors), None, "{}", value);
+        assert_eq!(errors.len(), 1, "{}", value);
+        assert!(errors.to_mcp_message().contains(message), "{}: {}", value, errors.to_mcp_message());
+    }
+}
@@ -0,0 +1,129 @@
+//! Tests for HEAD support and 405 answers on the route table
+
+mod common;
+
+use actix_web::http::{header, Method};
+use actix_web::middleware::from_fn;
+use actix_web::{test, web, App};
+use cakung_barat_server::method_routing::{allowed_methods, method_routing};
+use cakung_barat_server::storage::LocalStorage;
+use cakung_barat_server::{error_handlers, routes};
+use std::sync::Arc;
+
+macro_rules! test_app {
+    () => {{
+        // Every request here is answered before the database is reached,
+        // or from the post cache filled below
+        let state = common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+            .build()
+            .unwrap();
+        state
+            .post_cache
+            .insert(
+                "all_posts".to_string(),
+                cakung_barat_server::cache::Cached::new(Vec::new()),
+            )
+            .await;
+        test::init_service(
+            App::new()
+                .wrap(from_fn(method_routing))
+                .app_data(web::Data::new(state))
+                .configure(routes)
+                .default_service(web::route().to(error_handlers::not_found)),
+        )
+        .await
+    }};
+}
+
+#[actix_web::test]
+async fn test_allowed_methods_include_head_after_get() {
+    assert_eq!(allowed_methods("GET, POST"), ["GET", "HEAD", "POST"]);
+    assert_eq!(allowed_methods("PUT, DELETE"), ["PUT", "DELETE"]);
+    assert_eq!(allowed_methods("GET, HEAD"), ["GET", "HEAD"]);
+    assert!(allowed_methods("").is_empty());
+}
+
+#[actix_web::test]
+async fn test_head_is_answered_like_get_without_a_body() {
+    let app = test_app!();
+
+    let get = test::call_service(
+        &app,
+        test::TestRequest::get().uri("/api/postings").to_request(),
+    )
+    .await;
+    assert_eq!(get.status(), 200);
+    let get_type = get.headers().get(header::CONTENT_TYPE).cloned();
+    assert_eq!(test::read_body(get).await, "[]");
+
+    let head = test::call_service(
+        &app,
+        test::TestRequest::default()
+            .method(Method::HEAD)
+            .uri("/api/postings")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(head.status(), 200);
+    assert_eq!(head.headers().get(header::CONTENT_TYPE).cloned(), get_type);
+    assert!(test::read_body(head).await.is_empty());
+
+    // Protected routes still check the token
+    let head = test::call_service(
+        &app,
+        test::TestRequest::default()
+            .method(Method::HEAD)
+            .uri("/api/admin/stats")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(head.status(), 401);
+}
+
+#[actix_web::test]
+async fn test_unsupported_method_gets_405_with_allow() {
+    let app = test_app!();
+
+    for (method, uri, allow) in [
+        (Method::PATCH, "/api/postings", "GET, HEAD, POST"),
+        (Method::PATCH, "/api/postings/1", "GET, HEAD, PUT, DELETE"),
+        (Method::DELETE, "/api/gallery", "GET, HEAD"),
+        (Method::GET, "/api/admin/backup", "POST"),
+        (Method::POST, "/api/admin/submissions/1", "GET, HEAD"),
+        (Method::PUT, "/readyz", "GET, HEAD"),
+    ] {
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::default()
+                .method(method.clone())
+                .uri(uri)
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), 405, "{} {}", method, uri);
+        assert_eq!(
+            resp.headers().get(header::ALLOW).unwrap(),
+            allow,
+            "{} {}",
+            method,
+            uri
+        );
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"], "MethodNotAllowed");
+        assert_eq!(
+            body["message"],
+            format!("{} is not allowed on {}, use {}", method, uri, allow)
+        );
+    }
+
+    // Unknown paths are still 404
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::default()
+            .method(Method::PATCH)
+            .uri("/api/nothing-here")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 404);
+}
@@ -0,0 +1,119 @@
+//! Tests for `/metrics` protection via METRICS_AUTH.
+
+use actix_web::middleware::from_fn;
+use actix_web::{test, web, App, HttpResponse};
+use base64::Engine;
+use cakung_barat_server::auth::{generate_access_token, metrics_auth_guard, MetricsAuth};
+
+async fn metrics() -> HttpResponse {
+    HttpResponse::Ok().body("# metrics")
+}
+
+fn basic_header(credentials: &str) -> String {
+    format!(
+        "Basic {}",
+        base64::engine::general_purpose::STANDARD.encode(credentials)
+    )
+}
+
+#[actix_web::test]
+async fn test_parse_metrics_auth() {
+    assert_eq!(MetricsAuth::parse("bearer"), Some(MetricsAuth::Bearer));
+    assert_eq!(
+        MetricsAuth::parse("basic:prom:secret"),
+        Some(MetricsAuth::Basic {
+            username: "prom".to_string(),
+            password: "secret".to_string(),
+        })
+    );
+    assert_eq!(MetricsAuth::parse("basic:prom"), None);
+    assert_eq!(MetricsAuth::parse("token"), None);
+}
+
+#[actix_web::test]
+async fn test_unrecognized_metrics_auth_is_an_error() {
+    let lookup =
+        |value: &'static str| move |key: &str| (key == "METRICS_AUTH").then(|| value.to_string());
+
+    assert_eq!(MetricsAuth::from_lookup(lookup("  ")), Ok(None));
+    assert_eq!(
+        MetricsAuth::from_lookup(lookup("bearer")),
+        Ok(Some(MetricsAuth::Bearer))
+    );
+    let err = MetricsAuth::from_lookup(lookup("basic:prom")).unwrap_err();
+    assert!(err.contains("METRICS_AUTH"), "{}", err);
+}
+
+#[actix_web::test]
+async fn test_metrics_open_without_config() {
+    let app = test::init_service(
+        App::new()
+            .wrap(from_fn(metrics_auth_guard))
+            .route("/metrics", web::get().to(metrics)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
+
+#[actix_web::test]
+async fn test_metrics_basic_auth() {
+    let auth = MetricsAuth::parse("basic:prom:secret").unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(auth))
+            .wrap(from_fn(metrics_auth_guard))
+            .route("/metrics", web::get().to(metrics))
+            .route("/health", web::get().to(metrics)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+    assert!(resp.headers().contains_key("www-authenticate"));
+
+    let req = test::TestRequest::get()
+        .uri("/metrics")
+        .insert_header(("Authorization", basic_header("prom:wrong")))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+
+    let req = test::TestRequest::get()
+        .uri("/metrics")
+        .insert_header(("Authorization", basic_header("prom:secret")))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    // Other paths are not affected
+    let req = test::TestRequest::get().uri("/health").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
+
+#[actix_web::test]
+async fn test_metrics_bearer_auth() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(MetricsAuth::Bearer))
+            .wrap(from_fn(metrics_auth_guard))
+            .route("/metrics", web::get().to(metrics)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+
+    let token = generate_access_token("admin-id", "admin").unwrap();
+    let req = test::TestRequest::get()
+        .uri("/metrics")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
@@ -4,6 +4,7 @@ mod model_tests {
     use cakung_barat_server::posting::models::{Post, PostWithAssets, CreatePostingRequest, UpdatePostingRequest};
     use cakung_barat_server::storage::FolderContent;
     use cakung_barat_server::ErrorResponse;
+    use utoipa::OpenApi;
     use uuid::Uuid;
     use chrono::{NaiveDate, Utc};
 
@@ -103,6 +104,10 @@ mod model_tests {
             created_at: Some(Utc::now()),
             updated_at: Some(Utc::now()),
             asset_ids: vec![Uuid::new_v4(), Uuid::new_v4()],
+            cover_asset_id: None,
+            cover_filename: None,
+            translations_available: Vec::new(),
+            comment_count: 0,
         };
 
         assert!(!post_with_assets.id.is_nil());
@@ -116,6 +121,7 @@ mod model_tests {
             title: "Test Title".to_string(),
             category: "Test Category".to_string(),
             excerpt: "Test Excerpt".to_string(),
+            cover_asset_id: None,
         };
 
         // Test serialization
@@ -139,6 +145,7 @@ mod model_tests {
             category: None, // This should not update
             excerpt: Some("Updated Excerpt".to_string()),
             folder_id: None, // This should not update
+            cover_asset_id: None,
         };
 
         assert_eq!(partial_request.title, Some("Updated Title".to_string()));
@@ -152,6 +159,7 @@ mod model_tests {
             category: None,
             excerpt: None,
             folder_id: None,
+            cover_asset_id: None,
         };
 
         assert!(empty_request.title.is_none());
@@ -160,6 +168,58 @@ mod model_tests {
         assert!(empty_request.folder_id.is_none());
     }
 
+    /// Object built from the property examples of the component `name` in
+    /// the OpenAPI document, with the `required` property names
+    fn schema_example(name: &str) -> (serde_json::Value, Vec<String>) {
+        let doc = serde_json::to_value(cakung_barat_server::ApiDoc::openapi()).unwrap();
+        let schema = &doc["components"]["schemas"][name];
+        let properties = schema["properties"].as_object().unwrap();
+        let example: serde_json::Map<String, serde_json::Value> = properties
+            .iter()
+            .map(|(key, property)| {
+                let value = property
+                    .get("example")
+                    .or_else(|| property["examples"].get(0))
+                    .cloned()
+                    .unwrap_or_else(|| panic!("{}.{} has no example", name, key));
+                (key.clone(), value)
+            })
+            .collect();
+        let required = schema["required"]
+            .as_array()
+            .map(|names| names.iter().map(|n| n.as_str().unwrap().to_string()).collect())
+            .unwrap_or_default();
+        (serde_json::Value::Object(example), required)
+    }
+
+    fn keys(value: &serde_json::Value) -> Vec<String> {
+        let mut keys: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn test_posting_request_schemas_match_models() {
+        let (example, mut required) = schema_example("CreatePostingRequest");
+        let request: CreatePostingRequest = serde_json::from_value(example.clone()).unwrap();
+        let serialized = serde_json::to_value(&request).unwrap();
+        assert_eq!(serialized, example);
+        required.sort();
+        // Only the cover may be left out
+        let mut expected = keys(&serialized);
+        expected.retain(|key| key != "cover_asset_id");
+        assert_eq!(required, expected);
+
+        let (example, required) = schema_example("UpdatePostingRequest");
+        let request: UpdatePostingRequest = serde_json::from_value(example.clone()).unwrap();
+        let serialized = serde_json::to_value(&request).unwrap();
+        assert_eq!(serialized, example);
+        // Every field of an update is optional
+        assert!(required.is_empty(), "{:?}", required);
+        let empty: UpdatePostingRequest = serde_json::from_str("{}").unwrap();
+        assert_eq!(keys(&serde_json::to_value(&empty).unwrap()), keys(&example));
+    }
+
     #[test]
     fn test_folder_content_creation() {
         let folder_content = FolderContent {
@@ -252,6 +312,8 @@ mod model_tests {
             filename: "test_file.jpg".to_string(),
             url: "/assets/serve/test_file.jpg".to_string(),
             description: Some("A test asset".to_string()),
+            width: None,
+            height: None,
             created_at: asset1.created_at,
             updated_at: asset1.updated_at,
         };
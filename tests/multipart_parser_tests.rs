@@ -13,6 +13,7 @@ mod multipart_parser_tests {
             category: "Test Category".to_string(),
             excerpt: "Test excerpt".to_string(),
             files_data,
+            cover_asset_id: None,
         };
 
         assert_eq!(parsed_data.title, "Test Title");
@@ -56,6 +57,7 @@ mod multipart_parser_tests {
             category: String::new(),
             excerpt: String::new(),
             files_data: Vec::new(),
+            cover_asset_id: None,
         };
 
         assert_eq!(parsed_data.title, "");
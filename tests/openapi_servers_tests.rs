@@ -0,0 +1,102 @@
+//! Tests for the `servers` list of the OpenAPI document
+
+use actix_web::{test, App};
+use cakung_barat_server::config::{AppConfig, ServerConfig};
+use cakung_barat_server::ApiDoc;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use utoipa::{Modify, OpenApi};
+
+fn server_config(vars: &[(&str, &str)]) -> Result<ServerConfig, String> {
+    let vars: HashMap<String, String> = vars
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    ServerConfig::from_lookup(|key| vars.get(key).cloned())
+}
+
+/// `servers` of openapi.json built with the environment `vars`
+fn servers(vars: &[(&str, &str)]) -> Value {
+    let mut doc = ApiDoc::openapi();
+    server_config(vars)
+        .unwrap()
+        .openapi_servers
+        .modify(&mut doc);
+    serde_json::to_value(doc).unwrap()["servers"].clone()
+}
+
+#[actix_web::test]
+async fn test_served_document_defaults_to_localhost() {
+    let app = test::init_service(App::new().configure(cakung_barat_server::routes)).await;
+    let req = test::TestRequest::get()
+        .uri("/api-doc/openapi.json")
+        .to_request();
+    let doc: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(
+        doc["servers"],
+        json!([{ "url": "http://localhost:8080", "description": "Local server" }])
+    );
+}
+
+#[actix_web::test]
+async fn test_no_servers_are_hardcoded() {
+    let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    assert!(doc.get("servers").is_none(), "{}", doc["servers"]);
+}
+
+#[actix_web::test]
+async fn test_default_follows_port_and_public_base_url() {
+    assert_eq!(
+        servers(&[("PORT", "9000")]),
+        json!([{ "url": "http://localhost:9000", "description": "Local server" }])
+    );
+    assert_eq!(
+        servers(&[("PUBLIC_BASE_URL", "https://cakungbarat.example.id/")]),
+        json!([{ "url": "https://cakungbarat.example.id", "description": "Public server" }])
+    );
+    // A blank value lists no server, so the default applies
+    assert_eq!(
+        servers(&[("OPENAPI_SERVERS", " , ")]),
+        json!([{ "url": "http://localhost:8080", "description": "Local server" }])
+    );
+}
+
+#[actix_web::test]
+async fn test_servers_from_env() {
+    assert_eq!(
+        servers(&[
+            (
+                "OPENAPI_SERVERS",
+                "https://api.example.id/=Produksi, https://staging.example.id=Staging,/"
+            ),
+            ("PUBLIC_BASE_URL", "https://cakungbarat.example.id"),
+        ]),
+        json!([
+            { "url": "https://api.example.id", "description": "Produksi" },
+            { "url": "https://staging.example.id", "description": "Staging" },
+            { "url": "/" }
+        ])
+    );
+    assert_eq!(
+        servers(&[(
+            "OPENAPI_SERVERS",
+            "http://10.0.0.5:8080=Kantor kelurahan (LAN)"
+        )]),
+        json!([{ "url": "http://10.0.0.5:8080", "description": "Kantor kelurahan (LAN)" }])
+    );
+}
+
+#[actix_web::test]
+async fn test_invalid_server_is_reported() {
+    let err = server_config(&[("OPENAPI_SERVERS", "api.example.id=Produksi")]).unwrap_err();
+    assert!(err.contains("api.example.id"), "{}", err);
+
+    let err = AppConfig::from_lookup(|key| match key {
+        "SUPABASE_DATABASE_URL" => Some("postgres://localhost/db".to_string()),
+        "STORAGE_BACKEND" => Some("local".to_string()),
+        "OPENAPI_SERVERS" => Some("ftp://example.id".to_string()),
+        _ => None,
+    })
+    .unwrap_err();
+    assert!(err.to_string().contains("OPENAPI_SERVERS"), "{}", err);
+}
@@ -0,0 +1,218 @@
+//! Tests that the OpenAPI document matches the route table the server runs
+
+use actix_web::http::{Method, StatusCode};
+use actix_web::{test, web, App};
+use cakung_barat_server::error_handlers;
+use serde_json::Value;
+use std::collections::HashMap;
+
+const EXPECTED_PATHS: &[&str] = &[
+    "/api/postings",
+    "/api/postings/{id}",
+    "/api/postings/{id}/rendered",
+    "/api/search",
+    "/api/assets",
+    "/api/assets/{id}",
+    "/api/gallery",
+    "/api/assets/uploads",
+    "/api/assets/uploads/{id}",
+    "/api/assets/uploads/{id}/progress",
+    "/api/assets/uploads/{id}/finalize",
+    "/api/organization",
+    "/api/organization/{id}",
+    "/api/organization/members/{id}/avatar",
+    "/api/admin/cache/stats",
+    "/api/admin/cache/entries",
+    "/api/admin/cache/entries/{key}",
+    "/api/admin/read-only",
+    "/api/admin/stats",
+    "/api/events",
+    "/api/admin/webhooks",
+    "/api/admin/webhooks/{id}",
+    "/api/admin/webhooks/dead-letters",
+    "/api/admin/backup",
+    "/api/admin/restore",
+    "/api/admin/templates",
+    "/api/admin/mcp/tools/refresh",
+    "/api/admin/mcp/tools/browse",
+    "/api/admin/documents",
+    "/api/admin/folders/others/dissolve",
+    "/api/admin/assets/relativize-urls",
+    "/api/admin/assets/backfill-dimensions",
+    "/api/admin/assets/rebuild-folders",
+    "/api/admin/maintenance/post-folders",
+    "/readyz",
+    "/api/auth/status",
+    "/api/auth/login",
+    "/api/auth/refresh",
+    "/api/auth/sessions",
+    "/api/auth/admins",
+    "/api/auth/admins/{id}",
+    "/mcp",
+    "/sse",
+];
+
+async fn openapi() -> Value {
+    let app = test::init_service(App::new().configure(cakung_barat_server::routes)).await;
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/api-doc/openapi.json")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    test::read_body_json(resp).await
+}
+
+/// `path` with each `{param}` replaced by a plausible value
+fn concrete(path: &str) -> String {
+    path.split('/')
+        .map(|segment| match segment {
+            "{id}" | "{post_id}" => "00000000-0000-0000-0000-000000000000",
+            s if s.starts_with('{') => "contoh",
+            s => s,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[actix_web::test]
+async fn test_openapi_lists_expected_paths() {
+    let doc = openapi().await;
+    let paths = doc["paths"].as_object().unwrap();
+    for path in EXPECTED_PATHS {
+        assert!(
+            paths.contains_key(*path),
+            "{} missing from openapi.json",
+            path
+        );
+    }
+}
+
+#[actix_web::test]
+async fn test_every_documented_operation_is_routed() {
+    let doc = openapi().await;
+    let app = test::init_service(
+        App::new()
+            .configure(cakung_barat_server::routes)
+            .default_service(web::route().to(error_handlers::not_found)),
+    )
+    .await;
+
+    for (path, operations) in doc["paths"].as_object().unwrap() {
+        for method in operations.as_object().unwrap().keys() {
+            let method = Method::from_bytes(method.to_uppercase().as_bytes()).unwrap();
+            let resp = test::call_service(
+                &app,
+                test::TestRequest::default()
+                    .method(method.clone())
+                    .uri(&concrete(path))
+                    .to_request(),
+            )
+            .await;
+            // Handlers fail without app data, but never with "no such route"
+            assert!(
+                resp.status() != StatusCode::NOT_FOUND
+                    && resp.status() != StatusCode::METHOD_NOT_ALLOWED,
+                "{} {} is documented but not routed ({})",
+                method,
+                path,
+                resp.status()
+            );
+        }
+    }
+}
+
+#[actix_web::test]
+async fn test_operation_ids_are_set_and_unique() {
+    let doc = openapi().await;
+    let mut owners: HashMap<&str, String> = HashMap::new();
+    for (path, operations) in doc["paths"].as_object().unwrap() {
+        for (method, operation) in operations.as_object().unwrap() {
+            let operation_id = operation["operationId"]
+                .as_str()
+                .unwrap_or_else(|| panic!("{} {} has no operationId", method, path));
+            let owner = format!("{} {}", method, path);
+            if let Some(previous) = owners.insert(operation_id, owner.clone()) {
+                panic!(
+                    "{} and {} share operationId {}",
+                    previous, owner, operation_id
+                );
+            }
+        }
+    }
+    assert_eq!(
+        doc["paths"]["/api/auth/login"]["post"]["operationId"],
+        "login"
+    );
+    assert_eq!(
+        doc["paths"]["/mcp"]["post"]["operationId"],
+        "postMcpMessage"
+    );
+}
+
+#[actix_web::test]
+async fn test_security_schemes_are_declared() {
+    let doc = openapi().await;
+    let schemes = &doc["components"]["securitySchemes"];
+    assert_eq!(schemes["bearer_auth"]["scheme"], "bearer");
+    assert_eq!(schemes["api_key"]["name"], "X-API-Key");
+}
+
+#[actix_web::test]
+async fn test_major_schemas_have_examples() {
+    let doc = openapi().await;
+    let schemas = &doc["components"]["schemas"];
+    for name in [
+        "CreatePostingRequest",
+        "Asset",
+        "ErrorResponse",
+        "TokenResponse",
+        "AllAssetsResponse",
+    ] {
+        assert!(
+            schemas[name]["example"].is_object(),
+            "{} has no example",
+            name
+        );
+    }
+    assert_eq!(
+        schemas["ErrorResponse"]["properties"]["error"]["$ref"],
+        "#/components/schemas/ErrorCode"
+    );
+    assert!(schemas["ErrorCode"]["enum"]
+        .as_array()
+        .unwrap()
+        .contains(&Value::from("Unauthorized")));
+}
+
+#[actix_web::test]
+async fn test_every_path_documents_an_error_response() {
+    let doc = openapi().await;
+    for (path, operations) in doc["paths"].as_object().unwrap() {
+        let documents_error = operations.as_object().unwrap().values().any(|operation| {
+            operation["responses"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .any(|status| status.starts_with('4') || status.starts_with('5'))
+        });
+        assert!(documents_error, "{} documents no error response", path);
+    }
+}
+
+#[actix_web::test]
+async fn test_protected_operations_share_the_unauthorized_response() {
+    let doc = openapi().await;
+    let unauthorized = "#/components/responses/UnauthorizedResponse";
+    assert!(doc["components"]["responses"]["UnauthorizedResponse"].is_object());
+    assert_eq!(
+        doc["paths"]["/api/admin/stats"]["get"]["responses"]["401"]["$ref"],
+        unauthorized
+    );
+    assert_eq!(
+        doc["paths"]["/api/admin/cache/entries"]["get"]["responses"]["401"]["$ref"],
+        unauthorized
+    );
+}
@@ -0,0 +1,172 @@
+//! Tests for member avatars and the initials fallback
+
+mod common;
+
+use actix_web::http::{header, StatusCode};
+use actix_web::{test, web, App};
+use cakung_barat_server::cache::Cached;
+use cakung_barat_server::organization::avatar::{avatar_color, avatar_key, initials};
+use cakung_barat_server::organization::model::OrganizationMember;
+use cakung_barat_server::organization::routes;
+use cakung_barat_server::storage::LocalStorage;
+use cakung_barat_server::AppState;
+use serde_json::Value;
+use std::sync::Arc;
+
+fn member(id: i32, name: &str, photo: Option<&str>) -> OrganizationMember {
+    OrganizationMember {
+        id,
+        name: Some(name.to_string()),
+        position: "Staf".to_string(),
+        photo: photo.map(str::to_string),
+        parent_id: None,
+        level: 1,
+        role: "staf".to_string(),
+    }
+}
+
+async fn test_state(dir: &tempfile::TempDir) -> AppState {
+    // Members are served from the cache filled below, never the database
+    let state = common::state_builder(Arc::new(LocalStorage::new(dir.path().to_path_buf())))
+        .build()
+        .unwrap();
+    state
+        .organization_cache
+        .insert(
+            "org_members".to_string(),
+            Cached::new(
+                vec![
+                    member(1, "Budi Santoso", None),
+                    member(2, "Siti Aminah", Some("https://example.com/siti.jpg")),
+                    member(3, "  ", Some("")),
+                ]
+                .into(),
+            ),
+        )
+        .await;
+    state
+}
+
+#[actix_web::test]
+async fn test_initials_take_first_letters_of_two_words() {
+    assert_eq!(initials("Budi Santoso"), "BS");
+    assert_eq!(initials("ahmad"), "A");
+    assert_eq!(initials("Dr. Rina Kusuma Wardani"), "DR");
+    assert_eq!(initials("  "), "?");
+}
+
+#[actix_web::test]
+async fn test_avatar_color_is_deterministic_per_id() {
+    assert_eq!(avatar_color(7), avatar_color(7));
+    assert!(avatar_color(7).starts_with("hsl("));
+    assert_ne!(avatar_key(1, "BS"), avatar_key(1, "BT"));
+}
+
+#[actix_web::test]
+async fn test_member_without_photo_gets_generated_avatar_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(test_state(&dir).await))
+            .configure(routes::config),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/organization/members/1/avatar")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.headers().get(header::CONTENT_TYPE).unwrap(),
+        "image/svg+xml"
+    );
+    let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    assert!(body.starts_with("<svg"));
+    assert!(body.contains(">BS</text>"));
+    assert!(body.contains(&avatar_color(1)));
+
+    // The avatar was stored, and the stored copy is what gets served next
+    let stored = dir.path().join(avatar_key(1, "BS"));
+    assert_eq!(std::fs::read_to_string(&stored).unwrap(), body);
+    std::fs::write(&stored, "<svg>stored</svg>").unwrap();
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/organization/members/1/avatar")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(test::read_body(resp).await, "<svg>stored</svg>");
+
+    // A blank name and photo still give an image
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/organization/members/3/avatar")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(dir.path().join(avatar_key(3, "?")).exists());
+}
+
+#[actix_web::test]
+async fn test_member_photo_and_unknown_member() {
+    let dir = tempfile::tempdir().unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(test_state(&dir).await))
+            .configure(routes::config),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/organization/members/2/avatar")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::TEMPORARY_REDIRECT);
+    assert_eq!(
+        resp.headers().get(header::LOCATION).unwrap(),
+        "https://example.com/siti.jpg"
+    );
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/organization/members/99/avatar")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn test_member_list_links_avatars() {
+    let dir = tempfile::tempdir().unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(test_state(&dir).await))
+            .configure(routes::config),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get().uri("/organization").to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body[0]["id"], 1);
+    assert_eq!(body[0]["name"], "Budi Santoso");
+    assert_eq!(body[0]["avatar_url"], "/api/organization/members/1/avatar");
+    assert_eq!(body[1]["avatar_url"], "/api/organization/members/2/avatar");
+}
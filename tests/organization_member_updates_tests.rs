@@ -0,0 +1,242 @@
+//! Tests for creating, updating and deleting organization members one at a
+//! time while other requests change the same list
+
+mod common;
+
+use cakung_barat_server::organization::model::{
+    CreateMemberRequest, OrganizationMember, UpdateMemberRequest,
+};
+use cakung_barat_server::storage::{LocalStorage, ObjectStorage};
+use cakung_barat_server::AppState;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+fn app_state(root: &std::path::Path) -> AppState {
+    common::state_builder(Arc::new(LocalStorage::new(root)))
+        .with_persistence(true)
+        .build()
+        .unwrap()
+}
+
+fn create_request(name: &str) -> CreateMemberRequest {
+    CreateMemberRequest {
+        name: name.to_string(),
+        position: "Ketua RT".to_string(),
+        photo: String::new(),
+        parent_id: None,
+        level: 4,
+        role: "RT".to_string(),
+    }
+}
+
+fn no_changes() -> UpdateMemberRequest {
+    UpdateMemberRequest {
+        name: None,
+        position: None,
+        photo: None,
+        parent_id: None,
+        level: None,
+        role: None,
+    }
+}
+
+/// Members in organization.json after the persistence worker has written
+/// every queued update
+async fn persisted(state: &AppState) -> Vec<OrganizationMember> {
+    state.organization_persist_worker.flush().await;
+    let bytes = state
+        .storage
+        .download_file("organization.json")
+        .await
+        .unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_parallel_creates_get_unique_ids() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = app_state(dir.path());
+
+    let tasks: Vec<_> = (0..50)
+        .map(|i| {
+            let state = state.clone();
+            tokio::spawn(async move {
+                state
+                    .create_organization_member(&create_request(&format!("Ketua RT {:02}", i)))
+                    .await
+                    .unwrap()
+            })
+        })
+        .collect();
+    let mut ids = Vec::new();
+    for task in tasks {
+        ids.push(task.await.unwrap().id);
+    }
+
+    let unique: HashSet<i32> = ids.iter().copied().collect();
+    assert_eq!(unique.len(), 50, "duplicate ids: {:?}", ids);
+    assert_eq!(unique, (1..=50).collect::<HashSet<i32>>());
+
+    let members = state.get_organization_structure().await.unwrap();
+    assert_eq!(members.len(), 50);
+    let names: HashSet<String> = members.into_iter().filter_map(|m| m.name).collect();
+    assert_eq!(names.len(), 50);
+
+    // The coalesced writes end with the complete list
+    let stored = persisted(&state).await;
+    assert_eq!(stored.len(), 50);
+    assert_eq!(
+        stored.iter().map(|m| m.id).collect::<Vec<_>>(),
+        (1..=50).collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_parallel_updates_and_deletes_keep_each_change() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = app_state(dir.path());
+    for i in 0..20 {
+        state
+            .create_organization_member(&create_request(&format!("Warga {}", i)))
+            .await
+            .unwrap();
+    }
+
+    let tasks: Vec<_> = (1..=20)
+        .map(|id| {
+            let state = state.clone();
+            tokio::spawn(async move {
+                if id % 2 == 0 {
+                    assert!(state.delete_organization_member(id).await.unwrap());
+                } else {
+                    let request = UpdateMemberRequest {
+                        position: Some(format!("Ketua RT {:02}", id)),
+                        ..no_changes()
+                    };
+                    let member = state
+                        .update_organization_member(id, &request)
+                        .await
+                        .unwrap()
+                        .unwrap();
+                    assert_eq!(member.name, Some(format!("Warga {}", id - 1)));
+                }
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    let members = state.get_organization_structure().await.unwrap();
+    assert_eq!(members.len(), 10);
+    for member in &members {
+        assert_eq!(member.id % 2, 1);
+        assert_eq!(member.position, format!("Ketua RT {:02}", member.id));
+    }
+    assert_eq!(persisted(&state).await.len(), 10);
+}
+
+#[tokio::test]
+async fn test_ids_continue_from_stored_members_and_are_not_reused() {
+    let dir = tempfile::tempdir().unwrap();
+    let stored = vec![
+        OrganizationMember {
+            id: 7,
+            name: Some("Budi Santoso".to_string()),
+            position: "Lurah".to_string(),
+            photo: None,
+            parent_id: None,
+            level: 1,
+            role: "Pimpinan".to_string(),
+        },
+        OrganizationMember {
+            id: 3,
+            name: Some("Dewi Lestari".to_string()),
+            position: "Sekretaris Kelurahan".to_string(),
+            photo: None,
+            parent_id: Some(7),
+            level: 2,
+            role: "Staf".to_string(),
+        },
+    ];
+    LocalStorage::new(dir.path())
+        .upload_file("organization.json", &serde_json::to_vec(&stored).unwrap())
+        .await
+        .unwrap();
+    let state = app_state(dir.path());
+
+    let created = state
+        .create_organization_member(&create_request("Ahmad Fauzi"))
+        .await
+        .unwrap();
+    assert_eq!(created.id, 8);
+
+    // Deleting the newest member does not hand its id out again
+    assert!(state.delete_organization_member(8).await.unwrap());
+    let created = state
+        .create_organization_member(&create_request("Siti Aminah"))
+        .await
+        .unwrap();
+    assert_eq!(created.id, 9);
+
+    let ids: Vec<i32> = state
+        .get_organization_structure()
+        .await
+        .unwrap()
+        .iter()
+        .map(|m| m.id)
+        .collect();
+    assert_eq!(ids, vec![3, 7, 9]);
+}
+
+#[tokio::test]
+async fn test_missing_member_is_not_written() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = app_state(dir.path());
+    state
+        .create_organization_member(&create_request("Rudi Hartono"))
+        .await
+        .unwrap();
+
+    assert!(state
+        .update_organization_member(42, &no_changes())
+        .await
+        .unwrap()
+        .is_none());
+    assert!(!state.delete_organization_member(42).await.unwrap());
+    assert_eq!(state.get_organization_structure().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_restore_writes_file_before_returning() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = app_state(dir.path());
+    state
+        .create_organization_member(&create_request("Rudi Hartono"))
+        .await
+        .unwrap();
+
+    let restored = vec![OrganizationMember {
+        id: 5,
+        name: Some("Budi Santoso".to_string()),
+        position: "Lurah".to_string(),
+        photo: None,
+        parent_id: None,
+        level: 1,
+        role: "Pimpinan".to_string(),
+    }];
+    state.restore_organization_members(restored).await.unwrap();
+
+    // Read before the worker is flushed
+    let bytes = LocalStorage::new(dir.path())
+        .download_file("organization.json")
+        .await
+        .unwrap();
+    let stored: Vec<OrganizationMember> = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].id, 5);
+
+    // The earlier queued update does not overwrite the restored members
+    assert_eq!(persisted(&state).await.len(), 1);
+    assert_eq!(state.get_organization_structure().await.unwrap()[0].id, 5);
+}
@@ -0,0 +1,69 @@
+//! Tests for the assets embedded in the post list
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::asset::models::AssetSummary;
+use cakung_barat_server::posting::embed::parse_include;
+use cakung_barat_server::posting::handlers::get_all_postings;
+use cakung_barat_server::storage::LocalStorage;
+use std::sync::Arc;
+
+#[actix_web::test]
+async fn test_parse_include() {
+    assert_eq!(parse_include("assets:3").unwrap(), Some(3));
+    assert_eq!(parse_include("cover, assets:10").unwrap(), Some(10));
+    assert_eq!(parse_include("cover").unwrap(), None);
+    assert_eq!(parse_include("").unwrap(), None);
+    for include in [
+        "assets",
+        "assets:0",
+        "assets:11",
+        "assets:-1",
+        "assets:tiga",
+        "tags",
+        "Cover",
+    ] {
+        assert!(parse_include(include).is_err(), "{:?}", include);
+    }
+}
+
+#[actix_web::test]
+async fn test_asset_summary() {
+    let id = uuid::Uuid::new_v4();
+    let image = AssetSummary::new(id, "kerja-bakti.jpg");
+    assert_eq!(image.id, id);
+    assert_eq!(image.mime, "image/jpeg");
+    assert!(
+        image.url.ends_with("/assets/serve/kerja-bakti.jpg"),
+        "{}",
+        image.url
+    );
+    assert_eq!(image.thumbnail_url.as_deref(), Some(image.url.as_str()));
+
+    let document = AssetSummary::new(id, "undangan.pdf");
+    assert_eq!(document.mime, "application/pdf");
+    assert_eq!(document.thumbnail_url, None);
+}
+
+#[actix_web::test]
+async fn test_unknown_include_is_rejected() {
+    // Validation happens before the database is reached
+    let state = common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .build()
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .route("/api/postings", web::get().to(get_all_postings)),
+    )
+    .await;
+
+    for include in ["assets:50", "comments"] {
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/postings?include={}", include))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 400, "{}", include);
+    }
+}
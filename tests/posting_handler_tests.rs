@@ -12,6 +12,7 @@ mod posting_handler_tests {
             title: "Test Title".to_string(),
             category: "Test Category".to_string(),
             excerpt: "Test excerpt".to_string(),
+            cover_asset_id: None,
         };
 
         assert_eq!(create_request.title, "Test Title");
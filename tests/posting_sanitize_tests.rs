@@ -0,0 +1,114 @@
+//! Tests for sanitizing post excerpts and rendering them from Markdown
+
+use cakung_barat_server::config::AppConfig;
+use cakung_barat_server::posting::sanitize::{SanitizeConfig, DEFAULT_ALLOWED_TAGS};
+use std::collections::HashMap;
+
+fn config(vars: &[(&str, &str)]) -> Result<SanitizeConfig, String> {
+    let vars: HashMap<String, String> = vars
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    SanitizeConfig::from_lookup(|key| vars.get(key).cloned())
+}
+
+#[test]
+fn test_script_injection_is_stripped() {
+    let sanitize = SanitizeConfig::default();
+    let cleaned = sanitize.clean(
+        r#"<p onclick="steal()">Kerja bakti <strong>Minggu</strong></p><script>alert(document.cookie)</script><img src="x" onerror="alert(1)"><a href="javascript:alert(1)">klik</a>"#,
+    );
+
+    assert!(!cleaned.contains("script"), "{}", cleaned);
+    assert!(!cleaned.contains("alert"), "{}", cleaned);
+    assert!(!cleaned.contains("onclick"), "{}", cleaned);
+    assert!(!cleaned.contains("onerror"), "{}", cleaned);
+    assert!(!cleaned.contains("javascript:"), "{}", cleaned);
+    assert!(cleaned.starts_with("<p>Kerja bakti <strong>Minggu</strong></p>"));
+    assert!(cleaned.contains(r#"<img src="x">"#), "{}", cleaned);
+}
+
+#[test]
+fn test_word_markup_is_reduced_to_allowed_tags() {
+    let sanitize = SanitizeConfig::default();
+    let cleaned = sanitize.clean(
+        r#"<div class="MsoNormal"><span style="font-family:Calibri"><o:p>Posyandu</o:p> <em>balita</em></span></div>"#,
+    );
+    assert_eq!(cleaned, "Posyandu <em>balita</em>");
+}
+
+#[test]
+fn test_plain_text_is_escaped_when_rendered() {
+    let sanitize = SanitizeConfig::default();
+    assert_eq!(
+        sanitize.render_markdown("Kerja bakti & gotong royong, a < b"),
+        "<p>Kerja bakti &amp; gotong royong, a &lt; b</p>\n"
+    );
+    assert_eq!(
+        sanitize.render_markdown("> Kutipan dari Pak Lurah"),
+        "<blockquote>\n<p>Kutipan dari Pak Lurah</p>\n</blockquote>\n"
+    );
+}
+
+#[test]
+fn test_markdown_table_renders() {
+    let sanitize = SanitizeConfig::default();
+    let html = sanitize.render_markdown(
+        "Jadwal **Minggu**:\n\n| Jam | Kegiatan |\n|-----|----------|\n| 07.00 | Senam |\n| 08.00 | Kerja bakti |\n",
+    );
+
+    assert!(
+        html.contains("<p>Jadwal <strong>Minggu</strong>:</p>"),
+        "{}",
+        html
+    );
+    assert!(html.contains("<table>"), "{}", html);
+    assert!(html.contains("<th>Jam</th>"), "{}", html);
+    assert!(html.contains("<th>Kegiatan</th>"), "{}", html);
+    assert!(html.contains("<td>07.00</td>"), "{}", html);
+    assert!(html.contains("<td>Kerja bakti</td>"), "{}", html);
+    assert_eq!(html.matches("<tr>").count(), 3);
+}
+
+#[test]
+fn test_raw_html_in_markdown_is_sanitized() {
+    let sanitize = SanitizeConfig::default();
+    let html = sanitize.render_markdown(
+        "Pengumuman\n\n<script>alert(1)</script>\n\n[tautan](javascript:alert(2)) ~~batal~~",
+    );
+    assert!(!html.contains("alert"), "{}", html);
+    assert!(html.contains("<p>Pengumuman</p>"), "{}", html);
+    assert!(html.contains("<del>batal</del>"), "{}", html);
+}
+
+#[test]
+fn test_allowlist_from_env() {
+    assert_eq!(
+        config(&[]).unwrap().allowed_tags.len(),
+        DEFAULT_ALLOWED_TAGS.len()
+    );
+
+    let narrow = config(&[("POSTING_ALLOWED_TAGS", "p, STRONG,p")]).unwrap();
+    assert_eq!(narrow.allowed_tags, vec!["p", "strong"]);
+    assert_eq!(
+        narrow.clean("<p><strong>RW 05</strong> <em>Cakung</em></p>"),
+        "<p><strong>RW 05</strong> Cakung</p>"
+    );
+
+    assert!(config(&[("POSTING_ALLOWED_TAGS", "p,script")])
+        .unwrap_err()
+        .contains("script"));
+    assert!(config(&[("POSTING_ALLOWED_TAGS", "p,<b>")]).is_err());
+}
+
+#[test]
+fn test_app_config_reports_invalid_allowlist() {
+    let err = AppConfig::from_lookup(|key| match key {
+        "SUPABASE_DATABASE_URL" => Some("postgres://localhost/db".to_string()),
+        "STORAGE_BACKEND" => Some("local".to_string()),
+        "POSTING_ALLOWED_TAGS" => Some("style".to_string()),
+        _ => None,
+    })
+    .unwrap_err();
+    assert!(err.to_string().contains("POSTING_ALLOWED_TAGS"), "{}", err);
+}
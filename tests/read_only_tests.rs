@@ -0,0 +1,174 @@
+//! Tests for read-only maintenance mode
+
+mod common;
+
+use actix_web::middleware::from_fn;
+use actix_web::{http::header, test, web, App};
+use cakung_barat_server::auth::generate_access_token;
+use cakung_barat_server::read_only::{is_blocked_write, read_only_guard};
+use cakung_barat_server::{auth, cache, posting, read_only, AppState};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+fn read_only_state() -> web::Data<AppState> {
+    web::Data::new(
+        common::state_builder(Arc::new(common::NoopStorage))
+            .with_read_only(true)
+            .build()
+            .unwrap(),
+    )
+}
+
+fn bearer() -> (header::HeaderName, String) {
+    let token = generate_access_token("admin-id", "admin").unwrap();
+    (header::AUTHORIZATION, format!("Bearer {}", token))
+}
+
+#[actix_web::test]
+async fn test_write_classification() {
+    use actix_web::http::Method;
+
+    assert!(is_blocked_write(&Method::POST, "/api/postings"));
+    assert!(is_blocked_write(&Method::PUT, "/api/organization/1"));
+    assert!(is_blocked_write(&Method::DELETE, "/api/assets/1"));
+    assert!(!is_blocked_write(&Method::GET, "/api/postings"));
+    assert!(!is_blocked_write(&Method::POST, "/api/auth/login"));
+    assert!(!is_blocked_write(&Method::POST, "/api/auth/refresh"));
+    assert!(!is_blocked_write(&Method::POST, "/api/admin/read-only"));
+    assert!(!is_blocked_write(&Method::POST, "/mcp/message"));
+}
+
+#[actix_web::test]
+async fn test_writes_rejected_until_read_only_disabled() {
+    let state = read_only_state();
+    let app = test::init_service(
+        App::new()
+            .wrap(from_fn(read_only_guard))
+            .app_data(state.clone())
+            .service(
+                web::scope("/api")
+                    .configure(read_only::config)
+                    .configure(cache::handlers::config)
+                    .route(
+                        "/postings",
+                        web::post().to(posting::handlers::create_posting),
+                    ),
+            ),
+    )
+    .await;
+
+    let create = || {
+        test::TestRequest::post()
+            .uri("/api/postings")
+            .set_json(json!({"title": "t", "category": "c", "excerpt": "e"}))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, create()).await;
+    assert_eq!(resp.status(), 503);
+    assert_eq!(resp.headers().get(header::RETRY_AFTER).unwrap(), "120");
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "ServiceUnavailable");
+
+    // Reads still work
+    let req = test::TestRequest::get()
+        .uri("/api/admin/cache/stats")
+        .insert_header(bearer())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+
+    // The toggle itself is exempt but still requires a token
+    let req = test::TestRequest::post()
+        .uri("/api/admin/read-only")
+        .set_json(json!({"enabled": false}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 401);
+    assert!(state.is_read_only());
+
+    let req = test::TestRequest::post()
+        .uri("/api/admin/read-only")
+        .insert_header(bearer())
+        .set_json(json!({"enabled": false}))
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["read_only"], false);
+    assert!(!state.is_read_only());
+
+    // The write now reaches the handler, which fails on the unreachable database
+    let resp = test::call_service(&app, create()).await;
+    assert_eq!(resp.status(), 500);
+}
+
+#[actix_web::test]
+async fn test_login_allowed_in_read_only_mode() {
+    let state = read_only_state();
+    let app = test::init_service(
+        App::new()
+            .wrap(from_fn(read_only_guard))
+            .app_data(state.clone())
+            .service(web::scope("/api").configure(auth::handlers::config)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(json!({"username": "admin", "password": "secret"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_ne!(resp.status(), 503);
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/admins")
+        .insert_header(bearer())
+        .set_json(json!({"username": "new", "password": "secret123"}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 503);
+}
+
+#[actix_web::test]
+async fn test_mcp_documents_are_not_stored_in_read_only_mode() {
+    let state = read_only_state();
+    let registry = cakung_barat_server::mcp::tools::ToolRegistry::new().unwrap();
+    let arguments = |delivery: &str| {
+        json!({
+            "pemohon": {
+                "nama": "Budi Santoso",
+                "nik": "3175010101900001",
+                "ttl": "Jakarta, 1 Januari 1990",
+                "jk": true,
+                "agama": "Islam",
+                "pekerjaan": "Karyawan Swasta",
+                "alamat": "Jl. Cakung Barat No. 1",
+                "telp": "081234567890"
+            },
+            "skck": {
+                "keperluan": "Melamar pekerjaan",
+                "tujuan_instansi": "PT Sinar Abadi",
+                "masa_berlaku": "1 bulan"
+            },
+            "meta": { "kelurahan": "Cakung Barat" },
+            "delivery": delivery
+        })
+    };
+
+    let result = registry
+        .call_tool_async(
+            "generate_surat_pengantar_skck",
+            Some(arguments("url")),
+            &state,
+        )
+        .await;
+    assert!(result.is_error);
+    let text = result.content[0].text.as_deref().unwrap();
+    assert!(text.contains("mode baca saja"), "{}", text);
+
+    // Rendering without storing still works
+    let result = registry
+        .call_tool_async(
+            "generate_surat_pengantar_skck",
+            Some(arguments("inline")),
+            &state,
+        )
+        .await;
+    assert!(!result.is_error, "{:?}", result.content[0].text);
+}
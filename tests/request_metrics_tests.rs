@@ -0,0 +1,66 @@
+//! Tests for the per-route HTTP request metrics on /metrics
+
+use actix_web::middleware::from_fn;
+use actix_web::{test, web, App, HttpResponse};
+use cakung_barat_server::request_metrics::{self, track_requests, RequestMetrics};
+use prometheus::Registry;
+use uuid::Uuid;
+
+async fn get_posting(metrics: web::Data<RequestMetrics>) -> HttpResponse {
+    // The request being handled is counted
+    HttpResponse::Ok().body(metrics.in_flight().to_string())
+}
+
+#[actix_web::test]
+async fn test_requests_are_labelled_by_route_template() {
+    let metrics = web::Data::new(RequestMetrics::new(Registry::new()).unwrap());
+    let app = test::init_service(
+        App::new()
+            .wrap(from_fn(track_requests))
+            .app_data(metrics.clone())
+            .configure(request_metrics::config)
+            .service(web::scope("/api").route("/postings/{id}", web::get().to(get_posting))),
+    )
+    .await;
+
+    let first = Uuid::new_v4();
+    for id in [first, Uuid::new_v4(), Uuid::new_v4()] {
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/postings/{}", id))
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, "1");
+    }
+    let req = test::TestRequest::get().uri("/wp-login.php").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+    let body = String::from_utf8(test::call_and_read_body(&app, req).await.to_vec()).unwrap();
+
+    assert!(body.contains(
+        r#"cakung_barat_server_http_requests_total{endpoint="/api/postings/{id}",method="GET",status="200"} 3"#
+    ));
+    assert!(body.contains(
+        r#"cakung_barat_server_http_requests_duration_seconds_count{endpoint="/api/postings/{id}",method="GET",status="200"} 3"#
+    ));
+    assert!(body.contains(
+        r#"cakung_barat_server_http_requests_total{endpoint="unmatched",method="GET",status="404"} 1"#
+    ));
+    assert!(!body.contains(&first.to_string()));
+    assert!(!body.contains("wp-login"));
+    assert!(body.contains("cakung_barat_server_http_requests_in_flight 1"));
+    assert_eq!(metrics.in_flight(), 0);
+}
+
+#[actix_web::test]
+async fn test_metrics_share_the_given_registry() {
+    let registry = Registry::new();
+    let metrics = RequestMetrics::new(registry.clone()).unwrap();
+    let other = prometheus::IntGauge::new("storage_used_bytes", "Bytes stored").unwrap();
+    registry.register(Box::new(other.clone())).unwrap();
+    other.set(42);
+
+    assert!(metrics.render().unwrap().contains("storage_used_bytes 42"));
+    // Registering twice in one registry fails instead of splitting series
+    assert!(RequestMetrics::new(registry).is_err());
+}
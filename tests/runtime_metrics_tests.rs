@@ -0,0 +1,193 @@
+//! Tests for the cache size, worker queue, storage failure and MCP tool
+//! call series on /metrics
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::cache::{CacheKind, Cached};
+use cakung_barat_server::mcp::tools::registry::ToolContext;
+use cakung_barat_server::mcp::tools::ToolRegistry;
+use cakung_barat_server::posting::models::Post;
+use cakung_barat_server::request_metrics::{self, RequestMetrics};
+use cakung_barat_server::runtime_metrics::WorkerQueue;
+use cakung_barat_server::storage::LocalStorage;
+use cakung_barat_server::AppState;
+use prometheus::Registry;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+fn app_state(root: &std::path::Path) -> AppState {
+    common::state_builder(Arc::new(LocalStorage::new(root)))
+        .build()
+        .unwrap()
+}
+
+/// The `/metrics` endpoint data, with the metrics of `state` and `tools`
+/// registered once
+fn metrics(state: &AppState, tools: &ToolRegistry) -> web::Data<RequestMetrics> {
+    let registry = Registry::new();
+    state.cache_metrics.register(&registry).unwrap();
+    state.http_metrics.register(&registry).unwrap();
+    state.runtime_metrics.register(&registry).unwrap();
+    tools.call_metrics().register(&registry).unwrap();
+    web::Data::new(RequestMetrics::new(registry).unwrap())
+}
+
+async fn scrape(metrics: &web::Data<RequestMetrics>) -> String {
+    let app = test::init_service(
+        App::new()
+            .app_data(metrics.clone())
+            .configure(request_metrics::config),
+    )
+    .await;
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+    String::from_utf8(test::call_and_read_body(&app, req).await.to_vec()).unwrap()
+}
+
+fn posts(count: usize) -> Cached<Vec<Post>> {
+    Cached::new(
+        (0..count)
+            .map(|i| {
+                Post::new(
+                    format!("Kerja bakti {}", i),
+                    "Kegiatan".to_string(),
+                    "Kerja bakti di RW 01".to_string(),
+                    None,
+                )
+            })
+            .collect(),
+    )
+}
+
+#[tokio::test]
+async fn test_sampler_exports_cache_sizes_and_queue_depth() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut state = app_state(dir.path());
+    let (sender, mut receiver) = mpsc::channel(10);
+    state.organization_persist_sender = sender;
+    let metrics = metrics(&state, &ToolRegistry::new().unwrap());
+
+    state
+        .post_cache
+        .insert("all_posts".to_string(), posts(3))
+        .await;
+    state
+        .post_cache
+        .insert("page_1".to_string(), posts(1))
+        .await;
+    for _ in 0..3 {
+        state
+            .organization_persist_sender
+            .send(Vec::new())
+            .await
+            .unwrap();
+    }
+
+    state.sample_runtime_metrics().await;
+    let body = scrape(&metrics).await;
+    assert!(body.contains(r#"cakung_barat_server_cache_entries{cache="posts"} 2"#));
+    assert!(body.contains(r#"cakung_barat_server_cache_weighted_size{cache="posts"} 2"#));
+    assert!(body.contains(r#"cakung_barat_server_cache_weighted_size{cache="organization"} 0"#));
+    assert!(
+        body.contains(r#"cakung_barat_server_worker_queue_depth{queue="organization_persist"} 3"#)
+    );
+    assert!(body.contains(r#"cakung_barat_server_worker_queue_depth{queue="webhook"} 0"#));
+
+    // Invalidations and drained messages show up on the next sample
+    state.post_cache.invalidate("page_1").await;
+    receiver.recv().await.unwrap();
+    receiver.recv().await.unwrap();
+    state.sample_runtime_metrics().await;
+    let body = scrape(&metrics).await;
+    assert!(body.contains(r#"cakung_barat_server_cache_weighted_size{cache="posts"} 1"#));
+    assert!(
+        body.contains(r#"cakung_barat_server_worker_queue_depth{queue="organization_persist"} 1"#)
+    );
+}
+
+#[tokio::test]
+async fn test_background_sampler_updates_gauges() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut state = app_state(dir.path());
+    let (sender, _receiver) = mpsc::channel(10);
+    state.organization_persist_sender = sender;
+
+    state.start_runtime_metrics(Duration::from_millis(20));
+    // A second start keeps the running sampler
+    state.start_runtime_metrics(Duration::from_secs(3600));
+    state
+        .organization_persist_sender
+        .send(Vec::new())
+        .await
+        .unwrap();
+    state
+        .post_cache
+        .insert("all_posts".to_string(), posts(1))
+        .await;
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let metrics = &state.runtime_metrics;
+    assert_eq!(metrics.queue_depth(WorkerQueue::OrganizationPersist), 1);
+    assert_eq!(state.cache_metrics.weighted_size(CacheKind::Posts), 1);
+
+    metrics.stop();
+}
+
+#[tokio::test]
+async fn test_storage_failures_are_counted_by_operation() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = app_state(dir.path());
+    let metrics = metrics(&state, &ToolRegistry::new().unwrap());
+
+    state.storage.upload_file("ada.txt", b"isi").await.unwrap();
+    state.storage.download_file("ada.txt").await.unwrap();
+    assert!(state.storage.download_file("hilang.txt").await.is_err());
+    assert!(state.storage.download_file("../keluar.txt").await.is_err());
+    assert!(state.storage.delete_prefix("").await.is_err());
+
+    assert_eq!(state.http_metrics.failure_count("download"), 2);
+    assert_eq!(state.http_metrics.failure_count("upload"), 0);
+    let body = scrape(&metrics).await;
+    assert!(body.contains(
+        r#"cakung_barat_server_storage_operation_failures_total{operation="download"} 2"#
+    ));
+    assert!(body.contains(
+        r#"cakung_barat_server_storage_operation_failures_total{operation="delete_prefix"} 1"#
+    ));
+
+    // Local files are still streamed from disk through the wrapper
+    assert_eq!(
+        state.storage.local_path("ada.txt"),
+        Some(dir.path().join("ada.txt"))
+    );
+}
+
+#[tokio::test]
+async fn test_mcp_tool_calls_are_exported() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = web::Data::new(app_state(dir.path()));
+    let tools = ToolRegistry::new().unwrap();
+    let metrics = metrics(&state, &tools);
+    state
+        .post_cache
+        .insert("all_posts".to_string(), posts(1))
+        .await;
+
+    for _ in 0..2 {
+        let result = tools
+            .call_tool_with_context(
+                "list_postings",
+                Some(serde_json::json!({})),
+                &state,
+                &ToolContext::default(),
+            )
+            .await;
+        assert!(!result.is_error);
+    }
+
+    let body = scrape(&metrics).await;
+    assert!(body.contains(
+        r#"cakung_barat_server_mcp_tool_calls_total{outcome="success",tool="list_postings"} 2"#
+    ));
+}
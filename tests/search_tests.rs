@@ -0,0 +1,36 @@
+//! Tests for query validation of the full-text search endpoint
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::posting::search::search_postings;
+use cakung_barat_server::storage::LocalStorage;
+use std::sync::Arc;
+
+#[actix_web::test]
+async fn test_search_rejects_bad_queries() {
+    let state = common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .build()
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .route("/api/search", web::get().to(search_postings)),
+    )
+    .await;
+
+    for query in [
+        "",
+        "q=",
+        "q=%20%20",
+        "q=posyandu&limit=0",
+        "q=posyandu&limit=101",
+        "q=posyandu&offset=-1",
+    ] {
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/search?{}", query))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 400, "{}", query);
+    }
+}
@@ -0,0 +1,143 @@
+//! Tests for ServerConfig environment parsing
+
+use cakung_barat_server::config::{parse_origins, CorsOrigins, ServerConfig};
+use std::collections::HashMap;
+
+fn config_from(vars: &[(&str, &str)]) -> Result<ServerConfig, String> {
+    let vars: HashMap<String, String> = vars
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    ServerConfig::from_lookup(|key| vars.get(key).cloned())
+}
+
+#[test]
+fn test_defaults_match_previous_hardcoded_values() {
+    let config = config_from(&[]).unwrap();
+
+    assert_eq!(config.host, "0.0.0.0");
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.workers, None);
+    assert_eq!(config.max_connections, 25000);
+    assert!(config.cors_allow_credentials);
+    match config.cors_allowed_origins {
+        CorsOrigins::List(origins) => {
+            assert!(origins.contains(&"https://tsfarizi.github.io".to_string()));
+            assert!(origins.contains(&"http://localhost:5173".to_string()));
+        }
+        CorsOrigins::Any => panic!("default should be an explicit list"),
+    }
+}
+
+#[test]
+fn test_overrides_from_env() {
+    let config = config_from(&[
+        ("HOST", "127.0.0.1"),
+        ("PORT", "3000"),
+        ("WORKERS", "4"),
+        ("MAX_CONNECTIONS", "1000"),
+        (
+            "CORS_ALLOWED_ORIGINS",
+            "https://staging.example.com, http://localhost:4000/",
+        ),
+        ("CORS_ALLOW_CREDENTIALS", "false"),
+    ])
+    .unwrap();
+
+    assert_eq!(config.host, "127.0.0.1");
+    assert_eq!(config.port, 3000);
+    assert_eq!(config.workers, Some(4));
+    assert_eq!(config.max_connections, 1000);
+    assert!(!config.cors_allow_credentials);
+    assert_eq!(
+        config.cors_allowed_origins,
+        CorsOrigins::List(vec![
+            "https://staging.example.com".to_string(),
+            "http://localhost:4000".to_string(),
+        ])
+    );
+}
+
+#[test]
+fn test_blank_values_use_defaults() {
+    let config = config_from(&[("PORT", "  "), ("CORS_ALLOWED_ORIGINS", "")]).unwrap();
+
+    assert_eq!(config.port, 8080);
+    assert!(matches!(config.cors_allowed_origins, CorsOrigins::List(_)));
+}
+
+#[test]
+fn test_invalid_values_fail() {
+    assert!(config_from(&[("PORT", "eighty")])
+        .unwrap_err()
+        .contains("PORT"));
+    assert!(config_from(&[("PORT", "70000")]).is_err());
+    assert!(config_from(&[("WORKERS", "0")])
+        .unwrap_err()
+        .contains("WORKERS"));
+    assert!(config_from(&[("MAX_CONNECTIONS", "-1")]).is_err());
+    assert!(config_from(&[("CORS_ALLOW_CREDENTIALS", "maybe")]).is_err());
+}
+
+#[test]
+fn test_origin_parsing() {
+    assert_eq!(parse_origins("*").unwrap(), CorsOrigins::Any);
+    assert_eq!(
+        parse_origins("https://a.example.com, *").unwrap(),
+        CorsOrigins::Any
+    );
+    assert!(parse_origins(" , ,").is_err());
+    assert!(parse_origins("example.com").is_err());
+}
+
+#[test]
+fn test_any_origin_requires_credentials_off() {
+    let err = config_from(&[("CORS_ALLOWED_ORIGINS", "*")]).unwrap_err();
+    assert!(err.contains("CORS_ALLOW_CREDENTIALS"), "{}", err);
+    assert!(config_from(&[
+        ("CORS_ALLOWED_ORIGINS", "*"),
+        ("CORS_ALLOW_CREDENTIALS", "true"),
+    ])
+    .is_err());
+
+    let config = config_from(&[
+        ("CORS_ALLOWED_ORIGINS", "*"),
+        ("CORS_ALLOW_CREDENTIALS", "false"),
+    ])
+    .unwrap();
+    assert_eq!(config.cors_allowed_origins, CorsOrigins::Any);
+    assert!(!config.cors_allow_credentials);
+}
+
+#[test]
+fn test_storage_strict_startup_flag() {
+    assert!(!config_from(&[]).unwrap().storage_strict_startup);
+    assert!(
+        config_from(&[("STORAGE_STRICT_STARTUP", "true")])
+            .unwrap()
+            .storage_strict_startup
+    );
+    assert!(config_from(&[("STORAGE_STRICT_STARTUP", "maybe")]).is_err());
+}
+
+#[test]
+fn test_debug_http_log_flag() {
+    assert!(!config_from(&[]).unwrap().debug_http_log);
+    assert!(
+        config_from(&[("DEBUG_HTTP_LOG", "true")])
+            .unwrap()
+            .debug_http_log
+    );
+    assert!(config_from(&[("DEBUG_HTTP_LOG", "verbose")]).is_err());
+}
+
+#[test]
+fn test_frontend_dist_path() {
+    assert_eq!(config_from(&[]).unwrap().frontend_dist_path, None);
+    assert_eq!(
+        config_from(&[("FRONTEND_DIST_PATH", " /srv/frontend/dist ")])
+            .unwrap()
+            .frontend_dist_path,
+        Some(std::path::PathBuf::from("/srv/frontend/dist"))
+    );
+}
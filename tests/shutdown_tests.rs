@@ -0,0 +1,118 @@
+//! Tests for AppState shutdown ordering: pool close, then persistence flush.
+
+mod common;
+
+use cakung_barat_server::organization::model::OrganizationMember;
+use cakung_barat_server::storage::{FolderContent, ObjectStorage};
+use cakung_barat_server::AppState;
+use sqlx::PgPool;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+
+/// Mock storage that records whether the pool was already closed when the
+/// persistence worker wrote to it
+struct OrderRecordingStorage {
+    pool: OnceLock<PgPool>,
+    uploads: AtomicUsize,
+    pool_closed_at_upload: AtomicBool,
+}
+
+#[async_trait::async_trait]
+impl ObjectStorage for OrderRecordingStorage {
+    async fn upload_file(&self, _filename: &str, _file_data: &[u8]) -> Result<(), String> {
+        let closed = self.pool.get().map(|p| p.is_closed()).unwrap_or(false);
+        self.pool_closed_at_upload.store(closed, Ordering::SeqCst);
+        self.uploads.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn download_file(&self, _filename: &str) -> Result<Vec<u8>, String> {
+        Ok(vec![])
+    }
+
+    async fn delete_file(&self, _filename: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn create_folder(&self, _folder_name: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn list_folder_contents(&self, _folder_name: &str) -> Result<Vec<FolderContent>, String> {
+        Ok(vec![])
+    }
+
+    fn get_asset_url(&self, _filename: &str) -> String {
+        "http://mock-url".to_string()
+    }
+}
+
+fn member(id: i32) -> OrganizationMember {
+    OrganizationMember {
+        id,
+        name: Some(format!("Member {}", id)),
+        position: "Staf".to_string(),
+        photo: None,
+        parent_id: None,
+        level: 1,
+        role: "staf".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_shutdown_closes_pool_then_flushes_worker() {
+    let pool = common::unreachable_pool();
+    let storage = Arc::new(OrderRecordingStorage {
+        pool: OnceLock::new(),
+        uploads: AtomicUsize::new(0),
+        pool_closed_at_upload: AtomicBool::new(false),
+    });
+    storage.pool.set(pool.clone()).unwrap();
+
+    let state = AppState::new_with_pool_and_storage(pool.clone(), storage.clone())
+        .await
+        .unwrap();
+
+    // Queue an update; the worker is still inside its debounce window
+    state
+        .organization_persist_sender
+        .send(vec![member(1), member(2)])
+        .await
+        .unwrap();
+
+    let started = std::time::Instant::now();
+    state.shutdown().await;
+
+    assert!(pool.is_closed(), "pool should be closed");
+    assert_eq!(
+        storage.uploads.load(Ordering::SeqCst),
+        1,
+        "pending update should be flushed"
+    );
+    assert!(
+        storage.pool_closed_at_upload.load(Ordering::SeqCst),
+        "pool should be closed before the worker flush"
+    );
+    // Flush cuts the debounce delay short
+    assert!(started.elapsed() < std::time::Duration::from_millis(450));
+}
+
+#[tokio::test]
+async fn test_shutdown_without_pending_updates() {
+    let pool = common::unreachable_pool();
+    let storage = Arc::new(OrderRecordingStorage {
+        pool: OnceLock::new(),
+        uploads: AtomicUsize::new(0),
+        pool_closed_at_upload: AtomicBool::new(false),
+    });
+
+    let state = AppState::new_with_pool_and_storage(pool, storage.clone())
+        .await
+        .unwrap();
+
+    state.shutdown().await;
+    // Second call is a no-op
+    state.shutdown().await;
+
+    assert_eq!(storage.uploads.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,99 @@
+//! Tests for the Supabase concurrency limit and storage operation metrics
+
+use actix_web::{web, App, HttpResponse, HttpServer};
+use cakung_barat_server::http_client::{HttpMetrics, Retry, RetryPolicy};
+use cakung_barat_server::storage::{ObjectStorage, SupabaseConfig, SupabaseStorage};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const LIMIT: usize = 4;
+
+fn storage(url: String) -> SupabaseStorage {
+    let config = SupabaseConfig {
+        supabase_url: url,
+        supabase_anon_key: "anon-key".to_string(),
+        bucket_name: "bucket".to_string(),
+        service_role_key: None,
+        bucket_public: true,
+    };
+    let policy = RetryPolicy {
+        attempts: 1,
+        backoff: Duration::from_millis(10),
+    };
+    SupabaseStorage::new(config, reqwest::Client::new())
+        .with_retry(Retry::new(policy, HttpMetrics::new()))
+        .with_concurrency_limit(LIMIT)
+}
+
+#[derive(Default)]
+struct InFlight {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+async fn slow_upload(in_flight: web::Data<InFlight>) -> HttpResponse {
+    let now = in_flight.current.fetch_add(1, Ordering::SeqCst) + 1;
+    in_flight.peak.fetch_max(now, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    in_flight.current.fetch_sub(1, Ordering::SeqCst);
+    HttpResponse::Ok().finish()
+}
+
+#[actix_web::test]
+async fn test_concurrent_uploads_never_exceed_limit() {
+    let in_flight = web::Data::new(InFlight::default());
+    let data = in_flight.clone();
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(data.clone())
+            .default_service(web::to(slow_upload))
+    })
+    .workers(1)
+    .bind(("127.0.0.1", 0))
+    .unwrap();
+    let addr = server.addrs()[0];
+    let server = server.run();
+    let handle = server.handle();
+    actix_web::rt::spawn(server);
+
+    let storage = Arc::new(storage(format!("http://{}", addr)));
+    let uploads: Vec<_> = (0..50)
+        .map(|i| {
+            let storage = storage.clone();
+            actix_web::rt::spawn(async move {
+                storage
+                    .upload_file(&format!("file-{}.png", i), b"data")
+                    .await
+            })
+        })
+        .collect();
+    for upload in uploads {
+        upload.await.unwrap().unwrap();
+    }
+    handle.stop(false).await;
+
+    let peak = in_flight.peak.load(Ordering::SeqCst);
+    assert!(peak <= LIMIT, "peak concurrency {} exceeds {}", peak, LIMIT);
+    assert!(peak > 1, "uploads should overlap up to the limit");
+    assert_eq!(storage.retry.metrics.operation_count("upload"), 50);
+}
+
+#[actix_web::test]
+async fn test_rate_limited_responses_are_counted() {
+    let server = MockServer::start().await;
+    Mock::given(method("DELETE"))
+        .respond_with(ResponseTemplate::new(429))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let storage = storage(server.uri());
+
+    assert!(storage.delete_file("file.png").await.is_err());
+
+    assert_eq!(storage.retry.metrics.rate_limited_count("delete"), 1);
+    assert_eq!(storage.retry.metrics.retry_count("delete"), 0);
+    assert_eq!(storage.retry.metrics.operation_count("delete"), 1);
+}
@@ -0,0 +1,149 @@
+//! Tests for recursive prefix deletion
+
+use cakung_barat_server::storage::{LocalStorage, ObjectStorage, SupabaseConfig, SupabaseStorage};
+use wiremock::matchers::{body_json, body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const LIST_PATH: &str = "/storage/v1/object/list/bucket";
+
+fn storage(server: &MockServer) -> SupabaseStorage {
+    let config = SupabaseConfig {
+        supabase_url: server.uri(),
+        supabase_anon_key: "anon-key".to_string(),
+        bucket_name: "bucket".to_string(),
+        service_role_key: None,
+        bucket_public: true,
+    };
+    SupabaseStorage::new(config, reqwest::Client::new())
+}
+
+fn files(names: impl IntoIterator<Item = String>) -> serde_json::Value {
+    names
+        .into_iter()
+        .map(|name| serde_json::json!({ "name": name, "id": name, "metadata": { "size": 1 } }))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_supabase_lists_every_page_and_bulk_deletes() {
+    let server = MockServer::start().await;
+    let first_page: Vec<String> = (0..100).map(|i| format!("file-{:03}.png", i)).collect();
+    Mock::given(method("POST"))
+        .and(path(LIST_PATH))
+        .and(body_partial_json(
+            serde_json::json!({ "prefix": "posts/abc", "offset": 0 }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(files(first_page.clone())))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(LIST_PATH))
+        .and(body_partial_json(
+            serde_json::json!({ "prefix": "posts/abc", "offset": 100 }),
+        ))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(files(vec!["placeholder.txt".to_string()])),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut expected: Vec<String> = first_page
+        .iter()
+        .map(|name| format!("posts/abc/{}", name))
+        .collect();
+    expected.push("posts/abc/placeholder.txt".to_string());
+    Mock::given(method("DELETE"))
+        .and(path("/storage/v1/object/bucket"))
+        .and(body_json(serde_json::json!({ "prefixes": expected })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let removed = storage(&server).delete_prefix("posts/abc/").await.unwrap();
+
+    assert_eq!(removed, 101);
+}
+
+#[tokio::test]
+async fn test_supabase_descends_into_subfolders() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(LIST_PATH))
+        .and(body_partial_json(
+            serde_json::json!({ "prefix": "posts/abc" }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "name": "thumbs", "id": null },
+            { "name": "a.png", "id": "1" }
+        ])))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(LIST_PATH))
+        .and(body_partial_json(
+            serde_json::json!({ "prefix": "posts/abc/thumbs" }),
+        ))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!([{ "name": "a.webp", "id": "2" }])),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/storage/v1/object/bucket"))
+        .and(body_json(serde_json::json!({
+            "prefixes": ["posts/abc/a.png", "posts/abc/thumbs/a.webp"]
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    assert_eq!(
+        storage(&server).delete_prefix("posts/abc").await.unwrap(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn test_root_prefix_is_refused() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+    let storage = storage(&server);
+
+    for prefix in ["", "/", "//"] {
+        let err = storage.delete_prefix(prefix).await.unwrap_err();
+        assert!(err.contains("Refusing"), "{}", err);
+    }
+
+    let local = LocalStorage::new("storage");
+    assert!(local.delete_prefix("/").await.is_err());
+}
+
+#[tokio::test]
+async fn test_local_deletes_nested_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = LocalStorage::new(dir.path());
+    storage.create_folder("posts/abc").await.unwrap();
+    storage.upload_file("posts/abc/a.png", b"a").await.unwrap();
+    storage
+        .upload_file("posts/abc/thumbs/a.webp", b"a")
+        .await
+        .unwrap();
+    storage
+        .upload_file("posts/other/b.png", b"b")
+        .await
+        .unwrap();
+
+    assert_eq!(storage.delete_prefix("posts/abc").await.unwrap(), 2);
+    assert!(!dir.path().join("posts/abc").exists());
+    assert!(dir.path().join("posts/other/b.png").exists());
+    assert_eq!(storage.delete_prefix("posts/abc").await.unwrap(), 0);
+}
@@ -0,0 +1,177 @@
+//! Tests for storage health checks and their use in /readyz
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::health;
+use cakung_barat_server::storage::{
+    FolderContent, LocalStorage, ObjectStorage, SupabaseConfig, SupabaseStorage,
+};
+use serde_json::Value;
+use std::sync::Arc;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const LIST_PATH: &str = "/storage/v1/object/list/bucket";
+
+fn storage(server: &MockServer) -> SupabaseStorage {
+    let config = SupabaseConfig {
+        supabase_url: server.uri(),
+        supabase_anon_key: "anon-key".to_string(),
+        bucket_name: "bucket".to_string(),
+        service_role_key: None,
+        bucket_public: true,
+    };
+    SupabaseStorage::new(config, reqwest::Client::new())
+}
+
+async fn mock_list(server: &MockServer, response: ResponseTemplate) {
+    Mock::given(method("POST"))
+        .and(path(LIST_PATH))
+        .respond_with(response)
+        .mount(server)
+        .await;
+}
+
+#[actix_web::test]
+async fn test_supabase_health_check_passes() {
+    let server = MockServer::start().await;
+    mock_list(
+        &server,
+        ResponseTemplate::new(200).set_body_json(serde_json::json!([])),
+    )
+    .await;
+
+    storage(&server).health_check().await.unwrap();
+}
+
+#[actix_web::test]
+async fn test_supabase_unauthorized_names_credentials() {
+    let server = MockServer::start().await;
+    mock_list(&server, ResponseTemplate::new(401)).await;
+
+    let err = storage(&server).health_check().await.unwrap_err();
+
+    assert!(err.contains("rejected the storage credentials"), "{}", err);
+    assert!(err.contains("SUPABASE_ANON_KEY"), "{}", err);
+}
+
+#[actix_web::test]
+async fn test_supabase_wrapped_unauthorized_names_credentials() {
+    let server = MockServer::start().await;
+    mock_list(
+        &server,
+        ResponseTemplate::new(400).set_body_json(serde_json::json!({
+            "statusCode": "403",
+            "error": "Unauthorized",
+            "message": "invalid signature"
+        })),
+    )
+    .await;
+
+    let err = storage(&server).health_check().await.unwrap_err();
+
+    assert!(err.contains("rejected the storage credentials"), "{}", err);
+}
+
+#[actix_web::test]
+async fn test_supabase_missing_bucket_names_bucket() {
+    let server = MockServer::start().await;
+    mock_list(&server, ResponseTemplate::new(404)).await;
+
+    let err = storage(&server).health_check().await.unwrap_err();
+
+    assert!(err.contains("Bucket 'bucket' not found"), "{}", err);
+    assert!(err.contains("BUCKET_NAME"), "{}", err);
+}
+
+#[actix_web::test]
+async fn test_supabase_wrapped_missing_bucket_names_bucket() {
+    let server = MockServer::start().await;
+    mock_list(
+        &server,
+        ResponseTemplate::new(400).set_body_json(serde_json::json!({
+            "statusCode": "404",
+            "error": "Bucket not found",
+            "message": "Bucket not found"
+        })),
+    )
+    .await;
+
+    let err = storage(&server).health_check().await.unwrap_err();
+
+    assert!(err.contains("Bucket 'bucket' not found"), "{}", err);
+}
+
+#[actix_web::test]
+async fn test_local_health_check_requires_writable_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("storage");
+    LocalStorage::new(&root).health_check().await.unwrap();
+    assert!(root.is_dir());
+    assert_eq!(std::fs::read_dir(&root).unwrap().count(), 0);
+
+    let file = dir.path().join("not-a-dir");
+    std::fs::write(&file, b"").unwrap();
+    let err = LocalStorage::new(&file).health_check().await.unwrap_err();
+    assert!(err.contains("not writable"), "{}", err);
+}
+
+struct BrokenStorage;
+
+#[async_trait::async_trait]
+impl ObjectStorage for BrokenStorage {
+    async fn upload_file(&self, _filename: &str, _file_data: &[u8]) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn download_file(&self, _filename: &str) -> Result<Vec<u8>, String> {
+        Ok(vec![])
+    }
+
+    async fn delete_file(&self, _filename: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn create_folder(&self, _folder_name: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn list_folder_contents(&self, _folder_name: &str) -> Result<Vec<FolderContent>, String> {
+        Ok(vec![])
+    }
+
+    fn get_asset_url(&self, _filename: &str) -> String {
+        "http://mock-url".to_string()
+    }
+
+    async fn health_check(&self) -> Result<(), String> {
+        Err("Bucket 'bucket' not found; check BUCKET_NAME".to_string())
+    }
+}
+
+#[actix_web::test]
+async fn test_readyz_reports_storage_failure() {
+    let state = web::Data::new(
+        common::state_builder(Arc::new(BrokenStorage))
+            .build()
+            .unwrap(),
+    );
+    let app = test::init_service(
+        App::new()
+            .app_data(state)
+            .route("/readyz", web::get().to(health::readyz)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/readyz").to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 503);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["storage"], false);
+    assert_eq!(
+        body["storage_error"],
+        "Bucket 'bucket' not found; check BUCKET_NAME"
+    );
+}
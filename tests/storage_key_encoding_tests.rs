@@ -0,0 +1,71 @@
+//! Tests that object keys are percent-encoded in Supabase request paths
+
+use cakung_barat_server::storage::{encode_key, ObjectStorage, SupabaseConfig, SupabaseStorage};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Filenames and the path segment Supabase must receive for them
+const CASES: &[(&str, &str)] = &[
+    ("photo.png", "photo.png"),
+    ("my photo.png", "my%20photo.png"),
+    ("a+b.png", "a%2Bb.png"),
+    ("hari #1?.png", "hari%20%231%3F.png"),
+    ("50%.png", "50%25.png"),
+    ("kantor kelurahan ü.jpg", "kantor%20kelurahan%20%C3%BC.jpg"),
+    (
+        "posts/2024/foto bersama.png",
+        "posts/2024/foto%20bersama.png",
+    ),
+];
+
+fn storage(server: &MockServer) -> SupabaseStorage {
+    let config = SupabaseConfig {
+        supabase_url: server.uri(),
+        supabase_anon_key: "anon-key".to_string(),
+        bucket_name: "bucket".to_string(),
+        service_role_key: None,
+        bucket_public: true,
+    };
+    SupabaseStorage::new(config, reqwest::Client::new())
+}
+
+#[test]
+fn test_encode_key_keeps_folder_separators() {
+    for (filename, encoded) in CASES {
+        assert_eq!(encode_key(filename), *encoded, "{}", filename);
+    }
+}
+
+#[tokio::test]
+async fn test_keys_round_trip_through_upload_url_and_delete() {
+    for (filename, encoded) in CASES {
+        let server = MockServer::start().await;
+        let object_path = format!("/storage/v1/object/bucket/{}", encoded);
+        Mock::given(method("POST"))
+            .and(path(object_path.as_str()))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(object_path.as_str()))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let storage = storage(&server);
+
+        storage.upload_file(filename, b"data").await.unwrap();
+        assert_eq!(
+            storage.get_asset_url(filename),
+            format!(
+                "{}/storage/v1/object/public/bucket/{}",
+                server.uri(),
+                encoded
+            )
+        );
+        storage.delete_file(filename).await.unwrap();
+
+        server.verify().await;
+    }
+}
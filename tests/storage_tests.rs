@@ -9,6 +9,8 @@ mod storage_tests {
             supabase_url: "https://test.supabase.co".to_string(),
             supabase_anon_key: "test-anon-key".to_string(),
             bucket_name: "my-bucket".to_string(),
+            service_role_key: None,
+            bucket_public: true,
         };
         let debug_str = format!("{:?}", config);
 
@@ -24,6 +26,8 @@ mod storage_tests {
             supabase_url: "https://test.supabase.co".to_string(),
             supabase_anon_key: "test-anon-key".to_string(),
             bucket_name: "cakung-barat-supabase-bucket".to_string(),
+            service_role_key: None,
+            bucket_public: true,
         };
 
         assert_eq!(config.supabase_url, "https://test.supabase.co");
@@ -38,6 +42,8 @@ mod storage_tests {
             supabase_url: "https://test.supabase.co".to_string(),
             supabase_anon_key: "test-anon-key".to_string(),
             bucket_name: "my-custom-bucket".to_string(),
+            service_role_key: None,
+            bucket_public: true,
         };
 
         assert_eq!(config.bucket_name, "my-custom-bucket");
@@ -74,6 +80,8 @@ mod storage_tests {
             supabase_url: "https://test.supabase.co".to_string(),
             supabase_anon_key: "test-anon-key".to_string(),
             bucket_name: "test-bucket".to_string(),
+            service_role_key: None,
+            bucket_public: true,
         };
         let config2 = config1.clone();
 
@@ -0,0 +1,103 @@
+//! Tests for server-side copy and move against a mock Supabase
+
+use cakung_barat_server::storage::{ObjectStorage, SupabaseConfig, SupabaseStorage};
+use wiremock::matchers::{body_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn storage(server: &MockServer) -> SupabaseStorage {
+    let config = SupabaseConfig {
+        supabase_url: server.uri(),
+        supabase_anon_key: "anon-key".to_string(),
+        bucket_name: "bucket".to_string(),
+        service_role_key: Some("service-key".to_string()),
+        bucket_public: true,
+    };
+    SupabaseStorage::new(config, reqwest::Client::new())
+}
+
+fn transfer_body(from: &str, to: &str) -> serde_json::Value {
+    serde_json::json!({
+        "bucketId": "bucket",
+        "sourceKey": from,
+        "destinationKey": to,
+    })
+}
+
+fn not_found() -> ResponseTemplate {
+    ResponseTemplate::new(400).set_body_json(serde_json::json!({
+        "statusCode": "404",
+        "error": "not_found",
+        "message": "Object not found"
+    }))
+}
+
+#[tokio::test]
+async fn test_copy_uses_copy_endpoint() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/storage/v1/object/copy"))
+        .and(header("authorization", "Bearer service-key"))
+        .and(body_json(transfer_body("a.png", "posts/a.png")))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    storage(&server)
+        .copy_file("a.png", "posts/a.png")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_move_uses_move_endpoint() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/storage/v1/object/move"))
+        .and(body_json(transfer_body("old/a.png", "new/a.png")))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    storage(&server)
+        .move_file("old/a.png", "new/a.png")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_copy_of_missing_object_reports_not_found() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/storage/v1/object/copy"))
+        .respond_with(not_found())
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let err = storage(&server)
+        .copy_file("missing.png", "b.png")
+        .await
+        .unwrap_err();
+
+    assert!(err.contains("not found"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_move_of_missing_object_reports_not_found() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/storage/v1/object/move"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let err = storage(&server)
+        .move_file("missing.png", "b.png")
+        .await
+        .unwrap_err();
+
+    assert!(err.contains("not found"), "{}", err);
+}
@@ -0,0 +1,290 @@
+//! Tests for overwrite protection on uploads and the unique-name retry
+
+use cakung_barat_server::storage::{
+    upload_with_unique_name, FolderContent, LocalStorage, ObjectStorage, SupabaseConfig,
+    SupabaseStorage,
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// A backend without conditional writes, relying on the default
+/// `upload_new_file` and `exists`
+#[derive(Default)]
+struct PlainStorage {
+    files: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+#[async_trait::async_trait]
+impl ObjectStorage for PlainStorage {
+    async fn upload_file(&self, filename: &str, file_data: &[u8]) -> Result<(), String> {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(filename.to_string(), file_data.to_vec());
+        Ok(())
+    }
+
+    async fn download_file(&self, filename: &str) -> Result<Vec<u8>, String> {
+        self.files
+            .lock()
+            .unwrap()
+            .get(filename)
+            .cloned()
+            .ok_or_else(|| "not found".to_string())
+    }
+
+    async fn delete_file(&self, filename: &str) -> Result<(), String> {
+        self.files.lock().unwrap().remove(filename);
+        Ok(())
+    }
+
+    async fn create_folder(&self, _folder_name: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn list_folder_contents(&self, folder_name: &str) -> Result<Vec<FolderContent>, String> {
+        let prefix = match folder_name {
+            "" => String::new(),
+            folder => format!("{}/", folder),
+        };
+        Ok(self
+            .files
+            .lock()
+            .unwrap()
+            .keys()
+            .filter_map(|key| key.strip_prefix(&prefix))
+            .filter(|name| !name.contains('/'))
+            .map(|name| FolderContent {
+                name: name.to_string(),
+                is_file: true,
+                size: None,
+            })
+            .collect())
+    }
+
+    fn get_asset_url(&self, filename: &str) -> String {
+        format!("/assets/serve/{}", filename)
+    }
+}
+
+fn storage(server: &MockServer) -> SupabaseStorage {
+    let config = SupabaseConfig {
+        supabase_url: server.uri(),
+        supabase_anon_key: "anon-key".to_string(),
+        bucket_name: "bucket".to_string(),
+        service_role_key: None,
+        bucket_public: true,
+    };
+    SupabaseStorage::new(config, reqwest::Client::new())
+}
+
+fn duplicate() -> ResponseTemplate {
+    ResponseTemplate::new(400).set_body_json(serde_json::json!({
+        "statusCode": "409",
+        "error": "Duplicate",
+        "message": "The resource already exists"
+    }))
+}
+
+#[tokio::test]
+async fn test_upload_file_overwrites() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/storage/v1/object/bucket/organization.json"))
+        .and(header("x-upsert", "true"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    storage(&server)
+        .upload_file("organization.json", b"[]")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_upload_new_file_reports_existing_name() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/storage/v1/object/bucket/taken.png"))
+        .and(header("x-upsert", "false"))
+        .respond_with(duplicate())
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let stored = storage(&server)
+        .upload_new_file("taken.png", b"data")
+        .await
+        .unwrap();
+
+    assert!(!stored);
+}
+
+#[tokio::test]
+async fn test_upload_new_file_still_fails_on_other_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+            "statusCode": "400",
+            "error": "Invalid",
+            "message": "Invalid key"
+        })))
+        .mount(&server)
+        .await;
+
+    assert!(storage(&server)
+        .upload_new_file("bad.png", b"data")
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_taken_name_is_retried_with_a_new_name() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/storage/v1/object/bucket/name-0.png"))
+        .respond_with(duplicate())
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/storage/v1/object/bucket/name-1.png"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let storage = storage(&server);
+
+    let name =
+        upload_with_unique_name(&storage, b"data", |attempt| format!("name-{}.png", attempt))
+            .await
+            .unwrap();
+
+    assert_eq!(name, "name-1.png");
+}
+
+#[tokio::test]
+async fn test_gives_up_when_every_name_is_taken() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(duplicate())
+        .expect(3)
+        .mount(&server)
+        .await;
+    let storage = storage(&server);
+
+    let err = upload_with_unique_name(&storage, b"data", |attempt| format!("name-{}.png", attempt))
+        .await
+        .unwrap_err();
+
+    assert!(err.contains("No free storage name"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_local_upload_new_file_keeps_existing_object() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = LocalStorage::new(dir.path());
+
+    assert!(storage.upload_new_file("a/b.png", b"first").await.unwrap());
+    assert!(!storage.upload_new_file("a/b.png", b"second").await.unwrap());
+    assert_eq!(storage.download_file("a/b.png").await.unwrap(), b"first");
+
+    let name = upload_with_unique_name(&storage, b"third", |attempt| {
+        if attempt == 0 {
+            "a/b.png".to_string()
+        } else {
+            "a/c.png".to_string()
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(name, "a/c.png");
+}
+
+#[tokio::test]
+async fn test_default_upload_new_file_checks_exists() {
+    let storage = PlainStorage::default();
+    storage.upload_file("posts/a.png", b"first").await.unwrap();
+
+    assert!(storage.exists("posts/a.png").await.unwrap());
+    assert!(!storage.exists("posts/b.png").await.unwrap());
+    assert!(!storage.exists("a.png").await.unwrap());
+
+    let name = upload_with_unique_name(&storage, b"second", |attempt| {
+        format!("posts/{}.png", ["a", "b"][attempt])
+    })
+    .await
+    .unwrap();
+    assert_eq!(name, "posts/b.png");
+    assert_eq!(
+        storage.download_file("posts/a.png").await.unwrap(),
+        b"first"
+    );
+}
+
+#[tokio::test]
+async fn test_supabase_exists_uses_object_info() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/storage/v1/object/info/bucket/posts/a.png"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "name": "posts/a.png"
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/storage/v1/object/info/bucket/posts/missing.png"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+            "statusCode": "404",
+            "error": "not_found",
+            "message": "Object not found"
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/storage/v1/object/info/bucket/posts/denied.png"))
+        .respond_with(ResponseTemplate::new(403))
+        .mount(&server)
+        .await;
+    let storage = storage(&server);
+
+    assert!(storage.exists("posts/a.png").await.unwrap());
+    assert!(!storage.exists("posts/missing.png").await.unwrap());
+    assert!(storage.exists("posts/denied.png").await.is_err());
+}
+
+#[tokio::test]
+async fn test_create_folder_skips_existing_placeholder() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(
+            "/storage/v1/object/info/bucket/kegiatan/placeholder.txt",
+        ))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    storage(&server).create_folder("kegiatan").await.unwrap();
+}
+
+#[tokio::test]
+async fn test_local_exists() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = LocalStorage::new(dir.path());
+    storage.upload_file("a/b.png", b"data").await.unwrap();
+
+    assert!(storage.exists("a/b.png").await.unwrap());
+    assert!(!storage.exists("a/c.png").await.unwrap());
+    // Folders are not objects
+    assert!(!storage.exists("a").await.unwrap());
+    assert!(storage.exists("../b.png").await.is_err());
+}
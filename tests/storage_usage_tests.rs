@@ -0,0 +1,194 @@
+//! Tests for the storage usage report, bucket scan and usage config
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::storage::{LocalStorage, ObjectStorage, SupabaseConfig, SupabaseStorage};
+use cakung_barat_server::storage_usage::{
+    self, scan_bucket, StorageUsageConfig, StorageUsageReport, UNFILED_FOLDER,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars: HashMap<String, String> = vars
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    move |key| vars.get(key).cloned()
+}
+
+fn object(name: &str, size: u64) -> (String, u64) {
+    (name.to_string(), size)
+}
+
+fn asset(filename: &str, folder: Option<&str>) -> (String, Option<String>) {
+    (filename.to_string(), folder.map(str::to_string))
+}
+
+#[actix_web::test]
+async fn test_report_breaks_down_usage_by_folder() {
+    let objects = [
+        object("a.png", 100),
+        object("b.png", 50),
+        object("c.png", 25),
+        object("stray.png", 7),
+    ];
+    let assets = [
+        asset("a.png", Some("news")),
+        asset("b.png", Some("news")),
+        asset("b.png", Some("gallery")),
+        asset("c.png", None),
+        asset("gone.png", Some("news")),
+    ];
+
+    let report = StorageUsageReport::build(&objects, &assets, Some(364));
+
+    assert_eq!(report.total_bytes, 182);
+    assert_eq!(report.object_count, 4);
+    assert_eq!(report.quota_used, Some(0.5));
+    let folders: Vec<(&str, u64, usize)> = report
+        .folders
+        .iter()
+        .map(|f| (f.name.as_str(), f.bytes, f.assets))
+        .collect();
+    assert_eq!(
+        folders,
+        vec![
+            ("gallery", 50, 1),
+            ("news", 150, 3),
+            (UNFILED_FOLDER, 25, 1)
+        ]
+    );
+    assert_eq!(report.untracked_objects, 1);
+    assert_eq!(report.untracked_bytes, 7);
+    assert_eq!(report.missing_objects, vec!["gone.png".to_string()]);
+}
+
+#[actix_web::test]
+async fn test_report_without_quota() {
+    let report = StorageUsageReport::build(&[object("a.png", 10)], &[], None);
+
+    assert_eq!(report.quota_bytes, None);
+    assert_eq!(report.quota_used, None);
+    assert_eq!(report.untracked_bytes, 10);
+}
+
+#[actix_web::test]
+async fn test_scan_recurses_into_folders() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = LocalStorage::new(dir.path());
+    storage.upload_file("root.png", &[0; 10]).await.unwrap();
+    storage
+        .upload_file("posts/one/a.png", &[0; 20])
+        .await
+        .unwrap();
+    storage
+        .upload_file("posts/two/b.png", &[0; 30])
+        .await
+        .unwrap();
+
+    let mut objects = scan_bucket(&storage, "").await.unwrap();
+    objects.sort();
+
+    assert_eq!(
+        objects,
+        vec![
+            object("posts/one/a.png", 20),
+            object("posts/two/b.png", 30),
+            object("root.png", 10),
+        ]
+    );
+}
+
+#[actix_web::test]
+async fn test_supabase_listing_follows_pages() {
+    let server = MockServer::start().await;
+    let page = |offset: usize, count: usize| {
+        let files: Vec<_> = (offset..offset + count)
+            .map(|i| {
+                serde_json::json!({
+                    "name": format!("file-{}.png", i),
+                    "id": format!("id-{}", i),
+                    "metadata": { "size": 1 }
+                })
+            })
+            .collect();
+        ResponseTemplate::new(200).set_body_json(files)
+    };
+    for (offset, count) in [(0, 100), (100, 3)] {
+        Mock::given(method("POST"))
+            .and(path("/storage/v1/object/list/bucket"))
+            .and(body_partial_json(serde_json::json!({ "offset": offset })))
+            .respond_with(page(offset, count))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+    let config = SupabaseConfig {
+        supabase_url: server.uri(),
+        supabase_anon_key: "anon-key".to_string(),
+        bucket_name: "bucket".to_string(),
+        service_role_key: None,
+        bucket_public: true,
+    };
+    let storage = SupabaseStorage::new(config, reqwest::Client::new());
+
+    let objects = scan_bucket(&storage, "").await.unwrap();
+
+    assert_eq!(objects.len(), 103);
+    assert_eq!(objects.iter().map(|(_, size)| size).sum::<u64>(), 103);
+}
+
+#[actix_web::test]
+async fn test_config_defaults_and_overrides() {
+    let config = StorageUsageConfig::from_lookup(lookup(&[])).unwrap();
+    assert_eq!(config, StorageUsageConfig::default());
+    assert_eq!(config.quota_bytes, None);
+    assert_eq!(config.interval, Some(Duration::from_secs(3600)));
+
+    let config = StorageUsageConfig::from_lookup(lookup(&[
+        ("STORAGE_QUOTA_BYTES", "1073741824"),
+        ("STORAGE_USAGE_INTERVAL_SECS", "0"),
+    ]))
+    .unwrap();
+    assert_eq!(config.quota_bytes, Some(1073741824));
+    assert_eq!(config.interval, None);
+}
+
+#[actix_web::test]
+async fn test_config_rejects_invalid_values() {
+    let err =
+        StorageUsageConfig::from_lookup(lookup(&[("STORAGE_QUOTA_BYTES", "1GB")])).unwrap_err();
+    assert!(err.contains("STORAGE_QUOTA_BYTES"), "{}", err);
+
+    let err = StorageUsageConfig::from_lookup(lookup(&[("STORAGE_USAGE_INTERVAL_SECS", "-1")]))
+        .unwrap_err();
+    assert!(err.contains("STORAGE_USAGE_INTERVAL_SECS"), "{}", err);
+}
+
+#[actix_web::test]
+async fn test_usage_endpoint_requires_token() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = common::state_builder(Arc::new(LocalStorage::new(dir.path())))
+        .with_storage_quota(Some(1024))
+        .build()
+        .unwrap();
+    assert_eq!(state.storage_usage.quota_bytes(), Some(1024));
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(web::scope("/api").configure(storage_usage::config)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/admin/storage/usage")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 401);
+}
@@ -0,0 +1,201 @@
+//! Tests for complaint form validation and the submission state machine
+
+mod common;
+
+use actix_web::http::header;
+use actix_web::{test, web, App};
+use cakung_barat_server::asset::gallery::GalleryConfig;
+use cakung_barat_server::posting::multipart_parser::ParsedSubmissionMultipart;
+use cakung_barat_server::storage::LocalStorage;
+use cakung_barat_server::submission::handlers::{config, validate_submission, ValidSubmission};
+use cakung_barat_server::submission::{SubmissionConfig, SubmissionStatus};
+use std::sync::Arc;
+
+const BOUNDARY: &str = "----cakung-barat-boundary";
+
+fn state(submissions: SubmissionConfig) -> cakung_barat_server::db::AppState {
+    // Every request here is answered before the database is reached
+    common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .with_submission_config(submissions)
+        .build()
+        .unwrap()
+}
+
+fn form(nama: &str, nik: Option<&str>, kategori: &str, isi: &str) -> ParsedSubmissionMultipart {
+    ParsedSubmissionMultipart {
+        nama: nama.to_string(),
+        nik: nik.map(str::to_string),
+        kategori: kategori.to_string(),
+        isi: isi.to_string(),
+        attachment: None,
+    }
+}
+
+/// A multipart body with one text part per `(name, value)`
+fn multipart_body(parts: &[(&str, &str)]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (name, value) in parts {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                BOUNDARY, name, value
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(format!("--{}--\r\n", BOUNDARY).as_bytes());
+    body
+}
+
+#[actix_web::test]
+async fn test_status_transitions() {
+    use SubmissionStatus::*;
+
+    for (from, to, allowed) in [
+        (Open, InProgress, true),
+        (Open, Resolved, true),
+        (InProgress, InProgress, true),
+        (InProgress, Resolved, true),
+        (Resolved, Resolved, false),
+        (Resolved, InProgress, false),
+        (Resolved, Open, false),
+        (InProgress, Open, false),
+        (Open, Open, false),
+    ] {
+        assert_eq!(from.can_become(to), allowed, "{:?} -> {:?}", from, to);
+    }
+    assert_eq!(serde_json::to_value(InProgress).unwrap(), "in_progress");
+}
+
+#[actix_web::test]
+async fn test_submission_config_from_lookup() {
+    let config = SubmissionConfig::from_lookup(|_| None).unwrap();
+    assert_eq!(config, SubmissionConfig::default());
+
+    let config = SubmissionConfig::from_lookup(|key| match key {
+        "SUBMISSIONS_PER_HOUR" => Some("10".to_string()),
+        "SUBMISSION_MAX_LENGTH" => Some(" 800 ".to_string()),
+        _ => None,
+    })
+    .unwrap();
+    assert_eq!(config.per_ip_per_hour, 10);
+    assert_eq!(config.max_length, 800);
+
+    let err = SubmissionConfig::from_lookup(|key| {
+        (key == "SUBMISSIONS_PER_HOUR").then(|| "0".to_string())
+    })
+    .unwrap_err();
+    assert!(err.contains("SUBMISSIONS_PER_HOUR"), "{}", err);
+}
+
+#[actix_web::test]
+async fn test_valid_submission_is_trimmed() {
+    let submission = form(
+        " Budi ",
+        Some(" 3175071234560001 "),
+        "Kebersihan",
+        " Sampah menumpuk ",
+    );
+    assert_eq!(
+        validate_submission(&submission, 100).unwrap(),
+        ValidSubmission {
+            nama: "Budi",
+            nik: Some("3175071234560001"),
+            kategori: "kebersihan",
+            isi: "Sampah menumpuk",
+        }
+    );
+
+    // A blank NIK is the same as none
+    let submission = form("Budi", Some("  "), "lainnya", "Halo");
+    assert_eq!(validate_submission(&submission, 100).unwrap().nik, None);
+}
+
+#[actix_web::test]
+async fn test_invalid_submissions_are_rejected() {
+    for (submission, field) in [
+        (form(" ", None, "kebersihan", "Halo"), "nama"),
+        (form(&"x".repeat(101), None, "kebersihan", "Halo"), "nama"),
+        (form("Budi", Some("12345"), "kebersihan", "Halo"), "nik"),
+        (
+            form("Budi", Some("31750712345600ab"), "kebersihan", "Halo"),
+            "nik",
+        ),
+        (form("Budi", None, "", "Halo"), "kategori"),
+        (form("Budi", None, "politik", "Halo"), "kategori"),
+        (form("Budi", None, "kebersihan", "  "), "isi"),
+        (form("Budi", None, "kebersihan", "Sebelas huruf"), "isi"),
+    ] {
+        let err = validate_submission(&submission, 10).unwrap_err();
+        assert!(
+            err.message().starts_with(field),
+            "{:?}: {}",
+            submission,
+            err.message()
+        );
+    }
+}
+
+#[actix_web::test]
+async fn test_invalid_form_is_rejected_before_the_rate_limit() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state(SubmissionConfig {
+                per_ip_per_hour: 1,
+                ..SubmissionConfig::default()
+            })))
+            .service(web::scope("/api").configure(config)),
+    )
+    .await;
+
+    for _ in 0..3 {
+        let req = test::TestRequest::post()
+            .uri("/api/submissions")
+            .insert_header((
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={}", BOUNDARY),
+            ))
+            .set_payload(multipart_body(&[
+                ("nama", "Budi"),
+                ("kategori", "politik"),
+                ("isi", "Halo"),
+            ]))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 400);
+    }
+}
+
+#[actix_web::test]
+async fn test_inbox_requires_a_token() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state(SubmissionConfig::default())))
+            .service(web::scope("/api").configure(config)),
+    )
+    .await;
+
+    let id = uuid::Uuid::new_v4();
+    for req in [
+        test::TestRequest::get().uri("/api/admin/submissions"),
+        test::TestRequest::get().uri(&format!("/api/admin/submissions/{}", id)),
+        test::TestRequest::post()
+            .uri(&format!("/api/admin/submissions/{}/assign", id))
+            .set_json(serde_json::json!({ "assigned_to": "Pak RT" })),
+        test::TestRequest::post()
+            .uri(&format!("/api/admin/submissions/{}/resolve", id))
+            .set_json(serde_json::json!({ "resolution_notes": "Selesai" })),
+    ] {
+        let resp = test::call_service(&app, req.to_request()).await;
+        assert_eq!(resp.status(), 401);
+    }
+}
+
+#[actix_web::test]
+async fn test_submission_folders_cannot_be_public() {
+    let err = GalleryConfig::from_lookup(|key| {
+        (key == "PUBLIC_FOLDERS").then(|| format!("galeri, submissions/{}", uuid::Uuid::new_v4()))
+    })
+    .unwrap_err();
+    assert!(err.contains("submissions/"), "{}", err);
+}
@@ -0,0 +1,197 @@
+//! Tests for signed asset URLs from a private Supabase bucket
+
+use cakung_barat_server::storage::{LocalStorage, ObjectStorage, SupabaseConfig, SupabaseStorage};
+use std::collections::HashMap;
+use std::time::Duration;
+use wiremock::matchers::{body_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SIGN_PATH: &str = "/storage/v1/object/sign/bucket/posts/photo.png";
+
+fn storage(server: &MockServer, bucket_public: bool) -> SupabaseStorage {
+    let config = SupabaseConfig {
+        supabase_url: server.uri(),
+        supabase_anon_key: "anon-key".to_string(),
+        bucket_name: "bucket".to_string(),
+        service_role_key: Some("service-key".to_string()),
+        bucket_public,
+    };
+    SupabaseStorage::new(config, reqwest::Client::new())
+}
+
+fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars: HashMap<String, String> = vars
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    move |key| vars.get(key).cloned()
+}
+
+#[tokio::test]
+async fn test_private_bucket_requests_signed_url() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(SIGN_PATH))
+        .and(header("authorization", "Bearer service-key"))
+        .and(body_json(serde_json::json!({ "expiresIn": 3600 })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "signedURL": "/object/sign/bucket/posts/photo.png?token=abc"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let storage = storage(&server, false);
+
+    let url = storage
+        .get_signed_url("posts/photo.png", Duration::from_secs(3600))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        url,
+        format!(
+            "{}/storage/v1/object/sign/bucket/posts/photo.png?token=abc",
+            server.uri()
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_signed_urls_are_cached() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(SIGN_PATH))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "signedURL": "/object/sign/bucket/posts/photo.png?token=abc"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let storage = storage(&server, false);
+
+    let first = storage
+        .get_signed_url("posts/photo.png", Duration::from_secs(3600))
+        .await
+        .unwrap();
+    let second = storage
+        .get_signed_url("posts/photo.png", Duration::from_secs(3600))
+        .await
+        .unwrap();
+
+    assert_eq!(first, second);
+}
+
+#[tokio::test]
+async fn test_sign_failure_is_reported() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(SIGN_PATH))
+        .respond_with(ResponseTemplate::new(400))
+        .mount(&server)
+        .await;
+    let storage = storage(&server, false);
+
+    let err = storage
+        .get_signed_url("posts/photo.png", Duration::from_secs(3600))
+        .await
+        .unwrap_err();
+
+    assert!(err.contains("400"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_public_bucket_falls_back_to_public_url() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&server)
+        .await;
+    let storage = storage(&server, true);
+
+    let url = storage
+        .get_signed_url("posts/photo.png", Duration::from_secs(3600))
+        .await
+        .unwrap();
+
+    assert_eq!(url, storage.get_asset_url("posts/photo.png"));
+    assert!(url.contains("/object/public/bucket/"), "{}", url);
+}
+
+#[tokio::test]
+async fn test_download_urls_name_the_file() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(SIGN_PATH))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "signedURL": "/object/sign/bucket/posts/photo.png?token=abc"
+        })))
+        .mount(&server)
+        .await;
+
+    let url = storage(&server, false)
+        .get_download_url(
+            "posts/photo.png",
+            Duration::from_secs(3600),
+            "Foto Rapat – RW 05.png",
+        )
+        .await
+        .unwrap();
+    assert!(
+        url.ends_with("?token=abc&download=Foto%20Rapat%20%E2%80%93%20RW%2005.png"),
+        "{}",
+        url
+    );
+
+    let url = storage(&server, true)
+        .get_download_url("posts/photo.png", Duration::from_secs(3600), "foto.png")
+        .await
+        .unwrap();
+    assert!(
+        url.ends_with("/posts/photo.png?download=foto.png"),
+        "{}",
+        url
+    );
+}
+
+#[tokio::test]
+async fn test_backends_without_signing_return_public_url() {
+    let storage = LocalStorage::new("storage");
+
+    let url = storage
+        .get_signed_url("photo.png", Duration::from_secs(60))
+        .await
+        .unwrap();
+
+    assert_eq!(url, "/assets/serve/photo.png");
+}
+
+#[test]
+fn test_private_bucket_requires_service_role_key() {
+    let base = [
+        ("SUPABASE_URL", "https://project.supabase.co"),
+        ("SUPABASE_ANON_KEY", "anon-key"),
+        ("SUPABASE_BUCKET_PUBLIC", "false"),
+    ];
+    let err = SupabaseConfig::from_lookup(lookup(&base)).unwrap_err();
+    assert!(err.contains("SUPABASE_SERVICE_ROLE_KEY"), "{}", err);
+
+    let mut vars = base.to_vec();
+    vars.push(("SUPABASE_SERVICE_ROLE_KEY", "service-key"));
+    let config = SupabaseConfig::from_lookup(lookup(&vars)).unwrap();
+    assert!(!config.bucket_public);
+    assert_eq!(config.service_key(), "service-key");
+    assert!(!format!("{:?}", config).contains("service-key"));
+}
+
+#[test]
+fn test_anon_key_is_used_without_service_role_key() {
+    let config = SupabaseConfig::from_lookup(lookup(&[
+        ("SUPABASE_URL", "https://project.supabase.co"),
+        ("SUPABASE_ANON_KEY", "anon-key"),
+    ]))
+    .unwrap();
+
+    assert!(config.bucket_public);
+    assert_eq!(config.service_key(), "anon-key");
+}
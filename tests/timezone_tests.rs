@@ -0,0 +1,57 @@
+//! Tests for dates computed in the app time zone
+
+use cakung_barat_server::i18n::format_date_id;
+use cakung_barat_server::posting::models::Post;
+use cakung_barat_server::timezone::{app_timezone, date_at, today, TimezoneConfig};
+use chrono::{NaiveDate, TimeZone, Utc};
+
+#[test]
+fn test_dates_near_midnight_use_jakarta_time() {
+    assert_eq!(app_timezone(), chrono_tz::Asia::Jakarta);
+
+    // 23:30 UTC on New Year's Eve is already 06:30 on 1 January in Jakarta
+    let late = Utc.with_ymd_and_hms(2025, 12, 31, 23, 30, 0).unwrap();
+    assert_eq!(date_at(late), NaiveDate::from_ymd_opt(2026, 1, 1).unwrap());
+    assert_eq!(format_date_id(date_at(late)), "1 Januari 2026");
+
+    // 16:59 UTC is 23:59 in Jakarta, still the same day
+    let before = Utc.with_ymd_and_hms(2025, 12, 30, 16, 59, 0).unwrap();
+    assert_eq!(format_date_id(date_at(before)), "30 Desember 2025");
+    let after = Utc.with_ymd_and_hms(2025, 12, 30, 17, 0, 0).unwrap();
+    assert_eq!(format_date_id(date_at(after)), "31 Desember 2025");
+}
+
+#[test]
+fn test_new_post_is_dated_today_in_jakarta() {
+    let post = Post::new(
+        "Kerja bakti".to_string(),
+        "Kegiatan".to_string(),
+        "Kerja bakti hari Minggu".to_string(),
+        None,
+    );
+    let now = post.created_at.unwrap();
+    assert_eq!(post.date, date_at(now));
+    assert!(post.date <= today());
+}
+
+#[test]
+fn test_timezone_config() {
+    let lookup = |value: &'static str| move |key: &str| (key == "APP_TIMEZONE").then(|| value.to_string());
+
+    assert_eq!(
+        TimezoneConfig::from_lookup(|_| None).unwrap().timezone,
+        chrono_tz::Asia::Jakarta
+    );
+    assert_eq!(
+        TimezoneConfig::from_lookup(lookup(" ")).unwrap().timezone,
+        chrono_tz::Asia::Jakarta
+    );
+    assert_eq!(
+        TimezoneConfig::from_lookup(lookup("Asia/Jayapura"))
+            .unwrap()
+            .timezone,
+        chrono_tz::Asia::Jayapura
+    );
+    let error = TimezoneConfig::from_lookup(lookup("Jakarta")).unwrap_err();
+    assert!(error.contains("APP_TIMEZONE"), "{}", error);
+}
@@ -0,0 +1,65 @@
+//! Tests for language selection of posting responses
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::posting::handlers::{get_all_postings, get_posting_by_id};
+use cakung_barat_server::posting::translation::{parse_lang, put_posting_translation};
+use cakung_barat_server::storage::LocalStorage;
+use std::sync::Arc;
+
+#[actix_web::test]
+async fn test_parse_lang_accepts_the_whitelist() {
+    assert_eq!(parse_lang("en").unwrap(), "en");
+    assert_eq!(parse_lang(" EN ").unwrap(), "en");
+    assert_eq!(parse_lang("id").unwrap(), "id");
+    for lang in ["", "fr", "en-US", "eng"] {
+        assert!(parse_lang(lang).is_err(), "{:?}", lang);
+    }
+}
+
+#[actix_web::test]
+async fn test_unsupported_languages_are_rejected() {
+    // Validation happens before the database is reached
+    let state = common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .build()
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .route("/api/postings", web::get().to(get_all_postings))
+            .route("/api/postings/{id}", web::get().to(get_posting_by_id))
+            .route(
+                "/api/postings/{id}/translations/{lang}",
+                web::put().to(put_posting_translation),
+            ),
+    )
+    .await;
+
+    let id = uuid::Uuid::new_v4();
+    for uri in [
+        "/api/postings?lang=fr".to_string(),
+        format!("/api/postings/{}?lang=jv", id),
+    ] {
+        let req = test::TestRequest::get().uri(&uri).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 400, "{}", uri);
+    }
+
+    let body = serde_json::json!({ "title": "Community Clean-up", "excerpt": "Drains cleared" });
+    // Indonesian is edited on the post itself
+    for lang in ["fr", "id"] {
+        let req = test::TestRequest::put()
+            .uri(&format!("/api/postings/{}/translations/{}", id, lang))
+            .set_json(&body)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 400, "{}", lang);
+    }
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/postings/{}/translations/en", id))
+        .set_json(serde_json::json!({ "title": " ", "excerpt": "Drains cleared" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+}
@@ -0,0 +1,265 @@
+//! Tests for upload sessions and their progress reporting
+
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App};
+use cakung_barat_server::asset::handlers::{
+    finalize_upload, get_upload_progress, receive_upload, start_upload,
+};
+use cakung_barat_server::asset::upload_session::{
+    UploadSessionError, UploadSessions, UploadTarget, UPLOAD_ID_HEADER,
+};
+use cakung_barat_server::config::UploadConfig;
+use cakung_barat_server::storage::LocalStorage;
+use futures::StreamExt;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+fn target() -> UploadTarget {
+    UploadTarget {
+        filename: "video-kerja-bakti.mp4".to_string(),
+        ..UploadTarget::default()
+    }
+}
+
+#[actix_web::test]
+async fn test_progress_counter_advances() {
+    let sessions = UploadSessions::default();
+    let id = sessions.open(target()).id;
+    assert_eq!(sessions.progress(id).unwrap().received_bytes, 0);
+
+    let received = sessions.begin(id, Some(10)).unwrap();
+    assert_eq!(
+        sessions.begin(id, Some(10)).err(),
+        Some(UploadSessionError::Busy(id))
+    );
+    received.add(4);
+    let progress = sessions.progress(id).unwrap();
+    assert_eq!(progress.received_bytes, 4);
+    assert_eq!(progress.expected_bytes, Some(10));
+    assert!(!progress.complete);
+    assert_eq!(received.add(6), 10);
+    assert_eq!(
+        sessions.take(id).unwrap_err(),
+        UploadSessionError::Incomplete(id)
+    );
+
+    let file = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let progress = sessions.complete(id, file).unwrap();
+    assert_eq!(progress.received_bytes, 10);
+    assert!(progress.complete);
+
+    let (target, _file) = sessions.take(id).unwrap();
+    assert_eq!(target.filename, "video-kerja-bakti.mp4");
+    assert_eq!(
+        sessions.progress(id).unwrap_err(),
+        UploadSessionError::NotFound(id)
+    );
+}
+
+#[actix_web::test]
+async fn test_expired_sessions_are_removed_with_their_files() {
+    let sessions = UploadSessions::new(Duration::from_millis(50));
+    let id = sessions.open(target()).id;
+    sessions.begin(id, None).unwrap();
+    let temp = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let path = temp.to_path_buf();
+    sessions.complete(id, temp).unwrap();
+    let fresh = sessions.open(target()).id;
+    assert_eq!(sessions.remove_expired(), 0);
+    assert!(path.exists());
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    let newer = sessions.open(target()).id;
+    // Opening a session swept both expired ones
+    assert_eq!(sessions.len(), 1);
+    assert!(!path.exists());
+    for id in [id, fresh] {
+        assert_eq!(
+            sessions.progress(id).unwrap_err(),
+            UploadSessionError::NotFound(id)
+        );
+    }
+    assert!(sessions.progress(newer).is_ok());
+}
+
+#[actix_web::test]
+async fn test_upload_over_http() {
+    let app_state = common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .with_upload_config(UploadConfig {
+            max_file_size: Some(16),
+            ..UploadConfig::default()
+        })
+        .build()
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state))
+            .route("/api/assets/uploads", web::post().to(start_upload))
+            .route("/api/assets/uploads/{id}", web::put().to(receive_upload))
+            .route(
+                "/api/assets/uploads/{id}/progress",
+                web::get().to(get_upload_progress),
+            )
+            .route(
+                "/api/assets/uploads/{id}/finalize",
+                web::post().to(finalize_upload),
+            ),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/assets/uploads")
+            .set_json(json!({ "filename": "video.mp4", "folders": ["kegiatan"] }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let id = resp
+        .headers()
+        .get(UPLOAD_ID_HEADER)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["id"], id.as_str());
+    assert_eq!(body["received_bytes"], 0);
+    assert_eq!(body["complete"], false);
+
+    // Finalizing before the file arrived is refused
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri(&format!("/api/assets/uploads/{}/finalize", id))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::put()
+            .uri(&format!("/api/assets/uploads/{}", id))
+            .set_payload(vec![7u8; 32])
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::put()
+            .uri(&format!("/api/assets/uploads/{}", id))
+            .set_payload(vec![7u8; 12])
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let progress: Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get()
+            .uri(&format!("/api/assets/uploads/{}/progress", id))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(progress["received_bytes"], 12);
+    assert_eq!(progress["expected_bytes"], 12);
+    assert_eq!(progress["complete"], true);
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri(&format!("/api/assets/uploads/{}/progress", Uuid::new_v4()))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn test_slow_upload_does_not_delay_other_requests() {
+    const CHUNKS: usize = 20;
+    const CHUNK_SIZE: usize = 16 * 1024;
+
+    let app_state = web::Data::new(
+        common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+            .build()
+            .unwrap(),
+    );
+    // One worker, so anything blocking its thread also holds up the GETs
+    let server = actix_web::HttpServer::new(move || {
+        App::new()
+            .app_data(app_state.clone())
+            .route("/api/assets/uploads", web::post().to(start_upload))
+            .route("/api/assets/uploads/{id}", web::put().to(receive_upload))
+            .route(
+                "/api/assets/uploads/{id}/progress",
+                web::get().to(get_upload_progress),
+            )
+    })
+    .workers(1)
+    .bind(("127.0.0.1", 0))
+    .unwrap();
+    let base = format!("http://{}/api/assets/uploads", server.addrs()[0]);
+    let server = server.run();
+    let handle = server.handle();
+    actix_web::rt::spawn(server);
+
+    let client = reqwest::Client::new();
+    let opened: Value = client
+        .post(&base)
+        .json(&json!({ "filename": "video.mp4" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = opened["id"].as_str().unwrap().to_string();
+
+    let chunks = futures::stream::iter(0..CHUNKS).then(|_| async {
+        tokio::time::sleep(Duration::from_millis(25)).await;
+        Ok::<_, std::io::Error>(vec![7u8; CHUNK_SIZE])
+    });
+    let upload = actix_web::rt::spawn(
+        client
+            .put(format!("{}/{}", base, id))
+            .body(reqwest::Body::wrap_stream(chunks))
+            .send(),
+    );
+
+    let mut seen_in_flight = false;
+    for _ in 0..5 {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let started = std::time::Instant::now();
+        let progress: Value = client
+            .get(format!("{}/{}/progress", base, id))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let elapsed = started.elapsed();
+        assert!(
+            elapsed < Duration::from_millis(250),
+            "progress took {:?}",
+            elapsed
+        );
+        seen_in_flight |= progress["complete"] == false && progress["received_bytes"] != 0;
+    }
+    assert!(seen_in_flight, "the upload should still be running");
+
+    let resp = upload.await.unwrap().unwrap();
+    assert_eq!(resp.status(), reqwest::StatusCode::OK);
+    let done: Value = resp.json().await.unwrap();
+    assert_eq!(done["received_bytes"], CHUNKS * CHUNK_SIZE);
+    handle.stop(false).await;
+}
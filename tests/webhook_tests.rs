@@ -0,0 +1,154 @@
+//! Tests for webhook signing, delivery retries and the admin endpoints
+
+mod common;
+
+use actix_web::{test, web, App};
+use cakung_barat_server::http_client::RetryPolicy;
+use cakung_barat_server::storage::LocalStorage;
+use cakung_barat_server::webhook::dispatcher::{deliver, sign, SIGNATURE_HEADER};
+use cakung_barat_server::webhook::handlers::list_webhooks;
+use cakung_barat_server::webhook::{validate_webhook_url, WebhookEvent};
+use std::sync::Arc;
+use std::time::Duration;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SECRET: &str = "rahasia-bot-wa";
+const BODY: &[u8] = br#"{"event":"post.created"}"#;
+
+fn policy() -> RetryPolicy {
+    RetryPolicy {
+        attempts: 3,
+        backoff: Duration::from_millis(10),
+    }
+}
+
+async fn receiver(status: u16) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .respond_with(ResponseTemplate::new(status))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[actix_web::test]
+async fn test_signature_is_hex_hmac_sha256_of_body() {
+    assert_eq!(
+        sign("key", b"The quick brown fox jumps over the lazy dog"),
+        "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+    );
+}
+
+#[actix_web::test]
+async fn test_delivery_is_signed() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .and(header(SIGNATURE_HEADER, sign(SECRET, BODY).as_str()))
+        .and(header("X-Webhook-Event", "post.created"))
+        .and(header("Content-Type", "application/json"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/hook", server.uri());
+    deliver(
+        &reqwest::Client::new(),
+        &policy(),
+        &url,
+        SECRET,
+        "post.created",
+        BODY,
+    )
+    .await
+    .unwrap();
+
+    let received = server.received_requests().await.unwrap();
+    assert_eq!(received[0].body, BODY);
+}
+
+#[actix_web::test]
+async fn test_server_errors_are_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .expect(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/hook", server.uri());
+    let client = reqwest::Client::new();
+    let result = deliver(&client, &policy(), &url, SECRET, "post.created", BODY).await;
+    assert_eq!(result, Ok(()));
+}
+
+#[actix_web::test]
+async fn test_delivery_gives_up_after_all_attempts() {
+    let server = receiver(500).await;
+    let url = format!("{}/hook", server.uri());
+    let client = reqwest::Client::new();
+
+    let failure = deliver(&client, &policy(), &url, SECRET, "post.created", BODY)
+        .await
+        .unwrap_err();
+    assert_eq!(failure.attempts, 3);
+    assert!(failure.error.contains("500"), "{}", failure.error);
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[actix_web::test]
+async fn test_client_errors_are_not_retried() {
+    let server = receiver(410).await;
+    let url = format!("{}/hook", server.uri());
+    let client = reqwest::Client::new();
+
+    let failure = deliver(&client, &policy(), &url, SECRET, "post.deleted", BODY)
+        .await
+        .unwrap_err();
+    assert_eq!(failure.attempts, 1);
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[actix_web::test]
+async fn test_webhook_urls_and_events_are_validated() {
+    assert!(validate_webhook_url("https://bot.example.com/hooks").is_ok());
+    assert!(validate_webhook_url("http://10.0.0.5:3000/wa").is_ok());
+    assert!(validate_webhook_url("ftp://bot.example.com/hooks").is_err());
+    assert!(validate_webhook_url("bot.example.com/hooks").is_err());
+
+    let events: Vec<WebhookEvent> =
+        serde_json::from_str(r#"["post.created", "asset.uploaded"]"#).unwrap();
+    assert_eq!(
+        events,
+        vec![WebhookEvent::PostCreated, WebhookEvent::AssetUploaded]
+    );
+    assert!(serde_json::from_str::<WebhookEvent>(r#""post.published""#).is_err());
+}
+
+#[actix_web::test]
+async fn test_webhook_admin_requires_token() {
+    let state = common::state_builder(Arc::new(LocalStorage::new(std::env::temp_dir())))
+        .build()
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .route("/api/admin/webhooks", web::get().to(list_webhooks)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/admin/webhooks")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+}